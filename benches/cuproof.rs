@@ -0,0 +1,133 @@
+//! Criterion benchmarks: `cargo bench --bench cuproof`, or `cargo bench --bench cuproof -- fiat_shamir`
+//! for one group
+//!
+//! Every benchmark runs over insecure_seeded_setup params and ChaCha20 randomness with fixed
+//! seeds, so two runs on one machine measure the same arithmetic on the same numbers.
+//!
+//! Medians on an x86-64 Linux host (`-- --warm-up-time 1 --measurement-time 5`), before and after
+//! fiat_shamir hashed a length-prefixed binary encoding instead of decimal digits and
+//! pedersen_commit shared its squarings between g^m and h^r (interleaved sliding windows):
+//!
+//! | benchmark                      | before  | after   |
+//! |--------------------------------|---------|---------|
+//! | fiat_shamir/first_message/2048 | 35.4 µs | 1.33 µs |
+//! | fiat_shamir/first_message/3072 | 82.1 µs | 1.87 µs |
+//! | pedersen_commit/free/2048      | 874 µs  | 744 µs  |
+//! | pedersen_commit/free/3072      | 1.89 ms | 1.55 ms |
+//! | range64_2048/verify            | 5.59 ms | 5.52 ms |
+//! | batch_verify/100_proofs        | 579 ms  | 553 ms  |
+//!
+//! verify and batch_verify move within this host's run-to-run spread: their time is the four
+//! commitments (T1, T2 and both sides of the t_hat check), which go through the fixed-base tables
+//! (pedersen_commit/tables) rather than pedersen_commit, and a transcript was under 1% of it.
+//!
+//! The prover's hot path folds the inner-product vectors in place, computes l0 = r0 once and sums
+//! inner products into one accumulator: 1715 allocations per proof before, 1688 after on 512-bit
+//! params, and 1668 once the transcript stopped writing decimal digits
+//! (prove_and_verify_allocations_are_pinned in src/lib.rs). range64_2048/prove takes 34.5 ms; its
+//! time is the commitments' exponentiations.
+//!
+//! range64_2048/verify_fast checks T1 and T2 through a poe::VerifierAssist instead of the two
+//! commitments: two simultaneous exponentiations by 129-bit exponents and a hash-to-prime, 5.06 ms
+//! against verify's 5.52 ms on the same host.
+
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use cuproof::commitment::{commit, pedersen_commit};
+use cuproof::fiat_shamir::fiat_shamir;
+use cuproof::range_proof::{prove_with_rng, random_blinding, Cuproof};
+use cuproof::setup::{insecure_seeded_setup, Params};
+use cuproof::util::{read_params, read_proof, write_params, write_proof, ReadLimits};
+use cuproof::verify::{cuproof_verify_fast, verify};
+use cuproof::wire::{proof_from_wire, proof_to_wire};
+use num_bigint::BigInt;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+const SEED: &[u8] = b"cuproof bench";
+
+fn params(bits: usize) -> Params {
+    insecure_seeded_setup(bits, SEED).expect("bench modulus sizes are supported")
+}
+
+/// A proof of 2^63 + 12345 in [0, 2^64 - 1], the 64-bit range of prove
+fn proof(params: &Params, seed: u64) -> Cuproof {
+    let (v, r) = (BigInt::from(1u64 << 63) + 12345, BigInt::from(0x5eed_u32) + seed);
+    let (a, b) = (BigInt::from(0), BigInt::from(u64::MAX));
+    prove_with_rng(&v, &r, &a, &b, params, 64, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap()
+}
+
+fn pedersen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pedersen_commit");
+    for bits in [2048, 3072] {
+        let params = params(bits);
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let (m, r) = (BigInt::from(u64::MAX), random_blinding(&mut rng));
+        group.bench_with_input(BenchmarkId::new("free", bits), &params, |bench, p| {
+            bench.iter(|| pedersen_commit(p.g(), p.h(), black_box(&m), black_box(&r), p.n()))
+        });
+        commit(&params, &m, &r); // build the fixed-base tables outside the measurement
+        group.bench_with_input(BenchmarkId::new("tables", bits), &params, |bench, p| {
+            bench.iter(|| commit(p, black_box(&m), black_box(&r)))
+        });
+    }
+    group.finish();
+}
+
+fn prove_verify(c: &mut Criterion) {
+    let params = params(2048);
+    let proof = proof(&params, 0);
+    let mut group = c.benchmark_group("range64_2048");
+    group.sample_size(20);
+    group.bench_function("prove", |bench| bench.iter(|| self::proof(&params, 0)));
+    group.bench_function("verify", |bench| bench.iter(|| assert!(verify(black_box(&proof), &params))));
+    let assisted = proof.clone().with_verifier_assist(&params);
+    group.bench_function("verify_fast", |bench| bench.iter(|| cuproof_verify_fast(black_box(&assisted), &params).unwrap()));
+    group.finish();
+}
+
+fn batch_verify(c: &mut Criterion) {
+    let params = params(2048);
+    let proofs: Vec<Cuproof> = (0..100).map(|seed| proof(&params, seed)).collect();
+    let mut group = c.benchmark_group("batch_verify");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(proofs.len() as u64));
+    group.bench_function("100_proofs", |bench| bench.iter(|| assert!(proofs.iter().all(|proof| verify(proof, &params)))));
+    group.finish();
+}
+
+fn serialization(c: &mut Criterion) {
+    let params = params(2048);
+    let proof = proof(&params, 0);
+    let mut group = c.benchmark_group("serialization");
+    group.bench_function("proof_container", |bench| {
+        bench.iter(|| {
+            let mut bytes = Vec::new();
+            write_proof(&mut bytes, black_box(&proof)).unwrap();
+            read_proof(&mut &bytes[..], &ReadLimits::default()).unwrap()
+        })
+    });
+    group.bench_function("proof_wire", |bench| bench.iter(|| proof_from_wire(&proof_to_wire(black_box(&proof)).unwrap()).unwrap()));
+    group.bench_function("params_container", |bench| {
+        bench.iter(|| {
+            let mut bytes = Vec::new();
+            write_params(&mut bytes, black_box(&params)).unwrap();
+            read_params(&mut &bytes[..], &ReadLimits::default()).unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn challenges(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fiat_shamir");
+    for bits in [2048, 3072] {
+        let proof = proof(&params(bits), 0);
+        // the transcript of y in verify
+        let inputs = [&proof.A, &proof.S, &proof.C, &proof.C_v1, &proof.C_v2];
+        group.bench_with_input(BenchmarkId::new("first_message", bits), &inputs, |bench, inputs| bench.iter(|| fiat_shamir(black_box(inputs))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, pedersen, prove_verify, batch_verify, serialization, challenges);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+//! Generate the protobuf message types of src/proto.rs from proto/cuproof.proto (feature `proto`)
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "proto")]
+    {
+        println!("cargo:rerun-if-changed=proto/cuproof.proto");
+        let files = protox::compile(["proto/cuproof.proto"], ["proto"]).expect("proto/cuproof.proto does not compile");
+        prost_build::Config::new().compile_fds(files).expect("prost could not generate the message types");
+    }
+}
@@ -0,0 +1,831 @@
+//! The `cuproof` command line: the argument parser and one module per command
+//!
+//! Cli derives the parser: the global flags, and a Commands variant per command whose arguments
+//! struct lives in the command's module beside its handler. Their generated help (`cuproof --help`,
+//! `cuproof <command> --help`) is the reference for them. The invocations of earlier releases parse
+//! unchanged, except that `--format`, `--armor` and `--radix` now follow the command they apply to;
+//! given anywhere else they fail with a note saying so. args, files and passphrase hold what
+//! several commands share, output the --json document and the stdio the commands write through.
+//!
+//! run parses and dispatches, each handler returns a CommandOutcome or a CliError, and finish
+//! reports either and picks the exit status (see EXIT_CODES), so tests drive commands without
+//! spawning the binary.
+
+use std::fmt;
+use std::io::Write;
+use std::time::Duration;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use crate::logging::{self, LogFormat};
+use cuproof::Error;
+use cuproof::error::{ParamsError, SerializationError};
+use cuproof::range_proof::ProveError;
+use cuproof::setup::SetupError;
+use cuproof::verify::VerifyError;
+use cuproof::batch::BatchSummary;
+use cuproof::timing::{self, Phase, PhaseTimings};
+
+#[macro_use]
+mod output;
+mod args;
+mod files;
+mod passphrase;
+mod setup;
+mod commit;
+mod prove;
+mod offline;
+mod comparison;
+mod openings;
+mod verify;
+mod verify_opening;
+mod bundle;
+mod aggregate;
+mod batch;
+mod fingerprint;
+mod generators;
+mod dump;
+mod info;
+mod convert;
+mod encrypt;
+mod params;
+mod bench;
+mod selftest;
+mod run_script;
+mod ceremony;
+
+pub use output::{Output, Stdio};
+use output::Json;
+use output::STDIN_SECRETS;
+use args::count;
+use args::TimeArg;
+
+const EXIT_CODES: &str = "Exit status:\n  0  success; for verify, setup verify and ceremony verify: VALID; for verify-opening: MATCH; for batch-verify: every proof VALID\n  1  INVALID (batch-verify: at least one proof; selftest: at least one check failed; verify-opening: MISMATCH)\n  2  bad arguments, unreadable or malformed files (batch-verify: at least one proof; batch-prove: at least one row)\n  3  params failing validation\n  4  setup failed\n  5  no proof exists (e.g. v outside [a, b]; batch-prove: for at least one row)";
+
+const EXAMPLES: &str = "Examples:\n  cuproof setup fast params.bin\n  cuproof commit params.bin --value-env V --out c.bin --blinding-out r.bin\n  cuproof prove --value-env V --commitment c.bin --blinding-in r.bin params.bin 10 100 v.proof\n  cuproof verify params.bin 10 100 v.proof\n  cuproof prove --ephemeral params.bin 10 100 42 - | cuproof verify params.bin 10 100 -\n  cuproof --json batch-verify params.bin proofs/";
+
+/// How `-` paths behave, as printed by --help
+const STDIO: &str = "A path of - reads stdin or writes stdout, for params, proofs, commitments and blindings, and for\ninfo and convert. One input may read stdin. Binary files are not written to a terminal unless\n--force-binary is given; proofs are armored instead.";
+
+/// Flags that earlier releases took anywhere on the command line, and the commands they now follow
+const MOVED_FLAGS: [(&str, &str); 3] = [("--format", "setup or prove"), ("--armor", "prove"), ("--radix", "commit, prove or verify")];
+/// What a command concluded; finish prints verdicts on stdout and picks the exit status
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandOutcome {
+    /// the command did its work
+    Done,
+    /// a verification accepted
+    Valid,
+    /// a verification rejected, with the reason when it is more specific than a failed check
+    Invalid(Option<VerifyError>),
+    /// batch-verify ran over every proof and printed its report
+    Batch(BatchSummary),
+    /// batch-prove ran over every row and wrote its results; counts of the rows that were malformed
+    /// or could not be written, and of those without a proof
+    BatchProve { bad_rows: usize, unprovable: usize },
+    /// selftest ran every check and printed them; how many failed
+    SelfTest { failed: usize },
+    /// run ran a script and printed its steps; how many did not meet their expectation
+    Script { failed: usize },
+    /// verify-opening recomputed the commitment; whether it matched
+    Opening { matched: bool },
+    /// info described a file that fails its structural checks
+    Malformed,
+}
+
+impl CommandOutcome {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CommandOutcome::Done | CommandOutcome::Valid => 0,
+            CommandOutcome::Invalid(_) => Error::from(VerifyError::InvalidProof).exit_code(),
+            CommandOutcome::Batch(summary) if summary.unreadable > 0 => 2,
+            CommandOutcome::Batch(summary) if summary.invalid > 0 => Error::from(VerifyError::InvalidProof).exit_code(),
+            CommandOutcome::Batch(_) => 0,
+            CommandOutcome::BatchProve { bad_rows, .. } if *bad_rows > 0 => 2,
+            CommandOutcome::BatchProve { unprovable, .. } if *unprovable > 0 => Error::from(ProveError::ValueOutOfRange).exit_code(),
+            CommandOutcome::BatchProve { .. } => 0,
+            CommandOutcome::SelfTest { failed } if *failed > 0 => Error::from(VerifyError::InvalidProof).exit_code(),
+            CommandOutcome::SelfTest { .. } => 0,
+            CommandOutcome::Script { failed } if *failed > 0 => Error::from(VerifyError::InvalidProof).exit_code(),
+            CommandOutcome::Script { .. } => 0,
+            CommandOutcome::Opening { matched: true } => 0,
+            CommandOutcome::Opening { matched: false } => Error::from(VerifyError::InvalidProof).exit_code(),
+            CommandOutcome::Malformed => 2,
+        }
+    }
+}
+
+/// Why a command could not run; finish prints it on stderr
+#[derive(Debug)]
+pub enum CliError {
+    /// missing or malformed arguments; the message is the parser's, with the command's usage
+    Usage(String),
+    /// an argument's value was refused: the argument, what was given, and the message
+    InvalidValue { name: String, value: String, message: String },
+    /// an operation failed: `context: error`, with the path or field it concerns for --errors json
+    Failed { context: String, error: Error, fields: Vec<(&'static str, Json)> },
+}
+
+impl CliError {
+    fn failed(context: impl Into<String>, error: impl Into<Error>) -> CliError {
+        CliError::Failed { context: context.into(), error: error.into(), fields: Vec::new() }
+    }
+
+    /// Add `key` to the fields of a failure; usage errors have none
+    fn with(mut self, key: &'static str, value: impl Into<Json>) -> CliError {
+        if let CliError::Failed { fields, .. } = &mut self { fields.push((key, value.into())); }
+        self
+    }
+
+    fn exit_code(&self) -> i32 {
+        match self {
+            // the status of malformed files and arguments, Error::Serialization
+            CliError::Usage(_) | CliError::InvalidValue { .. } => 2,
+            CliError::Failed { error, .. } => error.exit_code(),
+        }
+    }
+
+    /// `usage`, or the kind of the underlying cuproof::Error
+    fn kind(&self) -> &'static str {
+        match self {
+            CliError::Usage(_) | CliError::InvalidValue { .. } => "usage",
+            CliError::Failed { error, .. } => error.kind(),
+        }
+    }
+
+    /// The error as --errors json writes it on stderr: a stable `kind` (`usage`, `usage.invalid_value`
+    /// or cuproof::Error::code), the `message` as printed without the flag, the `category` and
+    /// `exit_code`, then what the error concerns: path, field, expected and actual where they apply
+    fn to_json(&self) -> Json {
+        let fields = match self {
+            CliError::Usage(_) => Vec::new(),
+            CliError::InvalidValue { name, value, .. } => vec![("field", Json::from(name.as_str())), ("value", Json::from(value.as_str()))],
+            CliError::Failed { error, fields, .. } => fields.iter().cloned().chain(error_fields(error)).collect(),
+        };
+        error_json(self.code(), self.to_string(), self.kind(), self.exit_code(), fields)
+    }
+
+    /// The `kind` of to_json
+    fn code(&self) -> &'static str {
+        match self {
+            CliError::Usage(_) => "usage",
+            CliError::InvalidValue { .. } => "usage.invalid_value",
+            CliError::Failed { error, .. } => error.code(),
+        }
+    }
+}
+
+/// The stderr object of --errors json; fields follow kind, message, category and exit_code
+fn error_json(kind: &str, message: String, category: &str, exit_code: i32, fields: Vec<(&'static str, Json)>) -> Json {
+    let head = [("kind", Json::from(kind)), ("message", Json::from(message)), ("category", Json::from(category)), ("exit_code", Json::Int(exit_code as i128))];
+    Json::Object(head.into_iter().chain(fields).map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+/// What an error itself says about its cause, for --errors json: the field or statement it names, and
+/// the expected and actual values of a mismatch
+fn error_fields(error: &Error) -> Vec<(&'static str, Json)> {
+    use cuproof::util::{FormatError, LimitError, ParseError};
+    let mismatch = |expected: Json, actual: Json| vec![("expected", expected), ("actual", actual)];
+    match error {
+        Error::Serialization(SerializationError::Io(e)) => vec![("io_error", Json::from(io_error_name(e.kind())))],
+        Error::Serialization(SerializationError::Hex(e)) => match e {
+            ParseError::Whitespace(index) | ParseError::InvalidDigit { index, .. } => vec![("index", Json::from(*index))],
+            ParseError::WrongLength { expected, found } => mismatch(Json::from(*expected), Json::from(*found)),
+            ParseError::Empty | ParseError::NegativeNotAllowed => Vec::new(),
+        },
+        Error::Serialization(SerializationError::Format(e)) => match e {
+            FormatError::BadMagic { expected, found } => mismatch(Json::from(expected.name()), Json::from(found.map(|kind| kind.name()))),
+            FormatError::LengthMismatch { declared, available } => mismatch(Json::from(*declared), Json::from(*available)),
+            FormatError::UnsupportedVersion(version) => vec![("actual", Json::from(*version))],
+            _ => Vec::new(),
+        },
+        Error::Serialization(SerializationError::Limit(e)) => match e {
+            LimitError::IntegerTooLarge { field, max_bytes } => vec![("field", Json::from(*field)), ("expected", Json::from(*max_bytes))],
+            LimitError::CountExceedsInput { declared, available } => mismatch(Json::from(*available), Json::from(*declared)),
+            _ => Vec::new(),
+        },
+        Error::Params(e) | Error::Setup(SetupError::InvalidModulus(e)) => match e {
+            ParamsError::ModulusBelowDeclaredSize { bits, declared } => mismatch(Json::from(*declared), Json::from(*bits)),
+            ParamsError::ModulusTooSmall { bits, min_bits } => mismatch(Json::from(*min_bits), Json::from(*bits)),
+            ParamsError::ModulusTooLarge { bits, max_bits } => mismatch(Json::from(*max_bits), Json::from(*bits)),
+            ParamsError::GeneratorOutOfRange(field) | ParamsError::GeneratorIsMinusOne(field) | ParamsError::GeneratorNotCoprime(field) => {
+                vec![("field", Json::from(*field))]
+            }
+            ParamsError::GeneratorVectorMismatch { index } => vec![("index", Json::from(*index))],
+            _ => Vec::new(),
+        },
+        Error::Setup(e) => match e {
+            SetupError::InsecureModulusSize { bits, min_bits } => mismatch(Json::from(*min_bits), Json::from(*bits)),
+            SetupError::UndersizedPrime { bits, expected } | SetupError::OversizedPrime { bits, expected }
+            | SetupError::ModulusSizeMismatch { bits, expected } => mismatch(Json::from(*expected), Json::from(*bits)),
+            _ => Vec::new(),
+        },
+        Error::Verify(e) => match e {
+            VerifyError::EpochMismatch { proof, params } => mismatch(Json::from(*params), Json::from(*proof)),
+            VerifyError::StatementMismatch(field) => vec![("field", Json::from(*field))],
+            VerifyError::AggregateStatementMismatch { index, field } => vec![("index", Json::from(*index)), ("field", Json::from(*field))],
+            _ => Vec::new(),
+        },
+        Error::Prove(ProveError::Aggregate { index, .. }) => vec![("index", Json::from(*index))],
+        _ => Vec::new(),
+    }
+}
+
+/// std::io::ErrorKind as a stable snake_case name, so a missing file is told from a denied one
+fn io_error_name(kind: std::io::ErrorKind) -> &'static str {
+    use std::io::ErrorKind::*;
+    match kind {
+        NotFound => "not_found",
+        PermissionDenied => "permission_denied",
+        AlreadyExists => "already_exists",
+        IsADirectory => "is_a_directory",
+        InvalidData => "invalid_data",
+        InvalidInput => "invalid_input",
+        UnexpectedEof => "unexpected_eof",
+        StorageFull => "storage_full",
+        BrokenPipe => "broken_pipe",
+        _ => "other",
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(msg) => write!(f, "{}", msg),
+            CliError::InvalidValue { message, .. } => write!(f, "{}", message),
+            CliError::Failed { context, error, .. } => write!(f, "{}: {}", context, error),
+        }
+    }
+}
+
+fn usage(msg: &str) -> CliError {
+    CliError::Usage(msg.to_string())
+}
+
+/// The command line: global --json, --errors, --quiet, -v, --log-format, --threads and --force-binary, and a subcommand per command
+#[derive(Parser)]
+#[command(
+    name = "cuproof",
+    about = "Range proofs for committed integers over RSA groups",
+    subcommand_required = true,
+    arg_required_else_help = true,
+    after_help = format!("{}\n\n{}\n\n{}", EXAMPLES, STDIO, EXIT_CODES)
+)]
+struct Cli {
+    /// Print one JSON object on stdout; the human-readable text goes to stderr
+    #[arg(long, global = true)]
+    json: bool,
+    /// Print no human-readable results, setup progress, warnings or notes; the exit status and errors remain
+    #[arg(long, short, global = true)]
+    quiet: bool,
+    /// Also log the spans and events of setup, prove, verify and file IO on stderr; -vv logs everything
+    #[arg(long, short, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// json writes a failure as one object on stderr: kind, message, exit_code, path, field, expected, actual [--json implies json]
+    #[arg(long, value_name = "FORMAT", global = true, value_parser = ["text", "json"], default_value = "text")]
+    errors: String,
+    /// text prefixes each line with WARNING:, note:, debug:; json writes one object per event
+    #[arg(long, value_name = "FORMAT", global = true, value_parser = ["text", "json"], default_value = "text")]
+    log_format: String,
+    /// Worker threads for setup trusted, batch-verify and batch-prove; 1 runs serially [default: $CUPROOF_THREADS, else every core]
+    #[arg(long, value_name = "N", global = true, value_parser = count(1, usize::MAX))]
+    threads: Option<usize>,
+    /// Write binary files given as - to stdout even when it is a terminal
+    #[arg(long, global = true)]
+    force_binary: bool,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// The commands, in the order of `cuproof --help`; each one's arguments and handler are in its module
+#[derive(Subcommand)]
+enum Commands {
+    Setup(setup::SetupArgs),
+    Commit(commit::CommitArgs),
+    Prove(prove::ProveArgs),
+    ProveOfflineCommit(offline::ProveOfflineCommitArgs),
+    ProveOfflineChallenge(offline::ProveOfflineChallengeArgs),
+    ProveOfflineFinish(offline::ProveOfflineFinishArgs),
+    ProveLt(comparison::ProveLtArgs),
+    VerifyLt(comparison::VerifyLtArgs),
+    ProveOpenings(openings::ProveOpeningsArgs),
+    VerifyOpenings(openings::VerifyOpeningsArgs),
+    Verify(verify::VerifyArgs),
+    VerifyOpening(verify_opening::VerifyOpeningArgs),
+    Bundle(bundle::BundleArgs),
+    Aggregate(aggregate::AggregateArgs),
+    BatchVerify(batch::BatchVerifyArgs),
+    BatchProve(batch::BatchProveArgs),
+    Fingerprint(fingerprint::FingerprintArgs),
+    Generators(generators::GeneratorsArgs),
+    Dump(dump::DumpArgs),
+    Info(info::InfoArgs),
+    Convert(convert::ConvertArgs),
+    Encrypt(encrypt::EncryptArgs),
+    Params(params::ParamsArgs),
+    Bench(bench::BenchArgs),
+    Selftest(selftest::SelftestArgs),
+    Run(run_script::RunArgs),
+    Benchmark(bench::BenchmarkArgs),
+    Ceremony(ceremony::CeremonyArgs),
+}
+
+/// Parse `args`, the program name first, and run the command they name
+/// - returns: what the command concluded; a command line the parser refuses is CliError::Usage
+///   with its message, and --help prints the help and is Done
+/// - usage: main, and tests that check argument handling without spawning the binary
+pub fn run(args: Vec<String>, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(e) if matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) => {
+            let help = e.render().to_string();
+            if out.json { eprint!("{}", help); } else { let _ = write!(out.stdio.stdout, "{}", help); }
+            out.set("usage", help.trim_end());
+            out.set("exit_codes", EXIT_CODES);
+            return Ok(CommandOutcome::Done);
+        }
+        Err(e) => return Err(parse_error(e)),
+    };
+    out.json = cli.json;
+    out.errors_json = out.json || cli.errors == "json";
+    out.quiet = cli.quiet;
+    let log_format = if cli.log_format == "json" { LogFormat::Json } else { LogFormat::Text };
+    logging::init(logging::level(out.quiet, cli.verbose), log_format);
+    out.force_binary = cli.force_binary;
+    match &cli.command {
+        Commands::Setup(m) => setup::run_setup(m, cli.threads, out),
+        Commands::Commit(m) => commit::run_commit(m, out),
+        Commands::Prove(m) => with_timing(&m.time, out, |out| prove::run_prove(m, out)),
+        Commands::ProveOfflineCommit(m) => offline::run_prove_offline_commit(m, out),
+        Commands::ProveOfflineChallenge(m) => offline::run_prove_offline_challenge(m, out),
+        Commands::ProveOfflineFinish(m) => offline::run_prove_offline_finish(m, out),
+        Commands::ProveLt(m) => comparison::run_prove_lt(m, out),
+        Commands::VerifyLt(m) => comparison::run_verify_lt(m, out),
+        Commands::ProveOpenings(m) => openings::run_prove_openings(m, out),
+        Commands::VerifyOpenings(m) => openings::run_verify_openings(m, out),
+        Commands::Verify(m) => with_timing(&m.time, out, |out| verify::run_verify(m, out)),
+        Commands::VerifyOpening(m) => verify_opening::run_verify_opening(m, out),
+        Commands::Bundle(m) => bundle::run_bundle(m, out),
+        Commands::Aggregate(m) => aggregate::run_aggregate(m, out),
+        Commands::BatchVerify(m) => batch::run_batch_verify(m, cli.threads, out),
+        Commands::BatchProve(m) => batch::run_batch_prove(m, cli.threads, out),
+        Commands::Dump(m) => dump::run_dump(m, out),
+        Commands::Fingerprint(m) => fingerprint::run_fingerprint(m, out),
+        Commands::Generators(m) => generators::run_generators(m, out),
+        Commands::Info(m) => info::run_info(m, out),
+        Commands::Convert(m) => convert::run_convert(m, out),
+        Commands::Encrypt(m) => encrypt::run_encrypt(m, out),
+        Commands::Params(m) => params::run_params(m, out),
+        Commands::Bench(m) => bench::run_bench(m, out),
+        Commands::Selftest(m) => selftest::run_selftest_cli(m, out),
+        Commands::Run(m) => run_script::run_script(m, out),
+        Commands::Benchmark(_) if out.json => Err(usage("--json does not apply to benchmark, which prints tables")),
+        Commands::Benchmark(m) => bench::run_benchmark(m),
+        Commands::Ceremony(m) => ceremony::run_ceremony(m, out),
+    }
+}
+
+
+/// Report what run returned: the verdict, or the error on stderr, then the JSON object under --json
+/// - returns: the exit status (see EXIT_CODES)
+pub fn finish(outcome: Result<CommandOutcome, CliError>, mut out: Output) -> i32 {
+    let code = match &outcome {
+        Ok(outcome) => outcome.exit_code(),
+        Err(e) => e.exit_code(),
+    };
+    match outcome {
+        Ok(CommandOutcome::Done | CommandOutcome::Batch(_) | CommandOutcome::BatchProve { .. } | CommandOutcome::SelfTest { .. } | CommandOutcome::Script { .. } | CommandOutcome::Malformed) => {}
+        Ok(CommandOutcome::Valid) => {
+            out.say("VALID");
+            out.set("result", "valid");
+            out.set("error", Json::Null);
+        }
+        Ok(CommandOutcome::Opening { matched }) => {
+            out.say(if matched { "MATCH" } else { "MISMATCH" });
+            out.set("result", if matched { "match" } else { "mismatch" });
+        }
+        Ok(CommandOutcome::Invalid(reason)) => {
+            match &reason {
+                None => out.say("INVALID"),
+                Some(reason) => out.say(format_args!("INVALID ({})", reason)),
+            }
+            let error = Error::from(reason.unwrap_or(VerifyError::InvalidProof));
+            if out.errors_json { eprintln!("{}", error_json(error.code(), error.to_string(), error.kind(), code, error_fields(&error))); }
+            out.set("result", "invalid");
+            out.set("error", error.to_string());
+        }
+        Err(e) => {
+            if out.errors_json { eprintln!("{}", e.to_json()); } else { eprintln!("{}", e); }
+            out.fields = vec![("error".to_string(), json_object! { "kind" => e.kind(), "message" => e.to_string() })];
+        }
+    }
+    out.finish();
+    code
+}
+
+/// A command line the parser refused, as a usage error: its message, or for an unknown command
+/// `Unknown command <name>` as before, with a note when the culprit is one of MOVED_FLAGS; a
+/// refused value is CliError::InvalidValue, naming the argument
+fn parse_error(e: clap::Error) -> CliError {
+    if let (ErrorKind::InvalidSubcommand, Some(ContextValue::String(name))) = (e.kind(), e.get(ContextKind::InvalidSubcommand)) {
+        let usage = match e.get(ContextKind::Usage) {
+            Some(ContextValue::StyledStr(usage)) => usage.to_string(),
+            _ => Cli::command().render_usage().to_string(),
+        };
+        return CliError::Usage(format!("Unknown command {}\n{}", name, usage.trim()));
+    }
+    let message = e.render().to_string().trim_end().to_string();
+    if let (Some(ContextValue::String(arg)), Some(ContextValue::String(value))) = (e.get(ContextKind::InvalidArg), e.get(ContextKind::InvalidValue)) {
+        // `[b]` for a positional, `--radix <radix>` for an option
+        let name = arg.split(' ').next().unwrap_or(arg).trim_matches(['[', ']', '<', '>']).to_string();
+        return CliError::InvalidValue { name, value: value.clone(), message };
+    }
+    let moved = match e.get(ContextKind::InvalidArg) {
+        Some(ContextValue::String(arg)) => MOVED_FLAGS.iter().find(|(flag, _)| arg.split(['=', ' ']).next() == Some(flag)),
+        _ => None,
+    };
+    match moved {
+        Some((flag, commands)) => CliError::Usage(format!("{}\nnote: {} now goes after the command it applies to ({}), e.g. `cuproof prove {} ...`", message, flag, commands, flag)),
+        None => CliError::Usage(message),
+    }
+}
+
+/// Run `command`, under --time collecting its phases (see cuproof::timing) and reporting them on
+/// stderr and as the `timing` key; the command's own output and exit status are left as they are
+fn with_timing(time: &TimeArg, out: &mut Output, command: impl FnOnce(&mut Output) -> Result<CommandOutcome, CliError>) -> Result<CommandOutcome, CliError> {
+    if !time.time { return command(out); }
+    let mut timings = PhaseTimings::default();
+    let outcome = timing::record(&mut timings, || command(out));
+    let peak_rss_kib = timing::peak_rss_kib();
+    if !out.quiet {
+        match peak_rss_kib {
+            Some(kib) => eprintln!("Time: {}, peak RSS {} KiB", timings, kib),
+            None => eprintln!("Time: {}", timings),
+        }
+    }
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let mut report = Phase::ALL.iter().map(|&phase| (format!("{}_ms", phase.name()), Json::from(ms(timings.get(phase))))).collect::<Vec<_>>();
+    report.push(("total_ms".to_string(), Json::from(ms(timings.total))));
+    report.push(("peak_rss_kib".to_string(), Json::from(peak_rss_kib)));
+    out.set("timing", Json::Object(report));
+    outcome
+}
+
+/// Valid or Invalid for the boolean result of a check
+fn verdict(valid: bool) -> CommandOutcome {
+    if valid { CommandOutcome::Valid } else { CommandOutcome::Invalid(None) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use num_bigint::BigInt;
+    use rand::rngs::OsRng;
+    use cuproof::commitment::commit;
+    use cuproof::secret::Secret;
+    use cuproof::setup::{insecure_test_setup, trusted_setup_with_transcript, SetupPhase, SetupProgress};
+    use cuproof::util::{load_params, FileKind, ReadLimits, DEFAULT_MAX_READ_BYTES};
+    use cuproof::verify::{cuproof_verify_checked, cuproof_verify_with_range};
+    use args::{parse_cli_bigint, Radix, SecretSource};
+    use commit::commit_to_files;
+    use output::Captured;
+    use prove::prove_for_commitment_file;
+    use setup::{SetupCommand, SetupStatus, StatusStyle};
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("cuproof_cli_{}_{}.bin", name, std::process::id())).to_str().unwrap().to_string()
+    }
+
+    // Purpose: ensure the --json encoder escapes strings and parses back as what it was built from
+    // Params: an object with control characters, quotes, unicode, nulls, numbers and a nested array
+    // Output: serde_json reads the same values; later set() calls replace earlier keys in place
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn json_output_escapes_and_round_trips() {
+        let document = json_object! {
+            "text" => "a \"quoted\" \\ path\n\t\u{1}é",
+            "none" => Option::<u64>::None,
+            "n" => u64::MAX,
+            "ms" => 1.5,
+            "flags" => vec![true, false],
+        };
+        let parsed: serde_json::Value = serde_json::from_str(&document.to_string()).unwrap();
+        assert_eq!(parsed, serde_json::json!({
+            "text": "a \"quoted\" \\ path\n\t\u{1}é", "none": null, "n": u64::MAX, "ms": 1.5, "flags": [true, false],
+        }));
+        assert_eq!(Json::Float(f64::NAN).to_string(), "null");
+
+        let mut out = piped(b"", false).0;
+        out.set("result", "valid");
+        out.set("error", Json::Null);
+        out.set("result", "invalid");
+        assert_eq!(Json::Object(out.fields).to_string(), r#"{"result":"invalid","error":null}"#);
+    }
+
+    // Purpose: ensure `commit` output feeds `prove --commitment`, whose proof verifies for that commitment
+    // Params: insecure 512-bit params, v in [10, 100], a fresh and a fixed blinding, temp files
+    // Output: proof.C equals the saved commitment and verifies; a wrong value or foreign params are refused
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn commit_then_prove_then_verify() {
+        let params = insecure_test_setup(512);
+        let (a, b, v) = (BigInt::from(10), BigInt::from(100), BigInt::from(42));
+        let (path, blinding_out) = (temp_path("commitment"), temp_path("blinding"));
+        let mut out = piped(b"", false).0;
+        let commitment = commit_to_files(&mut out, &params, &v, None, &path, Some(&blinding_out), false).unwrap();
+        assert_eq!(cuproof::util::load_commitment(&path, &params).unwrap(), commitment);
+
+        let r = cuproof::util::load_blinding(&blinding_out).unwrap();
+        let proof = prove_for_commitment_file(&mut out, &params, &a, &b, &v, &path, &r, "", &[], &mut OsRng).unwrap();
+        assert_eq!(proof.C, commitment);
+        assert_eq!(proof.statement.as_ref().unwrap().commitment, commitment);
+        assert_eq!(cuproof_verify_checked(&proof, &params), Ok(()));
+        assert!(cuproof_verify_with_range(&proof, &params, &a, &b));
+
+        let wrong = prove_for_commitment_file(&mut out, &params, &a, &b, &BigInt::from(43), &path, &r, "", &[], &mut OsRng);
+        assert!(matches!(wrong, Err(CliError::Failed { error: Error::Prove(cuproof::error::ProveError::CommitmentMismatch), .. })));
+        let other = params.rotate_generators("other");
+        assert!(prove_for_commitment_file(&mut out, &other, &a, &b, &v, &path, &r, "", &[], &mut OsRng).is_err());
+
+        let fixed = commit_to_files(&mut out, &params, &v, Some(Secret::new(BigInt::from(7))), &path, None, false).unwrap();
+        assert_eq!(fixed, commit(&params, &v, &BigInt::from(7)));
+        assert_ne!(fixed, commitment);
+        for path in [path, blinding_out] { let _ = std::fs::remove_file(path); }
+    }
+
+    // Purpose: ensure CLI numbers parse as documented and malformed ones fail with a precise message
+    // Params: decimal, hex with and without prefix, mixed case, separators, negatives, --radix overrides, bad digits
+    // Output: the expected values, the decimal note for bare digits, errors naming the problem
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn cli_numbers_accept_decimal_and_hex() {
+        let value = |s: &str, radix: Radix| parse_cli_bigint(s, radix, true).map(|(v, _)| v);
+        assert_eq!(value("100", Radix::Auto), Ok(BigInt::from(100)));
+        assert_eq!(value("1_000_000", Radix::Auto), Ok(BigInt::from(1_000_000)));
+        assert_eq!(value("0x100", Radix::Auto), Ok(BigInt::from(0x100)));
+        assert_eq!(value("0XaB_cD", Radix::Auto), Ok(BigInt::from(0xabcd)));
+        assert_eq!(value("2a", Radix::Auto), Ok(BigInt::from(0x2a)));
+        assert_eq!(value("-42", Radix::Auto), Ok(BigInt::from(-42)));
+        assert_eq!(value("-0x2A", Radix::Auto), Ok(BigInt::from(-0x2a)));
+        assert_eq!(value("100", Radix::Hex), Ok(BigInt::from(0x100)));
+        assert_eq!(value("0x100", Radix::Hex), Ok(BigInt::from(0x100)));
+        assert_eq!(value("100", Radix::Dec), Ok(BigInt::from(100)));
+
+        assert_eq!(parse_cli_bigint("100", Radix::Auto, false).unwrap().1.as_deref(), Some("'100' parsed as decimal 100; use 0x prefix for hex"));
+        assert_eq!(parse_cli_bigint("7", Radix::Auto, false).unwrap().1, None);
+        assert_eq!(parse_cli_bigint("100", Radix::Dec, false).unwrap().1, None);
+
+        assert_eq!(value("0xGG", Radix::Auto), Err("'0xGG' is not a hex number: 'G' is not a hex digit".to_string()));
+        assert_eq!(value("12a", Radix::Dec), Err("'12a' is not a decimal number: 'a' is not a decimal digit".to_string()));
+        assert_eq!(value("0x10", Radix::Dec), Err("'0x10' is hex but --radix dec was given".to_string()));
+        assert!(value("", Radix::Auto).unwrap_err().contains("no digits"));
+        assert!(value("0x", Radix::Auto).unwrap_err().contains("no digits"));
+        for s in ["_1", "1_", "1__0"] { assert!(value(s, Radix::Auto).unwrap_err().contains("misplaced '_'"), "{}", s); }
+        assert_eq!(parse_cli_bigint("-5", Radix::Auto, false).unwrap_err(), "'-5' is negative; this argument must not be");
+    }
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("cuproof").chain(args.iter().copied()))
+    }
+
+    /// An Output over `stdin` and a captured stdout, a terminal when `stdout_tty`
+    fn piped(stdin: &[u8], stdout_tty: bool) -> (Output, Captured) {
+        let stdout = Captured::default();
+        let stdio = Stdio { stdin: Box::new(std::io::Cursor::new(stdin.to_vec())), stdout: Box::new(stdout.clone()), stdin_tty: false, stdout_tty, stderr_tty: false };
+        (Output::new(false, stdio), stdout)
+    }
+
+    /// `cuproof <args>` in-process over `stdin`: its outcome and stdout, and the exit status when it ran
+    fn run_piped(args: &[&str], stdin: &[u8], stdout_tty: bool) -> (Result<i32, CliError>, Vec<u8>) {
+        let (mut out, stdout) = piped(stdin, stdout_tty);
+        let code = match run(std::iter::once("cuproof").chain(args.iter().copied()).map(String::from).collect(), &mut out) {
+            Err(e) => Err(e),
+            outcome => Ok(finish(outcome, out)),
+        };
+        let bytes = stdout.0.borrow().clone();
+        (code, bytes)
+    }
+
+    /// The message of the usage error run returns for `args`
+    fn usage_error(args: &[&str]) -> String {
+        let mut out = piped(b"", false).0;
+        match run(std::iter::once("cuproof").chain(args.iter().copied()).map(String::from).collect(), &mut out) {
+            Err(e @ (CliError::Usage(_) | CliError::InvalidValue { .. })) => e.to_string(),
+            other => panic!("{:?}: expected a usage error, got {:?}", args, other),
+        }
+    }
+
+    // Purpose: ensure `-` paths pipe setup into prove into verify within one process, stdout carrying only the data
+    // Params: fast params written to stdout, then read from stdin by prove, whose binary proof verify reads from stdin
+    // Output: params and proof byte streams that load as such; VALID for the proved range, INVALID for another
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn prove_pipes_into_verify() {
+        let (code, params) = run_piped(&["setup", "fast", "-"], b"", false);
+        assert_eq!(code.unwrap(), 0);
+        assert!(cuproof::util::read_params(&mut params.as_slice(), &ReadLimits::default()).unwrap().is_insecure());
+        let params_path = temp_path("piped_params");
+        std::fs::write(&params_path, &params).unwrap();
+
+        let (code, proof) = run_piped(&["prove", "--ephemeral", "-", "10", "100", "42", "-"], &params, false);
+        assert_eq!(code.unwrap(), 0);
+        assert!(proof.starts_with(FileKind::Proof.magic()), "a pipe gets the binary proof and nothing else");
+
+        let (code, stdout) = run_piped(&["verify", &params_path, "10", "100", "-"], &proof, false);
+        assert_eq!(code.unwrap(), 0);
+        assert!(String::from_utf8(stdout).unwrap().ends_with("VALID\n"));
+        let (code, _) = run_piped(&["verify", &params_path, "10", "99", "-"], &proof, false);
+        assert_eq!(code.unwrap(), 1);
+
+        let (code, commitment) = run_piped(&["commit", "--blinding", "7", &params_path, "42", "--out", "-"], b"", false);
+        assert_eq!(code.unwrap(), 0);
+        let commitment = cuproof::util::read_commitment(&mut commitment.as_slice(), &load_params(&params_path).unwrap(), &ReadLimits::default()).unwrap();
+        assert_eq!(commitment, commit(&load_params(&params_path).unwrap(), &BigInt::from(42), &BigInt::from(7)));
+        let _ = std::fs::remove_file(params_path);
+    }
+
+    // Purpose: ensure terminals get no binary data unless asked, stdin feeds one input, and stdin reads are capped
+    // Params: setup and prove to a terminal stdout, with and without --force-binary; two `-` inputs; an oversized stdin
+    // Output: params refused and written with --force-binary; the proof armored (armor feature) or refused;
+    //         usage errors naming the clash; a read-limit failure
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn stdio_guards() {
+        let (code, _) = run_piped(&["setup", "fast", "-"], b"", true);
+        assert!(matches!(code, Err(CliError::Usage(message)) if message.contains("--force-binary")));
+        let (code, params) = run_piped(&["setup", "--force-binary", "fast", "-"], b"", true);
+        assert_eq!(code.unwrap(), 0);
+        assert!(params.starts_with(FileKind::Params.magic()));
+
+        let (code, proof) = run_piped(&["prove", "--ephemeral", "-", "10", "100", "42", "-"], &params, true);
+        if cfg!(feature = "armor") {
+            assert_eq!(code.unwrap(), 0);
+            assert!(proof.starts_with(b"-----BEGIN CUPROOF-----"));
+        } else {
+            assert!(matches!(code, Err(CliError::Usage(message)) if message.contains("--force-binary")));
+        }
+        let (code, proof) = run_piped(&["prove", "--ephemeral", "--force-binary", "-", "10", "100", "42", "-"], &params, true);
+        assert_eq!(code.unwrap(), 0);
+        assert!(proof.starts_with(FileKind::Proof.magic()));
+
+        let (code, _) = run_piped(&["verify", "-", "10", "100", "-"], &params, false);
+        assert!(matches!(code, Err(CliError::Usage(message)) if message.contains("params and proof cannot both read stdin")));
+        let (code, _) = run_piped(&["prove", "--value-stdin", "--ephemeral", "-", "10", "100", "-"], b"42\n", false);
+        assert!(matches!(code, Err(CliError::Usage(message)) if message.contains("cannot both read stdin")));
+        let (code, _) = run_piped(&["--json", "setup", "fast", "-"], b"", false);
+        assert!(matches!(code, Err(CliError::Usage(message)) if message.contains("--json needs a file for the params")));
+        let (code, _) = run_piped(&["setup", "trusted", "-"], b"", false);
+        assert!(matches!(code, Err(CliError::Usage(message)) if message.contains("whose transcript goes beside them")));
+
+        let oversized = vec![b'x'; DEFAULT_MAX_READ_BYTES + 1];
+        let (code, _) = run_piped(&["info", "-"], &oversized, false);
+        assert!(matches!(code, Err(CliError::Failed { context, error, .. }) if context == "Failed to read file -" && error.to_string().contains("read limit")));
+    }
+
+    // Purpose: ensure malformed command lines fail as usage errors naming the problem, before any file is touched
+    // Params: missing and surplus arguments, bad numbers, out-of-range counts, conflicting and dangling flags,
+    //         an unknown command and a flag in its pre-parser position
+    // Output: CliError::Usage (exit 2) whose message names the argument or the rule broken
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn argument_errors_are_usage_errors() {
+        let cases: &[(&[&str], &str)] = &[
+            (&["verify", "p", "10", "100"], "<proof_path>"),
+            (&["verify", "p", "proof"], "verify needs <a> <b> <proof_path>"),
+            (&["verify", "p", "1", "2", "proof", "--range", "1", "2"], "verify takes the range once"),
+            (&["verify", "p", "proof", "--statement", "s", "--commitment", "c1"], "cannot be used with"),
+            (&["verify", "p", "10", "100", "proof", "extra"], "unexpected argument 'extra'"),
+            (&["verify", "p", "xyz", "100", "proof"], "'xyz' is not a hex number"),
+            (&["verify", "p", "0x10", "100", "proof", "--radix", "dec"], "'0x10' is hex but --radix dec was given"),
+            (&["verify", "p", "10", "100", "proof", "--radix", "oct"], "unknown radix oct"),
+            (&["commit", "p", "-5", "--out", "c"], "'-5' is negative"),
+            (&["commit", "p", "42", "--value-stdin", "--out", "c"], "cannot be used with"),
+            (&["commit", "p", "--out", "c"], "<v|--value-stdin|--value-env <VAR>>"),
+            (&["commit", "p", "42"], "--out <commitment_path>"),
+            (&["prove", "--commitment", "c", "p", "1", "2", "3", "proof"], "--blinding-in <r_path>"),
+            (&["prove", "p", "1", "2", "proof"], "prove needs <v> before <proof_path>"),
+            (&["prove", "p", "proof"], "prove needs <a> <b>, or --range <a> <b>"),
+            (&["prove", "--ephemeral", "--range", "1", "2", "p", "1", "2", "3", "proof"], "prove takes the range once"),
+            (&["prove", "--value-env", "V", "p", "1", "2", "3", "proof"], "prove takes no <v> argument"),
+            (&["prove", "--format", "xml", "p", "1", "2", "3", "proof"], "unknown format xml"),
+            (&["prove", "p", "1", "2", "3", "proof"], "pass --ephemeral to discard it anyway"),
+            (&["prove", "--ephemeral", "--blinding-out", "r", "p", "1", "2", "3", "proof"], "cannot be used with"),
+            (&["prove", "--blinding", "7", "--blinding-out", "r", "p", "1", "2", "3", "proof"], "cannot be used with"),
+            (&["prove", "--commitment", "c", "--blinding-out", "r", "p", "1", "2", "3", "proof"], "--blinding-in <r_path>"),
+            (&["setup", "fast", "--bits", "0", "p"], "expected a whole number of at least 1"),
+            (&["setup", "fast", "--bits", "768", "p"], "fast setup supports [512, 1024] bits, not 768"),
+            (&["setup", "import", "p"], "--modulus-hex <hex>"),
+            (&["setup", "import", "--modulus-hex", "0xzz", "p"], "'0xzz' is not a hex number"),
+            (&["setup"], "<out>"),
+            (&["setup", "--mode", "slow", "p"], "invalid value 'slow'"),
+            (&["setup", "--mode", "fast", "p"], "INSECURE test params whose factorization is known; pass --allow-insecure"),
+            (&["setup", "--bits", "1024", "p"], "below the secure minimum of 2048 bits; pass --allow-insecure"),
+            (&["setup", "--mode", "fast", "--allow-insecure", "--seed", "abc", "p"], "'abc' is not an even number of hex digits"),
+            (&["setup", "--seed", "00", "p"], "--seed only applies to --mode fast"),
+            (&["setup", "--mode", "import", "p"], "--mode import needs --modulus-hex"),
+            (&["setup", "--bits", "1024", "fast", "p"], "--bits goes after `setup fast`"),
+            (&["bench", "--iterations", "0"], "expected a whole number from 1 to 100000"),
+            (&["bench", "--bits", "128"], "expected a whole number from 256 to"),
+            (&["--threads", "0", "info", "f"], "expected a whole number of at least 1"),
+            (&["benchmark", "medium"], "invalid value 'medium'"),
+            (&["ceremony", "verify", "initial", "params"], "<params> <proof> pairs"),
+            (&["--radix", "hex", "verify", "p", "10", "100", "proof"], "note: --radix now goes after the command"),
+            (&["--format", "json", "setup", "fast", "p"], "note: --format now goes after the command"),
+            (&["frobnicate"], "Unknown command frobnicate"),
+            (&[], "Usage: cuproof"),
+        ];
+        for (args, expected) in cases {
+            let message = usage_error(args);
+            assert!(message.contains(expected), "{:?}: {:?} not in\n{}", args, expected, message);
+        }
+        assert!(matches!(run(vec!["cuproof".into(), "--json".into(), "benchmark".into(), "fast".into()], &mut piped(b"", false).0), Err(CliError::Usage(_))));
+        assert_eq!(run(vec!["cuproof".into(), "--help".into()], &mut piped(b"", false).0).unwrap(), CommandOutcome::Done);
+    }
+
+    // Purpose: ensure the invocations of the hand-rolled parser this one replaced still parse, to the same values
+    // Params: every command in its old positional form, flags before, between and after the positionals
+    // Output: each parses; secrets, ranges, counts and global flags land on the expected arguments
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn earlier_invocations_still_parse() {
+        let forms: &[&[&str]] = &[
+            &["setup", "fast", "--bits", "1024", "p"],
+            &["setup", "trusted", "--bits", "3072", "--threads", "4", "--resume", "ck", "p"],
+            &["setup", "verify", "p", "p.transcript"],
+            &["setup", "import", "--modulus-hex", "c7f1", "p"],
+            &["setup", "--format", "bin", "fast", "p"],
+            &["commit", "p", "42", "--blinding", "7", "--out", "c", "--blinding-out", "r"],
+            &["commit", "p", "--value-stdin", "--blinding-stdin", "--out", "c"],
+            &["prove", "p", "a", "64", "2a", "proof"],
+            &["prove", "--armor", "p", "0", "100", "42", "-"],
+            &["prove", "--commitment", "c", "--blinding-in", "r", "p", "10", "100", "42", "proof"],
+            &["verify", "p", "-0x10", "0x10", "proof", "--radix", "hex"],
+            &["batch-verify", "p", "dir", "--jobs", "4"],
+            &["batch-prove", "p", "rows.csv", "proofs", "--jobs", "4", "--secrets", "values.csv", "--force"],
+            &["info", "f"],
+            &["dump", "--params", "p", "--field", "L[0]", "proof"],
+            &["convert", "in", "out"],
+            &["encrypt", "openings.csv", "openings.enc"],
+            &["params", "rotate", "p", "next", "out"],
+            &["bench", "--bits", "256", "--range-bits", "32", "--iterations", "2", "--setup-sample"],
+            &["selftest"],
+            &["fingerprint", "p"],
+            &["fingerprint", "--long", "p"],
+            &["selftest", "p"],
+            &["benchmark", "fast", "8", "16"],
+            &["benchmark", "setup"],
+            &["ceremony", "contribute", "prev", "out", "proof", "entropy"],
+            &["ceremony", "verify", "initial", "p1", "c1", "p2", "c2"],
+            &["verify", "p", "10", "100", "proof", "--json"],
+        ];
+        for args in forms {
+            if let Err(e) = parse(args) { panic!("{:?}: {}", args, e); }
+        }
+
+        let cli = parse(&["prove", "--value-env", "V", "--commitment", "c", "--blinding-stdin", "p", "-0x10", "-5", "proof", "-q"]).unwrap();
+        assert!(cli.quiet);
+        let Commands::Prove(m) = &cli.command else { panic!("not prove") };
+        assert_eq!((m.a.as_str(), m.b.as_deref(), m.v.as_deref(), m.proof_path.as_deref()), ("-0x10", Some("-5"), Some("proof"), None));
+        assert_eq!(m.value.source(None), Some(SecretSource::Env("V".to_string())));
+        assert_eq!(m.blinding_secret.source(None), Some(SecretSource::Stdin));
+
+        let cli = parse(&["--json", "setup", "trusted", "--threads", "3", "p"]).unwrap();
+        assert!(cli.json);
+        assert_eq!(cli.threads, Some(3));
+        let Commands::Setup(setup) = &cli.command else { panic!("not setup") };
+        let Some(SetupCommand::Trusted(m)) = &setup.command else { panic!("not setup trusted") };
+        assert_eq!((m.bits, m.params_path.as_str()), (None, "p"));
+    }
+
+    // Purpose: ensure a trusted setup drives SetupStatus, which logs, redraws or stays silent by style
+    // Params: a 256-bit serial setup reporting into a buffer in each style
+    // Output: progress events in every style; log lines naming the phases, a redrawn line ended
+    //         before the total, nothing when Off; the fingerprint printed on completion
+    // Usage: `cargo test --bin cuproof` or `cargo test`
+    #[test]
+    fn setup_status_follows_the_progress_callback() {
+        for style in [StatusStyle::Log, StatusStyle::Line, StatusStyle::Off] {
+            let mut status = SetupStatus::new(Vec::new(), style, 256);
+            let (params, _) = trusted_setup_with_transcript(256, true, None, |p| status.report(p)).unwrap();
+            status.finish(Some(&params));
+            assert!(status.events >= 1, "{:?}", style);
+            let text = String::from_utf8(status.sink).unwrap();
+            match style {
+                StatusStyle::Off => assert!(text.is_empty(), "{}", text),
+                StatusStyle::Log => {
+                    assert!(text.lines().next().unwrap().starts_with("sieving: "), "{}", text);
+                    assert!(text.contains("\nderiving generators: ") && !text.contains('\r'), "{}", text);
+                }
+                StatusStyle::Line => assert!(text.starts_with('\r') && text.contains("\nSetup finished in "), "{}", text),
+            }
+            if style != StatusStyle::Off {
+                assert!(text.ends_with(&format!("(fingerprint {})\n", params.fingerprint_hex())), "{}", text);
+            }
+        }
+        let status = SetupStatus::new(Vec::new(), StatusStyle::Log, 2048);
+        let progress = |candidates_tested, secs| SetupProgress { phase: SetupPhase::TestingP, candidates_tested, elapsed: Duration::from_secs(secs) };
+        assert_eq!(status.describe(progress(0, 0)), "testing p: 0 candidates, 0.0s elapsed, estimating time left");
+        assert_eq!(status.describe(progress(355, 10)), "testing p: 355 candidates, 10.0s elapsed, about 10s left");
+        assert!(status.describe(progress(5000, 60)).ends_with("taking longer than average"));
+    }
+}
@@ -0,0 +1,105 @@
+//! `cuproof aggregate`: the ranges of every row of a manifest in one aggregated proof
+
+use std::time::Instant;
+use clap::Args;
+use cuproof::aggregate::{aggregate_prove, individual_proofs_size, AggregateEntry, MAX_AGGREGATED_STATEMENTS};
+use cuproof::batch::{read_records, Record};
+use cuproof::range_proof::ProveError;
+use cuproof::util::{save_statements, write_aggregated_proof};
+use super::args::{parse_cli_bigint, Radix, RadixArg};
+use super::files::{check_columns, file_only, load_params_arg, say_params, warn_if_insecure, warn_if_legacy};
+use super::generators::{check_generators_arg, GeneratorsArg};
+use super::{usage, CliError, CommandOutcome, Output};
+
+/// Prove the ranges of every row of a manifest in one aggregated proof (see cuproof::aggregate)
+#[derive(Args)]
+#[command(after_help = "Every row must carry the blinding of its commitment, so the commitments can be opened later. \
+    Reports the size of the aggregated proof against one proof per row.
+
+Examples:
+  cuproof aggregate --statements-out agg.statements params.bin rows.csv agg.proof
+  cuproof aggregate --generators agg4.gens params.bin rows.csv agg.proof")]
+pub(super) struct AggregateArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// CSV or JSON lines with columns value, blinding, a and b, one statement per row
+    #[arg(value_name = "manifest")]
+    manifest: String,
+    /// Where to write the aggregated proof
+    #[arg(value_name = "proof_path|-")]
+    proof_path: String,
+    /// Also write the statements, in row order, for verify --statements
+    #[arg(long, value_name = "statements_path")]
+    statements_out: Option<String>,
+    #[command(flatten)]
+    generators: GeneratorsArg,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+/// The columns of aggregate manifests
+const AGGREGATE_COLUMNS: [&str; 4] = ["value", "blinding", "a", "b"];
+
+pub(super) fn run_aggregate(m: &AggregateArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let radix = m.radix.get();
+    let params_path = m.params_path.as_str();
+    let manifest_path = file_only(&m.manifest, "manifest")?;
+    let proof_path = m.proof_path.as_str();
+    let statements_path = m.statements_out.as_deref().map(|path| file_only(path, "statements file")).transpose()?;
+    if out.json && proof_path == "-" { return Err(usage("--json needs a proof path: stdout carries the JSON object")); }
+    let records = read_records(std::path::Path::new(manifest_path))
+        .map_err(|e| CliError::failed(format!("Failed to read manifest {}", manifest_path), e))?;
+    if records.is_empty() { return Err(CliError::Usage(format!("No rows to aggregate in {}", manifest_path))); }
+    if records.len() > MAX_AGGREGATED_STATEMENTS {
+        return Err(CliError::Usage(format!("{} has {} rows; an aggregated proof holds at most {}", manifest_path, records.len(), MAX_AGGREGATED_STATEMENTS)));
+    }
+    let entries = records.iter()
+        .map(|record| aggregate_row(record, radix).map_err(|e| CliError::Usage(format!("{} line {}: {}", manifest_path, record.line, e))))
+        .collect::<Result<Vec<_>, _>>()?;
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    if let Some(path) = m.generators.generators.as_deref() { check_generators_arg(out, path, &params, entries.len())?; }
+    let started = Instant::now();
+    let proof = aggregate_prove(&entries, &params).map_err(|e| match e {
+        ProveError::Aggregate { index, error } => CliError::failed(format!("Failed to prove {} line {}", manifest_path, records[index].line), *error),
+        e => CliError::failed("Failed to prove", e),
+    })?;
+    let prove_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let mut bytes = Vec::new();
+    write_aggregated_proof(&mut bytes, &proof).map_err(|e| CliError::failed("Failed to encode the aggregated proof", e))?;
+    out.write_output(proof_path, "proof", true, |w| w.write_all(&bytes))?;
+    if let Some(path) = statements_path {
+        save_statements(path, &proof.statements).map_err(|e| CliError::failed(format!("Failed to save statements {}", path), e))?;
+    }
+    let individual = individual_proofs_size(&proof);
+    let saving = 1.0 - bytes.len() as f64 / individual as f64;
+    if proof_path != "-" { out.say(format_args!("Saved aggregated proof of {} statements to {}", entries.len(), proof_path)); }
+    if let Some(path) = statements_path { out.say(format_args!("Saved statements to {}", path)); }
+    out.say(format_args!("Size: {} bytes, against {} bytes for {} single proofs ({:.1}% smaller)", bytes.len(), individual, entries.len(), 100.0 * saving));
+    say_params(out, params_path, &params);
+    out.set("proof_path", proof_path);
+    out.set("statements_path", statements_path);
+    out.set("statements", entries.len());
+    out.set("size", bytes.len());
+    out.set("individual_size", individual);
+    out.set("saving", saving);
+    out.set("prove_ms", prove_ms);
+    Ok(CommandOutcome::Done)
+}
+
+/// The entry of one aggregate manifest row; unlike batch-prove every row needs its blinding
+/// - returns: the entry, or why the row is unusable; the text of value and blinding is never quoted
+fn aggregate_row(record: &Record, radix: Radix) -> Result<AggregateEntry, String> {
+    check_columns(record, &AGGREGATE_COLUMNS)?;
+    let secret = |field: &str, signed: bool| {
+        let s = record.get(field).filter(|s| !s.is_empty()).ok_or_else(|| format!("no {}", field))?;
+        parse_cli_bigint(s, radix, signed).map(|(n, _)| n).map_err(|_| format!("invalid {}: not a number (the text is secret, so not shown)", field))
+    };
+    let number = |field: &str| {
+        let s = record.get(field).ok_or_else(|| format!("no {}", field))?;
+        parse_cli_bigint(s, radix, true).map(|(n, _)| n).map_err(|e| format!("invalid {}: {}", field, e))
+    };
+    Ok(AggregateEntry { v: secret("value", true)?, r: secret("blinding", false)?, a: number("a")?, b: number("b")? })
+}
@@ -0,0 +1,291 @@
+//! Arguments several commands share: the value parsers, --radix and the numbers it governs, the
+//! sources of secrets, and the worker count
+
+use std::env;
+use std::io::Write;
+use clap::Args;
+use num_bigint::BigInt;
+use cuproof::secret::Secret;
+use cuproof::util::{read_blinding, ReadLimits};
+use cuproof::workers::Workers;
+use super::files::read_secret_input;
+use super::{CliError, Output, STDIN_SECRETS};
+
+/// Hex bytes of --seed and --nonce; an alias, so the derive takes them as one value rather than many
+pub(super) type HexBytes = Vec<u8>;
+
+/// How numeric arguments of commit, prove and verify are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Radix {
+    /// `0x` prefix: hex; only decimal digits: decimal; hex letters without prefix: hex
+    Auto,
+    Hex,
+    Dec,
+}
+
+/// `--radix hex|dec`, overriding the guess parse_cli_bigint makes for bare digits
+#[derive(Args)]
+pub(super) struct RadixArg {
+    /// Read every number as hex, or as decimal; by default a 0x prefix or hex letters mean hex
+    #[arg(long = "radix", value_name = "hex|dec", value_parser = parse_radix)]
+    radix: Option<Radix>,
+}
+
+impl RadixArg {
+    /// --radix, Radix::Auto when absent
+    pub(super) fn get(&self) -> Radix {
+        self.radix.unwrap_or(Radix::Auto)
+    }
+}
+
+/// --time of prove and verify (see with_timing)
+#[derive(Args)]
+pub(super) struct TimeArg {
+    /// Print the time spent deserializing, deriving challenges, committing and in the inner-product argument, the total and the peak RSS on stderr [--json: timing]
+    #[arg(long)]
+    pub(super) time: bool,
+}
+
+/// --compress of setup and prove; every command reads compressed files
+#[derive(Args)]
+pub(super) struct CompressArg {
+    /// Compress the written file with zstd, at LEVEL from 1 to 22 if given [default level: 3]
+    #[arg(long, value_name = "LEVEL", num_args = 0..=1, require_equals = true, default_missing_value = "3", value_parser = count(1, 22), global = true)]
+    compress: Option<usize>,
+}
+
+impl CompressArg {
+    /// The zstd level, None without --compress
+    pub(super) fn level(&self) -> Option<i32> {
+        self.compress.map(|level| level as i32)
+    }
+}
+
+/// `--value-stdin` and `--value-env VAR`: where v comes from when it is not an argument
+#[derive(Args)]
+pub(super) struct ValueSecret {
+    /// Read the value from the next line of stdin
+    #[arg(long)]
+    pub(super) value_stdin: bool,
+    /// Read the value from the environment variable VAR
+    #[arg(long, value_name = "VAR")]
+    pub(super) value_env: Option<String>,
+}
+
+impl ValueSecret {
+    /// `given` on the command line, else --value-stdin, else --value-env
+    pub(super) fn source(&self, given: Option<SecretSource>) -> Option<SecretSource> {
+        secret_source(given, self.value_stdin, self.value_env.as_deref())
+    }
+}
+
+/// `--blinding-stdin` and `--blinding-env VAR`: where the blinding comes from when it is neither an
+/// argument nor a file
+#[derive(Args)]
+pub(super) struct BlindingSecret {
+    /// Read the blinding from the next line of stdin
+    #[arg(long)]
+    pub(super) blinding_stdin: bool,
+    /// Read the blinding from the environment variable VAR
+    #[arg(long, value_name = "VAR")]
+    pub(super) blinding_env: Option<String>,
+}
+
+impl BlindingSecret {
+    /// `given` on the command line or as a file, else --blinding-stdin, else --blinding-env
+    pub(super) fn source(&self, given: Option<SecretSource>) -> Option<SecretSource> {
+        secret_source(given, self.blinding_stdin, self.blinding_env.as_deref())
+    }
+}
+
+/// Where a secret number (v or a blinding) comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum SecretSource {
+    /// given on the command line, where shell history and process listings see it
+    Arg(String),
+    /// the next line of stdin; v comes before the blinding when both are piped
+    Stdin,
+    /// an environment variable
+    Env(String),
+    /// a file written by save_blinding, encrypted or not
+    File(String),
+}
+
+/// The source of a secret: `given` (on the command line, or a file), its stdin flag or its
+/// environment variable; the parser lets at most one of them through
+pub(super) fn secret_source(given: Option<SecretSource>, stdin: bool, env: Option<&str>) -> Option<SecretSource> {
+    given
+        .or_else(|| stdin.then_some(SecretSource::Stdin))
+        .or_else(|| env.map(|var| SecretSource::Env(var.to_string())))
+}
+
+/// --blinding: a number, or else the path of an existing file, as --blinding-in reads
+pub(super) fn blinding_secret(given: &str, radix: Radix) -> SecretSource {
+    if parse_cli_bigint(given, radix, false).is_err() && std::path::Path::new(given).is_file() {
+        SecretSource::File(given.to_string())
+    } else {
+        SecretSource::Arg(given.to_string())
+    }
+}
+
+/// Read the secret `name` from `source` and parse it with parse_cli_bigint
+/// - returns: the value; secrets from stdin and the environment never appear in messages, and a
+///   secret given as an argument draws a warning
+pub(super) fn read_secret(out: &mut Output, name: &str, source: SecretSource, radix: Radix, signed: bool) -> Result<Secret<BigInt>, CliError> {
+    let text = Secret::new(match source {
+        SecretSource::Arg(text) => {
+            tracing::warn!("{} was passed as an argument, where shell history, ps and CI logs can see it;\nprefer --{1}-stdin or --{1}-env VAR",
+                name, if name == "v" { "value" } else { name });
+            return num_arg(name, Secret::new(text).expose(), radix, signed).map(Secret::new);
+        }
+        SecretSource::File(path) => {
+            let bytes = read_secret_input(out, &path, "blinding")?;
+            return read_blinding(&mut bytes.expose().as_slice(), &ReadLimits::default())
+                .map(Secret::new)
+                .map_err(|e| CliError::failed(format!("Failed to load {} {}", name, path), e));
+        }
+        SecretSource::Env(var) => env::var(&var).map_err(|_| CliError::Usage(format!("environment variable {} is not set or not UTF-8", var)))?,
+        SecretSource::Stdin => read_stdin_secret(out, name)?,
+    });
+    let (value, note) = parse_cli_bigint(text.expose().trim(), radix, signed)
+        .map_err(|_| CliError::Usage(format!("Invalid {}: not a number in the expected radix (value withheld)", name)))?;
+    if note.is_some() { tracing::info!("{}: bare digits parsed as decimal; use 0x prefix for hex", name); }
+    Ok(Secret::new(value))
+}
+
+/// The next line of stdin, shared by the secrets of one command, prompting for `name` on a terminal
+fn read_stdin_secret(out: &mut Output, name: &str) -> Result<String, CliError> {
+    out.claim_stdin(STDIN_SECRETS)?;
+    if out.stdio.stdin_tty {
+        eprint!("{}: ", name);
+        let _ = std::io::stderr().flush();
+    }
+    let mut line = String::new();
+    out.stdio.stdin.read_line(&mut line).map_err(|e| CliError::failed(format!("Failed to read {} from stdin", name), e))?;
+    if line.is_empty() { return Err(CliError::Usage(format!("stdin ended before {} was read", name))); }
+    Ok(line)
+}
+
+/// Encoding of the files written by setup and prove
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum OutputFormat {
+    Bin,
+    Json,
+}
+
+/// The value parser of `--format json|bin`
+pub(super) fn parse_format(s: &str) -> Result<OutputFormat, String> {
+    match s {
+        "bin" => Ok(OutputFormat::Bin),
+        "json" if cfg!(feature = "json") => Ok(OutputFormat::Json),
+        "json" => Err("--format json requires the json feature".to_string()),
+        other => Err(format!("unknown format {}; use json or bin", other)),
+    }
+}
+
+/// A value parser for a whole number in min..=max
+pub(super) fn count(min: usize, max: usize) -> impl Fn(&str) -> Result<usize, String> + Clone + Send + Sync + 'static {
+    move |s: &str| match s.parse::<usize>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ if max == usize::MAX => Err(format!("expected a whole number of at least {}", min)),
+        _ => Err(format!("expected a whole number from {} to {}", min, max)),
+    }
+}
+
+/// A value parser for the numbers of commit, prove and verify: the text, once parse_cli_bigint
+/// accepts it; the handler reads it with num_arg in the radix --radix picks
+pub(super) fn number(signed: bool) -> impl Fn(&str) -> Result<String, String> + Clone + Send + Sync + 'static {
+    move |s: &str| parse_cli_bigint(s, Radix::Auto, signed).map(|_| s.to_string())
+}
+
+/// A value parser for a hex modulus or commitment, with or without 0x
+pub(super) fn hex_number(s: &str) -> Result<BigInt, String> {
+    parse_cli_bigint(s, Radix::Hex, false).map(|(n, _)| n)
+}
+
+/// The value parser of `setup --seed`: hex bytes, with or without 0x
+pub(super) fn parse_seed(s: &str) -> Result<HexBytes, String> {
+    match hex::decode(s.strip_prefix("0x").unwrap_or(s)) {
+        Ok(seed) if !seed.is_empty() => Ok(seed),
+        _ => Err(format!("'{}' is not an even number of hex digits", s)),
+    }
+}
+
+/// The value parser of `--nonce`: hex bytes, with or without 0x; empty is no nonce
+pub(super) fn parse_nonce(s: &str) -> Result<HexBytes, String> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s)).map_err(|_| format!("'{}' is not an even number of hex digits", s))
+}
+
+/// The value parser of `prove --seed`: exactly 32 bytes of hex, with or without 0x
+pub(super) fn parse_prove_seed(s: &str) -> Result<[u8; 32], String> {
+    let mut seed = [0u8; 32];
+    hex::decode_to_slice(s.strip_prefix("0x").unwrap_or(s), &mut seed).map_err(|_| format!("'{}' is not 64 hex digits", s))?;
+    Ok(seed)
+}
+
+/// The value parser of `--radix hex|dec`
+fn parse_radix(s: &str) -> Result<Radix, String> {
+    match s {
+        "hex" => Ok(Radix::Hex),
+        "dec" => Ok(Radix::Dec),
+        other => Err(format!("unknown radix {}; use hex or dec", other)),
+    }
+}
+
+/// Parse a numeric CLI argument: `0x`-prefixed hex or plain decimal, `_` allowed between digits,
+/// a leading `-` when `signed`
+/// - returns: the value and, for bare digits read as decimal, a note saying so (old releases read them as hex)
+/// - usage: every number commit, prove and verify take goes through here
+pub(super) fn parse_cli_bigint(s: &str, radix: Radix, signed: bool) -> Result<(BigInt, Option<String>), String> {
+    let (negative, body) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    if negative && !signed { return Err(format!("'{}' is negative; this argument must not be", s)); }
+    let prefixed = body.strip_prefix("0x").or_else(|| body.strip_prefix("0X"));
+    let (digits, base) = match (prefixed, radix) {
+        (Some(_), Radix::Dec) => return Err(format!("'{}' is hex but --radix dec was given", s)),
+        (Some(hex), _) => (hex, 16),
+        (None, Radix::Hex) => (body, 16),
+        (None, Radix::Dec) => (body, 10),
+        (None, Radix::Auto) if body.bytes().all(|c| c.is_ascii_digit() || c == b'_') => (body, 10),
+        (None, Radix::Auto) => (body, 16),
+    };
+    let name = if base == 16 { "hex" } else { "decimal" };
+    if digits.is_empty() { return Err(format!("'{}' has no digits", s)); }
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err(format!("'{}' has a misplaced '_' separator; use it only between digits", s));
+    }
+    if let Some(bad) = digits.chars().find(|&c| c != '_' && !c.is_digit(base)) {
+        return Err(format!("'{}' is not a {} number: '{}' is not a {} digit", s, name, bad, name));
+    }
+    let clean: String = digits.chars().filter(|&c| c != '_').collect();
+    let value = BigInt::parse_bytes(clean.as_bytes(), base).expect("digits checked above");
+    let note = (prefixed.is_none() && radix == Radix::Auto && base == 10 && value >= BigInt::from(10))
+        .then(|| format!("'{}' parsed as decimal {}; use 0x prefix for hex", s, value));
+    Ok((if negative { -value } else { value }, note))
+}
+
+/// parse_cli_bigint for the argument `name`, logging its note
+pub(super) fn num_arg(name: &str, s: &str, radix: Radix, signed: bool) -> Result<BigInt, CliError> {
+    let (value, note) = parse_cli_bigint(s, radix, signed)
+        .map_err(|e| CliError::InvalidValue { name: name.to_string(), value: s.to_string(), message: format!("Invalid {}: {}", name, e) })?;
+    if let Some(note) = note { tracing::info!("{}: {}", name, note); }
+    Ok(value)
+}
+
+/// Where the worker count comes from when neither --jobs nor --threads gives it
+const THREADS_ENV: &str = "CUPROOF_THREADS";
+
+/// The workers of setup trusted, batch-verify and batch-prove: `jobs` where the command has --jobs,
+/// else the global --threads, else $CUPROOF_THREADS, else every core; one worker runs everything
+/// on the main thread
+pub(super) fn workers(jobs: Option<usize>, threads: Option<usize>) -> Result<Workers, CliError> {
+    let threads = match (jobs.or(threads), env::var(THREADS_ENV)) {
+        (Some(n), _) => n,
+        (None, Ok(text)) if !text.trim().is_empty() => count(1, usize::MAX)(text.trim())
+            .map_err(|e| CliError::Usage(format!("{} must be a whole number of at least 1: {}", THREADS_ENV, e)))?,
+        (None, _) => Workers::available(),
+    };
+    Ok(Workers::new(threads))
+}
@@ -0,0 +1,237 @@
+//! batch-verify and batch-prove: many proofs over one set of params (see cuproof::batch)
+
+use std::collections::HashMap;
+use clap::Args;
+use rand::rngs::OsRng;
+use cuproof::batch::{discover_proofs, prove_batch, read_manifest, read_records, verify_batch, BatchFailure, BatchResult, BatchSummary, ProveJob, Record};
+use cuproof::range_proof::{proof_id, random_blinding};
+use cuproof::util::{bigint_to_hex, save_proof, write_atomically};
+use super::args::{count, parse_cli_bigint, workers, Radix, RadixArg};
+use super::files::{check_columns, file_only, load_params_arg, warn_if_insecure};
+use super::{CliError, CommandOutcome, Output};
+
+/// Verify every *.proof file in a directory, or the proofs a manifest lists (see cuproof::batch)
+#[derive(Args)]
+#[command(after_help = "Exits 0 only if every proof is VALID.\n\nExample:\n  cuproof batch-verify --jobs 8 params.bin proofs/")]
+pub(super) struct BatchVerifyArgs {
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    #[arg(value_name = "dir|manifest")]
+    input: String,
+    /// Same as --threads
+    #[arg(long, value_name = "N", value_parser = count(1, usize::MAX))]
+    jobs: Option<usize>,
+}
+
+/// Prove every row of a CSV or JSON-lines manifest, one proof file per row (see cuproof::batch)
+#[derive(Args)]
+#[command(after_help = format!("Every row is checked before any is proved; the rest are proved even if some fail. \
+    The status, proof id, commitment and error of each row go to {} in out_dir. \
+    Exits 0 only if every row was proved.\n\nExample:\n  cuproof batch-prove --jobs 8 --secrets /secure/values.csv params.bin rows.csv proofs/", BATCH_RESULTS))]
+pub(super) struct BatchProveArgs {
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Columns out (the proof file name), value, blinding (random when empty), a and b
+    #[arg(value_name = "manifest")]
+    manifest: String,
+    #[arg(value_name = "out_dir")]
+    out_dir: String,
+    /// Same as --threads
+    #[arg(long, value_name = "N", value_parser = count(1, usize::MAX))]
+    jobs: Option<usize>,
+    /// Take value and blinding from this file, with columns out, value and blinding, instead of the manifest
+    #[arg(long, value_name = "path")]
+    secrets: Option<String>,
+    /// Replace proof files that already exist
+    #[arg(long)]
+    force: bool,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+/// `threads` is the global --threads, which --jobs overrides
+pub(super) fn run_batch_verify(m: &BatchVerifyArgs, threads: Option<usize>, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let workers = workers(m.jobs, threads)?;
+    let params = load_params_arg(out, &m.params_path)?;
+    warn_if_insecure(&params);
+    let input_arg = file_only(&m.input, "proof directory or manifest")?;
+    let input = std::path::Path::new(input_arg);
+    let items = if input.is_dir() {
+        discover_proofs(input).map_err(|e| CliError::failed(format!("Failed to list {}", input_arg), e))?
+    } else {
+        read_manifest(input).map_err(|e| CliError::failed(format!("Failed to read manifest {}", input_arg), e))?
+    };
+    if items.is_empty() { return Err(CliError::Usage(format!("No proofs to verify in {}", input_arg))); }
+    let results = verify_batch(&params, &items, Some(&workers));
+    let summary = BatchSummary::of(&results);
+    print_batch_report(&results, &summary, out);
+    Ok(CommandOutcome::Batch(summary))
+}
+
+/// One line per proof, then the counts; the same as total, valid, invalid, unreadable and one
+/// results entry (path, status, error) per proof under --json
+fn print_batch_report(results: &[BatchResult], summary: &BatchSummary, out: &mut Output) {
+    let mut entries = Vec::new();
+    for r in results {
+        let (status, error) = match &r.result {
+            Ok(()) => ("valid", None),
+            Err(e @ BatchFailure::Verify(_)) => ("invalid", Some(e.to_string())),
+            Err(e @ BatchFailure::Load(_)) => ("unreadable", Some(e.to_string())),
+        };
+        match &error {
+            None => out.say(format_args!("VALID    {}", r.path.display())),
+            Some(e) => out.say(format_args!("{:<8} {}: {}", if status == "invalid" { "INVALID" } else { "ERROR" }, r.path.display(), e)),
+        }
+        entries.push(json_object! { "path" => r.path.display().to_string(), "status" => status, "error" => error });
+    }
+    out.say(format_args!("{} proofs: {} valid, {} invalid, {} unreadable", summary.total(), summary.valid, summary.invalid, summary.unreadable));
+    out.set("total", summary.total());
+    out.set("valid", summary.valid);
+    out.set("invalid", summary.invalid);
+    out.set("unreadable", summary.unreadable);
+    out.set("results", entries);
+}
+
+/// The results manifest batch-prove writes beside the proofs, one JSON object per row
+const BATCH_RESULTS: &str = "results.jsonl";
+
+/// The columns of batch-prove manifests, and of the --secrets files that take value and blinding out of them
+const MANIFEST_COLUMNS: [&str; 5] = ["out", "value", "blinding", "a", "b"];
+const SECRETS_COLUMNS: [&str; 3] = ["out", "value", "blinding"];
+
+/// `threads` is the global --threads, which --jobs overrides
+pub(super) fn run_batch_prove(m: &BatchProveArgs, threads: Option<usize>, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let workers = workers(m.jobs, threads)?;
+    let params = load_params_arg(out, &m.params_path)?;
+    warn_if_insecure(&params);
+    let manifest_path = file_only(&m.manifest, "manifest")?;
+    let out_dir = std::path::Path::new(file_only(&m.out_dir, "output directory")?);
+    let manifest = read_records(std::path::Path::new(manifest_path))
+        .map_err(|e| CliError::failed(format!("Failed to read manifest {}", manifest_path), e))?;
+    if manifest.is_empty() { return Err(CliError::Usage(format!("No rows to prove in {}", manifest_path))); }
+    let secrets = match m.secrets.as_deref() {
+        Some(path) => {
+            let records = read_records(std::path::Path::new(file_only(path, "secrets file")?))
+                .map_err(|e| CliError::failed(format!("Failed to read secrets file {}", path), e))?;
+            let mut by_out = HashMap::new();
+            for record in records {
+                check_columns(&record, &SECRETS_COLUMNS).map_err(|e| CliError::Usage(format!("{} line {}: {}", path, record.line, e)))?;
+                let Some(name) = record.get("out").map(str::to_string) else {
+                    return Err(CliError::Usage(format!("{} line {}: no out to match the manifest by", path, record.line)));
+                };
+                if let Some(earlier) = by_out.insert(name.clone(), record) {
+                    return Err(CliError::Usage(format!("{} line {}: {} already has secrets", path, earlier.line, name)));
+                }
+            }
+            Some(by_out)
+        }
+        None => None,
+    };
+
+    // every row is checked, and its output claimed, before anything is proved
+    let (mut claimed, mut batch) = (HashMap::new(), Vec::new());
+    let rows: Vec<(Option<String>, Result<(), String>)> = manifest.iter().map(|record| {
+        let name = record.get("out").map(str::to_string);
+        let job = batch_prove_row(record, secrets.as_ref(), m.radix.get()).and_then(|job| {
+            let name = name.as_deref().ok_or("no out column naming the proof file")?;
+            if !is_plain_file_name(name) || name == BATCH_RESULTS {
+                return Err(format!("out {} is not a file name for out_dir", name));
+            }
+            if let Some(line) = claimed.insert(name.to_string(), record.line) {
+                return Err(format!("out {} is also the proof of line {}", name, line));
+            }
+            if out_dir.join(name).exists() && !m.force {
+                return Err(format!("{} already exists; pass --force to replace it", out_dir.join(name).display()));
+            }
+            Ok(job)
+        });
+        (name, job.map(|job| batch.push(job)))
+    })
+    .collect();
+    std::fs::create_dir_all(out_dir).map_err(|e| CliError::failed(format!("Failed to create {}", out_dir.display()), e))?;
+
+    let mut proofs = prove_batch(&params, &batch, Some(&workers)).into_iter();
+    drop(batch);
+    let (mut bad_rows, mut unprovable, mut entries, mut lines) = (0, 0, Vec::new(), String::new());
+    for (record, (name, job)) in manifest.iter().zip(rows) {
+        let (status, proof, error) = match job.map(|_| proofs.next().expect("one result per valid row")) {
+            Err(reason) => ("invalid", None, Some(reason)),
+            Ok(Err(e)) => ("unprovable", None, Some(e.to_string())),
+            Ok(Ok(proof)) => {
+                let path = out_dir.join(name.as_deref().expect("valid rows name their proof"));
+                match path.to_str().map(|path| save_proof(path, &proof)) {
+                    Some(Ok(())) => ("proved", Some(proof), None),
+                    Some(Err(e)) => ("error", None, Some(format!("failed to write {}: {}", path.display(), e))),
+                    None => ("error", None, Some(format!("{} is not UTF-8", path.display()))),
+                }
+            }
+        };
+        match status {
+            "invalid" | "error" => bad_rows += 1,
+            "unprovable" => unprovable += 1,
+            _ => {}
+        }
+        let label = format!("line {} ({})", record.line, name.as_deref().unwrap_or("no out"));
+        match &error {
+            None => out.say(format_args!("PROVED   {}", label)),
+            Some(e) => out.say(format_args!("{:<8} {}: {}", status.to_uppercase(), label, e)),
+        }
+        let entry = json_object! {
+            "line" => record.line,
+            "out" => name,
+            "status" => status,
+            "proof_id" => proof.as_ref().map(proof_id),
+            "commitment" => proof.as_ref().map(|proof| bigint_to_hex(&proof.C)),
+            "error" => error,
+        };
+        lines.push_str(&format!("{}\n", entry));
+        entries.push(entry);
+    }
+    let results_path = out_dir.join(BATCH_RESULTS);
+    write_atomically(results_path.to_str().unwrap_or(BATCH_RESULTS), &Default::default(), |w| w.write_all(lines.as_bytes()))
+        .map_err(|e| CliError::failed(format!("Failed to write {}", results_path.display()), e))?;
+    let proved = entries.len() - bad_rows - unprovable;
+    out.say(format_args!("{} rows: {} proved, {} failed; results in {}", entries.len(), proved, bad_rows + unprovable, results_path.display()));
+    out.set("total", entries.len());
+    out.set("proved", proved);
+    out.set("failed", bad_rows + unprovable);
+    out.set("results_path", results_path.display().to_string());
+    out.set("results", entries);
+    Ok(CommandOutcome::BatchProve { bad_rows, unprovable })
+}
+
+/// The job of one manifest row, with value and blinding from `secrets` when given; a missing
+/// blinding is drawn at random
+/// - returns: the job, or why the row is unusable; the text of value and blinding is never quoted
+fn batch_prove_row(record: &Record, secrets: Option<&HashMap<String, Record>>, radix: Radix) -> Result<ProveJob, String> {
+    check_columns(record, &MANIFEST_COLUMNS)?;
+    let source = match secrets {
+        Some(secrets) => {
+            if record.get("value").is_some() || record.get("blinding").is_some() {
+                return Err("value and blinding belong in the secrets file when --secrets is given".to_string());
+            }
+            let name = record.get("out").ok_or("no out column to find the secrets by")?;
+            secrets.get(name).ok_or_else(|| format!("no secrets for {}", name))?
+        }
+        None => record,
+    };
+    let secret = |field: &str| source.get(field).map(|s| {
+        parse_cli_bigint(s, radix, false).map(|(n, _)| n).map_err(|_| format!("invalid {}: not a non-negative number (the text is secret, so not shown)", field))
+    });
+    let number = |field: &str| {
+        let s = record.get(field).ok_or_else(|| format!("no {}", field))?;
+        parse_cli_bigint(s, radix, true).map(|(n, _)| n).map_err(|e| format!("invalid {}: {}", field, e))
+    };
+    Ok(ProveJob {
+        v: secret("value").ok_or("no value")??,
+        r: secret("blinding").transpose()?.unwrap_or_else(|| random_blinding(&mut OsRng)),
+        a: number("a")?,
+        b: number("b")?,
+    })
+}
+
+/// A name that is a single path component, so joined to a directory it stays inside
+fn is_plain_file_name(name: &str) -> bool {
+    let path = std::path::Path::new(name);
+    path.file_name().is_some_and(|file| file == path.as_os_str()) && name != "." && name != ".."
+}
@@ -0,0 +1,101 @@
+//! `cuproof bench` and `cuproof benchmark`: timings on this machine (see cuproof::benchmark)
+
+use std::time::Duration;
+use clap::Args;
+use cuproof::benchmark::{bench_machine, benchmark_multiple_ranges, benchmark_setup_threads, print_benchmark_summary, MachineBenchConfig, Timings};
+use cuproof::setup::MAX_MODULUS_BITS;
+use super::args::count;
+use super::{CliError, CommandOutcome, Output};
+
+/// Time prove and verify on this machine over throwaway INSECURE params; writes no files
+#[derive(Args)]
+#[command(after_help = "Reports the mean, median and p95.\n\nExample:\n  cuproof bench --bits 3072 --iterations 20")]
+pub(super) struct BenchArgs {
+    /// Modulus size [default: 2048]
+    #[arg(long, value_name = "N", value_parser = count(256, MAX_MODULUS_BITS))]
+    bits: Option<usize>,
+    /// Range [0, 2^N - 1] [default: 64]
+    #[arg(long, value_name = "N", value_parser = count(1, 256))]
+    range_bits: Option<usize>,
+    /// Proofs to time [default: 10]
+    #[arg(long, value_name = "N", value_parser = count(1, 100_000))]
+    iterations: Option<usize>,
+    /// Also time one real trusted setup
+    #[arg(long)]
+    setup_sample: bool,
+}
+
+/// Tables of proof sizes and timings per range length, or of trusted setup per thread count
+#[derive(Args)]
+#[command(after_help = "Examples:\n  cuproof benchmark fast 8 16 32 64\n  cuproof benchmark trusted 8 16 32 64 128 256 512 1024\n  cuproof benchmark setup 1 4 8")]
+pub(super) struct BenchmarkArgs {
+    #[arg(value_name = "mode", value_parser = ["fast", "trusted", "setup"])]
+    mode: String,
+    #[arg(value_name = "range_lengths|thread_counts", num_args = 0.., value_parser = count(1, usize::MAX))]
+    counts: Vec<usize>,
+}
+
+pub(super) fn run_bench(m: &BenchArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let config = MachineBenchConfig {
+        bits: m.bits.unwrap_or(2048),
+        range_bits: m.range_bits.unwrap_or(64),
+        iterations: m.iterations.unwrap_or(10),
+        setup_sample: m.setup_sample,
+    };
+    tracing::info!("timing over throwaway INSECURE {}-bit params; nothing is written", config.bits);
+    let report = bench_machine(&config);
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    out.say(format_args!(
+        "{}-bit params, range [0, 2^{} - 1], {} iterations (params generated in {:.1} ms)",
+        config.bits, config.range_bits, config.iterations, ms(report.params_time)
+    ));
+    out.say(format_args!("{:<8} {:>12} {:>12} {:>12}", "", "mean ms", "median ms", "p95 ms"));
+    for (name, t) in [("prove", &report.prove), ("verify", &report.verify)] {
+        out.say(format_args!("{:<8} {:>12.2} {:>12.2} {:>12.2}", name, ms(t.mean), ms(t.median), ms(t.p95)));
+    }
+    out.say(format_args!("proof size: {} bytes ({} bytes as a proof file)", report.proof_size_bytes, report.encoded_size_bytes));
+    if let Some(sample) = report.setup_sample {
+        out.say(format_args!("trusted setup sample: {:.1} ms", ms(sample)));
+    }
+    if !report.all_verified { out.say("WARNING: some benchmark proofs did not verify"); }
+    let timings = |t: &Timings| json_object! { "mean_ms" => ms(t.mean), "median_ms" => ms(t.median), "p95_ms" => ms(t.p95) };
+    out.set("bits", config.bits);
+    out.set("range_bits", config.range_bits);
+    out.set("iterations", config.iterations);
+    out.set("params_ms", ms(report.params_time));
+    out.set("prove", timings(&report.prove));
+    out.set("verify", timings(&report.verify));
+    out.set("proof_size_bytes", report.proof_size_bytes);
+    out.set("encoded_size_bytes", report.encoded_size_bytes);
+    out.set("setup_sample_ms", report.setup_sample.map(ms));
+    out.set("all_verified", report.all_verified);
+    Ok(CommandOutcome::Done)
+}
+
+pub(super) fn run_benchmark(m: &BenchmarkArgs) -> Result<CommandOutcome, CliError> {
+    let mode = m.mode.as_str();
+    let counts = m.counts.clone();
+    if mode == "setup" {
+        let mut thread_counts = counts;
+        if thread_counts.is_empty() {
+            let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+            thread_counts = vec![1, cores];
+        }
+        println!("Benchmark trusted setup 2048 bit với số luồng {:?}", thread_counts);
+        benchmark_setup_threads(2048, &thread_counts, 3);
+        return Ok(CommandOutcome::Done);
+    }
+    let use_fast_setup = mode == "fast";
+
+    // Default range lengths if none specified
+    let range_lengths = if counts.is_empty() { vec![8, 16, 32, 64, 128, 256, 512, 1024] } else { counts };
+
+    println!("Bắt đầu benchmark Cuproof với {} độ dài khoảng", range_lengths.len());
+    println!("Chế độ setup: {}", if use_fast_setup { "fast" } else { "trusted" });
+    println!("Các độ dài khoảng: {:?}", range_lengths);
+    println!();
+
+    let results = benchmark_multiple_ranges(range_lengths, use_fast_setup);
+    print_benchmark_summary(&results);
+    Ok(CommandOutcome::Done)
+}
@@ -0,0 +1,149 @@
+//! `cuproof bundle`: a proof, its statement and its params fingerprint in one file
+
+use clap::{ArgGroup, Args, Subcommand};
+use cuproof::bundle::{BundleError, EmbeddedParams, ParamsDirectory, ProofBundle};
+use cuproof::range_proof::Statement;
+use cuproof::setup::FingerprintHex;
+use cuproof::util::{bigint_to_signed_hex, read_bundle, write_bundle, ReadLimits};
+use cuproof::verify::VerifyError;
+use super::files::{load_params_arg, read_proof_arg, warn_if_insecure, warn_if_legacy};
+use super::{usage, CliError, CommandOutcome, Json, Output};
+
+/// Put a proof, its statement and its params fingerprint in one file, or verify such a bundle
+#[derive(Args)]
+#[command(arg_required_else_help = false)]
+pub(super) struct BundleArgs {
+    #[command(subcommand)]
+    command: BundleCommand,
+}
+
+#[derive(Subcommand)]
+enum BundleCommand {
+    Create(BundleCreateArgs),
+    Verify(BundleVerifyArgs),
+}
+
+/// Bundle a proof with the statement it proves and the fingerprint of its params
+#[derive(Args)]
+#[command(after_help = "The proof must verify for the statement under the params, or nothing is written.\n\n\
+    Example:\n  cuproof bundle create --embed-params --meta purpose=kyc params.bin v.proof v.bundle")]
+struct BundleCreateArgs {
+    /// Params the proof was made under
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Proof written by prove
+    #[arg(value_name = "proof_path")]
+    proof_path: String,
+    /// Where to write the bundle
+    #[arg(value_name = "bundle_path|-")]
+    bundle_path: String,
+    /// The statement to bundle; by default the one the proof embeds
+    #[arg(long, value_name = "statement_path")]
+    statement: Option<String>,
+    /// Include the params themselves, for verifiers without a copy
+    #[arg(long)]
+    embed_params: bool,
+    /// A metadata entry; repeat for more
+    #[arg(long, value_name = "key=value", value_parser = parse_meta)]
+    meta: Vec<(String, String)>,
+}
+
+/// Verify a bundle under the params with its fingerprint
+#[derive(Args)]
+#[command(
+    group(ArgGroup::new("params-source").args(["params", "params_dir", "embedded"]).required(true)),
+    after_help = "Exits 0 and prints VALID when the proof verifies for the bundle's statement, 1 and INVALID when it \
+        does not, and 2 when no params with the bundle's fingerprint are found.\n\n\
+        Examples:\n  cuproof bundle verify --params params.bin v.bundle\n  cuproof bundle verify --params-dir trusted/ v.bundle"
+)]
+struct BundleVerifyArgs {
+    /// Bundle written by bundle create
+    #[arg(value_name = "bundle_path")]
+    bundle_path: String,
+    /// Params the verifier trusts
+    #[arg(long, value_name = "params_path")]
+    params: Option<String>,
+    /// A directory of params files; the one with the bundle's fingerprint is used
+    #[arg(long, value_name = "dir")]
+    params_dir: Option<String>,
+    /// Use the params in the bundle; their maker may know the factorization of n
+    #[arg(long)]
+    embedded: bool,
+}
+
+/// The value parser of `bundle create --meta`: key=value, the key without whitespace, the value on one line
+fn parse_meta(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() && !key.contains(char::is_whitespace) && !value.contains(['\n', '\r']) => {
+            Ok((key.to_string(), value.to_string()))
+        }
+        _ => Err(format!("'{}' is not key=value with a key without whitespace", s)),
+    }
+}
+
+pub(super) fn run_bundle(m: &BundleArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    match &m.command {
+        BundleCommand::Create(m) => {
+            let (params_path, proof_path, bundle_path) = (m.params_path.as_str(), m.proof_path.as_str(), m.bundle_path.as_str());
+            warn_if_legacy(params_path);
+            let params = load_params_arg(out, params_path)?;
+            warn_if_insecure(&params);
+            let (proof, _) = read_proof_arg(out, proof_path, Some(&params))?;
+            let statement = match m.statement.as_deref() {
+                Some(path) => {
+                    let bytes = out.read_input(path, "statement")?;
+                    Statement::from_bytes(&bytes, &ReadLimits::default()).map_err(|e| CliError::failed(format!("Failed to load statement {}", path), e))?
+                }
+                None => proof.statement.clone().ok_or_else(|| usage("the proof carries no statement; give one with --statement"))?,
+            };
+            let mut bundle = ProofBundle::new(&params, statement, proof);
+            if m.embed_params { bundle = bundle.with_params(params.clone()); }
+            for (key, value) in &m.meta {
+                bundle = bundle.with_metadata(key, value);
+            }
+            match bundle.verify(&params) {
+                Ok(()) => {}
+                Err(BundleError::Verify(e)) => return Err(CliError::failed("Refusing to bundle a proof that does not verify", e)),
+                Err(e) => unreachable!("the params resolve their own fingerprint: {}", e),
+            }
+            out.write_output(bundle_path, "bundle", true, |w| write_bundle(w, &bundle).map(|_| ()))?;
+            out.say(format_args!("Saved bundle to {} (params fingerprint {}{})", bundle_path, params.fingerprint_hex().short(),
+                if bundle.params.is_some() { ", params embedded" } else { "" }));
+            out.set("bundle_path", bundle_path);
+            out.set("params_fingerprint", params.fingerprint_hex().to_string());
+            out.set("embedded_params", bundle.params.is_some());
+            Ok(CommandOutcome::Done)
+        }
+        BundleCommand::Verify(m) => {
+            let bundle_path = m.bundle_path.as_str();
+            let bytes = out.read_input(bundle_path, "bundle")?;
+            let bundle = read_bundle(&mut bytes.as_slice(), &ReadLimits::default())
+                .map_err(|e| CliError::failed(format!("Failed to load bundle {}", bundle_path), e))?;
+            let fingerprint = FingerprintHex(bundle.params_fingerprint);
+            out.say(format_args!("Bundle {}: params fingerprint {}, range [{}, {}]", bundle_path, fingerprint.short(),
+                bigint_to_signed_hex(&bundle.statement.a), bigint_to_signed_hex(&bundle.statement.b)));
+            for (key, value) in &bundle.metadata { out.say(format_args!("  {}: {}", key, value)); }
+            out.set("params_fingerprint", fingerprint.to_string());
+            out.set("metadata", Json::Object(bundle.metadata.iter().map(|(key, value)| (key.clone(), Json::from(value.as_str()))).collect()));
+            let verified = if let Some(path) = m.params.as_deref() {
+                let params = load_params_arg(out, path)?;
+                warn_if_insecure(&params);
+                bundle.verify(&params)
+            } else if let Some(dir) = m.params_dir.as_deref() {
+                bundle.verify(&ParamsDirectory(dir))
+            } else {
+                tracing::warn!("Verifying {} under the params it embeds; whoever made it chose them", bundle_path);
+                if let Some(params) = &bundle.params { warn_if_insecure(params); }
+                bundle.verify(&EmbeddedParams)
+            };
+            match verified {
+                Ok(()) => Ok(CommandOutcome::Valid),
+                Err(BundleError::Verify(VerifyError::InvalidProof)) => Ok(CommandOutcome::Invalid(None)),
+                Err(BundleError::Verify(e)) => Ok(CommandOutcome::Invalid(Some(e))),
+                Err(BundleError::Resolver(e)) => Err(CliError::failed("Failed to look up the params", e)),
+                Err(e) => Err(CliError::failed("Failed to find the params", std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string()))
+                    .with("expected", fingerprint.to_string())),
+            }
+        }
+    }
+}
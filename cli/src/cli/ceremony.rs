@@ -0,0 +1,79 @@
+//! `cuproof ceremony`: multi-party re-randomization of params (see cuproof::ceremony)
+
+use clap::{Args, Subcommand};
+use cuproof::ceremony::{verify_chain, Contribution};
+use cuproof::util::{load_contribution_proof, save_contribution_proof};
+use super::args::OutputFormat;
+use super::files::{file_only, load_params_arg, warn_if_insecure, write_params_arg};
+use super::{usage, verdict, CliError, CommandOutcome, Output};
+
+/// Contribute to, or verify, a multi-party params ceremony
+#[derive(Args)]
+#[command(arg_required_else_help = false)]
+pub(super) struct CeremonyArgs {
+    #[command(subcommand)]
+    command: CeremonyCommand,
+}
+
+#[derive(Subcommand)]
+enum CeremonyCommand {
+    Contribute(ContributeArgs),
+    Verify(CeremonyVerifyArgs),
+}
+
+/// Re-randomize params, writing the new params and a proof of the contribution
+#[derive(Args)]
+struct ContributeArgs {
+    #[arg(value_name = "prev_params")]
+    prev_params: String,
+    #[arg(value_name = "out_params")]
+    out_params: String,
+    #[arg(value_name = "out_proof")]
+    out_proof: String,
+    #[arg(value_name = "entropy")]
+    entropy: Option<String>,
+}
+
+/// Check a chain of contributions from the initial params
+#[derive(Args)]
+struct CeremonyVerifyArgs {
+    #[arg(value_name = "initial_params")]
+    initial_params: String,
+    #[arg(value_names = ["params", "proof"], num_args = 0..)]
+    chain: Vec<String>,
+}
+
+pub(super) fn run_ceremony(m: &CeremonyArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    match &m.command {
+        CeremonyCommand::Contribute(m) => {
+            let out_params = m.out_params.as_str();
+            let out_proof = file_only(&m.out_proof, "contribution proof")?;
+            let prev = load_params_arg(out, &m.prev_params)?;
+            warn_if_insecure(&prev);
+            let entropy = m.entropy.as_deref().map(str::as_bytes).unwrap_or(&[]);
+            let (params, proof) = Contribution::contribute(&prev, entropy);
+            write_params_arg(out, out_params, &params, OutputFormat::Bin, None)?;
+            save_contribution_proof(out_proof, &proof).map_err(|e| CliError::failed("Failed to save contribution", e))?;
+            out.say(format_args!("Saved contributed params to {} and proof to {}", out_params, out_proof));
+            out.set("params_path", out_params);
+            out.set("proof_path", out_proof);
+            out.set("fingerprint", params.fingerprint_hex().to_string());
+            Ok(CommandOutcome::Done)
+        }
+        CeremonyCommand::Verify(m) => {
+            if !m.chain.len().is_multiple_of(2) {
+                return Err(usage("ceremony verify takes <params> <proof> pairs after <initial_params>"));
+            }
+            let initial = load_params_arg(out, &m.initial_params)?;
+            warn_if_insecure(&initial);
+            let mut chain = Vec::new();
+            for pair in m.chain.chunks(2) {
+                let params = load_params_arg(out, &pair[0])?;
+                warn_if_insecure(&params);
+                let proof = load_contribution_proof(file_only(&pair[1], "contribution proof")?).map_err(|e| CliError::failed(format!("Failed to load proof {}", pair[1]), e))?;
+                chain.push(Contribution::new(params, proof));
+            }
+            Ok(verdict(verify_chain(&initial, &chain)))
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! `cuproof commit`: commit to v, saving the commitment and optionally its blinding
+
+use clap::{ArgGroup, Args};
+use num_bigint::BigInt;
+use rand::rngs::OsRng;
+use cuproof::commitment::commit;
+use cuproof::range_proof::random_blinding;
+use cuproof::secret::Secret;
+use cuproof::setup::Params;
+use cuproof::util::{bigint_to_hex, write_blinding, write_commitment};
+use super::args::{number, read_secret, BlindingSecret, RadixArg, SecretSource, ValueSecret};
+use super::files::{load_params_arg, warn_blinding_file, warn_if_insecure, warn_if_legacy, write_secret_output};
+use super::{CliError, CommandOutcome, Output};
+
+/// Commit to v, saving the commitment and printing it
+#[derive(Args)]
+#[command(
+    allow_negative_numbers = true,
+    group(ArgGroup::new("value").args(["v", "value_stdin", "value_env"]).required(true)),
+    group(ArgGroup::new("blinding-source").args(["blinding", "blinding_stdin", "blinding_env"])),
+    after_help = "Examples:\n  cuproof commit params.bin --value-stdin --out c.bin --blinding-out r.bin\n  \
+        CUPROOF_PASSPHRASE=... cuproof commit params.bin --value-env V --out c.bin --blinding-out r.bin --encrypt"
+)]
+pub(super) struct CommitArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// The value; seen by shell history and ps, prefer --value-stdin or --value-env
+    #[arg(value_name = "v", value_parser = number(false))]
+    v: Option<String>,
+    #[command(flatten)]
+    value: ValueSecret,
+    /// The blinding [default: drawn from the OS CSPRNG]
+    #[arg(long, value_name = "r", value_parser = number(false))]
+    blinding: Option<String>,
+    #[command(flatten)]
+    blinding_secret: BlindingSecret,
+    #[arg(long, value_name = "commitment_path")]
+    out: String,
+    /// Save the blinding, which opens the commitment
+    #[arg(long, value_name = "r_path")]
+    blinding_out: Option<String>,
+    /// Encrypt the blinding file under a passphrase: $CUPROOF_PASSPHRASE, else typed on the terminal
+    #[arg(long, requires = "blinding_out")]
+    encrypt: bool,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+pub(super) fn run_commit(m: &CommitArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let radix = m.radix.get();
+    let value_source = m.value.source(m.v.clone().map(SecretSource::Arg)).expect("the value group is required");
+    let blinding_source = m.blinding_secret.source(m.blinding.clone().map(SecretSource::Arg));
+    let v = read_secret(out, "v", value_source, radix, false)?;
+    let blinding = blinding_source.map(|source| read_secret(out, "blinding", source, radix, false)).transpose()?;
+    let (params_path, commitment_path, blinding_out) = (m.params_path.as_str(), m.out.as_str(), m.blinding_out.as_deref());
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let commitment = commit_to_files(out, &params, v.expose(), blinding, commitment_path, blinding_out, m.encrypt)?;
+    if let Some(path) = blinding_out { warn_blinding_file(path); }
+    tracing::info!("Saved commitment to {}", commitment_path);
+    out.say(bigint_to_hex(&commitment));
+    out.set("commitment", bigint_to_hex(&commitment));
+    out.set("commitment_path", commitment_path);
+    out.set("blinding_path", blinding_out);
+    Ok(CommandOutcome::Done)
+}
+
+/// `commit`: commit to v under `params` with `blinding`, or a fresh one from the OS CSPRNG, and save
+/// the commitment to `commitment_path` and the blinding to `blinding_out` if given (`-`: stdout),
+/// encrypted when `encrypt`
+/// - returns: the commitment
+pub(super) fn commit_to_files(out: &mut Output, params: &Params, v: &BigInt, blinding: Option<Secret<BigInt>>, commitment_path: &str, blinding_out: Option<&str>, encrypt: bool) -> Result<BigInt, CliError> {
+    let r = blinding.unwrap_or_else(|| Secret::new(random_blinding(&mut OsRng)));
+    let commitment = commit(params, v, r.expose());
+    out.write_output(commitment_path, "commitment", true, |w| write_commitment(w, &commitment, params).map(|_| ()))?;
+    if let Some(path) = blinding_out { write_secret_output(out, path, "blinding", encrypt, |w| write_blinding(w, r.expose()).map(|_| ()))?; }
+    Ok(commitment)
+}
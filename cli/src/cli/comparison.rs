@@ -0,0 +1,135 @@
+//! prove-lt and verify-lt: one committed value less than another (see cuproof::comparison)
+
+use std::time::Instant;
+use clap::{ArgGroup, Args};
+use cuproof::commitment::commit;
+use cuproof::comparison::{prove_less_than, verify_less_than, MAX_COMPARISON_BITS};
+use cuproof::range_proof::ProveError;
+use cuproof::util::{read_comparison_proof, write_comparison_proof, ReadLimits};
+use cuproof::verify::VerifyError;
+use super::args::{count, read_secret, secret_source, RadixArg, SecretSource};
+use super::files::{load_commitment_arg, load_params_arg, say_params, warn_if_insecure, warn_if_legacy};
+use super::{CliError, CommandOutcome, Output};
+
+/// Prove that the value of one commitment is less than the value of another, revealing neither
+#[derive(Args)]
+#[command(
+    group(ArgGroup::new("value1").args(["value1_stdin", "value1_env"]).required(true)),
+    group(ArgGroup::new("value2").args(["value2_stdin", "value2_env"]).required(true)),
+    after_help = "Values read from stdin come one per line: v1, then v2. Exits 5 with prove.not_less_than when v1 >= v2 \
+        and prove.difference_too_wide when v2 - v1 does not fit in --bits.\n\n\
+        Example:\n  cuproof prove-lt --value1-env BID --value2-env BUDGET --blinding1-in r1.bin --blinding2-in r2.bin \\\n    \
+        params.bin bid.c budget.c lt.proof"
+)]
+pub(super) struct ProveLtArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Commitment to the smaller value v1, written by commit
+    #[arg(value_name = "c1_path")]
+    c1_path: String,
+    /// Commitment to the larger value v2, written by commit
+    #[arg(value_name = "c2_path")]
+    c2_path: String,
+    /// Where to write the comparison proof
+    #[arg(value_name = "proof_path|-")]
+    proof_path: String,
+    /// Read the value v1 from the next line of stdin
+    #[arg(long)]
+    value1_stdin: bool,
+    /// Read the value v1 from the environment variable VAR
+    #[arg(long, value_name = "VAR")]
+    value1_env: Option<String>,
+    /// Read the value v2 from the next line of stdin
+    #[arg(long)]
+    value2_stdin: bool,
+    /// Read the value v2 from the environment variable VAR
+    #[arg(long, value_name = "VAR")]
+    value2_env: Option<String>,
+    /// The blinding of the first commitment, written by commit --blinding-out
+    #[arg(long, value_name = "r1_path")]
+    blinding1_in: String,
+    /// The blinding of the second commitment, written by commit --blinding-out
+    #[arg(long, value_name = "r2_path")]
+    blinding2_in: String,
+    /// Width the difference v2 - v1 must fit in; the proof shows 1 <= v2 - v1 < 2^N
+    #[arg(long, value_name = "N", value_parser = count(1, MAX_COMPARISON_BITS as usize), default_value = "64")]
+    bits: usize,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+/// Verify that the value of one commitment is less than the value of another
+#[derive(Args)]
+#[command(after_help = "The commitments are the verifier's own; swapped, or for other values, the proof is INVALID.\n\n\
+    Example:\n  cuproof verify-lt params.bin bid.c budget.c lt.proof")]
+pub(super) struct VerifyLtArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Commitment to the smaller value v1, written by commit
+    #[arg(value_name = "c1_path")]
+    c1_path: String,
+    /// Commitment to the larger value v2, written by commit
+    #[arg(value_name = "c2_path")]
+    c2_path: String,
+    /// Comparison proof written by prove-lt
+    #[arg(value_name = "proof_path")]
+    proof_path: String,
+}
+
+pub(super) fn run_prove_lt(m: &ProveLtArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let radix = m.radix.get();
+    let value1_source = secret_source(None, m.value1_stdin, m.value1_env.as_deref()).expect("the value1 group is required");
+    let value2_source = secret_source(None, m.value2_stdin, m.value2_env.as_deref()).expect("the value2 group is required");
+    let (params_path, c1_path, c2_path, proof_path) = (m.params_path.as_str(), m.c1_path.as_str(), m.c2_path.as_str(), m.proof_path.as_str());
+    let bits = m.bits as u32;
+    let v1 = read_secret(out, "v1", value1_source, radix, false)?;
+    let v2 = read_secret(out, "v2", value2_source, radix, false)?;
+    let r1 = read_secret(out, "blinding1", SecretSource::File(m.blinding1_in.clone()), radix, false)?;
+    let r2 = read_secret(out, "blinding2", SecretSource::File(m.blinding2_in.clone()), radix, false)?;
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    for (path, v, r) in [(c1_path, &v1, &r1), (c2_path, &v2, &r2)] {
+        let commitment = load_commitment_arg(out, path, &params)?;
+        if commit(&params, v.expose(), r.expose()) != commitment {
+            return Err(CliError::failed("Failed to prove", ProveError::CommitmentMismatch).with("path", path));
+        }
+    }
+    let started = Instant::now();
+    let proof = prove_less_than(v1.expose(), r1.expose(), v2.expose(), r2.expose(), bits, &params)
+        .map_err(|e| CliError::failed("Failed to prove", e))?;
+    let prove_ms = started.elapsed().as_secs_f64() * 1000.0;
+    out.write_output(proof_path, "proof", true, |w| write_comparison_proof(w, &proof).map(|_| ()))?;
+    if proof_path != "-" { out.say(format_args!("Saved comparison proof to {}", proof_path)); }
+    say_params(out, params_path, &params);
+    out.set("proof_path", proof_path);
+    out.set("bits", bits);
+    out.set("prove_ms", prove_ms);
+    Ok(CommandOutcome::Done)
+}
+
+pub(super) fn run_verify_lt(m: &VerifyLtArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (params_path, c1_path, c2_path, proof_path) = (m.params_path.as_str(), m.c1_path.as_str(), m.c2_path.as_str(), m.proof_path.as_str());
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let c1 = load_commitment_arg(out, c1_path, &params)?;
+    let c2 = load_commitment_arg(out, c2_path, &params)?;
+    let bytes = out.read_input(proof_path, "proof")?;
+    let proof = read_comparison_proof(&mut bytes.as_slice(), &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load comparison proof {}", proof_path), e))?;
+    say_params(out, params_path, &params);
+    out.say(format_args!("Claim: {} commits to less than {}, by less than 2^{}", c1_path, c2_path, proof.bits));
+    out.set("proof", json_object! {
+        "size" => bytes.len(),
+        "bits" => proof.bits,
+        "epoch" => proof.proof.epoch,
+    });
+    Ok(match verify_less_than(&c1, &c2, &proof, &params) {
+        Ok(()) => CommandOutcome::Valid,
+        Err(VerifyError::InvalidProof) => CommandOutcome::Invalid(None),
+        Err(e) => CommandOutcome::Invalid(Some(e)),
+    })
+}
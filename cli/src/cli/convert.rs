@@ -0,0 +1,208 @@
+//! `cuproof convert`: params, proofs and statements in another encoding, or legacy ones in the current format
+
+use std::fmt;
+use clap::Args;
+use cuproof::error::SerializationError;
+use cuproof::inspect::{inspect, FileInfo, InspectedKind};
+use cuproof::range_proof::{Cuproof, Statement};
+use cuproof::setup::Params;
+use cuproof::util::{container_version, read_params, write_params, write_proof, FileKind, ReadLimits, FILE_FORMAT_VERSION};
+use super::files::{decode_proof_arg, load_params_arg, warn_if_other_params};
+use super::{CliError, CommandOutcome, Output};
+#[cfg(feature = "json")]
+use super::usage;
+
+/// Rewrite a params, proof or statement file in another encoding, or a legacy one in the current format
+#[derive(Args)]
+#[command(after_help = "Every input is parsed with the checks of the loaders before anything is written.\n\nExamples:\n  cuproof convert --upgrade params.txt params.bin\n  cuproof convert --to armor --params params.bin v.proof v.asc\n  cuproof convert --to json --params params.bin --allow-lossy v.proof v.json")]
+pub(super) struct ConvertArgs {
+    /// Binary, JSON, CBOR, armored or legacy text; the encoding is detected
+    #[arg(value_name = "in_path")]
+    in_path: String,
+    #[arg(value_name = "out_path")]
+    out_path: String,
+    /// Encoding of the written file (json, cbor and armor need their features; armor is for proofs only)
+    #[arg(long, value_name = "bin|json|cbor|armor", default_value = "bin", value_parser = parse_encoding)]
+    to: Encoding,
+    /// Migrate a legacy text file to the current format version
+    #[arg(long)]
+    upgrade: bool,
+    /// Drop what the output encoding cannot hold: a proof's statement or the metadata of params in JSON and CBOR
+    #[arg(long)]
+    allow_lossy: bool,
+    /// Params the proof was made under: the modulus size of JSON proofs, the Params-Fingerprint of armored ones
+    #[arg(long, value_name = "params_path")]
+    params: Option<String>,
+}
+
+pub(super) fn run_convert(m: &ConvertArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (in_path, out_path) = (m.in_path.as_str(), m.out_path.as_str());
+    let to = m.to;
+    let params = m.params.as_deref().map(|path| load_params_arg(out, path)).transpose()?;
+    let bytes = out.read_input(in_path, "input")?;
+    let info = inspect(&bytes);
+    // inspect reports legacy text as version 0 whatever the kind
+    let legacy = info.version == Some(0);
+    if legacy && !m.upgrade {
+        return Err(CliError::Usage(format!(
+            "{} is a {} file in the legacy text format; pass --upgrade to migrate it to format version {}", in_path, info.kind, FILE_FORMAT_VERSION
+        )));
+    }
+    let (document, from) = decode_document(&bytes, &info, params.as_ref())?;
+    if let (Document::Proof(proof), Some(params)) = (&document, &params) { warn_if_other_params(proof, params, m.params.as_deref().unwrap_or("-")); }
+    let dropped = dropped_by(&document, to);
+    if let Some(dropped) = dropped
+        && !m.allow_lossy
+    {
+        return Err(CliError::Usage(format!("the {} encoding has no place for the {} of this {}; pass --allow-lossy to drop it", to, dropped, document.kind())));
+    }
+    let encoded = encode_document(&document, to, params.as_ref())?;
+    out.write_output(out_path, document.kind(), matches!(to, Encoding::Bin | Encoding::Cbor), |w| w.write_all(&encoded))?;
+    let version = match (&document, to) {
+        (Document::Params(_), Encoding::Bin) => container_version(&encoded, FileKind::Params),
+        (Document::Proof(_), Encoding::Bin) => container_version(&encoded, FileKind::Proof),
+        _ => None,
+    };
+    let kind = document.kind();
+    match (legacy, version) {
+        (true, Some(version)) => out.say(format_args!("Migrated legacy text {} file to format version {} at {}", kind, version, out_path)),
+        (_, Some(version)) => out.say(format_args!("Saved {} file in format version {} to {}", kind, version, out_path)),
+        (_, None) => out.say(format_args!("Converted {} file from {} to {} at {}", kind, from, to, out_path)),
+    }
+    if let Some(dropped) = dropped { tracing::warn!("the {} of the {} was dropped (--allow-lossy)", dropped, kind); }
+    out.set("kind", kind);
+    out.set("path", out_path);
+    out.set("from", from);
+    out.set("to", to.to_string());
+    out.set("version", version);
+    out.set("legacy", legacy);
+    out.set("dropped", dropped);
+    Ok(CommandOutcome::Done)
+}
+
+/// Encoding of the files convert writes; it tells that of its input from the first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    /// the checksummed containers of save_params and save_proof, or Statement::to_bytes
+    Bin,
+    Json,
+    Cbor,
+    Armor,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Bin => "bin",
+            Encoding::Json => "json",
+            Encoding::Cbor => "cbor",
+            Encoding::Armor => "armor",
+        })
+    }
+}
+
+/// The value parser of `convert --to bin|json|cbor|armor`
+fn parse_encoding(s: &str) -> Result<Encoding, String> {
+    match s {
+        "bin" => Ok(Encoding::Bin),
+        "json" if cfg!(feature = "json") => Ok(Encoding::Json),
+        "cbor" if cfg!(feature = "cbor") => Ok(Encoding::Cbor),
+        "armor" if cfg!(feature = "armor") => Ok(Encoding::Armor),
+        "json" | "cbor" | "armor" => Err(format!("--to {} requires the {} feature", s, s)),
+        other => Err(format!("unknown encoding {}; use bin, json, cbor or armor", other)),
+    }
+}
+
+/// A file convert reads: params, a proof or a statement
+enum Document {
+    Params(Params),
+    Proof(Box<Cuproof>),
+    Statement(Statement),
+}
+
+impl Document {
+    fn kind(&self) -> &'static str {
+        match self {
+            Document::Params(_) => "params",
+            Document::Proof(_) => "proof",
+            Document::Statement(_) => "statement",
+        }
+    }
+}
+
+/// Decode `bytes`, which inspect described as `info`, with the checks of the loaders: params are
+/// validated, proofs and statements pass the load_proof checks, and armored proofs must name
+/// `params` when given. CBOR, which inspect does not recognise, is tried on unknown files
+/// - returns: the document and the encoding it was in
+fn decode_document(bytes: &[u8], info: &FileInfo, params: Option<&Params>) -> Result<(Document, &'static str), CliError> {
+    let from = match info.encoding {
+        "container" | "statement section" => "bin",
+        encoding => encoding,
+    };
+    let document = match info.kind {
+        InspectedKind::Params => read_params(&mut &bytes[..], &ReadLimits::default())
+            .map(Document::Params)
+            .map_err(|e| CliError::failed("Failed to load params", e))?,
+        InspectedKind::Proof => Document::Proof(Box::new(decode_proof_arg(bytes, params)?)),
+        InspectedKind::Statement => Statement::from_bytes(bytes, &ReadLimits::default())
+            .map(Document::Statement)
+            .map_err(|e| CliError::failed("Failed to load statement", e))?,
+        #[cfg(feature = "cbor")]
+        InspectedKind::Unknown if info.encoding == "unknown" => {
+            let document = match (Cuproof::from_cbor(bytes), Params::from_cbor(bytes)) {
+                (Ok(proof), _) => Document::Proof(Box::new(proof)),
+                (_, Ok(params)) => Document::Params(params),
+                (Err(proof_err), Err(params_err)) => {
+                    return Err(CliError::failed(format!("Not CBOR params ({}) nor a CBOR proof", params_err), proof_err));
+                }
+            };
+            return Ok((document, "cbor"));
+        }
+        InspectedKind::Unknown => {
+            let reason = info.stopped.as_ref().map_or_else(String::new, |s| format!(": {}", s.reason));
+            return Err(CliError::failed("Failed to load input", SerializationError::Invalid(format!("not a params, proof or statement file{}", reason))));
+        }
+        other => return Err(CliError::Usage(format!("convert reads params, proofs and statements, not {} files", other))),
+    };
+    Ok((document, from))
+}
+
+/// What writing `document` as `to` would leave out: JSON and CBOR have no place for the statement
+/// of a proof nor for the metadata section of params
+fn dropped_by(document: &Document, to: Encoding) -> Option<&'static str> {
+    match (document, to) {
+        (Document::Proof(proof), Encoding::Json | Encoding::Cbor) if proof.statement.is_some() => Some("statement"),
+        (Document::Params(params), Encoding::Json | Encoding::Cbor) if params.metadata().is_some() => Some("metadata section"),
+        _ => None,
+    }
+}
+
+/// The bytes of `document` as `to`; `params` give a JSON proof its modulus size and an armored one
+/// its Params-Fingerprint header
+fn encode_document(document: &Document, to: Encoding, params: Option<&Params>) -> Result<Vec<u8>, CliError> {
+    let failed = |e: std::io::Error| CliError::failed(format!("Failed to encode the {}", document.kind()), e);
+    let mut bytes = Vec::new();
+    match (document, to) {
+        (Document::Params(p), Encoding::Bin) => { write_params(&mut bytes, p).map_err(failed)?; }
+        (Document::Proof(proof), Encoding::Bin) => { write_proof(&mut bytes, proof).map_err(failed)?; }
+        (Document::Statement(statement), Encoding::Bin) => bytes = statement.to_bytes(),
+        #[cfg(feature = "json")]
+        (Document::Params(p), Encoding::Json) => { cuproof::util::write_params_json(&mut bytes, p).map_err(failed)?; }
+        #[cfg(feature = "json")]
+        (Document::Proof(proof), Encoding::Json) => {
+            let params = params.ok_or_else(|| usage("JSON proofs record the modulus size; give the params with --params"))?;
+            cuproof::util::write_proof_json(&mut bytes, proof, params.bits()).map_err(failed)?;
+        }
+        #[cfg(feature = "cbor")]
+        (Document::Params(p), Encoding::Cbor) => bytes = p.to_cbor(),
+        #[cfg(feature = "cbor")]
+        (Document::Proof(proof), Encoding::Cbor) => bytes = proof.to_cbor(),
+        #[cfg(feature = "armor")]
+        (Document::Proof(proof), Encoding::Armor) => {
+            bytes = params.map_or_else(|| proof.to_armored(), |params| proof.to_armored_with_params(params)).into_bytes();
+        }
+        (document, to) => return Err(CliError::Usage(format!("{} files have no {} encoding", document.kind(), to))),
+    }
+    let _ = params;
+    Ok(bytes)
+}
@@ -0,0 +1,100 @@
+//! `cuproof dump`: every field of a proof in hex, verified or not
+
+use clap::Args;
+use num_bigint::BigInt;
+use sha2::{Digest, Sha256};
+use cuproof::range_proof::Cuproof;
+use cuproof::verify::proof_challenges;
+use super::files::{load_params_arg, print_statement, read_proof_arg};
+use super::{CliError, CommandOutcome, Output};
+
+/// Print every field of a proof in hex with its byte length, whether or not the proof verifies
+#[derive(Args)]
+#[command(after_help = "Without --params, group elements are padded to the widest of them.\n\nExamples:\n  cuproof dump --params params.bin v.proof\n  cuproof dump --field C v.proof")]
+pub(super) struct DumpArgs {
+    #[arg(value_name = "proof_path")]
+    proof_path: String,
+    /// Print only this field's hex: A, S, T1, ..., L[i] or R[i] (L and R for every round), ipp_a, ipp_b, proof_id, or with --params y, z, x, u[i]
+    #[arg(long, value_name = "NAME")]
+    field: Option<String>,
+    /// Pad group elements to the modulus width and recompute the Fiat-Shamir challenges
+    #[arg(long, value_name = "params_path")]
+    params: Option<String>,
+}
+
+pub(super) fn run_dump(m: &DumpArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let params = m.params.as_deref().map(|path| load_params_arg(out, path)).transpose()?;
+    let (proof, _) = read_proof_arg(out, &m.proof_path, params.as_ref())?;
+    let proof_id = BigInt::from_bytes_be(num_bigint::Sign::Plus, &Sha256::digest(proof.to_bytes()));
+    let fields = dump_fields(&proof);
+    let width = match &params {
+        Some(params) => params.n().bits().div_ceil(8) as usize,
+        None => fields.iter().filter(|f| f.2).map(|f| f.1.bits().div_ceil(8) as usize).max().unwrap_or(0),
+    };
+    let mut rows: Vec<(String, &BigInt, usize)> = vec![("proof_id".to_string(), &proof_id, 32)];
+    rows.extend(fields.into_iter().map(|(name, value, element)| (name, value, if element { width } else { 0 })));
+    let challenges = params.as_ref().map(|params| proof_challenges(&proof, params));
+    let mut challenge_rows = Vec::new();
+    if let Some(c) = &challenges {
+        challenge_rows.extend([("y".to_string(), &c.y, 0), ("z".to_string(), &c.z, 0), ("x".to_string(), &c.x, 0)]);
+        challenge_rows.extend(c.ipp.iter().enumerate().map(|(i, u)| (format!("u[{}]", i), u, 0)));
+    }
+    let entry = |(name, value, width): &(String, &BigInt, usize)| {
+        let (hex, bytes) = padded_hex(value, *width);
+        json_object! { "name" => name.as_str(), "bytes" => bytes, "hex" => hex }
+    };
+
+    if let Some(wanted) = m.field.as_deref() {
+        let round = |name: &str| name.strip_suffix(']').and_then(|name| name.split_once('[')).map(|(series, _)| series.to_string());
+        let matches: Vec<_> = rows.iter().chain(&challenge_rows)
+            .filter(|(name, _, _)| name == wanted || round(name).as_deref() == Some(wanted))
+            .collect();
+        if matches.is_empty() {
+            let hint = if params.is_none() && (matches!(wanted, "y" | "z" | "x" | "u") || wanted.starts_with("u[")) { "; challenges need --params" } else { "" };
+            return Err(CliError::Usage(format!("No field {} in the proof{}", wanted, hint)));
+        }
+        for row in &matches { out.say(padded_hex(row.1, row.2).0); }
+        out.set("fields", matches.into_iter().map(entry).collect::<Vec<_>>());
+        return Ok(CommandOutcome::Done);
+    }
+    let line = |(name, value, width): &(String, &BigInt, usize)| {
+        let (hex, bytes) = padded_hex(value, *width);
+        format!("{:<8} {:>4} bytes  {}", name, bytes, hex)
+    };
+    out.say(format_args!("{:<8} {}", "epoch", proof.epoch));
+    for row in &rows { out.say(line(row)); }
+    print_statement(&proof, out);
+    if let Some(params) = &params {
+        out.say(format_args!("Challenges (params {}):", params.fingerprint_hex()));
+        for row in &challenge_rows { out.say(line(row)); }
+    }
+    out.set("epoch", proof.epoch);
+    out.set("fields", rows.iter().map(entry).collect::<Vec<_>>());
+    out.set("challenges", challenges.as_ref().map(|_| challenge_rows.iter().map(entry).collect::<Vec<_>>()));
+    Ok(CommandOutcome::Done)
+}
+
+/// The integers of a proof in Cuproof order, the IPP rounds as L[i] and R[i] in stored order:
+/// name, value and whether it is a group element rather than a scalar
+fn dump_fields(proof: &Cuproof) -> Vec<(String, &BigInt, bool)> {
+    let element = |name: &str, value| (name.to_string(), value, true);
+    let scalar = |name: &str, value| (name.to_string(), value, false);
+    let mut fields = vec![element("A", &proof.A), element("S", &proof.S), element("T1", &proof.T1), element("T2", &proof.T2)];
+    fields.extend([scalar("tau_x", &proof.tau_x), scalar("mu", &proof.mu), scalar("t_hat", &proof.t_hat)]);
+    fields.extend([element("C", &proof.C), element("C_v1", &proof.C_v1), element("C_v2", &proof.C_v2)]);
+    fields.extend([scalar("t0", &proof.t0), scalar("t1", &proof.t1), scalar("t2", &proof.t2), scalar("tau1", &proof.tau1), scalar("tau2", &proof.tau2)]);
+    for (i, (l, r)) in proof.ipp_proof.L.iter().zip(&proof.ipp_proof.R).enumerate() {
+        fields.extend([(format!("L[{}]", i), l, true), (format!("R[{}]", i), r, true)]);
+    }
+    fields.extend([scalar("ipp_a", &proof.ipp_proof.a), scalar("ipp_b", &proof.ipp_proof.b)]);
+    fields
+}
+
+/// Big-endian hex of |x| zero-padded to at least `width` bytes (and to whole bytes), with a leading
+/// `-` for negative x; returns the hex and its byte length
+fn padded_hex(x: &BigInt, width: usize) -> (String, usize) {
+    let (sign, magnitude) = x.to_bytes_be();
+    let bytes = if magnitude == [0] { 1 } else { magnitude.len() }.max(width);
+    let hex = format!("{}{:0>digits$}", if sign == num_bigint::Sign::Minus { "-" } else { "" }, hex::encode(&magnitude), digits = 2 * bytes);
+    (hex, bytes)
+}
@@ -0,0 +1,33 @@
+//! `cuproof encrypt`: a secret file, e.g. the manifest of prove-openings, encrypted under a passphrase
+//! (see cuproof::encrypted)
+
+use clap::Args;
+use cuproof::encrypted::is_encrypted;
+use cuproof::secret::Secret;
+use super::files::write_secret_output;
+use super::{CliError, CommandOutcome, Output};
+
+/// Encrypt a secret file under a passphrase, for the commands that read only encrypted secrets
+#[derive(Args)]
+#[command(after_help = "The passphrase is $CUPROOF_PASSPHRASE, else typed twice on the terminal. Commands given the encrypted \
+    file ask for the same passphrase; remove the file in the clear once it is encrypted.\n\n\
+    Example:\n  cuproof encrypt openings.csv openings.enc")]
+pub(super) struct EncryptArgs {
+    /// The file in the clear, or - for stdin
+    #[arg(value_name = "in_path|-")]
+    in_path: String,
+    /// Where to write the encrypted file
+    #[arg(value_name = "out_path")]
+    out_path: String,
+}
+
+pub(super) fn run_encrypt(m: &EncryptArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (in_path, out_path) = (m.in_path.as_str(), m.out_path.as_str());
+    let bytes = Secret::new(out.read_input(in_path, "secret file")?);
+    if is_encrypted(bytes.expose()) { return Err(CliError::Usage(format!("{} is already encrypted", in_path))); }
+    write_secret_output(out, out_path, "secret file", true, |w| w.write_all(bytes.expose()))?;
+    out.say(format_args!("Saved encrypted file to {}", out_path));
+    out.set("path", out_path);
+    out.set("bytes", bytes.expose().len());
+    Ok(CommandOutcome::Done)
+}
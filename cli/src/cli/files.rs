@@ -0,0 +1,248 @@
+//! The files commands read and write through their path arguments: params, proofs (compressed or
+//! not) and commitments, secrets encrypted or not, `-` for stdin or stdout, and the warnings that go with them
+
+use std::io::Write;
+use num_bigint::BigInt;
+use rand::rngs::OsRng;
+use cuproof::Error;
+use cuproof::encrypted::{decrypt, encrypt, is_encrypted};
+use cuproof::error::SerializationError;
+use cuproof::range_proof::Cuproof;
+use cuproof::secret::Secret;
+use cuproof::setup::{FingerprintHex, Params};
+use cuproof::util::{bigint_to_hex, bigint_to_signed_hex, compress_container, is_legacy_file, load_params, read_commitment, read_params, read_proof, write_params, write_proof, ReadLimits, PROOF_FORMAT_VERSION};
+use cuproof::batch::Record;
+use super::args::OutputFormat;
+use super::output::file_stamp;
+use super::passphrase::passphrase;
+use super::{CliError, Json, Output};
+
+/// load_params for a command argument, or read_params on stdin for `-`; the error names the
+/// specific validation failure, or the IO / format error. Within a script, the params of a file
+/// already loaded by an earlier step
+pub(super) fn load_params_arg(out: &mut Output, path: &str) -> Result<Params, CliError> {
+    let stamp = if path == "-" { None } else { file_stamp(path) };
+    let cached = out.params_cache.as_ref().and_then(|cache| cache.entries.get(path)).filter(|(cached, _)| stamp.is_some() && *cached == stamp);
+    if let Some((_, params)) = cached { return Ok(params.clone()); }
+    let loaded = match path {
+        "-" => {
+            out.claim_stdin("params")?;
+            read_params(&mut out.stdio.stdin, &ReadLimits::default()).map_err(Error::from)
+        }
+        _ => load_params(path).map_err(Error::from),
+    };
+    let params = loaded.map_err(|e| match e {
+        e @ (Error::Params(_) | Error::Serialization(SerializationError::Metadata(_))) => {
+            CliError::failed(format!("Params file {} failed validation", path), e)
+        }
+        e => CliError::failed(format!("Failed to load params {}", path), e),
+    }.with("path", path).with("file", "params"))?;
+    if let Some(cache) = &mut out.params_cache {
+        cache.loads += 1;
+        if path != "-" { cache.entries.insert(path.to_string(), (stamp, params.clone())); }
+    }
+    Ok(params)
+}
+
+/// Write params to `path`, or stdout for `-`, in `format`; binary params are compressed at the
+/// zstd level `compress` when given
+pub(super) fn write_params_arg(out: &mut Output, path: &str, params: &Params, format: OutputFormat, compress: Option<i32>) -> Result<(), CliError> {
+    match (format, compress) {
+        (OutputFormat::Bin, Some(level)) => {
+            let bytes = compressed(level, |w| write_params(w, params)).map_err(|e| CliError::failed("Failed to compress params", e))?;
+            out.write_output(path, "params", true, |w| w.write_all(&bytes))
+        }
+        (OutputFormat::Bin, None) => out.write_output(path, "params", true, |w| write_params(w, params).map(|_| ())),
+        #[cfg(feature = "json")]
+        (OutputFormat::Json, _) => out.write_output(path, "params", false, |w| cuproof::util::write_params_json(w, params).map(|_| ())),
+        #[cfg(not(feature = "json"))]
+        (OutputFormat::Json, _) => unreachable!("parse_format refuses json without the json feature"),
+    }
+}
+
+/// The container `write` produces, compressed at the zstd `level` (see util::compress_container)
+fn compressed(level: i32, write: impl FnOnce(&mut Vec<u8>) -> std::io::Result<usize>) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    write(&mut bytes)?;
+    compress_container(&bytes, level)
+}
+
+/// Write the proof to `path`, or stdout for `-`, compressed at the zstd level `compress` when
+/// given; a binary proof bound for a terminal is armored instead (armor feature), so a terminal
+/// never receives binary without --force-binary
+pub(super) fn write_proof_arg(out: &mut Output, path: &str, proof: &Cuproof, params: &Params, format: OutputFormat, armor: bool, compress: Option<i32>) -> Result<(), CliError> {
+    let to_terminal = path == "-" && out.stdio.stdout_tty && !out.force_binary;
+    if armor || (to_terminal && format == OutputFormat::Bin && cfg!(feature = "armor")) {
+        let text = armored_proof(proof, params).map_err(|e| CliError::failed("Failed to save proof", e))?;
+        return out.write_output(path, "proof", false, |w| w.write_all(text.as_bytes()));
+    }
+    match (format, compress) {
+        (OutputFormat::Bin, Some(level)) => {
+            let bytes = compressed(level, |w| write_proof(w, proof)).map_err(|e| CliError::failed("Failed to compress proof", e))?;
+            out.write_output(path, "proof", true, |w| w.write_all(&bytes))
+        }
+        (OutputFormat::Bin, None) => out.write_output(path, "proof", true, |w| write_proof(w, proof).map(|_| ())),
+        #[cfg(feature = "json")]
+        (OutputFormat::Json, _) => out.write_output(path, "proof", false, |w| cuproof::util::write_proof_json(w, proof, params.bits()).map(|_| ())),
+        #[cfg(not(feature = "json"))]
+        (OutputFormat::Json, _) => unreachable!("parse_format refuses json without the json feature"),
+    }
+}
+
+#[cfg(feature = "armor")]
+fn armored_proof(proof: &Cuproof, params: &Params) -> std::io::Result<String> {
+    Ok(proof.to_armored_with_params(params))
+}
+
+#[cfg(not(feature = "armor"))]
+fn armored_proof(_proof: &Cuproof, _params: &Params) -> std::io::Result<String> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "armored proofs require the armor feature"))
+}
+
+/// The proof at `path`, or on stdin for `-`, with its bytes; armored proofs must also carry the
+/// Params-Fingerprint of `params` when given
+pub(super) fn read_proof_arg(out: &mut Output, path: &str, params: Option<&Params>) -> Result<(Cuproof, Vec<u8>), CliError> {
+    let bytes = out.read_input(path, "proof")?;
+    Ok((decode_proof_arg(&bytes, params)?, bytes))
+}
+
+/// The proof in `bytes`, in any format read_proof takes, with the Params-Fingerprint check of
+/// read_proof_arg
+pub(super) fn decode_proof_arg(bytes: &[u8], params: Option<&Params>) -> Result<Cuproof, CliError> {
+    #[cfg(feature = "armor")]
+    if let Ok(text) = std::str::from_utf8(bytes)
+        && text.trim_start().starts_with("-----BEGIN CUPROOF-----")
+    {
+        let proof = match params {
+            Some(params) => Cuproof::from_armored_for(text, params),
+            None => Cuproof::from_armored(text),
+        };
+        return proof.map_err(|e| CliError::failed("Failed to load proof", e));
+    }
+    let _ = params;
+    read_proof(&mut &bytes[..], &ReadLimits::default()).map_err(|e| CliError::failed("Failed to load proof", e))
+}
+
+/// The commitment at `path`, written by commit under `params`
+pub(super) fn load_commitment_arg(out: &mut Output, path: &str, params: &Params) -> Result<BigInt, CliError> {
+    let bytes = out.read_input(path, "commitment")?;
+    read_commitment(&mut bytes.as_slice(), params, &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load commitment {}", path), e))
+}
+
+/// Refuse fields outside `columns` in a manifest row, most likely misspelt ones
+pub(super) fn check_columns(record: &Record, columns: &[&str]) -> Result<(), String> {
+    match record.fields.iter().find(|(name, _)| !columns.contains(&name.as_str())) {
+        Some((name, _)) => Err(format!("unknown column {}; expected {}", name, columns.join(", "))),
+        None => Ok(()),
+    }
+}
+
+/// Print the statement embedded in a proof file, or note that it has none; under --json it is the
+/// statement field (null for proofs without one)
+pub(super) fn print_statement(proof: &Cuproof, out: &mut Output) {
+    let Some(statement) = &proof.statement else {
+        out.say(format_args!("Statement: none recorded (proof file from before format version {})", PROOF_FORMAT_VERSION));
+        out.set("statement", Json::Null);
+        return;
+    };
+    out.say("Statement:");
+    out.say(format_args!("  commitment:         {}", bigint_to_hex(&statement.commitment)));
+    out.say(format_args!("  range:              [{}, {}]", bigint_to_signed_hex(&statement.a), bigint_to_signed_hex(&statement.b)));
+    out.say(format_args!("  bits:               {}", statement.bits));
+    out.say(format_args!("  label:              {:?}", statement.label));
+    out.say(format_args!("  params fingerprint: {}", hex::encode(statement.params_fingerprint)));
+    out.set("statement", json_object! {
+        "commitment" => bigint_to_hex(&statement.commitment),
+        "a" => bigint_to_signed_hex(&statement.a),
+        "b" => bigint_to_signed_hex(&statement.b),
+        "bits" => statement.bits,
+        "label" => statement.label.clone(),
+        "params_fingerprint" => hex::encode(statement.params_fingerprint),
+    });
+}
+
+/// Write the secret `what` to `path` with `write`, or stdout for `-`; with `encrypt`, encrypted
+/// under the passphrase of the command (see passphrase) rather than in the clear
+pub(super) fn write_secret_output(out: &mut Output, path: &str, what: &'static str, encrypt_it: bool, write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>) -> Result<(), CliError> {
+    if !encrypt_it { return out.write_output(path, what, true, write); }
+    let mut plaintext = Secret::new(Vec::new());
+    write(plaintext.expose_mut()).map_err(|e| CliError::failed(format!("Failed to save {}", what), e))?;
+    let passphrase = passphrase(out, what, true)?;
+    let encrypted = encrypt(plaintext.expose(), passphrase.expose().as_bytes(), &mut OsRng).map_err(|e| CliError::failed(format!("Failed to encrypt {}", what), e))?;
+    out.write_output(path, what, true, |w| w.write_all(&encrypted))
+}
+
+/// The bytes of the secret input `path`, or of stdin for `-`, decrypted with the passphrase of the
+/// command when the file is encrypted; a wrong passphrase and an altered file fail with their own kinds
+pub(super) fn read_secret_input(out: &mut Output, path: &str, what: &'static str) -> Result<Secret<Vec<u8>>, CliError> {
+    let bytes = Secret::new(out.read_input(path, what)?);
+    if !is_encrypted(bytes.expose()) { return Ok(bytes); }
+    decrypt_input(out, path, what, &bytes)
+}
+
+/// read_secret_input for a secret that is only ever written encrypted: a file in the clear is
+/// refused rather than read, so a copy taken out of the encrypted storage cannot be fed back in
+pub(super) fn read_encrypted_input(out: &mut Output, path: &str, what: &'static str) -> Result<Secret<Vec<u8>>, CliError> {
+    let bytes = Secret::new(out.read_input(path, what)?);
+    if !is_encrypted(bytes.expose()) {
+        let e = SerializationError::Invalid(format!("the {} is not encrypted; it must be written by cuproof under a passphrase", what));
+        return Err(CliError::failed(format!("Failed to load {} {}", what, path), e).with("path", path).with("file", what));
+    }
+    decrypt_input(out, path, what, &bytes)
+}
+
+/// Decrypt the encrypted `bytes` read from `path` under the passphrase of the command
+fn decrypt_input(out: &mut Output, path: &str, what: &'static str, bytes: &Secret<Vec<u8>>) -> Result<Secret<Vec<u8>>, CliError> {
+    let passphrase = passphrase(out, what, false)?;
+    decrypt(bytes.expose(), passphrase.expose().as_bytes())
+        .map_err(|e| CliError::failed(format!("Failed to decrypt {} {}", what, path), e).with("path", path).with("file", what))
+}
+
+/// Remind that the blinding file at `path` is as secret as the value
+pub(super) fn warn_blinding_file(path: &str) {
+    tracing::warn!("{} holds the blinding that opens the commitment; keep it as secret as the value", path);
+}
+
+/// Refuse `-` for an argument that must name a file
+pub(super) fn file_only<'a>(path: &'a str, what: &str) -> Result<&'a str, CliError> {
+    if path == "-" { return Err(CliError::Usage(format!("- is not supported for the {}; give a file path", what))); }
+    Ok(path)
+}
+
+/// Point at `convert` when `path` is still in the text format that predates the binary container
+pub(super) fn warn_if_legacy(path: &str) {
+    if path != "-" && is_legacy_file(path).unwrap_or(false) {
+        tracing::info!("{} uses the legacy text format; rewrite it with `cuproof convert --upgrade {} <out_path>`", path, path);
+    }
+}
+
+/// Name the params a command loaded by their short fingerprint, so runs over different params
+/// stand out in logs
+pub(super) fn say_params(out: &mut Output, path: &str, params: &Params) {
+    let fingerprint = params.fingerprint_hex();
+    out.say(format_args!("Params {}: {} bits, fingerprint {}", path, params.bits(), fingerprint.short()));
+    out.set("params_fingerprint", fingerprint.to_string());
+}
+
+/// Log a loud warning when `proof` records the fingerprint of params other than
+/// `params`; verification then fails, and this says why before it does
+pub(super) fn warn_if_other_params(proof: &Cuproof, params: &Params, path: &str) {
+    let Some(statement) = &proof.statement else { return };
+    let loaded = params.fingerprint_hex();
+    if statement.params_fingerprint == loaded.0 { return; }
+    tracing::warn!(
+        "PARAMS MISMATCH. The proof was made under params with fingerprint {},\n\
+         but {} has fingerprint {}; it cannot verify against them.\n\
+         Check that prover and verifier use the same params file (`cuproof fingerprint`).",
+        FingerprintHex(statement.params_fingerprint).short(), path, loaded.short()
+    );
+}
+
+/// Log a loud warning when `params` carry the INSECURE marker of the test setups
+pub(super) fn warn_if_insecure(params: &Params) {
+    if params.is_insecure() {
+        tracing::warn!("INSECURE TEST PARAMETERS. The factorization of n is public, so proofs over these\n\
+            params prove nothing. Use `setup trusted` for anything but testing.");
+    }
+}
@@ -0,0 +1,26 @@
+//! `cuproof fingerprint`: the hash identifying params whatever their file format
+
+use clap::Args;
+use super::files::load_params_arg;
+use super::{CliError, CommandOutcome, Output};
+
+/// Print the fingerprint of params: a hash of their canonical encoding, the same for every file format
+#[derive(Args)]
+#[command(after_help = "prove, verify and info print the same short fingerprint, so runs over different params stand out in logs.\n\nExamples:\n  cuproof fingerprint params.bin\n  cuproof fingerprint --long params.json")]
+pub(super) struct FingerprintArgs {
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Print the full SHA-256 instead of its first 16 hex digits
+    #[arg(long)]
+    long: bool,
+}
+
+pub(super) fn run_fingerprint(m: &FingerprintArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let path = m.params_path.as_str();
+    let fingerprint = load_params_arg(out, path)?.fingerprint_hex();
+    out.say(if m.long { fingerprint.to_string() } else { fingerprint.short() });
+    out.set("params_path", path);
+    out.set("fingerprint", fingerprint.to_string());
+    out.set("short", fingerprint.short());
+    Ok(CommandOutcome::Done)
+}
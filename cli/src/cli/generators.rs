@@ -0,0 +1,84 @@
+//! `cuproof generators`, and the --generators check of aggregate and verify
+
+use clap::Args;
+use cuproof::aggregate::AGGREGATE_GENERATOR_LABEL;
+use cuproof::generators::{GeneratorVector, MAX_GENERATORS};
+use cuproof::setup::Params;
+use cuproof::util::{read_generators, write_generators, ReadLimits};
+use super::args::count;
+use super::files::{load_params_arg, say_params, warn_if_legacy};
+use super::{CliError, CommandOutcome, Output};
+
+/// Derive a generator vector from params and print its fingerprint, writing it with its derivation seed (see cuproof::generators)
+#[derive(Args)]
+#[command(after_help = "The vector is derived from the params fingerprint, the label and the count, so anyone holding the params \
+    can derive it again; the file pins it, and aggregate and verify check a pinned file against the params.\n\n\
+    Example:\n  cuproof generators --label agg/g --count 4 --out agg4.gens params.bin")]
+pub(super) struct GeneratorsArgs {
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    #[arg(long, value_name = "string", help = format!("Domain-separation label; aggregated proofs commit under {}", AGGREGATE_GENERATOR_LABEL))]
+    label: String,
+    /// Generators to derive; an aggregated proof of N statements uses N
+    #[arg(long, value_name = "N", value_parser = count(1, MAX_GENERATORS))]
+    count: usize,
+    /// Write the vector, for aggregate --generators and verify --generators
+    #[arg(long, value_name = "path")]
+    out: Option<String>,
+}
+
+/// --generators of aggregate and verify: a vector file, as `cuproof generators` writes, to check
+#[derive(Args)]
+pub(super) struct GeneratorsArg {
+    #[arg(long = "generators", value_name = "generators_path",
+        help = format!("Check that this vector, written by `cuproof generators --label {} --count <statements>`, matches its derivation from the params", AGGREGATE_GENERATOR_LABEL))]
+    pub(super) generators: Option<String>,
+}
+
+pub(super) fn run_generators(m: &GeneratorsArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let params_path = m.params_path.as_str();
+    let (label, count) = (m.label.as_str(), m.count);
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    let vector = GeneratorVector::derive(&params, label, count);
+    if let Some(path) = m.out.as_deref() {
+        out.write_output(path, "generators", true, |w| write_generators(w, &vector).map(|_| ()))?;
+        if path != "-" { out.say(format_args!("Saved {} generators to {}", count, path)); }
+    }
+    out.say(format_args!("Seed: {}", vector.seed()));
+    out.say(format_args!("Fingerprint: {}", vector.fingerprint_hex()));
+    say_params(out, params_path, &params);
+    out.set("params_path", params_path);
+    out.set("path", m.out.as_deref());
+    out.set("label", label);
+    out.set("count", count);
+    out.set("seed", vector.seed());
+    out.set("fingerprint", vector.fingerprint_hex().to_string());
+    out.set("params_fingerprint", params.fingerprint_hex().to_string());
+    Ok(CommandOutcome::Done)
+}
+
+/// Load the generator vector at `path` and check it is the one an aggregated proof of `count`
+/// statements commits under: label AGGREGATE_GENERATOR_LABEL, `count` generators, each what
+/// derivation from `params` gives
+/// - returns: Ok, a usage error for a vector of another label or count, or the ParamsError of
+///   GeneratorVector::check for one that was derived from other params or altered
+pub(super) fn check_generators_arg(out: &mut Output, path: &str, params: &Params, count: usize) -> Result<(), CliError> {
+    let bytes = out.read_input(path, "generators")?;
+    let vector = read_generators(&mut &bytes[..], &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load generators {}", path), e).with("path", path).with("file", "generators"))?;
+    if vector.label != AGGREGATE_GENERATOR_LABEL || vector.count() != count {
+        return Err(CliError::Usage(format!(
+            "{} holds {} generators under {}; an aggregated proof of {} statements commits under {} generators of {}",
+            path, vector.count(), vector.label, count, count, AGGREGATE_GENERATOR_LABEL,
+        )));
+    }
+    vector.check(params).map_err(|e| CliError::failed(format!("Generators {} do not match the params", path), e).with("path", path))?;
+    out.say(format_args!("Generators {} match their derivation (fingerprint {})", path, vector.fingerprint_hex().short()));
+    out.set("generators", json_object! {
+        "path" => path,
+        "count" => vector.count(),
+        "fingerprint" => vector.fingerprint_hex().to_string(),
+    });
+    Ok(())
+}
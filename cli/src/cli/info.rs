@@ -0,0 +1,58 @@
+//! `cuproof info`: what a file is, from structural checks only (see cuproof::inspect)
+
+use clap::Args;
+use cuproof::inspect::{inspect, Detail, FileInfo};
+use cuproof::setup::SHORT_FINGERPRINT_BYTES;
+use super::{CliError, CommandOutcome, Json, Output};
+
+/// Describe a proof, params, statement, commitment or archive file from structural checks only
+#[derive(Args)]
+#[command(after_help = "Nothing is verified. Exits 2 for files that fail the checks, reporting how far parsing got.\n\nExample:\n  cuproof info v.proof")]
+pub(super) struct InfoArgs {
+    #[arg(value_name = "file")]
+    file: String,
+}
+
+pub(super) fn run_info(m: &InfoArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let path = m.file.as_str();
+    let info = inspect(&out.read_input(path, "file")?);
+    let version = match info.version {
+        Some(0) => " (legacy text)".to_string(),
+        Some(version) => format!(", version {}", version),
+        None => String::new(),
+    };
+    out.say(format_args!("{}: {} bytes, {} ({}{})", path, info.size, info.kind, info.encoding, version));
+    if let Some(fingerprint) = params_fingerprint(&info) { out.say(format_args!("Params fingerprint: {}", fingerprint)); }
+    if !info.components.is_empty() { out.say("Components:"); }
+    for c in &info.components { out.say(format_args!("  {:<12} {:>6} bytes", c.name, c.bytes)); }
+    for (key, value) in &info.details { out.say(format_args!("{:<29} {}", format!("{}:", key), value)); }
+    match &info.stopped {
+        None => out.say("Structure: ok (nothing was verified)"),
+        Some(s) => out.say(format_args!("Structure: parsing stopped at {}: {}", s.at, s.reason)),
+    }
+    out.set("path", path);
+    out.set("kind", info.kind.to_string());
+    out.set("encoding", info.encoding);
+    out.set("size", info.size);
+    out.set("version", info.version);
+    out.set("components", info.components.iter().map(|c| json_object! { "name" => c.name.as_str(), "bytes" => c.bytes }).collect::<Vec<_>>());
+    out.set("details", Json::Object(info.details.iter().map(|(key, value)| {
+        let value = match value {
+            Detail::Text(s) => Json::from(s.as_str()),
+            Detail::Number(n) => Json::from(*n),
+            Detail::Flag(b) => Json::from(*b),
+        };
+        (key.to_string(), value)
+    }).collect()));
+    out.set("well_formed", info.is_well_formed());
+    out.set("stopped", info.stopped.as_ref().map(|s| json_object! { "at" => s.at.as_str(), "reason" => s.reason.as_str() }));
+    Ok(if info.is_well_formed() { CommandOutcome::Done } else { CommandOutcome::Malformed })
+}
+
+/// The short fingerprint of the params a file is, or was made under, from inspect's details
+fn params_fingerprint(info: &FileInfo) -> Option<String> {
+    info.details.iter().find_map(|(key, value)| match (*key, value) {
+        ("fingerprint" | "params_fingerprint", Detail::Text(hex)) => hex.get(..2 * SHORT_FINGERPRINT_BYTES).map(str::to_string),
+        _ => None,
+    })
+}
@@ -0,0 +1,199 @@
+//! prove-offline-commit, prove-offline-challenge and prove-offline-finish: a proof made in two
+//! stages on an air-gapped machine (see cuproof::offline)
+
+use clap::{ArgGroup, Args};
+use rand::rngs::OsRng;
+use cuproof::offline::{offline_commit, offline_commit_for_commitment, offline_finish, ChallengeBundle, SpentLog};
+use cuproof::range_proof::{proof_id, ProveError};
+use cuproof::util::{bigint_to_hex, read_challenge_bundle, read_commitment, read_first_message, read_offline_state, write_challenge_bundle, write_first_message, write_offline_state, ReadLimits};
+use super::args::{blinding_secret, num_arg, number, parse_nonce, read_secret, BlindingSecret, HexBytes, OutputFormat, RadixArg, SecretSource, ValueSecret};
+use super::files::{load_params_arg, read_encrypted_input, say_params, warn_if_insecure, warn_if_legacy, write_proof_arg, write_secret_output};
+use super::{CliError, CommandOutcome, Output};
+
+/// First half of an air-gapped proof: write the first message and the prover state
+#[derive(Args)]
+#[command(
+    allow_negative_numbers = true,
+    group(ArgGroup::new("value").args(["v", "value_stdin", "value_env"]).required(true)),
+    group(ArgGroup::new("blinding-source").args(["blinding", "blinding_in", "blinding_stdin", "blinding_env"]).required(true)),
+    after_help = "Secrets read from stdin come one per line: v, then the blinding. The state file holds the \
+        witness, so it is always written encrypted under a passphrase: $CUPROOF_PASSPHRASE, else typed on the terminal.\n\n\
+        Example:\n  cuproof prove-offline-commit --value-env V --blinding-in r.bin --commitment c.bin \\\n    \
+        --message v.first --state v.state params.bin 10 100"
+)]
+pub(super) struct ProveOfflineCommitArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Lower end of the range
+    #[arg(value_name = "a", allow_hyphen_values = true, value_parser = number(true))]
+    a: String,
+    /// Upper end of the range, inclusive
+    #[arg(value_name = "b", allow_hyphen_values = true, value_parser = number(true))]
+    b: String,
+    /// The value; prefer --value-stdin or --value-env
+    #[arg(value_name = "v", allow_hyphen_values = true, value_parser = number(true))]
+    v: Option<String>,
+    #[command(flatten)]
+    value: ValueSecret,
+    /// Check that v and the blinding open this commitment, written by commit, before anything else
+    #[arg(long, value_name = "commitment_path")]
+    commitment: Option<String>,
+    /// The blinding of the commitment, or when not a number a file holding it, as --blinding-in
+    #[arg(long, value_name = "r|r_path")]
+    blinding: Option<String>,
+    /// Read the blinding from a file written by commit or --blinding-out
+    #[arg(long, value_name = "r_path")]
+    blinding_in: Option<String>,
+    #[command(flatten)]
+    blinding_secret: BlindingSecret,
+    /// Where to write the first message, for prove-offline-challenge on the online machine
+    #[arg(long, value_name = "message_path")]
+    message: String,
+    /// Where to write the prover state, encrypted; it must stay on this machine
+    #[arg(long, value_name = "state_path")]
+    state: String,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+/// Online step of an air-gapped proof: derive the challenges for a first message
+#[derive(Args)]
+#[command(after_help = "The challenges are the Fiat-Shamir challenges verify recomputes, so the bundle holds no secret.\n\n\
+    Example:\n  cuproof prove-offline-challenge --label session/7 --nonce 4f1c --out v.bundle params.bin v.first")]
+pub(super) struct ProveOfflineChallengeArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// First message written by prove-offline-commit
+    #[arg(value_name = "message_path")]
+    message_path: String,
+    /// Where to write the challenge bundle, for prove-offline-finish
+    #[arg(long, value_name = "bundle_path")]
+    out: String,
+    /// The verifier's context, e.g. a session name, recorded as the statement label of the proof
+    #[arg(long, value_name = "string", default_value = "")]
+    label: String,
+    /// Bind the challenges to this nonce from the verifier; the proof then verifies only with the same verify --nonce
+    #[arg(long, value_name = "hex", value_parser = parse_nonce)]
+    nonce: Option<HexBytes>,
+}
+
+/// Second half of an air-gapped proof: answer a challenge bundle with the prover state, once
+#[derive(Args)]
+#[command(after_help = "A state is used once: its id is recorded in the spent log before the proof is made, a recorded id \
+    is refused (exit 5), and the state file is removed afterwards. The state is decrypted with the passphrase it was written \
+    under; a state file in the clear is refused.\n\n\
+    Example:\n  cuproof prove-offline-finish --state v.state params.bin v.bundle v.proof")]
+pub(super) struct ProveOfflineFinishArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Challenge bundle written by prove-offline-challenge
+    #[arg(value_name = "bundle_path")]
+    bundle_path: String,
+    /// Where to write the proof
+    #[arg(value_name = "proof_path")]
+    proof_path: String,
+    /// Encrypted prover state written by prove-offline-commit
+    #[arg(long, value_name = "state_path")]
+    state: String,
+    /// Ids of the states already used, checked and appended to [default: <state_path>.spent]
+    #[arg(long, value_name = "path")]
+    spent_log: Option<String>,
+}
+
+pub(super) fn run_prove_offline_commit(m: &ProveOfflineCommitArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let radix = m.radix.get();
+    let value_source = m.value.source(m.v.clone().map(SecretSource::Arg)).expect("the value group is required");
+    let given = m.blinding.as_deref().map(|r| blinding_secret(r, radix)).or_else(|| m.blinding_in.clone().map(SecretSource::File));
+    let blinding_source = m.blinding_secret.source(given).expect("the blinding group is required");
+    let (params_path, message_path, state_path) = (m.params_path.as_str(), m.message.as_str(), m.state.as_str());
+    let a = num_arg("a", &m.a, radix, true)?;
+    let b = num_arg("b", &m.b, radix, true)?;
+    let v = read_secret(out, "v", value_source, radix, true)?;
+    let r = read_secret(out, "blinding", blinding_source, radix, false)?;
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let (message, state) = match m.commitment.as_deref() {
+        Some(path) => {
+            let bytes = out.read_input(path, "commitment")?;
+            let commitment = read_commitment(&mut bytes.as_slice(), &params, &ReadLimits::default())
+                .map_err(|e| CliError::failed(format!("Failed to load commitment {}", path), e))?;
+            offline_commit_for_commitment(&commitment, v.expose(), r.expose(), &a, &b, &params, &mut OsRng)
+        }
+        None => offline_commit(v.expose(), r.expose(), &a, &b, &params, &mut OsRng),
+    }
+    .map_err(|e| CliError::failed("Failed to prove", e))?;
+    // the state is written first: a message whose state was lost cannot be finished
+    write_secret_output(out, state_path, "offline state", true, |w| write_offline_state(w, &state).map(|_| ()))?;
+    out.write_output(message_path, "first message", true, |w| write_first_message(w, &message).map(|_| ()))?;
+    out.say(format_args!("Saved offline state to {}", state_path));
+    out.say(format_args!("Saved first message to {}", message_path));
+    out.say(format_args!("State id: {}", hex::encode(message.id)));
+    say_params(out, params_path, &params);
+    out.set("message_path", message_path);
+    out.set("state_path", state_path);
+    out.set("state_id", hex::encode(message.id));
+    out.set("commitment", bigint_to_hex(&message.statement.commitment));
+    Ok(CommandOutcome::Done)
+}
+
+pub(super) fn run_prove_offline_challenge(m: &ProveOfflineChallengeArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (params_path, message_path, bundle_path, label) = (m.params_path.as_str(), m.message_path.as_str(), m.out.as_str(), m.label.as_str());
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    let bytes = out.read_input(message_path, "first message")?;
+    let message = read_first_message(&mut bytes.as_slice(), &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load first message {}", message_path), e))?;
+    let nonce = m.nonce.as_deref().unwrap_or_default();
+    let bundle = ChallengeBundle::new(&message, &params, label, nonce).map_err(|e| CliError::failed("Failed to derive the challenges", e))?;
+    out.write_output(bundle_path, "challenge bundle", true, |w| write_challenge_bundle(w, &bundle).map(|_| ()))?;
+    if bundle_path != "-" { out.say(format_args!("Saved challenge bundle to {}", bundle_path)); }
+    out.say(format_args!("State id: {}", hex::encode(message.id)));
+    say_params(out, params_path, &params);
+    out.set("bundle_path", bundle_path);
+    out.set("state_id", hex::encode(message.id));
+    out.set("label", label);
+    out.set("nonce", m.nonce.as_ref().map(hex::encode));
+    out.set("commitment", bigint_to_hex(&message.statement.commitment));
+    Ok(CommandOutcome::Done)
+}
+
+pub(super) fn run_prove_offline_finish(m: &ProveOfflineFinishArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (params_path, bundle_path, proof_path, state_path) = (m.params_path.as_str(), m.bundle_path.as_str(), m.proof_path.as_str(), m.state.as_str());
+    let spent_path = m.spent_log.clone().unwrap_or_else(|| format!("{}.spent", state_path));
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let bytes = out.read_input(bundle_path, "challenge bundle")?;
+    let bundle = read_challenge_bundle(&mut bytes.as_slice(), &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load challenge bundle {}", bundle_path), e))?;
+    let bytes = read_encrypted_input(out, state_path, "offline state")?;
+    let state = read_offline_state(&mut bytes.expose().as_slice(), &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load offline state {}", state_path), e))?;
+    let id = state.id();
+    let spent = SpentLog::new(&spent_path);
+    let used = spent.contains(&id).map_err(|e| CliError::failed(format!("Failed to read spent log {}", spent_path), e))?;
+    if used { return Err(CliError::failed("Failed to prove", ProveError::StateSpent).with("state_id", hex::encode(id))); }
+    if bundle.message.id != id { return Err(CliError::failed("Failed to prove", ProveError::ChallengeMismatch)); }
+    // recorded before proving, so a crash after this point cannot leave the state usable twice
+    spent.record(&id).map_err(|e| CliError::failed(format!("Failed to write spent log {}", spent_path), e))?;
+    let proof = offline_finish(state, &bundle, &params).map_err(|e| CliError::failed("Failed to prove", e))?;
+    write_proof_arg(out, proof_path, &proof, &params, OutputFormat::Bin, false, None)?;
+    if state_path != "-" && let Err(e) = std::fs::remove_file(state_path) {
+        tracing::warn!("could not remove the used state {}: {}", state_path, e);
+    }
+    if proof_path != "-" { out.say(format_args!("Saved proof to {}", proof_path)); }
+    say_params(out, params_path, &params);
+    let proof_id = proof_id(&proof);
+    out.say(format_args!("Proof id: {}", proof_id));
+    out.set("proof_path", proof_path);
+    out.set("proof_id", proof_id);
+    out.set("commitment", bigint_to_hex(&proof.C));
+    out.set("state_id", hex::encode(id));
+    out.set("nonce", (!bundle.nonce.is_empty()).then(|| hex::encode(&bundle.nonce)));
+    out.set("spent_log", spent_path);
+    Ok(CommandOutcome::Done)
+}
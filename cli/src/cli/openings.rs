@@ -0,0 +1,117 @@
+//! prove-openings and verify-openings: knowledge of the openings of many commitments in one proof
+//! (see cuproof::openings)
+
+use std::time::Instant;
+use clap::Args;
+use num_bigint::BigInt;
+use cuproof::batch::{parse_records, Record};
+use cuproof::commitment::commit;
+use cuproof::error::SerializationError;
+use cuproof::openings::{prove_openings_batch, verify_openings_batch, MAX_BATCH_OPENINGS};
+use cuproof::range_proof::ProveError;
+use cuproof::secret::Secret;
+use cuproof::util::{bigint_to_hex, read_openings_proof, write_openings_proof, ReadLimits};
+use cuproof::verify::VerifyError;
+use super::args::{parse_cli_bigint, Radix, RadixArg};
+use super::files::{check_columns, file_only, load_params_arg, read_encrypted_input, say_params, warn_if_insecure, warn_if_legacy};
+use super::{CliError, CommandOutcome, Output};
+
+/// Prove knowledge of the openings of every commitment of a manifest in one proof
+#[derive(Args)]
+#[command(after_help = "The manifest holds every opening, so it is read only encrypted: write the rows with `cuproof encrypt` \
+    and give the passphrase as $CUPROOF_PASSPHRASE, else on the terminal. The commitments are computed from the openings; \
+    publish them in the order of the proof file.\n\n\
+    Example:\n  cuproof encrypt openings.csv openings.enc\n  cuproof prove-openings --radix dec params.bin openings.enc openings.proof")]
+pub(super) struct ProveOpeningsArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Rows encrypted by `cuproof encrypt`: CSV or JSON lines with columns value and blinding, one commitment per row
+    #[arg(value_name = "manifest_path")]
+    manifest_path: String,
+    /// Where to write the proof, which lists the commitments in manifest order
+    #[arg(value_name = "proof_path|-")]
+    proof_path: String,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+/// Verify a proof of knowledge of the openings of a list of commitments
+#[derive(Args)]
+#[command(after_help = "The proof covers the commitments listed in its file, in order; check them against the ones published.\n\n\
+    Example:\n  cuproof verify-openings params.bin openings.proof")]
+pub(super) struct VerifyOpeningsArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// Proof written by prove-openings
+    #[arg(value_name = "proof_path")]
+    proof_path: String,
+}
+
+const OPENING_COLUMNS: [&str; 2] = ["value", "blinding"];
+
+fn opening_row(record: &Record, radix: Radix) -> Result<(BigInt, BigInt), String> {
+    check_columns(record, &OPENING_COLUMNS)?;
+    let secret = |field: &str| {
+        let s = record.get(field).filter(|s| !s.is_empty()).ok_or_else(|| format!("no {}", field))?;
+        parse_cli_bigint(s, radix, false).map(|(n, _)| n).map_err(|_| format!("invalid {}: not a non-negative number (the text is secret, so not shown)", field))
+    };
+    Ok((secret("value")?, secret("blinding")?))
+}
+
+pub(super) fn run_prove_openings(m: &ProveOpeningsArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let radix = m.radix.get();
+    let (params_path, manifest_path, proof_path) = (m.params_path.as_str(), file_only(&m.manifest_path, "manifest")?, m.proof_path.as_str());
+    let text = read_encrypted_input(out, manifest_path, "manifest")?;
+    let text = std::str::from_utf8(text.expose())
+        .map_err(|_| CliError::failed(format!("Failed to read manifest {}", manifest_path), SerializationError::Invalid("the rows are not UTF-8".to_string())))?;
+    let records = parse_records(text).map_err(|e| CliError::failed(format!("Failed to read manifest {}", manifest_path), e))?;
+    if records.len() > MAX_BATCH_OPENINGS {
+        return Err(CliError::Usage(format!("{} has {} rows; a proof covers at most {}", manifest_path, records.len(), MAX_BATCH_OPENINGS)));
+    }
+    let mut openings = Secret::new(Vec::with_capacity(records.len()));
+    for record in &records {
+        let opening = opening_row(record, radix).map_err(|e| CliError::Usage(format!("{} line {}: {}", manifest_path, record.line, e)))?;
+        openings.expose_mut().push(opening);
+    }
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let commitments: Vec<BigInt> = openings.expose().iter().map(|(m, r)| commit(&params, m, r)).collect();
+    let started = Instant::now();
+    let proof = prove_openings_batch(openings.expose(), &commitments, &params).map_err(|e| match e {
+        ProveError::Aggregate { index, error } => CliError::failed(format!("Failed to prove opening {} of {}", index + 1, manifest_path), *error),
+        e => CliError::failed("Failed to prove", e),
+    })?;
+    let prove_ms = started.elapsed().as_secs_f64() * 1000.0;
+    out.write_output(proof_path, "proof", true, |w| write_openings_proof(w, &commitments, &proof).map(|_| ()))?;
+    if proof_path != "-" { out.say(format_args!("Saved proof of {} openings to {}", commitments.len(), proof_path)); }
+    say_params(out, params_path, &params);
+    out.set("proof_path", proof_path);
+    out.set("openings", commitments.len());
+    out.set("prove_ms", prove_ms);
+    Ok(CommandOutcome::Done)
+}
+
+pub(super) fn run_verify_openings(m: &VerifyOpeningsArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (params_path, proof_path) = (m.params_path.as_str(), m.proof_path.as_str());
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let bytes = out.read_input(proof_path, "proof")?;
+    let (commitments, proof) = read_openings_proof(&mut bytes.as_slice(), &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load openings proof {}", proof_path), e))?;
+    say_params(out, params_path, &params);
+    out.say(format_args!("Claim: the prover knows an opening of each of the {} commitments in {}", commitments.len(), proof_path));
+    out.set("proof", json_object! {
+        "size" => bytes.len(),
+        "bits" => proof.bits,
+    });
+    out.set("commitments", commitments.iter().map(bigint_to_hex).collect::<Vec<_>>());
+    Ok(match verify_openings_batch(&commitments, &proof, &params) {
+        Ok(()) => CommandOutcome::Valid,
+        Err(VerifyError::InvalidProof) => CommandOutcome::Invalid(None),
+        Err(e) => CommandOutcome::Invalid(Some(e)),
+    })
+}
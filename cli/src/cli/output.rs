@@ -0,0 +1,295 @@
+//! Where the commands write: the JSON object of --json, the human-readable lines, and the standard
+//! streams behind `-` paths
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{IsTerminal, Read, Write};
+use cuproof::secret::Secret;
+use cuproof::setup::Params;
+use cuproof::util::{write_atomically, DEFAULT_MAX_READ_BYTES};
+use super::CliError;
+
+/// A JSON value of the --json output; the CLI only emits small flat documents, so it encodes them
+/// itself rather than depending on the json feature
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// A Json::Object from `key => value` pairs whose values convert into Json
+macro_rules! json_object {
+    ($($key:expr => $value:expr),* $(,)?) => {
+        $crate::cli::Json::Object(vec![$(($key.to_string(), $crate::cli::Json::from($value))),*])
+    };
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Json { Json::Bool(b) }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Json { Json::Str(s.to_string()) }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Json { Json::Str(s) }
+}
+
+impl From<f64> for Json {
+    fn from(x: f64) -> Json { Json::Float(x) }
+}
+
+macro_rules! json_from_int {
+    ($($t:ty),*) => { $(impl From<$t> for Json { fn from(n: $t) -> Json { Json::Int(n as i128) } })* };
+}
+json_from_int!(u16, u32, u64, usize);
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(o: Option<T>) -> Json { o.map_or(Json::Null, Into::into) }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Json { Json::Array(v.into_iter().map(Into::into).collect()) }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Int(n) => write!(f, "{}", n),
+            Json::Float(x) if x.is_finite() => write!(f, "{}", x),
+            Json::Float(_) => write!(f, "null"),
+            Json::Str(s) => {
+                write!(f, "\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        '\t' => write!(f, "\\t")?,
+                        c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write!(f, "{}:{}", Json::Str(key.clone()), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Where a command's results go: human-readable lines on stdout, or with --json those lines on
+/// stderr and the fields set by the command as one JSON object on stdout when it finishes
+/// Keys are stable: setup sets params_path, bits, fingerprint, insecure, security_bits,
+/// transcript_path; commit sets commitment, commitment_path, blinding_path; prove sets proof_path,
+/// proof_id, commitment, prove_ms, blinding_path and nonce; verify (and setup / ceremony verify) sets result,
+/// error and, for proofs, proof, statement and mismatches; batch-prove sets total, proved, failed,
+/// results_path and results; dump sets epoch, fields, challenges and statement; convert sets kind,
+/// path, from, to, version, legacy and dropped; verify-opening sets result, commitment and
+/// recomputed; selftest sets fingerprint, passed, failed and checks; run sets workspace, passed,
+/// failed, skipped, params_loads and steps; fingerprint sets params_path,
+/// fingerprint and short; generators sets params_path, path, label, count, seed, fingerprint and
+/// params_fingerprint, and aggregate and verify --generators set generators; prove-offline-commit
+/// sets message_path, state_path, state_id and commitment, prove-offline-challenge bundle_path,
+/// state_id, label and commitment, prove-offline-finish proof_path, proof_id, commitment, state_id
+/// and spent_log; prove-lt sets proof_path, bits and prove_ms, verify-lt result, error and proof;
+/// prove and verify also set params_fingerprint, and with --time timing; failures replace everything
+/// with error.kind and error.message
+/// With --errors json (implied by --json) a failure, or an INVALID verdict, is also one JSON object on
+/// the last line of stderr, whose kind tells it apart (see CliError::to_json)
+/// With --quiet the human-readable lines, warnings and notes are dropped; the exit status, the JSON
+/// object and the error remain. Warnings and notes are tracing events (see logging)
+/// A path argument of `-` reads stdin or writes stdout (see read_input and write_output); stdout
+/// then carries only that data and the human-readable lines go to stderr
+pub struct Output {
+    pub(super) json: bool,
+    /// failures go to stderr as one JSON object (--errors json, implied by --json)
+    pub(super) errors_json: bool,
+    pub(super) quiet: bool,
+    pub(super) force_binary: bool,
+    pub(super) fields: Vec<(String, Json)>,
+    pub(super) stdio: Stdio,
+    /// what reads stdin (a `-` input, or the secrets of --value-stdin and --blinding-stdin), and what
+    /// is written to stdout
+    stdin_claim: Option<&'static str>,
+    stdout_claim: Option<&'static str>,
+    /// the params cache of the script a step belongs to (see run_script::run)
+    pub(super) params_cache: Option<ParamsCache>,
+    /// the passphrase of the encrypted files of this command, asked for once (see passphrase)
+    pub(super) passphrase: Option<Secret<String>>,
+}
+
+/// The standard streams behind `-` paths, --value-stdin and --blinding-stdin, and the report on
+/// stdout; tests run commands over in-memory buffers instead
+pub struct Stdio {
+    pub stdin: Box<dyn std::io::BufRead>,
+    pub stdout: Box<dyn Write>,
+    /// secrets read from a terminal are prompted for
+    pub stdin_tty: bool,
+    /// binary data is not written to a terminal without --force-binary
+    pub stdout_tty: bool,
+    /// the progress of a trusted setup is one redrawn line on a terminal, plain log lines otherwise
+    pub stderr_tty: bool,
+}
+
+impl Stdio {
+    /// The streams of this process
+    pub fn process() -> Stdio {
+        Stdio {
+            stdin: Box::new(std::io::stdin().lock()),
+            stdout: Box::new(std::io::stdout()),
+            stdin_tty: std::io::stdin().is_terminal(),
+            stdout_tty: std::io::stdout().is_terminal(),
+            stderr_tty: std::io::stderr().is_terminal(),
+        }
+    }
+}
+
+/// Stdout of a command run in-process, read back once its Output is gone: script steps and tests
+#[derive(Clone, Default)]
+pub(super) struct Captured(pub(super) std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+/// Params the steps of a script share: each path is loaded once, and again only once the file
+/// changes (a step writing it, or its size or modification time differing)
+#[derive(Default)]
+pub(super) struct ParamsCache {
+    pub(super) entries: HashMap<String, (Option<(u64, std::time::SystemTime)>, Params)>,
+    /// how often a file was actually loaded
+    pub(super) loads: usize,
+}
+
+/// Size and modification time of the file at `path`, which tell a rewritten file from the cached one
+pub(super) fn file_stamp(path: &str) -> Option<(u64, std::time::SystemTime)> {
+    std::fs::metadata(path).and_then(|m| Ok((m.len(), m.modified()?))).ok()
+}
+
+/// The claim on stdin of secrets, which may share it: v comes before the blinding
+pub(super) const STDIN_SECRETS: &str = "--value-stdin or --blinding-stdin";
+
+impl Output {
+    /// - params: json: whether --json was given; run sets the flags again from the parsed command line.
+    ///   stdio: Stdio::process(), or buffers
+    pub fn new(json: bool, stdio: Stdio) -> Output {
+        Output {
+            json, errors_json: json, quiet: false, force_binary: false, fields: Vec::new(), stdio, stdin_claim: None, stdout_claim: None,
+            params_cache: None, passphrase: None,
+        }
+    }
+
+    /// Also write failures as JSON without --json, for `--errors json` on a command line run refuses
+    pub fn json_errors(mut self, on: bool) -> Output {
+        self.errors_json |= on;
+        self
+    }
+
+    /// A human-readable line
+    pub(super) fn say(&mut self, line: impl fmt::Display) {
+        if self.quiet { return; }
+        if self.json || self.stdout_claim.is_some() {
+            eprintln!("{}", line);
+        } else {
+            let _ = writeln!(self.stdio.stdout, "{}", line);
+        }
+    }
+
+    /// Reserve stdin for `what`; only secrets share it
+    pub(super) fn claim_stdin(&mut self, what: &'static str) -> Result<(), CliError> {
+        match self.stdin_claim {
+            Some(earlier) if earlier != what || what != STDIN_SECRETS => {
+                Err(CliError::Usage(format!("{} and {} cannot both read stdin; give one of them a file", earlier, what)))
+            }
+            _ => {
+                self.stdin_claim = Some(what);
+                Ok(())
+            }
+        }
+    }
+
+    /// Reserve stdout for `what`, which neither the JSON object nor other data may share
+    pub(super) fn claim_stdout(&mut self, what: &'static str) -> Result<(), CliError> {
+        if self.json { return Err(CliError::Usage(format!("--json needs a file for the {}: stdout carries the JSON object", what))); }
+        if let Some(earlier) = self.stdout_claim {
+            return Err(CliError::Usage(format!("the {} and the {} cannot both be written to stdout", earlier, what)));
+        }
+        self.stdout_claim = Some(what);
+        Ok(())
+    }
+
+    /// The bytes of the input `path`, or of stdin for `-`; at most DEFAULT_MAX_READ_BYTES, like the loaders
+    pub(super) fn read_input(&mut self, path: &str, what: &'static str) -> Result<Vec<u8>, CliError> {
+        let limit = DEFAULT_MAX_READ_BYTES as u64 + 1;
+        let mut bytes = Vec::new();
+        let read = if path == "-" {
+            self.claim_stdin(what)?;
+            self.stdio.stdin.as_mut().take(limit).read_to_end(&mut bytes)
+        } else {
+            std::fs::File::open(path).and_then(|file| file.take(limit).read_to_end(&mut bytes))
+        };
+        let failed = |e| CliError::failed(format!("Failed to read {} {}", what, path), e).with("path", path).with("file", what);
+        read.map_err(failed)?;
+        if bytes.len() > DEFAULT_MAX_READ_BYTES {
+            return Err(failed(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("more than the {}-byte read limit", DEFAULT_MAX_READ_BYTES))));
+        }
+        Ok(bytes)
+    }
+
+    /// Write the output `path` with `write`, atomically, or stdout for `-`; `binary` output only goes
+    /// to a terminal with --force-binary
+    pub(super) fn write_output(&mut self, path: &str, what: &'static str, binary: bool, write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>) -> Result<(), CliError> {
+        let failed = |e| CliError::failed(format!("Failed to save {}", what), e).with("path", path).with("file", what);
+        if let Some(cache) = &mut self.params_cache { cache.entries.remove(path); }
+        if path != "-" { return write_atomically(path, &Default::default(), write).map_err(failed); }
+        self.claim_stdout(what)?;
+        if binary && self.stdio.stdout_tty && !self.force_binary {
+            return Err(CliError::Usage(format!("refusing to write the binary {} to a terminal; redirect stdout or pass --force-binary", what)));
+        }
+        write(&mut self.stdio.stdout).and_then(|_| self.stdio.stdout.flush()).map_err(failed)
+    }
+
+    /// Set `key` of the JSON object, replacing an earlier value
+    pub(super) fn set(&mut self, key: &str, value: impl Into<Json>) {
+        let value = value.into();
+        match self.fields.iter_mut().find(|(k, _)| k == key) {
+            Some(field) => field.1 = value,
+            None => self.fields.push((key.to_string(), value)),
+        }
+    }
+
+    /// Print the JSON object under --json
+    pub(super) fn finish(mut self) {
+        if self.json { let _ = writeln!(self.stdio.stdout, "{}", Json::Object(self.fields)); }
+        let _ = self.stdio.stdout.flush();
+    }
+}
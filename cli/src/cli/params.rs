@@ -0,0 +1,47 @@
+//! `cuproof params`: operations on existing params files
+
+use clap::{Args, Subcommand};
+use super::args::OutputFormat;
+use super::files::{load_params_arg, warn_if_insecure, write_params_arg};
+use super::{CliError, CommandOutcome, Output};
+
+/// Manage params files
+#[derive(Args)]
+#[command(arg_required_else_help = false)]
+pub(super) struct ParamsArgs {
+    #[command(subcommand)]
+    command: ParamsCommand,
+}
+
+#[derive(Subcommand)]
+enum ParamsCommand {
+    Rotate(RotateArgs),
+}
+
+/// Derive fresh generators under a label; proofs from earlier epochs no longer verify
+#[derive(Args)]
+struct RotateArgs {
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    #[arg(value_name = "label")]
+    label: String,
+    #[arg(value_name = "out_params")]
+    out_params: String,
+}
+
+pub(super) fn run_params(m: &ParamsArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let ParamsCommand::Rotate(m) = &m.command;
+    let out_params = m.out_params.as_str();
+    let params = load_params_arg(out, &m.params_path)?;
+    warn_if_insecure(&params);
+    let rotated = params.rotate_generators(&m.label);
+    write_params_arg(out, out_params, &rotated, OutputFormat::Bin, None)?;
+    out.say(format_args!(
+        "Saved params at generator epoch {} to {} (fingerprint {}); proofs from epoch {} no longer verify against them",
+        rotated.epoch(), out_params, rotated.fingerprint_hex(), params.epoch()
+    ));
+    out.set("params_path", out_params);
+    out.set("epoch", rotated.epoch());
+    out.set("fingerprint", rotated.fingerprint_hex().to_string());
+    Ok(CommandOutcome::Done)
+}
@@ -0,0 +1,69 @@
+//! The passphrase of encrypted secret files (see cuproof::encrypted): `$CUPROOF_PASSPHRASE`, or
+//! asked for on the terminal without echo, once per command
+
+use std::env;
+use cuproof::secret::Secret;
+use super::{CliError, Output};
+
+/// The environment variable read before any prompt
+pub(super) const PASSPHRASE_ENV: &str = "CUPROOF_PASSPHRASE";
+
+/// The passphrase for the encrypted `what`: the one this command already has, else
+/// $CUPROOF_PASSPHRASE, else typed on the terminal, twice when `confirm` (a file about to be encrypted)
+/// - returns: the passphrase; a usage error when it is empty, or when it is neither in the
+///   environment nor can be asked for
+pub(super) fn passphrase<'a>(out: &'a mut Output, what: &str, confirm: bool) -> Result<&'a Secret<String>, CliError> {
+    if out.passphrase.is_none() {
+        let given = match env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) => Secret::new(passphrase),
+            Err(env::VarError::NotUnicode(_)) => return Err(CliError::Usage(format!("{} is not UTF-8", PASSPHRASE_ENV))),
+            Err(env::VarError::NotPresent) => prompt_twice(what, confirm)?,
+        };
+        if given.expose().is_empty() { return Err(CliError::Usage(format!("the passphrase for the {} is empty", what))); }
+        out.passphrase = Some(given);
+    }
+    Ok(out.passphrase.as_ref().expect("set above"))
+}
+
+/// Ask for the passphrase on the terminal, and again to confirm it when `confirm`
+fn prompt_twice(what: &str, confirm: bool) -> Result<Secret<String>, CliError> {
+    let unavailable = |e: std::io::Error| {
+        CliError::Usage(format!("no passphrase for the {}: set {} or run on a terminal ({})", what, PASSPHRASE_ENV, e))
+    };
+    let first = prompt(&format!("Passphrase for the {}", what)).map_err(unavailable)?;
+    if confirm && prompt("Repeat the passphrase").map_err(unavailable)?.expose() != first.expose() {
+        return Err(CliError::Usage("the passphrases do not match".to_string()));
+    }
+    Ok(first)
+}
+
+/// Print `label` on the controlling terminal and read a line from it with echo off
+#[cfg(unix)]
+fn prompt(label: &str) -> std::io::Result<Secret<String>> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::fd::AsRawFd;
+    let mut tty = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    write!(tty, "{}: ", label)?;
+    tty.flush()?;
+    let fd = tty.as_raw_fd();
+    let mut saved = std::mem::MaybeUninit::<libc::termios>::uninit();
+    if unsafe { libc::tcgetattr(fd, saved.as_mut_ptr()) } != 0 { return Err(std::io::Error::last_os_error()); }
+    let saved = unsafe { saved.assume_init() };
+    let mut silent = saved;
+    silent.c_lflag &= !libc::ECHO;
+    silent.c_lflag |= libc::ECHONL;
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &silent) } != 0 { return Err(std::io::Error::last_os_error()); }
+    let mut line = Secret::new(String::new());
+    let read = BufReader::new(&tty).read_line(line.expose_mut());
+    // the echo comes back even when the read failed
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &saved) };
+    read?;
+    let len = line.expose().trim_end_matches(['\n', '\r']).len();
+    line.expose_mut().truncate(len);
+    Ok(line)
+}
+
+#[cfg(not(unix))]
+fn prompt(_label: &str) -> std::io::Result<Secret<String>> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "no passphrase prompt on this platform"))
+}
@@ -0,0 +1,191 @@
+//! `cuproof prove`: prove a <= v <= b, for a fresh commitment to v or for one written by commit
+
+use clap::{ArgGroup, Args};
+use num_bigint::BigInt;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand::rngs::OsRng;
+use rand_chacha::ChaCha20Rng;
+use cuproof::commitment::commit;
+use cuproof::range_proof::{prove_with_context, prove_with_context_and_rng, synthetic_nonce_rng, random_blinding, proof_id, Cuproof, ProveError};
+use cuproof::secret::Secret;
+use cuproof::setup::Params;
+use cuproof::util::{bigint_to_hex, read_commitment, write_blinding, ReadLimits};
+use super::args::{blinding_secret, num_arg, CompressArg, number, parse_format, parse_nonce, parse_prove_seed, read_secret, BlindingSecret, HexBytes, OutputFormat, RadixArg, SecretSource, TimeArg, ValueSecret};
+use super::files::{load_params_arg, say_params, warn_blinding_file, warn_if_insecure, warn_if_legacy, write_proof_arg, write_secret_output};
+use super::{usage, CliError, CommandOutcome, Output};
+
+/// Prove a <= v <= b, for a fresh commitment to v or for one written by commit
+#[derive(Args)]
+#[command(
+    override_usage = "cuproof prove [OPTIONS] <params_path> <a> <b> [<v>] <proof_path|->\n       cuproof prove [OPTIONS] <params_path> --range <a> <b> [<v>] <proof_path|->",
+    allow_negative_numbers = true,
+    group(ArgGroup::new("value").args(["value_stdin", "value_env"])),
+    group(ArgGroup::new("blinding-source").args(["blinding", "blinding_in", "blinding_stdin", "blinding_env"])),
+    after_help = "A drawn blinding must be saved with --blinding-out, or discarded on purpose with --ephemeral. \
+        With --commitment the opening is checked before proving, and the proof is for that commitment. \
+        With --seed or --deterministic the proof id is printed, so a rerun can be matched against it.\n\nExamples:\n  cuproof prove --blinding-out r.bin params.bin 10 100 42 v.proof\n  cuproof prove --value-env V --commitment c.bin --blinding-in r.bin params.bin 10 100 v.proof\n  cuproof prove --compress=19 --blinding-out r.bin params.bin 10 100 42 v.proof\n  cuproof prove params.bin --commitment c.bin --blinding r.bin --range 10 100 42 v.proof\n  cuproof prove --armor --ephemeral --value-stdin params.bin 0x0 0xffff -"
+)]
+pub(super) struct ProveArgs {
+    /// Encoding of the written file (json needs the json feature)
+    #[arg(long, value_name = "json|bin", default_value = "bin", value_parser = parse_format)]
+    format: OutputFormat,
+    /// Write the proof as ASCII armor (armor feature)
+    #[arg(long)]
+    armor: bool,
+    #[command(flatten)]
+    compress: CompressArg,
+    #[command(flatten)]
+    pub(super) value: ValueSecret,
+    /// Prove the range of this commitment, opened by the blinding
+    #[arg(long, value_name = "commitment_path", requires = "blinding-source")]
+    commitment: Option<String>,
+    /// The blinding of the commitment, or when not a number a file holding it, as --blinding-in [default: drawn from the OS CSPRNG]
+    #[arg(long, value_name = "r|r_path")]
+    blinding: Option<String>,
+    /// Read the blinding from a file written by commit or --blinding-out
+    #[arg(long, value_name = "r_path")]
+    blinding_in: Option<String>,
+    #[command(flatten)]
+    pub(super) blinding_secret: BlindingSecret,
+    /// Save the drawn blinding, which opens the commitment in the proof
+    #[arg(long, value_name = "r_path", conflicts_with = "blinding-source")]
+    blinding_out: Option<String>,
+    /// Encrypt the --blinding-out file under a passphrase: $CUPROOF_PASSPHRASE, else typed on the terminal
+    #[arg(long, requires = "blinding_out")]
+    encrypt: bool,
+    /// Discard the drawn blinding: nobody can open the commitment or prove anything else about it
+    #[arg(long, conflicts_with_all = ["blinding-source", "blinding_out"])]
+    ephemeral: bool,
+    /// Derive all randomness, a drawn blinding included, from this 32-byte seed; safe only if the seed is secret and never reused
+    #[arg(long, value_name = "hex32", value_parser = parse_prove_seed)]
+    seed: Option<[u8; 32]>,
+    /// Derive the prover's randomness from v, the blinding, the range and the params: the same inputs give the same proof
+    #[arg(long, conflicts_with = "seed", requires = "blinding-source")]
+    deterministic: bool,
+    /// Bind the proof to this nonce from the verifier; it then verifies only with the same verify --nonce
+    #[arg(long, value_name = "hex", value_parser = parse_nonce)]
+    nonce: Option<HexBytes>,
+    /// Set this context label in the proof's statement; with --nonce the challenges are bound to it too, and verify --statement must expect it
+    #[arg(long, value_name = "string", default_value = "")]
+    label: String,
+    #[command(flatten)]
+    pub(super) time: TimeArg,
+    /// The range, instead of <a> <b>
+    #[arg(long, num_args = 2, value_names = ["a", "b"], allow_hyphen_values = true, value_parser = number(true))]
+    range: Option<Vec<String>>,
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    // with --range, a holds v or the proof path and the later positionals move up; run_prove parses them
+    /// Lower end of the range
+    #[arg(value_name = "a", allow_hyphen_values = true)]
+    pub(super) a: String,
+    /// Upper end of the range, inclusive
+    #[arg(value_name = "b", allow_hyphen_values = true)]
+    pub(super) b: Option<String>,
+    /// The value, left out with --value-stdin or --value-env
+    #[arg(value_name = "v|proof_path", allow_hyphen_values = true)]
+    pub(super) v: Option<String>,
+    /// Where to write the proof; - writes stdout, armored when it is a terminal
+    #[arg(value_name = "proof_path|-")]
+    pub(super) proof_path: Option<String>,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+pub(super) fn run_prove(m: &ProveArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (format, armor) = (m.format, m.armor);
+    if armor && format != OutputFormat::Bin { return Err(usage("--armor cannot be combined with --format json")); }
+    let compress = m.compress.level();
+    if compress.is_some() && (armor || format != OutputFormat::Bin) { return Err(usage("--compress applies to the binary format, not to --armor or --format json")); }
+    if armor && !cfg!(feature = "armor") { return Err(usage("--armor requires the armor feature")); }
+    let radix = m.radix.get();
+    // the positionals after the params: <a> <b> unless --range gives the range, then <v> unless it
+    // comes from stdin or the environment, and the proof path last
+    let positionals = [Some(m.a.as_str()), m.b.as_deref(), m.v.as_deref(), m.proof_path.as_deref()].into_iter().flatten().collect::<Vec<_>>();
+    let (range, rest) = match &m.range {
+        Some(range) => ((range[0].as_str(), range[1].as_str()), &positionals[..]),
+        None if positionals.len() >= 2 => ((positionals[0], positionals[1]), &positionals[2..]),
+        None => return Err(usage("prove needs <a> <b>, or --range <a> <b>")),
+    };
+    let (value_source, proof_path) = match (m.value.source(None), rest) {
+        (Some(source), [proof_path]) => (source, *proof_path),
+        (None, [v, proof_path]) => (SecretSource::Arg(v.to_string()), *proof_path),
+        (Some(_), [_, _]) => return Err(usage("prove takes no <v> argument with --value-stdin or --value-env")),
+        (None, [] | [_]) | (Some(_), []) => return Err(usage("prove needs <v> before <proof_path>, or --value-stdin or --value-env")),
+        _ => return Err(usage("prove takes the range once: as <a> <b> or as --range <a> <b>")),
+    };
+    let given = m.blinding.as_deref().map(|r| blinding_secret(r, radix)).or_else(|| m.blinding_in.clone().map(SecretSource::File));
+    let blinding_source = m.blinding_secret.source(given);
+    let blinding_out = m.blinding_out.as_deref();
+    if blinding_source.is_none() && blinding_out.is_none() && !m.ephemeral {
+        return Err(usage(
+            "prove would draw a blinding and discard it, so nobody could open the commitment in the proof again;\n\
+             save it with --blinding-out <r_path>, supply one with --blinding, --blinding-in, --blinding-stdin or\n\
+             --blinding-env, or pass --ephemeral to discard it anyway",
+        ));
+    }
+    let params_path = m.params_path.as_str();
+    let a = num_arg("a", range.0, radix, true)?;
+    let b = num_arg("b", range.1, radix, true)?;
+    let v = read_secret(out, "v", value_source, radix, true)?;
+    let r = blinding_source.map(|source| read_secret(out, "blinding", source, radix, false)).transpose()?;
+    if out.json && proof_path == "-" { return Err(usage("--json needs a proof path: stdout carries the JSON object")); }
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    if m.seed.is_some() {
+        tracing::warn!("a --seed proof is only safe if the seed is secret and used for no other proof: anyone\n\
+            holding the seed learns the prover's randomness, and two proofs from one seed leak v.");
+    }
+    let started = std::time::Instant::now();
+    let mut seeded = m.seed.map(ChaCha20Rng::from_seed);
+    // NOTE: r is the commitment blinding and must stay secret to the prover
+    let r = match (r, &mut seeded) {
+        (Some(r), _) => r,
+        (None, Some(rng)) => Secret::new(random_blinding(rng)),
+        (None, None) => Secret::new(random_blinding(&mut OsRng)),
+    };
+    // the parser lets --deterministic through only with a blinding, which the witness must include
+    if m.deterministic { seeded = Some(synthetic_nonce_rng(v.expose(), r.expose(), &a, &b, &params)); }
+    let (label, nonce) = (m.label.as_str(), m.nonce.as_deref().unwrap_or_default());
+    let proof = match (m.commitment.as_deref(), seeded.as_mut()) {
+        (Some(commitment_path), Some(rng)) => prove_for_commitment_file(out, &params, &a, &b, v.expose(), commitment_path, r.expose(), label, nonce, rng)?,
+        (Some(commitment_path), None) => prove_for_commitment_file(out, &params, &a, &b, v.expose(), commitment_path, r.expose(), label, nonce, &mut OsRng)?,
+        (None, Some(rng)) => prove_with_context_and_rng(v.expose(), r.expose(), &a, &b, &params, label, nonce, rng).map_err(|e| CliError::failed("Failed to prove", e))?,
+        (None, None) => prove_with_context(v.expose(), r.expose(), &a, &b, &params, label, nonce).map_err(|e| CliError::failed("Failed to prove", e))?,
+    };
+    let prove_ms = started.elapsed().as_secs_f64() * 1000.0;
+    // the blinding is saved first: a proof whose blinding was lost is what --blinding-out prevents
+    if let Some(path) = blinding_out {
+        write_secret_output(out, path, "blinding", m.encrypt, |w| write_blinding(w, r.expose()).map(|_| ()))?;
+        warn_blinding_file(path);
+    }
+    write_proof_arg(out, proof_path, &proof, &params, format, armor, compress)?;
+    if proof_path != "-" { out.say(format_args!("Saved proof to {}", proof_path)); }
+    say_params(out, params_path, &params);
+    let proof_id = proof_id(&proof);
+    if seeded.is_some() { out.say(format_args!("Proof id: {}", proof_id)); }
+    out.set("proof_path", proof_path);
+    out.set("proof_id", proof_id);
+    out.set("commitment", bigint_to_hex(&proof.C));
+    out.set("prove_ms", prove_ms);
+    out.set("blinding_path", blinding_out);
+    out.set("nonce", m.nonce.as_ref().map(hex::encode));
+    out.set("label", label);
+    Ok(CommandOutcome::Done)
+}
+
+/// `prove --commitment`: prove a <= v <= b for the commitment saved by `commit`, opened by the
+/// blinding r, bound to `label` and the verifier's `nonce` when the nonce is not empty
+/// - returns: the proof, whose C is the saved commitment; ProveError::CommitmentMismatch when v and
+///   r do not open it
+#[allow(clippy::too_many_arguments)]
+pub(super) fn prove_for_commitment_file<R: RngCore + CryptoRng>(out: &mut Output, params: &Params, a: &BigInt, b: &BigInt, v: &BigInt, commitment_path: &str, r: &BigInt, label: &str, nonce: &[u8], rng: &mut R) -> Result<Cuproof, CliError> {
+    let bytes = out.read_input(commitment_path, "commitment")?;
+    let commitment = read_commitment(&mut bytes.as_slice(), params, &ReadLimits::default())
+        .map_err(|e| CliError::failed(format!("Failed to load commitment {}", commitment_path), e))?;
+    // as cuproof_prove_for_commitment_with_rng, which takes no nonce
+    if commit(params, v, r) != commitment { return Err(CliError::failed("Failed to prove", ProveError::CommitmentMismatch)); }
+    prove_with_context_and_rng(v, r, a, b, params, label, nonce, rng).map_err(|e| CliError::failed("Failed to prove", e))
+}
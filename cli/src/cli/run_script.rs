@@ -0,0 +1,190 @@
+//! `cuproof run`: the steps of a TOML or JSON script in-process, each checked against its expectation (see script)
+
+use std::collections::HashMap;
+use std::env;
+use std::time::Instant;
+use clap::Args;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use cuproof::error::SerializationError;
+use crate::script::{self, Expect, Script};
+use super::output::{Captured, ParamsCache};
+use super::{run, CliError, CommandOutcome, Json, Output, Stdio};
+
+/// Run a TOML or JSON script of commands, checking each step against its expectation (see script)
+#[derive(Args)]
+#[command(after_help = format!("Params are loaded once and shared by the steps. A step's --json keys are ${{step.key}} in later steps, \
+    so a commit step's blinding_path or commitment can be passed on. Besides the commands, a step may run `{}`. \
+    Prints PASS or FAIL per step and exits 0 only if every step met its expectation; steps after the first \
+    that did not are skipped unless keep_going is set.\n\nExample:\n  cuproof run testdata/scripts/roundtrip.toml", TAMPER_USAGE))]
+pub(super) struct RunArgs {
+    /// The steps, with expect ok, valid, invalid or error; .json for JSON, TOML otherwise
+    #[arg(value_name = "script")]
+    script: String,
+    /// Where ${name} files go [default: the script's workspace, else a temporary directory removed afterwards]
+    #[arg(long, value_name = "dir")]
+    workspace: Option<String>,
+    /// Keep the temporary workspace and print its path
+    #[arg(long)]
+    keep: bool,
+}
+
+/// The script-only step that corrupts a file, as run's help shows it
+const TAMPER_USAGE: &str = "tamper <in> <out> [offset]";
+
+/// How a script step ended, as the summary names it
+struct StepResult {
+    /// "valid", "invalid", "ok", "failed" (a non-zero status without an error), "error", or
+    /// "unexpanded" when a ${...} reference has no value
+    outcome: &'static str,
+    /// the error's kind (see CliError::to_json)
+    kind: Option<&'static str>,
+    message: Option<String>,
+    /// the keys the step set in its --json object
+    fields: Vec<(String, Json)>,
+    stdout: Vec<u8>,
+}
+
+/// Run the steps of a script (see script) with a shared params cache, in the workspace
+pub(super) fn run_script(m: &RunArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let path = m.script.as_str();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::failed(format!("Failed to read script {}", path), e).with("path", path).with("file", "script"))?;
+    let script = script::parse_script(&text, path.ends_with(".json"))
+        .map_err(|e| CliError::failed(format!("Malformed script {}", path), SerializationError::Invalid(e)).with("path", path))?;
+    let base = std::path::Path::new(path).parent().unwrap_or(std::path::Path::new("."));
+    let (workspace, temporary) = match (m.workspace.as_deref(), &script.workspace) {
+        (Some(dir), _) => (std::path::PathBuf::from(dir), false),
+        (None, Some(dir)) => (base.join(dir), false),
+        (None, None) => (env::temp_dir().join(format!("cuproof_run_{}_{:x}", std::process::id(), OsRng.next_u64())), true),
+    };
+    std::fs::create_dir_all(&workspace).map_err(|e| CliError::failed(format!("Failed to create {}", workspace.display()), e))?;
+    if temporary && m.keep { out.say(format_args!("Workspace: {}", workspace.display())); }
+
+    let mut cache = Some(ParamsCache::default());
+    let mut named: HashMap<&str, Vec<(String, Json)>> = HashMap::new();
+    let mut reports = Vec::new();
+    let (mut failed, mut skipped) = (0, 0);
+    for step in &script.steps {
+        if failed > 0 && !script.keep_going {
+            out.say(format_args!("SKIP  {}", step.name));
+            reports.push(json_object! { "name" => step.name.as_str(), "result" => "skip" });
+            skipped += 1;
+            continue;
+        }
+        let start = Instant::now();
+        let args = step.args.iter().map(|arg| script::expand(arg, &mut |name| script_var(name, &script, &named, &workspace))).collect::<Result<Vec<_>, _>>();
+        let result = match &args {
+            Ok(args) => run_step(args, &mut cache),
+            Err(e) => StepResult { outcome: "unexpanded", kind: None, message: Some(e.clone()), fields: Vec::new(), stdout: Vec::new() },
+        };
+        let ms = start.elapsed().as_secs_f64() * 1000.0;
+        let held = match step.expect {
+            Expect::Ok => matches!(result.outcome, "ok" | "valid"),
+            Expect::Valid => result.outcome == "valid",
+            Expect::Invalid => result.outcome == "invalid",
+            Expect::Error => result.kind.is_some_and(|kind| step.error.as_deref().is_none_or(|want| kind == want || kind.split('.').next() == Some(want))),
+        };
+        let expected = match &step.error {
+            Some(kind) => format!("error {}", kind),
+            None => step.expect.name().to_string(),
+        };
+        let outcome = match result.kind {
+            Some(kind) => format!("error {}", kind),
+            None => result.outcome.to_string(),
+        };
+        if held {
+            out.say(format_args!("PASS  {}: {} ({:.1} ms)", step.name, outcome, ms));
+        } else {
+            failed += 1;
+            match &result.message {
+                Some(message) => out.say(format_args!("FAIL  {}: {}, expected {}: {}", step.name, outcome, expected, message)),
+                None => out.say(format_args!("FAIL  {}: {}, expected {}", step.name, outcome, expected)),
+            }
+            for line in String::from_utf8_lossy(&result.stdout).lines() { out.say(format_args!("      {}", line)); }
+        }
+        reports.push(json_object! {
+            "name" => step.name.as_str(),
+            "result" => if held { "pass" } else { "fail" },
+            "args" => args.unwrap_or_default(),
+            "expected" => expected,
+            "outcome" => result.outcome,
+            "kind" => result.kind,
+            "message" => result.message,
+            "ms" => ms,
+        });
+        if step.named { named.insert(step.name.as_str(), result.fields); }
+    }
+    let loads = cache.map_or(0, |cache| cache.loads);
+    let times = if loads == 1 { "once".to_string() } else { format!("{} times", loads) };
+    out.say(format_args!("{} steps: {} as expected, {} not, {} skipped; params loaded {}", script.steps.len(), script.steps.len() - failed - skipped, failed, skipped, times));
+    if temporary && !m.keep { let _ = std::fs::remove_dir_all(&workspace); }
+    out.set("workspace", workspace.display().to_string());
+    out.set("passed", script.steps.len() - failed - skipped);
+    out.set("failed", failed);
+    out.set("skipped", skipped);
+    out.set("params_loads", loads);
+    out.set("steps", reports);
+    Ok(CommandOutcome::Script { failed: failed + skipped })
+}
+
+/// The text of `${name}` in a step of `script` (see script)
+fn script_var(name: &str, script: &Script, named: &HashMap<&str, Vec<(String, Json)>>, workspace: &std::path::Path) -> Result<String, String> {
+    if let Some((step, key)) = name.split_once('.') {
+        let fields = named.get(step).ok_or_else(|| format!("no earlier step is named {}", step))?;
+        return match fields.iter().find(|(k, _)| k == key).map(|(_, value)| value) {
+            Some(Json::Str(s)) => Ok(s.clone()),
+            Some(value @ (Json::Int(_) | Json::Bool(_) | Json::Float(_))) => Ok(value.to_string()),
+            Some(_) => Err(format!("{} of step {} is not a plain value", key, step)),
+            None => Err(format!("step {} set no {}", step, key)),
+        };
+    }
+    if name == "workspace" { return Ok(workspace.display().to_string()); }
+    if let Some(value) = script.vars.get(name) { return Ok(value.clone()); }
+    script::check_name(name)?;
+    Ok(workspace.join(name).display().to_string())
+}
+
+/// Run one step in-process over an empty stdin and a captured stdout, sharing `cache` with the others
+fn run_step(args: &[String], cache: &mut Option<ParamsCache>) -> StepResult {
+    let result = |outcome, kind, message, fields, stdout| StepResult { outcome, kind, message, fields, stdout };
+    match args.first().map(String::as_str) {
+        Some("tamper") => return match tamper(&args[1..]) {
+            Ok(()) => result("ok", None, None, Vec::new(), Vec::new()),
+            Err(e) => result("error", Some(e.code()), Some(e.to_string()), Vec::new(), Vec::new()),
+        },
+        Some("run") => return result("error", Some("usage"), Some("a script cannot run scripts".to_string()), Vec::new(), Vec::new()),
+        _ => {}
+    }
+    let stdout = Captured::default();
+    let stdio = Stdio { stdin: Box::new(std::io::empty()), stdout: Box::new(stdout.clone()), stdin_tty: false, stdout_tty: false, stderr_tty: false };
+    let mut out = Output::new(false, stdio);
+    out.params_cache = cache.take();
+    let outcome = run(std::iter::once("cuproof".to_string()).chain(args.iter().cloned()).collect(), &mut out);
+    *cache = out.params_cache.take();
+    let fields = std::mem::take(&mut out.fields);
+    drop(out);
+    let stdout = stdout.0.take();
+    match outcome {
+        Ok(CommandOutcome::Valid | CommandOutcome::Opening { matched: true }) => result("valid", None, None, fields, stdout),
+        Ok(CommandOutcome::Invalid(reason)) => result("invalid", None, reason.map(|r| r.to_string()), fields, stdout),
+        Ok(CommandOutcome::Opening { matched: false }) => result("invalid", None, None, fields, stdout),
+        Ok(outcome) if outcome.exit_code() == 0 => result("ok", None, None, fields, stdout),
+        Ok(_) => result("failed", None, None, fields, stdout),
+        Err(e) => result("error", Some(e.code()), Some(e.to_string()), fields, stdout),
+    }
+}
+
+/// `tamper <in> <out> [offset]`: copy a file, flipping the low bit of the byte at offset (the middle one by default)
+fn tamper(args: &[String]) -> Result<(), CliError> {
+    let [input, output, rest @ ..] = args else { return Err(CliError::Usage(format!("usage: {}", TAMPER_USAGE))) };
+    let mut bytes = std::fs::read(input).map_err(|e| CliError::failed(format!("Failed to read {}", input), e).with("path", input.as_str()))?;
+    let offset = match rest {
+        [] => bytes.len() / 2,
+        [offset] => offset.parse().map_err(|_| CliError::Usage(format!("tamper: '{}' is not a byte offset", offset)))?,
+        _ => return Err(CliError::Usage(format!("usage: {}", TAMPER_USAGE))),
+    };
+    let byte = bytes.get_mut(offset).ok_or_else(|| CliError::Usage(format!("tamper: {} has no byte {}", input, offset)))?;
+    *byte ^= 1;
+    std::fs::write(output, &bytes).map_err(|e| CliError::failed(format!("Failed to write {}", output), e).with("path", output.as_str()))
+}
@@ -0,0 +1,52 @@
+//! `cuproof selftest`: known answers, then prove, verify and reject a tampered proof (see cuproof::selftest)
+
+use clap::Args;
+use rand::rngs::OsRng;
+use cuproof::selftest::{run_selftest, Check};
+use cuproof::setup::insecure_test_setup;
+use super::files::{load_params_arg, warn_if_legacy};
+use super::{CliError, CommandOutcome, Output};
+
+/// Check this binary and the given params: transcript known answers, then prove, verify and reject a tampered proof
+#[derive(Args)]
+#[command(after_help = "Prints PASS or FAIL per check and exits 0 only if every check passes; a few seconds at 2048 bits.\n\nExample:\n  cuproof selftest params.bin")]
+pub(super) struct SelftestArgs {
+    /// Params to check [default: the built-in INSECURE 512-bit test params]
+    #[arg(value_name = "params_path")]
+    params_path: Option<String>,
+}
+
+pub(super) fn run_selftest_cli(m: &SelftestArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let params = match m.params_path.as_deref() {
+        Some(path) => {
+            warn_if_legacy(path);
+            let params = load_params_arg(out, path)?;
+            out.say(format_args!("Checking {}-bit params {} (fingerprint {})", params.bits(), path, params.fingerprint_hex()));
+            params
+        }
+        None => {
+            out.say("Checking the built-in INSECURE 512-bit test params");
+            insecure_test_setup(512)
+        }
+    };
+    let checks = run_selftest(&params, &mut OsRng);
+    let failed = checks.iter().filter(|c| c.result.is_err()).count();
+    let ms = |c: &Check| c.elapsed.as_secs_f64() * 1000.0;
+    for c in &checks {
+        match &c.result {
+            Ok(()) => out.say(format_args!("PASS  {} ({:.1} ms)", c.name, ms(c))),
+            Err(e) => out.say(format_args!("FAIL  {}: {}", c.name, e)),
+        }
+    }
+    out.say(format_args!("{} checks: {} passed, {} failed", checks.len(), checks.len() - failed, failed));
+    out.set("fingerprint", params.fingerprint_hex().to_string());
+    out.set("passed", checks.len() - failed);
+    out.set("failed", failed);
+    out.set("checks", checks.iter().map(|c| json_object! {
+        "name" => c.name,
+        "result" => if c.result.is_ok() { "pass" } else { "fail" },
+        "error" => c.result.clone().err(),
+        "ms" => ms(c),
+    }).collect::<Vec<_>>());
+    Ok(CommandOutcome::SelfTest { failed })
+}
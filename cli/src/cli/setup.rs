@@ -0,0 +1,388 @@
+//! `cuproof setup`: generate, import or check public parameters, either as `setup <out> --mode ...`
+//! or as one of the setup fast | trusted | verify | import subcommands
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+use clap::{ArgGroup, Args, Subcommand, ValueEnum};
+use num_bigint::BigInt;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use cuproof::setup::{trusted_setup_with_transcript, trusted_setup_resumable_with_transcript, insecure_test_setup, insecure_seeded_setup, params_from_modulus_with, check_modulus_bits, security_level_bits, Params, SetupError, MAX_MODULUS_BITS, SetupProgress, SetupPhase, expected_setup_candidates, INSECURE_TEST_MODULUS_BITS};
+use cuproof::setup_transcript::{verify_setup_transcript, SetupTranscript};
+use cuproof::util::{load_setup_transcript, save_setup_transcript};
+use super::args::{count, hex_number, parse_format, parse_seed, workers, CompressArg, HexBytes, OutputFormat};
+use super::files::{file_only, load_params_arg, warn_if_insecure, write_params_arg};
+use super::{usage, verdict, CliError, CommandOutcome, Output};
+
+/// Generate, import or check public parameters
+#[derive(Args)]
+#[command(
+    override_usage = "cuproof setup [OPTIONS] <out>\n       cuproof setup <fast|trusted|verify|import> [OPTIONS] <params_path> ...",
+    subcommand_negates_reqs = true,
+    after_help = "An existing params file is only replaced with --force.\n\nExamples:\n  cuproof setup --bits 3072 --threads 8 params.bin\n  cuproof setup --mode fast --allow-insecure --bits 256 --seed 00c0ffee params.bin\n  cuproof setup --mode import --modulus-hex c7f1...9b params.bin\n  cuproof setup fast params.bin\n  cuproof setup trusted --bits 4096 --compress params.bin\n  cuproof setup verify params.bin params.bin.transcript"
+)]
+pub(super) struct SetupArgs {
+    /// Encoding of the written file (json needs the json feature)
+    #[arg(long, value_name = "json|bin", default_value = "bin", value_parser = parse_format, global = true)]
+    format: OutputFormat,
+    /// Overwrite an existing params file
+    #[arg(long, global = true)]
+    force: bool,
+    /// Accept --mode fast and moduli below 2048 bits, for tests only
+    #[arg(long, global = true)]
+    allow_insecure: bool,
+    #[command(flatten)]
+    compress: CompressArg,
+    /// Where to write the params
+    #[arg(value_name = "out", required = true)]
+    out: Option<String>,
+    /// fast: INSECURE test params; trusted: a fresh modulus; import: an existing one [default: trusted]
+    #[arg(long, value_name = "fast|trusted|import")]
+    mode: Option<SetupMode>,
+    /// Modulus size [default: 512 with --mode fast, 2048 with trusted, that of the modulus with import]
+    #[arg(long, value_name = "N", value_parser = count(1, MAX_MODULUS_BITS))]
+    bits: Option<usize>,
+    /// Derive --mode fast params from this seed, the same ones on every run [default: drawn from the OS CSPRNG]
+    #[arg(long, value_name = "hex", value_parser = parse_seed)]
+    seed: Option<HexBytes>,
+    /// The modulus for --mode import
+    #[arg(long, value_name = "hex", conflicts_with = "seed", value_parser = hex_number)]
+    modulus_hex: Option<BigInt>,
+    #[command(subcommand)]
+    pub(super) command: Option<SetupCommand>,
+}
+
+/// --mode of `setup <out>`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SetupMode {
+    Fast,
+    Trusted,
+    Import,
+}
+
+#[derive(Subcommand)]
+pub(super) enum SetupCommand {
+    Fast(SetupFastArgs),
+    Trusted(SetupTrustedArgs),
+    Verify(SetupVerifyArgs),
+    Import(SetupImportArgs),
+}
+
+/// INSECURE test params: the factorization of n is public
+#[derive(Args)]
+pub(super) struct SetupFastArgs {
+    /// Modulus size [default: 512]
+    #[arg(long, value_name = "512|1024", value_parser = count(1, usize::MAX))]
+    bits: Option<usize>,
+    /// Where to write the params
+    #[arg(value_name = "params_path")]
+    params_path: String,
+}
+
+/// Params from a fresh RSA modulus whose factors are discarded; also writes <params_path>.transcript
+#[derive(Args)]
+pub(super) struct SetupTrustedArgs {
+    /// Modulus size [default: 2048]
+    #[arg(long, value_name = "2048|3072|4096", value_parser = count(1, usize::MAX))]
+    pub(super) bits: Option<usize>,
+    /// Checkpoint to, and continue from, this file (searches serially)
+    #[arg(long, value_name = "checkpoint")]
+    resume: Option<String>,
+    /// Where to write the params
+    #[arg(value_name = "params_path")]
+    pub(super) params_path: String,
+}
+
+/// Check params against the transcript of their trusted setup
+#[derive(Args)]
+pub(super) struct SetupVerifyArgs {
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    #[arg(value_name = "transcript_path")]
+    transcript_path: String,
+}
+
+/// Params over an existing modulus
+#[derive(Args)]
+#[command(group(ArgGroup::new("modulus").args(["modulus_hex", "known"]).required(true)))]
+pub(super) struct SetupImportArgs {
+    #[arg(long, value_name = "hex", value_parser = hex_number)]
+    modulus_hex: Option<BigInt>,
+    /// A published modulus (known-moduli feature)
+    #[arg(long, value_name = "name")]
+    known: Option<String>,
+    /// Where to write the params
+    #[arg(value_name = "params_path")]
+    params_path: String,
+}
+
+impl SetupCommand {
+    fn name(&self) -> &'static str {
+        match self {
+            SetupCommand::Fast(_) => "fast",
+            SetupCommand::Trusted(_) => "trusted",
+            SetupCommand::Verify(_) => "verify",
+            SetupCommand::Import(_) => "import",
+        }
+    }
+}
+
+/// `threads` is the global --threads, for setup trusted
+pub(super) fn run_setup(m: &SetupArgs, threads: Option<usize>, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let Some(command) = &m.command else { return run_setup_mode(m, threads, out) };
+    // The options of `setup <out>` parse before a mode too, where they would be ignored
+    let given = [("mode", m.mode.is_some()), ("bits", m.bits.is_some()), ("seed", m.seed.is_some()), ("modulus-hex", m.modulus_hex.is_some())];
+    if let Some((id, _)) = given.into_iter().find(|(_, given)| *given) {
+        return Err(CliError::Usage(format!("--{} goes after `setup {}`, or use `setup <out> --mode ...`", id, command.name())));
+    }
+    let (format, allow_insecure) = (m.format, m.allow_insecure);
+    check_compress(m)?;
+    let (path, bits) = match command {
+        SetupCommand::Verify(v) => {
+            let params = load_params_arg(out, &v.params_path)?;
+            warn_if_insecure(&params);
+            let transcript_path = file_only(&v.transcript_path, "setup transcript")?;
+            let transcript = load_setup_transcript(transcript_path).map_err(|e| CliError::failed("Failed to load transcript", e))?;
+            out.set("fingerprint", params.fingerprint_hex().to_string());
+            return Ok(verdict(verify_setup_transcript(&params, &transcript)));
+        }
+        SetupCommand::Import(import) => {
+            let path = import.params_path.as_str();
+            refuse_overwrite(m.force, path)?;
+            let (n, bits) = match (&import.modulus_hex, &import.known) {
+                (Some(n), _) => (n.clone(), n.bits() as usize),
+                #[cfg(feature = "known-moduli")]
+                (None, Some(name)) => cuproof::known_moduli::known_modulus(name).ok_or_else(|| {
+                    CliError::Usage(format!("Unknown modulus {}; known: {:?}", name, cuproof::known_moduli::KNOWN_MODULUS_NAMES))
+                })?,
+                #[cfg(not(feature = "known-moduli"))]
+                (None, Some(_)) => return Err(usage("--known requires the known-moduli feature")),
+                (None, None) => unreachable!("the modulus group is required"),
+            };
+            let params = params_from_modulus_with(&n, bits, allow_insecure).map_err(|e| CliError::failed("Import failed", e))?;
+            return save_setup(out, path, &params, format, m.compress.level(), None);
+        }
+        SetupCommand::Fast(fast) => (fast.params_path.as_str(), fast.bits),
+        SetupCommand::Trusted(trusted) => (trusted.params_path.as_str(), trusted.bits),
+    };
+    refuse_overwrite(m.force, path)?;
+    let (params, transcript) = match command {
+        SetupCommand::Trusted(trusted) => {
+            let checkpoint = trusted.resume.as_deref().map(|path| file_only(path, "setup checkpoint")).transpose()?;
+            let (params, transcript) = trusted_setup_cli(out, threads, path, bits.unwrap_or(2048), allow_insecure, checkpoint)?;
+            (params, Some(transcript))
+        }
+        _ => {
+            let bits = bits.unwrap_or(512);
+            if !INSECURE_TEST_MODULUS_BITS.contains(&bits) {
+                return Err(CliError::Usage(format!("fast setup supports {:?} bits, not {}", INSECURE_TEST_MODULUS_BITS, bits)));
+            }
+            (insecure_test_setup(bits), None)
+        }
+    };
+    save_setup(out, path, &params, format, m.compress.level(), transcript)
+}
+
+/// `cuproof setup <out> --mode ...`: the form taking every setup as options of one command
+fn run_setup_mode(m: &SetupArgs, threads: Option<usize>, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let (format, allow_insecure) = (m.format, m.allow_insecure);
+    check_compress(m)?;
+    let path = m.out.as_deref().expect("the parser enforces required arguments");
+    let mode = m.mode.unwrap_or(SetupMode::Trusted);
+    let (seed, modulus) = (m.seed.as_ref(), m.modulus_hex.as_ref());
+    if seed.is_some() && mode != SetupMode::Fast { return Err(usage("--seed only applies to --mode fast")); }
+    match (mode, modulus) {
+        (SetupMode::Import, None) => return Err(usage("--mode import needs --modulus-hex")),
+        (SetupMode::Import, Some(_)) | (_, None) => {}
+        (_, Some(_)) => return Err(usage("--modulus-hex only applies to --mode import")),
+    }
+    if mode == SetupMode::Fast && !allow_insecure {
+        return Err(usage("--mode fast writes INSECURE test params whose factorization is known; pass --allow-insecure"));
+    }
+    let bits = match (mode, m.bits) {
+        (_, Some(bits)) => bits,
+        (SetupMode::Fast, None) => 512,
+        (SetupMode::Import, None) => modulus.expect("checked above").bits() as usize,
+        _ => 2048,
+    };
+    // Refuse a size here, before a prime search that would only fail at the end
+    check_modulus_bits(bits, allow_insecure || mode == SetupMode::Fast).map_err(|e| match e {
+        SetupError::InsecureModulusSize { .. } => CliError::Usage(format!("{}; pass --allow-insecure for test params", e)),
+        e => CliError::Usage(e.to_string()),
+    })?;
+    refuse_overwrite(m.force, path)?;
+    let (params, transcript) = match mode {
+        SetupMode::Fast => {
+            let seed = seed.cloned().unwrap_or_else(|| {
+                let mut seed = vec![0u8; 32];
+                OsRng.fill_bytes(&mut seed);
+                seed
+            });
+            (insecure_seeded_setup(bits, &seed).map_err(|e| CliError::failed("Setup failed", e))?, None)
+        }
+        SetupMode::Import => {
+            let n = modulus.expect("checked above");
+            (params_from_modulus_with(n, bits, allow_insecure).map_err(|e| CliError::failed("Import failed", e))?, None)
+        }
+        SetupMode::Trusted => {
+            let (params, transcript) = trusted_setup_cli(out, threads, path, bits, allow_insecure, None)?;
+            (params, Some(transcript))
+        }
+    };
+    save_setup(out, path, &params, format, m.compress.level(), transcript)
+}
+
+/// A trusted setup reporting its progress on stderr (see SetupStatus), resumable from `checkpoint` when given
+/// - params: path where the params go; their transcript goes beside them, so it cannot be stdout
+pub(super) fn trusted_setup_cli(out: &Output, threads: Option<usize>, path: &str, bits: usize, allow_insecure: bool, checkpoint: Option<&str>) -> Result<(Params, SetupTranscript), CliError> {
+    file_only(path, "params of a trusted setup, whose transcript goes beside them")?;
+    let workers = workers(None, threads)?;
+    let style = match (out.quiet, out.stdio.stderr_tty && !out.json) {
+        (true, _) => StatusStyle::Off,
+        (false, true) => StatusStyle::Line,
+        (false, false) => StatusStyle::Log,
+    };
+    let mut status = SetupStatus::new(std::io::stderr(), style, bits);
+    let result = match checkpoint {
+        Some(checkpoint) => {
+            if threads.is_some() { tracing::warn!("--threads is ignored with --resume: resumable setups search serially"); }
+            if std::path::Path::new(checkpoint).exists() { tracing::info!("Resuming setup from {}", checkpoint); }
+            trusted_setup_resumable_with_transcript(bits, allow_insecure, checkpoint, |p| status.report(p))
+        }
+        None => trusted_setup_with_transcript(bits, allow_insecure, Some(&workers), |p| status.report(p)),
+    };
+    status.finish(result.as_ref().ok().map(|(params, _)| params));
+    result.map_err(|e| CliError::failed("Setup failed", e))
+}
+
+/// Refuse --compress with --format json, which has no compressed form
+fn check_compress(m: &SetupArgs) -> Result<(), CliError> {
+    if m.compress.level().is_some() && m.format != OutputFormat::Bin { return Err(usage("--compress applies to the binary format, not to --format json")); }
+    Ok(())
+}
+
+/// Refuse to replace an existing params file unless --force was given
+fn refuse_overwrite(force: bool, path: &str) -> Result<(), CliError> {
+    if path != "-" && std::path::Path::new(path).exists() && !force {
+        return Err(CliError::Usage(format!("{} already exists; pass --force to overwrite it", path)));
+    }
+    Ok(())
+}
+
+/// Write freshly made params, and their transcript beside them, then report both
+fn save_setup(out: &mut Output, path: &str, params: &Params, format: OutputFormat, compress: Option<i32>, transcript: Option<SetupTranscript>) -> Result<CommandOutcome, CliError> {
+    write_params_arg(out, path, params, format, compress)?;
+    let transcript_path = match transcript {
+        Some(transcript) => {
+            let transcript_path = format!("{}.transcript", path);
+            save_setup_transcript(&transcript_path, &transcript).map_err(|e| CliError::failed("Failed to save setup transcript", e))?;
+            out.say(format_args!("Saved setup transcript to {}", transcript_path));
+            Some(transcript_path)
+        }
+        None => None,
+    };
+    warn_if_insecure(params);
+    out.say(format_args!("Saved {}-bit public parameters to {} (fingerprint {})", params.bits(), path, params.fingerprint_hex()));
+    match security_bits(params) {
+        0 => out.say("Security level: none, for tests only"),
+        level => out.say(format_args!("Security level: about {} bits", level)),
+    }
+    set_params_fields(out, path, params, transcript_path);
+    Ok(CommandOutcome::Done)
+}
+
+/// security_level_bits of the modulus, or 0 for params marked insecure
+fn security_bits(params: &Params) -> usize {
+    if params.is_insecure() { 0 } else { security_level_bits(params.bits()) }
+}
+
+fn set_params_fields(out: &mut Output, path: &str, params: &Params, transcript_path: Option<String>) {
+    out.set("params_path", path);
+    out.set("bits", params.bits());
+    out.set("fingerprint", params.fingerprint_hex().to_string());
+    out.set("insecure", params.is_insecure());
+    out.set("security_bits", security_bits(params));
+    out.set("transcript_path", transcript_path);
+}
+
+/// How SetupStatus shows progress: one line redrawn in place, a log line per phase and every
+/// STATUS_LOG_INTERVAL, or nothing (--quiet)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum StatusStyle {
+    Line,
+    Log,
+    Off,
+}
+
+/// Time between two log lines of the same phase when stderr is not a terminal
+const STATUS_LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The progress of `setup trusted`: phase, candidates tested, elapsed time and a rough ETA from
+/// expected_setup_candidates
+/// Under --json it logs plain lines, so nothing is left half-drawn on the terminal the JSON goes to
+pub(super) struct SetupStatus<W: Write> {
+    pub(super) sink: W,
+    style: StatusStyle,
+    expected: f64,
+    started: Instant,
+    /// phase and elapsed time of the last log line
+    logged: Option<(SetupPhase, Duration)>,
+    /// progress events received, drawn or not
+    pub(super) events: u64,
+    /// a Line is on screen and needs ending before anything else is printed
+    drawn: bool,
+}
+
+impl<W: Write> SetupStatus<W> {
+    pub(super) fn new(sink: W, style: StatusStyle, bits: usize) -> Self {
+        SetupStatus { sink, style, expected: expected_setup_candidates(bits), started: Instant::now(), logged: None, events: 0, drawn: false }
+    }
+
+    pub(super) fn report(&mut self, p: SetupProgress) {
+        self.events += 1;
+        let line = self.describe(p);
+        match self.style {
+            StatusStyle::Off => {}
+            StatusStyle::Line => {
+                let _ = write!(self.sink, "\r{:<79}", line);
+                let _ = self.sink.flush();
+                self.drawn = true;
+            }
+            StatusStyle::Log => {
+                let due = match self.logged {
+                    Some((phase, at)) => phase != p.phase || p.elapsed >= at + STATUS_LOG_INTERVAL,
+                    None => true,
+                };
+                if due {
+                    let _ = writeln!(self.sink, "{}", line);
+                    self.logged = Some((p.phase, p.elapsed));
+                }
+            }
+        }
+    }
+
+    /// e.g. `testing q: 412 candidates, 12.3s elapsed, about 20s left`
+    pub(super) fn describe(&self, p: SetupProgress) -> String {
+        let secs = p.elapsed.as_secs_f64();
+        let left = match (p.phase, p.candidates_tested) {
+            (SetupPhase::DerivingGenerators, _) => "almost done".to_string(),
+            (_, 0) => "estimating time left".to_string(),
+            (_, tested) if (tested as f64) < self.expected => {
+                format!("about {:.0}s left", secs * (self.expected / tested as f64 - 1.0))
+            }
+            _ => "taking longer than average".to_string(),
+        };
+        format!("{}: {} candidates, {:.1}s elapsed, {}", p.phase, p.candidates_tested, secs, left)
+    }
+
+    /// End the status line, then give the total time and, on success, the params fingerprint
+    pub(super) fn finish(&mut self, params: Option<&Params>) {
+        if self.style == StatusStyle::Off { return; }
+        if self.drawn { let _ = writeln!(self.sink); }
+        let secs = self.started.elapsed().as_secs_f64();
+        let _ = match params {
+            Some(params) => writeln!(self.sink, "Setup finished in {:.1}s (fingerprint {})", secs, params.fingerprint_hex()),
+            None => writeln!(self.sink, "Setup stopped after {:.1}s", secs),
+        };
+    }
+}
@@ -0,0 +1,215 @@
+//! `cuproof verify`: a proof for a range, or an aggregated proof against its statements
+
+use std::io::Read;
+use clap::Args;
+use num_bigint::BigInt;
+use cuproof::aggregate::{aggregate_verify, aggregate_verify_statements, AggregatedCuproof};
+use cuproof::batch::{check_item_with_nonce, Expected};
+use cuproof::range_proof::{Cuproof, Statement};
+use cuproof::util::{bigint_to_hex, bigint_to_signed_hex, container_version, is_aggregated_proof, load_statements, read_aggregated_proof, FileKind, ReadLimits};
+use cuproof::verify::{verify_statement_with_nonce, VerifyError};
+use super::args::{hex_number, num_arg, number, parse_nonce, HexBytes, RadixArg, TimeArg};
+use super::files::{file_only, load_params_arg, print_statement, read_proof_arg, say_params, warn_if_insecure, warn_if_legacy, warn_if_other_params};
+use super::generators::{check_generators_arg, GeneratorsArg};
+use super::{usage, CliError, CommandOutcome, Output};
+
+/// Verify a proof for the range [a, b], or an aggregated proof; params and proofs may be binary, JSON or armored
+#[derive(Args)]
+#[command(
+    override_usage = "cuproof verify [OPTIONS] <params_path> <a> <b> <proof_path>\n       cuproof verify [OPTIONS] <params_path> <proof_path> <--range <a> <b>|--statement <statement_path>>\n       cuproof verify [OPTIONS] <params_path> <aggregated_proof_path> [--statements <statements_path>]",
+    allow_negative_numbers = true,
+    after_help = "The range and commitment checked are the caller's; one the proof states otherwise is INVALID. \
+        An aggregated proof, recognised by its file type, carries its own statements; --statements checks them against the caller's.\n\n\
+        Examples:\n  cuproof verify params.bin 10 100 v.proof\n  cuproof verify params.bin v.proof --range 10 100 --commitment 5e0c...17\n  cuproof verify params.bin v.proof --statement v.statement\n  cuproof verify params.bin agg.proof --statements agg.statements"
+)]
+pub(super) struct VerifyArgs {
+    /// Params written by setup
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    // a holds the proof path when the range comes from --range or --statement; run_verify parses it
+    /// Lower end of the range
+    #[arg(value_name = "a|proof_path", allow_hyphen_values = true)]
+    a: String,
+    /// Upper end of the range, inclusive
+    #[arg(value_name = "b", allow_hyphen_values = true, value_parser = number(true))]
+    b: Option<String>,
+    #[arg(value_name = "proof_path")]
+    proof_path: Option<String>,
+    /// The range the proof must be for, instead of <a> <b>
+    #[arg(long, num_args = 2, value_names = ["a", "b"], allow_hyphen_values = true, value_parser = number(true))]
+    range: Option<Vec<String>>,
+    /// The commitment the proof must be for
+    #[arg(long, value_name = "hex", value_parser = hex_number)]
+    commitment: Option<BigInt>,
+    /// A statement file the proof's embedded statement must equal in every field
+    #[arg(long, value_name = "statement_path", conflicts_with_all = ["range", "commitment"])]
+    statement: Option<String>,
+    /// For an aggregated proof: a statements file, as aggregate --statements-out writes, its statements must equal in order
+    #[arg(long, value_name = "statements_path", conflicts_with_all = ["range", "commitment", "statement"])]
+    statements: Option<String>,
+    #[command(flatten)]
+    generators: GeneratorsArg,
+    /// The nonce this verifier issued for the session; a proof made for another nonce, or none, is INVALID
+    #[arg(long, value_name = "hex", value_parser = parse_nonce)]
+    nonce: Option<HexBytes>,
+    #[command(flatten)]
+    pub(super) time: TimeArg,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+pub(super) fn run_verify(m: &VerifyArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let radix = m.radix.get();
+    let params_path = m.params_path.as_str();
+    let range = m.range.as_ref().map(|range| range.iter().map(String::as_str).collect::<Vec<_>>());
+    // a proof path alone after the params may be an aggregated proof, which carries its statements
+    if range.is_none() && m.b.is_none() && m.statement.is_none() {
+        let path = m.a.as_str();
+        if path == "-" || starts_aggregated(path) {
+            let bytes = out.read_input(path, "proof")?;
+            if is_aggregated_proof(&bytes) { return run_verify_aggregated(m, out, &bytes); }
+        }
+    }
+    if m.statements.is_some() {
+        return Err(usage("--statements is for aggregated proofs; check a single proof with --statement <statement_path>"));
+    }
+    if m.generators.generators.is_some() { return Err(usage("--generators is for aggregated proofs; a single proof commits under g and h only")); }
+    // the range comes from exactly one of <a> <b>, --range and --statement
+    let (range, proof_path) = match (range, m.b.as_deref(), m.proof_path.as_deref(), m.statement.as_deref()) {
+        (None, Some(b), Some(proof_path), None) => (Some((m.a.as_str(), b)), proof_path),
+        (Some(range), None, None, None) => (Some((range[0], range[1])), m.a.as_str()),
+        (None, None, None, Some(_)) => (None, m.a.as_str()),
+        (Some(_), Some(_), _, _) | (None, Some(_), Some(_), Some(_)) => {
+            return Err(usage("verify takes the range once: as <a> <b>, as --range <a> <b> or from --statement"));
+        }
+        _ => return Err(usage("verify needs <a> <b> <proof_path>, or <proof_path> with --range <a> <b> or --statement <statement_path>")),
+    };
+    let range = range.map(|(a, b)| Ok::<_, CliError>((num_arg("a", a, radix, true)?, num_arg("b", b, radix, true)?))).transpose()?;
+    warn_if_legacy(params_path);
+    warn_if_legacy(proof_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let (proof, bytes) = read_proof_arg(out, proof_path, Some(&params))?;
+    say_params(out, params_path, &params);
+    warn_if_other_params(&proof, &params, params_path);
+    let expected = match m.statement.as_deref() {
+        Some(path) => {
+            let bytes = out.read_input(path, "statement")?;
+            Some(Statement::from_bytes(&bytes, &ReadLimits::default()).map_err(|e| CliError::failed(format!("Failed to load statement {}", path), e))?)
+        }
+        None => None,
+    };
+    out.set("proof", json_object! {
+        "size" => bytes.len(),
+        "version" => container_version(&bytes, FileKind::Proof),
+        "epoch" => proof.epoch,
+    });
+    print_statement(&proof, out);
+    let nonce = m.nonce.as_deref().unwrap_or_default();
+    let (mismatches, checked) = match (&expected, range) {
+        (Some(expected), _) => (statement_mismatches(&proof, expected, None), verify_statement_with_nonce(&proof, &params, expected, nonce)),
+        (None, Some((a, b))) => {
+            let expected = Expected { a, b, commitment: m.commitment.clone() };
+            // a statement of only what the caller pins; a commitment left open is the proof's own
+            let pinned = Statement::new(expected.commitment.as_ref().unwrap_or(&proof.C), &expected.a, &expected.b, 0, &params);
+            (statement_mismatches(&proof, &pinned, Some(["commitment", "range"])), check_item_with_nonce(&proof, &params, Some(&expected), nonce))
+        }
+        (None, None) => unreachable!("the range comes from <a> <b>, --range or --statement"),
+    };
+    for (field, stated, wanted) in &mismatches {
+        out.say(format_args!("Mismatch: {} is {} in the proof, expected {}", field, stated, wanted));
+    }
+    out.set("mismatches", mismatches.iter().map(|(field, stated, wanted)| json_object! {
+        "field" => *field, "proof" => stated.as_str(), "expected" => wanted.as_str(),
+    }).collect::<Vec<_>>());
+    Ok(match checked {
+        Ok(()) => CommandOutcome::Valid,
+        Err(VerifyError::InvalidProof) => CommandOutcome::Invalid(None),
+        Err(e) => CommandOutcome::Invalid(Some(e)),
+    })
+}
+
+/// Whether the file at `path` starts like an aggregated proof; files that cannot be read do not
+fn starts_aggregated(path: &str) -> bool {
+    let mut magic = [0u8; 8];
+    std::fs::File::open(path).and_then(|mut file| file.read_exact(&mut magic)).is_ok_and(|()| is_aggregated_proof(&magic))
+}
+
+/// verify for an aggregated proof: the proof's own statements, compared with --statements when given
+fn run_verify_aggregated(m: &VerifyArgs, out: &mut Output, bytes: &[u8]) -> Result<CommandOutcome, CliError> {
+    if m.commitment.is_some() {
+        return Err(usage("--commitment does not apply to aggregated proofs; give their statements with --statements"));
+    }
+    if m.nonce.as_ref().is_some_and(|nonce| !nonce.is_empty()) {
+        return Err(usage("--nonce does not apply to aggregated proofs, whose transcript binds no nonce"));
+    }
+    let params_path = m.params_path.as_str();
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let proof = read_aggregated_proof(&mut &bytes[..], &ReadLimits::default()).map_err(|e| CliError::failed("Failed to load aggregated proof", e))?;
+    if let Some(path) = m.generators.generators.as_deref() { check_generators_arg(out, path, &params, proof.statements.len())?; }
+    say_params(out, params_path, &params);
+    let expected = match m.statements.as_deref() {
+        Some(path) => Some(load_statements(file_only(path, "statements file")?).map_err(|e| CliError::failed(format!("Failed to load statements {}", path), e))?),
+        None => None,
+    };
+    out.set("proof", json_object! {
+        "size" => bytes.len(),
+        "version" => container_version(bytes, FileKind::AggregatedProof),
+        "epoch" => proof.epoch,
+        "statements" => proof.statements.len(),
+    });
+    print_aggregated_statements(&proof, out);
+    let checked = match &expected {
+        Some(expected) => aggregate_verify_statements(&proof, &params, expected),
+        None => aggregate_verify(&proof, &params),
+    };
+    Ok(match checked {
+        Ok(()) => CommandOutcome::Valid,
+        Err(VerifyError::InvalidProof) => CommandOutcome::Invalid(None),
+        Err(e) => CommandOutcome::Invalid(Some(e)),
+    })
+}
+
+/// One line per statement of an aggregated proof: its index, range and commitment; under --json
+/// the statements field, with the fields print_statement gives a single proof's
+fn print_aggregated_statements(proof: &AggregatedCuproof, out: &mut Output) {
+    out.say(format_args!("Aggregated proof of {} statements:", proof.statements.len()));
+    let mut entries = Vec::new();
+    for (index, statement) in proof.statements.iter().enumerate() {
+        let (a, b) = (bigint_to_signed_hex(&statement.a), bigint_to_signed_hex(&statement.b));
+        out.say(format_args!("  {:>3}: [{}, {}] commitment {}", index, a, b, bigint_to_hex(&statement.commitment)));
+        entries.push(json_object! {
+            "commitment" => bigint_to_hex(&statement.commitment),
+            "a" => a,
+            "b" => b,
+            "bits" => statement.bits,
+            "label" => statement.label.clone(),
+            "params_fingerprint" => hex::encode(statement.params_fingerprint),
+        });
+    }
+    out.set("statements", entries);
+}
+
+/// Where the proof disagrees with `expected`: its commitment C, and the other fields of its embedded
+/// statement when it has one (whose commitment must be C, see cuproof_verify_checked); only the
+/// `fields` named, when given
+/// - returns: (field, value in the proof, expected value) per disagreement, hex as in print_statement
+fn statement_mismatches(proof: &Cuproof, expected: &Statement, fields: Option<[&str; 2]>) -> Vec<(&'static str, String, String)> {
+    let range = |a: &BigInt, b: &BigInt| format!("[{}, {}]", bigint_to_signed_hex(a), bigint_to_signed_hex(b));
+    let mut found = Vec::new();
+    if proof.C != expected.commitment { found.push(("commitment", bigint_to_hex(&proof.C), bigint_to_hex(&expected.commitment))); }
+    if let Some(stated) = &proof.statement {
+        if (&stated.a, &stated.b) != (&expected.a, &expected.b) {
+            found.push(("range", range(&stated.a, &stated.b), range(&expected.a, &expected.b)));
+        }
+        if stated.bits != expected.bits { found.push(("bit width", stated.bits.to_string(), expected.bits.to_string())); }
+        if stated.label != expected.label { found.push(("label", format!("{:?}", stated.label), format!("{:?}", expected.label))); }
+        if stated.params_fingerprint != expected.params_fingerprint {
+            found.push(("params fingerprint", hex::encode(stated.params_fingerprint), hex::encode(expected.params_fingerprint)));
+        }
+    }
+    found.retain(|(field, _, _)| fields.is_none_or(|fields| fields.contains(field)));
+    found
+}
@@ -0,0 +1,66 @@
+//! `cuproof verify-opening`: check that v and a blinding open a commitment
+
+use clap::{ArgGroup, Args};
+use num_bigint::BigInt;
+use cuproof::commitment::{commit, is_canonical_commitment};
+use cuproof::error::SerializationError;
+use cuproof::util::{bigint_to_hex, ct_eq_bigint};
+use super::args::{hex_number, number, read_secret, BlindingSecret, RadixArg, SecretSource, ValueSecret};
+use super::files::{load_params_arg, say_params, warn_if_insecure, warn_if_legacy};
+use super::{CliError, CommandOutcome, Output};
+
+/// Check that v and a blinding open a commitment: recompute g^v h^r mod n and compare
+#[derive(Args)]
+#[command(
+    allow_negative_numbers = true,
+    group(ArgGroup::new("value-source").args(["value", "value_stdin", "value_env"]).required(true)),
+    group(ArgGroup::new("blinding-source").args(["blinding", "blinding_in", "blinding_stdin", "blinding_env"]).required(true)),
+    after_help = "Prints MATCH or MISMATCH; exits 0, 1, or 2 when the commitment is not canonical or an input is unreadable.\n\nExamples:\n  cuproof verify-opening params.bin --commitment 5e0c...17 --value-env V --blinding-in r.bin\n  printf '42\\n0x1f2e\\n' | cuproof verify-opening params.bin --commitment 5e0c...17 --value-stdin --blinding-stdin"
+)]
+pub(super) struct VerifyOpeningArgs {
+    /// Params the commitment was made under
+    #[arg(value_name = "params_path")]
+    params_path: String,
+    /// The commitment, reduced below n; C + n and other representatives are refused
+    #[arg(long, value_name = "hex", value_parser = hex_number)]
+    commitment: BigInt,
+    /// The value; seen by shell history and ps, prefer --value-stdin or --value-env
+    #[arg(long, value_name = "v", allow_hyphen_values = true, value_parser = number(true))]
+    value: Option<String>,
+    #[command(flatten)]
+    value_secret: ValueSecret,
+    /// The blinding
+    #[arg(long, value_name = "r", value_parser = number(false))]
+    blinding: Option<String>,
+    /// Read the blinding from a file written by commit or --blinding-out
+    #[arg(long, value_name = "r_path")]
+    blinding_in: Option<String>,
+    #[command(flatten)]
+    blinding_secret: BlindingSecret,
+    #[command(flatten)]
+    radix: RadixArg,
+}
+
+pub(super) fn run_verify_opening(m: &VerifyOpeningArgs, out: &mut Output) -> Result<CommandOutcome, CliError> {
+    let radix = m.radix.get();
+    let value_source = m.value_secret.source(m.value.clone().map(SecretSource::Arg)).expect("the value group is required");
+    let given = m.blinding.clone().map(SecretSource::Arg).or_else(|| m.blinding_in.clone().map(SecretSource::File));
+    let blinding_source = m.blinding_secret.source(given).expect("the blinding group is required");
+    let v = read_secret(out, "v", value_source, radix, true)?;
+    let r = read_secret(out, "blinding", blinding_source, radix, false)?;
+    let params_path = m.params_path.as_str();
+    warn_if_legacy(params_path);
+    let params = load_params_arg(out, params_path)?;
+    warn_if_insecure(&params);
+    let commitment = &m.commitment;
+    if !is_canonical_commitment(&params, commitment) {
+        let e = SerializationError::Invalid("commitment is not a nonzero residue modulo n".to_string());
+        return Err(CliError::failed("Refusing the commitment", e));
+    }
+    let recomputed = commit(&params, v.expose(), r.expose());
+    say_params(out, params_path, &params);
+    out.set("commitment", bigint_to_hex(commitment));
+    out.set("recomputed", bigint_to_hex(&recomputed));
+    let width = params.n().bits().div_ceil(8) as usize;
+    Ok(CommandOutcome::Opening { matched: ct_eq_bigint(&recomputed, commitment, width) })
+}
@@ -0,0 +1,74 @@
+//! Diagnostics of the binary on stderr: the subscriber for the library's tracing spans and events
+//! and for the CLI's own warnings and notes. The library never installs one; embedders bring theirs
+
+use std::fmt::Write as _;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::{self, FormatEvent, FormatFields};
+use tracing_subscriber::fmt::{FmtContext, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// How --log-format writes each event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `WARNING: ...`, `note: ...` lines; from -v on the enclosing spans and the fields too
+    Text,
+    /// one JSON object per event with its level, fields and spans
+    Json,
+}
+
+/// The most verbose level shown: errors only with --quiet, warnings and notes by default, the
+/// library's spans and events with -v, everything with -vv
+pub fn level(quiet: bool, verbose: u8) -> Level {
+    match (quiet, verbose) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    }
+}
+
+/// Install the stderr subscriber for this process
+/// - usage: once per process, by cli::run; later calls (the CLI's unit tests run many commands in
+///   one process) keep the first subscriber
+pub fn init(level: Level, format: LogFormat) {
+    let builder = tracing_subscriber::fmt().with_writer(std::io::stderr).with_max_level(level);
+    let _ = match format {
+        LogFormat::Text => builder.event_format(Text).try_init(),
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(true).try_init(),
+    };
+}
+
+/// The text format: every line of an event prefixed by its level as the CLI always wrote them
+struct Text;
+
+impl<S, N> FormatEvent<S, N> for Text
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: format::Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let level = *event.metadata().level();
+        let prefix = match level {
+            Level::ERROR => "error",
+            Level::WARN => "WARNING",
+            Level::INFO => "note",
+            Level::DEBUG => "debug",
+            Level::TRACE => "trace",
+        };
+        let mut text = String::new();
+        if level > Level::INFO {
+            for span in ctx.event_scope().into_iter().flat_map(|scope| scope.from_root()) {
+                text.push_str(span.name());
+                if let Some(fields) = span.extensions().get::<FormattedFields<N>>().filter(|fields| !fields.is_empty()) {
+                    write!(text, "{{{}}}", fields)?;
+                }
+                text.push_str(": ");
+            }
+        }
+        ctx.format_fields(format::Writer::new(&mut text), event)?;
+        for line in text.lines() {
+            writeln!(writer, "{}: {}", prefix, line)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::env;
+
+mod cli;
+mod logging;
+mod script;
+
+/// CLI entry: `cuproof [--json] [--errors text|json] [--quiet] [-v] [--log-format text|json] [--threads N] <command> ...`, one of
+/// - setup <out> [--mode fast|trusted|import], or setup fast | trusted | verify | import: generate,
+///   check or import public parameters
+/// - commit: commit to v, printing the commitment
+/// - prove: prove a <= v <= b, for a fresh commitment or one written by commit
+/// - prove-offline-commit | prove-offline-challenge | prove-offline-finish: prove in two stages on an
+///   air-gapped machine, the state between them kept on that machine and usable once
+/// - prove-lt | verify-lt: prove and verify that one committed value is less than another
+/// - prove-openings | verify-openings: prove and verify knowledge of the openings of many
+///   commitments, read from an encrypted manifest of rows, in one proof
+/// - verify: verify a proof against params and a range, or an aggregated proof against its statements
+/// - verify-opening: check that a value and blinding open a commitment
+/// - aggregate: prove the ranges of every row of a manifest in one aggregated proof
+/// - batch-verify: verify a directory or manifest of proofs
+/// - batch-prove: prove every row of a CSV or JSON-lines manifest
+/// - info: describe a file from structural checks only
+/// - dump: print every field of a proof in hex
+/// - fingerprint: print the fingerprint identifying a set of params
+/// - generators: derive a generator vector from params, print its fingerprint and pin it in a file
+/// - convert: rewrite params, proofs and statements between the binary, JSON, CBOR and armored
+///   encodings, migrating legacy text files with --upgrade
+/// - encrypt: encrypt a secret file, e.g. a prove-openings manifest, under a passphrase
+/// - params rotate: derive fresh generators
+/// - bench, benchmark: timings on this machine
+/// - selftest: known-answer and prove / verify checks of this binary and its params
+/// - run: a TOML or JSON script of these commands, each step checked against its expectation
+/// - ceremony contribute | verify: multi-party params ceremonies
+///
+/// `cuproof --help` and `cuproof <command> --help` print every argument, with examples; see
+/// cli::Cli. cli::run parses and runs the command and cli::finish maps its outcome to the exit
+/// status (see cli::EXIT_CODES): verdicts go to stdout, errors to stderr. With the global `--json`
+/// flag stdout carries exactly one JSON object instead and all human-readable text goes to stderr;
+/// it, or `--errors json`, writes errors on stderr as JSON objects with a machine-readable kind.
+/// Warnings, notes and the library's spans go through the tracing subscriber of logging.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    // run reads --json and --errors again from the parsed command line; this covers command lines it refuses
+    let json_errors = args.iter().any(|a| a == "--errors=json") || args.windows(2).any(|w| w[0] == "--errors" && w[1] == "json");
+    let mut out = cli::Output::new(args.iter().any(|a| a == "--json"), cli::Stdio::process()).json_errors(json_errors);
+    let outcome = cli::run(args, &mut out);
+    std::process::exit(cli::finish(outcome, out));
+}
@@ -0,0 +1,436 @@
+//! Scripts for `cuproof run`: a sequence of the CLI's commands with an expectation for each
+//!
+//! A script is TOML or JSON (picked by the `.json` extension, TOML otherwise). Both hold the same
+//! keys: optional `workspace` (a directory, relative to the script), `keep_going` (run the steps
+//! after an unmet expectation instead of skipping them) and a `vars` table, then the steps:
+//!
+//! ```toml
+//! [vars]
+//! v = 42
+//!
+//! [[step]]
+//! name = "commit"
+//! run = ["commit", "${params}", "${v}", "--out", "${c}", "--blinding-out", "${r}"]
+//!
+//! [[step]]
+//! run = "verify ${params} 0 10 ${proof}"
+//! expect = "invalid"
+//! ```
+//!
+//! ```json
+//! {"vars": {"v": 42}, "step": [{"name": "commit", "run": ["commit", "${params}", "${v}", "--out", "${c}"]}]}
+//! ```
+//!
+//! `run` is the command line after `cuproof`, as a list or split at whitespace. `expect` is `ok`
+//! (the default: exit status 0), `valid`, `invalid` or `error`, and `error` may name the kind the
+//! error must have (see `cuproof --errors json`), or its category. In arguments `${name}` is a var,
+//! `${step.key}` the key a named earlier step set in its --json object (its commitment, proof_id,
+//! ...), `${workspace}` the workspace and any other `${name}` the file `name` in it; `$$` is a `$`.
+//! Only the TOML the examples need is read: tables, arrays of tables, and single-line strings,
+//! integers, booleans and arrays.
+
+use std::collections::HashMap;
+
+/// What a step must conclude
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expect {
+    /// exit status 0
+    Ok,
+    /// a verification accepted (or a commitment opening matched)
+    Valid,
+    /// a verification rejected (or a commitment opening did not match)
+    Invalid,
+    /// the command failed
+    Error,
+}
+
+impl Expect {
+    pub fn name(self) -> &'static str {
+        match self {
+            Expect::Ok => "ok",
+            Expect::Valid => "valid",
+            Expect::Invalid => "invalid",
+            Expect::Error => "error",
+        }
+    }
+}
+
+/// One command of a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// how the summary and `${name.key}` references call it: its `name`, else its 1-based position
+    pub name: String,
+    /// whether `name` was given, so later steps may refer to it
+    pub named: bool,
+    /// the arguments after `cuproof`, before `${...}` expansion
+    pub args: Vec<String>,
+    pub expect: Expect,
+    /// with Expect::Error, the kind or category the error must have
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    pub workspace: Option<String>,
+    pub keep_going: bool,
+    pub vars: HashMap<String, String>,
+    pub steps: Vec<Step>,
+}
+
+/// A parsed TOML or JSON value; JSON null is dropped with its key
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn describe(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "a string",
+            Value::Int(_) => "an integer",
+            Value::Bool(_) => "a boolean",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+
+    /// A string, integer or boolean as the text a var expands to
+    fn scalar(&self) -> Option<String> {
+        match self {
+            Value::Str(s) => Some(s.clone()),
+            Value::Int(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Array(_) | Value::Table(_) => None,
+        }
+    }
+}
+
+/// Parse a script; `json` picks the syntax
+/// - returns: the script, or where and why it is malformed
+pub fn parse_script(text: &str, json: bool) -> Result<Script, String> {
+    let root = if json { parse_json(text)? } else { parse_toml(text)? };
+    let Value::Table(fields) = root else { return Err("a script is an object".to_string()) };
+    let mut script = Script { workspace: None, keep_going: false, vars: HashMap::new(), steps: Vec::new() };
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("workspace", Value::Str(dir)) => script.workspace = Some(dir),
+            ("keep_going", Value::Bool(b)) => script.keep_going = b,
+            ("vars", Value::Table(vars)) => {
+                for (name, value) in vars {
+                    check_name(&name)?;
+                    let text = value.scalar().ok_or_else(|| format!("var {} is {}; use a string, integer or boolean", name, value.describe()))?;
+                    script.vars.insert(name, text);
+                }
+            }
+            ("step", Value::Array(steps)) => {
+                for (index, step) in steps.into_iter().enumerate() {
+                    script.steps.push(parse_step(index + 1, step).map_err(|e| format!("step {}: {}", index + 1, e))?);
+                }
+            }
+            (key @ ("workspace" | "keep_going" | "vars" | "step"), value) => return Err(format!("{} cannot be {}", key, value.describe())),
+            (other, _) => return Err(format!("unknown key {}; a script has workspace, keep_going, vars and step", other)),
+        }
+    }
+    if script.steps.is_empty() { return Err("the script has no steps".to_string()); }
+    let mut names: Vec<&str> = script.steps.iter().filter(|s| s.named).map(|s| s.name.as_str()).collect();
+    names.sort_unstable();
+    if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!("two steps are named {}", pair[0]));
+    }
+    Ok(script)
+}
+
+fn parse_step(position: usize, value: Value) -> Result<Step, String> {
+    let Value::Table(fields) = value else { return Err(format!("a step is a table, not {}", value.describe())) };
+    let mut step = Step { name: position.to_string(), named: false, args: Vec::new(), expect: Expect::Ok, error: None };
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("name", Value::Str(name)) => {
+                check_name(&name)?;
+                step.name = name;
+                step.named = true;
+            }
+            ("run", Value::Str(line)) => step.args = line.split_whitespace().map(str::to_string).collect(),
+            ("run", Value::Array(items)) => {
+                step.args = items.iter().map(|item| item.scalar().ok_or("run lists strings")).collect::<Result<_, _>>()?;
+            }
+            ("expect", Value::Str(expect)) => {
+                step.expect = match expect.as_str() {
+                    "ok" => Expect::Ok,
+                    "valid" => Expect::Valid,
+                    "invalid" => Expect::Invalid,
+                    "error" => Expect::Error,
+                    other => return Err(format!("expect {}; use ok, valid, invalid or error", other)),
+                };
+            }
+            ("error", Value::Str(kind)) => step.error = Some(kind),
+            (key @ ("name" | "run" | "expect" | "error"), value) => return Err(format!("{} cannot be {}", key, value.describe())),
+            (other, _) => return Err(format!("unknown key {}; a step has name, run, expect and error", other)),
+        }
+    }
+    if step.args.is_empty() { return Err("no command to run".to_string()); }
+    if step.error.is_some() && step.expect != Expect::Error {
+        match step.expect {
+            Expect::Ok => step.expect = Expect::Error,
+            _ => return Err("error applies to expect = \"error\"".to_string()),
+        }
+    }
+    Ok(step)
+}
+
+/// Names of vars and steps: letters, digits, `_` and `-`
+pub fn check_name(name: &str) -> Result<(), String> {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') { return Ok(()); }
+    Err(format!("'{}' is not a name: use letters, digits, _ and -", name))
+}
+
+/// Expand the `${...}` references of `arg` (see the module docs)
+/// - params: lookup: the text of a reference, or why it has none
+pub fn expand(arg: &str, lookup: &mut dyn FnMut(&str) -> Result<String, String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = arg;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        let Some(body) = rest.strip_prefix('{') else { return Err(format!("'$' in {} starts neither ${{...}} nor $$", arg)) };
+        let end = body.find('}').ok_or_else(|| format!("unterminated ${{ in {}", arg))?;
+        out.push_str(&lookup(&body[..end])?);
+        rest = &body[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn skip_space(chars: &mut Chars) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// A whole JSON document
+fn parse_json(text: &str) -> Result<Value, String> {
+    let mut chars = text.chars().peekable();
+    let value = json_value(&mut chars)?.ok_or("the script is null")?;
+    skip_space(&mut chars);
+    if chars.next().is_some() { return Err("text after the script".to_string()); }
+    Ok(value)
+}
+
+/// A JSON value; None for null
+fn json_value(chars: &mut Chars) -> Result<Option<Value>, String> {
+    skip_space(chars);
+    let value = match chars.next() {
+        Some('"') => Value::Str(quoted(chars, '"')?),
+        Some('{') => {
+            let mut fields = Vec::new();
+            skip_space(chars);
+            if chars.next_if_eq(&'}').is_none() {
+                loop {
+                    skip_space(chars);
+                    if chars.next() != Some('"') { return Err("expected a quoted key".to_string()); }
+                    let key = quoted(chars, '"')?;
+                    skip_space(chars);
+                    if chars.next() != Some(':') { return Err(format!("expected ':' after \"{}\"", key)); }
+                    if let Some(value) = json_value(chars)? { fields.push((key, value)); }
+                    skip_space(chars);
+                    match chars.next() {
+                        Some(',') => continue,
+                        Some('}') => break,
+                        _ => return Err("expected ',' or '}'".to_string()),
+                    }
+                }
+            }
+            Value::Table(fields)
+        }
+        Some('[') => {
+            let mut items = Vec::new();
+            skip_space(chars);
+            if chars.next_if_eq(&']').is_none() {
+                loop {
+                    items.push(json_value(chars)?.ok_or("null in an array")?);
+                    skip_space(chars);
+                    match chars.next() {
+                        Some(',') => continue,
+                        Some(']') => break,
+                        _ => return Err("expected ',' or ']'".to_string()),
+                    }
+                }
+            }
+            Value::Array(items)
+        }
+        Some(c) => {
+            let mut word = c.to_string();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '.' || *c == '+') { word.push(c); }
+            match word.as_str() {
+                "null" => return Ok(None),
+                _ => scalar_word(&word)?,
+            }
+        }
+        None => return Err("unexpected end of the script".to_string()),
+    };
+    Ok(Some(value))
+}
+
+/// true, false or an integer
+fn scalar_word(word: &str) -> Result<Value, String> {
+    match word {
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        _ => word.replace('_', "").parse().map(Value::Int).map_err(|_| format!("'{}' is not a string, integer or boolean", word)),
+    }
+}
+
+/// The rest of a string whose opening `quote` was consumed; `'` strings take no escapes, as in TOML
+fn quoted(chars: &mut Chars, quote: char) -> Result<String, String> {
+    let mut s = String::new();
+    loop {
+        match chars.next() {
+            Some(c) if c == quote => return Ok(s),
+            Some('\\') if quote == '"' => match chars.next() {
+                Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                Some('n') => s.push('\n'),
+                Some('t') => s.push('\t'),
+                Some('r') => s.push('\r'),
+                Some('u') => {
+                    let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+                    s.push(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or("invalid \\u escape")?);
+                }
+                _ => return Err("invalid escape".to_string()),
+            },
+            Some(c) => s.push(c),
+            None => return Err("unterminated string".to_string()),
+        }
+    }
+}
+
+/// The TOML subset of the module docs
+fn parse_toml(text: &str) -> Result<Value, String> {
+    let mut root: Vec<(String, Value)> = Vec::new();
+    // where `key = value` lines go: the root, a [table] or the last table of an [[array]]
+    let mut section: Option<(String, bool)> = None;
+    for (index, line) in text.lines().enumerate() {
+        let at = |e: String| format!("line {}: {}", index + 1, e);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        if let Some(name) = line.strip_prefix("[[") {
+            let name = name.split_once("]]").filter(|(_, rest)| is_comment(rest)).map(|(name, _)| name.trim())
+                .ok_or_else(|| at("expected [[name]]".to_string()))?;
+            match root.iter_mut().find(|(key, _)| key == name) {
+                Some((_, Value::Array(tables))) => tables.push(Value::Table(Vec::new())),
+                Some(_) => return Err(at(format!("{} is not an array of tables", name))),
+                None => root.push((name.to_string(), Value::Array(vec![Value::Table(Vec::new())]))),
+            }
+            section = Some((name.to_string(), true));
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name.split_once(']').filter(|(_, rest)| is_comment(rest)).map(|(name, _)| name.trim())
+                .ok_or_else(|| at("expected [name]".to_string()))?;
+            if root.iter().any(|(key, _)| key == name) { return Err(at(format!("{} is defined twice", name))); }
+            root.push((name.to_string(), Value::Table(Vec::new())));
+            section = Some((name.to_string(), false));
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(|| at("expected key = value".to_string()))?;
+        let key = key.trim().trim_matches('"').to_string();
+        let mut chars = value.chars().peekable();
+        let value = toml_value(&mut chars).map_err(at)?;
+        if !is_comment(&chars.collect::<String>()) { return Err(at("text after the value".to_string())); }
+        let table = match &section {
+            None => &mut root,
+            Some((name, array)) => match root.iter_mut().find(|(key, _)| key == name) {
+                Some((_, Value::Array(tables))) if *array => match tables.last_mut() {
+                    Some(Value::Table(fields)) => fields,
+                    _ => unreachable!("[[{}]] pushed a table", name),
+                },
+                Some((_, Value::Table(fields))) => fields,
+                _ => unreachable!("[{}] was pushed", name),
+            },
+        };
+        if table.iter().any(|(k, _)| *k == key) { return Err(at(format!("{} is set twice", key))); }
+        table.push((key, value));
+    }
+    Ok(Value::Table(root))
+}
+
+/// Nothing but space and a comment
+fn is_comment(rest: &str) -> bool {
+    let rest = rest.trim();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+fn toml_value(chars: &mut Chars) -> Result<Value, String> {
+    skip_space(chars);
+    match chars.next() {
+        Some(quote @ ('"' | '\'')) => Ok(Value::Str(quoted(chars, quote)?)),
+        Some('[') => {
+            let mut items = Vec::new();
+            loop {
+                skip_space(chars);
+                if chars.next_if_eq(&']').is_some() { break; }
+                items.push(toml_value(chars)?);
+                skip_space(chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some(']') => break,
+                    _ => return Err("expected ',' or ']'; arrays stay on one line".to_string()),
+                }
+            }
+            Ok(Value::Array(items))
+        }
+        Some(c) => {
+            let mut word = c.to_string();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_' || *c == '+') { word.push(c); }
+            scalar_word(&word)
+        }
+        None => Err("missing value".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Purpose: ensure the TOML and JSON forms of a script parse to the same steps, and references expand
+    // Params: a two-step script in both syntaxes; arguments with vars, step keys, files and $$
+    // Output: equal Scripts with expect, error and default names filled in; expansions through the
+    //         lookup, and errors for unknown keys, duplicate names and unterminated references
+    // Usage: `cargo test --bin cuproof -- script`
+    #[test]
+    fn toml_and_json_scripts_agree() {
+        let toml = "# a comment\nkeep_going = true\n[vars]\nv = 42\n\n[[step]]\nname = \"c\"\nrun = [\"commit\", \"${params}\", \"${v}\"]  # trailing\n\n[[step]]\nrun = 'verify ${params} 0 10 ${c.commitment}'\nerror = \"serialization\"\n";
+        let json = r#"{"keep_going": true, "vars": {"v": 42}, "step": [
+            {"name": "c", "run": ["commit", "${params}", "${v}"]},
+            {"run": "verify ${params} 0 10 ${c.commitment}", "error": "serialization", "ignored": null}]}"#;
+        let script = parse_script(toml, false).unwrap();
+        assert_eq!(script, parse_script(json, true).unwrap());
+        assert!(script.keep_going && script.workspace.is_none());
+        assert_eq!(script.vars.get("v").map(String::as_str), Some("42"));
+        assert_eq!((script.steps[0].name.as_str(), script.steps[0].named, script.steps[0].expect), ("c", true, Expect::Ok));
+        assert_eq!((script.steps[1].name.as_str(), script.steps[1].named, script.steps[1].expect), ("2", false, Expect::Error));
+        assert_eq!(script.steps[1].args, ["verify", "${params}", "0", "10", "${c.commitment}"]);
+
+        let mut lookup = |name: &str| match name {
+            "v" => Ok("42".to_string()),
+            other => Err(format!("no {}", other)),
+        };
+        assert_eq!(expand("x${v}y$$", &mut lookup), Ok("x42y$".to_string()));
+        assert_eq!(expand("${w}", &mut lookup), Err("no w".to_string()));
+        assert!(expand("${v", &mut lookup).unwrap_err().contains("unterminated"));
+        assert!(expand("$v", &mut lookup).is_err());
+
+        assert!(parse_script("[[step]]\nrun = \"info x\"\ncolour = 1\n", false).unwrap_err().contains("unknown key colour"));
+        assert!(parse_script("[[step]]\nname = \"a\"\nrun = \"info x\"\n[[step]]\nname = \"a\"\nrun = \"info y\"\n", false).unwrap_err().contains("two steps are named a"));
+        assert!(parse_script("[[step]]\nrun = [\"info\",\n", false).unwrap_err().starts_with("line 2"));
+        assert!(parse_script("{\"step\": []}", true).unwrap_err().contains("no steps"));
+    }
+}
@@ -104,16 +104,23 @@ mod tests {
         let v = BigInt::from(42);
         let r = random_bigint(128);
         
+        let dir = std::env::temp_dir();
+        let params_path = dir.join(format!("cuproof256_params_{}.txt", std::process::id()));
+        let proof_path = dir.join(format!("cuproof256_proof_{}.txt", std::process::id()));
+        let (params_path, proof_path) = (params_path.to_str().unwrap(), proof_path.to_str().unwrap());
+
         // Save params
-        save_params("test_params.txt", &g, &h, &n).unwrap();
+        save_params(params_path, &g, &h, &n).unwrap();
         
         // Generate and save proof
         let proof = cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
-        save_proof("test_proof_save.txt", &proof).unwrap();
+        save_proof(proof_path, &proof).unwrap();
         
         // Load params and proof
-        let (g2, h2, n2) = load_params("test_params.txt").unwrap();
-        let proof2 = load_proof("test_proof_save.txt").unwrap();
+        let (g2, h2, n2) = load_params(params_path).unwrap();
+        let proof2 = load_proof(proof_path).unwrap();
+        let _ = std::fs::remove_file(params_path);
+        let _ = std::fs::remove_file(proof_path);
         
         // Verify loaded proof
         assert!(cuproof_verify_with_range(&proof2, &g2, &h2, &n2, &a, &b));
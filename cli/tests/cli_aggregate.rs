@@ -0,0 +1,55 @@
+//! `cuproof aggregate` and `cuproof verify` on aggregated proofs, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::setup::insecure_test_setup;
+use cuproof::util::save_params;
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_aggregate_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure four aggregated statements verify, and a tampered statements file fails naming the statement
+// Params: insecure 512-bit params; a CSV manifest of four (value, blinding, a, b) rows, one with a negative range
+// Output: aggregate exits 0 reporting the saving against four single proofs; verify VALID with and
+//         without --statements; INVALID naming statement 2 once its upper bound is changed in the
+//         statements file; a single proof's --statement options refused for aggregated proofs
+// Usage: `cargo test --test cli_aggregate` or `cargo test`
+#[test]
+fn aggregated_proofs_verify_against_their_statements() {
+    let (params_path, manifest_path) = (temp_path("params.bin"), temp_path("rows.csv"));
+    let (proof_path, statements_path) = (temp_path("agg.proof"), temp_path("agg.statements"));
+    save_params(&params_path, &insecure_test_setup(512)).unwrap();
+    std::fs::write(&manifest_path, "value,blinding,a,b\n42,0x1f2e,0,100\n7,0x51,0,10\n-3,0x99,-10,0\n55,0x7a7a,50,100\n").unwrap();
+
+    let out = cuproof(&["aggregate", "--statements-out", &statements_path, &params_path, &manifest_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("for 4 single proofs") && stdout.contains("% smaller"), "{}", stdout);
+
+    let out = cuproof(&["--json", "verify", &params_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let json = String::from_utf8_lossy(&out.stdout);
+    assert!(json.contains("\"result\":\"valid\"") && json.contains("\"statements\":4"), "{}", json);
+    let out = cuproof(&["verify", &params_path, &proof_path, "--statements", &statements_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).ends_with("VALID\n"));
+
+    // each statement section is 7 lines: header, commitment, a, b, bits, label, fingerprint
+    let text = std::fs::read_to_string(&statements_path).unwrap();
+    let mut lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 4 * 7);
+    assert_eq!(lines[2 * 7 + 3], "00");
+    lines[2 * 7 + 3] = "01";
+    std::fs::write(&statements_path, lines.join("\n") + "\n").unwrap();
+    let out = cuproof(&["verify", &params_path, &proof_path, "--statements", &statements_path]);
+    assert_eq!(out.status.code(), Some(1), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).ends_with("INVALID (statement 2: range does not match)\n"), "{}", String::from_utf8_lossy(&out.stdout));
+
+    let out = cuproof(&["verify", &params_path, &proof_path, "--range", "0", "100"]);
+    assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+    for path in [params_path, manifest_path, proof_path, statements_path] { let _ = std::fs::remove_file(path); }
+}
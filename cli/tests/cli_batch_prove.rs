@@ -0,0 +1,103 @@
+//! `cuproof batch-prove` over CSV and JSON-lines manifests, driving the built binary
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use num_bigint::BigInt;
+use cuproof::commitment::commit;
+use cuproof::util::{bigint_to_hex, load_params, load_proof};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn path(dir: &Path, name: &str) -> String {
+    dir.join(name).to_str().unwrap().to_string()
+}
+
+/// A fresh directory holding fast params; returns (dir, params)
+fn work_dir(name: &str) -> (PathBuf, String) {
+    let dir = std::env::temp_dir().join(format!("cuproof_batch_prove_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let params = path(&dir, "params.bin");
+    assert!(cuproof(&["setup", "fast", &params]).status.success());
+    (dir, params)
+}
+
+/// The rows of results.jsonl as (out, status, error)
+fn results(dir: &Path) -> Vec<(String, String, Option<String>)> {
+    std::fs::read_to_string(dir.join("results.jsonl")).unwrap().lines().map(|line| {
+        let row: serde_json::Value = serde_json::from_str(line).unwrap();
+        (row["out"].as_str().unwrap_or_default().to_string(), row["status"].as_str().unwrap().to_string(), row["error"].as_str().map(str::to_string))
+    })
+    .collect()
+}
+
+// Purpose: ensure one invalid row fails alone: the other rows are proved and the exit status says a row failed
+// Params: a CSV manifest of three valid rows (one with a blinding) around a row whose value is not a number
+// Output: exit 2; proofs for the valid rows that verify, the given blinding's commitment; results.jsonl naming
+//         the invalid row's field without quoting the secret text
+// Usage: `cargo test --test cli_batch_prove` or `cargo test`
+#[test]
+fn invalid_row_among_valid_rows() {
+    let (dir, params) = work_dir("csv");
+    let (manifest, out_dir) = (path(&dir, "rows.csv"), dir.join("proofs"));
+    std::fs::write(&manifest, "out,value,blinding,a,b\na.proof,42,0x1234,10,100\nbad.proof,4z2secret,,10,100\nb.proof,10,,10,100\nc.proof,0x64,,-5,100\n").unwrap();
+    for jobs in ["1", "3"] {
+        let _ = std::fs::remove_dir_all(&out_dir);
+        let out = cuproof(&["batch-prove", "--jobs", jobs, &params, &manifest, out_dir.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains("INVALID  line 3 (bad.proof): invalid value"), "{}", stdout);
+        assert!(stdout.contains("4 rows: 3 proved, 1 failed"), "{}", stdout);
+        assert!(!stdout.contains("4z2secret"));
+
+        let rows = results(&out_dir);
+        assert_eq!(rows.iter().map(|(out, status, _)| (out.as_str(), status.as_str())).collect::<Vec<_>>(),
+            [("a.proof", "proved"), ("bad.proof", "invalid"), ("b.proof", "proved"), ("c.proof", "proved")]);
+        assert!(rows[1].2.as_deref().unwrap().starts_with("invalid value") && !rows[1].2.as_deref().unwrap().contains("4z2secret"));
+        assert!(!out_dir.join("bad.proof").exists());
+        for (file, a, b) in [("a.proof", "10", "100"), ("b.proof", "10", "100"), ("c.proof", "-5", "100")] {
+            let verdict = cuproof(&["verify", &params, a, b, &path(&out_dir, file)]);
+            assert_eq!(verdict.status.code(), Some(0), "{}", file);
+        }
+        let expected = commit(&load_params(&params).unwrap(), &BigInt::from(42), &BigInt::from(0x1234));
+        assert_eq!(load_proof(&path(&out_dir, "a.proof")).unwrap().C, expected);
+        let row: serde_json::Value = serde_json::from_str(std::fs::read_to_string(out_dir.join("results.jsonl")).unwrap().lines().next().unwrap()).unwrap();
+        assert_eq!(row["commitment"].as_str(), Some(bigint_to_hex(&expected).as_str()));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// Purpose: ensure JSON-lines manifests with --secrets prove what they can and never replace proofs unasked
+// Params: a JSON-lines manifest without secrets, a secrets CSV keyed by out (one value outside its range),
+//         then the same run again without and with --force
+// Output: exit 5 with the out-of-range row unprovable; then exit 2 with every row refused as existing;
+//         --force proves again; a value in the manifest next to --secrets is an invalid row
+// Usage: `cargo test --test cli_batch_prove` or `cargo test`
+#[test]
+fn json_lines_manifest_with_secrets_file() {
+    let (dir, params) = work_dir("jsonl");
+    let (manifest, secrets, out_dir) = (path(&dir, "rows.jsonl"), path(&dir, "secrets.csv"), path(&dir, "proofs"));
+    std::fs::write(&manifest, "{\"out\": \"x.proof\", \"a\": 0, \"b\": 255}\n{\"out\": \"y.proof\", \"a\": \"0x10\", \"b\": \"0x20\"}\n").unwrap();
+    std::fs::write(&secrets, "out,value,blinding\ny.proof,0x40,\nx.proof,200,77\n").unwrap();
+    let run = |extra: &[&str]| cuproof(&[&["batch-prove", "--secrets", secrets.as_str(), params.as_str(), manifest.as_str(), out_dir.as_str()][..], extra].concat());
+
+    let out = run(&[]);
+    assert_eq!(out.status.code(), Some(5));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("UNPROVABLE line 2 (y.proof): value lies outside the range"), "{}", stdout);
+    assert_eq!(results(Path::new(&out_dir)).iter().map(|(_, status, _)| status.as_str()).collect::<Vec<_>>(), ["proved", "unprovable"]);
+    assert_eq!(cuproof(&["verify", &params, "0", "255", &path(Path::new(&out_dir), "x.proof")]).status.code(), Some(0));
+
+    let out = run(&[]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("already exists; pass --force"));
+    assert_eq!(run(&["--force"]).status.code(), Some(5));
+
+    std::fs::write(&manifest, "{\"out\": \"x.proof\", \"value\": 1, \"a\": 0, \"b\": 255}\n").unwrap();
+    let out = run(&["--force"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("value and blinding belong in the secrets file"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
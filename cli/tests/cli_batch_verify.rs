@@ -0,0 +1,130 @@
+//! `cuproof batch-verify` over a directory and a manifest, driving the built binary
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use cuproof::util::{load_proof, save_proof};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn path(dir: &Path, name: &str) -> String {
+    dir.join(name).to_str().unwrap().to_string()
+}
+
+/// The last stdout line, which holds the counts
+fn summary(out: &Output) -> String {
+    String::from_utf8_lossy(&out.stdout).lines().last().unwrap_or_default().to_string()
+}
+
+/// A directory with two valid proofs, a proof whose t_hat was altered, a proof under rotated
+/// params, an unparseable file and a file batch-verify must ignore; returns (dir, params)
+fn drop_dir(name: &str) -> (PathBuf, String) {
+    let dir = std::env::temp_dir().join(format!("cuproof_batch_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (params, rotated) = (path(&dir, "params.bin"), path(&dir, "rotated.bin"));
+    assert!(cuproof(&["setup", "fast", &params]).status.success());
+    assert!(cuproof(&["params", "rotate", &params, "next", &rotated]).status.success());
+    for (file, v) in [("a.proof", "20"), ("b.proof", "30"), ("tampered.proof", "40")] {
+        assert!(cuproof(&["prove", "--ephemeral", &params, "10", "100", v, &path(&dir, file)]).status.success());
+    }
+    let tampered = path(&dir, "tampered.proof");
+    let mut proof = load_proof(&tampered).unwrap();
+    proof.t_hat += 1;
+    save_proof(&tampered, &proof).unwrap();
+    assert!(cuproof(&["prove", "--ephemeral", &rotated, "10", "100", "50", &path(&dir, "old_epoch.proof")]).status.success());
+    std::fs::write(dir.join("garbage.proof"), b"CUPROOF\0 not really").unwrap();
+    std::fs::write(dir.join("notes.txt"), b"not a proof and not named like one").unwrap();
+    (dir, params)
+}
+
+// Purpose: ensure a directory batch counts valid, tampered and unparseable proofs and names each failure
+// Params: the drop directory above, then the same directory without the unparseable file, --jobs 1 and 3
+// Output: "5 proofs: 2 valid, 2 invalid, 1 unreadable" with exit 2; then 1 while invalid proofs remain
+// Usage: `cargo test --test cli_batch_verify` or `cargo test`
+#[test]
+fn directory_batch_reports_each_failure() {
+    let (dir, params) = drop_dir("dir");
+    let dir_arg = dir.to_str().unwrap();
+    for jobs in ["1", "3"] {
+        let out = cuproof(&["batch-verify", &params, dir_arg, "--jobs", jobs]);
+        assert_eq!(out.status.code(), Some(2));
+        assert_eq!(summary(&out), "5 proofs: 2 valid, 2 invalid, 1 unreadable");
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains(&format!("INVALID  {}: proof does not verify", path(&dir, "tampered.proof"))), "{}", stdout);
+        assert!(stdout.contains(&format!("INVALID  {}: proof was made under generator epoch", path(&dir, "old_epoch.proof"))), "{}", stdout);
+        assert!(stdout.contains(&format!("ERROR    {}:", path(&dir, "garbage.proof"))), "{}", stdout);
+        assert!(stdout.contains(&format!("VALID    {}", path(&dir, "a.proof"))), "{}", stdout);
+        assert!(!stdout.contains("notes.txt"));
+    }
+
+    std::fs::remove_file(dir.join("garbage.proof")).unwrap();
+    let out = cuproof(&["batch-verify", &params, dir_arg]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(summary(&out), "4 proofs: 2 valid, 2 invalid, 0 unreadable");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// Purpose: ensure a manifest selects proofs, checks the ranges it lists, and exits 0 only when all verify
+// Params: manifests over the drop directory: valid proofs with their ranges, then one with a wrong range, then a malformed line
+// Output: exit 0 and "2 proofs: 2 valid, ..."; exit 1 naming the range mismatch; exit 2 for the
+//         malformed manifest; --json counts and per-proof statuses
+// Usage: `cargo test --test cli_batch_verify` or `cargo test`
+#[test]
+fn manifest_batch_checks_listed_ranges() {
+    let (dir, params) = drop_dir("manifest");
+    let manifest = path(&dir, "nightly.txt");
+    std::fs::write(&manifest, "# nightly drop\na.proof 0a 64\n\nb.proof\n").unwrap();
+    let out = cuproof(&["batch-verify", &params, &manifest]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stdout));
+    assert_eq!(summary(&out), "2 proofs: 2 valid, 0 invalid, 0 unreadable");
+
+    std::fs::write(&manifest, "a.proof 0a 64\nb.proof 0 64\n").unwrap();
+    let out = cuproof(&["batch-verify", &params, &manifest]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stdout).contains(&format!("INVALID  {}: statement range does not match", path(&dir, "b.proof"))));
+
+    std::fs::write(&manifest, "a.proof 0a\n").unwrap();
+    let out = cuproof(&["batch-verify", &params, &manifest]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("manifest line 1"));
+
+    std::fs::write(&manifest, "a.proof\ntampered.proof\n").unwrap();
+    let out = cuproof(&["batch-verify", &params, &manifest, "--json"]);
+    assert_eq!(out.status.code(), Some(1));
+    let document: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!((document["total"].as_u64(), document["valid"].as_u64(), document["invalid"].as_u64()), (Some(2), Some(1), Some(1)));
+    assert_eq!(document["results"][1]["status"], "invalid");
+    assert_eq!(document["results"][1]["error"], "proof does not verify");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// Purpose: ensure the worker count changes nothing but speed, wherever it comes from
+// Params: the drop directory with --json and --threads 1, --threads 4, CUPROOF_THREADS=4 and a
+//         CUPROOF_THREADS that is not a number
+// Output: the same exit status and byte-identical reports for every count; exit 2 naming the variable
+// Usage: `cargo test --test cli_batch_verify` or `cargo test`
+#[test]
+fn thread_count_does_not_change_results() {
+    let (dir, params) = drop_dir("threads");
+    let dir_arg = dir.to_str().unwrap();
+    let run = |threads: &[&str], env: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_cuproof"));
+        command.env_remove("CUPROOF_THREADS").args([&["--json"], threads, &["batch-verify", params.as_str(), dir_arg]].concat());
+        if let Some(env) = env { command.env("CUPROOF_THREADS", env); }
+        command.output().expect("failed to run cuproof")
+    };
+    let serial = run(&["--threads", "1"], None);
+    assert_eq!(serial.status.code(), Some(2));
+    let document: serde_json::Value = serde_json::from_slice(&serial.stdout).unwrap();
+    assert_eq!((document["valid"].as_u64(), document["invalid"].as_u64(), document["unreadable"].as_u64()), (Some(2), Some(2), Some(1)));
+    for parallel in [run(&["--threads", "4"], None), run(&[], Some("4")), run(&["--threads", "4"], Some("1"))] {
+        assert_eq!(parallel.status.code(), serial.status.code());
+        assert_eq!(String::from_utf8_lossy(&parallel.stdout), String::from_utf8_lossy(&serial.stdout));
+    }
+
+    let out = run(&[], Some("many"));
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("CUPROOF_THREADS must be a whole number of at least 1"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
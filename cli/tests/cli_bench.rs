@@ -0,0 +1,40 @@
+//! `cuproof bench` at tiny sizes, driving the built binary
+
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).current_dir(std::env::temp_dir()).args(args).output().expect("failed to run cuproof")
+}
+
+// Purpose: smoke-test the bench report in both output modes without writing files
+// Params: 256-bit throwaway params, a 32-bit range, 2 iterations, with and without --setup-sample; a bad --iterations
+// Output: prove / verify rows and the proof size in the text report; the JSON report has every timing key,
+//         setup_sample_ms only when asked; the bad value exits 2
+// Usage: `cargo test --test cli_bench` or `cargo test`
+#[test]
+fn bench_reports_timings_and_sizes() {
+    let out = cuproof(&["bench", "--bits", "256", "--range-bits", "32", "--iterations", "2"]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let rows: Vec<&str> = stdout.lines().collect();
+    assert!(rows[0].starts_with("256-bit params, range [0, 2^32 - 1], 2 iterations"), "{}", stdout);
+    assert!(rows[1].contains("mean ms") && rows[1].contains("median ms") && rows[1].contains("p95 ms"));
+    assert!(rows[2].starts_with("prove ") && rows[3].starts_with("verify "));
+    assert!(rows[4].starts_with("proof size: "));
+
+    let out = cuproof(&["--json", "bench", "--bits", "256", "--range-bits", "32", "--iterations", "2", "--setup-sample"]);
+    assert_eq!(out.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!((report["bits"].as_u64(), report["range_bits"].as_u64(), report["iterations"].as_u64()), (Some(256), Some(32), Some(2)));
+    for op in ["prove", "verify"] {
+        for key in ["mean_ms", "median_ms", "p95_ms"] {
+            assert!(report[op][key].as_f64().is_some_and(|ms| ms > 0.0), "{} {} in {}", op, key, report);
+        }
+    }
+    assert!(report["proof_size_bytes"].as_u64().is_some_and(|n| n > 0));
+    assert!(report["setup_sample_ms"].is_number());
+    assert_eq!(report["all_verified"], true);
+
+    let out = cuproof(&["bench", "--iterations", "0"]);
+    assert_eq!(out.status.code(), Some(2));
+}
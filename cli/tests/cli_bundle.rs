@@ -0,0 +1,57 @@
+//! `cuproof bundle create` and `bundle verify` with each source of params, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_bundle_{}_{}", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+// Purpose: ensure a bundle verifies under embedded params, a params file and a params directory
+// Params: fast params; a proof of v = 42 in [10, 100] bundled with its params and one metadata entry,
+//         then bundled again with a statement claiming [10, 50]
+// Output: exit 0 and VALID for each source of params, the metadata echoed; exit 2 from an empty
+//         directory and without a source; creating the bundle of the forged statement fails
+// Usage: `cargo test --test cli_bundle` or `cargo test`
+#[test]
+fn bundles_verify_under_each_params_source() {
+    let dir = temp_path("dir");
+    let empty = temp_path("empty");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::create_dir_all(&empty).unwrap();
+    let (params_path, proof_path, bundle_path, statement_path, forged_path) =
+        (format!("{}/params.bin", dir), temp_path("proof"), temp_path("bundle"), temp_path("statement"), temp_path("forged"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    assert!(cuproof(&["prove", "--blinding", "0x1234", &params_path, "10", "100", "42", &proof_path]).status.success());
+    let out = cuproof(&["bundle", "create", "--embed-params", "--meta", "purpose=kyc check", "--json", &params_path, &proof_path, &bundle_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("\"embedded_params\":true"));
+
+    let verify = |args: &[&str]| {
+        let out = cuproof(&[&["bundle", "verify", bundle_path.as_str()][..], args].concat());
+        (out.status.code(), String::from_utf8_lossy(&out.stdout).to_string())
+    };
+    for args in [&["--embedded"][..], &["--params", &params_path], &["--params-dir", &dir]] {
+        let (code, stdout) = verify(args);
+        assert_eq!(code, Some(0), "{:?}: {}", args, stdout);
+        assert_eq!(stdout.lines().last(), Some("VALID"));
+        assert!(stdout.contains("purpose: kyc check"));
+    }
+    assert_eq!(verify(&["--params-dir", &empty]).0, Some(2));
+    assert_eq!(verify(&[]).0, Some(2));
+
+    let proof = cuproof::util::load_proof(&proof_path).unwrap();
+    let mut statement = proof.statement.unwrap();
+    statement.b = 50u32.into();
+    std::fs::write(&statement_path, statement.to_bytes()).unwrap();
+    let out = cuproof(&["bundle", "create", "--statement", &statement_path, &params_path, &proof_path, &forged_path]);
+    assert_ne!(out.status.code(), Some(0));
+    assert!(!std::path::Path::new(&forged_path).exists());
+    for path in [proof_path, bundle_path, statement_path] { let _ = std::fs::remove_file(path); }
+    for path in [dir, empty] { let _ = std::fs::remove_dir_all(path); }
+}
@@ -0,0 +1,47 @@
+//! `setup --compress` and `prove --compress`, and compressed files read back by other commands, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::util::{is_compressed, load_params, load_proof};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_compressed_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure --compress writes compressed params and proofs that every reader takes, and that
+//          it is refused where there is no binary container to compress
+// Params: fast params with and without --compress; a proof of 42 in [10, 100] at level 19 over the
+//         compressed params; --compress=23; --compress with --armor
+// Output: compressed files smaller than the plain params, loading to the same values; verify
+//         VALID under either params file; info reporting zstd; exit 2 for both refusals
+// Usage: `cargo test --test cli_compressed_files` or `cargo test`
+#[test]
+fn compressed_params_and_proofs_are_read_everywhere() {
+    let (compressed_path, plain_path, proof_path) = (temp_path("params.bin"), temp_path("plain.bin"), temp_path("v.proof"));
+    assert!(cuproof(&["setup", "fast", "--compress", &compressed_path]).status.success());
+    assert!(cuproof(&["setup", "fast", &plain_path]).status.success());
+    let (compressed, plain) = (std::fs::read(&compressed_path).unwrap(), std::fs::read(&plain_path).unwrap());
+    assert!(is_compressed(&compressed) && !is_compressed(&plain));
+    assert!(compressed.len() < plain.len());
+    assert_eq!(load_params(&compressed_path).unwrap(), load_params(&plain_path).unwrap());
+
+    let out = cuproof(&["prove", "--compress=19", "--ephemeral", &compressed_path, "10", "100", "42", &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(is_compressed(&std::fs::read(&proof_path).unwrap()));
+    assert!(load_proof(&proof_path).is_ok());
+    for params_path in [&compressed_path, &plain_path] {
+        let out = cuproof(&["verify", params_path, "10", "100", &proof_path]);
+        assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    }
+    let out = cuproof(&["info", &proof_path]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("zstd"), "{}", String::from_utf8_lossy(&out.stdout));
+
+    assert_eq!(cuproof(&["setup", "fast", "--force", "--compress=23", &compressed_path]).status.code(), Some(2));
+    let out = cuproof(&["prove", "--compress", "--armor", "--ephemeral", &plain_path, "10", "100", "42", &proof_path]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--compress applies to the binary format"), "{}", String::from_utf8_lossy(&out.stderr));
+    for path in [compressed_path, plain_path, proof_path] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,112 @@
+//! `cuproof convert` between every encoding of the fixture proof, and of legacy text files, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::util::{load_params, load_proof, save_proof};
+
+const PROOF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/dump.proof");
+const PARAMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/dump_params.bin");
+const LEGACY_PARAMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/params_legacy.txt");
+const LEGACY_PROOF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/proof_legacy.txt");
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("cuproof_convert_{}_{}", std::process::id(), name));
+    path.to_str().unwrap().to_string()
+}
+
+/// `cuproof convert --params PARAMS --to <to> <input> <output>`, which must succeed
+fn convert(to: &str, input: &str, output: &str) {
+    let out = cuproof(&["convert", "--params", PARAMS, "--to", to, input, output]);
+    assert_eq!(out.status.code(), Some(0), "{} to {}: {}", input, to, String::from_utf8_lossy(&out.stderr));
+}
+
+/// The encodings this build writes
+fn encodings() -> Vec<&'static str> {
+    let mut encodings = vec!["bin"];
+    if cfg!(feature = "json") { encodings.push("json"); }
+    if cfg!(feature = "cbor") { encodings.push("cbor"); }
+    if cfg!(feature = "armor") { encodings.push("armor"); }
+    encodings
+}
+
+// Purpose: ensure every encoding of a proof converts to every other without changing the proof
+// Params: the fixture proof with its statement removed (JSON and CBOR have no place for it), in
+//         each encoding this build has, converted to each one and back to bin
+// Output: the bin container of the round trip equal byte for byte to that of the fixture
+// Usage: `cargo test --test cli_convert`, `cargo test --features json,cbor,armor --test cli_convert`
+#[test]
+fn proofs_round_trip_across_every_encoding_pair() {
+    let mut bare = load_proof(PROOF).unwrap();
+    bare.statement = None;
+    let base = temp_path("base.proof");
+    save_proof(&base, &bare).unwrap();
+    let expected = std::fs::read(&base).unwrap();
+    let mut paths = vec![base.clone()];
+    for from in encodings() {
+        let input = temp_path(&format!("in.{}", from));
+        convert(from, &base, &input);
+        for to in encodings() {
+            let (middle, back) = (temp_path(&format!("{}_to.{}", from, to)), temp_path(&format!("{}_{}_back.bin", from, to)));
+            convert(to, &input, &middle);
+            convert("bin", &middle, &back);
+            assert_eq!(std::fs::read(&back).unwrap(), expected, "{} -> {} -> bin", from, to);
+            paths.extend([middle, back]);
+        }
+        paths.push(input);
+    }
+    for path in paths { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure convert refuses to drop a proof's statement unless told to, and keeps it where it can
+// Params: the fixture proof, which records its statement; JSON and CBOR (features json, cbor) and armor
+// Output: without --allow-lossy exit 2 naming the statement and no file written; with it a proof
+//         without statement; armor and bin keep the proof byte for byte
+// Usage: `cargo test --test cli_convert`
+#[test]
+fn lossy_conversions_need_allow_lossy() {
+    let expected = std::fs::read(PROOF).unwrap();
+    let mut bare = load_proof(PROOF).unwrap();
+    bare.statement = None;
+    for to in encodings() {
+        let (output, back) = (temp_path(&format!("lossy.{}", to)), temp_path(&format!("lossy_{}.bin", to)));
+        if to == "json" || to == "cbor" {
+            let out = cuproof(&["convert", "--params", PARAMS, "--to", to, PROOF, &output]);
+            assert_eq!(out.status.code(), Some(2));
+            assert!(String::from_utf8_lossy(&out.stderr).contains("--allow-lossy"), "{}", String::from_utf8_lossy(&out.stderr));
+            assert!(!std::path::Path::new(&output).exists());
+            let out = cuproof(&["convert", "--params", PARAMS, "--allow-lossy", "--to", to, PROOF, &output]);
+            assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+            assert!(String::from_utf8_lossy(&out.stderr).contains("statement of the proof was dropped"));
+            convert("bin", &output, &back);
+            assert_eq!(load_proof(&back).unwrap().to_bytes(), bare.to_bytes());
+        } else {
+            convert(to, PROOF, &output);
+            convert("bin", &output, &back);
+            assert_eq!(std::fs::read(&back).unwrap(), expected, "{}", to);
+        }
+        for path in [output, back] { let _ = std::fs::remove_file(path); }
+    }
+}
+
+// Purpose: ensure legacy text files are only migrated with --upgrade, and keep their content when they are
+// Params: testdata/params_legacy.txt and testdata/proof_legacy.txt
+// Output: exit 2 pointing at --upgrade without it; with it binary files holding the same params and proof
+// Usage: `cargo test --test cli_convert`
+#[test]
+fn legacy_files_upgrade_only_with_upgrade() {
+    let (params, proof) = (temp_path("upgraded_params.bin"), temp_path("upgraded.proof"));
+    for (legacy, output) in [(LEGACY_PARAMS, &params), (LEGACY_PROOF, &proof)] {
+        let out = cuproof(&["convert", legacy, output]);
+        assert_eq!(out.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("--upgrade"), "{}", String::from_utf8_lossy(&out.stderr));
+        let out = cuproof(&["convert", "--upgrade", legacy, output]);
+        assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+        assert!(String::from_utf8_lossy(&out.stdout).contains("Migrated legacy text"));
+    }
+    assert_eq!(load_params(&params).unwrap().fingerprint_hex(), load_params(LEGACY_PARAMS).unwrap().fingerprint_hex());
+    assert_eq!(load_proof(&proof).unwrap().to_bytes(), load_proof(LEGACY_PROOF).unwrap().to_bytes());
+    for path in [params, proof] { let _ = std::fs::remove_file(path); }
+}
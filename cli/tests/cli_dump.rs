@@ -0,0 +1,53 @@
+//! `cuproof dump` of the fixture proof in testdata, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::util::{bigint_to_hex, load_proof, save_proof};
+
+const PROOF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/dump.proof");
+const PARAMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/dump_params.bin");
+const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/dump.golden");
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn stdout(out: &Output) -> String {
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    String::from_utf8(out.stdout.clone()).unwrap()
+}
+
+// Purpose: ensure the dump of the fixture proof stays byte-for-byte what other implementations were compared against
+// Params: testdata/dump.proof (v = 42 in [10, 100], blinding 0x1234) with its 512-bit params
+// Output: stdout equal to testdata/dump.golden: fields padded to 64-byte group elements, statement, challenges
+// Usage: `cargo test --test cli_dump` or `cargo test`
+#[test]
+fn dump_matches_golden_file() {
+    let dump = stdout(&cuproof(&["dump", "--params", PARAMS, PROOF]));
+    assert_eq!(dump, std::fs::read_to_string(GOLDEN).unwrap());
+}
+
+// Purpose: ensure --field prints bare hex for scripts, and dump works on proofs that do not verify
+// Params: the fixture proof, then a copy with t_hat altered
+// Output: C's padded hex; six L lines; a usage error for challenges without --params;
+//         the altered proof dumps its new t_hat while verify rejects it
+// Usage: `cargo test --test cli_dump` or `cargo test`
+#[test]
+fn field_selection_and_invalid_proofs() {
+    let proof = load_proof(PROOF).unwrap();
+    assert_eq!(stdout(&cuproof(&["dump", "--field", "C", PROOF])).trim(), format!("{:0>128}", bigint_to_hex(&proof.C)));
+    assert_eq!(stdout(&cuproof(&["dump", "--field", "L", PROOF])).lines().count(), 6);
+    assert_eq!(stdout(&cuproof(&["dump", "--params", PARAMS, "--field", "u[5]", PROOF])).lines().count(), 1);
+    let out = cuproof(&["dump", "--field", "y", PROOF]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("challenges need --params"));
+
+    let tampered = std::env::temp_dir().join(format!("cuproof_dump_tampered_{}.proof", std::process::id()));
+    let tampered = tampered.to_str().unwrap();
+    let mut bad = proof.clone();
+    bad.t_hat += 1;
+    save_proof(tampered, &bad).unwrap();
+    assert_eq!(cuproof(&["verify", PARAMS, "10", "100", tampered]).status.code(), Some(1));
+    let hex = stdout(&cuproof(&["dump", "--params", PARAMS, "--field", "t_hat", tampered]));
+    assert_eq!(hex.trim().trim_start_matches('0'), bigint_to_hex(&bad.t_hat).trim_start_matches('0'));
+    let _ = std::fs::remove_file(tampered);
+}
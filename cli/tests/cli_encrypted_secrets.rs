@@ -0,0 +1,70 @@
+//! `--encrypt` and encrypted secret files read back under `CUPROOF_PASSPHRASE`, driving the built binary
+
+use std::process::{Command, Output};
+use num_bigint::BigInt;
+use cuproof::commitment::commit;
+use cuproof::encrypted::is_encrypted;
+use cuproof::util::{load_encrypted, load_params, load_proof, read_blinding, ReadLimits};
+
+fn cuproof(passphrase: Option<&str>, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_cuproof"));
+    match passphrase {
+        Some(passphrase) => command.env("CUPROOF_PASSPHRASE", passphrase),
+        None => command.env_remove("CUPROOF_PASSPHRASE"),
+    };
+    command.args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_encrypted_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+fn stderr(out: &Output) -> String {
+    String::from_utf8_lossy(&out.stderr).into_owned()
+}
+
+// Purpose: ensure commit --encrypt writes the blinding encrypted, and that prove reads it back with
+//          the right passphrase and fails with distinct kinds for a wrong one and an altered file
+// Params: fast params, v = 42 in [10, 100], the passphrase "correct horse"; "battery staple"; the
+//         file with its last byte flipped; --encrypt without --blinding-out
+// Output: an encrypted file holding the blinding that opens the commitment; a proof for the
+//         commitment that verifies; exit 2 with serialization.wrong_passphrase, then
+//         serialization.tampered; exit 2 naming --blinding-out
+// Usage: `cargo test --test cli_encrypted_secrets` or `cargo test`
+#[test]
+fn encrypted_blinding_round_trips_through_commit_and_prove() {
+    let (params_path, commitment_path, blinding_path, proof_path) = (temp_path("params.bin"), temp_path("c.bin"), temp_path("r.bin"), temp_path("v.proof"));
+    assert!(cuproof(None, &["setup", "fast", &params_path]).status.success());
+    let params = load_params(&params_path).unwrap();
+
+    let out = cuproof(Some("correct horse"), &["commit", &params_path, "42", "--out", &commitment_path, "--blinding-out", &blinding_path, "--encrypt"]);
+    assert_eq!(out.status.code(), Some(0), "{}", stderr(&out));
+    assert!(is_encrypted(&std::fs::read(&blinding_path).unwrap()));
+    let plaintext = load_encrypted(&blinding_path, b"correct horse").unwrap();
+    let r = read_blinding(&mut plaintext.expose().as_slice(), &ReadLimits::default()).unwrap();
+    let out = cuproof(None, &["info", &blinding_path]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("argon2id"), "{}", String::from_utf8_lossy(&out.stdout));
+
+    let prove = |passphrase: &str| cuproof(Some(passphrase), &[
+        "--errors", "json", "prove", "--commitment", &commitment_path, "--blinding-in", &blinding_path, &params_path, "10", "100", "42", &proof_path,
+    ]);
+    let out = prove("correct horse");
+    assert_eq!(out.status.code(), Some(0), "{}", stderr(&out));
+    assert_eq!(load_proof(&proof_path).unwrap().C, commit(&params, &BigInt::from(42), &r));
+    assert_eq!(cuproof(None, &["verify", &params_path, "10", "100", &proof_path]).status.code(), Some(0));
+
+    let out = prove("battery staple");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr(&out).contains("\"kind\":\"serialization.wrong_passphrase\""), "{}", stderr(&out));
+    let mut bytes = std::fs::read(&blinding_path).unwrap();
+    *bytes.last_mut().unwrap() ^= 1;
+    std::fs::write(&blinding_path, &bytes).unwrap();
+    let out = prove("correct horse");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr(&out).contains("\"kind\":\"serialization.tampered\""), "{}", stderr(&out));
+
+    let out = cuproof(Some("correct horse"), &["commit", &params_path, "42", "--out", &commitment_path, "--encrypt"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr(&out).contains("--blinding-out"), "{}", stderr(&out));
+    for path in [params_path, commitment_path, blinding_path, proof_path] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,83 @@
+//! Exit statuses of `cuproof verify`, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_exit_{}_{}.bin", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+/// Insecure 512-bit params and a proof of 0x2a in [0xa, 0x64] over them
+fn params_and_proof(name: &str) -> (String, String) {
+    let (params, proof) = (temp_path(&format!("{}_params", name)), temp_path(&format!("{}_proof", name)));
+    assert!(cuproof(&["setup", "fast", &params]).status.success());
+    assert!(cuproof(&["prove", "--ephemeral", &params, "a", "64", "2a", &proof]).status.success());
+    (params, proof)
+}
+
+// Purpose: ensure a valid proof exits 0 with VALID on stdout
+// Params: fresh insecure params and an honest proof
+// Output: status 0, stdout ends with VALID, nothing but warnings on stderr
+// Usage: `cargo test --test cli_exit_codes` or `cargo test`
+#[test]
+fn valid_proof_exits_zero() {
+    let (params, proof) = params_and_proof("valid");
+    let out = cuproof(&["verify", &params, "a", "64", &proof]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&out.stdout).trim_end().ends_with("VALID"));
+    for path in [params, proof] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure a proof that does not verify exits 1 with INVALID on stdout
+// Params: the honest proof checked against another range, and against rotated params
+// Output: status 1 and an INVALID line on stdout for both
+// Usage: `cargo test --test cli_exit_codes` or `cargo test`
+#[test]
+fn invalid_proof_exits_one() {
+    let (params, proof) = params_and_proof("invalid");
+    let out = cuproof(&["verify", &params, "0", "64", &proof]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("INVALID"));
+
+    let rotated = temp_path("invalid_rotated");
+    assert!(cuproof(&["params", "rotate", &params, "next", &rotated]).status.success());
+    let out = cuproof(&["verify", &rotated, "a", "64", &proof]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("INVALID"));
+    for path in [params, proof, rotated] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure operational failures exit 2 with the error on stderr rather than a verdict
+// Params: missing arguments, a missing params file, a missing proof file, a garbage proof file, a bad hex bound
+// Output: status 2, no VALID / INVALID on stdout, a message on stderr
+// Usage: `cargo test --test cli_exit_codes` or `cargo test`
+#[test]
+fn operational_errors_exit_two() {
+    let (params, proof) = params_and_proof("errors");
+    let garbage = temp_path("errors_garbage");
+    std::fs::write(&garbage, b"CUPROOF\0 definitely not a proof").unwrap();
+    let missing = temp_path("errors_missing");
+    let cases: [&[&str]; 6] = [
+        &["verify", &params, "a", "64"],
+        &["verify", &missing, "a", "64", &proof],
+        &["verify", &params, "a", "64", &missing],
+        &["verify", &params, "a", "64", &garbage],
+        &["verify", &params, "xyz", "64", &proof],
+        &["frobnicate"],
+    ];
+    for args in cases {
+        let out = cuproof(args);
+        assert_eq!(out.status.code(), Some(2), "{:?}", args);
+        assert!(!String::from_utf8_lossy(&out.stdout).contains("VALID"), "{:?}", args);
+        assert!(!out.stderr.is_empty(), "{:?}", args);
+    }
+    let help = cuproof(&["--help"]);
+    assert_eq!(help.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&help.stdout).contains("Exit status"));
+    for path in [params, proof, garbage] { let _ = std::fs::remove_file(path); }
+}
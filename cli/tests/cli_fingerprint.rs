@@ -0,0 +1,88 @@
+//! `cuproof fingerprint` and the params fingerprint shown by prove, verify and info, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::setup::insecure_test_setup;
+use cuproof::util::{load_params, save_params};
+
+const LEGACY_PARAMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../testdata/params_legacy.txt");
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("cuproof_fingerprint_{}_{}", std::process::id(), name));
+    path.to_str().unwrap().to_string()
+}
+
+/// stdout of `cuproof fingerprint`, which must succeed
+fn fingerprint(args: &[&str]) -> String {
+    let out = cuproof(&[&["fingerprint"], args].concat());
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    String::from_utf8(out.stdout).unwrap().trim().to_string()
+}
+
+// Purpose: ensure the fingerprint identifies params, not files: it survives save / load and every encoding
+// Params: the insecure test params saved as a container, the legacy text file and its conversion,
+//         and with the json feature the JSON files in testdata
+// Output: 16 hex digits, the start of the 64 of --long and of Params::fingerprint_hex; equal
+//         fingerprints for files of the same params whatever their bytes
+// Usage: `cargo test --test cli_fingerprint` or `cargo test`
+#[test]
+fn fingerprint_is_stable_across_files_and_formats() {
+    let (bin, converted) = (temp_path("params.bin"), temp_path("converted.bin"));
+    let params = insecure_test_setup(512);
+    save_params(&bin, &params).unwrap();
+    let short = fingerprint(&[&bin]);
+    let long = fingerprint(&["--long", &bin]);
+    assert_eq!((short.len(), long.len()), (16, 64));
+    assert_eq!(long, params.fingerprint_hex().to_string());
+    assert_eq!(long, load_params(&bin).unwrap().fingerprint_hex().to_string());
+    assert!(long.starts_with(&short));
+
+    assert!(cuproof(&["convert", "--upgrade", LEGACY_PARAMS, &converted]).status.success());
+    assert_ne!(std::fs::read(LEGACY_PARAMS).unwrap(), std::fs::read(&converted).unwrap());
+    assert_eq!(fingerprint(&["--long", LEGACY_PARAMS]), fingerprint(&["--long", &converted]));
+
+    #[cfg(feature = "json")]
+    for json in ["params_v1.json", "params_v2.json"] {
+        let json = format!("{}/../testdata/{}", env!("CARGO_MANIFEST_DIR"), json);
+        assert_eq!(fingerprint(&["--long", &json]), long);
+    }
+    for path in [bin, converted] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure prove, verify and info name the params they loaded, and verify warns on a proof made under other params
+// Params: fast params and their rotation; a proof under the first, verified against both
+// Output: the short fingerprint in prove's, verify's and info's output; no warning for the matching
+//         params; for the rotated ones a PARAMS MISMATCH warning naming both fingerprints, then exit 1
+// Usage: `cargo test --test cli_fingerprint` or `cargo test`
+#[test]
+fn commands_show_the_fingerprint_and_verify_warns_on_mismatch() {
+    let (params, rotated, proof) = (temp_path("fast.bin"), temp_path("rotated.bin"), temp_path("v.proof"));
+    assert!(cuproof(&["setup", "fast", &params]).status.success());
+    assert!(cuproof(&["params", "rotate", &params, "next", &rotated]).status.success());
+    let (short, other) = (fingerprint(&[&params]), fingerprint(&[&rotated]));
+    assert_ne!(short, other);
+
+    let out = cuproof(&["prove", "--ephemeral", &params, "10", "100", "42", &proof]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains(&format!("Params {}: 512 bits, fingerprint {}", params, short)));
+    let out = cuproof(&["info", &params]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains(&format!("Params fingerprint: {}", short)));
+    let out = cuproof(&["info", &proof]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains(&format!("Params fingerprint: {}", short)));
+
+    let out = cuproof(&["verify", &params, "10", "100", &proof]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&out.stdout).contains(&format!("fingerprint {}", short)));
+    assert!(!String::from_utf8_lossy(&out.stderr).contains("PARAMS MISMATCH"));
+
+    let out = cuproof(&["verify", &rotated, "10", "100", &proof]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains(&format!("WARNING: PARAMS MISMATCH. The proof was made under params with fingerprint {}", short)), "{}", stderr);
+    assert!(stderr.contains(&format!("has fingerprint {}", other)), "{}", stderr);
+    let verdict = String::from_utf8_lossy(&out.stdout);
+    assert!(verdict.contains(&format!("fingerprint {}", other)) && verdict.trim_end().ends_with("verify with the matching params)"), "{}", verdict);
+    for path in [params, rotated, proof] { let _ = std::fs::remove_file(path); }
+}
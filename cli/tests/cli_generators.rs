@@ -0,0 +1,73 @@
+//! `cuproof generators` and the --generators check of aggregate and verify, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::generators::GeneratorVector;
+use cuproof::setup::insecure_test_setup;
+use cuproof::util::{load_generators, save_generators, save_params};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_generators_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure a derived vector is saved with its seed, loads back, and pins the generators of
+//          aggregate and verify, while an altered or mismatched vector is refused
+// Params: insecure 512-bit params; a two-row aggregate manifest; vectors of 2 and 3 under agg/g
+// Output: generators prints the fingerprint of GeneratorVector::derive and writes a file that loads
+//         to the same vector; aggregate and verify pass with it; a copy with generator 1 changed
+//         and a valid checksum fails with params.generator_vector_mismatch (exit 3), a byte edit
+//         with serialization.format (exit 2), a vector of 3 and a single proof with usage errors
+// Usage: `cargo test --test cli_generators` or `cargo test`
+#[test]
+fn generator_files_are_checked_against_their_derivation() {
+    let (params_path, manifest_path, proof_path) = (temp_path("params.bin"), temp_path("rows.csv"), temp_path("agg.proof"));
+    let (gens_path, altered_path, three_path) = (temp_path("agg2.gens"), temp_path("altered.gens"), temp_path("agg3.gens"));
+    let params = insecure_test_setup(512);
+    save_params(&params_path, &params).unwrap();
+    std::fs::write(&manifest_path, "value,blinding,a,b\n42,0x1f2e,0,100\n7,0x51,0,10\n").unwrap();
+
+    let out = cuproof(&["generators", "--label", "agg/g", "--count", "2", "--out", &gens_path, &params_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let expected = GeneratorVector::derive(&params, "agg/g", 2);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains(&format!("Fingerprint: {}", expected.fingerprint_hex())), "{}", stdout);
+    assert!(stdout.contains(&format!("Seed: {}/agg/g/2", params.fingerprint_hex())), "{}", stdout);
+    let loaded = load_generators(&gens_path).unwrap();
+    assert_eq!(loaded, expected);
+    let out = cuproof(&["info", &gens_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+
+    let out = cuproof(&["aggregate", "--generators", &gens_path, &params_path, &manifest_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let out = cuproof(&["--json", "verify", "--generators", &gens_path, &params_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let json = String::from_utf8_lossy(&out.stdout);
+    assert!(json.contains("\"result\":\"valid\"") && json.contains(&expected.fingerprint_hex().to_string()), "{}", json);
+
+    let mut altered = loaded.clone();
+    altered.generators[1] += 1;
+    save_generators(&altered_path, &altered).unwrap();
+    let out = cuproof(&["--errors", "json", "verify", "--generators", &altered_path, &params_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(3), "{}", String::from_utf8_lossy(&out.stderr));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("\"kind\":\"params.generator_vector_mismatch\"") && stderr.contains("\"index\":1"), "{}", stderr);
+
+    let mut bytes = std::fs::read(&gens_path).unwrap();
+    let last = bytes.len() - 40;
+    bytes[last] ^= 1;
+    std::fs::write(&altered_path, &bytes).unwrap();
+    let out = cuproof(&["--errors", "json", "aggregate", "--generators", &altered_path, &params_path, &manifest_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"serialization.format\""), "{}", String::from_utf8_lossy(&out.stderr));
+
+    save_generators(&three_path, &GeneratorVector::derive(&params, "agg/g", 3)).unwrap();
+    let out = cuproof(&["verify", "--generators", &three_path, &params_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("holds 3 generators"), "{}", String::from_utf8_lossy(&out.stderr));
+    let out = cuproof(&["verify", "--generators", &gens_path, &params_path, "0", "100", &proof_path]);
+    assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+    for path in [params_path, manifest_path, proof_path, gens_path, altered_path, three_path] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,59 @@
+//! `cuproof info` on good and damaged files, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_info_{}_{}.bin", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+// Purpose: ensure info describes a proof and params without verifying them, and damaged files as far as they parse
+// Params: fast params, a proof of 42 in [10, 100], the proof cut to 400 bytes, 2 KiB of hashed noise
+// Output: exit 0 with the statement, IPP rounds and fingerprint for the good files; exit 2 naming
+//         where parsing stopped for the others; --json carries the same as keys
+// Usage: `cargo test --test cli_info` or `cargo test`
+#[test]
+fn info_reports_structure_and_damage() {
+    let (params, proof, truncated, noise) = (temp_path("params"), temp_path("proof"), temp_path("truncated"), temp_path("noise"));
+    assert!(cuproof(&["setup", "fast", &params]).status.success());
+    assert!(cuproof(&["prove", "--ephemeral", &params, "10", "100", "42", &proof]).status.success());
+
+    let out = cuproof(&["info", &proof]);
+    assert_eq!(out.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    for expected in ["proof (container, version 2)", "ipp_rounds:", "range:                        [0a, 64]", "canonical:                    yes", "Structure: ok"] {
+        assert!(stdout.contains(expected), "missing {:?} in\n{}", expected, stdout);
+    }
+
+    let out = cuproof(&["info", &params]);
+    assert_eq!(out.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("params (container, version 1)") && stdout.contains("insecure:                     yes"), "{}", stdout);
+
+    let bytes = std::fs::read(&proof).unwrap();
+    std::fs::write(&truncated, &bytes[..400]).unwrap();
+    let out = cuproof(&["info", &truncated]);
+    assert_eq!(out.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("  A ") && stdout.contains("parsing stopped at container"), "{}", stdout);
+
+    let out = cuproof(&["--json", "info", &truncated]);
+    assert_eq!(out.status.code(), Some(2));
+    let document: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!((document["kind"].as_str(), document["well_formed"].as_bool()), (Some("proof"), Some(false)));
+    assert_eq!(document["stopped"]["at"], "container");
+    assert_eq!(document["components"][0]["name"], "A");
+
+    let noise_bytes: Vec<u8> = (0..2048u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+    std::fs::write(&noise, &noise_bytes).unwrap();
+    let out = cuproof(&["--json", "info", &noise]);
+    assert_eq!(out.status.code(), Some(2));
+    let document: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!((document["kind"].as_str(), document["stopped"]["at"].as_str()), (Some("unknown"), Some("magic")));
+    for path in [params, proof, truncated, noise] { let _ = std::fs::remove_file(path); }
+}
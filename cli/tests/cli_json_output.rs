@@ -0,0 +1,182 @@
+//! Schema of `cuproof --json`: one JSON object on stdout per command, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use serde_json::Value;
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_json_{}_{}.bin", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+/// Run with --json; stdout must be exactly one JSON object
+fn json(args: &[&str]) -> (Option<i32>, Value) {
+    let out = cuproof(&[&["--json"], args].concat());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout.trim_end().lines().count(), 1, "{}", stdout);
+    let document: Value = serde_json::from_str(&stdout).unwrap_or_else(|e| panic!("{}: {}", e, stdout));
+    assert!(document.is_object(), "{}", stdout);
+    (out.status.code(), document)
+}
+
+fn assert_keys(document: &Value, keys: &[&str]) {
+    for key in keys {
+        assert!(document.get(key).is_some(), "missing {} in {}", key, document);
+    }
+}
+
+// Purpose: pin the keys of setup, commit, prove and verify so scripts can rely on them
+// Params: fast params, a commitment to 42 and a proof for it, verified against its range and a wrong one
+// Output: setup has params_path, bits, fingerprint, insecure, transcript_path; commit has commitment,
+//         commitment_path, blinding_path; prove has proof_path, proof_id, commitment, prove_ms, params_fingerprint;
+//         verify has result, error, proof {size, version, epoch} and statement; human text is on stderr
+// Usage: `cargo test --test cli_json_output` or `cargo test`
+#[test]
+fn commands_emit_one_object_with_stable_keys() {
+    let (params, commitment, blinding, proof) = (temp_path("params"), temp_path("commitment"), temp_path("blinding"), temp_path("proof"));
+    let (code, setup) = json(&["setup", "fast", &params]);
+    assert_eq!(code, Some(0));
+    assert_keys(&setup, &["params_path", "bits", "fingerprint", "insecure", "transcript_path"]);
+    assert_eq!((setup["bits"].as_u64(), setup["insecure"].as_bool()), (Some(512), Some(true)));
+    assert!(setup["transcript_path"].is_null());
+    assert_eq!(setup["fingerprint"].as_str().map(str::len), Some(64));
+
+    let (code, commit) = json(&["commit", &params, "42", "--out", &commitment, "--blinding-out", &blinding]);
+    assert_eq!(code, Some(0));
+    assert_keys(&commit, &["commitment", "commitment_path", "blinding_path"]);
+
+    let (code, prove) = json(&["prove", "--commitment", &commitment, "--blinding-in", &blinding, &params, "10", "100", "42", &proof]);
+    assert_eq!(code, Some(0));
+    assert_keys(&prove, &["proof_path", "proof_id", "commitment", "prove_ms", "params_fingerprint"]);
+    assert_eq!(prove["params_fingerprint"], setup["fingerprint"]);
+    assert_eq!(prove["commitment"], commit["commitment"]);
+    assert!(prove["prove_ms"].is_number());
+
+    let out = cuproof(&["--json", "verify", &params, "10", "100", &proof]);
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Statement:"));
+    let (code, verify) = json(&["verify", &params, "10", "100", &proof]);
+    assert_eq!(code, Some(0));
+    assert_keys(&verify, &["result", "error", "proof", "statement"]);
+    assert_eq!((verify["result"].as_str(), verify["error"].is_null()), (Some("valid"), true));
+    assert_keys(&verify["proof"], &["size", "version", "epoch"]);
+    assert_eq!(verify["proof"]["size"].as_u64(), std::fs::metadata(&proof).ok().map(|m| m.len()));
+    assert_eq!(verify["statement"]["commitment"], commit["commitment"]);
+
+    let (code, verify) = json(&["verify", &params, "0", "100", &proof]);
+    assert_eq!(code, Some(1));
+    assert_eq!(verify["result"], "invalid");
+    assert!(verify["error"].is_string());
+    for path in [params, commitment, blinding, proof] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure failures are an error object on stdout with a non-zero exit, in both flag positions
+// Params: a missing params file, an unknown command, and benchmark, which has no JSON form
+// Output: {"error": {"kind", "message"}} with kind serialization / usage and exit 2
+// Usage: `cargo test --test cli_json_output` or `cargo test`
+#[test]
+fn errors_are_json_objects() {
+    let missing = temp_path("missing");
+    let (code, document) = json(&["verify", &missing, "10", "100", &missing]);
+    assert_eq!(code, Some(2));
+    assert_keys(&document["error"], &["kind", "message"]);
+    assert_eq!(document["error"]["kind"], "serialization");
+
+    let out = cuproof(&["frobnicate", "--json"]);
+    assert_eq!(out.status.code(), Some(2));
+    let document: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(document["error"]["kind"], "usage");
+    assert!(document["error"]["message"].as_str().unwrap().contains("Unknown command frobnicate"));
+
+    let (code, document) = json(&["benchmark", "fast", "8"]);
+    assert_eq!((code, document["error"]["kind"].as_str()), (Some(2), Some("usage")));
+}
+
+/// Run with --errors json; the last line of stderr must be the error object
+fn stderr_error(args: &[&str]) -> (Option<i32>, Value) {
+    let out = cuproof(&[&["--errors", "json"], args].concat());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    let last = stderr.trim_end().lines().last().unwrap_or_default();
+    let document: Value = serde_json::from_str(last).unwrap_or_else(|e| panic!("{}: {}", e, stderr));
+    assert_keys(&document, &["kind", "message", "category", "exit_code"]);
+    assert_eq!(document["exit_code"].as_i64().map(|code| code as i32), out.status.code());
+    (out.status.code(), document)
+}
+
+// Purpose: ensure --errors json tells failures apart by kind, with the path or field they concern
+// Params: a missing params file, a malformed hex argument, a tampered proof file, a proof for another
+//         range, and a missing params file under --json
+// Output: serialization.io with io_error not_found and the path; usage.invalid_value naming the
+//         argument; serialization.format for a corrupted file; verify.invalid_proof with exit 1;
+//         --json writes the same object on stderr and keeps the object on stdout
+// Usage: `cargo test --test cli_json_output` or `cargo test`
+#[test]
+fn errors_json_names_the_kind_on_stderr() {
+    let (params, proof, missing) = (temp_path("errors_params"), temp_path("errors_proof"), temp_path("errors_missing"));
+    assert_eq!(cuproof(&["setup", "fast", &params]).status.code(), Some(0));
+    assert_eq!(cuproof(&["prove", "--ephemeral", &params, "10", "100", "42", &proof]).status.code(), Some(0));
+
+    let (code, error) = stderr_error(&["verify", &missing, "10", "100", &proof]);
+    assert_eq!(code, Some(2));
+    assert_eq!((error["kind"].as_str(), error["io_error"].as_str()), (Some("serialization.io"), Some("not_found")));
+    assert_eq!((error["path"].as_str(), error["file"].as_str()), (Some(missing.as_str()), Some("params")));
+
+    let (code, error) = stderr_error(&["verify", &params, "10", "0xfg", &proof]);
+    assert_eq!((code, error["kind"].as_str(), error["category"].as_str()), (Some(2), Some("usage.invalid_value"), Some("usage")));
+    assert_eq!((error["field"].as_str(), error["value"].as_str()), (Some("b"), Some("0xfg")));
+
+    let mut bytes = std::fs::read(&proof).unwrap();
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 1;
+    let tampered = temp_path("errors_tampered");
+    std::fs::write(&tampered, &bytes).unwrap();
+    let (code, error) = stderr_error(&["verify", &params, "10", "100", &tampered]);
+    assert_eq!((code, error["kind"].as_str()), (Some(2), Some("serialization.format")), "{}", error);
+
+    let (code, error) = stderr_error(&["verify", &params, "0", "100", &proof]);
+    assert_eq!((code, error["kind"].as_str(), error["category"].as_str()), (Some(1), Some("verify.statement_mismatch"), Some("verify")));
+    assert_eq!(error["field"], "range");
+
+    let out = cuproof(&["--json", "verify", &missing, "10", "100", &proof]);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    let error: Value = serde_json::from_str(stderr.trim_end().lines().last().unwrap()).unwrap();
+    assert_eq!(error["kind"], "serialization.io");
+    let document: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(document["error"]["kind"], "serialization");
+    for path in [params, proof, tampered] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure --time adds a per-phase report to prove and verify without changing their results
+// Params: fast params, a proof of 42 in [10, 100] made and verified with --time --json
+// Output: timing holds deserialize_ms, challenges_ms, commitments_ms, ipp_ms, total_ms and
+//         peak_rss_kib; totals are positive; the verdict, exit status and other keys are unchanged,
+//         and without --json the report is one Time: line on stderr while stdout ends in VALID
+// Usage: `cargo test --test cli_json_output` or `cargo test`
+#[test]
+fn time_reports_the_phases_of_prove_and_verify() {
+    let (params, proof) = (temp_path("time_params"), temp_path("time_proof"));
+    assert_eq!(json(&["setup", "fast", &params]).0, Some(0));
+    let (code, prove) = json(&["prove", "--time", "--ephemeral", &params, "10", "100", "42", &proof]);
+    assert_eq!(code, Some(0));
+    assert_keys(&prove, &["proof_path", "proof_id", "commitment", "prove_ms", "timing"]);
+    let (code, verify) = json(&["verify", "--time", &params, "10", "100", &proof]);
+    assert_eq!((code, verify["result"].as_str()), (Some(0), Some("valid")));
+    for timing in [&prove["timing"], &verify["timing"]] {
+        assert_keys(timing, &["deserialize_ms", "challenges_ms", "commitments_ms", "ipp_ms", "total_ms", "peak_rss_kib"]);
+        assert!(timing["total_ms"].as_f64().is_some_and(|ms| ms > 0.0), "{}", timing);
+        assert!(timing["deserialize_ms"].as_f64().is_some_and(|ms| ms > 0.0), "{}", timing);
+    }
+    assert!(prove["timing"]["ipp_ms"].as_f64().is_some_and(|ms| ms > 0.0), "{}", prove);
+
+    let out = cuproof(&["verify", "--time", &params, "10", "100", &proof]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&out.stdout).ends_with("VALID\n"));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.lines().any(|line| line.starts_with("Time: deserialize ") && line.contains(", total ")), "{}", stderr);
+    let (code, _) = json(&["verify", "--time", &params, "0", "100", &proof]);
+    assert_eq!(code, Some(1));
+    for path in [params, proof] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,51 @@
+//! `cuproof -v`, `--quiet` and `--log-format json` on stderr, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::setup::insecure_test_setup;
+use cuproof::util::save_params;
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_logging_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure the verbosity flags and log formats shape stderr as documented
+// Params: insecure 512-bit params (which draw a warning); prove v = 0x2a in [0, 0x64] with the
+//         blinding from the environment, at the default level, with -v, with --quiet and as JSON
+// Output: the insecure warning by default; -v adds the prove span with the proof id; --quiet leaves
+//         stderr empty; with --log-format json every line is a JSON object, one of them "proof created"
+// Usage: `cargo test --test cli_logging` or `cargo test`
+#[test]
+fn verbosity_and_format_shape_stderr() {
+    let (params_path, proof_path) = (temp_path("params.bin"), temp_path("v.proof"));
+    save_params(&params_path, &insecure_test_setup(512)).unwrap();
+    let prove = |flags: &[&str]| {
+        let mut args = flags.to_vec();
+        args.extend_from_slice(&["prove", "--value-env", "CUPROOF_LOGGING_V", "--blinding-env", "CUPROOF_LOGGING_R", &params_path, "0x0", "0x64", &proof_path]);
+        let out = Command::new(env!("CARGO_BIN_EXE_cuproof")).args(&args)
+            .env("CUPROOF_LOGGING_V", "0x2a").env("CUPROOF_LOGGING_R", "0x1f2e").output().unwrap();
+        assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+        String::from_utf8_lossy(&out.stderr).into_owned()
+    };
+
+    let stderr = prove(&[]);
+    assert!(stderr.contains("WARNING: INSECURE TEST PARAMETERS"), "{}", stderr);
+    assert!(!stderr.contains("debug:"), "{}", stderr);
+
+    let stderr = prove(&["-v"]);
+    assert!(stderr.lines().any(|line| line.starts_with("debug: prove{bits=64") && line.contains("proof created proof_id=")), "{}", stderr);
+
+    assert_eq!(prove(&["--quiet"]), "");
+
+    let stderr = prove(&["--log-format", "json", "-v"]);
+    let events: Vec<serde_json::Value> = stderr.lines().map(|line| serde_json::from_str(line).expect(line)).collect();
+    let created = events.iter().find(|event| event["fields"]["message"] == "proof created").expect(&stderr);
+    assert_eq!(created["span"]["name"], "prove");
+    assert_eq!(created["fields"]["proof_id"].as_str().map(str::len), Some(64));
+
+    assert_eq!(cuproof(&["-q", "-v", "fingerprint", &params_path]).status.code(), Some(2));
+    for path in [params_path, proof_path] { let _ = std::fs::remove_file(path); }
+}
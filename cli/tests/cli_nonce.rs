@@ -0,0 +1,75 @@
+//! `cuproof prove --nonce` and `verify --nonce` binding a proof to the verifier's session, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_nonce_{}_{}.bin", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+// Purpose: ensure a proof made with --nonce verifies only with the same nonce, with a range or a statement file
+// Params: fast params; a proof of v = 42 in [10, 100] under nonce 0x5e55101f, one without a nonce
+// Output: exit 0 and VALID with the matching nonce; exit 1 and INVALID without one or with another;
+//         the nonce-less proof still VALID without --nonce and an empty one; exit 2 for odd hex
+// Usage: `cargo test --test cli_nonce` or `cargo test`
+#[test]
+fn proofs_verify_only_under_their_nonce() {
+    let (params_path, proof_path, plain_path, statement_path) = (temp_path("params"), temp_path("proof"), temp_path("plain"), temp_path("statement"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    let out = cuproof(&["prove", "--blinding", "0x1234", "--nonce", "0x5e55101f", "--json", &params_path, "10", "100", "42", &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("\"nonce\":\"5e55101f\""));
+    assert!(cuproof(&["prove", "--blinding", "0x1234", &params_path, "10", "100", "42", &plain_path]).status.success());
+    let verify = |path: &str, args: &[&str]| {
+        let out = cuproof(&[&["verify", params_path.as_str(), path, "--range", "10", "100"][..], args].concat());
+        (out.status.code(), String::from_utf8_lossy(&out.stdout).lines().last().map(str::to_string))
+    };
+    let valid = (Some(0), Some("VALID".to_string()));
+
+    assert_eq!(verify(&proof_path, &["--nonce", "5e55101f"]), valid);
+    assert_eq!(verify(&proof_path, &[]).0, Some(1));
+    assert_eq!(verify(&proof_path, &["--nonce", "5e55101e"]).0, Some(1));
+    assert_eq!(verify(&plain_path, &[]), valid);
+    assert_eq!(verify(&plain_path, &["--nonce", ""]), valid);
+    assert_eq!(verify(&plain_path, &["--nonce", "5e55101f"]).0, Some(1));
+    assert_eq!(verify(&proof_path, &["--nonce", "5e5"]).0, Some(2));
+
+    let proof = cuproof::util::load_proof(&proof_path).unwrap();
+    std::fs::write(&statement_path, proof.statement.unwrap().to_bytes()).unwrap();
+    let statement = |nonce: &str| cuproof(&["verify", &params_path, &proof_path, "--statement", &statement_path, "--nonce", nonce]).status.code();
+    assert_eq!(statement("5e55101f"), Some(0));
+    assert_eq!(statement("00"), Some(1));
+    for path in [params_path, proof_path, plain_path, statement_path] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure prove --label with --nonce binds the label, so a statement expecting another label fails under the same nonce
+// Params: fast params; a proof of v = 42 in [10, 100] under label "withdraw" and nonce 0x5e55101f; its statement
+//         file, and the same statement relabelled "deposit" in a copy of the proof and in the statement file
+// Output: exit 0 with the proof's own statement and nonce; exit 1 for the relabelled statement, with or
+//         without the proof's embedded label rewritten to match
+// Usage: `cargo test --test cli_nonce` or `cargo test`
+#[test]
+fn label_and_nonce_compose() {
+    let (params_path, proof_path, relabelled_path) = (temp_path("label_params"), temp_path("label_proof"), temp_path("label_relabelled"));
+    let (statement_path, deposit_path) = (temp_path("label_statement"), temp_path("label_deposit"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    let out = cuproof(&["prove", "--blinding", "0x1234", "--nonce", "5e55101f", "--label", "withdraw", &params_path, "10", "100", "42", &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let proof = cuproof::util::load_proof(&proof_path).unwrap();
+    let statement = proof.statement.clone().unwrap();
+    assert_eq!(statement.label, "withdraw");
+    std::fs::write(&statement_path, statement.to_bytes()).unwrap();
+    std::fs::write(&deposit_path, statement.with_label("deposit").to_bytes()).unwrap();
+    cuproof::util::save_proof(&relabelled_path, &proof.with_label("deposit")).unwrap();
+    let verify = |proof: &str, statement: &str| cuproof(&["verify", &params_path, proof, "--statement", statement, "--nonce", "5e55101f"]).status.code();
+
+    assert_eq!(verify(&proof_path, &statement_path), Some(0));
+    assert_eq!(verify(&proof_path, &deposit_path), Some(1));
+    assert_eq!(verify(&relabelled_path, &deposit_path), Some(1));
+    for path in [params_path, proof_path, relabelled_path, statement_path, deposit_path] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,56 @@
+//! What `cuproof prove` does with the blinding of the commitment it proves for, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use num_bigint::BigInt;
+use cuproof::commitment::commit;
+use cuproof::util::{load_blinding, load_params, load_proof};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_blinding_{}_{}.bin", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+// Purpose: ensure prove keeps the blinding it draws, uses the one it is given, and refuses to lose it silently
+// Params: fast params, v = 42 in [10, 100]; --blinding-out, --blinding 0x1234, --blinding-in of the saved file,
+//         no blinding option at all, and --ephemeral
+// Output: the saved and the supplied blindings open the commitment in their proofs (commit(v, r) == C) and
+//         the proofs verify; without an option prove exits 2 naming --ephemeral and writes no proof
+// Usage: `cargo test --test cli_prove_blinding` or `cargo test`
+#[test]
+fn prove_saves_supplies_or_refuses_to_discard_the_blinding() {
+    let (params_path, saved, proof, refused) = (temp_path("params"), temp_path("saved"), temp_path("proof"), temp_path("refused"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    let params = load_params(&params_path).unwrap();
+    let v = BigInt::from(42);
+    let verify = |proof: &str| cuproof(&["verify", &params_path, "10", "100", proof]).status.code();
+
+    let out = cuproof(&["prove", "--blinding-out", &saved, &params_path, "10", "100", "42", &proof]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("holds the blinding"));
+    let r = load_blinding(&saved).unwrap();
+    assert_eq!(commit(&params, &v, &r), load_proof(&proof).unwrap().C);
+    assert_eq!(verify(&proof), Some(0));
+
+    let out = cuproof(&["prove", "--blinding-in", &saved, &params_path, "10", "100", "42", &proof]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(commit(&params, &v, &r), load_proof(&proof).unwrap().C);
+
+    let out = cuproof(&["prove", "--blinding", "0x1234", &params_path, "10", "100", "42", &proof]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(commit(&params, &v, &BigInt::from(0x1234)), load_proof(&proof).unwrap().C);
+    assert_eq!(verify(&proof), Some(0));
+
+    let out = cuproof(&["prove", &params_path, "10", "100", "42", &refused]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--ephemeral"));
+    assert!(!std::path::Path::new(&refused).exists());
+
+    assert!(cuproof(&["prove", "--ephemeral", &params_path, "10", "100", "42", &refused]).status.success());
+    assert_eq!(verify(&refused), Some(0));
+    for path in [params_path, saved, proof, refused] { let _ = std::fs::remove_file(path); }
+}
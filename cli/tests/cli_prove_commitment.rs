@@ -0,0 +1,53 @@
+//! `cuproof prove --commitment` for a commitment written by `cuproof commit`, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::util::{bigint_to_hex, load_blinding, load_commitment, load_params, load_proof};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_prove_commitment_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure commit → prove --commitment → verify --commitment chains, and a wrong opening is
+//          refused before anything is proved
+// Params: fast params; a commitment to 42 with its blinding saved by commit; the range [10, 100]
+//         given by --range and positionally; the blinding as a file, as hex, and a wrong one
+// Output: proofs whose C is the committed value, VALID against --commitment <hex>; a wrong blinding
+//         exits 5 with prove.commitment_mismatch and writes no proof
+// Usage: `cargo test --test cli_prove_commitment` or `cargo test`
+#[test]
+fn proofs_are_bound_to_the_committed_value() {
+    let (params_path, commitment_path, blinding_path) = (temp_path("params.bin"), temp_path("c.bin"), temp_path("r.bin"));
+    let (proof_path, wrong_path) = (temp_path("v.proof"), temp_path("wrong.proof"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    let out = cuproof(&["commit", &params_path, "42", "--out", &commitment_path, "--blinding-out", &blinding_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let params = load_params(&params_path).unwrap();
+    let commitment = load_commitment(&commitment_path, &params).unwrap();
+    let commitment_hex = bigint_to_hex(&commitment);
+    let blinding_hex = format!("0x{}", bigint_to_hex(&load_blinding(&blinding_path).unwrap()));
+
+    let out = cuproof(&["prove", &params_path, "--commitment", &commitment_path, "--blinding", &blinding_path, "--range", "10", "100", "42", &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(load_proof(&proof_path).unwrap().C, commitment);
+    let out = cuproof(&["verify", &params_path, &proof_path, "--range", "10", "100", "--commitment", &commitment_hex]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout).lines().last(), Some("VALID"));
+
+    let out = cuproof(&["prove", "--commitment", &commitment_path, "--blinding", &blinding_hex, &params_path, "10", "100", "42", &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let out = cuproof(&["verify", &params_path, "10", "100", &proof_path, "--commitment", &commitment_hex]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stdout));
+
+    let out = cuproof(&["--errors", "json", "prove", &params_path, "--commitment", &commitment_path, "--blinding", "0x1234", "--range", "10", "100", "42", &wrong_path]);
+    assert_eq!(out.status.code(), Some(5), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"prove.commitment_mismatch\""), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(!std::path::Path::new(&wrong_path).exists());
+    let out = cuproof(&["prove", &params_path, "--commitment", &commitment_path, "--blinding", &blinding_path, "--range", "10", "100", "43", &wrong_path]);
+    assert_eq!(out.status.code(), Some(5), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(!std::path::Path::new(&wrong_path).exists());
+    for path in [params_path, commitment_path, blinding_path, proof_path, wrong_path] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,60 @@
+//! `cuproof prove-lt` and `verify-lt` on commitments written by commit, driving the built binary
+
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).env("BID", "41").env("BUDGET", "42").output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_lt_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure a bid one below its budget proves and verifies, in that order only, and that
+//          equal values and mismatched openings are refused at prove time
+// Params: fast params; commitments to 41 (bid) and 42 (budget), and a second commitment to 41
+// Output: exit 0 and VALID for (bid, budget); exit 1 and INVALID, the commitment mismatching, for
+//         (budget, bid); exit 5 with prove.not_less_than for (bid, bid copy) and
+//         prove.commitment_mismatch for swapped blindings; info names the file a comparison proof
+// Usage: `cargo test --test cli_prove_lt` or `cargo test`
+#[test]
+fn bid_below_budget_proves_and_verifies() {
+    let params_path = temp_path("params.bin");
+    let (bid_path, bid_blinding) = (temp_path("bid.c"), temp_path("bid.r"));
+    let (same_path, same_blinding) = (temp_path("same.c"), temp_path("same.r"));
+    let (budget_path, budget_blinding, proof_path) = (temp_path("budget.c"), temp_path("budget.r"), temp_path("lt.proof"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    for (v, c, r) in [("41", &bid_path, &bid_blinding), ("41", &same_path, &same_blinding), ("42", &budget_path, &budget_blinding)] {
+        assert!(cuproof(&["commit", &params_path, v, "--out", c, "--blinding-out", r]).status.success());
+    }
+
+    let out = cuproof(&[
+        "prove-lt", "--value1-env", "BID", "--value2-env", "BUDGET", "--blinding1-in", &bid_blinding, "--blinding2-in", &budget_blinding,
+        "--bits", "8", &params_path, &bid_path, &budget_path, &proof_path,
+    ]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let out = cuproof(&["verify-lt", &params_path, &bid_path, &budget_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout).lines().last(), Some("VALID"));
+    let out = cuproof(&["verify-lt", &params_path, &budget_path, &bid_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(1), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout).lines().last(), Some("INVALID (statement commitment does not match)"));
+    let out = cuproof(&["info", &proof_path]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("comparison proof"), "{}", String::from_utf8_lossy(&out.stdout));
+
+    let out = cuproof(&[
+        "--errors", "json", "prove-lt", "--value1-env", "BID", "--value2-env", "BID", "--blinding1-in", &bid_blinding,
+        "--blinding2-in", &same_blinding, &params_path, &bid_path, &same_path, &proof_path,
+    ]);
+    assert_eq!(out.status.code(), Some(5), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"prove.not_less_than\""), "{}", String::from_utf8_lossy(&out.stderr));
+    let out = cuproof(&[
+        "--errors", "json", "prove-lt", "--value1-env", "BID", "--value2-env", "BUDGET", "--blinding1-in", &budget_blinding,
+        "--blinding2-in", &bid_blinding, &params_path, &bid_path, &budget_path, &proof_path,
+    ]);
+    assert_eq!(out.status.code(), Some(5), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"prove.commitment_mismatch\""), "{}", String::from_utf8_lossy(&out.stderr));
+    for path in [params_path, bid_path, bid_blinding, same_path, same_blinding, budget_path, budget_blinding, proof_path] {
+        let _ = std::fs::remove_file(path);
+    }
+}
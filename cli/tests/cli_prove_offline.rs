@@ -0,0 +1,91 @@
+//! `cuproof prove-offline-commit`, `prove-offline-challenge` and `prove-offline-finish`, driving the built binary
+
+use std::process::{Command, Output};
+use cuproof::encrypted::is_encrypted;
+use cuproof::util::{bigint_to_hex, load_commitment, load_encrypted, load_params, load_proof, read_offline_state, save_offline_state, ReadLimits};
+
+const PASSPHRASE: &str = "correct horse";
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).env("CUPROOF_PASSPHRASE", PASSPHRASE).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_offline_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure the two offline halves and the online challenge step chain into a proof verify
+//          accepts, that the state is kept encrypted, and that it is used only once
+// Params: fast params; a commitment to 42 from commit; the range [10, 100]; the label session/7
+//         and the nonce 4f1c; $CUPROOF_PASSPHRASE set to "correct horse"; the state decrypted and
+//         saved in the clear
+// Output: a proof of the committed value carrying the label, VALID under --nonce 4f1c and INVALID
+//         without it; a state file that info reports as encrypted, without its secrets;
+//         after finishing, the state file is gone and a copy of it exits 5 with prove.state_spent;
+//         exit 2 with serialization.invalid for the state in the clear;
+//         exit 5 with prove.commitment_mismatch for a blinding that does not open the commitment
+// Usage: `cargo test --test cli_prove_offline` or `cargo test`
+#[test]
+fn offline_halves_make_one_verifiable_proof() {
+    let (params_path, commitment_path, blinding_path) = (temp_path("params.bin"), temp_path("c.bin"), temp_path("r.bin"));
+    let (message_path, state_path, copy_path, bundle_path) = (temp_path("v.first"), temp_path("v.state"), temp_path("copy.state"), temp_path("v.bundle"));
+    let (proof_path, spent_path) = (temp_path("v.proof"), temp_path("spent.log"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    assert!(cuproof(&["commit", &params_path, "42", "--out", &commitment_path, "--blinding-out", &blinding_path]).status.success());
+    let params = load_params(&params_path).unwrap();
+    let commitment = load_commitment(&commitment_path, &params).unwrap();
+
+    let out = cuproof(&[
+        "prove-offline-commit", "--blinding-in", &blinding_path, "--commitment", &commitment_path,
+        "--message", &message_path, "--state", &state_path, &params_path, "10", "100", "42",
+    ]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("State id: "));
+    let out = cuproof(&["--json", "info", &state_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("\"kind\":\"encrypted\""), "{}", String::from_utf8_lossy(&out.stdout));
+    assert!(!String::from_utf8_lossy(&out.stdout).contains(&bigint_to_hex(&commitment)));
+    assert!(is_encrypted(&std::fs::read(&state_path).unwrap()));
+    let plaintext = load_encrypted(&state_path, PASSPHRASE.as_bytes()).unwrap();
+    let state = read_offline_state(&mut plaintext.expose().as_slice(), &ReadLimits::default()).unwrap();
+    std::fs::copy(&state_path, &copy_path).unwrap();
+
+    let out = cuproof(&["prove-offline-challenge", "--label", "session/7", "--nonce", "4f1c", "--out", &bundle_path, &params_path, &message_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+
+    let out = cuproof(&["prove-offline-finish", "--state", &state_path, "--spent-log", &spent_path, &params_path, &bundle_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(!std::path::Path::new(&state_path).exists());
+    let proof = load_proof(&proof_path).unwrap();
+    assert_eq!(proof.C, commitment);
+    assert_eq!(proof.statement.as_ref().map(|s| s.label.as_str()), Some("session/7"));
+    let verify = |nonce: &[&str]| {
+        let commitment = bigint_to_hex(&commitment);
+        let mut args = vec!["verify", &params_path, &proof_path, "--range", "10", "100", "--commitment", &commitment];
+        args.extend(nonce);
+        let out = cuproof(&args);
+        String::from_utf8_lossy(&out.stdout).lines().last().map(str::to_string)
+    };
+    assert_eq!(verify(&["--nonce", "4f1c"]).as_deref(), Some("VALID"));
+    assert_eq!(verify(&[]).as_deref(), Some("INVALID"));
+
+    let out = cuproof(&["--errors", "json", "prove-offline-finish", "--state", &copy_path, "--spent-log", &spent_path, &params_path, &bundle_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(5), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"prove.state_spent\""), "{}", String::from_utf8_lossy(&out.stderr));
+    save_offline_state(&copy_path, &state).unwrap();
+    let out = cuproof(&["--errors", "json", "prove-offline-finish", "--state", &copy_path, "--spent-log", &spent_path, &params_path, &bundle_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"serialization.invalid\""), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("not encrypted"), "{}", String::from_utf8_lossy(&out.stderr));
+
+    let out = cuproof(&[
+        "--errors", "json", "prove-offline-commit", "--blinding", "0x1234", "--commitment", &commitment_path,
+        "--message", &message_path, "--state", &state_path, &params_path, "10", "100", "42",
+    ]);
+    assert_eq!(out.status.code(), Some(5), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"prove.commitment_mismatch\""), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(!std::path::Path::new(&state_path).exists());
+    for path in [params_path, commitment_path, blinding_path, message_path, state_path, copy_path, bundle_path, proof_path, spent_path] {
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,69 @@
+//! `cuproof prove-openings` and `verify-openings`, driving the built binary
+
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    cuproof_with(PASSPHRASE, args)
+}
+
+fn cuproof_with(passphrase: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).env("CUPROOF_PASSPHRASE", passphrase).args(args).output().expect("failed to run cuproof")
+}
+
+const PASSPHRASE: &str = "correct horse";
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_openings_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure the openings of an encrypted manifest prove in one proof that verifies, and only
+//          for the commitments it lists, and that the manifest is read only encrypted
+// Params: fast params; 20 rows of value and blinding, encrypted by `cuproof encrypt` under
+//         $CUPROOF_PASSPHRASE; the proof with one commitment replaced by another row's; the rows
+//         in the clear; another passphrase
+// Output: an encrypted manifest; exit 0 and VALID listing the 20 commitments; info names an
+//         openings proof; exit 1 and INVALID for the altered list; exit 2 with
+//         serialization.invalid for the rows in the clear, serialization.wrong_passphrase for the
+//         other passphrase; a nonzero exit for a negative value in the rows
+// Usage: `cargo test --test cli_prove_openings` or `cargo test`
+#[test]
+fn openings_prove_and_verify() {
+    let (params_path, rows_path, manifest_path) = (temp_path("params.bin"), temp_path("rows.csv"), temp_path("rows.enc"));
+    let (proof_path, altered_path) = (temp_path("openings.proof"), temp_path("altered.proof"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    let rows: String = (0..20).map(|i| format!("{},{}\n", 1000 + i, 0x5eed_0000u64 + 977 * i)).collect();
+    std::fs::write(&rows_path, format!("value,blinding\n{}", rows)).unwrap();
+    let out = cuproof(&["encrypt", &rows_path, &manifest_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(cuproof::encrypted::is_encrypted(&std::fs::read(&manifest_path).unwrap()));
+
+    let out = cuproof(&["prove-openings", "--radix", "dec", &params_path, &manifest_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let out = cuproof(&["--json", "verify-openings", &params_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+    let out = cuproof(&["info", &proof_path]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("openings proof"), "{}", String::from_utf8_lossy(&out.stdout));
+
+    let (mut commitments, proof) = cuproof::util::load_openings_proof(&proof_path).unwrap();
+    assert_eq!(commitments.len(), 20);
+    assert!(commitments.iter().all(|c| stdout.contains(&cuproof::util::bigint_to_hex(c))), "{}", stdout);
+    commitments[7] = commitments[8].clone();
+    cuproof::util::save_openings_proof(&altered_path, &commitments, &proof).unwrap();
+    let out = cuproof(&["verify-openings", &params_path, &altered_path]);
+    assert_eq!(out.status.code(), Some(1), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout).lines().last(), Some("INVALID"));
+
+    let out = cuproof(&["--errors", "json", "prove-openings", "--radix", "dec", &params_path, &rows_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"serialization.invalid\""), "{}", String::from_utf8_lossy(&out.stderr));
+    let out = cuproof_with("battery staple", &["--errors", "json", "prove-openings", "--radix", "dec", &params_path, &manifest_path, &proof_path]);
+    assert_eq!(out.status.code(), Some(2), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("\"kind\":\"serialization.wrong_passphrase\""), "{}", String::from_utf8_lossy(&out.stderr));
+
+    std::fs::write(&rows_path, "value,blinding\n-5,17\n").unwrap();
+    assert!(cuproof(&["encrypt", &rows_path, &manifest_path]).status.success());
+    let out = cuproof(&["prove-openings", "--radix", "dec", &params_path, &manifest_path, &proof_path]);
+    assert_ne!(out.status.code(), Some(0));
+    for path in [params_path, rows_path, manifest_path, proof_path, altered_path] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,64 @@
+//! `cuproof run` over the example scripts in testdata/scripts, driving the built binary
+
+use std::process::{Command, Output};
+use serde_json::Value;
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_run_test_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+fn script(name: &str) -> String {
+    format!("{}/../testdata/scripts/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+// Purpose: ensure both example scripts run every step as expected, loading params once
+// Params: testdata/scripts/roundtrip.toml (commit, prove, verify, openings) and tamper.json
+//         (invalid range, tampered file, bad hex, missing params, value out of range)
+// Output: exit 0; --json reports every step passed, params_loads 1 and the error kinds met; the
+//         temporary workspace is removed afterwards
+// Usage: `cargo test --test cli_run` or `cargo test`
+#[test]
+fn example_scripts_meet_their_expectations() {
+    for (name, steps) in [("roundtrip.toml", 6), ("tamper.json", 9)] {
+        let out = cuproof(&["--json", "run", &script(name)]);
+        assert_eq!(out.status.code(), Some(0), "{}: {}", name, String::from_utf8_lossy(&out.stderr));
+        let document: Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!((document["passed"].as_u64(), document["failed"].as_u64()), (Some(steps), Some(0)), "{}", document);
+        assert_eq!(document["params_loads"], 1, "{}", document);
+        assert!(!std::path::Path::new(document["workspace"].as_str().unwrap()).exists());
+        if name == "tamper.json" {
+            let kinds: Vec<&str> = document["steps"].as_array().unwrap().iter().filter_map(|step| step["kind"].as_str()).collect();
+            assert_eq!(kinds, ["serialization.format", "usage.invalid_value", "serialization.io", "prove.value_out_of_range"]);
+        }
+    }
+}
+
+// Purpose: ensure an unmet expectation fails the run and skips the steps after it, unless keep_going
+// Params: a script whose second step expects valid from a verify against the wrong range, in a
+//         given workspace; the same with keep_going
+// Output: exit 1 with FAIL naming the outcome and the expectation, SKIP for the third step, and
+//         the workspace files kept; with keep_going the third step runs and passes
+// Usage: `cargo test --test cli_run` or `cargo test`
+#[test]
+fn unmet_expectations_fail_the_run() {
+    let (path, workspace) = (temp_path("failing.toml"), temp_path("workspace"));
+    let steps = "[[step]]\nrun = \"prove --ephemeral ${params} 0 10 7 ${proof}\"\n[[step]]\nrun = \"verify ${params} 0 5 ${proof}\"\nexpect = \"valid\"\n[[step]]\nrun = \"verify ${params} 0 10 ${proof}\"\nexpect = \"valid\"\n";
+    std::fs::write(&path, format!("[[step]]\nrun = \"setup fast ${{params}}\"\n{}", steps)).unwrap();
+    let out = cuproof(&["run", "--workspace", &workspace, &path]);
+    assert_eq!(out.status.code(), Some(1), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("FAIL  3: invalid, expected valid") && stdout.contains("SKIP  4"), "{}", stdout);
+    assert!(stdout.ends_with("4 steps: 2 as expected, 1 not, 1 skipped; params loaded once\n"), "{}", stdout);
+    assert!(std::path::Path::new(&workspace).join("proof").exists());
+
+    std::fs::write(&path, format!("keep_going = true\n[[step]]\nrun = \"setup fast ${{params}}\"\n{}", steps)).unwrap();
+    let out = cuproof(&["run", "--workspace", &workspace, &path]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("PASS  4: valid"));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_dir_all(&workspace);
+}
@@ -0,0 +1,103 @@
+//! Secrets for `cuproof prove` and `cuproof commit` from stdin, the environment and argv, driving the built binary
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn cuproof(args: &[&str], stdin: &str, env: &[(&str, &str)]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cuproof"))
+        .args(args)
+        .envs(env.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run cuproof");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_secret_{}_{}.bin", name, std::process::id())).to_str().unwrap().to_string()
+}
+
+fn setup(name: &str) -> String {
+    let params = temp_path(&format!("{}_params", name));
+    assert!(cuproof(&["setup", "fast", &params], "", &[]).status.success());
+    params
+}
+
+fn verify(params: &str, proof: &str) -> String {
+    let out = cuproof(&["verify", params, "10", "100", proof], "", &[]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    String::from_utf8_lossy(&out.stdout).into_owned()
+}
+
+// Purpose: ensure prove reads v from stdin or an environment variable, without warning or echoing it
+// Params: insecure params, v = 42 piped on stdin and set in CUPROOF_TEST_V, range [10, 100]
+// Output: both proofs verify; stderr has no argv warning and never contains the value
+// Usage: `cargo test --test cli_secret_input` or `cargo test`
+#[test]
+fn prove_reads_value_from_stdin_and_env() {
+    let params = setup("stdin_env");
+    let (from_stdin, from_env) = (temp_path("stdin_proof"), temp_path("env_proof"));
+
+    let out = cuproof(&["prove", "--ephemeral", "--value-stdin", &params, "10", "100", &from_stdin], "0x2a\n", &[]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!stderr.contains("passed as an argument") && !stderr.contains("2a"), "{}", stderr);
+    assert!(!stderr.contains("v: "), "prompted without a TTY: {}", stderr);
+    assert!(verify(&params, &from_stdin).ends_with("VALID\n"));
+
+    let out = cuproof(&["prove", "--ephemeral", "--value-env", "CUPROOF_TEST_V", &params, "10", "100", &from_env], "", &[("CUPROOF_TEST_V", "42")]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!stderr.contains("passed as an argument") && !stderr.contains("42"), "{}", stderr);
+    assert!(verify(&params, &from_env).ends_with("VALID\n"));
+
+    let missing = cuproof(&["prove", "--ephemeral", "--value-env", "CUPROOF_TEST_UNSET", &params, "10", "100", &from_env], "", &[]);
+    assert_eq!(missing.status.code(), Some(2));
+    let empty = cuproof(&["prove", "--ephemeral", "--value-stdin", &params, "10", "100", &from_env], "", &[]);
+    assert_eq!(empty.status.code(), Some(2));
+    let garbage = cuproof(&["prove", "--ephemeral", "--value-stdin", &params, "10", "100", &from_env], "0xsecretGG\n", &[]);
+    assert_eq!(garbage.status.code(), Some(2));
+    assert!(!String::from_utf8_lossy(&garbage.stderr).contains("secretGG"));
+    for path in [params, from_stdin, from_env] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure the positional v still works but warns that argv leaks it
+// Params: insecure params, v = 42 as an argument
+// Output: the proof verifies; stderr carries the warning
+// Usage: `cargo test --test cli_secret_input` or `cargo test`
+#[test]
+fn positional_value_still_works_but_warns() {
+    let params = setup("positional");
+    let proof = temp_path("positional_proof");
+    let out = cuproof(&["prove", "--ephemeral", &params, "10", "100", "42", &proof], "", &[]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("WARNING: v was passed as an argument"));
+    assert!(verify(&params, &proof).ends_with("VALID\n"));
+    for path in [params, proof] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure commit takes v and the blinding from stdin (in that order) and prove takes the blinding from the environment
+// Params: insecure params, v = 42 and blinding 0x1234 piped to commit, the blinding in CUPROOF_TEST_R for prove --commitment
+// Output: the proof verifies and its statement records the printed commitment
+// Usage: `cargo test --test cli_secret_input` or `cargo test`
+#[test]
+fn commit_and_prove_read_the_blinding_without_argv() {
+    let params = setup("blinding");
+    let (commitment, proof) = (temp_path("blinding_commitment"), temp_path("blinding_proof"));
+    let out = cuproof(&["commit", &params, "--value-stdin", "--blinding-stdin", "--out", &commitment], "42\n0x1234\n", &[]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(!String::from_utf8_lossy(&out.stderr).contains("passed as an argument"));
+    let printed = String::from_utf8_lossy(&out.stdout).trim().to_string();
+
+    let args = ["prove", "--value-env", "CUPROOF_TEST_V", "--commitment", &commitment, "--blinding-env", "CUPROOF_TEST_R", &params, "10", "100", &proof];
+    let out = cuproof(&args, "", &[("CUPROOF_TEST_V", "42"), ("CUPROOF_TEST_R", "0x1234")]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(verify(&params, &proof).contains(&printed));
+
+    let wrong = cuproof(&args, "", &[("CUPROOF_TEST_V", "42"), ("CUPROOF_TEST_R", "0x1235")]);
+    assert_eq!(wrong.status.code(), Some(5));
+    for path in [params, commitment, proof] { let _ = std::fs::remove_file(path); }
+}
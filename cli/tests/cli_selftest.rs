@@ -0,0 +1,36 @@
+//! `cuproof selftest` on the built-in test params and on a params file, driving the built binary
+
+use std::process::{Command, Output};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+// Purpose: ensure every built-in check passes on the embedded test params and on freshly generated ones
+// Params: no params path, then fast params written by setup, with and without --json
+// Output: exit 0 and seven PASS lines; the JSON report lists seven passing checks and no failures;
+//         a missing params file is an argument error (exit 2) before any check runs
+// Usage: `cargo test --test cli_selftest` or `cargo test`
+#[test]
+fn selftest_passes_every_check() {
+    let out = cuproof(&["selftest"]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert_eq!(stdout.lines().filter(|l| l.starts_with("PASS  ")).count(), 7, "{}", stdout);
+    assert!(!stdout.contains("FAIL"), "{}", stdout);
+    assert!(stdout.contains("7 checks: 7 passed, 0 failed"), "{}", stdout);
+
+    let params = std::env::temp_dir().join(format!("cuproof_selftest_params_{}.bin", std::process::id()));
+    let params = params.to_str().unwrap();
+    assert!(cuproof(&["setup", "fast", params]).status.success());
+    let out = cuproof(&["--json", "selftest", params]);
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!((report["passed"].as_u64(), report["failed"].as_u64()), (Some(7), Some(0)));
+    let checks = report["checks"].as_array().unwrap();
+    assert!(checks.iter().all(|c| c["result"] == "pass" && c["error"].is_null()), "{}", report);
+    assert_eq!(checks[1]["name"], "transcript known answers");
+    let _ = std::fs::remove_file(params);
+
+    assert_eq!(cuproof(&["selftest", "/nonexistent/cuproof_params.bin"]).status.code(), Some(2));
+}
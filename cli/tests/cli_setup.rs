@@ -0,0 +1,86 @@
+//! `cuproof setup <out> --mode ...` at small test sizes, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use cuproof::util::load_params;
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_setup_{}_{}.bin", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+// Purpose: ensure --mode fast is deterministic per seed and refused without --allow-insecure
+// Params: 256-bit params from seed c0ffee twice and from seed 0xbeef
+// Output: identical files and fingerprint for the same seed, a different modulus for the other seed,
+//         exit 2 and no file without --allow-insecure
+// Usage: `cargo test --test cli_setup` or `cargo test`
+#[test]
+fn fast_mode_is_seeded_and_opt_in() {
+    let (first, second, other, refused) = (temp_path("fast1"), temp_path("fast2"), temp_path("fast3"), temp_path("refused"));
+    let out = cuproof(&["setup", "--mode", "fast", "--allow-insecure", "--bits", "256", "--seed", "c0ffee", &first]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Saved 256-bit public parameters") && stdout.contains("Security level: none"), "{}", stdout);
+    assert!(cuproof(&["setup", "--mode", "fast", "--allow-insecure", "--bits", "256", "--seed", "c0ffee", &second]).status.success());
+    assert!(cuproof(&["setup", "--mode", "fast", "--allow-insecure", "--bits", "256", "--seed", "0xbeef", &other]).status.success());
+    assert_eq!(std::fs::read(&first).unwrap(), std::fs::read(&second).unwrap());
+    let params = load_params(&first).unwrap();
+    assert!(params.is_insecure() && params.bits() == 256);
+    assert_ne!(params.n(), load_params(&other).unwrap().n());
+
+    let out = cuproof(&["setup", "--mode", "fast", "--bits", "256", &refused]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--allow-insecure"));
+    assert!(!std::path::Path::new(&refused).exists());
+    for path in [first, second, other] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure --mode trusted and --mode import produce usable params at test sizes, and only with --allow-insecure
+// Params: trusted at 256 bits; import of the modulus of seeded 384-bit fast params
+// Output: a transcript that `setup verify` accepts; imported params over the same n; exit 2 without --allow-insecure
+// Usage: `cargo test --test cli_setup` or `cargo test`
+#[test]
+fn trusted_and_import_modes_at_small_sizes() {
+    let (trusted, source, imported) = (temp_path("trusted"), temp_path("source"), temp_path("imported"));
+    let transcript = format!("{}.transcript", trusted);
+    assert_eq!(cuproof(&["setup", "--bits", "256", &trusted]).status.code(), Some(2));
+    let out = cuproof(&["setup", "--mode", "trusted", "--bits", "256", "--allow-insecure", &trusted]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(load_params(&trusted).unwrap().bits(), 256);
+    let out = cuproof(&["setup", "verify", &trusted, &transcript]);
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "VALID");
+
+    assert!(cuproof(&["setup", "--mode", "fast", "--allow-insecure", "--bits", "384", "--seed", "01", &source]).status.success());
+    let n = load_params(&source).unwrap().n().to_str_radix(16);
+    assert_eq!(cuproof(&["setup", "--mode", "import", "--modulus-hex", &n, &imported]).status.code(), Some(2));
+    let out = cuproof(&["setup", "--mode", "import", "--modulus-hex", &n, "--allow-insecure", &imported]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let params = load_params(&imported).unwrap();
+    assert_eq!((params.bits(), params.n().to_str_radix(16)), (384, n));
+    for path in [trusted, transcript, source, imported] { let _ = std::fs::remove_file(path); }
+}
+
+// Purpose: ensure setup never replaces an existing file unless --force is given
+// Params: a placeholder file, then fast setups over it with and without --force, in both command forms
+// Output: exit 2 naming --force and the file untouched; with --force, params over the placeholder
+// Usage: `cargo test --test cli_setup` or `cargo test`
+#[test]
+fn existing_files_need_force() {
+    let path = temp_path("existing");
+    std::fs::write(&path, b"keep me").unwrap();
+    for args in [&["setup", "--mode", "fast", "--allow-insecure", "--bits", "256"][..], &["setup", "fast"]] {
+        let out = cuproof(&[args, &[path.as_str()]].concat());
+        assert_eq!(out.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&out.stderr).contains("already exists; pass --force"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep me");
+    }
+    assert!(cuproof(&["setup", "--mode", "fast", "--allow-insecure", "--bits", "256", "--force", &path]).status.success());
+    assert_eq!(load_params(&path).unwrap().bits(), 256);
+    assert!(cuproof(&["setup", "fast", "--force", &path]).status.success());
+    assert_eq!(load_params(&path).unwrap().bits(), 512);
+    let _ = std::fs::remove_file(path);
+}
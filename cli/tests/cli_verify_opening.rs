@@ -0,0 +1,61 @@
+//! `cuproof verify-opening` on openings an auditor might be handed, driving the built binary
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use num_bigint::BigInt;
+use cuproof::commitment::commit;
+use cuproof::setup::insecure_test_setup;
+use cuproof::util::{bigint_to_hex, save_params};
+
+fn cuproof(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cuproof"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run cuproof");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_opening_{}_{}.bin", name, std::process::id())).to_str().unwrap().to_string()
+}
+
+// Purpose: ensure verify-opening tells a correct opening from a wrong value, a wrong blinding and a non-canonical commitment
+// Params: insecure 512-bit params; C = g^42 h^0x1f2e; v and r piped on stdin
+// Output: MATCH and exit 0 for (C, 42, 0x1f2e); MISMATCH and exit 1 for v = 43 and for r = 0x1f2f;
+//         exit 2 for C + n, which opens to the same residue but is refused; --json carries the recomputed C
+// Usage: `cargo test --test cli_verify_opening` or `cargo test`
+#[test]
+fn openings_match_mismatch_or_are_refused() {
+    let params_path = temp_path("params");
+    let params = insecure_test_setup(512);
+    save_params(&params_path, &params).unwrap();
+    let c = commit(&params, &BigInt::from(42), &BigInt::from(0x1f2e));
+    let opening = |commitment: &BigInt, stdin: &str| {
+        cuproof(&["verify-opening", &params_path, "--commitment", &bigint_to_hex(commitment), "--value-stdin", "--blinding-stdin"], stdin)
+    };
+
+    let out = opening(&c, "42\n0x1f2e\n");
+    assert_eq!(out.status.code(), Some(0), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).ends_with("MATCH\n"));
+
+    for wrong in ["43\n0x1f2e\n", "42\n0x1f2f\n"] {
+        let out = opening(&c, wrong);
+        assert_eq!(out.status.code(), Some(1), "{}", String::from_utf8_lossy(&out.stderr));
+        assert!(String::from_utf8_lossy(&out.stdout).ends_with("MISMATCH\n"));
+    }
+
+    let out = opening(&(&c + params.n()), "42\n0x1f2e\n");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("not a nonzero residue modulo n"), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("MATCH"));
+
+    let out = cuproof(&["--json", "verify-opening", &params_path, "--commitment", &bigint_to_hex(&c), "--value-stdin", "--blinding-stdin"], "43\n0x1f2e\n");
+    let json = String::from_utf8_lossy(&out.stdout);
+    let recomputed = bigint_to_hex(&commit(&params, &BigInt::from(43), &BigInt::from(0x1f2e)));
+    assert!(json.contains("\"result\":\"mismatch\"") && json.contains(&format!("\"recomputed\":\"{}\"", recomputed)), "{}", json);
+    let _ = std::fs::remove_file(params_path);
+}
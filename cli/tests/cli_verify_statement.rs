@@ -0,0 +1,67 @@
+//! `cuproof verify` pinning the range and commitment the caller expects, driving the built binary
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use num_bigint::BigInt;
+use cuproof::commitment::commit;
+use cuproof::range_proof::Statement;
+use cuproof::util::{bigint_to_hex, load_params, load_proof};
+
+fn cuproof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cuproof")).args(args).output().expect("failed to run cuproof")
+}
+
+fn temp_path(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("cuproof_statement_{}_{}.bin", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+// Purpose: ensure verify accepts a proof only for the range and commitment the caller supplies
+// Params: fast params, a proof of v = 42 in [10, 100] with blinding 0x1234; --range, --commitment and
+//         --statement files, matching and not
+// Output: exit 0 and VALID when everything matches; exit 1 with the mismatched field named in the verdict
+//         and a Mismatch line giving both values otherwise; exit 2 for a range given twice
+// Usage: `cargo test --test cli_verify_statement` or `cargo test`
+#[test]
+fn verify_enforces_the_supplied_statement() {
+    let (params_path, proof_path, statement_path) = (temp_path("params"), temp_path("proof"), temp_path("statement"));
+    assert!(cuproof(&["setup", "fast", &params_path]).status.success());
+    assert!(cuproof(&["prove", "--blinding", "0x1234", &params_path, "10", "100", "42", &proof_path]).status.success());
+    let params = load_params(&params_path).unwrap();
+    let commitment = bigint_to_hex(&commit(&params, &BigInt::from(42), &BigInt::from(0x1234)));
+    let other = bigint_to_hex(&commit(&params, &BigInt::from(43), &BigInt::from(0x1234)));
+    let verify = |args: &[&str]| {
+        let out = cuproof(&[&["verify", params_path.as_str(), proof_path.as_str()][..], args].concat());
+        (out.status.code(), String::from_utf8_lossy(&out.stdout).to_string())
+    };
+
+    let (code, stdout) = verify(&["--range", "10", "100", "--commitment", &commitment]);
+    assert_eq!((code, stdout.lines().last()), (Some(0), Some("VALID")), "{}", stdout);
+    let out = cuproof(&["verify", &params_path, "10", "100", &proof_path, "--commitment", &commitment]);
+    assert_eq!(out.status.code(), Some(0));
+
+    let (code, stdout) = verify(&["--range", "10", "99"]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains("Mismatch: range is [0a, 64] in the proof, expected [0a, 63]"), "{}", stdout);
+    assert!(stdout.contains("INVALID (statement range does not match)"), "{}", stdout);
+
+    let (code, stdout) = verify(&["--range", "10", "100", "--commitment", &other]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains(&format!("Mismatch: commitment is {} in the proof, expected {}", commitment, other)), "{}", stdout);
+    assert!(stdout.contains("INVALID (statement commitment does not match)"), "{}", stdout);
+
+    let proof = load_proof(&proof_path).unwrap();
+    let stated = proof.statement.clone().unwrap();
+    std::fs::write(&statement_path, stated.to_bytes()).unwrap();
+    assert_eq!(verify(&["--statement", &statement_path]).0, Some(0));
+    let narrower = Statement::new(&proof.C, &BigInt::from(10), &BigInt::from(99), stated.bits, &params);
+    std::fs::write(&statement_path, narrower.to_bytes()).unwrap();
+    let (code, stdout) = verify(&["--statement", &statement_path]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains("Mismatch: range") && stdout.contains("INVALID (statement range does not match)"), "{}", stdout);
+
+    let out = cuproof(&["verify", &params_path, "10", "100", &proof_path, "--range", "10", "100"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("verify takes the range once"));
+    for path in [params_path, proof_path, statement_path] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,54 @@
+//! One Verifier for a whole worker pool: `cargo run --release --example shared_verifier [params file]`
+//!
+//! A server builds the Verifier once at startup and hands each worker a clone (or an
+//! `Arc<Verifier>`); the clones share one `Arc<Params>` and its precomputation tables, so no
+//! request copies g, h and n. Without a params file the example uses insecure 512-bit params.
+
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use cuproof::setup::insecure_test_setup;
+use cuproof::{load_params, Cuproof, Params, RangeProver, Verifier};
+use num_bigint::BigInt;
+
+const WORKERS: usize = 8;
+
+fn main() {
+    let (params, insecure) = match std::env::args().nth(1) {
+        Some(path) => (load_params(&path).expect("failed to load params"), false),
+        None => (insecure_test_setup(512).allow_insecure_params(), true),
+    };
+    let params: Arc<Params> = Arc::new(params);
+    let (verifier, prover) = if insecure {
+        (Verifier::new_insecure(Arc::clone(&params)), RangeProver::new_insecure(Arc::clone(&params)))
+    } else {
+        (Verifier::new(Arc::clone(&params)), RangeProver::new(Arc::clone(&params)))
+    };
+    let (verifier, prover) = (verifier.expect("params rejected"), prover.expect("params rejected"));
+
+    // stand-in for incoming requests: proofs of 10 <= v <= 100, every third one altered
+    let (a, b) = (BigInt::from(10), BigInt::from(100));
+    let requests: Vec<Cuproof> = (0..24)
+        .map(|i| {
+            let mut proof = prover.prove(&BigInt::from(10 + i * 3), &BigInt::from(1000 + i), &a, &b).unwrap();
+            if i % 3 == 2 { proof.t_hat += 1; }
+            proof
+        })
+        .collect();
+
+    let (send, receive) = mpsc::channel::<(usize, Cuproof)>();
+    let receive = Arc::new(Mutex::new(receive));
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|w| {
+            let (verifier, receive) = (verifier.clone(), Arc::clone(&receive));
+            thread::spawn(move || loop {
+                let Ok((id, proof)) = receive.lock().unwrap().recv() else { break };
+                println!("worker {} request {}: {}", w, id, if verifier.verify(&proof) { "valid" } else { "rejected" });
+            })
+        })
+        .collect();
+    for request in requests.into_iter().enumerate() { send.send(request).unwrap(); }
+    drop(send);
+    for worker in workers { worker.join().unwrap(); }
+    println!("{} workers shared one params allocation ({} references left)", WORKERS, Arc::strong_count(&params));
+}
@@ -0,0 +1,136 @@
+/* C declarations of ffi/src/lib.rs, maintained by hand: change this header in the same commit as
+ * any extern "C" function or CUPROOF_ constant there. tests/c_abi.rs checks that every export has
+ * a matching prototype and every constant a matching #define. */
+
+#ifndef CUPROOF_H
+#define CUPROOF_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Success; for cuproof_verify_bytes, the proof is valid
+ */
+#define CUPROOF_OK 0
+
+/**
+ * The proof was rejected (Error::Verify)
+ */
+#define CUPROOF_ERR_VERIFY 1
+
+/**
+ * Params or a proof could not be decoded (Error::Serialization)
+ */
+#define CUPROOF_ERR_SERIALIZATION 2
+
+/**
+ * Params decoded but failed validation (Error::Params)
+ */
+#define CUPROOF_ERR_PARAMS 3
+
+/**
+ * Generating params failed (Error::Setup); not returned by the functions of this library yet
+ */
+#define CUPROOF_ERR_SETUP 4
+
+/**
+ * No proof exists for the given inputs, e.g. a value outside [a, b] (Error::Prove)
+ */
+#define CUPROOF_ERR_PROVE 5
+
+/**
+ * A required pointer was null
+ */
+#define CUPROOF_ERR_NULL -1
+
+/**
+ * A string argument is not UTF-8 or not non-negative hex
+ */
+#define CUPROOF_ERR_ARGUMENT -2
+
+/**
+ * The library panicked; the call had no effect on its outputs
+ */
+#define CUPROOF_ERR_PANIC -3
+
+/**
+ * Validated params, created by cuproof_params_load and released by cuproof_params_free
+ */
+typedef struct CuproofParams CuproofParams;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Load and validate params
+ *
+ * On CUPROOF_OK, `*out_handle` holds a handle to release with cuproof_params_free; on any other
+ * code it is left untouched.
+ *
+ * # Safety
+ * `bytes` must point to `len` readable bytes and `out_handle` to a writable pointer
+ */
+int32_t cuproof_params_load(const uint8_t *bytes, size_t len, CuproofParams **out_handle);
+
+/**
+ * Release a handle of cuproof_params_load; null is ignored
+ *
+ * # Safety
+ * `handle` must be null or a handle of cuproof_params_load not yet freed, and no other call may
+ * be using it
+ */
+void cuproof_params_free(CuproofParams *handle);
+
+/**
+ * Check a proof that the value committed in `commitment_hex` lies in [`a_hex`, `b_hex`]
+ *
+ * Returns CUPROOF_OK for a valid proof, CUPROOF_ERR_VERIFY for one that does not verify, is for
+ * another commitment or whose recorded range differs, CUPROOF_ERR_SERIALIZATION for bytes that
+ * are not a proof.
+ *
+ * # Safety
+ * `params` must be a live handle, `proof_bytes` must point to `len` readable bytes and the hex
+ * arguments must be NUL-terminated strings
+ */
+int32_t cuproof_verify_bytes(const CuproofParams *params,
+                             const uint8_t *proof_bytes,
+                             size_t len,
+                             const char *a_hex,
+                             const char *b_hex,
+                             const char *commitment_hex);
+
+/**
+ * Prove a <= value <= b for the commitment of `value_hex` under `blinding_hex`
+ *
+ * On CUPROOF_OK, `*out_bytes` and `*out_len` hold the proof in the binary container format of
+ * save_proof; release it with cuproof_bytes_free. On any other code both are left untouched.
+ *
+ * # Safety
+ * `params` must be a live handle, the hex arguments NUL-terminated strings, and `out_bytes` and
+ * `out_len` writable
+ */
+int32_t cuproof_prove_bytes(const CuproofParams *params,
+                            const char *value_hex,
+                            const char *blinding_hex,
+                            const char *a_hex,
+                            const char *b_hex,
+                            uint8_t **out_bytes,
+                            size_t *out_len);
+
+/**
+ * Release the bytes of cuproof_prove_bytes; null is ignored
+ *
+ * # Safety
+ * `bytes` and `len` must be exactly what cuproof_prove_bytes returned, not yet freed
+ */
+void cuproof_bytes_free(uint8_t *bytes, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CUPROOF_H */
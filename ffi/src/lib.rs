@@ -0,0 +1,157 @@
+//! C ABI of the prover and verifier; include/cuproof.h, maintained by hand, declares it
+//!
+//! Params are loaded once into an opaque handle, which is immutable and may be shared between
+//! threads: any number of cuproof_verify_bytes / cuproof_prove_bytes calls may run on it at once.
+//! Params and proofs are passed in any format load_params / load_proof accept, integers as hex
+//! strings (`0x` optional). Every function returns a status code: CUPROOF_OK, the exit code of
+//! the CLI for the cuproof::Error variant that failed (CUPROOF_ERR_VERIFY ..= CUPROOF_ERR_PROVE),
+//! or a negative code for a misuse of the ABI. No panic crosses the boundary: one is caught and
+//! reported as CUPROOF_ERR_PANIC. Everything this library allocates is released by the matching
+//! `_free` function and by nothing else.
+
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use cuproof::batch::{check_item, Expected};
+use cuproof::range_proof::prove;
+use cuproof::setup::Params;
+use cuproof::util::{hex_to_bigint, read_params, read_proof, write_proof, ReadLimits};
+use cuproof::Error;
+use num_bigint::BigInt;
+
+/// Success; for cuproof_verify_bytes, the proof is valid
+pub const CUPROOF_OK: i32 = 0;
+/// The proof was rejected (Error::Verify)
+pub const CUPROOF_ERR_VERIFY: i32 = 1;
+/// Params or a proof could not be decoded (Error::Serialization)
+pub const CUPROOF_ERR_SERIALIZATION: i32 = 2;
+/// Params decoded but failed validation (Error::Params)
+pub const CUPROOF_ERR_PARAMS: i32 = 3;
+/// Generating params failed (Error::Setup); not returned by the functions of this library yet
+pub const CUPROOF_ERR_SETUP: i32 = 4;
+/// No proof exists for the given inputs, e.g. a value outside [a, b] (Error::Prove)
+pub const CUPROOF_ERR_PROVE: i32 = 5;
+/// A required pointer was null
+pub const CUPROOF_ERR_NULL: i32 = -1;
+/// A string argument is not UTF-8 or not non-negative hex
+pub const CUPROOF_ERR_ARGUMENT: i32 = -2;
+/// The library panicked; the call had no effect on its outputs
+pub const CUPROOF_ERR_PANIC: i32 = -3;
+
+/// Validated params, created by cuproof_params_load and released by cuproof_params_free
+pub struct CuproofParams {
+    params: Params,
+}
+
+/// Load and validate params
+///
+/// On CUPROOF_OK, `*out_handle` holds a handle to release with cuproof_params_free; on any other
+/// code it is left untouched.
+///
+/// # Safety
+/// `bytes` must point to `len` readable bytes and `out_handle` to a writable pointer
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cuproof_params_load(bytes: *const u8, len: usize, out_handle: *mut *mut CuproofParams) -> i32 {
+    if bytes.is_null() || out_handle.is_null() { return CUPROOF_ERR_NULL; }
+    let bytes = unsafe { std::slice::from_raw_parts(bytes, len) };
+    guard(|| {
+        let params = read_params(&mut &bytes[..], &ReadLimits::default()).map_err(|e| Error::from(e).exit_code())?;
+        let handle = Box::into_raw(Box::new(CuproofParams { params }));
+        unsafe { *out_handle = handle };
+        Ok(())
+    })
+}
+
+/// Release a handle of cuproof_params_load; null is ignored
+///
+/// # Safety
+/// `handle` must be null or a handle of cuproof_params_load not yet freed, and no other call may
+/// be using it
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cuproof_params_free(handle: *mut CuproofParams) {
+    if handle.is_null() { return; }
+    drop(unsafe { Box::from_raw(handle) });
+}
+
+/// Check a proof that the value committed in `commitment_hex` lies in [`a_hex`, `b_hex`]
+///
+/// Returns CUPROOF_OK for a valid proof, CUPROOF_ERR_VERIFY for one that does not verify, is for
+/// another commitment or whose recorded range differs, CUPROOF_ERR_SERIALIZATION for bytes that
+/// are not a proof.
+///
+/// # Safety
+/// `params` must be a live handle, `proof_bytes` must point to `len` readable bytes and the hex
+/// arguments must be NUL-terminated strings
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cuproof_verify_bytes(
+    params: *const CuproofParams, proof_bytes: *const u8, len: usize, a_hex: *const c_char, b_hex: *const c_char, commitment_hex: *const c_char,
+) -> i32 {
+    if params.is_null() || proof_bytes.is_null() { return CUPROOF_ERR_NULL; }
+    let (params, bytes) = unsafe { (&(*params).params, std::slice::from_raw_parts(proof_bytes, len)) };
+    let (a, b, commitment) = match unsafe { (hex_arg(a_hex), hex_arg(b_hex), hex_arg(commitment_hex)) } {
+        (Ok(a), Ok(b), Ok(commitment)) => (a, b, commitment),
+        (Err(code), _, _) | (_, Err(code), _) | (_, _, Err(code)) => return code,
+    };
+    guard(|| {
+        let proof = read_proof(&mut &bytes[..], &ReadLimits::default()).map_err(|e| Error::from(e).exit_code())?;
+        check_item(&proof, params, Some(&Expected { a, b, commitment: Some(commitment) })).map_err(|e| Error::from(e).exit_code())
+    })
+}
+
+/// Prove a <= value <= b for the commitment of `value_hex` under `blinding_hex`
+///
+/// On CUPROOF_OK, `*out_bytes` and `*out_len` hold the proof in the binary container format of
+/// save_proof; release it with cuproof_bytes_free. On any other code both are left untouched.
+///
+/// # Safety
+/// `params` must be a live handle, the hex arguments NUL-terminated strings, and `out_bytes` and
+/// `out_len` writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cuproof_prove_bytes(
+    params: *const CuproofParams, value_hex: *const c_char, blinding_hex: *const c_char, a_hex: *const c_char, b_hex: *const c_char,
+    out_bytes: *mut *mut u8, out_len: *mut usize,
+) -> i32 {
+    if params.is_null() || out_bytes.is_null() || out_len.is_null() { return CUPROOF_ERR_NULL; }
+    let params = unsafe { &(*params).params };
+    let (v, r, a, b) = match unsafe { (hex_arg(value_hex), hex_arg(blinding_hex), hex_arg(a_hex), hex_arg(b_hex)) } {
+        (Ok(v), Ok(r), Ok(a), Ok(b)) => (v, r, a, b),
+        (Err(code), ..) | (_, Err(code), ..) | (_, _, Err(code), _) | (.., Err(code)) => return code,
+    };
+    guard(|| {
+        let proof = prove(&v, &r, &a, &b, params).map_err(|e| Error::from(e).exit_code())?;
+        let mut bytes = Vec::new();
+        write_proof(&mut bytes, &proof).map_err(|e| Error::from(e).exit_code())?;
+        let bytes = Box::into_raw(bytes.into_boxed_slice());
+        unsafe {
+            *out_len = bytes.len();
+            *out_bytes = bytes.cast::<u8>();
+        }
+        Ok(())
+    })
+}
+
+/// Release the bytes of cuproof_prove_bytes; null is ignored
+///
+/// # Safety
+/// `bytes` and `len` must be exactly what cuproof_prove_bytes returned, not yet freed
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cuproof_bytes_free(bytes: *mut u8, len: usize) {
+    if bytes.is_null() { return; }
+    drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)) });
+}
+
+/// Run `f` at the boundary: its error code, CUPROOF_OK, or CUPROOF_ERR_PANIC if it panicked
+fn guard(f: impl FnOnce() -> Result<(), i32>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => CUPROOF_OK,
+        Ok(Err(code)) => code,
+        Err(_) => CUPROOF_ERR_PANIC,
+    }
+}
+
+/// A non-negative hex integer from a C string
+unsafe fn hex_arg(s: *const c_char) -> Result<BigInt, i32> {
+    if s.is_null() { return Err(CUPROOF_ERR_NULL); }
+    let s = unsafe { CStr::from_ptr(s) }.to_str().map_err(|_| CUPROOF_ERR_ARGUMENT)?;
+    hex_to_bigint(s).map_err(|_| CUPROOF_ERR_ARGUMENT)
+}
@@ -0,0 +1,89 @@
+/* Exercise include/cuproof.h from C; built and run by tests/c_abi.rs
+ *
+ * usage: abi <params file> <commitment to 0x2a under blinding 0x1234, as hex>
+ * Prints "ok" and exits 0 when every check passes, otherwise names the first failing check. */
+
+#include <pthread.h>
+#include <stdio.h>
+#include <string.h>
+#include "cuproof.h"
+
+#define CHECK(cond) do { if (!(cond)) { fprintf(stderr, "check failed at line %d: %s\n", __LINE__, #cond); return 1; } } while (0)
+
+struct shared {
+    const CuproofParams *params;
+    const uint8_t *proof;
+    size_t len;
+    const char *commitment;
+    int32_t status;
+};
+
+static void *verify_thread(void *arg) {
+    struct shared *s = arg;
+    s->status = cuproof_verify_bytes(s->params, s->proof, s->len, "0a", "64", s->commitment);
+    return NULL;
+}
+
+static uint8_t *read_file(const char *path, size_t *len) {
+    FILE *f = fopen(path, "rb");
+    if (!f) return NULL;
+    fseek(f, 0, SEEK_END);
+    *len = (size_t)ftell(f);
+    fseek(f, 0, SEEK_SET);
+    uint8_t *bytes = malloc(*len);
+    if (bytes && fread(bytes, 1, *len, f) != *len) { free(bytes); bytes = NULL; }
+    fclose(f);
+    return bytes;
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 3);
+    size_t params_len = 0;
+    uint8_t *params_bytes = read_file(argv[1], &params_len);
+    CHECK(params_bytes != NULL);
+    const char *commitment = argv[2];
+
+    CuproofParams *params = NULL;
+    CHECK(cuproof_params_load(params_bytes, params_len - 1, &params) == CUPROOF_ERR_SERIALIZATION);
+    CHECK(params == NULL);
+    CHECK(cuproof_params_load(NULL, 0, &params) == CUPROOF_ERR_NULL);
+    CHECK(cuproof_params_load(params_bytes, params_len, &params) == CUPROOF_OK);
+    CHECK(params != NULL);
+
+    uint8_t *proof = NULL;
+    size_t proof_len = 0;
+    CHECK(cuproof_prove_bytes(params, "2a", "0x1234", "0a", "64", &proof, &proof_len) == CUPROOF_OK);
+    CHECK(proof != NULL && proof_len > 0);
+    CHECK(cuproof_verify_bytes(params, proof, proof_len, "0a", "64", commitment) == CUPROOF_OK);
+
+    /* one handle, several verifiers at once */
+    pthread_t threads[4];
+    struct shared shared[4];
+    for (int i = 0; i < 4; i++) {
+        shared[i] = (struct shared){ params, proof, proof_len, commitment, -100 };
+        CHECK(pthread_create(&threads[i], NULL, verify_thread, &shared[i]) == 0);
+    }
+    for (int i = 0; i < 4; i++) {
+        CHECK(pthread_join(threads[i], NULL) == 0);
+        CHECK(shared[i].status == CUPROOF_OK);
+    }
+
+    CHECK(cuproof_verify_bytes(params, proof, proof_len, "0a", "63", commitment) == CUPROOF_ERR_VERIFY);
+    CHECK(cuproof_verify_bytes(params, proof, proof_len, "0a", "64", "1234") == CUPROOF_ERR_VERIFY);
+    CHECK(cuproof_verify_bytes(params, proof, proof_len / 2, "0a", "64", commitment) == CUPROOF_ERR_SERIALIZATION);
+    CHECK(cuproof_verify_bytes(params, proof, proof_len, "0a", "not hex", commitment) == CUPROOF_ERR_ARGUMENT);
+    CHECK(cuproof_verify_bytes(params, proof, proof_len, "0a", NULL, commitment) == CUPROOF_ERR_NULL);
+
+    uint8_t *unused = NULL;
+    size_t unused_len = 0;
+    CHECK(cuproof_prove_bytes(params, "65", "0x1234", "0a", "64", &unused, &unused_len) == CUPROOF_ERR_PROVE);
+    CHECK(unused == NULL && unused_len == 0);
+
+    cuproof_bytes_free(proof, proof_len);
+    cuproof_bytes_free(NULL, 0);
+    cuproof_params_free(params);
+    cuproof_params_free(NULL);
+    free(params_bytes);
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,139 @@
+//! include/cuproof.h checked against the exports of src/lib.rs, and tests/abi.c compiled against
+//! it and the static library, then run
+
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Command;
+use cuproof::group::Group;
+use cuproof::setup::insecure_test_setup;
+use cuproof::util::{bigint_to_hex, save_params};
+use num_bigint::BigInt;
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("cuproof_ffi_{}_{}", std::process::id(), name)).to_str().unwrap().to_string()
+}
+
+/// Split C or Rust source into identifier, number and punctuation tokens
+fn tokens(source: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            let mut word = c.to_string();
+            while let Some(&n) = chars.peek() && (n.is_alphanumeric() || n == '_') { word.push(n); chars.next(); }
+            out.push(word);
+        } else if !c.is_whitespace() {
+            out.push(c.to_string());
+        }
+    }
+    out
+}
+
+/// The C spelling of a Rust parameter or return type of the ABI, e.g. `*mut *mut u8` -> `uint8_t **`
+fn c_type(rust: &[String]) -> String {
+    match rust {
+        [star, kind, rest @ ..] if star == "*" => {
+            let inner = c_type(rest);
+            let inner = if kind == "const" && rest.first().is_none_or(|t| t != "*") { format!("const {}", inner) } else { inner };
+            format!("{} *", inner)
+        }
+        [name] => match name.as_str() {
+            "u8" => "uint8_t", "i32" => "int32_t", "usize" => "size_t", "c_char" => "char", other => other,
+        }.to_string(),
+        _ => panic!("no C spelling for {:?}", rust),
+    }
+}
+
+/// The C prototype of every `extern "C" fn` in `lib_rs`, keyed by name
+fn rust_exports(lib_rs: &str) -> Vec<(String, String)> {
+    let mut exports = Vec::new();
+    for (_, rest) in lib_rs.match_indices("extern \"C\" fn ").map(|(i, _)| lib_rs.split_at(i + "extern \"C\" fn ".len())) {
+        let signature = &rest[..rest.find('{').unwrap()];
+        let (name, rest) = signature.split_once('(').unwrap();
+        let (args, ret) = rest.rsplit_once(')').unwrap();
+        let args: Vec<String> = args.split(',').map(str::trim).filter(|a| !a.is_empty()).map(|arg| {
+            let (param, ty) = arg.split_once(':').unwrap();
+            format!("{} {}", c_type(&tokens(ty)), param.trim())
+        }).collect();
+        let ret = ret.trim().strip_prefix("->").map_or("void".to_string(), |ty| c_type(&tokens(ty)));
+        let args = if args.is_empty() { "void".to_string() } else { args.join(", ") };
+        exports.push((name.trim().to_string(), format!("{} {}({});", ret, name.trim(), args)));
+    }
+    exports
+}
+
+/// `header` without its comments and preprocessor lines
+fn declarations(header: &str) -> String {
+    let mut out = String::new();
+    let mut rest = header;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + rest[start..].find("*/").unwrap() + 2..];
+    }
+    out.push_str(rest);
+    out.lines().map(|line| line.split("//").next().unwrap()).filter(|line| !line.trim_start().starts_with('#')).collect::<Vec<_>>().join("\n")
+}
+
+// Purpose: ensure include/cuproof.h, which is maintained by hand, declares exactly the ABI of
+//          src/lib.rs: a prototype with the same parameter and return types for every
+//          `extern "C" fn`, no prototype without an export, and a #define with the same value for
+//          every CUPROOF_ constant
+// Params: src/lib.rs and include/cuproof.h as checked in
+// Output: every export and constant is found in the header, and the header names no other function
+// Usage: `cargo test -p cuproof-ffi --test c_abi`
+#[test]
+fn header_declares_every_export() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_rs = std::fs::read_to_string(crate_dir.join("src/lib.rs")).unwrap();
+    let header = std::fs::read_to_string(crate_dir.join("include/cuproof.h")).unwrap();
+    let declared = tokens(&declarations(&header));
+
+    let exports = rust_exports(&lib_rs);
+    assert!(exports.len() >= 5, "found only {:?}", exports);
+    for (name, prototype) in &exports {
+        let expected = tokens(prototype);
+        assert!(declared.windows(expected.len()).any(|w| w == expected), "include/cuproof.h lacks `{}` for {}", prototype, name);
+    }
+    let functions: Vec<&String> = declared.windows(2).filter(|w| w[1] == "(" && w[0].starts_with("cuproof_")).map(|w| &w[0]).collect();
+    for function in functions {
+        assert!(exports.iter().any(|(name, _)| name == function), "include/cuproof.h declares {}, which src/lib.rs does not export", function);
+    }
+
+    for line in lib_rs.lines().filter_map(|line| line.strip_prefix("pub const CUPROOF_")) {
+        let (name, value) = line.split_once(": i32 = ").unwrap();
+        let define = format!("#define CUPROOF_{} {}", name, value.trim_end_matches(';'));
+        assert!(header.lines().any(|line| line == define), "include/cuproof.h lacks `{}`", define);
+    }
+}
+
+// Purpose: ensure the C ABI works as declared: a C program can load params, prove, verify from
+//          several threads at once, and gets the documented codes for bad input
+// Params: insecure 512-bit params saved to a file; the commitment to 42 under blinding 0x1234;
+//         the C compiler of $CC, else cc
+// Output: abi.c compiles with -Wall -Werror, links against libcuproof_ffi.a and prints "ok"
+// Usage: `cargo test -p cuproof-ffi`
+#[test]
+fn c_program_proves_and_verifies() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let exe = std::env::current_exe().unwrap();
+    let library = exe.parent().and_then(Path::parent).unwrap().join("libcuproof_ffi.a");
+    assert!(library.exists(), "{} was not built", library.display());
+    let (params_path, program) = (temp_path("params.bin"), temp_path("abi"));
+    let params = insecure_test_setup(512);
+    save_params(&params_path, &params).unwrap();
+    let commitment = params.commit(&BigInt::from(42), &BigInt::from(0x1234));
+
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let out = Command::new(cc)
+        .args(["-std=c11", "-Wall", "-Werror", "-I"]).arg(crate_dir.join("include"))
+        .arg(crate_dir.join("tests/abi.c")).arg(&library)
+        .args(["-lpthread", "-ldl", "-lm", "-o", &program])
+        .output().expect("no C compiler");
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+
+    let out = Command::new(&program).args([&params_path, &bigint_to_hex(&commitment)]).output().unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "ok\n");
+    for path in [params_path, program] { let _ = std::fs::remove_file(path); }
+}
@@ -0,0 +1,11 @@
+//! Cuproof::from_armored on arbitrary text
+#![no_main]
+
+use cuproof::range_proof::Cuproof;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = Cuproof::from_armored(text);
+    }
+});
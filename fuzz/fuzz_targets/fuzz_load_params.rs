@@ -0,0 +1,10 @@
+//! load_params on arbitrary bytes, including validate_params and validate_metadata on whatever parses
+#![no_main]
+
+use cuproof::util::{migrate_params, read_params, ReadLimits};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read_params(&mut &data[..], &ReadLimits::default());
+    let _ = migrate_params(data);
+});
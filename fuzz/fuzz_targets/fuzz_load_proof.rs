@@ -0,0 +1,11 @@
+//! load_proof on arbitrary bytes: every format it detects (container, legacy text, armor), plus the
+//! migration path of `cuproof convert`
+#![no_main]
+
+use cuproof::util::{migrate_proof, read_proof, ReadLimits};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read_proof(&mut &data[..], &ReadLimits::default());
+    let _ = migrate_proof(data);
+});
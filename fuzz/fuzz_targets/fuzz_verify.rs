@@ -0,0 +1,26 @@
+//! verify on structure-aware input: libFuzzer's bytes build Params and a Cuproof through the
+//! crate's `arbitrary::Arbitrary` implementations, so runs reach the transcript and commitment
+//! checks instead of stopping in the decoders. Every generated proof must be rejected and re-encode
+//! losslessly
+#![no_main]
+
+use cuproof::range_proof::Cuproof;
+use cuproof::setup::Params;
+use cuproof::util::{read_proof, write_proof, ReadLimits};
+use cuproof::verify::{cuproof_verify_checked, verify};
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+use libfuzzer_sys::fuzz_target;
+
+// the input stays raw bytes: Cuproof has no Debug for libFuzzer to print a typed input with
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    let (Ok(params), Ok(proof)) = (Params::arbitrary(&mut u), Cuproof::arbitrary(&mut u)) else { return };
+    // forging a proof from fuzzer bytes would break soundness
+    assert!(!verify(&proof, &params));
+    assert!(cuproof_verify_checked(&proof, &params).is_err());
+
+    let mut bytes = Vec::new();
+    write_proof(&mut bytes, &proof).expect("generated proofs encode");
+    let loaded = read_proof(&mut &bytes[..], &ReadLimits::default()).expect("generated proofs load");
+    assert!(!verify(&loaded, &params));
+});
@@ -0,0 +1,31 @@
+//! The core of cuproof without std: wire-format params in, a verified wire-format proof out
+//!
+//! Built with `cuproof = { default-features = false }`, so nothing here may reach for a clock,
+//! a file or the OS random source. tests/check.rs also builds this crate for a bare-metal target
+//! when one is installed
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use cuproof::range_proof::prove_with_rng;
+use cuproof::verify::verify;
+use cuproof::wire::{params_from_wire, proof_from_wire, proof_to_wire};
+use num_bigint::BigInt;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// Prove a <= v <= b under the params in `params_wire`, with blinding `r` and randomness from `seed`
+/// - returns: the proof in wire format once it decodes again and verify accepts it; the
+///   first failing step otherwise
+pub fn prove_to_wire(params_wire: &[u8], v: u64, r: u64, a: u64, b: u64, seed: u64) -> Result<Vec<u8>, &'static str> {
+    let params = params_from_wire(params_wire).map_err(|_| "params do not decode")?;
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let [v, r, a, b] = [v, r, a, b].map(BigInt::from);
+    let proof = prove_with_rng(&v, &r, &a, &b, &params, 64, &mut rng).map_err(|_| "proving failed")?;
+    let bytes = proof_to_wire(&proof).map_err(|_| "proof does not encode")?;
+    let decoded = proof_from_wire(&bytes).map_err(|_| "proof does not decode")?;
+    if !verify(&decoded, &params) { return Err("proof rejected"); }
+    Ok(bytes)
+}
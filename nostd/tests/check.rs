@@ -0,0 +1,27 @@
+//! `cargo check` of this crate on its own, so the std feature of cuproof cannot be switched on by
+//! another workspace member
+
+use std::path::Path;
+use std::process::Command;
+
+const BARE_METAL: &str = "thumbv7em-none-eabihf";
+
+// Purpose: ensure cuproof builds with default features off, for a bare-metal target when its
+//          standard library is installed (`rustup target add thumbv7em-none-eabihf`), else the host
+// Params: this crate alone, in a target directory of its own
+// Output: cargo check succeeds
+// Usage: `cargo test -p cuproof-nostd`
+#[test]
+fn builds_without_std() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let sysroot = Command::new(rustc).args(["--print", "sysroot"]).output().unwrap();
+    let sysroot = String::from_utf8(sysroot.stdout).unwrap();
+    let installed = Path::new(sysroot.trim()).join("lib/rustlib").join(BARE_METAL).exists();
+
+    let mut check = Command::new(env!("CARGO"));
+    check.args(["check", "--quiet", "-p", "cuproof-nostd", "--lib"]).current_dir(root).arg("--target-dir").arg(root.join("target/nostd-check"));
+    if installed { check.args(["--target", BARE_METAL]); }
+    let out = check.output().unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+}
@@ -0,0 +1,18 @@
+//! Proving and verifying through a cuproof built without std
+
+use cuproof_nostd::prove_to_wire;
+
+// Purpose: ensure core prove/verify and wire serialization work in the no_std build
+// Params: the insecure 512-bit reference params testdata/params_v1.wire; v = 42 in [10, 100]
+// Output: a proof that decodes and verifies, the same bytes for the same seed; an error for
+//         v outside the range and for truncated params
+// Usage: `cargo test -p cuproof-nostd`
+#[test]
+fn proves_and_verifies_without_std() {
+    let params = include_bytes!("../../testdata/params_v1.wire");
+    let proof = prove_to_wire(params, 42, 0x1234, 10, 100, 7).unwrap();
+    assert_eq!(prove_to_wire(params, 42, 0x1234, 10, 100, 7).unwrap(), proof);
+    assert_ne!(prove_to_wire(params, 42, 0x1234, 10, 100, 8).unwrap(), proof);
+    assert!(prove_to_wire(params, 101, 0x1234, 10, 100, 7).is_err());
+    assert_eq!(prove_to_wire(&params[..params.len() - 1], 42, 0x1234, 10, 100, 7), Err("params do not decode"));
+}
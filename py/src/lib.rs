@@ -0,0 +1,152 @@
+//! The `cuproof` Python module
+//!
+//! ```python
+//! import cuproof
+//! params = cuproof.Params.load("params.bin")
+//! commitment, blinding = params.commit(42)
+//! proof = params.prove(42, blinding, 10, 100)
+//! result = params.verify(proof, 10, 100, commitment)
+//! assert result, result.reason
+//! ```
+//!
+//! Integers are Python ints of any size; proofs are `bytes` in the container format of
+//! save_proof, so they can be passed to and from the CLI unchanged. Proving and verifying release
+//! the GIL, so a thread pool over one Params runs them in parallel. Failures to load, save or
+//! prove raise CuproofError; verification never raises for a bad proof, it returns a
+//! VerifyResult naming the reason.
+
+use cuproof::batch::{check_item, Expected};
+use cuproof::group::Group;
+use cuproof::range_proof::{prove, random_blinding};
+use cuproof::setup::Params;
+use cuproof::util::{load_params, read_params, read_proof, save_params, write_proof, ReadLimits};
+use cuproof::Error;
+use num_bigint::BigInt;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rand::rngs::OsRng;
+
+create_exception!(cuproof, CuproofError, PyException, "A load, save or prove failed; the message says why");
+
+fn raise(e: impl Into<Error>) -> PyErr {
+    CuproofError::new_err(e.into().to_string())
+}
+
+/// Validated public parameters
+#[pyclass(name = "Params", module = "cuproof", frozen)]
+struct PyParams {
+    params: Params,
+}
+
+#[pymethods]
+impl PyParams {
+    /// Params from a file in any format `cuproof setup` and `convert` write
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<PyParams> {
+        load_params(path).map(|params| PyParams { params }).map_err(raise)
+    }
+
+    /// Params from the bytes of such a file
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<PyParams> {
+        read_params(&mut &data[..], &ReadLimits::default()).map(|params| PyParams { params }).map_err(raise)
+    }
+
+    /// Write the params in the binary container format
+    fn save(&self, path: &str) -> PyResult<()> {
+        save_params(path, &self.params).map_err(raise)
+    }
+
+    /// SHA-256 fingerprint as 64 hex digits, as `cuproof fingerprint` prints it
+    #[getter]
+    fn fingerprint(&self) -> String {
+        self.params.fingerprint_hex().to_string()
+    }
+
+    #[getter]
+    fn bits(&self) -> usize {
+        self.params.bits()
+    }
+
+    /// C = g^value * h^blinding, drawing the blinding when none is given
+    /// - returns: (commitment, blinding)
+    #[pyo3(signature = (value, blinding=None))]
+    fn commit(&self, value: BigInt, blinding: Option<BigInt>) -> (BigInt, BigInt) {
+        let r = blinding.unwrap_or_else(|| random_blinding(&mut OsRng));
+        (self.params.commit(&value, &r), r)
+    }
+
+    /// Prove a <= value <= b for the commitment of value under blinding
+    /// - returns: the proof as bytes; raises CuproofError when value lies outside [a, b]
+    fn prove<'py>(&self, py: Python<'py>, value: BigInt, blinding: BigInt, a: BigInt, b: BigInt) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = py.allow_threads(|| -> Result<Vec<u8>, Error> {
+            let proof = prove(&value, &blinding, &a, &b, &self.params)?;
+            let mut bytes = Vec::new();
+            write_proof(&mut bytes, &proof)?;
+            Ok(bytes)
+        });
+        Ok(PyBytes::new_bound(py, &bytes.map_err(raise)?))
+    }
+
+    /// Check a proof of a <= v <= b, and that it is for `commitment` when one is given
+    #[pyo3(signature = (proof_bytes, a, b, commitment=None))]
+    fn verify(&self, py: Python<'_>, proof_bytes: &[u8], a: BigInt, b: BigInt, commitment: Option<BigInt>) -> VerifyResult {
+        let expected = Expected { a, b, commitment };
+        py.allow_threads(|| check(&self.params, proof_bytes, Some(&expected)))
+    }
+
+    /// Check each proof on its own, against the statement it carries
+    /// - returns: one VerifyResult per proof, in order
+    fn batch_verify(&self, py: Python<'_>, proofs: Vec<Vec<u8>>) -> Vec<VerifyResult> {
+        py.allow_threads(|| proofs.iter().map(|bytes| check(&self.params, bytes, None)).collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Params(bits={}, fingerprint={})", self.params.bits(), self.params.fingerprint_hex())
+    }
+}
+
+/// Outcome of a verification; true exactly when the proof was accepted
+#[pyclass(module = "cuproof", frozen, get_all)]
+struct VerifyResult {
+    valid: bool,
+    /// stable error code, e.g. "verify.invalid_proof" (see cuproof::error::ERROR_CODES)
+    code: Option<&'static str>,
+    /// what failed, in words
+    reason: Option<String>,
+}
+
+#[pymethods]
+impl VerifyResult {
+    fn __bool__(&self) -> bool {
+        self.valid
+    }
+
+    fn __repr__(&self) -> String {
+        match (&self.code, &self.reason) {
+            (Some(code), Some(reason)) => format!("VerifyResult(valid=False, code={:?}, reason={:?})", code, reason),
+            _ => "VerifyResult(valid=True)".to_string(),
+        }
+    }
+}
+
+fn check(params: &Params, bytes: &[u8], expected: Option<&Expected>) -> VerifyResult {
+    let result = read_proof(&mut &bytes[..], &ReadLimits::default())
+        .map_err(Error::from)
+        .and_then(|proof| check_item(&proof, params, expected).map_err(Error::from));
+    match result {
+        Ok(()) => VerifyResult { valid: true, code: None, reason: None },
+        Err(e) => VerifyResult { valid: false, code: Some(e.code()), reason: Some(e.to_string()) },
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "cuproof")]
+fn cuproof_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyParams>()?;
+    m.add_class::<VerifyResult>()?;
+    m.add("CuproofError", m.py().get_type_bound::<CuproofError>())?;
+    Ok(())
+}
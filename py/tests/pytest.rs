@@ -0,0 +1,23 @@
+//! tests/test_cuproof.py from `cargo test`: installs the module with maturin into the current
+//! Python environment, then runs pytest on it
+
+use std::path::Path;
+use std::process::Command;
+
+fn run(program: &str, args: &[&str], dir: &Path) {
+    let out = Command::new(program).args(args).current_dir(dir).output()
+        .unwrap_or_else(|e| panic!("cannot run {} ({}); `pip install maturin pytest` into the active environment", program, e));
+    assert!(out.status.success(), "{} {:?} failed:\n{}{}", program, args, String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
+}
+
+// Purpose: run the Python tests of the bindings from the Rust test harness
+// Params: the crate directory; maturin and pytest on PATH, in a virtualenv maturin can install into
+// Output: maturin develop and pytest both succeed
+// Usage: `cargo test` in py/
+#[test]
+fn python_tests_pass() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    run("maturin", &["develop", "--quiet"], dir);
+    let python = std::env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+    run(&python, &["-m", "pytest", "-q", "tests"], dir);
+}
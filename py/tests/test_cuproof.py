@@ -0,0 +1,52 @@
+"""The cuproof module: a round trip and the failures a caller must be able to tell apart
+
+Run by tests/pytest.rs after `maturin develop`, or directly with `pytest tests/`."""
+
+import pathlib
+
+import pytest
+
+import cuproof
+
+PARAMS = pathlib.Path(__file__).resolve().parents[2] / "testdata" / "dump_params.bin"
+
+
+@pytest.fixture(scope="module")
+def params():
+    return cuproof.Params.load(str(PARAMS))
+
+
+def test_round_trip(params, tmp_path):
+    assert params.bits == 512
+    assert len(params.fingerprint) == 64
+    assert cuproof.Params.from_bytes(PARAMS.read_bytes()).fingerprint == params.fingerprint
+    params.save(str(tmp_path / "params.bin"))
+    assert cuproof.Params.load(str(tmp_path / "params.bin")).fingerprint == params.fingerprint
+
+    value = 2**70 + 42
+    commitment, blinding = params.commit(value)
+    assert params.commit(value, blinding) == (commitment, blinding)
+    proof = params.prove(value, blinding, 2**70, 2**71)
+    assert isinstance(proof, bytes)
+    result = params.verify(proof, 2**70, 2**71, commitment)
+    assert result and result.valid and result.code is None and result.reason is None
+    assert params.verify(proof, 2**70, 2**71)
+    assert [bool(r) for r in params.batch_verify([proof, proof])] == [True, True]
+
+
+def test_invalid_proofs_name_the_reason(params):
+    commitment, blinding = params.commit(42)
+    proof = params.prove(42, blinding, 10, 100)
+
+    other, _ = params.commit(42)
+    result = params.verify(proof, 10, 100, other)
+    assert not result and result.code == "verify.statement_mismatch" and "commitment" in result.reason
+    assert params.verify(proof, 10, 99, commitment).code == "verify.statement_mismatch"
+    truncated = params.verify(proof[: len(proof) // 2], 10, 100, commitment)
+    assert not truncated and truncated.code.startswith("serialization.")
+    assert [r.valid for r in params.batch_verify([proof, b"not a proof"])] == [True, False]
+
+    with pytest.raises(cuproof.CuproofError):
+        params.prove(101, blinding, 10, 100)
+    with pytest.raises(cuproof.CuproofError):
+        cuproof.Params.from_bytes(b"not params")
@@ -0,0 +1,371 @@
+//! One proof for many range statements (`cuproof aggregate`)
+//!
+//! An AggregatedCuproof proves a_j <= v_j <= b_j for every statement j of a list with a single
+//! A, S, T1, T2 and inner-product argument over the concatenated decomposition vectors, so it
+//! grows by three commitments and one inner-product round per doubling of the list instead of by
+//! a whole proof per statement. A and S commit each statement's part of the vectors under its own
+//! generator of `Params::derive_generators(AGGREGATE_GENERATOR_LABEL, m)`, which prover and
+//! verifier derive independently. The Fiat-Shamir transcript hashes every statement's
+//! commitments in order, and aggregate_verify makes the checks verify makes of a single proof
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::BigInt;
+use num_traits::Zero;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use crate::commitment::mod_exp;
+use crate::fiat_shamir::{fiat_shamir, FiatShamir};
+use crate::group::Group;
+use crate::range_proof::{
+    check_prove_inputs, commit_value, prove_polynomial, random_blinding, square_decomposition, IPPProof, Polynomial,
+    ProveError, Statement,
+};
+#[cfg(feature = "std")]
+use crate::range_proof::Cuproof;
+use crate::scalar::{Scalar, ScalarBound};
+use crate::setup::Params;
+#[cfg(feature = "tracing")]
+use crate::setup::FingerprintHex;
+use crate::trace::traced;
+use crate::verify::{range_accepted, Challenges, VerifyError};
+
+/// Vector dimension each statement contributes, as in prove
+pub const AGGREGATE_STATEMENT_BITS: usize = 64;
+
+/// Label of the per-statement generators A and S are committed under
+pub const AGGREGATE_GENERATOR_LABEL: &str = "agg/g";
+
+/// Most statements one aggregated proof may hold; the decoder refuses larger counts before allocating
+pub const MAX_AGGREGATED_STATEMENTS: usize = 1 << 16;
+
+/// One statement to aggregate and its opening: C = g^v h^r, with a <= v <= b
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateEntry {
+    pub v: BigInt,
+    pub r: BigInt,
+    pub a: BigInt,
+    pub b: BigInt,
+}
+
+/// The commitments of one statement in an aggregated proof: C to v, C_v1 to v1 = 4v - 4a + 1
+/// and C_v2 to v2 = 4b - 4v + 1
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueCommitments {
+    pub C: BigInt,
+    pub C_v1: BigInt,
+    pub C_v2: BigInt,
+}
+
+/// A range proof for every statement of a list at once; `statements[j]` describes
+/// `commitments[j]` as Cuproof::statement does, with bits AGGREGATE_STATEMENT_BITS
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct AggregatedCuproof {
+    pub commitments: Vec<ValueCommitments>,
+    pub A: BigInt,
+    pub S: BigInt,
+    pub T1: BigInt,
+    pub T2: BigInt,
+    pub tau_x: BigInt,
+    pub mu: BigInt,
+    pub t_hat: BigInt,
+    pub t0: BigInt,
+    pub t1: BigInt,
+    pub t2: BigInt,
+    pub tau1: BigInt,
+    pub tau2: BigInt,
+    pub ipp_proof: IPPProof,
+    pub epoch: u64,
+    pub statements: Vec<Statement>,
+}
+
+/// Inner-product rounds of an aggregated proof of `count` statements: the concatenated vectors
+/// are padded to the next power of two
+pub fn aggregate_rounds(count: usize) -> usize {
+    (AGGREGATE_STATEMENT_BITS * count).next_power_of_two().trailing_zeros() as usize
+}
+
+/// Prove every entry's range in one proof
+/// - returns: the proof, whose commitments[j] opens with (entries[j].v, entries[j].r);
+///   InvalidDimension for no entries or more than MAX_AGGREGATED_STATEMENTS, Aggregate naming the
+///   first entry prove would refuse
+/// - usage: many statements under the same params, e.g. every balance of a report
+#[cfg(feature = "std")]
+pub fn aggregate_prove(entries: &[AggregateEntry], params: &Params) -> Result<AggregatedCuproof, ProveError> {
+    aggregate_prove_with_rng(entries, params, &mut OsRng)
+}
+
+/// aggregate_prove drawing every blinding but the entries' r from `rng`
+pub fn aggregate_prove_with_rng<R: RngCore + CryptoRng>(entries: &[AggregateEntry], params: &Params, rng: &mut R) -> Result<AggregatedCuproof, ProveError> {
+    traced!(
+        tracing::debug_span!("aggregate_prove", statements = entries.len(), params = %FingerprintHex(params.fingerprint())),
+        prove_aggregate(entries, params, rng),
+        |proof, elapsed_ms| match proof {
+            Ok(proof) => tracing::debug!(rounds = proof.ipp_proof.L.len(), elapsed_ms, "aggregated proof created"),
+            Err(e) => tracing::debug!(error = %e, elapsed_ms, "aggregated proof refused"),
+        }
+    )
+}
+
+#[allow(non_snake_case)]
+fn prove_aggregate<R: RngCore + CryptoRng>(entries: &[AggregateEntry], params: &Params, rng: &mut R) -> Result<AggregatedCuproof, ProveError> {
+    if entries.is_empty() || entries.len() > MAX_AGGREGATED_STATEMENTS {
+        return Err(ProveError::InvalidDimension(AGGREGATE_STATEMENT_BITS * entries.len()));
+    }
+    let n = params.challenge_modulus();
+    let m = entries.len();
+    let dimension = 1 << aggregate_rounds(m);
+    let mut d = Vec::with_capacity(dimension);
+    let mut commitments = Vec::with_capacity(m);
+    for (index, entry) in entries.iter().enumerate() {
+        let refused = |error| ProveError::Aggregate { index, error: Box::new(error) };
+        check_prove_inputs(&entry.v, &entry.a, &entry.b, AGGREGATE_STATEMENT_BITS).map_err(refused)?;
+        let v1 = 4 * &entry.v - 4 * &entry.a + 1;
+        let v2 = 4 * &entry.b - 4 * &entry.v + 1;
+        let d_base = square_decomposition(&v1, &v2).map_err(refused)?;
+        d.extend((0..AGGREGATE_STATEMENT_BITS).map(|i| d_base[i % d_base.len()].clone()));
+        let C = params.commit(&entry.v, &entry.r);
+        let (C_v1, _r_v1) = commit_value(params, &v1, rng);
+        let (C_v2, _r_v2) = commit_value(params, &v2, rng);
+        commitments.push(ValueCommitments { C, C_v1, C_v2 });
+    }
+    d.resize(dimension, BigInt::zero());
+
+    let alpha = random_blinding(rng);
+    let rho = random_blinding(rng);
+    let sL = (0..dimension).map(|_| random_blinding(rng)).collect::<Vec<_>>();
+    let sR = (0..dimension).map(|_| random_blinding(rng)).collect::<Vec<_>>();
+
+    // A and S commit each statement's share of d and of sL + sR under its own generator
+    let generators = params.derive_generators(AGGREGATE_GENERATOR_LABEL, m);
+    let s = sL.iter().zip(&sR).map(|(l, r)| l + r).collect::<Vec<_>>();
+    let A = vector_commit(params, &generators, &d, &alpha);
+    let S = vector_commit(params, &generators, &s, &rho);
+
+    let (y, z) = aggregate_challenges(&A, &S, &commitments, n);
+    let Polynomial { T1, T2, t0, t1, t2, tau1, tau2, t_hat, mu, tau_x, ipp_proof } = prove_polynomial(&d, &sL, &sR, &alpha, &rho, &y, &z, params, &mut FiatShamir::new(), rng);
+
+    let statements = entries.iter().zip(&commitments)
+        .map(|(entry, c)| Statement::new(&c.C, &entry.a, &entry.b, AGGREGATE_STATEMENT_BITS, params))
+        .collect();
+    Ok(AggregatedCuproof {
+        commitments, A, S, T1, T2, tau_x, mu, t_hat, t0, t1, t2, tau1, tau2, ipp_proof,
+        epoch: params.epoch(),
+        statements,
+    })
+}
+
+/// h^blinding times generators[j] raised to the sum of statement j's coordinates of `values`;
+/// padding coordinates count towards the last statement
+fn vector_commit(params: &Params, generators: &[BigInt], values: &[BigInt], blinding: &BigInt) -> BigInt {
+    let n = params.n();
+    let last = generators.len() - 1;
+    let mut sums = vec![BigInt::zero(); generators.len()];
+    for (i, x) in values.iter().enumerate() {
+        sums[(i / AGGREGATE_STATEMENT_BITS).min(last)] += x;
+    }
+    generators.iter().zip(&sums).fold(params.h_pow(blinding), |acc, (g, e)| acc * mod_exp(g, e, n) % n)
+}
+
+/// y over A, S and every statement's (C, C_v1, C_v2) in order, then z over y
+#[allow(non_snake_case)]
+fn aggregate_challenges(A: &BigInt, S: &BigInt, commitments: &[ValueCommitments], n: &BigInt) -> (BigInt, BigInt) {
+    let mut transcript = vec![A, S];
+    for c in commitments { transcript.extend([&c.C, &c.C_v1, &c.C_v2]); }
+    let y = fiat_shamir(&transcript) % n;
+    let z = fiat_shamir(&[&y]) % n;
+    (y, z)
+}
+
+/// Recompute the challenges of an aggregated proof as aggregate_verify does, whether or not it verifies
+/// - usage: the known-answer vectors (vectors module), as verify::proof_challenges for single proofs
+pub fn aggregated_proof_challenges(proof: &AggregatedCuproof, params: &Params) -> Challenges {
+    let n = params.challenge_modulus();
+    let (y, z) = aggregate_challenges(&proof.A, &proof.S, &proof.commitments, n);
+    let x = fiat_shamir(&[&proof.T1, &proof.T2]) % n;
+    let ipp = proof.ipp_proof.L.iter().zip(&proof.ipp_proof.R).map(|(l, r)| fiat_shamir(&[l, r]) % n).collect();
+    Challenges { y, z, x, ipp }
+}
+
+/// Check an aggregated proof against params, with the checks verify makes of a single proof
+/// - returns: Ok(()) for a valid proof; EpochMismatch first, then AggregateStatementMismatch naming
+///   the first statement that does not describe its commitments under these params, InvalidProof otherwise
+/// - usage: `cuproof verify` on an aggregated proof file
+pub fn aggregate_verify(proof: &AggregatedCuproof, params: &Params) -> Result<(), VerifyError> {
+    traced!(
+        tracing::debug_span!("aggregate_verify", statements = proof.statements.len(), params = %FingerprintHex(params.fingerprint())),
+        verify_aggregate(proof, params),
+        |result, elapsed_ms| match result {
+            Ok(()) => tracing::debug!(elapsed_ms, "aggregated proof verified"),
+            Err(e) => tracing::debug!(error = %e, elapsed_ms, "aggregated proof rejected"),
+        }
+    )
+}
+
+fn verify_aggregate(proof: &AggregatedCuproof, params: &Params) -> Result<(), VerifyError> {
+    if proof.epoch != params.epoch() {
+        return Err(VerifyError::EpochMismatch { proof: proof.epoch, params: params.epoch() });
+    }
+    let m = proof.commitments.len();
+    if m == 0 || proof.statements.len() != m { return Err(VerifyError::InvalidProof); }
+    for (index, (statement, c)) in proof.statements.iter().zip(&proof.commitments).enumerate() {
+        let mismatch = |field| Err(VerifyError::AggregateStatementMismatch { index, field });
+        if statement.commitment != c.C { return mismatch("commitment"); }
+        if statement.params_fingerprint != params.fingerprint() { return mismatch("params fingerprint"); }
+        if statement.bits != AGGREGATE_STATEMENT_BITS { return mismatch("bit width"); }
+        if !range_accepted(&statement.a, &statement.b) { return mismatch("range"); }
+    }
+    if aggregate_checks(proof, params) { Ok(()) } else { Err(VerifyError::InvalidProof) }
+}
+
+fn aggregate_checks(proof: &AggregatedCuproof, params: &Params) -> bool {
+    let n = params.challenge_modulus();
+    let (y, z) = aggregate_challenges(&proof.A, &proof.S, &proof.commitments, n);
+    let x = fiat_shamir(&[&proof.T1, &proof.T2]) % n;
+    if y.is_zero() || z.is_zero() || x.is_zero() { return false; }
+
+    // every scalar in [0, B) for the widest range and the rounds the statement count fixes (see scalar)
+    let rounds = aggregate_rounds(proof.commitments.len());
+    let value_bits = proof.statements.iter().map(|s| s.a.bits().max(s.b.bits()) + 1).max().unwrap_or(0);
+    let bound = ScalarBound::new(n.bits(), value_bits, rounds as u32);
+    let scalars = [
+        ("t0", &proof.t0), ("t1", &proof.t1), ("t2", &proof.t2), ("tau1", &proof.tau1), ("tau2", &proof.tau2),
+        ("t_hat", &proof.t_hat), ("tau_x", &proof.tau_x), ("mu", &proof.mu), ("ipp a", &proof.ipp_proof.a), ("ipp b", &proof.ipp_proof.b),
+    ];
+    if bound.check(scalars).is_err() { return false; }
+
+    if params.commit(&proof.t1, &proof.tau1) != proof.T1 { return false; }
+    if params.commit(&proof.t2, &proof.tau2) != proof.T2 { return false; }
+    let t = [&proof.t0, &proof.t1, &proof.t2].map(|t| bound.scalar("t", t).expect("checked above"));
+    let rhs_t = bound.scalar("x", &x).ok().and_then(|x| Scalar::checked_poly_eval(&t, &x));
+    if rhs_t.as_ref().map(Scalar::value) != Some(&proof.t_hat) { return false; }
+
+    if proof.ipp_proof.L.len() != rounds || proof.ipp_proof.R.len() != rounds { return false; }
+
+    if ![&proof.A, &proof.S, &proof.T1, &proof.T2].into_iter().all(|x| params.is_element(x)) { return false; }
+    proof.commitments.iter().all(|c| {
+        [&c.C, &c.C_v1, &c.C_v2].into_iter().all(|x| params.is_element(x)) && c.C != c.C_v1 && c.C != c.C_v2 && c.C_v1 != c.C_v2
+    })
+}
+
+/// aggregate_verify plus a comparison of the embedded statements with what the caller expects
+/// - params: proof, params, expected one statement per aggregated statement, in order
+/// - returns: Ok(()) when the proof verifies and every statement equals its expected one field by
+///   field; AggregateStatementMismatch naming the first index that differs (a list of another
+///   length mismatches at the first index only one of them has)
+/// - usage: `cuproof verify --statements`, so the ranges come from the verifier's records
+pub fn aggregate_verify_statements(proof: &AggregatedCuproof, params: &Params, expected: &[Statement]) -> Result<(), VerifyError> {
+    aggregate_verify(proof, params)?;
+    if let Some(index) = (0..proof.statements.len().max(expected.len())).find(|&i| proof.statements.get(i) != expected.get(i)) {
+        let field = match (proof.statements.get(index), expected.get(index)) {
+            (Some(stated), Some(wanted)) => statement_difference(stated, wanted),
+            _ => "count",
+        };
+        return Err(VerifyError::AggregateStatementMismatch { index, field });
+    }
+    Ok(())
+}
+
+/// The first field in which two unequal statements differ
+fn statement_difference(stated: &Statement, wanted: &Statement) -> &'static str {
+    if stated.commitment != wanted.commitment { "commitment" }
+    else if stated.a != wanted.a || stated.b != wanted.b { "range" }
+    else if stated.bits != wanted.bits { "bit width" }
+    else if stated.label != wanted.label { "label" }
+    else { "params fingerprint" }
+}
+
+/// The encoded size of one single proof per statement of `proof`, to compare with the aggregated
+/// proof: each is built from the aggregated proof's own fields with the inner-product argument of
+/// a single statement, so integers of the same width are counted
+#[cfg(feature = "std")]
+pub fn individual_proofs_size(proof: &AggregatedCuproof) -> usize {
+    let rounds = aggregate_rounds(1);
+    let ipp_proof = IPPProof {
+        L: proof.ipp_proof.L.iter().take(rounds).cloned().collect(),
+        R: proof.ipp_proof.R.iter().take(rounds).cloned().collect(),
+        a: proof.ipp_proof.a.clone(),
+        b: proof.ipp_proof.b.clone(),
+    };
+    proof.commitments.iter().zip(&proof.statements).map(|(c, statement)| {
+        let single = Cuproof {
+            A: proof.A.clone(), S: proof.S.clone(), T1: proof.T1.clone(), T2: proof.T2.clone(),
+            tau_x: proof.tau_x.clone(), mu: proof.mu.clone(), t_hat: proof.t_hat.clone(),
+            C: c.C.clone(), C_v1: c.C_v1.clone(), C_v2: c.C_v2.clone(),
+            t0: proof.t0.clone(), t1: proof.t1.clone(), t2: proof.t2.clone(), tau1: proof.tau1.clone(), tau2: proof.tau2.clone(),
+            ipp_proof: ipp_proof.clone(),
+            epoch: proof.epoch,
+            statement: Some(statement.clone()),
+            assist: None,
+        };
+        crate::util::encode_proof(&single).map(|bytes| bytes.len()).unwrap_or(0)
+    }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+
+    fn entries() -> Vec<AggregateEntry> {
+        [(42, 7, 0, 100), (0, 8, 0, 1), (-5, 9, -10, 10), (1000, 10, 1000, 1 << 20)].into_iter()
+            .map(|(v, r, a, b)| AggregateEntry { v: BigInt::from(v), r: BigInt::from(r), a: BigInt::from(a), b: BigInt::from(b) })
+            .collect()
+    }
+
+    // Purpose: ensure an aggregated proof verifies, binds its statements and is smaller than single proofs
+    // Params: insecure 512-bit params, four entries with small, negative and wide ranges
+    // Output: Ok from aggregate_verify; commitments open with the entries; 8 rounds; altered
+    //         statements and expected lists rejected naming the index, altered T1, commitments
+    //         and round counts rejected as invalid
+    // Usage: `cargo test -- src::aggregate` or `cargo test`
+    #[test]
+    fn aggregated_proofs_verify_and_bind_their_statements() {
+        let params = insecure_test_setup(512);
+        let entries = entries();
+        let proof = aggregate_prove(&entries, &params).unwrap();
+        assert_eq!(aggregate_verify(&proof, &params), Ok(()));
+        assert_eq!(proof.ipp_proof.L.len(), 8);
+        for (entry, c) in entries.iter().zip(&proof.commitments) {
+            assert_eq!(c.C, params.commit(&entry.v, &entry.r));
+        }
+        assert_eq!(aggregate_verify_statements(&proof, &params, &proof.statements), Ok(()));
+        assert!(crate::util::encode_aggregated_proof(&proof).unwrap().len() < individual_proofs_size(&proof));
+
+        let mut expected = proof.statements.clone();
+        expected[2].b += 1;
+        assert_eq!(aggregate_verify_statements(&proof, &params, &expected), Err(VerifyError::AggregateStatementMismatch { index: 2, field: "range" }));
+        assert_eq!(aggregate_verify_statements(&proof, &params, &expected[..2]), Err(VerifyError::AggregateStatementMismatch { index: 2, field: "count" }));
+
+        let mut bad = proof.clone();
+        bad.statements[1].commitment += 1;
+        assert_eq!(aggregate_verify(&bad, &params), Err(VerifyError::AggregateStatementMismatch { index: 1, field: "commitment" }));
+        let mut bad = proof.clone();
+        bad.T1 += 1;
+        assert_eq!(aggregate_verify(&bad, &params), Err(VerifyError::InvalidProof));
+        let mut bad = proof.clone();
+        bad.commitments[3].C_v1 = bad.commitments[3].C.clone();
+        assert_eq!(aggregate_verify(&bad, &params), Err(VerifyError::InvalidProof));
+        let mut bad = proof.clone();
+        bad.ipp_proof.L.pop();
+        bad.ipp_proof.R.pop();
+        assert_eq!(aggregate_verify(&bad, &params), Err(VerifyError::InvalidProof));
+    }
+
+    // Purpose: ensure the prover names the entry it cannot prove
+    // Params: no entries; four entries with entry 2 outside its range
+    // Output: InvalidDimension(0), then Aggregate { index: 2, ValueOutOfRange }
+    // Usage: `cargo test -- src::aggregate` or `cargo test`
+    #[test]
+    fn unprovable_entries_are_named() {
+        let params = insecure_test_setup(512);
+        assert_eq!(aggregate_prove(&[], &params).err(), Some(ProveError::InvalidDimension(0)));
+        let mut entries = entries();
+        entries[2].v = BigInt::from(11);
+        assert_eq!(aggregate_prove(&entries, &params).err(), Some(ProveError::Aggregate { index: 2, error: Box::new(ProveError::ValueOutOfRange) }));
+    }
+}
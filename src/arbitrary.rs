@@ -0,0 +1,271 @@
+//! Structure-aware generation of proofs, statements and params from unstructured bytes, for
+//! property tests and fuzz targets (crate feature `arbitrary`)
+//!
+//! Params, IPPProof, Statement and Cuproof implement `arbitrary::Arbitrary`, so a fuzz target takes
+//! them as its input (`fuzz_target!(|input: (Params, Cuproof)| ...)`) and any other caller builds
+//! them with `Cuproof::arbitrary(&mut Unstructured::new(data))`. Inputs running out of bytes are
+//! read as zeros, so every input yields a value.
+//!
+//! Generated values are structurally valid, not valid proofs: group elements lie in [1, n) for the
+//! embedded 512-bit TEST_MODULUS (the modulus of `insecure_test_setup(512)`), IPP proofs have
+//! equal non-empty L and R, and the result passes every load_proof check. any_valid_proof runs the
+//! prover instead, for properties that need proofs that verify. for_each_case drives either from
+//! seeded random bytes
+
+use crate::group::Group;
+use crate::range_proof::{prove_with_rng, random_blinding, Cuproof, IPPProof, Statement};
+use crate::setup::{Params, DEFAULT_GENERATOR_SEED};
+use crate::util::{hex_to_bigint, MAX_IPP_ROUNDS};
+use ::arbitrary::{Arbitrary, Error, Result, Unstructured};
+use num_bigint::{BigInt, Sign};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::sync::OnceLock;
+
+/// Modulus of `insecure_test_setup(512)`; its factorization is public, so values generated under
+/// it are for tests only
+pub const TEST_MODULUS: &str = "ca6fda61436b6d5476ba071eea23db80312c1ea4c6b51e8adf16d21bbf0e491c6c5dc6e6a021f2463dfa98b42654c50775b7dd15845e434c0a462c092c63d8a3";
+
+/// Declared size of TEST_MODULUS in bits
+pub const TEST_MODULUS_BITS: usize = 512;
+
+/// Largest inner-product round count generated; prove makes 6
+const MAX_GENERATED_ROUNDS: u64 = 8;
+
+/// Longest statement label generated, in bytes
+const MAX_LABEL_BYTES: usize = 32;
+
+/// A BigInt in [0, bound); zero for bound <= 1. Input running out is read as zeros
+fn bigint_below(u: &mut Unstructured<'_>, bound: &BigInt) -> Result<BigInt> {
+    if bound <= &BigInt::from(1) { return Ok(BigInt::from(0)); }
+    // 8 bytes beyond the size of bound keep the reduction bias below 2^-64
+    let mut bytes = vec![0u8; bound.bits().div_ceil(8) as usize + 8];
+    u.fill_buffer(&mut bytes)?;
+    Ok(BigInt::from_bytes_be(Sign::Plus, &bytes) % bound)
+}
+
+/// A BigInt in [1, bound); one for bound <= 2
+fn nonzero_below(u: &mut Unstructured<'_>, bound: &BigInt) -> Result<BigInt> {
+    Ok(bigint_below(u, &(bound - 1))? + 1)
+}
+
+/// TEST_MODULUS as a BigInt
+pub fn test_modulus() -> &'static BigInt {
+    static N: OnceLock<BigInt> = OnceLock::new();
+    N.get_or_init(|| hex_to_bigint(TEST_MODULUS).expect("TEST_MODULUS is hex"))
+}
+
+/// The params of `insecure_test_setup(512)`, built from TEST_MODULUS without a prime search
+pub fn test_params() -> Params {
+    static PARAMS: OnceLock<Params> = OnceLock::new();
+    PARAMS.get_or_init(|| Params::derive(test_modulus().clone(), TEST_MODULUS_BITS, DEFAULT_GENERATOR_SEED).mark_insecure()).clone()
+}
+
+/// A non-negative scalar of at most `2 * TEST_MODULUS_BITS` bits, the size of honest responses
+fn scalar(u: &mut Unstructured<'_>) -> Result<BigInt> {
+    bigint_below(u, &(BigInt::from(1) << (2 * TEST_MODULUS_BITS)))
+}
+
+/// Params over TEST_MODULUS with generators derived from an arbitrary seed, at epoch 0 to 3
+/// All of them pass validate_params and are marked insecure
+impl<'a> Arbitrary<'a> for Params {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Params> {
+        if bool::arbitrary(u)? { return Ok(test_params()); }
+        let mut seed = [0u8; 8];
+        u.fill_buffer(&mut seed)?;
+        let epoch = u.int_in_range(0..=3)?;
+        let seed = format!("arbitrary/{}", hex::encode(seed));
+        Ok(Params::derive(test_modulus().clone(), TEST_MODULUS_BITS, &seed).with_epoch(epoch).mark_insecure())
+    }
+}
+
+/// 1 to 8 rounds of elements in [1, n), and scalars a, b
+impl<'a> Arbitrary<'a> for IPPProof {
+    #[allow(non_snake_case)]
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<IPPProof> {
+        let n = test_modulus();
+        let rounds = u.int_in_range(1..=MAX_GENERATED_ROUNDS.min(MAX_IPP_ROUNDS as u64))? as usize;
+        let L = (0..rounds).map(|_| nonzero_below(u, n)).collect::<Result<_>>()?;
+        let R = (0..rounds).map(|_| nonzero_below(u, n)).collect::<Result<_>>()?;
+        Ok(IPPProof { L, R, a: scalar(u)?, b: scalar(u)? })
+    }
+}
+
+/// A statement over a non-empty range below 2^64 with a power-of-two bit width; the fingerprint is
+/// that of test_params or arbitrary bytes
+impl<'a> Arbitrary<'a> for Statement {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Statement> {
+        let commitment = nonzero_below(u, test_modulus())?;
+        let a = u.int_in_range(0..=u64::MAX - 1)?;
+        let b = u.int_in_range(a + 1..=u64::MAX)?;
+        let bits = 1usize << u.int_in_range(1..=MAX_GENERATED_ROUNDS)?;
+        let label_len = u.int_in_range(0..=MAX_LABEL_BYTES as u64)? as usize;
+        let mut label = vec![0u8; label_len];
+        u.fill_buffer(&mut label)?;
+        let mut params_fingerprint = test_params().fingerprint();
+        if bool::arbitrary(u)? { u.fill_buffer(&mut params_fingerprint)?; }
+        Ok(Statement {
+            commitment,
+            a: BigInt::from(a),
+            b: BigInt::from(b),
+            bits,
+            label: String::from_utf8_lossy(&label).into_owned(),
+            params_fingerprint,
+        })
+    }
+}
+
+/// Group elements in [1, n) and scalars as above; when a statement is attached it names C and the
+/// IPP round count, so the cross-checks of verify pass and the transcript checks run
+impl<'a> Arbitrary<'a> for Cuproof {
+    #[allow(non_snake_case)]
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Cuproof> {
+        let n = test_modulus();
+        let [A, S, T1, T2, C, C_v1, C_v2] = [(); 7].map(|_| nonzero_below(u, n));
+        let (A, S, T1, T2, C, C_v1, C_v2) = (A?, S?, T1?, T2?, C?, C_v1?, C_v2?);
+        let [tau_x, mu, t_hat, t0, t1, t2, tau1, tau2] = [(); 8].map(|_| scalar(u));
+        let (tau_x, mu, t_hat, t0, t1, t2, tau1, tau2) = (tau_x?, mu?, t_hat?, t0?, t1?, t2?, tau1?, tau2?);
+        let ipp_proof = IPPProof::arbitrary(u)?;
+        let epoch = u.int_in_range(0..=3)?;
+        let statement = if bool::arbitrary(u)? {
+            let statement = Statement::arbitrary(u)?;
+            Some(Statement { commitment: C.clone(), bits: 1 << ipp_proof.L.len(), ..statement })
+        } else {
+            None
+        };
+        Ok(Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof, epoch, statement, assist: None })
+    }
+}
+
+/// An honest proof together with the witness it was made from
+#[derive(Clone)]
+pub struct ValidProof {
+    pub proof: Cuproof,
+    pub v: BigInt,
+    pub a: BigInt,
+    pub b: BigInt,
+}
+
+/// Strategy for proofs that verify under `params`: the prover runs on a range [a, b] below 2^32
+/// and a value in it, all drawn from the input, with randomness seeded from it
+/// - returns: a generator for for_each_case or a fuzz target, e.g.
+///   `let proofs = any_valid_proof(&params); let valid = proofs(&mut u)?;`
+/// - usage: properties of verifiers and encoders that need real proofs. Each value costs a full
+///   prove (dimension 64), so keep case counts small
+pub fn any_valid_proof<G: Group>(params: &G) -> impl Fn(&mut Unstructured<'_>) -> Result<ValidProof> + '_ {
+    move |u| {
+        let a = u.int_in_range(0..=u64::from(u32::MAX) - 1)?;
+        let b = u.int_in_range(a + 1..=u64::from(u32::MAX))?;
+        let v = u.int_in_range(a..=b)?;
+        let mut seed = [0u8; 32];
+        u.fill_buffer(&mut seed)?;
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let (v, a, b) = (BigInt::from(v), BigInt::from(a), BigInt::from(b));
+        let r = random_blinding(&mut rng);
+        let proof = prove_with_rng(&v, &r, &a, &b, params, 64, &mut rng).map_err(|_| Error::IncorrectFormat)?;
+        Ok(ValidProof { proof, v, a, b })
+    }
+}
+
+/// Bytes fed to each for_each_case case; enough for a proof with statement at MAX_GENERATED_ROUNDS
+const CASE_BYTES: usize = 4096;
+
+/// Run `property` on `cases` inputs of seeded random bytes
+/// - params: cases, seed (a failing case reproduces with the same seed), property
+/// - usage: unit tests of the generators; a case index is passed so failures can name it
+pub fn for_each_case(cases: usize, seed: u64, mut property: impl FnMut(usize, &mut Unstructured<'_>)) {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let mut data = vec![0u8; CASE_BYTES];
+    for case in 0..cases {
+        rng.fill_bytes(&mut data);
+        property(case, &mut Unstructured::new(&data));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::{insecure_test_setup, validate_params};
+    use crate::util::{decode_proof_container, encode_proof, check_header_nonzero, check_ipp_lengths};
+    use crate::verify::{verify, cuproof_verify_checked, cuproof_verify_with_range};
+
+    // Purpose: ensure the embedded modulus is that of insecure_test_setup(512)
+    // Params: none
+    // Output: test_params equals insecure_test_setup(512) and passes validate_params
+    // Usage: `cargo test --features arbitrary -- src::arbitrary`
+    #[test]
+    fn test_params_match_the_insecure_setup() {
+        let params = test_params();
+        assert_eq!(params, insecure_test_setup(512));
+        assert!(params.is_insecure());
+        assert!(validate_params(&params).is_ok());
+    }
+
+    // Purpose: property: every generated proof, statement and params is structurally valid
+    // Params: 256 cases of seeded random bytes, plus empty and all-ones input
+    // Output: values stay in their ranges, proofs survive encode / load and are rejected by the verifier
+    // Usage: `cargo test --features arbitrary -- src::arbitrary`
+    #[test]
+    fn generated_values_are_structurally_valid() {
+        let n = test_modulus();
+        let check = |case: usize, u: &mut Unstructured<'_>| {
+            let params: Params = u.arbitrary().unwrap();
+            assert!(validate_params(&params).is_ok(), "case {}", case);
+            assert_eq!(params.n(), n);
+
+            let proof: Cuproof = u.arbitrary().unwrap();
+            for x in [&proof.A, &proof.S, &proof.T1, &proof.T2, &proof.C, &proof.C_v1, &proof.C_v2] {
+                assert!(x > &BigInt::from(0) && x < n, "case {}", case);
+                assert!(check_header_nonzero(x).is_ok());
+            }
+            assert!(check_ipp_lengths(proof.ipp_proof.L.len(), proof.ipp_proof.R.len()).is_ok(), "case {}", case);
+            if let Some(statement) = &proof.statement {
+                assert!(statement.a < statement.b, "case {}", case);
+                assert_eq!(statement.commitment, proof.C);
+            }
+            let bytes = encode_proof(&proof).unwrap();
+            let loaded = decode_proof_container(bytes.clone()).unwrap();
+            assert_eq!(encode_proof(&loaded).unwrap(), bytes, "case {}", case);
+            assert!(!verify(&proof, &params), "case {}", case);
+            assert!(cuproof_verify_checked(&loaded, &test_params()).is_err(), "case {}", case);
+        };
+        for_each_case(256, 142, check);
+        check(256, &mut Unstructured::new(&[]));
+        check(257, &mut Unstructured::new(&[0xff; CASE_BYTES]));
+    }
+
+    // Purpose: ensure generation is a function of the input bytes
+    // Params: one buffer of seeded random bytes read twice
+    // Output: identical encodings
+    // Usage: `cargo test --features arbitrary -- src::arbitrary`
+    #[test]
+    fn generation_is_deterministic() {
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let mut data = vec![0u8; CASE_BYTES];
+        for _ in 0..8 {
+            rng.fill_bytes(&mut data);
+            let (mut u, mut again) = (Unstructured::new(&data), Unstructured::new(&data));
+            let (first, second): (Cuproof, Cuproof) = (u.arbitrary().unwrap(), again.arbitrary().unwrap());
+            assert_eq!(encode_proof(&first).unwrap(), encode_proof(&second).unwrap());
+            assert_eq!(u.len(), again.len());
+        }
+    }
+
+    // Purpose: property: any_valid_proof yields proofs that verify for their range and no other
+    // Params: 3 cases under test_params
+    // Output: cuproof_verify_with_range accepts [a, b] and rejects [a, b + 1]; the statement names the range
+    // Usage: `cargo test --features arbitrary -- src::arbitrary`
+    #[test]
+    fn any_valid_proof_verifies() {
+        let params = test_params();
+        let proofs = any_valid_proof(&params);
+        for_each_case(3, 142, |case, u| {
+            let ValidProof { proof, v, a, b } = proofs(u).unwrap();
+            assert!(a <= v && v <= b, "case {}", case);
+            assert!(cuproof_verify_with_range(&proof, &params, &a, &b), "case {}", case);
+            assert!(!cuproof_verify_with_range(&proof, &params, &a, &(&b + 1)), "case {}", case);
+            let statement = proof.statement.as_ref().unwrap();
+            assert_eq!((&statement.a, &statement.b, statement.bits), (&a, &b, 64));
+        });
+    }
+}
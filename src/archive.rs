@@ -0,0 +1,324 @@
+//! Proof archives: many proofs in one memory-mapped file, parsed one at a time on access
+//!
+//! ```text
+//! magic      "CUPRARCH"
+//! version    u16        ARCHIVE_FORMAT_VERSION
+//! count      u32        number of proofs
+//! index      count * (offset u64, length u32), offsets from the start of the file
+//! checksum   SHA-256 over everything above
+//! entries    one save_proof container per proof, at the offsets of the index
+//! ```
+//!
+//! All integers are big-endian. open checks the header, the index checksum and that every entry
+//! lies inside the file, without reading the entries; each entry keeps the checksum of its
+//! container, so a corrupted entry fails `get` for that entry only. On Unix the file is mapped with
+//! mmap(2) and an entry is copied out of the mapping only when it is parsed; elsewhere it is read
+//! into memory once.
+
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io;
+use crate::range_proof::Cuproof;
+use crate::util::{read_proof, write_atomically, Encoder, ProofLoadError, ReadLimits, SaveOptions};
+
+/// Version field of archives written by ProofArchive::create
+pub const ARCHIVE_FORMAT_VERSION: u16 = 1;
+
+pub(crate) const ARCHIVE_MAGIC: &[u8; 8] = b"CUPRARCH";
+/// magic, u16 version, u32 count
+pub(crate) const ARCHIVE_HEADER_LEN: usize = 8 + 2 + 4;
+/// u64 offset, u32 length
+pub(crate) const INDEX_ENTRY_LEN: usize = 8 + 4;
+pub(crate) const INDEX_CHECKSUM_LEN: usize = 32;
+
+/// Why an archive could not be opened, written or read
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// reading, mapping or writing the file failed
+    Io(io::Error),
+    /// the file does not start with the archive magic
+    BadMagic,
+    /// the version field is not ARCHIVE_FORMAT_VERSION
+    UnsupportedVersion(u16),
+    /// the file ends inside the header or the index it declares
+    Truncated { needed: usize, available: usize },
+    /// the index checksum does not match
+    IndexChecksumMismatch,
+    /// an index entry points outside the file
+    EntryOutOfBounds { index: usize, offset: u64, len: u32 },
+    /// get was called with an index past the end
+    NoSuchEntry { index: usize, len: usize },
+    /// the entry at `index` is not a valid proof; other entries are unaffected
+    Proof { index: usize, error: ProofLoadError },
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "archive I/O failed: {}", e),
+            ArchiveError::BadMagic => write!(f, "not a cuproof proof archive"),
+            ArchiveError::UnsupportedVersion(v) => write!(f, "unsupported archive format version {}", v),
+            ArchiveError::Truncated { needed, available } => {
+                write!(f, "archive needs {} bytes for its header and index but has {}", needed, available)
+            }
+            ArchiveError::IndexChecksumMismatch => write!(f, "archive index checksum mismatch: the file is corrupted"),
+            ArchiveError::EntryOutOfBounds { index, offset, len } => {
+                write!(f, "archive entry {} ({} bytes at offset {}) lies outside the file", index, len, offset)
+            }
+            ArchiveError::NoSuchEntry { index, len } => write!(f, "archive entry {} out of range for {} entries", index, len),
+            ArchiveError::Proof { index, error } => write!(f, "archive entry {}: {}", index, error),
+        }
+    }
+}
+
+impl core::error::Error for ArchiveError {}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> ArchiveError {
+        ArchiveError::Io(e)
+    }
+}
+
+/// A read-only view of a whole file: mapped on Unix, read into memory elsewhere
+struct Mapping {
+    #[cfg(unix)]
+    ptr: *mut libc::c_void,
+    #[cfg(unix)]
+    len: usize,
+    #[cfg(not(unix))]
+    bytes: Vec<u8>,
+}
+
+// SAFETY: the mapping is private and read-only and is never handed out mutably
+#[cfg(unix)]
+unsafe impl Send for Mapping {}
+#[cfg(unix)]
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    #[cfg(unix)]
+    fn open(path: &str) -> io::Result<Mapping> {
+        use std::os::unix::io::AsRawFd;
+        let file = fs::File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "archive too large to map"))?;
+        // mmap refuses empty mappings; an empty archive fails the header check anyway
+        if len == 0 { return Ok(Mapping { ptr: std::ptr::null_mut(), len: 0 }); }
+        // SAFETY: a fresh private read-only mapping of `len` bytes of an open file; the descriptor
+        // may be closed once mmap returns
+        let ptr = unsafe { libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0) };
+        if ptr == libc::MAP_FAILED { return Err(io::Error::last_os_error()); }
+        Ok(Mapping { ptr, len })
+    }
+
+    #[cfg(not(unix))]
+    fn open(path: &str) -> io::Result<Mapping> {
+        Ok(Mapping { bytes: fs::read(path)? })
+    }
+
+    #[cfg(unix)]
+    fn bytes(&self) -> &[u8] {
+        if self.len == 0 { return &[]; }
+        // SAFETY: ptr maps `len` readable bytes until drop. Like every mmap-based reader, this
+        // assumes the file is not truncated or rewritten in place while open; create replaces
+        // archives by rename, which leaves existing mappings intact
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+
+    #[cfg(not(unix))]
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: ptr and len are exactly what mmap returned, and no slice outlives self
+            unsafe { libc::munmap(self.ptr, self.len) };
+        }
+    }
+}
+
+/// A proof archive opened for lazy reading
+/// - usage: `ProofArchive::open(path)?` at startup costs one mapping and an index check; `get(i)`
+///   parses proof i (with the checks of load_proof) only when it is asked for
+pub struct ProofArchive {
+    map: Mapping,
+    count: usize,
+}
+
+impl ProofArchive {
+    /// Write `proofs` as an archive at `path`, replacing it atomically (see util::write_atomically)
+    /// - returns: ArchiveError::Io on write failures
+    pub fn create(path: &str, proofs: &[Cuproof]) -> Result<(), ArchiveError> {
+        let count = u32::try_from(proofs.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many proofs for one archive"))?;
+        let mut encoder = Encoder::new();
+        let mut entries = Vec::new();
+        let mut spans = Vec::with_capacity(proofs.len());
+        let data_start = (ARCHIVE_HEADER_LEN + proofs.len() * INDEX_ENTRY_LEN + INDEX_CHECKSUM_LEN) as u64;
+        for proof in proofs {
+            let container = encoder.encode_proof(proof)?;
+            let len = u32::try_from(container.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "proof too large for an archive entry"))?;
+            spans.push((data_start + entries.len() as u64, len));
+            entries.extend_from_slice(container);
+        }
+        let mut head = Vec::with_capacity(data_start as usize);
+        head.extend_from_slice(ARCHIVE_MAGIC);
+        head.extend_from_slice(&ARCHIVE_FORMAT_VERSION.to_be_bytes());
+        head.extend_from_slice(&count.to_be_bytes());
+        for (offset, len) in spans {
+            head.extend_from_slice(&offset.to_be_bytes());
+            head.extend_from_slice(&len.to_be_bytes());
+        }
+        let checksum = Sha256::digest(&head);
+        head.extend_from_slice(&checksum);
+        write_atomically(path, &SaveOptions::default(), |w| {
+            w.write_all(&head)?;
+            w.write_all(&entries)
+        })?;
+        Ok(())
+    }
+
+    /// Map the archive at `path` and check its header and index
+    /// - returns: the archive, or the ArchiveError of the first problem in the header or index;
+    ///   entries are not read
+    pub fn open(path: &str) -> Result<ProofArchive, ArchiveError> {
+        let map = Mapping::open(path)?;
+        let bytes = map.bytes();
+        if !bytes.starts_with(ARCHIVE_MAGIC) { return Err(ArchiveError::BadMagic); }
+        if bytes.len() < ARCHIVE_HEADER_LEN { return Err(ArchiveError::Truncated { needed: ARCHIVE_HEADER_LEN, available: bytes.len() }); }
+        let version = u16::from_be_bytes([bytes[8], bytes[9]]);
+        if version != ARCHIVE_FORMAT_VERSION { return Err(ArchiveError::UnsupportedVersion(version)); }
+        let count = u32::from_be_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) as usize;
+        let index_end = ARCHIVE_HEADER_LEN + count * INDEX_ENTRY_LEN;
+        let needed = index_end + INDEX_CHECKSUM_LEN;
+        if bytes.len() < needed { return Err(ArchiveError::Truncated { needed, available: bytes.len() }); }
+        if Sha256::digest(&bytes[..index_end])[..] != bytes[index_end..needed] { return Err(ArchiveError::IndexChecksumMismatch); }
+        let archive = ProofArchive { map, count };
+        for index in 0..count {
+            let (offset, len) = archive.span(index);
+            if offset.checked_add(len as u64).is_none_or(|end| end > archive.map.bytes().len() as u64) {
+                return Err(ArchiveError::EntryOutOfBounds { index, offset, len });
+            }
+        }
+        Ok(archive)
+    }
+
+    /// Number of proofs in the archive
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Parse proof `index`
+    /// - returns: the Cuproof, NoSuchEntry past the end, or Proof { index, .. } when that entry is corrupt
+    pub fn get(&self, index: usize) -> Result<Cuproof, ArchiveError> {
+        if index >= self.count { return Err(ArchiveError::NoSuchEntry { index, len: self.count }); }
+        let (offset, len) = self.span(index);
+        let mut entry = &self.map.bytes()[offset as usize..offset as usize + len as usize];
+        read_proof(&mut entry, &ReadLimits::default()).map_err(|error| ArchiveError::Proof { index, error })
+    }
+
+    /// get for every entry in order, each parsed as the iterator reaches it
+    pub fn iter(&self) -> impl Iterator<Item = Result<Cuproof, ArchiveError>> + '_ {
+        (0..self.count).map(move |index| self.get(index))
+    }
+
+    /// (offset, length) of entry `index` from the index; open checked index < count
+    fn span(&self, index: usize) -> (u64, u32) {
+        let at = ARCHIVE_HEADER_LEN + index * INDEX_ENTRY_LEN;
+        let field = &self.map.bytes()[at..at + INDEX_ENTRY_LEN];
+        let offset = u64::from_be_bytes(field[..8].try_into().expect("8-byte slice"));
+        let len = u32::from_be_bytes(field[8..].try_into().expect("4-byte slice"));
+        (offset, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_proof::prove;
+    use crate::setup::insecure_test_setup;
+    use crate::util::random_bigint;
+    use crate::verify::verify;
+    use num_bigint::BigInt;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("cuproof_archive_{}_{}.bin", name, std::process::id())).to_str().unwrap().to_string()
+    }
+
+    // Purpose: ensure archives round-trip, parse entries on demand and isolate a corrupted entry
+    // Params: 1000 labelled proofs over insecure 512-bit params (8 distinct proofs, relabelled per entry)
+    // Output: random entries verify and carry their labels; after flipping a byte inside one entry only
+    //         that entry fails, and the iterator yields exactly one error
+    // Usage: `cargo test --features mmap -- src::archive`
+    #[test]
+    fn archive_of_1000_proofs_is_read_lazily() {
+        let params = insecure_test_setup(512);
+        let base: Vec<Cuproof> = (0..8)
+            .map(|i| prove(&BigInt::from(10 + i), &random_bigint(128), &BigInt::from(0), &BigInt::from(100), &params).unwrap())
+            .collect();
+        let proofs: Vec<Cuproof> = (0..1000).map(|i| base[i % base.len()].clone().with_label(&format!("entry/{}", i))).collect();
+        let path = temp_path("lazy");
+        ProofArchive::create(&path, &proofs).unwrap();
+
+        let archive = ProofArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 1000);
+        for i in [0, 1, 417, 998, 999] {
+            let proof = archive.get(i).unwrap();
+            assert_eq!(proof.statement.as_ref().unwrap().label, format!("entry/{}", i));
+            assert!(verify(&proof, &params));
+        }
+        assert!(matches!(archive.get(1000), Err(ArchiveError::NoSuchEntry { index: 1000, len: 1000 })));
+        drop(archive);
+
+        // corrupt one byte in the middle of entry 500
+        let mut bytes = fs::read(&path).unwrap();
+        let at = ARCHIVE_HEADER_LEN + 500 * INDEX_ENTRY_LEN;
+        let offset = u64::from_be_bytes(bytes[at..at + 8].try_into().unwrap()) as usize;
+        bytes[offset + 40] ^= 0x01;
+        fs::write(&path, &bytes).unwrap();
+        let archive = ProofArchive::open(&path).unwrap();
+        assert!(matches!(archive.get(500), Err(ArchiveError::Proof { index: 500, .. })));
+        assert!(verify(&archive.get(499).unwrap(), &params));
+        assert!(verify(&archive.get(501).unwrap(), &params));
+        let failures: Vec<usize> = archive.iter().enumerate().filter(|(_, r)| r.is_err()).map(|(i, _)| i).collect();
+        assert_eq!(failures, vec![500]);
+        let _ = fs::remove_file(&path);
+    }
+
+    // Purpose: ensure open rejects damaged headers and indexes before any entry is parsed
+    // Params: a 3-proof archive, edited in its magic, version, index and length
+    // Output: BadMagic, UnsupportedVersion, IndexChecksumMismatch, Truncated; an empty archive opens
+    // Usage: `cargo test --features mmap -- src::archive`
+    #[test]
+    fn damaged_index_is_rejected_on_open() {
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(5), &random_bigint(128), &BigInt::from(0), &BigInt::from(9), &params).unwrap();
+        let path = temp_path("damaged");
+        ProofArchive::create(&path, &[proof.clone(), proof.clone(), proof]).unwrap();
+        let good = fs::read(&path).unwrap();
+        let open_edited = |edit: &dyn Fn(&mut Vec<u8>)| {
+            let mut bytes = good.clone();
+            edit(&mut bytes);
+            fs::write(&path, &bytes).unwrap();
+            ProofArchive::open(&path).err().unwrap()
+        };
+        assert!(matches!(open_edited(&|b| b[0] = b'X'), ArchiveError::BadMagic));
+        assert!(matches!(open_edited(&|b| b[9] = 2), ArchiveError::UnsupportedVersion(2)));
+        assert!(matches!(open_edited(&|b| b[ARCHIVE_HEADER_LEN + 3] ^= 1), ArchiveError::IndexChecksumMismatch));
+        assert!(matches!(open_edited(&|b| b.truncate(30)), ArchiveError::Truncated { .. }));
+        assert!(matches!(open_edited(&|b| b[10] = 0x7f), ArchiveError::Truncated { .. }));
+        assert!(matches!(open_edited(&|b| b.clear()), ArchiveError::BadMagic));
+
+        ProofArchive::create(&path, &[]).unwrap();
+        let empty = ProofArchive::open(&path).unwrap();
+        assert!(empty.is_empty() && empty.iter().next().is_none());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,300 @@
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use crate::range_proof::Cuproof;
+use crate::setup::Params;
+use crate::util::{decode_proof_container, encode_proof, DEFAULT_MAX_READ_BYTES, FILE_FORMAT_VERSION};
+
+const BEGIN_LINE: &str = "-----BEGIN CUPROOF-----";
+const END_LINE: &str = "-----END CUPROOF-----";
+
+/// Width of the base64 body lines written by to_armored
+pub const ARMOR_LINE_WIDTH: usize = 64;
+
+/// Longest text from_armored reads: base64 and line breaks grow a DEFAULT_MAX_READ_BYTES body by
+/// well under half
+pub const MAX_ARMORED_BYTES: usize = 2 * DEFAULT_MAX_READ_BYTES;
+
+/// Why from_armored rejected a text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArmorError {
+    /// no `-----BEGIN CUPROOF-----` line where the text starts
+    MissingBegin,
+    /// no `-----END CUPROOF-----` line where the text ends
+    MissingEnd,
+    /// a header line that is malformed, repeated or unknown
+    BadHeader(String),
+    /// the Version header is not FILE_FORMAT_VERSION
+    UnsupportedVersion(String),
+    /// the body is not base64
+    BadBase64,
+    /// no `=` checksum line after the body
+    MissingChecksum,
+    /// the checksum line does not match the body
+    ChecksumMismatch,
+    /// the Params-Fingerprint header names other params than the ones given
+    ParamsMismatch { expected: String, found: String },
+    /// the body decodes but is not a valid proof container
+    InvalidProof(String),
+    /// the text is longer than MAX_ARMORED_BYTES
+    TooLarge,
+}
+
+impl fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArmorError::MissingBegin => write!(f, "missing {} line", BEGIN_LINE),
+            ArmorError::MissingEnd => write!(f, "missing {} line", END_LINE),
+            ArmorError::BadHeader(line) => write!(f, "bad armor header {:?}", line),
+            ArmorError::UnsupportedVersion(v) => write!(f, "unsupported armor version {}", v),
+            ArmorError::BadBase64 => write!(f, "armor body is not base64"),
+            ArmorError::MissingChecksum => write!(f, "missing armor checksum line"),
+            ArmorError::ChecksumMismatch => write!(f, "armor checksum does not match the body"),
+            ArmorError::ParamsMismatch { expected, found } => {
+                write!(f, "proof was made for params {}, not {}", found, expected)
+            }
+            ArmorError::InvalidProof(msg) => write!(f, "invalid armored proof: {}", msg),
+            ArmorError::TooLarge => write!(f, "armored text exceeds {} bytes", MAX_ARMORED_BYTES),
+        }
+    }
+}
+
+impl core::error::Error for ArmorError {}
+
+/// `=` followed by the first 3 bytes of SHA-256(body) in unpadded base64
+fn checksum_line(body: &[u8]) -> String {
+    format!("={}", STANDARD_NO_PAD.encode(&Sha256::digest(body)[..3]))
+}
+
+/// ASCII armor of proofs, for pasting into chats, emails and tickets
+///
+/// ```text
+/// -----BEGIN CUPROOF-----
+/// Version: 1
+/// Params-Fingerprint: <64 hex digits, only written by to_armored_with_params>
+///
+/// <base64 of the save_proof container, 64 columns>
+/// =<base64 of the first 3 bytes of SHA-256 of the container>
+/// -----END CUPROOF-----
+/// ```
+///
+/// Parsing ignores whitespace around the block and around each line, accepts CRLF line endings
+/// and body lines of any width, so text reflowed by a mail client still reads back
+impl Cuproof {
+    pub fn to_armored(&self) -> String {
+        self.armor(None)
+    }
+
+    /// to_armored plus a Params-Fingerprint header naming the params the proof was made over
+    pub fn to_armored_with_params(&self, params: &Params) -> String {
+        self.armor(Some(params))
+    }
+
+    fn armor(&self, params: Option<&Params>) -> String {
+        let body = encode_proof(self).expect("proof containers stay far below 4 GiB");
+        let mut out = format!("{}\nVersion: {}\n", BEGIN_LINE, FILE_FORMAT_VERSION);
+        if let Some(params) = params {
+            out.push_str(&format!("Params-Fingerprint: {}\n", params.fingerprint_hex()));
+        }
+        out.push('\n');
+        let encoded = STANDARD.encode(&body);
+        for line in encoded.as_bytes().chunks(ARMOR_LINE_WIDTH) {
+            out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+            out.push('\n');
+        }
+        out.push_str(&checksum_line(&body));
+        out.push('\n');
+        out.push_str(END_LINE);
+        out.push('\n');
+        out
+    }
+
+    /// Decode a proof written by to_armored or to_armored_with_params, running the checks of load_proof
+    /// - returns: Cuproof or the ArmorError of the first problem found; a Params-Fingerprint header is not checked
+    pub fn from_armored(text: &str) -> Result<Cuproof, ArmorError> {
+        Armored::parse(text)?.into_proof()
+    }
+
+    /// from_armored, also rejecting text whose Params-Fingerprint header names other params
+    /// - usage: the CLI verifier, so a proof pasted against the wrong params fails with a clear message
+    pub fn from_armored_for(text: &str, params: &Params) -> Result<Cuproof, ArmorError> {
+        let armored = Armored::parse(text)?;
+        if let Some(found) = &armored.fingerprint {
+            let expected = params.fingerprint_hex().to_string();
+            if !found.eq_ignore_ascii_case(&expected) {
+                return Err(ArmorError::ParamsMismatch { expected, found: found.clone() });
+            }
+        }
+        armored.into_proof()
+    }
+}
+
+impl fmt::Display for Cuproof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_armored())
+    }
+}
+
+impl FromStr for Cuproof {
+    type Err = ArmorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Cuproof::from_armored(s)
+    }
+}
+
+/// The parts of an armored block
+struct Armored {
+    fingerprint: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Armored {
+    fn parse(text: &str) -> Result<Armored, ArmorError> {
+        if text.len() > MAX_ARMORED_BYTES { return Err(ArmorError::TooLarge); }
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next() != Some(BEGIN_LINE) { return Err(ArmorError::MissingBegin); }
+        let mut lines: Vec<&str> = lines.collect();
+        if lines.pop() != Some(END_LINE) { return Err(ArmorError::MissingEnd); }
+
+        // base64 has no ':', so headers are the leading lines that contain one
+        let mut version = None;
+        let mut fingerprint = None;
+        let header_count = lines.iter().take_while(|l| l.contains(':')).count();
+        for line in &lines[..header_count] {
+            let bad = || ArmorError::BadHeader(line.to_string());
+            let (key, value) = line.split_once(':').ok_or_else(bad)?;
+            let slot = match key.trim() {
+                "Version" => &mut version,
+                "Params-Fingerprint" => &mut fingerprint,
+                _ => return Err(bad()),
+            };
+            if slot.replace(value.trim().to_string()).is_some() { return Err(bad()); }
+        }
+        let version = version.ok_or_else(|| ArmorError::BadHeader("missing Version".to_string()))?;
+        if version != FILE_FORMAT_VERSION.to_string() { return Err(ArmorError::UnsupportedVersion(version)); }
+        if let Some(fp) = &fingerprint
+            && !(fp.len() == 64 && fp.bytes().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(ArmorError::BadHeader(format!("Params-Fingerprint: {}", fp)));
+        }
+
+        let rest = &lines[header_count..];
+        let (checksum, body_lines) = match rest.split_last() {
+            Some((last, body)) if last.starts_with('=') && !body.is_empty() => (*last, body),
+            _ => return Err(ArmorError::MissingChecksum),
+        };
+        let encoded: String = body_lines.iter().flat_map(|l| l.chars()).filter(|c| !c.is_whitespace()).collect();
+        let body = STANDARD.decode(encoded.as_bytes()).map_err(|_| ArmorError::BadBase64)?;
+        if checksum != checksum_line(&body) { return Err(ArmorError::ChecksumMismatch); }
+        Ok(Armored { fingerprint, body })
+    }
+
+    fn into_proof(self) -> Result<Cuproof, ArmorError> {
+        decode_proof_container(self.body).map_err(|e| ArmorError::InvalidProof(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+    use crate::range_proof::{prove, IPPProof};
+    use crate::setup::insecure_test_setup;
+    use crate::verify::verify;
+
+    // A small hand-written proof whose armor is checked in as testdata/proof_v1_crlf.asc
+    fn golden_proof() -> Cuproof {
+        let int = |x: i64| BigInt::from(x);
+        Cuproof {
+            A: int(1), S: int(2), T1: int(3), T2: int(4), tau_x: int(5), mu: int(6), t_hat: int(7),
+            C: int(8), C_v1: int(9), C_v2: int(10), t0: int(11), t1: int(12), t2: int(13), tau1: int(14), tau2: int(15),
+            ipp_proof: IPPProof { L: vec![int(16), int(17)], R: vec![int(18), int(19)], a: int(20), b: int(21) },
+            epoch: 2,
+            statement: None,
+            assist: None,
+        }
+    }
+
+    // Purpose: ensure armored proofs round-trip through to_armored / from_armored and Display / FromStr
+    // Params: insecure 512-bit params, an honest proof over them
+    // Output: the decoded proof verifies; body lines are 64 columns; reflowed text and load_proof
+    //         still read it; the fingerprint header is checked by from_armored_for
+    // Usage: `cargo test --features armor -- src::armor`
+    #[test]
+    fn armor_round_trips() {
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(30), &BigInt::from(42), &BigInt::from(10), &BigInt::from(100), &params).unwrap();
+        let text = proof.to_armored_with_params(&params);
+        assert!(text.starts_with("-----BEGIN CUPROOF-----\nVersion: 1\nParams-Fingerprint: "));
+        assert!(text.lines().all(|l| l.len() <= ARMOR_LINE_WIDTH || l.starts_with("Params-Fingerprint")));
+        assert!(verify(&Cuproof::from_armored_for(&text, &params).unwrap(), &params));
+
+        let shown = proof.to_string();
+        assert!(!shown.contains("Params-Fingerprint"));
+        let parsed: Cuproof = shown.parse().unwrap();
+        assert!(verify(&parsed, &params));
+
+        // Mail clients rewrap long lines and indent quoted text
+        let (head, rest) = text.split_once("\n\n").unwrap();
+        let (body, tail) = rest.split_once("\n=").unwrap();
+        let body = body.replace('\n', "");
+        let rewrapped: Vec<String> = body.as_bytes().chunks(40).map(|c| format!("  {}", std::str::from_utf8(c).unwrap())).collect();
+        let reflowed = format!("\n  {}\n{}\n={}\n\n", head.replace('\n', "\n  "), rewrapped.join("\n"), tail);
+        assert!(verify(&Cuproof::from_armored(&reflowed).unwrap(), &params));
+
+        let path = std::env::temp_dir().join(format!("cuproof_armor_{}.asc", std::process::id()));
+        std::fs::write(&path, &text).unwrap();
+        assert!(verify(&crate::util::load_proof(path.to_str().unwrap()).unwrap(), &params));
+        std::fs::remove_file(&path).unwrap();
+
+        let other = params.rotate_generators("armor");
+        assert!(matches!(Cuproof::from_armored_for(&text, &other), Err(ArmorError::ParamsMismatch { .. })));
+    }
+
+    // Purpose: ensure damaged armor is rejected before the proof is decoded
+    // Params: the armor of an honest proof with one body character or the checksum changed
+    // Output: ChecksumMismatch for both; MissingChecksum / MissingEnd / BadHeader for truncated or edited text
+    // Usage: `cargo test --features armor -- src::armor`
+    #[test]
+    fn armor_rejects_corruption() {
+        let text = golden_proof().to_armored();
+        let lines: Vec<&str> = text.lines().collect();
+        let checksum_at = lines.iter().position(|l| l.starts_with('=')).unwrap();
+
+        let mut bad_checksum = lines.clone();
+        let flipped = if lines[checksum_at].ends_with('A') { "B" } else { "A" };
+        let edited = format!("{}{}", &lines[checksum_at][..4], flipped);
+        bad_checksum[checksum_at] = &edited;
+        assert_eq!(Cuproof::from_armored(&bad_checksum.join("\n")).err(), Some(ArmorError::ChecksumMismatch));
+
+        let mut bad_body = lines.clone();
+        let body = lines[3].replacen('A', "B", 1).replacen('Q', "R", 1);
+        assert_ne!(body, lines[3]);
+        bad_body[3] = &body;
+        assert_eq!(Cuproof::from_armored(&bad_body.join("\n")).err(), Some(ArmorError::ChecksumMismatch));
+
+        let without_checksum: Vec<&str> = lines.iter().enumerate().filter(|&(i, _)| i != checksum_at).map(|(_, l)| *l).collect();
+        assert_eq!(Cuproof::from_armored(&without_checksum.join("\n")).err(), Some(ArmorError::MissingChecksum));
+        assert_eq!(Cuproof::from_armored(&lines[..lines.len() - 1].join("\n")).err(), Some(ArmorError::MissingEnd));
+        assert_eq!(Cuproof::from_armored(&text.replace("Version: 1", "Version: 2")).err(), Some(ArmorError::UnsupportedVersion("2".into())));
+        assert!(matches!(Cuproof::from_armored(&text.replace("Version", "Comment")), Err(ArmorError::BadHeader(_))));
+        assert_eq!("not armor".parse::<Cuproof>().err(), Some(ArmorError::MissingBegin));
+    }
+
+    // Purpose: ensure the armor stays stable and text saved with Windows line endings still parses
+    // Params: testdata/proof_v1_crlf.asc, the golden proof armored over insecure 512-bit params, with CRLF
+    // Output: the fixture decodes to the golden proof under its params; its LF form is today's armor
+    // Usage: `cargo test --features armor -- src::armor`
+    #[test]
+    fn armor_fixture_with_crlf_parses() {
+        let fixture = include_str!("../testdata/proof_v1_crlf.asc");
+        assert!(fixture.contains("\r\n"));
+        let params = insecure_test_setup(512);
+        let proof = Cuproof::from_armored_for(fixture, &params).unwrap();
+        assert_eq!((proof.mu.clone(), proof.ipp_proof.R.clone()), (BigInt::from(6), vec![BigInt::from(18), BigInt::from(19)]));
+        assert_eq!(fixture.replace("\r\n", "\n"), golden_proof().to_armored_with_params(&params));
+    }
+}
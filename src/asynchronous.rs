@@ -0,0 +1,341 @@
+//! Proving and verification off an async executor's threads (feature `async`)
+//!
+//! A verify takes milliseconds and a prove tens of them, long enough to stall every other task of
+//! an executor thread that runs them inline. The functions here hand the work to an [`Executor`]
+//! that may block, and return a [`Task`] future for its result. The crate depends on no runtime:
+//! [`ThreadExecutor`] starts a thread per job, and a runtime's blocking pool takes a few lines:
+//!
+//! ```ignore
+//! struct Tokio(tokio::runtime::Handle);
+//!
+//! impl cuproof::asynchronous::Executor for Tokio {
+//!     fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+//!         drop(self.0.spawn_blocking(job));
+//!     }
+//! }
+//!
+//! let verifier = AsyncVerifier::new(verifier, Tokio(tokio::runtime::Handle::current()));
+//! verifier.verify(proof).await?;
+//! ```
+//!
+//! AsyncVerifier and AsyncProver hold an `Arc<Verifier>` or a RangeProver and clone cheaply, so
+//! one built at startup serves every request. A [`CancellationToken`] stops a verification
+//! between its phases (challenges, each commitment check, the inner-product and element checks);
+//! one already running finishes its current exponentiation first.
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+use num_bigint::BigInt;
+use crate::range_proof::{prove, Cuproof, ProveError, RangeProver, Statement};
+use crate::secret::Secret;
+use crate::setup::Params;
+use crate::verify::{cuproof_verify_checked, verify_interruptible, Verifier, VerifyError};
+
+/// Somewhere to run jobs that block: a runtime's blocking pool, or threads of the caller's own
+pub trait Executor: Send + Sync {
+    /// Run `job` to completion on a thread where blocking is allowed; a job dropped without
+    /// running panics the Task awaiting it
+    fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>);
+}
+
+/// A new thread for every job
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadExecutor;
+
+impl Executor for ThreadExecutor {
+    fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+        std::thread::spawn(job);
+    }
+}
+
+/// A flag shared by clones: cancel on one, and every verification given another stops at its
+/// next phase with VerifyError::Cancelled
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// The result of a job on an Executor, as a future
+///
+/// A panic in the job resumes in the task that polls it. Dropping the Task does not stop the
+/// job; cancel its CancellationToken for that.
+pub struct Task<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+struct Slot<T> {
+    result: Option<std::thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.lock().unwrap_or_else(PoisonError::into_inner);
+        match slot.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The job's side of a Task: fills the slot once, or with a panic if the job is dropped unrun
+struct Completion<T> {
+    slot: Option<Arc<Mutex<Slot<T>>>>,
+}
+
+impl<T> Completion<T> {
+    fn complete(mut self, result: std::thread::Result<T>) {
+        if let Some(slot) = self.slot.take() { fill(&slot, result); }
+    }
+}
+
+impl<T> Drop for Completion<T> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() { fill(&slot, Err(Box::new("the executor dropped the job without running it"))); }
+    }
+}
+
+fn fill<T>(slot: &Mutex<Slot<T>>, result: std::thread::Result<T>) {
+    let waker = {
+        let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        slot.result = Some(result);
+        slot.waker.take()
+    };
+    if let Some(waker) = waker { waker.wake(); }
+}
+
+/// Run `work` on `executor`
+/// - returns: the Task resolving to its output
+/// - usage: other blocking calls of the crate (setup, batch verification) from async code
+pub fn spawn<T: Send + 'static>(executor: &dyn Executor, work: impl FnOnce() -> T + Send + 'static) -> Task<T> {
+    let slot = Arc::new(Mutex::new(Slot { result: None, waker: None }));
+    let completion = Completion { slot: Some(slot.clone()) };
+    executor.spawn_blocking(Box::new(move || completion.complete(panic::catch_unwind(AssertUnwindSafe(work)))));
+    Task { slot }
+}
+
+/// cuproof_verify_checked on a thread of its own
+/// - params: proof, params shared with the caller's other tasks
+/// - returns: Task resolving to what cuproof_verify_checked returns
+/// - usage: `verify_async(proof, params.clone()).await?` in a request handler; AsyncVerifier for
+///   checked params, another executor or cancellation
+pub fn verify_async(proof: Cuproof, params: Arc<Params>) -> Task<Result<(), VerifyError>> {
+    spawn(&ThreadExecutor, move || cuproof_verify_checked(&proof, &*params))
+}
+
+/// prove on a thread of its own
+/// - params: v, r the opening, a and b the range, params; v and r are wiped when the job ends
+/// - returns: Task resolving to what prove returns
+pub fn prove_async(v: BigInt, r: BigInt, a: BigInt, b: BigInt, params: Arc<Params>) -> Task<Result<Cuproof, ProveError>> {
+    prove_on(&ThreadExecutor, v, r, a, b, params)
+}
+
+fn prove_on(executor: &dyn Executor, v: BigInt, r: BigInt, a: BigInt, b: BigInt, params: Arc<Params>) -> Task<Result<Cuproof, ProveError>> {
+    let (v, r) = (Secret::new(v), Secret::new(r));
+    spawn(executor, move || prove(v.expose(), r.expose(), &a, &b, &*params))
+}
+
+/// A Verifier and the Executor its verifications run on
+#[derive(Clone)]
+pub struct AsyncVerifier {
+    verifier: Arc<Verifier>,
+    executor: Arc<dyn Executor>,
+}
+
+impl AsyncVerifier {
+    /// - params: a Verifier or an `Arc<Verifier>` shared with synchronous callers, the executor
+    pub fn new(verifier: impl Into<Arc<Verifier>>, executor: impl Executor + 'static) -> AsyncVerifier {
+        AsyncVerifier { verifier: verifier.into(), executor: Arc::new(executor) }
+    }
+
+    pub fn verifier(&self) -> &Arc<Verifier> { &self.verifier }
+
+    /// See Verifier::verify_checked
+    pub fn verify(&self, proof: Cuproof) -> Task<Result<(), VerifyError>> {
+        let verifier = self.verifier.clone();
+        spawn(&*self.executor, move || verifier.verify_checked(&proof))
+    }
+
+    /// Verifier::verify_checked, stopping at the next phase once `cancel` is cancelled
+    /// - returns: Task resolving to Err(Cancelled) when cancelled before a verdict, otherwise as verify
+    /// - usage: a request handler cancels when its client disconnects or its deadline passes
+    pub fn verify_cancellable(&self, proof: Cuproof, cancel: &CancellationToken) -> Task<Result<(), VerifyError>> {
+        let (verifier, cancel) = (self.verifier.clone(), cancel.clone());
+        spawn(&*self.executor, move || verify_interruptible(&proof, verifier.params(), &|| cancel.is_cancelled()))
+    }
+
+    /// See Verifier::verify_statement
+    pub fn verify_statement(&self, proof: Cuproof, expected: Statement) -> Task<Result<(), VerifyError>> {
+        let verifier = self.verifier.clone();
+        spawn(&*self.executor, move || verifier.verify_statement(&proof, &expected))
+    }
+}
+
+/// A RangeProver and the Executor its proofs run on
+#[derive(Clone)]
+pub struct AsyncProver {
+    prover: RangeProver,
+    executor: Arc<dyn Executor>,
+}
+
+impl AsyncProver {
+    pub fn new(prover: RangeProver, executor: impl Executor + 'static) -> AsyncProver {
+        AsyncProver { prover, executor: Arc::new(executor) }
+    }
+
+    pub fn prover(&self) -> &RangeProver { &self.prover }
+
+    /// See RangeProver::prove; v and r are wiped when the job ends
+    pub fn prove(&self, v: BigInt, r: BigInt, a: BigInt, b: BigInt) -> Task<Result<Cuproof, ProveError>> {
+        prove_on(&*self.executor, v, r, a, b, self.prover.shared_params().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::task::Wake;
+    use std::thread::Thread;
+    use std::time::{Duration, Instant};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use crate::range_proof::{prove_with_rng, random_blinding};
+    use crate::setup::{insecure_seeded_setup, insecure_test_setup};
+    use crate::workers::Workers;
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Poll every future on this thread until all are ready, parking between wake-ups
+    fn join_all<F: Future>(futures: Vec<F>) -> Vec<F::Output> {
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut futures: Vec<_> = futures.into_iter().map(Box::pin).collect();
+        let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+        while outputs.iter().any(Option::is_none) {
+            for (future, output) in futures.iter_mut().zip(outputs.iter_mut()).filter(|(_, output)| output.is_none()) {
+                if let Poll::Ready(value) = future.as_mut().poll(&mut cx) { *output = Some(value); }
+            }
+            if outputs.iter().any(Option::is_none) { std::thread::park_timeout(Duration::from_millis(50)); }
+        }
+        outputs.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// A fixed set of threads taking jobs from one queue, as a runtime's blocking pool does
+    struct Pool(Mutex<mpsc::Sender<Box<dyn FnOnce() + Send>>>);
+
+    impl Pool {
+        fn new(threads: usize) -> Pool {
+            let (sender, receiver) = mpsc::channel::<Box<dyn FnOnce() + Send>>();
+            let receiver = Arc::new(Mutex::new(receiver));
+            for _ in 0..threads {
+                let receiver = receiver.clone();
+                std::thread::spawn(move || loop {
+                    let Ok(job) = receiver.lock().unwrap().recv() else { break };
+                    job();
+                });
+            }
+            Pool(Mutex::new(sender))
+        }
+    }
+
+    impl Executor for Pool {
+        fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+            self.0.lock().unwrap().send(job).unwrap();
+        }
+    }
+
+    fn sample(params: &Params, seed: u64) -> Cuproof {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let (v, a, b) = (BigInt::from(30 + seed), BigInt::from(10), BigInt::from(1000));
+        prove_with_rng(&v, &random_blinding(&mut rng), &a, &b, params, 64, &mut rng).unwrap()
+    }
+
+    // Purpose: ensure many verifications awaited at once on a shared pool each get their own verdict
+    // Params: insecure 512-bit params behind one Arc<Verifier>; 32 proofs, every fourth with t_hat
+    //         altered; a pool of Workers::available() threads
+    // Output: Ok(()) for the honest proofs and InvalidProof for the altered ones, in submission order
+    // Usage: `cargo test --features async -- asynchronous`
+    #[test]
+    fn concurrent_verifications_on_a_pool() {
+        let params = Arc::new(insecure_test_setup(512).allow_insecure_params());
+        let verifier = AsyncVerifier::new(Verifier::new_insecure(params.clone()).unwrap(), Pool::new(Workers::available().max(2)));
+        let proofs: Vec<Cuproof> = (0..32).map(|seed| {
+            let mut proof = sample(&params, seed);
+            if seed % 4 == 0 { proof.t_hat += 1; }
+            proof
+        }).collect();
+        let tasks: Vec<_> = proofs.into_iter().map(|proof| verifier.verify(proof)).collect();
+        for (seed, result) in join_all(tasks).into_iter().enumerate() {
+            let expected = if seed % 4 == 0 { Err(VerifyError::InvalidProof) } else { Ok(()) };
+            assert_eq!(result, expected, "proof {}", seed);
+        }
+        let proof = join_all(vec![prove_async(BigInt::from(5), BigInt::from(7), BigInt::from(0), BigInt::from(10), params.clone())]);
+        let proof = proof.into_iter().next().unwrap().unwrap();
+        assert_eq!(join_all(vec![verify_async(proof, params)]), vec![Ok(())]);
+    }
+
+    // Purpose: ensure a cancelled token stops verification before its exponentiations
+    // Params: 2048-bit seeded params; an honest proof; a token cancelled before and one never cancelled
+    // Output: Err(Cancelled) well within the time the uncancelled verification takes; Ok(()) for it
+    // Usage: `cargo test --features async -- asynchronous`
+    #[test]
+    fn cancelled_verification_returns_promptly() {
+        let params = Arc::new(insecure_seeded_setup(2048, b"cuproof async").unwrap().allow_insecure_params());
+        let verifier = AsyncVerifier::new(Verifier::new_insecure(params.clone()).unwrap(), ThreadExecutor);
+        let proof = sample(&params, 1);
+        let start = Instant::now();
+        assert_eq!(join_all(vec![verifier.verify_cancellable(proof.clone(), &CancellationToken::new())]), vec![Ok(())]);
+        let full = start.elapsed();
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let start = Instant::now();
+        assert_eq!(join_all(vec![verifier.verify_cancellable(proof, &cancel)]), vec![Err(VerifyError::Cancelled)]);
+        assert!(start.elapsed() < full, "cancelled after {:?}, a full verification took {:?}", start.elapsed(), full);
+    }
+
+    // Purpose: ensure a panicking job and a job the executor drops both surface in the awaiting task
+    // Params: a job that panics; an executor that discards every job
+    // Output: both Tasks panic when polled instead of staying pending
+    // Usage: `cargo test --features async -- asynchronous`
+    #[test]
+    fn lost_jobs_do_not_hang() {
+        struct Discard;
+        impl Executor for Discard {
+            fn spawn_blocking(&self, _job: Box<dyn FnOnce() + Send>) {}
+        }
+        let panicked = spawn(&ThreadExecutor, || -> u32 { panic!("job failed") });
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| join_all(vec![panicked]))).is_err());
+        let dropped = spawn(&Discard, || 1u32);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| join_all(vec![dropped]))).is_err());
+    }
+}
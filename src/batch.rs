@@ -0,0 +1,842 @@
+//! Verify many proof files against one set of params, or prove many rows of a table
+//!
+//! The inputs are either every `*.proof` file in a directory or the lines of a manifest. Each
+//! manifest line names a proof file, optionally followed by the range the proof must claim and
+//! the commitment it must be for:
+//!
+//! ```text
+//! # path [a b [commitment]], numbers in hex as in params and proof files
+//! drops/0001.proof
+//! drops/0002.proof 0a 64
+//! drops/0003.proof 0a 64 6765847...
+//! ```
+//!
+//! Relative paths are resolved against the manifest's directory. Params are loaded once by the
+//! caller; proofs are loaded and verified on the caller's Workers and reported in input order.
+//!
+//! Proving reads records: CSV with a header row, or JSON lines with one flat object per line.
+//! Fields are kept as text, an empty CSV field or a JSON null being absent, and the caller gives
+//! them meaning (`cuproof batch-prove` reads the columns value, blinding, a, b and out):
+//!
+//! ```text
+//! out,value,blinding,a,b
+//! 0001.proof,42,,10,100
+//! {"out": "0002.proof", "value": 7, "a": "0", "b": "0xff"}
+//! ```
+//!
+//! A BatchAttestation records that a set of proofs verified. Its verify checks the recorded
+//! combination with one commitment and no proofs; audit re-derives it from the proofs with two
+//! 128-bit exponentiations per proof instead of verify's two full ones. Its docs say what each
+//! does and does not show.
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::commitment::{commit, is_canonical_commitment, mod_exp};
+use crate::error::Error;
+use crate::range_proof::{proof_id, prove, Cuproof, ProveError};
+use crate::setup::{FingerprintHex, Params};
+use crate::util::{hex_to_bigint, hex_to_bigint_signed, load_proof, ParseError};
+use crate::verify::{range_accepted, verify_except_commitments, verify_with_nonce, VerifyCheck, VerifyError};
+use crate::workers::{map_on, Workers};
+
+/// Extension of the files discover_proofs picks up
+pub const PROOF_EXTENSION: &str = "proof";
+
+/// What a proof must claim besides verifying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub a: BigInt,
+    pub b: BigInt,
+    /// the commitment the proof must be for, when the manifest names one
+    pub commitment: Option<BigInt>,
+}
+
+/// One proof file to verify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchItem {
+    pub path: PathBuf,
+    pub expected: Option<Expected>,
+}
+
+/// Why a manifest could not be read
+#[derive(Debug)]
+pub enum ManifestError {
+    /// the manifest file could not be read
+    Io(io::Error),
+    /// a line has the wrong number of fields (1, 3 or 4 are allowed)
+    FieldCount { line: usize, found: usize },
+    /// a number on a line is not hex
+    Number { line: usize, field: &'static str, error: ParseError },
+    /// a CSV or JSON line of a record file is malformed
+    Syntax { line: usize, reason: String },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io(e) => write!(f, "{}", e),
+            ManifestError::FieldCount { line, found } => {
+                write!(f, "manifest line {}: expected `path [a b [commitment]]`, found {} fields", line, found)
+            }
+            ManifestError::Number { line, field, error } => write!(f, "manifest line {}: invalid {}: {}", line, field, error),
+            ManifestError::Syntax { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl core::error::Error for ManifestError {}
+
+/// Every `*.proof` file directly inside `dir`, sorted by path
+/// - returns: items without expectations, or the error of reading the directory
+pub fn discover_proofs(dir: &Path) -> io::Result<Vec<BatchItem>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == PROOF_EXTENSION) { paths.push(path); }
+    }
+    paths.sort();
+    Ok(paths.into_iter().map(|path| BatchItem { path, expected: None }).collect())
+}
+
+/// Parse a manifest (see the module docs); blank lines and lines starting with `#` are skipped
+/// - params: text of the manifest, base directory for relative paths
+/// - returns: the items in manifest order, or the first malformed line (1-based)
+pub fn parse_manifest(text: &str, base: &Path) -> Result<Vec<BatchItem>, ManifestError> {
+    let mut items = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() || fields[0].starts_with('#') { continue; }
+        let number = |field: &'static str, s: &str, signed: bool| {
+            let parsed = if signed { hex_to_bigint_signed(s) } else { hex_to_bigint(s) };
+            parsed.map_err(|error| ManifestError::Number { line: line_no, field, error })
+        };
+        let expected = match fields.len() {
+            1 => None,
+            3 | 4 => Some(Expected {
+                a: number("a", fields[1], true)?,
+                b: number("b", fields[2], true)?,
+                commitment: fields.get(3).map(|c| number("commitment", c, false)).transpose()?,
+            }),
+            found => return Err(ManifestError::FieldCount { line: line_no, found }),
+        };
+        items.push(BatchItem { path: base.join(fields[0]), expected });
+    }
+    Ok(items)
+}
+
+/// Read and parse the manifest at `path`
+pub fn read_manifest(path: &Path) -> Result<Vec<BatchItem>, ManifestError> {
+    let text = std::fs::read_to_string(path).map_err(ManifestError::Io)?;
+    parse_manifest(&text, path.parent().unwrap_or(Path::new("")))
+}
+
+/// One row of a record file: its 1-based line and its fields in file order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<(String, String)>,
+}
+
+impl Record {
+    /// The field `name`, None when absent
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse CSV with a header row, or JSON lines when the first line is an object (see the module
+/// docs); blank lines and lines starting with `#` are skipped
+/// - returns: the records in file order, or the first malformed line (1-based); a field given
+///   twice on a line, or a CSV line with more fields than the header, is malformed
+pub fn parse_records(text: &str) -> Result<Vec<Record>, ManifestError> {
+    let mut lines = text.lines().enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let Some((first_no, first)) = lines.next() else { return Ok(Vec::new()) };
+    let syntax = |line: usize| move |reason: String| ManifestError::Syntax { line, reason };
+    let mut records = Vec::new();
+    if first.starts_with('{') {
+        for (line, text) in std::iter::once((first_no, first)).chain(lines) {
+            let fields = parse_json_object(text).map_err(syntax(line))?;
+            records.push(Record { line, fields: check_names(fields).map_err(syntax(line))? });
+        }
+        return Ok(records);
+    }
+    let header = parse_csv_line(first).map_err(syntax(first_no))?;
+    check_names(header.iter().map(|name| (name.clone(), String::new())).collect()).map_err(syntax(first_no))?;
+    for (line, text) in lines {
+        let values = parse_csv_line(text).map_err(syntax(line))?;
+        if values.len() > header.len() {
+            return Err(ManifestError::Syntax { line, reason: format!("{} fields under a header of {}", values.len(), header.len()) });
+        }
+        let fields = header.iter().cloned().zip(values).filter(|(_, value)| !value.is_empty()).collect();
+        records.push(Record { line, fields });
+    }
+    Ok(records)
+}
+
+/// Read and parse the record file at `path`
+pub fn read_records(path: &Path) -> Result<Vec<Record>, ManifestError> {
+    parse_records(&std::fs::read_to_string(path).map_err(ManifestError::Io)?)
+}
+
+fn check_names(fields: Vec<(String, String)>) -> Result<Vec<(String, String)>, String> {
+    for (i, (name, _)) in fields.iter().enumerate() {
+        if name.is_empty() { return Err("empty field name".to_string()); }
+        if fields[..i].iter().any(|(earlier, _)| earlier == name) { return Err(format!("field {} given twice", name)); }
+    }
+    Ok(fields)
+}
+
+/// Split one CSV line on commas, trimming unquoted fields; `"..."` quotes a field and `""` inside
+/// quotes is a quote
+fn parse_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+        let mut field = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next() {
+                    Some('"') if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err("unterminated quoted field".to_string()),
+                }
+            }
+            while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+            if chars.peek().is_some_and(|c| *c != ',') { return Err("text after a quoted field".to_string()); }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') { field.push(c); }
+            field.truncate(field.trim_end().len());
+        }
+        fields.push(field);
+        if chars.next().is_none() { return Ok(fields); }
+    }
+}
+
+/// Parse one flat JSON object whose values are strings, integers or null, keeping each value's text
+/// and dropping nulls; anything nested is refused
+fn parse_json_object(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut chars = text.chars().peekable();
+    let skip_space = |chars: &mut std::iter::Peekable<std::str::Chars>| while chars.next_if(|c| c.is_whitespace()).is_some() {};
+    let expect = |chars: &mut std::iter::Peekable<std::str::Chars>, want: char| {
+        skip_space(chars);
+        match chars.next() {
+            Some(c) if c == want => Ok(()),
+            Some(c) => Err(format!("expected '{}', found '{}'", want, c)),
+            None => Err(format!("expected '{}' at the end of the line", want)),
+        }
+    };
+    expect(&mut chars, '{')?;
+    let mut fields = Vec::new();
+    skip_space(&mut chars);
+    if chars.next_if_eq(&'}').is_none() {
+        loop {
+            expect(&mut chars, '"')?;
+            let name = parse_json_string(&mut chars)?;
+            expect(&mut chars, ':')?;
+            skip_space(&mut chars);
+            let value = match chars.peek() {
+                Some('"') => {
+                    chars.next();
+                    Some(parse_json_string(&mut chars)?)
+                }
+                Some(c) if *c == '-' || c.is_ascii_digit() => {
+                    let mut number = String::new();
+                    while let Some(c) = chars.next_if(|c| *c == '-' || c.is_ascii_digit()) { number.push(c); }
+                    Some(number)
+                }
+                Some('n') => {
+                    if !(0..4).map(|_| chars.next()).eq("null".chars().map(Some)) { return Err("invalid literal".to_string()); }
+                    None
+                }
+                _ => return Err(format!("the value of {} must be a string, an integer or null", name)),
+            };
+            fields.push((name, value));
+            skip_space(&mut chars);
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err("expected ',' or '}'".to_string()),
+            }
+        }
+    }
+    skip_space(&mut chars);
+    if chars.next().is_some() { return Err("text after the object".to_string()); }
+    Ok(fields.into_iter().filter_map(|(name, value)| value.map(|value| (name, value))).collect())
+}
+
+/// The rest of a JSON string whose opening quote was consumed
+fn parse_json_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    let mut s = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(s),
+            Some('\\') => match chars.next() {
+                Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                Some('n') => s.push('\n'),
+                Some('t') => s.push('\t'),
+                Some('r') => s.push('\r'),
+                Some('u') => {
+                    let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+                    let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or("invalid \\u escape")?;
+                    s.push(c);
+                }
+                _ => return Err("invalid escape".to_string()),
+            },
+            Some(c) => s.push(c),
+            None => return Err("unterminated string".to_string()),
+        }
+    }
+}
+
+/// One proof to make: v in [a, b] under the commitment blinding r
+/// Not Debug, so v and r cannot end up in a log line
+pub struct ProveJob {
+    pub v: BigInt,
+    pub r: BigInt,
+    pub a: BigInt,
+    pub b: BigInt,
+}
+
+/// Prove every job on `workers`, or in turn on the calling thread without
+/// - returns: one result per job, in the order of `batch`
+/// - usage: `cuproof batch-prove`; params are shared, so their tables are built once
+pub fn prove_batch(params: &Params, batch: &[ProveJob], workers: Option<&Workers>) -> Vec<Result<Cuproof, ProveError>> {
+    map_on(workers, batch, |job| prove(&job.v, &job.r, &job.a, &job.b, params))
+}
+
+/// Why one proof of a batch was not accepted
+#[derive(Debug)]
+pub enum BatchFailure {
+    /// the file could not be read or does not decode as a proof
+    Load(Error),
+    /// the proof decoded but was rejected
+    Verify(VerifyError),
+}
+
+impl fmt::Display for BatchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchFailure::Load(e) => write!(f, "{}", e),
+            BatchFailure::Verify(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// The verdict on one item
+#[derive(Debug)]
+pub struct BatchResult {
+    pub path: PathBuf,
+    pub result: Result<(), BatchFailure>,
+}
+
+/// Counts over a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchSummary {
+    pub valid: usize,
+    pub invalid: usize,
+    pub unreadable: usize,
+}
+
+impl BatchSummary {
+    pub fn of(results: &[BatchResult]) -> BatchSummary {
+        let mut summary = BatchSummary::default();
+        for r in results {
+            match &r.result {
+                Ok(()) => summary.valid += 1,
+                Err(BatchFailure::Verify(_)) => summary.invalid += 1,
+                Err(BatchFailure::Load(_)) => summary.unreadable += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn total(&self) -> usize {
+        self.valid + self.invalid + self.unreadable
+    }
+}
+
+/// Check one decoded proof: cuproof_verify_checked, then the item's expectations
+/// - returns: Ok(()) or the VerifyError naming what failed; a proof without a statement that does
+///   not verify for the expected range is InvalidProof
+pub fn check_item(proof: &Cuproof, params: &Params, expected: Option<&Expected>) -> Result<(), VerifyError> {
+    check_item_with_nonce(proof, params, expected, &[])
+}
+
+/// check_item for a proof made by range_proof::prove_with_nonce, verified as verify_with_nonce does
+pub fn check_item_with_nonce(proof: &Cuproof, params: &Params, expected: Option<&Expected>, nonce: &[u8]) -> Result<(), VerifyError> {
+    verify_with_nonce(proof, params, nonce)?;
+    let Some(expected) = expected else { return Ok(()) };
+    if proof.statement.as_ref().is_some_and(|s| (&s.a, &s.b) != (&expected.a, &expected.b)) {
+        return Err(VerifyError::StatementMismatch("range"));
+    }
+    if expected.commitment.as_ref().is_some_and(|c| *c != proof.C) {
+        return Err(VerifyError::StatementMismatch("commitment"));
+    }
+    // the proof verified above, so all cuproof_verify_with_range has left to ask is the range itself
+    if !range_accepted(&expected.a, &expected.b) { return Err(VerifyError::InvalidProof); }
+    Ok(())
+}
+
+/// Load and check every item on `workers`, or in turn on the calling thread without
+/// - returns: one result per item, in the order of `items`
+/// - usage: `cuproof batch-verify`; params are shared, so their tables are built once
+pub fn verify_batch(params: &Params, items: &[BatchItem], workers: Option<&Workers>) -> Vec<BatchResult> {
+    let results = map_on(workers, items, |item| match item.path.to_str().map(load_proof) {
+        Some(Ok(proof)) => check_item(&proof, params, item.expected.as_ref()).map_err(BatchFailure::Verify),
+        Some(Err(e)) => Err(BatchFailure::Load(e.into())),
+        None => Err(BatchFailure::Load(io::Error::new(io::ErrorKind::InvalidInput, "path is not UTF-8").into())),
+    });
+    items.iter().zip(results).map(|(item, result)| BatchResult { path: item.path.clone(), result }).collect()
+}
+
+/// Bits of each weight in a BatchAttestation's combination
+pub const ATTESTATION_WEIGHT_BITS: usize = 128;
+
+/// Domain separator of the weights seed
+const ATTESTATION_DOMAIN: &[u8] = b"cuproof batch attestation v1";
+
+/// A record that a set of proofs verified, with a cheap check of the record and an audit against the proofs
+///
+/// The two exponentiations per proof of verify, T1 = g^t1 h^tau1 and T2 = g^t2 h^tau2, are
+/// replaced by one random linear combination over the whole set (the small-exponent batch test):
+/// with odd 128-bit weights w_i, w'_i,
+///
+/// ```text
+/// prod T1_i^w_i T2_i^w'_i = g^(sum w_i t1_i + w'_i t2_i) h^(sum w_i tau1_i + w'_i tau2_i)  (mod n)
+/// ```
+///
+/// compared as canonical residues in [1, n), as verify compares T1 and T2. The weights are
+/// SHA-256 over a seed, itself SHA-256 over the params fingerprint and the proof ids in order, so
+/// anyone re-derives them and nobody chooses them. `combined`, `exponent_g` and `exponent_h` are
+/// the two sides' parts.
+///
+/// verify checks the record alone: the params, the seed against the ids, `combined` in canonical
+/// form and the equation for the recorded parts, one commitment whatever the size of the set. A
+/// passing verify shows that the record is consistent, not that it is the proofs': whoever
+/// built it could have written any exponents and their commitment. It catches a record altered
+/// after create, in `combined`, the exponents, the seed or the ids, without fetching a proof.
+///
+/// audit re-derives the parts from the proofs and then runs verify, at verify's cheap checks
+/// (transcript, polynomial, t_hat commitment, scalar bound, shape) plus two 128-bit
+/// exponentiations per proof. What a passing audit shows, about exactly the proofs listed:
+/// - every check of verify other than T1 and T2 held for each proof, and each T1_i and T2_i is
+///   the canonical residue verify compares;
+/// - each T1_i and T2_i equals its commitment up to an element of order two, except with
+///   probability about 2^-ATTESTATION_WEIGHT_BITS per attempt at building the set; finding an
+///   element of small order other than -1 is believed as hard as factoring n (the low-order
+///   assumption). Re-randomizing a proof to re-roll the seed buys one attempt per proof made;
+/// - of the T1_i and T2_i, an even number at most is the negated commitment: -1 enters the
+///   combination raised to the sum of their weights, and each weight is odd, so a single
+///   negation always fails the equation.
+///
+/// What neither shows: that no two of the T1_i and T2_i are negated together, whose signs
+/// cancel (verify rejects each such proof; cuproof_verify_fast with each proof's
+/// VerifierAssist checks every commitment exactly); anything about ranges, labels or nonces the
+/// verifier expects (proofs made with a nonce fail, the transcript being FiatShamir::new); who
+/// built it, since it carries no signature; or, for audit, anything without the proofs, whose
+/// retention the auditor must arrange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchAttestation {
+    pub params_fingerprint: [u8; 32],
+    /// range_proof::proof_id of each proof, in the order the weights are drawn
+    pub proof_ids: Vec<String>,
+    pub weights_seed: [u8; 32],
+    /// prod T1_i^w_i T2_i^w'_i mod n
+    pub combined: BigInt,
+    /// sum w_i t1_i + w'_i t2_i
+    pub exponent_g: BigInt,
+    /// sum w_i tau1_i + w'_i tau2_i
+    pub exponent_h: BigInt,
+}
+
+impl BatchAttestation {
+    /// The attestation of `proofs` under `params`, once it audits against them
+    /// - returns: the error audit gives when a proof fails a check or the combined equation does
+    ///   not hold, so no attestation is built over a set that verify would reject
+    /// - usage: at settlement, `BatchAttestation::create(&proofs, &params)?`, then publish it
+    pub fn create(proofs: &[Cuproof], params: &Params) -> Result<BatchAttestation, AttestationError> {
+        let proof_ids: Vec<String> = proofs.iter().map(proof_id).collect();
+        let weights_seed = attestation_seed(&params.fingerprint(), &proof_ids);
+        let (combined, exponent_g, exponent_h) = combine(proofs.iter(), &weights_seed, params);
+        let attestation = BatchAttestation { params_fingerprint: params.fingerprint(), proof_ids, weights_seed, combined, exponent_g, exponent_h };
+        attestation.audit(params, proofs)?;
+        Ok(attestation)
+    }
+
+    /// Check the record alone, as described on BatchAttestation: no proof is fetched or verified
+    /// - returns: Ok(()) when the record is consistent; otherwise other params, a seed that is not
+    ///   the ids', or the combined equation (for a `combined` out of canonical form as well)
+    /// - usage: anyone holding the attestation, e.g. before accepting a settlement; audit for the proofs
+    pub fn verify(&self, params: &Params) -> Result<(), AttestationError> {
+        if self.params_fingerprint != params.fingerprint() {
+            return Err(AttestationError::WrongParams { expected: FingerprintHex(self.params_fingerprint), found: params.fingerprint_hex() });
+        }
+        if attestation_seed(&self.params_fingerprint, &self.proof_ids) != self.weights_seed { return Err(AttestationError::SeedMismatch); }
+        // commit reduces mod n, so a combined out of [1, n) cannot equal it
+        if commit(params, &self.exponent_g, &self.exponent_h) != self.combined { return Err(AttestationError::CombinedEquation); }
+        Ok(())
+    }
+
+    /// Re-check the attestation against the proofs `provider` returns for its ids
+    /// - returns: Ok(()) when, as described on BatchAttestation, every proof passes; otherwise the
+    ///   first problem: the errors of verify, before any proof is fetched, then a proof the
+    ///   provider lacks or whose id differs, a proof failing a check (with its index), or recorded
+    ///   witnesses that are not the proofs'
+    /// - usage: the auditor's path, which needs every proof; verify checks the record without them
+    pub fn audit<P: ProofProvider + ?Sized>(&self, params: &Params, provider: &P) -> Result<(), AttestationError> {
+        self.verify(params)?;
+        let mut proofs = Vec::with_capacity(self.proof_ids.len());
+        for (index, id) in self.proof_ids.iter().enumerate() {
+            let proof = provider.proof(index, id)
+                .map_err(|error| AttestationError::Provider { index, error })?
+                .ok_or(AttestationError::MissingProof { index })?;
+            if proof_id(&proof) != *id { return Err(AttestationError::WrongProof { index }); }
+            verify_except_commitments(&proof, params).map_err(|check| AttestationError::Proof { index, check })?;
+            // verify compares T1 and T2 with commit as integers, so only the canonical form passes
+            for (element, check) in [(&proof.T1, VerifyCheck::T1Commitment), (&proof.T2, VerifyCheck::T2Commitment)] {
+                if !is_canonical_commitment(params, element) { return Err(AttestationError::Proof { index, check }); }
+            }
+            proofs.push(proof);
+        }
+        let (combined, exponent_g, exponent_h) = combine(proofs.iter(), &self.weights_seed, params);
+        if combined != self.combined || exponent_g != self.exponent_g || exponent_h != self.exponent_h {
+            return Err(AttestationError::WitnessMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// SHA-256 over the domain, the params fingerprint, the count and the length-prefixed ids
+fn attestation_seed(fingerprint: &[u8; 32], proof_ids: &[String]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(ATTESTATION_DOMAIN);
+    hasher.update(fingerprint);
+    hasher.update((proof_ids.len() as u64).to_be_bytes());
+    for id in proof_ids {
+        hasher.update((id.len() as u64).to_be_bytes());
+        hasher.update(id.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// The weights of T1 and T2 of proof `index`: the two halves of SHA-256 over the seed and index,
+/// each with its lowest bit set, so a negated T1 or T2 flips the sign of the combination
+fn attestation_weights(seed: &[u8; 32], index: u64) -> (BigInt, BigInt) {
+    let digest = Sha256::new().chain_update(seed).chain_update(index.to_be_bytes()).finalize();
+    let (w1, w2) = digest.split_at(ATTESTATION_WEIGHT_BITS / 8);
+    (BigInt::from_bytes_be(Sign::Plus, w1) | BigInt::one(), BigInt::from_bytes_be(Sign::Plus, w2) | BigInt::one())
+}
+
+/// (prod T1^w T2^w' mod n, sum w t1 + w' t2, sum w tau1 + w' tau2) over `proofs`
+fn combine<'a>(proofs: impl Iterator<Item = &'a Cuproof>, seed: &[u8; 32], params: &Params) -> (BigInt, BigInt, BigInt) {
+    let n = params.n();
+    let (mut combined, mut exponent_g, mut exponent_h) = (BigInt::one(), BigInt::zero(), BigInt::zero());
+    for (index, proof) in proofs.enumerate() {
+        let (w1, w2) = attestation_weights(seed, index as u64);
+        combined = combined * mod_exp(&proof.T1, &w1, n) % n * mod_exp(&proof.T2, &w2, n) % n;
+        exponent_g += &w1 * &proof.t1 + &w2 * &proof.t2;
+        exponent_h += &w1 * &proof.tau1 + &w2 * &proof.tau2;
+    }
+    (combined, exponent_g, exponent_h)
+}
+
+/// Where BatchAttestation::audit gets the proof behind each recorded id
+pub trait ProofProvider {
+    /// The proof at `index` of the attestation, whose proof_id should be `id`
+    /// - returns: Ok(None) when the provider has no such proof, an error when looking failed
+    fn proof(&self, index: usize, id: &str) -> io::Result<Option<Cuproof>>;
+}
+
+/// The proofs the attestation was created over, in the same order
+impl ProofProvider for [Cuproof] {
+    fn proof(&self, index: usize, _id: &str) -> io::Result<Option<Cuproof>> {
+        Ok(self.get(index).cloned())
+    }
+}
+
+/// Proof files named by their id: `<dir>/<id>.proof`
+#[derive(Debug, Clone, Copy)]
+pub struct ProofDirectory<'a>(pub &'a Path);
+
+impl ProofProvider for ProofDirectory<'_> {
+    fn proof(&self, _index: usize, id: &str) -> io::Result<Option<Cuproof>> {
+        let path = self.0.join(format!("{}.{}", id, PROOF_EXTENSION));
+        if !path.is_file() { return Ok(None); }
+        let path = path.to_str().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path is not UTF-8"))?;
+        load_proof(path).map(Some).map_err(io::Error::other)
+    }
+}
+
+/// Why BatchAttestation::verify or audit rejected an attestation
+#[derive(Debug)]
+pub enum AttestationError {
+    /// the attestation is for params with another fingerprint
+    WrongParams { expected: FingerprintHex, found: FingerprintHex },
+    /// the weights seed is not the one the ids derive
+    SeedMismatch,
+    /// the provider failed to look up proof `index`
+    Provider { index: usize, error: io::Error },
+    /// the provider has no proof `index`
+    MissingProof { index: usize },
+    /// the provider's proof `index` has another id
+    WrongProof { index: usize },
+    /// proof `index` fails the named check
+    Proof { index: usize, check: VerifyCheck },
+    /// the recorded combination is not the proofs'
+    WitnessMismatch,
+    /// the combined commitment equation does not hold: some T1 or T2 is not its commitment
+    CombinedEquation,
+}
+
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttestationError::WrongParams { expected, found } => write!(f, "attestation is for params {}, not {}", expected, found),
+            AttestationError::SeedMismatch => write!(f, "weights seed does not match the proof ids"),
+            AttestationError::Provider { index, error } => write!(f, "proof {}: {}", index, error),
+            AttestationError::MissingProof { index } => write!(f, "proof {} is missing", index),
+            AttestationError::WrongProof { index } => write!(f, "proof {} does not have the recorded id", index),
+            AttestationError::Proof { index, check } => write!(f, "proof {} fails the {} check", index, check),
+            AttestationError::WitnessMismatch => write!(f, "recorded combination does not match the proofs"),
+            AttestationError::CombinedEquation => write!(f, "combined commitment equation does not hold"),
+        }
+    }
+}
+
+impl core::error::Error for AttestationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_proof::{prove, random_blinding};
+    use crate::verify::cuproof_verify_with_range;
+    use crate::setup::insecure_test_setup;
+    use crate::util::save_proof;
+    use rand::rngs::OsRng;
+
+    // Purpose: ensure manifests parse paths, ranges and commitments and report the malformed line
+    // Params: a manifest with comments, each line shape, and broken variants
+    // Output: items in order with paths under the base; FieldCount / Number errors with line numbers
+    // Usage: `cargo test -- src::batch` or `cargo test`
+    #[test]
+    fn manifest_lines_parse() {
+        let text = "# nightly\n\na.proof\nsub/b.proof 0a 64\n/abs/c.proof -5 0x10 ff\n";
+        let items = parse_manifest(text, Path::new("/drops")).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0], BatchItem { path: PathBuf::from("/drops/a.proof"), expected: None });
+        assert_eq!(items[1].path, PathBuf::from("/drops/sub/b.proof"));
+        assert_eq!(items[1].expected, Some(Expected { a: BigInt::from(10), b: BigInt::from(100), commitment: None }));
+        assert_eq!(items[2].path, PathBuf::from("/abs/c.proof"));
+        assert_eq!(items[2].expected, Some(Expected { a: BigInt::from(-5), b: BigInt::from(16), commitment: Some(BigInt::from(255)) }));
+
+        assert!(matches!(parse_manifest("a.proof\nb.proof 1\n", Path::new("")), Err(ManifestError::FieldCount { line: 2, found: 2 })));
+        assert!(matches!(parse_manifest("a.proof 1 zz\n", Path::new("")), Err(ManifestError::Number { line: 1, field: "b", .. })));
+        assert!(matches!(parse_manifest("a.proof 1 2 -3\n", Path::new("")), Err(ManifestError::Number { field: "commitment", .. })));
+    }
+
+    // Purpose: ensure record files parse as CSV or JSON lines and report the malformed line
+    // Params: a CSV manifest with quoting, comments and an empty field; the same rows as JSON lines; broken variants
+    // Output: identical records, absent fields dropped; Syntax errors with line numbers
+    // Usage: `cargo test -- src::batch` or `cargo test`
+    #[test]
+    fn records_parse_csv_and_json_lines() {
+        let csv = "# export\nout, value ,blinding,a,b\n\n\"a,1.proof\",42,,10,100\nb.proof, 7 ,\"0x1f\",-5,0x10\n";
+        let json = "{\"out\": \"a,1.proof\", \"value\": 42, \"blinding\": null, \"a\": 10, \"b\": \"100\"}\n\
+                    # b\n{\"out\":\"b.proof\",\"value\":\"7\",\"blinding\":\"0x1f\",\"a\":-5,\"b\":\"0x10\"}\n";
+        let field = |name: &str, value: &str| (name.to_string(), value.to_string());
+        let expected = [
+            vec![field("out", "a,1.proof"), field("value", "42"), field("a", "10"), field("b", "100")],
+            vec![field("out", "b.proof"), field("value", "7"), field("blinding", "0x1f"), field("a", "-5"), field("b", "0x10")],
+        ];
+        for (text, lines) in [(csv, [4, 5]), (json, [1, 3])] {
+            let records = parse_records(text).unwrap();
+            assert_eq!(records.iter().map(|r| r.line).collect::<Vec<_>>(), lines);
+            assert_eq!(records.iter().map(|r| r.fields.clone()).collect::<Vec<_>>(), expected);
+            assert_eq!((records[0].get("blinding"), records[1].get("blinding")), (None, Some("0x1f")));
+        }
+        assert_eq!(parse_records("\n# nothing\n").unwrap(), Vec::new());
+
+        let line_of = |text: &str| match parse_records(text) {
+            Err(ManifestError::Syntax { line, .. }) => line,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(line_of("out,a\nx,1,2\n"), 2);
+        assert_eq!(line_of("out,out\n"), 1);
+        assert_eq!(line_of("out,a\n\"x,1\n"), 2);
+        assert_eq!(line_of("{\"a\": 1}\n{\"a\": {\"b\": 1}}\n"), 2);
+        assert_eq!(line_of("{\"a\": 1, \"a\": 2}\n"), 1);
+        assert_eq!(line_of("{\"a\": 1} x\n"), 1);
+    }
+
+    // Purpose: ensure prove_batch returns one result per job in order, with and without workers
+    // Params: insecure params; two provable jobs around one with v outside its range
+    // Output: proofs for the given commitments that verify for their ranges, and ValueOutOfRange in place
+    // Usage: `cargo test -- src::batch` or `cargo test`
+    #[test]
+    fn prove_batch_keeps_order() {
+        let params = insecure_test_setup(512);
+        let job = |v: i32, a: i32, b: i32| ProveJob { v: BigInt::from(v), r: BigInt::from(v + 1000), a: BigInt::from(a), b: BigInt::from(b) };
+        let batch = [job(42, 10, 100), job(5, 10, 100), job(0, -3, 3)];
+        for workers in [None, Some(Workers::new(3))] {
+            let results = prove_batch(&params, &batch, workers.as_ref());
+            assert!(matches!(results[1], Err(ProveError::ValueOutOfRange)));
+            for i in [0, 2] {
+                let proof = results[i].as_ref().unwrap();
+                assert_eq!(proof.C, crate::commitment::commit(&params, &batch[i].v, &batch[i].r));
+                assert!(cuproof_verify_with_range(proof, &params, &batch[i].a, &batch[i].b));
+            }
+        }
+    }
+
+    // Purpose: ensure verify_batch keeps input order and names each failure, with and without workers
+    // Params: insecure params; a valid proof, the same proof with a wrong expected range and commitment, a garbage file
+    // Output: Ok, StatementMismatch("range"), StatementMismatch("commitment"), Load; the same summary on the calling thread and 4 workers
+    // Usage: `cargo test -- src::batch` or `cargo test`
+    #[test]
+    fn batch_reports_in_order() {
+        let params = insecure_test_setup(512);
+        let dir = std::env::temp_dir().join(format!("cuproof_batch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (BigInt::from(10), BigInt::from(100));
+        let proof = prove(&BigInt::from(42), &random_blinding(&mut OsRng), &a, &b, &params).unwrap();
+        let good = dir.join("good.proof");
+        save_proof(good.to_str().unwrap(), &proof).unwrap();
+        let garbage = dir.join("garbage.proof");
+        std::fs::write(&garbage, b"not a proof").unwrap();
+
+        let expect = |a: i32, b: i32, commitment: Option<BigInt>| Some(Expected { a: BigInt::from(a), b: BigInt::from(b), commitment });
+        let items = vec![
+            BatchItem { path: good.clone(), expected: expect(10, 100, Some(proof.C.clone())) },
+            BatchItem { path: good.clone(), expected: expect(0, 100, None) },
+            BatchItem { path: good.clone(), expected: expect(10, 100, Some(BigInt::from(7))) },
+            BatchItem { path: garbage.clone(), expected: None },
+        ];
+        for workers in [None, Some(Workers::new(4))] {
+            let results = verify_batch(&params, &items, workers.as_ref());
+            assert!(results[0].result.is_ok());
+            assert!(matches!(results[1].result, Err(BatchFailure::Verify(VerifyError::StatementMismatch("range")))));
+            assert!(matches!(results[2].result, Err(BatchFailure::Verify(VerifyError::StatementMismatch("commitment")))));
+            assert!(matches!(results[3].result, Err(BatchFailure::Load(_))));
+            assert_eq!(results[3].path, garbage);
+            assert_eq!(BatchSummary::of(&results), BatchSummary { valid: 1, invalid: 2, unreadable: 1 });
+        }
+        assert_eq!(discover_proofs(&dir).unwrap().iter().map(|i| i.path.clone()).collect::<Vec<_>>(), vec![garbage, good]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // Purpose: ensure an attestation audits from the proofs and from a directory of proof files
+    // Params: sample_proof(201..=205) under one set of params; copies of the attestation with an id,
+    //         the seed or a witness changed; a consistent record of other exponents; other params;
+    //         a provider lacking a proof
+    // Output: Ok(()) from the proofs and from <dir>/<id>.proof; SeedMismatch, CombinedEquation,
+    //         WitnessMismatch, WrongParams, MissingProof and WrongProof for the altered inputs
+    // Usage: `cargo test -- src::batch` or `cargo test`
+    #[test]
+    fn attestations_audit() {
+        let samples: Vec<_> = (201..=205).map(crate::testing::sample_proof).collect();
+        let params = samples[0].0.clone();
+        let proofs: Vec<Cuproof> = samples.into_iter().map(|(_, _, proof)| proof).collect();
+        let attestation = BatchAttestation::create(&proofs, &params).unwrap();
+        assert_eq!(attestation.proof_ids.len(), 5);
+        assert!(attestation.audit(&params, &proofs[..]).is_ok());
+
+        let dir = std::env::temp_dir().join(format!("cuproof_attestation_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (proof, id) in proofs.iter().zip(&attestation.proof_ids) {
+            save_proof(dir.join(format!("{}.proof", id)).to_str().unwrap(), proof).unwrap();
+        }
+        assert!(attestation.audit(&params, &ProofDirectory(&dir)).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let altered = |change: &dyn Fn(&mut BatchAttestation)| {
+            let mut altered = attestation.clone();
+            change(&mut altered);
+            altered.audit(&params, &proofs[..])
+        };
+        assert!(matches!(altered(&|a| a.proof_ids.swap(0, 1)), Err(AttestationError::SeedMismatch)));
+        assert!(matches!(altered(&|a| a.weights_seed[0] ^= 1), Err(AttestationError::SeedMismatch)));
+        assert!(matches!(altered(&|a| a.exponent_h += 1), Err(AttestationError::CombinedEquation)));
+        // a record that holds together but is not the proofs' passes verify and fails only the audit
+        let consistent = |a: &mut BatchAttestation| {
+            a.exponent_g += 1;
+            a.combined = commit(&params, &a.exponent_g, &a.exponent_h);
+        };
+        let mut forged = attestation.clone();
+        consistent(&mut forged);
+        assert!(forged.verify(&params).is_ok());
+        assert!(matches!(altered(&consistent), Err(AttestationError::WitnessMismatch)));
+        let other = crate::setup::fast_test_setup_seeded(201);
+        assert!(matches!(attestation.audit(&other, &proofs[..]), Err(AttestationError::WrongParams { .. })));
+        assert!(matches!(attestation.audit(&params, &proofs[..4]), Err(AttestationError::MissingProof { index: 4 })));
+        let reordered = [&proofs[1..], &proofs[..1]].concat();
+        assert!(matches!(attestation.audit(&params, &reordered[..]), Err(AttestationError::WrongProof { index: 0 })));
+    }
+
+    // Purpose: ensure verify checks the record without the proofs and catches a tampered combined value
+    // Params: sample_proof(210..=212) attested, then dropped; copies with combined multiplied by g,
+    //         replaced by combined + n, and with another ids list
+    // Output: Ok(()) for the record as created; CombinedEquation for either combined, SeedMismatch
+    //         for the ids, all without a ProofProvider
+    // Usage: `cargo test -- src::batch` or `cargo test`
+    #[test]
+    fn verify_checks_the_record_without_the_proofs() {
+        let samples: Vec<_> = (210..=212).map(crate::testing::sample_proof).collect();
+        let params = samples[0].0.clone();
+        let attestation = {
+            let proofs: Vec<Cuproof> = samples.into_iter().map(|(_, _, proof)| proof).collect();
+            BatchAttestation::create(&proofs, &params).unwrap()
+        };
+        assert!(attestation.verify(&params).is_ok());
+        let altered = |change: &dyn Fn(&mut BatchAttestation)| {
+            let mut altered = attestation.clone();
+            change(&mut altered);
+            altered.verify(&params)
+        };
+        assert!(matches!(altered(&|a| a.combined = &a.combined * params.g() % params.n()), Err(AttestationError::CombinedEquation)));
+        assert!(matches!(altered(&|a| a.combined += params.n()), Err(AttestationError::CombinedEquation)));
+        assert!(matches!(altered(&|a| { a.proof_ids.pop(); }), Err(AttestationError::SeedMismatch)));
+    }
+
+    // Purpose: ensure create refuses a set holding one invalid proof
+    // Params: sample_proof(206..=209) with proof 2 altered: tau1 + 1 (only T1's equation breaks),
+    //         t_hat + 1, and T1, then T2, replaced by its negation n - T with t_hat recomputed for
+    //         the new challenge
+    // Output: create refuses each set, with CombinedEquation for tau1 and for each negation and
+    //         Proof { index: 2, PolynomialEvaluation } for t_hat
+    // Usage: `cargo test -- src::batch` or `cargo test`
+    #[test]
+    fn invalid_proofs_are_detected() {
+        let samples: Vec<_> = (206..=209).map(crate::testing::sample_proof).collect();
+        let params = samples[0].0.clone();
+        let proofs: Vec<Cuproof> = samples.into_iter().map(|(_, _, proof)| proof).collect();
+        let with_invalid = |change: &dyn Fn(&mut Cuproof)| {
+            let mut proofs = proofs.clone();
+            change(&mut proofs[2]);
+            assert!(!crate::verify::verify(&proofs[2], &params));
+            BatchAttestation::create(&proofs, &params)
+        };
+        assert!(matches!(with_invalid(&|p| p.tau1 += 1), Err(AttestationError::CombinedEquation)));
+        assert!(matches!(
+            with_invalid(&|p| p.t_hat += 1),
+            Err(AttestationError::Proof { index: 2, check: VerifyCheck::PolynomialEvaluation })
+        ));
+        // a prover building T1 = -g^t1 h^tau1 answers the challenge x that T1 hashes to
+        let negate = |p: &mut Cuproof, t2: bool| {
+            let element = if t2 { &mut p.T2 } else { &mut p.T1 };
+            *element = params.n() - &*element;
+            let x = crate::verify::proof_challenges(p, &params).x;
+            p.t_hat = crate::range_proof::math::poly_eval(&[&p.t0, &p.t1, &p.t2], &x);
+        };
+        assert!(matches!(with_invalid(&|p| negate(p, false)), Err(AttestationError::CombinedEquation)));
+        assert!(matches!(with_invalid(&|p| negate(p, true)), Err(AttestationError::CombinedEquation)));
+    }
+}
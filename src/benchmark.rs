@@ -1,8 +1,8 @@
 use std::time::{Instant, Duration};
 use num_bigint::BigInt;
-use crate::setup::{trusted_setup, fast_test_setup};
-use crate::range_proof::{cuproof_prove, proof_size_bytes};
-use crate::verify::cuproof_verify;
+use crate::setup::{trusted_setup, trusted_setup_with, trusted_setup_with_threads, insecure_test_setup, throwaway_params};
+use crate::range_proof::{prove, proof_size_bytes};
+use crate::verify::verify;
 use crate::util::random_bigint;
 
 /// Kết quả đo benchmark cho một độ dài khoảng cụ thể
@@ -41,16 +41,16 @@ pub fn benchmark_range_length(range_length: usize, use_fast_setup: bool) -> Benc
     // Đo thời gian setup với độ chính xác cao
     let setup_time = measure_time_accurate(|| {
         let _ = if use_fast_setup {
-            fast_test_setup()
+            insecure_test_setup(512)
         } else {
-            trusted_setup(2048)
+            trusted_setup(2048).expect("2048-bit setup is supported")
         };
     }, 5);
     
-    let (g, h, n) = if use_fast_setup {
-        fast_test_setup()
+    let params = if use_fast_setup {
+        insecure_test_setup(512)
     } else {
-        trusted_setup(2048)
+        trusted_setup(2048).expect("2048-bit setup is supported")
     };
     
     // Tạo dữ liệu test dựa trên số bit
@@ -61,20 +61,20 @@ pub fn benchmark_range_length(range_length: usize, use_fast_setup: bool) -> Benc
     
     // Đo thời gian tạo proof với độ chính xác cao
     let prove_time = measure_time_accurate(|| {
-        let _proof = cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
+        let _proof = prove(&v, &r, &a, &b, &params).expect("v lies in [a, b]");
     }, 3);
     
-    let proof = cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
+    let proof = prove(&v, &r, &a, &b, &params).expect("v lies in [a, b]");
     
     // Đo kích thước proof
     let proof_size = proof_size_bytes(&proof);
     
     // Đo thời gian verify với độ chính xác cao
     let verify_time = measure_time_accurate(|| {
-        let _result = cuproof_verify(&proof, &g, &h, &n);
+        let _result = verify(&proof, &params);
     }, 10);
     
-    let verify_result = cuproof_verify(&proof, &g, &h, &n);
+    let verify_result = verify(&proof, &params);
     
     BenchmarkResult {
         range_length,
@@ -178,10 +178,10 @@ pub fn print_benchmark_summary(results: &[BenchmarkResult]) {
 
 /// Benchmark với các giá trị test khác nhau trong cùng một khoảng
 pub fn benchmark_different_values_in_range(range_length: usize, use_fast_setup: bool) -> Vec<BenchmarkResult> {
-    let (g, h, n) = if use_fast_setup {
-        fast_test_setup()
+    let params = if use_fast_setup {
+        insecure_test_setup(512)
     } else {
-        fast_test_setup() // Sử dụng fast cho test này
+        insecure_test_setup(512) // Sử dụng fast cho test này
     };
     
     let a = BigInt::from(0);
@@ -205,13 +205,13 @@ pub fn benchmark_different_values_in_range(range_length: usize, use_fast_setup:
         let v = BigInt::from(test_v as i32);
         
         let prove_start = Instant::now();
-        let proof = cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
+        let proof = prove(&v, &r, &a, &b, &params).expect("v lies in [a, b]");
         let prove_time = prove_start.elapsed();
         
         let proof_size = proof_size_bytes(&proof);
         
         let verify_start = Instant::now();
-        let verify_result = cuproof_verify(&proof, &g, &h, &n);
+        let verify_result = verify(&proof, &params);
         let verify_time = verify_start.elapsed();
         
         let result = BenchmarkResult {
@@ -231,6 +231,125 @@ pub fn benchmark_different_values_in_range(range_length: usize, use_fast_setup:
     results
 }
 
+/// Đo thời gian (wall-clock) của trusted setup với từng số luồng tìm số nguyên tố
+/// Mỗi cấu hình chạy `runs` lần và lấy trung bình, vì thời gian tìm số nguyên tố dao động lớn
+pub fn benchmark_setup_threads(bits: usize, thread_counts: &[usize], runs: usize) -> Vec<(usize, Duration)> {
+    let runs = runs.max(1);
+    let mut results = Vec::new();
+    for &threads in thread_counts {
+        let start = Instant::now();
+        for _ in 0..runs {
+            trusted_setup_with_threads(bits, bits < 2048, Some(threads)).expect("benchmark setup size is allowed");
+        }
+        let avg = start.elapsed() / runs as u32;
+        println!("  {} luồng: {} ms (trung bình {} lần)", threads, avg.as_millis(), runs);
+        results.push((threads, avg));
+    }
+    if let (Some(first), Some(last)) = (results.first(), results.last()) {
+        println!("  Tăng tốc {} -> {} luồng: {:.2}x", first.0, last.0, first.1.as_secs_f64() / last.1.as_secs_f64().max(f64::EPSILON));
+    }
+    results
+}
+
+/// Cấu hình của bench_machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MachineBenchConfig {
+    /// Kích thước modulus của params dùng một lần
+    pub bits: usize,
+    /// Độ dài khoảng [0, 2^range_bits - 1]; số chiều IPP luôn là 64 như prove
+    pub range_bits: usize,
+    /// Số lần prove và verify được đo
+    pub iterations: usize,
+    /// Đo thêm một lần trusted setup thật (tìm số nguyên tố) ở cùng kích thước
+    pub setup_sample: bool,
+}
+
+/// Thống kê thời gian của một thao tác qua các lần đo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    pub mean: Duration,
+    pub median: Duration,
+    /// Percentile 95 theo nearest-rank
+    pub p95: Duration,
+}
+
+impl Timings {
+    /// Thống kê của `samples` (không rỗng); sắp xếp lại `samples`
+    pub fn of(samples: &mut [Duration]) -> Timings {
+        assert!(!samples.is_empty(), "Timings::of needs at least one sample");
+        samples.sort();
+        let len = samples.len();
+        let mean = samples.iter().sum::<Duration>() / len as u32;
+        let median = if len % 2 == 1 { samples[len / 2] } else { (samples[len / 2 - 1] + samples[len / 2]) / 2 };
+        let p95 = samples[(len * 95).div_ceil(100) - 1];
+        Timings { mean, median, p95 }
+    }
+}
+
+/// Kết quả của bench_machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineBenchReport {
+    pub config: MachineBenchConfig,
+    /// Thời gian tạo params dùng một lần (không tìm số nguyên tố)
+    pub params_time: Duration,
+    pub prove: Timings,
+    pub verify: Timings,
+    /// proof_size_bytes của proof
+    pub proof_size_bytes: usize,
+    /// Kích thước proof khi lưu bằng save_proof (container)
+    pub encoded_size_bytes: usize,
+    /// Thời gian một lần trusted setup thật, nếu được yêu cầu
+    pub setup_sample: Option<Duration>,
+    /// Mọi proof đo được đều verify thành công
+    pub all_verified: bool,
+}
+
+/// Đo prove và verify trên máy này mà không ghi file nào và không in gì
+/// - params: config; bits >= 64, range_bits và iterations > 0
+/// - returns: MachineBenchReport với mean / median / p95 của từng thao tác
+/// - usage: `cuproof bench`; params là throwaway_params (INSECURE, chỉ để đo thời gian), nên
+///   không cần chờ tìm số nguyên tố trừ khi setup_sample được bật
+pub fn bench_machine(config: &MachineBenchConfig) -> MachineBenchReport {
+    assert!(config.range_bits > 0 && config.iterations > 0, "bench_machine needs range_bits and iterations > 0");
+    let start = Instant::now();
+    let params = throwaway_params(config.bits, &mut rand::rngs::OsRng);
+    let params_time = start.elapsed();
+
+    let a = BigInt::from(0);
+    let b = (BigInt::from(1) << config.range_bits) - 1;
+    let v = BigInt::from(1) << (config.range_bits - 1);
+    let (mut prove_samples, mut verify_samples) = (Vec::new(), Vec::new());
+    let mut all_verified = true;
+    let mut last = None;
+    for _ in 0..config.iterations {
+        let r = random_bigint(256);
+        let start = Instant::now();
+        let proof = prove(&v, &r, &a, &b, &params).expect("v lies in [a, b]");
+        prove_samples.push(start.elapsed());
+        let start = Instant::now();
+        all_verified &= verify(&proof, &params);
+        verify_samples.push(start.elapsed());
+        last = Some(proof);
+    }
+    let proof = last.expect("iterations > 0");
+
+    let setup_sample = config.setup_sample.then(|| {
+        let start = Instant::now();
+        trusted_setup_with(config.bits, true).expect("bench sizes are allowed with allow_insecure");
+        start.elapsed()
+    });
+    MachineBenchReport {
+        config: *config,
+        params_time,
+        prove: Timings::of(&mut prove_samples),
+        verify: Timings::of(&mut verify_samples),
+        proof_size_bytes: proof_size_bytes(&proof),
+        encoded_size_bytes: crate::util::encode_proof(&proof).map(|bytes| bytes.len()).unwrap_or(0),
+        setup_sample,
+        all_verified,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,6 +363,32 @@ mod tests {
         assert!(result.proof_size_bytes > 0);
     }
 
+    #[test]
+    fn test_benchmark_setup_threads() {
+        let results = benchmark_setup_threads(256, &[1, 2], 1);
+        assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    // Purpose: ensure bench_machine measures every iteration over throwaway params of the requested size
+    // Params: 256-bit throwaway params, an 8-bit range, 3 iterations; hand-made samples for Timings
+    // Output: proofs verify, sizes are non-zero, median <= p95; Timings of 1..=20 ms has mean 10.5, median 10.5, p95 19 ms
+    // Usage: `cargo test -- src::benchmark` or `cargo test`
+    #[test]
+    fn machine_bench_reports_timings() {
+        let params = throwaway_params(256, &mut rand::rngs::OsRng);
+        assert!(params.is_insecure() && params.n().bits() == 256);
+        let config = MachineBenchConfig { bits: 256, range_bits: 8, iterations: 3, setup_sample: false };
+        let report = bench_machine(&config);
+        assert!(report.all_verified);
+        assert!(report.proof_size_bytes > 0 && report.encoded_size_bytes > 0);
+        assert!(report.prove.median <= report.prove.p95 && report.verify.median <= report.verify.p95);
+        assert_eq!(report.setup_sample, None);
+
+        let mut samples: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+        let timings = Timings::of(&mut samples);
+        assert_eq!((timings.mean, timings.median, timings.p95), (Duration::from_micros(10_500), Duration::from_micros(10_500), Duration::from_millis(19)));
+    }
+
     #[test]
     fn test_benchmark_multiple_ranges() {
         let range_lengths = vec![8, 16, 32];
@@ -0,0 +1,43 @@
+//! Write the known-answer vectors: `cargo run --features vectors --bin cuproof-vectors [dir]`
+//!
+//! Writes one `<name>.json` per vectors::VECTOR_CASES entry into `dir` (tests/vectors by default)
+//! and leaves unchanged files alone. A file whose contents would change while its version equals
+//! VECTORS_VERSION is not rewritten: bump the version first, so implementations outside this
+//! crate can tell the vectors they were tested against are out of date.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use cuproof::vectors::{render_vector, vector_file_version, vectors_params, VECTORS_VERSION, VECTOR_CASES};
+
+fn main() -> ExitCode {
+    let dir = std::env::args().nth(1).map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors")));
+    let params = vectors_params();
+    let mut refused = 0;
+    for case in VECTOR_CASES {
+        let path = dir.join(format!("{}.json", case.name));
+        let text = match render_vector(case, &params) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("{}: prover refused: {}", case.name, e);
+                return ExitCode::FAILURE;
+            }
+        };
+        let existing = std::fs::read_to_string(&path).ok();
+        match existing {
+            Some(old) if old == text => println!("{}: unchanged", path.display()),
+            Some(old) if vector_file_version(&old) == Some(VECTORS_VERSION) => {
+                eprintln!("{}: contents changed but the version is still {}; bump VECTORS_VERSION", path.display(), VECTORS_VERSION);
+                refused += 1;
+            }
+            _ => {
+                if let Err(e) = std::fs::write(&path, &text) {
+                    eprintln!("{}: {}", path.display(), e);
+                    return ExitCode::FAILURE;
+                }
+                println!("{}: written", path.display());
+            }
+        }
+    }
+    if refused == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
@@ -0,0 +1,426 @@
+//! Borsh encoding of proofs and range statements, for verifiers running in on-chain programs
+//!
+//! Cuproof, IPPProof and Statement implement the `borsh` crate's BorshSerialize and
+//! BorshDeserialize. The bytes are exactly what borsh derives for the structs below, so a contract
+//! can declare them with `#[derive(BorshDeserialize)]` and read this module's output directly:
+//!
+//! ```text
+//! struct Cuproof {
+//!     version: u8,                       // BORSH_FORMAT_VERSION
+//!     epoch: u64,
+//!     A: Vec<u8>, S: Vec<u8>, T1: Vec<u8>, T2: Vec<u8>, tau_x: Vec<u8>, mu: Vec<u8>, t_hat: Vec<u8>,
+//!     C: Vec<u8>, C_v1: Vec<u8>, C_v2: Vec<u8>, t0: Vec<u8>, t1: Vec<u8>, t2: Vec<u8>,
+//!     tau1: Vec<u8>, tau2: Vec<u8>,
+//!     ipp_proof: IPPProof,
+//! }
+//! struct IPPProof { L: Vec<Vec<u8>>, R: Vec<Vec<u8>>, a: Vec<u8>, b: Vec<u8> }
+//! struct Statement { version: u8, a: Vec<u8>, b: Vec<u8>, params_fingerprint: [u8; 32] }
+//! ```
+//!
+//! As in borsh, integers are little-endian and a `Vec` is a `u32` element count followed by its
+//! elements. A big integer is the little-endian bytes of its magnitude with no zero byte at the
+//! most significant end; zero is the empty vector. Negative values cannot be encoded (honest
+//! proofs never hold one).
+//!
+//! Decoding accepts only what encoding produces: lengths are checked against MAX_PROOF_INT_BYTES
+//! and MAX_IPP_ROUNDS before anything is allocated, non-canonical integers and trailing bytes are
+//! rejected, and decoded proofs pass the checks of load_proof. The trait methods report a
+//! rejection as an io::Error carrying the BorshError; to_borsh and from_borsh return it typed.
+//! testdata/proof_v1.borsh is a reference encoding (see the tests below for the values it holds)
+
+use ::borsh::io::{self, Read, Write};
+use ::borsh::{BorshDeserialize, BorshSerialize};
+use num_bigint::{BigInt, BigUint, Sign};
+use std::fmt;
+use crate::range_proof::{Cuproof, IPPProof};
+use crate::setup::Params;
+use crate::util::{check_header_nonzero, check_ipp_lengths, DecodeError, EncodeError, MAX_PROOF_INT_BYTES};
+
+/// Version field of every proof and statement written by this module
+pub const BORSH_FORMAT_VERSION: u8 = 1;
+
+/// Why a borsh decoder rejected its input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BorshError {
+    /// a field is truncated, non-canonical or too large
+    Field(DecodeError),
+    /// the version field is not BORSH_FORMAT_VERSION
+    UnsupportedVersion(u8),
+    /// bytes follow the last field
+    TrailingBytes(usize),
+    /// the decoded proof fails the checks of load_proof
+    InvalidProof(&'static str),
+    /// the statement was made for params with another fingerprint
+    ParamsMismatch,
+    /// the statement's lower bound exceeds its upper bound
+    EmptyRange,
+    /// the reader failed for a reason of its own
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for BorshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorshError::Field(e) => write!(f, "bad borsh field: {}", e),
+            BorshError::UnsupportedVersion(v) => write!(f, "unsupported borsh format version {}", v),
+            BorshError::TrailingBytes(n) => write!(f, "{} unexpected bytes after the last field", n),
+            BorshError::InvalidProof(msg) => write!(f, "invalid proof: {}", msg),
+            BorshError::ParamsMismatch => write!(f, "statement was made for different params"),
+            BorshError::EmptyRange => write!(f, "statement range is empty (a > b)"),
+            BorshError::Io(kind) => write!(f, "borsh reader failed: {}", kind),
+        }
+    }
+}
+
+impl core::error::Error for BorshError {}
+
+impl From<DecodeError> for BorshError {
+    fn from(e: DecodeError) -> BorshError {
+        BorshError::Field(e)
+    }
+}
+
+impl From<BorshError> for io::Error {
+    fn from(e: BorshError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// The BorshError a trait method reported, or Io for a failure of the reader itself
+fn borsh_error(e: io::Error) -> BorshError {
+    let kind = e.kind();
+    match e.into_inner().map(|inner| inner.downcast::<BorshError>()) {
+        Some(Ok(e)) => *e,
+        _ => BorshError::Io(kind),
+    }
+}
+
+/// The encoding of `value` as a fresh vector
+/// - returns: bytes, or EncodeError::Negative for a negative integer and TooWide for a vector of
+///   more than u32::MAX elements
+/// - usage: `to_borsh(&statement)`, like borsh's `to_vec` with a typed error
+pub fn to_borsh<T: BorshSerialize>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    let mut out = Vec::new();
+    value.serialize(&mut out).map_err(|e| {
+        e.into_inner().and_then(|inner| inner.downcast::<EncodeError>().ok()).map_or(EncodeError::TooWide { needed: 8, width: 4 }, |e| *e)
+    })?;
+    Ok(out)
+}
+
+/// Decode a complete document; nothing may follow the value
+/// - usage: `from_borsh::<Statement>(&bytes)`, like borsh's `from_slice` with a typed error
+pub fn from_borsh<T: BorshDeserialize>(bytes: &[u8]) -> Result<T, BorshError> {
+    let mut rest = bytes;
+    let value = T::deserialize(&mut rest).map_err(borsh_error)?;
+    if !rest.is_empty() { return Err(BorshError::TrailingBytes(rest.len())); }
+    Ok(value)
+}
+
+/// The claim a proof makes: its value lies in [a, b] under the params with this fingerprint
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub a: BigInt,
+    pub b: BigInt,
+    /// SHA-256 fingerprint of the params (Params::fingerprint)
+    pub params_fingerprint: [u8; 32],
+}
+
+impl Statement {
+    /// The statement a <= v <= b under `params`
+    pub fn new(a: &BigInt, b: &BigInt, params: &Params) -> Statement {
+        Statement { a: a.clone(), b: b.clone(), params_fingerprint: params.fingerprint() }
+    }
+
+    /// The bounds (a, b) of a statement made for `params`
+    /// - returns: (a, b), or ParamsMismatch when the fingerprint names other params and EmptyRange for a > b
+    /// - usage: pass to verify::cuproof_verify_with_range together with the proof
+    pub fn bounds(&self, params: &Params) -> Result<(BigInt, BigInt), BorshError> {
+        if self.params_fingerprint != params.fingerprint() { return Err(BorshError::ParamsMismatch); }
+        if self.a > self.b { return Err(BorshError::EmptyRange); }
+        Ok((self.a.clone(), self.b.clone()))
+    }
+}
+
+fn put_len<W: Write>(w: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, EncodeError::TooWide { needed: 8, width: 4 }))?;
+    w.write_all(&len.to_le_bytes())
+}
+
+fn put_int<W: Write>(w: &mut W, x: &BigInt) -> io::Result<()> {
+    if x.sign() == Sign::Minus { return Err(io::Error::new(io::ErrorKind::InvalidInput, EncodeError::Negative)); }
+    let bytes = if x.sign() == Sign::NoSign { Vec::new() } else { x.magnitude().to_bytes_le() };
+    put_len(w, bytes.len())?;
+    w.write_all(&bytes)
+}
+
+fn put_ints<W: Write>(w: &mut W, xs: &[BigInt]) -> io::Result<()> {
+    put_len(w, xs.len())?;
+    xs.iter().try_for_each(|x| put_int(w, x))
+}
+
+/// Exactly `len` bytes, or Truncated naming how many were left
+fn take<R: Read>(r: &mut R, len: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len { return Err(BorshError::Field(DecodeError::Truncated { needed: len, available: bytes.len() }).into()); }
+    Ok(bytes)
+}
+
+fn array<R: Read, const N: usize>(r: &mut R) -> io::Result<[u8; N]> {
+    Ok(take(r, N)?.try_into().expect("take returned N bytes"))
+}
+
+fn len<R: Read>(r: &mut R) -> io::Result<usize> {
+    Ok(u32::from_le_bytes(array(r)?) as usize)
+}
+
+fn int<R: Read>(r: &mut R) -> io::Result<BigInt> {
+    let len = len(r)?;
+    if len > MAX_PROOF_INT_BYTES { return Err(BorshError::Field(DecodeError::TooLarge(len)).into()); }
+    let bytes = take(r, len)?;
+    if bytes.last() == Some(&0) { return Err(BorshError::Field(DecodeError::LeadingZero).into()); }
+    Ok(BigInt::from(BigUint::from_bytes_le(&bytes)))
+}
+
+/// A `Vec` of integers; the count is checked by check_ipp_lengths before anything is allocated
+fn ints<R: Read>(r: &mut R) -> io::Result<Vec<BigInt>> {
+    let count = len(r)?;
+    check_ipp_lengths(count, count).map_err(BorshError::InvalidProof)?;
+    (0..count).map(|_| int(r)).collect()
+}
+
+fn check_version(version: u8) -> io::Result<()> {
+    if version != BORSH_FORMAT_VERSION { return Err(BorshError::UnsupportedVersion(version).into()); }
+    Ok(())
+}
+
+impl BorshSerialize for IPPProof {
+    fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        put_ints(w, &self.L)?;
+        put_ints(w, &self.R)?;
+        put_int(w, &self.a)?;
+        put_int(w, &self.b)
+    }
+}
+
+impl BorshDeserialize for IPPProof {
+    #[allow(non_snake_case)]
+    fn deserialize_reader<R: Read>(r: &mut R) -> io::Result<IPPProof> {
+        let L = ints(r)?;
+        let R = ints(r)?;
+        check_ipp_lengths(L.len(), R.len()).map_err(BorshError::InvalidProof)?;
+        Ok(IPPProof { L, R, a: int(r)?, b: int(r)? })
+    }
+}
+
+impl BorshSerialize for Cuproof {
+    fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&[BORSH_FORMAT_VERSION])?;
+        w.write_all(&self.epoch.to_le_bytes())?;
+        let ints = [
+            &self.A, &self.S, &self.T1, &self.T2, &self.tau_x, &self.mu, &self.t_hat, &self.C, &self.C_v1,
+            &self.C_v2, &self.t0, &self.t1, &self.t2, &self.tau1, &self.tau2,
+        ];
+        for x in ints { put_int(w, x)?; }
+        self.ipp_proof.serialize(w)
+    }
+}
+
+impl BorshDeserialize for Cuproof {
+    #[allow(non_snake_case)]
+    fn deserialize_reader<R: Read>(r: &mut R) -> io::Result<Cuproof> {
+        let [version] = array(r)?;
+        check_version(version)?;
+        let epoch = u64::from_le_bytes(array(r)?);
+        let mut ints = (0..15).map(|_| int(r)).collect::<io::Result<Vec<_>>>()?.into_iter();
+        let mut next = || ints.next().expect("fifteen integers were decoded");
+        let (A, S, T1, T2) = (next(), next(), next(), next());
+        let (tau_x, mu, t_hat, C, C_v1, C_v2) = (next(), next(), next(), next(), next(), next());
+        let (t0, t1, t2, tau1, tau2) = (next(), next(), next(), next(), next());
+        let ipp_proof = IPPProof::deserialize_reader(r)?;
+        for x in [&A, &S, &T1, &T2] { check_header_nonzero(x).map_err(BorshError::InvalidProof)?; }
+        Ok(Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof, epoch, statement: None, assist: None })
+    }
+}
+
+impl BorshSerialize for Statement {
+    fn serialize<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&[BORSH_FORMAT_VERSION])?;
+        put_int(w, &self.a)?;
+        put_int(w, &self.b)?;
+        w.write_all(&self.params_fingerprint)
+    }
+}
+
+impl BorshDeserialize for Statement {
+    fn deserialize_reader<R: Read>(r: &mut R) -> io::Result<Statement> {
+        let [version] = array(r)?;
+        check_version(version)?;
+        let (a, b) = (int(r)?, int(r)?);
+        Ok(Statement { a, b, params_fingerprint: array(r)? })
+    }
+}
+
+/// Encode a proof as the borsh `Cuproof` above
+/// - returns: bytes, or EncodeError::Negative if a field is negative (honest proofs never are)
+pub fn proof_to_borsh(proof: &Cuproof) -> Result<Vec<u8>, EncodeError> {
+    to_borsh(proof)
+}
+
+/// Decode a borsh `Cuproof`, running the checks of load_proof
+/// - returns: Cuproof or the BorshError of the first problem found
+pub fn proof_from_borsh(bytes: &[u8]) -> Result<Cuproof, BorshError> {
+    from_borsh(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+    use crate::verify::{verify, cuproof_verify_with_range};
+    use num_traits::{One, Zero};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    // A small hand-written proof whose encoding is checked in as testdata/proof_v1.borsh
+    fn golden_proof() -> Cuproof {
+        let int = |x: i64| BigInt::from(x);
+        Cuproof {
+            A: int(1), S: int(2), T1: int(3), T2: int(4), tau_x: int(0), mu: int(6), t_hat: int(7),
+            C: int(8), C_v1: int(9), C_v2: int(10), t0: int(11), t1: int(12), t2: int(13), tau1: int(14), tau2: int(0x1234),
+            ipp_proof: IPPProof { L: vec![int(16), int(17)], R: vec![int(18), int(19)], a: int(20), b: int(0xff00) },
+            epoch: 2,
+            statement: None,
+            assist: None,
+        }
+    }
+
+    // Purpose: ensure the borsh encodings stay byte-stable, deterministic and round-trip through validation
+    // Params: the golden proof, insecure 512-bit params, an honest proof and its statement
+    // Output: the encoding equals the checked-in fixture; decoded values re-encode identically and verify
+    // Usage: `cargo test --features borsh -- src::borsh`
+    #[test]
+    fn borsh_matches_golden_and_round_trips() {
+        let golden = include_bytes!("../testdata/proof_v1.borsh");
+        assert_eq!(proof_to_borsh(&golden_proof()).unwrap(), golden);
+        assert_eq!(&golden[..9], &[1, 2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&golden[9..14], &[1, 0, 0, 0, 1]);
+        assert_eq!(&golden[golden.len() - 6..], &[2, 0, 0, 0, 0x00, 0xff]);
+        let decoded = proof_from_borsh(golden).unwrap();
+        assert_eq!(proof_to_borsh(&decoded).unwrap(), golden);
+
+        let params = insecure_test_setup(512);
+        let (a, b) = (BigInt::from(10), BigInt::from(100));
+        let proof = crate::range_proof::prove(&BigInt::from(30), &BigInt::from(42), &a, &b, &params).unwrap();
+        let bytes = proof_to_borsh(&proof).unwrap();
+        assert_eq!(proof_to_borsh(&proof).unwrap(), bytes);
+        assert!(verify(&proof_from_borsh(&bytes).unwrap(), &params));
+
+        let statement = Statement::new(&a, &b, &params);
+        let bytes = to_borsh(&statement).unwrap();
+        assert_eq!(bytes.len(), 1 + 5 + 5 + 32);
+        let decoded: Statement = from_borsh(&bytes).unwrap();
+        assert_eq!(decoded, statement);
+        let (lo, hi) = decoded.bounds(&params).unwrap();
+        assert!(cuproof_verify_with_range(&proof, &params, &lo, &hi));
+        let other = Statement { params_fingerprint: [0; 32], ..decoded.clone() };
+        assert_eq!(other.bounds(&params), Err(BorshError::ParamsMismatch));
+        assert_eq!(Statement { a: b.clone(), b: a.clone(), ..decoded }.bounds(&params), Err(BorshError::EmptyRange));
+    }
+
+    // The structs of the module documentation, as a contract would declare them
+    #[allow(non_snake_case)]
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    #[borsh(crate = "::borsh")]
+    struct ContractProof {
+        version: u8,
+        epoch: u64,
+        A: Vec<u8>, S: Vec<u8>, T1: Vec<u8>, T2: Vec<u8>, tau_x: Vec<u8>, mu: Vec<u8>, t_hat: Vec<u8>,
+        C: Vec<u8>, C_v1: Vec<u8>, C_v2: Vec<u8>, t0: Vec<u8>, t1: Vec<u8>, t2: Vec<u8>,
+        tau1: Vec<u8>, tau2: Vec<u8>,
+        ipp_proof: ContractIpp,
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    #[borsh(crate = "::borsh")]
+    struct ContractIpp { L: Vec<Vec<u8>>, R: Vec<Vec<u8>>, a: Vec<u8>, b: Vec<u8> }
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    #[borsh(crate = "::borsh")]
+    struct ContractStatement { version: u8, a: Vec<u8>, b: Vec<u8>, params_fingerprint: [u8; 32] }
+
+    // Purpose: ensure the encoding is what `#[derive(BorshDeserialize)]` reads, as the module
+    //          documentation promises contract authors
+    // Params: the golden fixture, an honest proof and its statement under insecure 512-bit params
+    // Output: the derived structs read every document, hold the little-endian magnitudes and write
+    //         the same bytes back
+    // Usage: `cargo test --features borsh -- src::borsh`
+    #[test]
+    fn borsh_matches_derived_structs() {
+        let le = |x: &BigInt| if x.is_zero() { Vec::new() } else { x.magnitude().to_bytes_le() };
+        let golden = include_bytes!("../testdata/proof_v1.borsh");
+        let contract: ContractProof = ::borsh::from_slice(golden).unwrap();
+        assert_eq!((contract.version, contract.epoch, contract.tau2.clone()), (BORSH_FORMAT_VERSION, 2, vec![0x34, 0x12]));
+        assert_eq!(contract.ipp_proof.L, vec![vec![16], vec![17]]);
+        assert_eq!(::borsh::to_vec(&contract).unwrap(), golden);
+
+        let params = insecure_test_setup(512);
+        let (a, b) = (BigInt::from(10), BigInt::from(100));
+        let proof = crate::range_proof::prove(&BigInt::from(30), &BigInt::from(42), &a, &b, &params).unwrap();
+        let bytes = proof_to_borsh(&proof).unwrap();
+        let contract: ContractProof = ::borsh::from_slice(&bytes).unwrap();
+        assert_eq!((&contract.C, &contract.ipp_proof.R[0]), (&le(&proof.C), &le(&proof.ipp_proof.R[0])));
+        assert_eq!(::borsh::to_vec(&contract).unwrap(), bytes);
+
+        let statement = Statement::new(&a, &b, &params);
+        let bytes = to_borsh(&statement).unwrap();
+        let contract: ContractStatement = ::borsh::from_slice(&bytes).unwrap();
+        assert_eq!(contract, ContractStatement { version: BORSH_FORMAT_VERSION, a: vec![10], b: vec![100], params_fingerprint: params.fingerprint() });
+    }
+
+    // Purpose: ensure decoding rejects damaged, oversized or invalid documents before allocating
+    // Params: the golden encoding with one field changed, truncated or extended, hand-built length
+    //         prefixes past the limits, and seeded random byte strings
+    // Output: one BorshError per defect; random input is an error, never a panic
+    // Usage: `cargo test --features borsh -- src::borsh`
+    #[test]
+    fn borsh_rejects_invalid_documents() {
+        let golden = include_bytes!("../testdata/proof_v1.borsh").to_vec();
+        let mut version = golden.clone();
+        version[0] = 2;
+        assert_eq!(proof_from_borsh(&version).err(), Some(BorshError::UnsupportedVersion(2)));
+        let mut padded = golden[..9].to_vec();
+        padded.extend_from_slice(&[2, 0, 0, 0, 1, 0]);
+        padded.extend_from_slice(&golden[14..]);
+        assert_eq!(proof_from_borsh(&padded).err(), Some(BorshError::Field(DecodeError::LeadingZero)));
+        let mut huge = golden[..9].to_vec();
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(proof_from_borsh(&huge).err(), Some(BorshError::Field(DecodeError::TooLarge(u32::MAX as usize))));
+        assert!(matches!(proof_from_borsh(&golden[..golden.len() - 1]), Err(BorshError::Field(DecodeError::Truncated { .. }))));
+        let mut trailing = golden.clone();
+        trailing.push(0);
+        assert_eq!(proof_from_borsh(&trailing).err(), Some(BorshError::TrailingBytes(1)));
+
+        let zero_a = Cuproof { A: BigInt::zero(), ..golden_proof() };
+        assert!(matches!(proof_from_borsh(&proof_to_borsh(&zero_a).unwrap()), Err(BorshError::InvalidProof(_))));
+        let no_rounds = Cuproof { ipp_proof: IPPProof { L: vec![], R: vec![], a: BigInt::one(), b: BigInt::one() }, ..golden_proof() };
+        assert!(matches!(proof_from_borsh(&proof_to_borsh(&no_rounds).unwrap()), Err(BorshError::InvalidProof(_))));
+        let many_rounds = IPPProof { L: vec![BigInt::one(); 65], R: vec![BigInt::one(); 65], a: BigInt::one(), b: BigInt::one() };
+        assert!(matches!(from_borsh::<IPPProof>(&to_borsh(&many_rounds).unwrap()), Err(BorshError::InvalidProof(_))));
+        let negative = Cuproof { mu: BigInt::from(-6), ..golden_proof() };
+        assert_eq!(proof_to_borsh(&negative), Err(EncodeError::Negative));
+
+        let mut rng = ChaCha20Rng::seed_from_u64(138);
+        for _ in 0..500 {
+            let len = rng.gen_range(0..256);
+            let mut bytes: Vec<u8> = (0..len).map(|_| rng.r#gen()).collect();
+            assert!(proof_from_borsh(&bytes).is_err());
+            let _ = from_borsh::<Statement>(&bytes);
+            if !bytes.is_empty() {
+                bytes[0] = BORSH_FORMAT_VERSION;
+                assert!(proof_from_borsh(&bytes).is_err());
+            }
+        }
+    }
+}
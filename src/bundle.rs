@@ -0,0 +1,256 @@
+//! Proof bundles: a proof, the statement it proves and the params it was made under, in one file
+//!
+//! A bundle records the fingerprint of its params and may embed the params themselves; verify asks
+//! a ParamsResolver for the params with that fingerprint, so the verifier decides where params come
+//! from: a file it already trusts (ParamsFile, or Params itself), a directory of them
+//! (ParamsDirectory), or the copy in the bundle (EmbeddedParams). Embedded params were chosen by
+//! whoever made the bundle, who may know the factorization of n: accept them only when that
+//! party is trusted with the setup, e.g. for offline checks of one's own proofs.
+//!
+//! The file layout is in util::save_bundle; the container's SHA-256 covers all of it.
+
+use alloc::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use crate::range_proof::{Cuproof, Statement};
+use crate::setup::{FingerprintHex, Params};
+use crate::util::load_params;
+use crate::verify::{verify_statement, VerifyError};
+
+/// Most metadata entries accepted in a bundle from outside
+pub const MAX_BUNDLE_METADATA: usize = 256;
+
+/// A proof with its statement and the fingerprint of its params, the params optionally embedded
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProofBundle {
+    pub params_fingerprint: [u8; 32],
+    /// the params themselves, for verifiers without a copy (see the module docs)
+    pub params: Option<Params>,
+    pub statement: Statement,
+    pub proof: Cuproof,
+    /// free-form `key value` pairs, e.g. who made the proof and for what; keys hold no whitespace,
+    /// values no line breaks
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl ProofBundle {
+    /// The bundle of `proof` for `statement` under `params`, recording their fingerprint only
+    /// - usage: `ProofBundle::new(&params, statement, proof).with_params(params)` to embed them
+    pub fn new(params: &Params, statement: Statement, proof: Cuproof) -> ProofBundle {
+        ProofBundle { params_fingerprint: params.fingerprint(), params: None, statement, proof, metadata: BTreeMap::new() }
+    }
+
+    /// Embed `params`, and record their fingerprint
+    pub fn with_params(mut self, params: Params) -> ProofBundle {
+        self.params_fingerprint = params.fingerprint();
+        self.params = Some(params);
+        self
+    }
+
+    /// Set the metadata entry `key`
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> ProofBundle {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Verify the proof as a proof of the bundle's statement under the params `resolver` finds
+    /// - params: resolver, e.g. `&ParamsFile("params.bin")` or `&EmbeddedParams`
+    /// - returns: Ok(()), or UnknownParams / Resolver / WrongParams when no params were found, and
+    ///   Verify with the error of verify::verify_statement otherwise: a statement that disagrees
+    ///   with the proof's commitments, range or params is StatementMismatch. A proof without its
+    ///   own statement is checked against the bundle's
+    pub fn verify(&self, resolver: &dyn ParamsResolver) -> Result<(), BundleError> {
+        let expected = FingerprintHex(self.params_fingerprint);
+        let params = resolver.resolve(&self.params_fingerprint, self.params.as_ref())
+            .map_err(BundleError::Resolver)?
+            .ok_or(BundleError::UnknownParams(expected))?;
+        let found = params.fingerprint_hex();
+        if found != expected { return Err(BundleError::WrongParams { expected, found }); }
+        let proof = match &self.proof.statement {
+            Some(_) => Cow::Borrowed(&self.proof),
+            None => Cow::Owned(Cuproof { statement: Some(self.statement.clone()), ..self.proof.clone() }),
+        };
+        verify_statement(&proof, &params, &self.statement).map_err(BundleError::Verify)
+    }
+}
+
+/// Where ProofBundle::verify gets the params of a fingerprint
+pub trait ParamsResolver {
+    /// The params whose fingerprint is `fingerprint`
+    /// - params: fingerprint, embedded the bundle's own copy, if it has one
+    /// - returns: Ok(None) when this resolver has no such params, an error when looking failed
+    fn resolve(&self, fingerprint: &[u8; 32], embedded: Option<&Params>) -> io::Result<Option<Params>>;
+}
+
+/// Params the caller already holds resolve their own fingerprint
+impl ParamsResolver for Params {
+    fn resolve(&self, fingerprint: &[u8; 32], _embedded: Option<&Params>) -> io::Result<Option<Params>> {
+        Ok((self.fingerprint() == *fingerprint).then(|| self.clone()))
+    }
+}
+
+/// The params embedded in the bundle; see the module docs before trusting them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmbeddedParams;
+
+impl ParamsResolver for EmbeddedParams {
+    fn resolve(&self, fingerprint: &[u8; 32], embedded: Option<&Params>) -> io::Result<Option<Params>> {
+        Ok(embedded.filter(|params| params.fingerprint() == *fingerprint).cloned())
+    }
+}
+
+/// The params file at a path, loaded with load_params
+#[derive(Debug, Clone, Copy)]
+pub struct ParamsFile<'a>(pub &'a str);
+
+impl ParamsResolver for ParamsFile<'_> {
+    fn resolve(&self, fingerprint: &[u8; 32], _embedded: Option<&Params>) -> io::Result<Option<Params>> {
+        Params::resolve(&load_params(self.0)?, fingerprint, None)
+    }
+}
+
+/// The params files in a directory: the first, by file name, that loads and has the fingerprint;
+/// other files are skipped
+#[derive(Debug, Clone, Copy)]
+pub struct ParamsDirectory<'a>(pub &'a str);
+
+impl ParamsResolver for ParamsDirectory<'_> {
+    fn resolve(&self, fingerprint: &[u8; 32], _embedded: Option<&Params>) -> io::Result<Option<Params>> {
+        let mut paths = fs::read_dir(self.0)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.sort();
+        let found = paths.iter()
+            .filter(|path| path.is_file())
+            .filter_map(|path| path.to_str().and_then(|path| load_params(path).ok()))
+            .find(|params| params.fingerprint() == *fingerprint);
+        Ok(found)
+    }
+}
+
+/// Why ProofBundle::verify rejected a bundle
+#[derive(Debug)]
+pub enum BundleError {
+    /// the resolver has no params with the bundle's fingerprint
+    UnknownParams(FingerprintHex),
+    /// the resolver failed, e.g. its params file did not load
+    Resolver(io::Error),
+    /// the resolver returned params with another fingerprint
+    WrongParams { expected: FingerprintHex, found: FingerprintHex },
+    /// the proof does not verify as a proof of the statement under the resolved params
+    Verify(VerifyError),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::UnknownParams(fingerprint) => write!(f, "no params with fingerprint {}", fingerprint),
+            BundleError::Resolver(e) => write!(f, "could not look up the params: {}", e),
+            BundleError::WrongParams { expected, found } => write!(f, "expected params with fingerprint {}, got {}", expected, found),
+            BundleError::Verify(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl core::error::Error for BundleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_proof;
+    use crate::util::{load_bundle, save_bundle, save_params};
+    use num_bigint::BigInt;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("cuproof_bundle_{}_{}", name, std::process::id()));
+        path.to_str().unwrap().to_string()
+    }
+
+    // Purpose: ensure a bundle with embedded params verifies on its own and survives save/load
+    // Params: sample_proof(198) bundled with its params and one metadata entry
+    // Output: the loaded bundle equals the saved one and verifies with EmbeddedParams; without
+    //         the embedded copy EmbeddedParams finds nothing
+    // Usage: `cargo test -- src::bundle` or `cargo test`
+    #[test]
+    fn embedded_params_verify() {
+        let (params, statement, proof) = sample_proof(198);
+        let bundle = ProofBundle::new(&params, statement, proof).with_params(params.clone()).with_metadata("prover", "alice at example.org");
+        let path = temp_path("embedded.bin");
+        save_bundle(&path, &bundle).unwrap();
+        let loaded = load_bundle(&path).unwrap();
+        assert!(loaded == bundle);
+        assert_eq!(loaded.metadata.get("prover").map(String::as_str), Some("alice at example.org"));
+        assert!(loaded.verify(&EmbeddedParams).is_ok());
+
+        let bare = ProofBundle { params: None, ..loaded };
+        assert!(matches!(bare.verify(&EmbeddedParams), Err(BundleError::UnknownParams(f)) if f == params.fingerprint_hex()));
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure bundles without params verify against params the resolver supplies
+    // Params: sample_proof(199) bundled without params; the params themselves, their file, a
+    //         directory holding them after a stray file, and other params
+    // Output: Ok(()) from Params, ParamsFile and ParamsDirectory; UnknownParams from other params
+    //         and from a directory without a match; Resolver for a missing file
+    // Usage: `cargo test -- src::bundle` or `cargo test`
+    #[test]
+    fn resolver_supplied_params_verify() {
+        let (params, statement, proof) = sample_proof(199);
+        let bundle = ProofBundle::new(&params, statement, proof);
+        assert!(bundle.verify(&params).is_ok());
+
+        let dir = temp_path("params_dir");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(format!("{}/a_stray.txt", dir), "not params").unwrap();
+        let params_path = format!("{}/b_params.bin", dir);
+        save_params(&params_path, &params).unwrap();
+        assert!(bundle.verify(&ParamsFile(&params_path)).is_ok());
+        assert!(bundle.verify(&ParamsDirectory(&dir)).is_ok());
+
+        let other = crate::setup::fast_test_setup_seeded(199);
+        assert!(matches!(bundle.verify(&other), Err(BundleError::UnknownParams(_))));
+        save_params(&params_path, &other).unwrap();
+        assert!(matches!(bundle.verify(&ParamsDirectory(&dir)), Err(BundleError::UnknownParams(_))));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(bundle.verify(&ParamsFile(&params_path)), Err(BundleError::Resolver(_))));
+    }
+
+    // Purpose: ensure a bundle whose statement disagrees with the proof transcript is rejected
+    // Params: sample_proof(200) with its statement's commitment, bit width, range or label changed;
+    //         the proof with and without its own statement
+    // Output: Verify(StatementMismatch) naming the field, however the params are resolved; a proof
+    //         without a statement is held to the commitment and bit width of the bundle's
+    // Usage: `cargo test -- src::bundle` or `cargo test`
+    #[test]
+    fn disagreeing_statement_is_rejected() {
+        let (params, statement, proof) = sample_proof(200);
+        let forge = |change: &dyn Fn(&mut Statement)| {
+            let mut forged = statement.clone();
+            change(&mut forged);
+            forged
+        };
+        let cases = [
+            (forge(&|s| s.commitment += BigInt::from(1)), "commitment"),
+            (forge(&|s| s.bits = 32), "bit width"),
+            (forge(&|s| s.b += 1), "range"),
+            (forge(&|s| s.label = "another context".to_string()), "label"),
+        ];
+        for (forged, field) in cases {
+            let bundle = ProofBundle::new(&params, forged, proof.clone()).with_params(params.clone());
+            for resolver in [&params as &dyn ParamsResolver, &EmbeddedParams] {
+                match bundle.verify(resolver) {
+                    Err(BundleError::Verify(VerifyError::StatementMismatch(f))) => assert_eq!(f, field),
+                    other => panic!("{}: {:?}", field, other),
+                }
+            }
+            // range and label are claims the transcript does not bind, so only the proof's own
+            // statement can contradict them
+            let bare = ProofBundle { proof: Cuproof { statement: None, ..proof.clone() }, ..bundle };
+            if ["commitment", "bit width"].contains(&field) {
+                assert!(matches!(bare.verify(&params), Err(BundleError::Verify(VerifyError::StatementMismatch(f))) if f == field));
+            }
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use ciborium::value::{Integer, Value};
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Signed, Zero};
+use std::fmt;
+use crate::range_proof::{Cuproof, IPPProof};
+use crate::setup::{validate_params, Params, ParamsError};
+use crate::util::{check_header_nonzero, check_ipp_lengths, check_proof_int};
+
+/// Value of the version key (0) of every document written by to_cbor
+pub const CBOR_FORMAT_VERSION: u64 = 1;
+
+/// RFC 8949 negative bignum: the byte string holds -1 - x
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+
+/// Why from_cbor rejected a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CborError {
+    /// not a single well-formed CBOR item of the expected shape
+    Malformed(String),
+    /// the version key is not CBOR_FORMAT_VERSION
+    UnsupportedVersion(u64),
+    /// a required key is absent
+    MissingKey(u64),
+    /// a key outside the schema
+    UnknownKey(u64),
+    /// the decoded value fails the checks of load_proof
+    InvalidProof(&'static str),
+    /// the decoded params fail validate_params
+    InvalidParams(ParamsError),
+    /// the bytes decode, but are not the canonical encoding of what they decode to
+    NonCanonical,
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CborError::Malformed(msg) => write!(f, "malformed CBOR: {}", msg),
+            CborError::UnsupportedVersion(v) => write!(f, "unsupported CBOR format version {}", v),
+            CborError::MissingKey(k) => write!(f, "missing CBOR key {}", k),
+            CborError::UnknownKey(k) => write!(f, "unknown CBOR key {}", k),
+            CborError::InvalidProof(msg) => write!(f, "invalid proof: {}", msg),
+            CborError::InvalidParams(e) => write!(f, "invalid params: {}", e),
+            CborError::NonCanonical => write!(f, "CBOR is not in canonical form"),
+        }
+    }
+}
+
+impl core::error::Error for CborError {}
+
+fn malformed(msg: &str) -> CborError {
+    CborError::Malformed(msg.to_string())
+}
+
+/// Big-endian magnitude without leading zeros; zero is the empty string
+fn magnitude_bytes(x: &BigInt) -> Vec<u8> {
+    if x.is_zero() { Vec::new() } else { x.magnitude().to_bytes_be() }
+}
+
+fn int_value(x: &BigInt) -> Value {
+    if x.is_negative() {
+        Value::Tag(TAG_NEGATIVE_BIGNUM, Box::new(Value::Bytes(magnitude_bytes(&(-x - BigInt::one())))))
+    } else {
+        Value::Bytes(magnitude_bytes(x))
+    }
+}
+
+fn int_from(value: Value) -> Result<BigInt, CborError> {
+    let x = match value {
+        Value::Bytes(bytes) => BigInt::from_bytes_be(Sign::Plus, &bytes),
+        // the decoder folds bignums that fit into i128 into integers; the canonical check in
+        // from_cbor rejects plain CBOR integers, which never re-encode to the same bytes
+        Value::Integer(i) => BigInt::from(i128::from(i)),
+        Value::Tag(TAG_NEGATIVE_BIGNUM, inner) => match *inner {
+            Value::Bytes(bytes) => -BigInt::from_bytes_be(Sign::Plus, &bytes) - BigInt::one(),
+            _ => return Err(malformed("negative bignum must wrap a byte string")),
+        },
+        _ => return Err(malformed("expected a big integer byte string")),
+    };
+    check_proof_int(&x).map_err(CborError::InvalidProof)?;
+    Ok(x)
+}
+
+fn uint_from(value: Value) -> Result<u64, CborError> {
+    match value {
+        Value::Integer(i) => u64::try_from(i).map_err(|_| malformed("expected an unsigned integer")),
+        _ => Err(malformed("expected an unsigned integer")),
+    }
+}
+
+fn ints_from(value: Value) -> Result<Vec<BigInt>, CborError> {
+    match value {
+        Value::Array(items) => items.into_iter().map(int_from).collect(),
+        _ => Err(malformed("expected an array of big integers")),
+    }
+}
+
+fn encode(entries: Vec<(u64, Value)>) -> Vec<u8> {
+    let map = Value::Map(entries.into_iter().map(|(k, v)| (Value::Integer(Integer::from(k)), v)).collect());
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&map, &mut bytes).expect("writing CBOR to a Vec cannot fail");
+    bytes
+}
+
+/// Map entries of a decoded document, taken out key by key
+struct Fields(Vec<(u64, Value)>);
+
+impl Fields {
+    fn decode(bytes: &[u8]) -> Result<Fields, CborError> {
+        let value: Value = ciborium::de::from_reader(bytes).map_err(|e| CborError::Malformed(e.to_string()))?;
+        let Value::Map(entries) = value else { return Err(malformed("expected a map")); };
+        let entries = entries.into_iter().map(|(k, v)| Ok((uint_from(k)?, v))).collect::<Result<Vec<_>, CborError>>()?;
+        let mut fields = Fields(entries);
+        let version = uint_from(fields.take(0)?)?;
+        if version != CBOR_FORMAT_VERSION { return Err(CborError::UnsupportedVersion(version)); }
+        Ok(fields)
+    }
+
+    fn optional(&mut self, key: u64) -> Option<Value> {
+        let i = self.0.iter().position(|(k, _)| *k == key)?;
+        Some(self.0.remove(i).1)
+    }
+
+    fn take(&mut self, key: u64) -> Result<Value, CborError> {
+        self.optional(key).ok_or(CborError::MissingKey(key))
+    }
+
+    fn int(&mut self, key: u64) -> Result<BigInt, CborError> {
+        int_from(self.take(key)?)
+    }
+
+    /// Fail on keys nobody took
+    fn finish(self) -> Result<(), CborError> {
+        match self.0.first() {
+            Some((k, _)) => Err(CborError::UnknownKey(*k)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Canonical CBOR (RFC 8949 §4.2.1 core deterministic encoding) of proofs and params
+///
+/// A proof is a map with unsigned keys in ascending order: 0 version, 1 epoch, 2 A, 3 S, 4 T1,
+/// 5 T2, 6 tau_x, 7 mu, 8 t_hat, 9 C, 10 C_v1, 11 C_v2, 12 t0, 13 t1, 14 t2, 15 tau1, 16 tau2,
+/// 17 IPP L (array), 18 IPP R (array), 19 IPP a, 20 IPP b. Big integers are byte strings of the
+/// big-endian magnitude with no leading zeros (zero is empty); negative values are tag 3 bignums.
+///
+/// Policy: decoding is strict. A document is accepted only if re-encoding what it decodes to
+/// reproduces it byte for byte, so reordered keys, non-minimal lengths, indefinite-length items,
+/// padded integers and trailing bytes are all rejected with NonCanonical rather than normalized.
+/// Equal proofs therefore have equal bytes, and hashing to_cbor() gives a stable proof id
+impl Cuproof {
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let ipp = &self.ipp_proof;
+        let mut entries = vec![(0, Value::Integer(Integer::from(CBOR_FORMAT_VERSION))), (1, Value::Integer(Integer::from(self.epoch)))];
+        let scalars = [
+            &self.A, &self.S, &self.T1, &self.T2, &self.tau_x, &self.mu, &self.t_hat, &self.C, &self.C_v1, &self.C_v2,
+            &self.t0, &self.t1, &self.t2, &self.tau1, &self.tau2,
+        ];
+        entries.extend((2u64..).zip(scalars.into_iter().map(int_value)));
+        entries.push((17, Value::Array(ipp.L.iter().map(int_value).collect())));
+        entries.push((18, Value::Array(ipp.R.iter().map(int_value).collect())));
+        entries.push((19, int_value(&ipp.a)));
+        entries.push((20, int_value(&ipp.b)));
+        encode(entries)
+    }
+
+    /// Decode a proof written by to_cbor, running the checks of load_proof
+    /// - returns: Cuproof or the CborError of the first problem found
+    #[allow(non_snake_case)]
+    pub fn from_cbor(bytes: &[u8]) -> Result<Cuproof, CborError> {
+        let mut f = Fields::decode(bytes)?;
+        let epoch = uint_from(f.take(1)?)?;
+        let mut scalars = (2..=16).map(|k| f.int(k)).collect::<Result<Vec<_>, _>>()?.into_iter();
+        let mut next = || scalars.next().expect("fifteen scalars were decoded");
+        let (A, S, T1, T2) = (next(), next(), next(), next());
+        let (tau_x, mu, t_hat, C, C_v1, C_v2) = (next(), next(), next(), next(), next(), next());
+        let (t0, t1, t2, tau1, tau2) = (next(), next(), next(), next(), next());
+        let (L, R) = (ints_from(f.take(17)?)?, ints_from(f.take(18)?)?);
+        let (a, b) = (f.int(19)?, f.int(20)?);
+        f.finish()?;
+        for x in [&A, &S, &T1, &T2] { check_header_nonzero(x).map_err(CborError::InvalidProof)?; }
+        check_ipp_lengths(L.len(), R.len()).map_err(CborError::InvalidProof)?;
+        let proof = Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof: IPPProof { L, R, a, b }, epoch, statement: None, assist: None };
+        if proof.to_cbor() != bytes { return Err(CborError::NonCanonical); }
+        Ok(proof)
+    }
+}
+
+/// Canonical CBOR of params: 0 version, 1 modulus bits, 2 g, 3 h, 4 n, 5 generator seed (text,
+/// omitted without one), 6 epoch, 7 insecure (true, omitted unless set). Like the JSON and serde
+/// forms it carries neither the metadata section nor the `allow_insecure_params` acknowledgement.
+/// Decoding follows the strict policy of Cuproof::to_cbor
+impl Params {
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut entries = vec![
+            (0, Value::Integer(Integer::from(CBOR_FORMAT_VERSION))),
+            (1, Value::Integer(Integer::from(self.bits() as u64))),
+            (2, int_value(self.g())),
+            (3, int_value(self.h())),
+            (4, int_value(self.n())),
+        ];
+        if let Some(seed) = self.generator_seed() { entries.push((5, Value::Text(seed.to_string()))); }
+        entries.push((6, Value::Integer(Integer::from(self.epoch()))));
+        if self.is_insecure() { entries.push((7, Value::Bool(true))); }
+        encode(entries)
+    }
+
+    /// Decode params written by to_cbor and run validate_params on them
+    /// - returns: Params or the CborError of the first problem found
+    pub fn from_cbor(bytes: &[u8]) -> Result<Params, CborError> {
+        let mut f = Fields::decode(bytes)?;
+        let bits = usize::try_from(uint_from(f.take(1)?)?).map_err(|_| malformed("modulus size out of range"))?;
+        let (g, h, n) = (f.int(2)?, f.int(3)?, f.int(4)?);
+        let seed = match f.optional(5) {
+            Some(Value::Text(seed)) => Some(seed),
+            Some(_) => return Err(malformed("generator seed must be text")),
+            None => None,
+        };
+        let epoch = uint_from(f.take(6)?)?;
+        let insecure = match f.optional(7) {
+            Some(Value::Bool(b)) => b,
+            Some(_) => return Err(malformed("insecure flag must be a boolean")),
+            None => false,
+        };
+        f.finish()?;
+        let params = Params::new(g, h, n, bits).with_generator_seed(seed).with_epoch(epoch);
+        let params = if insecure { params.mark_insecure() } else { params };
+        validate_params(&params).map_err(CborError::InvalidParams)?;
+        if params.to_cbor() != bytes { return Err(CborError::NonCanonical); }
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+
+    // A small hand-written proof whose encoding is checked in as testdata/proof_v1.cbor
+    fn golden_proof() -> Cuproof {
+        let int = |x: i64| BigInt::from(x);
+        Cuproof {
+            A: int(1), S: int(2), T1: int(3), T2: int(4), tau_x: int(0), mu: int(-6), t_hat: int(7),
+            C: int(8), C_v1: int(9), C_v2: int(10), t0: int(11), t1: int(12), t2: int(13), tau1: int(14), tau2: int(0x1234),
+            ipp_proof: IPPProof { L: vec![int(16), int(17)], R: vec![int(18), int(19)], a: int(20), b: int(21) },
+            epoch: 2,
+            statement: None,
+            assist: None,
+        }
+    }
+
+    // Purpose: ensure the canonical encodings stay byte-stable and round-trip through validation
+    // Params: the golden proof, insecure 512-bit params and an honest proof over them
+    // Output: encodings equal the checked-in fixtures; decoded values re-encode identically and verify
+    // Usage: `cargo test --features cbor -- src::cbor`
+    #[test]
+    fn cbor_matches_golden_and_round_trips() {
+        let golden = include_bytes!("../testdata/proof_v1.cbor");
+        assert_eq!(golden_proof().to_cbor(), golden);
+        let decoded = Cuproof::from_cbor(golden).unwrap();
+        assert_eq!((decoded.mu.clone(), decoded.tau_x.clone()), (BigInt::from(-6), BigInt::zero()));
+        assert_eq!(decoded.to_cbor(), golden);
+
+        let params = insecure_test_setup(512);
+        assert_eq!(params.to_cbor(), include_bytes!("../testdata/params_v1.cbor"));
+        assert_eq!(Params::from_cbor(&params.to_cbor()).unwrap(), params);
+        let rotated = params.rotate_generators("cbor");
+        assert_eq!(Params::from_cbor(&rotated.to_cbor()).unwrap(), rotated);
+
+        let proof = crate::range_proof::prove(&BigInt::from(42), &crate::util::random_bigint(128), &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+        let decoded = Cuproof::from_cbor(&proof.to_cbor()).unwrap();
+        assert_eq!(decoded.to_cbor(), proof.to_cbor());
+        assert!(crate::verify::verify(&decoded, &params));
+    }
+
+    // Purpose: ensure non-canonical and invalid encodings of the golden proof are rejected, not normalized
+    // Params: the golden fixture edited byte-wise and re-encoded with reordered keys
+    // Output: NonCanonical for alternative encodings of the same proof, the specific error otherwise
+    // Usage: `cargo test --features cbor -- src::cbor`
+    #[test]
+    fn cbor_rejects_non_canonical_and_invalid_documents() {
+        let golden = include_bytes!("../testdata/proof_v1.cbor").to_vec();
+        // map of 21 entries, then key 0 and version 1
+        assert_eq!(golden[..3], [0xb5, 0x00, 0x01]);
+        let edited = |at: usize, remove: usize, insert: &[u8]| {
+            let mut bytes = golden.clone();
+            bytes.splice(at..at + remove, insert.iter().copied());
+            Cuproof::from_cbor(&bytes).err()
+        };
+        // version 1 in a two-byte head
+        assert_eq!(edited(2, 1, &[0x18, 0x01]), Some(CborError::NonCanonical));
+        // A = 0x01 padded to 0x0001
+        assert_eq!(golden[5..8], [0x02, 0x41, 0x01]);
+        assert_eq!(edited(6, 2, &[0x42, 0x00, 0x01]), Some(CborError::NonCanonical));
+        // trailing byte
+        assert_eq!(edited(golden.len(), 0, &[0x00]), Some(CborError::NonCanonical));
+        assert_eq!(edited(2, 1, &[0x02]), Some(CborError::UnsupportedVersion(2)));
+        assert_eq!(edited(6, 2, &[0x40]).unwrap(), CborError::InvalidProof("zero scalar in header"));
+        assert!(matches!(edited(0, 1, &[0xb6]), Some(CborError::Malformed(_))));
+
+        // same entries, keys in descending order
+        let Value::Map(mut entries) = ciborium::de::from_reader::<Value, _>(golden.as_slice()).unwrap() else { panic!("not a map") };
+        entries.reverse();
+        let mut reordered = Vec::new();
+        ciborium::ser::into_writer(&Value::Map(entries.clone()), &mut reordered).unwrap();
+        assert_eq!(Cuproof::from_cbor(&reordered).err(), Some(CborError::NonCanonical));
+        entries.push((Value::Integer(Integer::from(99u64)), Value::Null));
+        entries.reverse();
+        let mut extra = Vec::new();
+        ciborium::ser::into_writer(&Value::Map(entries), &mut extra).unwrap();
+        assert_eq!(Cuproof::from_cbor(&extra).err(), Some(CborError::UnknownKey(99)));
+
+        let mut params = insecure_test_setup(512).to_cbor();
+        let last = params.len() - 1;
+        assert_eq!(params[last], 0xf5);
+        params[last] = 0xf4;
+        assert_eq!(Params::from_cbor(&params).err(), Some(CborError::NonCanonical));
+    }
+}
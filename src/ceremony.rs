@@ -0,0 +1,225 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Zero};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use crate::fiat_shamir::fiat_shamir;
+use crate::setup::{validate_params, Params, SecretInt};
+
+/// Bits of a contributor's secret exponent beyond |n|, so base^s is statistically close to uniform in <base>
+const EXPONENT_SLACK_BITS: usize = 128;
+
+/// Challenge size of the update proofs
+const CHALLENGE_BITS: usize = 128;
+
+/// Bits of the proof nonce beyond |s| + |c|, so the response hides s statistically
+const NONCE_SLACK_BITS: usize = 128;
+
+/// Schnorr-style proof of knowledge of s with `updated = base^s mod n`
+/// Over Z_n^* the group order is unknown, so the response z = k + c * s is an integer and is not reduced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DlogProof {
+    pub commitment: BigInt,
+    pub response: BigInt,
+}
+
+/// Proofs that both generators of a contribution were raised to exponents known to the contributor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContributionProof {
+    pub g: DlogProof,
+    pub h: DlogProof,
+}
+
+/// One step of the generator ceremony: the parameters after the update and the proof linking them
+/// to the previous step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contribution {
+    pub params: Params,
+    pub proof: ContributionProof,
+}
+
+impl Contribution {
+    /// Rerandomize the generators of `prev`: g' = g^s, h' = h^t for fresh secret s, t
+    /// - params: prev parameters of the previous step, entropy participant-supplied bytes
+    /// - returns: the updated Params (same modulus, no generator seed) and the update proof
+    /// - usage: each participant runs this once and publishes both outputs. The entropy is mixed
+    ///   with OS randomness and s, t are wiped before returning, so the discrete log relation
+    ///   between g' and h' stays unknown as long as one participant discarded their exponents
+    pub fn contribute(prev: &Params, entropy: &[u8]) -> (Params, ContributionProof) {
+        let n = prev.n();
+        let mut os_seed = [0u8; 32];
+        OsRng.fill_bytes(&mut os_seed);
+        let s = secret_exponent(b"g", entropy, &os_seed, n);
+        let t = secret_exponent(b"h", entropy, &os_seed, n);
+        os_seed.iter_mut().for_each(|b| *b = 0);
+
+        let g = pow_secret(prev.g(), &s, n);
+        let h = pow_secret(prev.h(), &t, n);
+        let proof = ContributionProof {
+            g: prove_dlog(prev.g(), &g, &s, n),
+            h: prove_dlog(prev.h(), &h, &t, n),
+        };
+        let params = Params::new(g, h, n.clone(), prev.bits());
+        // rerandomized generators do not hide a factorization that is already public
+        (if prev.is_insecure() { params.mark_insecure() } else { params }, proof)
+    }
+
+    pub fn new(params: Params, proof: ContributionProof) -> Self {
+        Contribution { params, proof }
+    }
+}
+
+/// Derive a secret exponent of |n| + EXPONENT_SLACK_BITS bits from participant entropy and OS randomness
+fn secret_exponent(label: &[u8], entropy: &[u8], os_seed: &[u8], n: &BigInt) -> SecretInt {
+    let bits = n.bits() as usize + EXPONENT_SLACK_BITS;
+    let mut wide = Vec::with_capacity(bits.div_ceil(256) * 32);
+    for block in 0..bits.div_ceil(256) as u32 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"cuproof/ceremony/");
+        hasher.update(label);
+        hasher.update((entropy.len() as u64).to_be_bytes());
+        hasher.update(entropy);
+        hasher.update(os_seed);
+        hasher.update(block.to_be_bytes());
+        wide.extend_from_slice(&hasher.finalize());
+    }
+    let secret = SecretInt::new(BigUint::from_bytes_be(&wide) >> (wide.len() * 8 - bits));
+    wide.iter_mut().for_each(|b| *b = 0);
+    secret
+}
+
+/// base^e mod n without copying the secret exponent into an unwiped BigInt
+fn pow_secret(base: &BigInt, e: &SecretInt, n: &BigInt) -> BigInt {
+    BigInt::from_biguint(Sign::Plus, base.magnitude().modpow(e.expose(), n.magnitude()))
+}
+
+/// Challenge for an update proof, bound to the modulus, both generators and the commitment
+fn challenge(base: &BigInt, updated: &BigInt, commitment: &BigInt, n: &BigInt) -> BigInt {
+    fiat_shamir(&[n, base, updated, commitment]) >> (256 - CHALLENGE_BITS)
+}
+
+fn prove_dlog(base: &BigInt, updated: &BigInt, secret: &SecretInt, n: &BigInt) -> DlogProof {
+    let nonce_bits = secret.expose().bits() as usize + CHALLENGE_BITS + NONCE_SLACK_BITS;
+    let mut buf = vec![0u8; nonce_bits.div_ceil(8)];
+    OsRng.fill_bytes(&mut buf);
+    let k = SecretInt::new(BigUint::from_bytes_be(&buf));
+    buf.iter_mut().for_each(|b| *b = 0);
+
+    let commitment = pow_secret(base, &k, n);
+    let c = challenge(base, updated, &commitment, n);
+    let c_u = c.to_biguint().expect("challenge is non-negative");
+    let response = BigInt::from_biguint(Sign::Plus, k.expose() + c_u * secret.expose());
+    DlogProof { commitment, response }
+}
+
+fn verify_dlog(base: &BigInt, updated: &BigInt, proof: &DlogProof, n: &BigInt) -> bool {
+    let max_response_bits = n.bits() as usize + EXPONENT_SLACK_BITS + CHALLENGE_BITS + NONCE_SLACK_BITS + 1;
+    if proof.response < BigInt::zero() || proof.response.bits() as usize > max_response_bits { return false; }
+    if proof.commitment <= BigInt::zero() || &proof.commitment >= n { return false; }
+    let c = challenge(base, updated, &proof.commitment, n);
+    base.modpow(&proof.response, n) == &proof.commitment * updated.modpow(&c, n) % n
+}
+
+/// Check one ceremony step: same modulus and size, valid new generators and both update proofs
+/// - params: prev parameters before the step, contribution the step to check
+/// - returns: true if `contribution.params` was derived from `prev` by a proven rerandomization
+/// - usage: building block of verify_chain; also useful to check a single file pair
+pub fn verify_contribution(prev: &Params, contribution: &Contribution) -> bool {
+    let next = &contribution.params;
+    if next.n() != prev.n() || next.bits() != prev.bits() { return false; }
+    if validate_params(next).is_err() { return false; }
+    let one = BigInt::one();
+    if next.g() == &one || next.h() == &one { return false; }
+    verify_dlog(prev.g(), next.g(), &contribution.proof.g, prev.n())
+        && verify_dlog(prev.h(), next.h(), &contribution.proof.h, prev.n())
+}
+
+/// Verify a whole ceremony transcript
+/// - params: initial dealer parameters, contributions in the order they were made
+/// - returns: true if every step verifies against the one before it
+/// - usage: anyone can run this before adopting the final `contributions.last().params`
+pub fn verify_chain(initial: &Params, contributions: &[Contribution]) -> bool {
+    if validate_params(initial).is_err() { return false; }
+    let mut prev = initial;
+    for contribution in contributions {
+        if !verify_contribution(prev, contribution) { return false; }
+        prev = &contribution.params;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+
+    fn run_chain(initial: &Params, parties: usize) -> Vec<Contribution> {
+        let mut chain: Vec<Contribution> = Vec::new();
+        for i in 0..parties {
+            let prev = chain.last().map(|c| &c.params).unwrap_or(initial);
+            let (params, proof) = Contribution::contribute(prev, format!("participant {}", i).as_bytes());
+            chain.push(Contribution::new(params, proof));
+        }
+        chain
+    }
+
+    // Purpose: ensure a 3-party ceremony verifies and its output params still prove/verify
+    // Params: fast params as the dealer output, three contributions
+    // Output: verify_chain true; fresh generators; a range proof under the final params
+    // Usage: `cargo test -- src::ceremony` or `cargo test`
+    #[test]
+    fn three_party_chain_verifies() {
+        let initial = insecure_test_setup(512);
+        let chain = run_chain(&initial, 3);
+        assert!(verify_chain(&initial, &chain));
+        assert!(verify_chain(&initial, &[]));
+
+        let last = &chain.last().unwrap().params;
+        assert_eq!(last.n(), initial.n());
+        assert_ne!(last.g(), initial.g());
+        assert_ne!(last.h(), initial.h());
+        assert_eq!(last.generator_seed(), None);
+
+        // proofs survive the file format used by the CLI
+        let path = std::env::temp_dir().join(format!("cuproof_contribution_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        crate::util::save_contribution_proof(path, &chain[0].proof).unwrap();
+        assert_eq!(crate::util::load_contribution_proof(path).unwrap(), chain[0].proof);
+        let _ = std::fs::remove_file(path);
+
+        let (a, b, v) = (BigInt::from(0), BigInt::from(1000), BigInt::from(321));
+        let proof = crate::range_proof::prove(&v, &BigInt::from(5), &a, &b, last).unwrap();
+        assert!(crate::verify::verify(&proof, last));
+    }
+
+    // Purpose: ensure forged, reordered or tampered contributions break the chain
+    // Params: a 3-party chain with one step replaced or modified at a time
+    // Output: verify_chain false for each forgery
+    // Usage: `cargo test -- src::ceremony` or `cargo test`
+    #[test]
+    fn forged_contribution_rejected() {
+        let initial = insecure_test_setup(512);
+        let chain = run_chain(&initial, 3);
+
+        // a step whose generators were picked freely, reusing the honest proof
+        let mut forged = chain.clone();
+        let honest = &forged[1].params;
+        let g = honest.g() * honest.g() % honest.n();
+        forged[1].params = Params::new(g, honest.h().clone(), honest.n().clone(), honest.bits());
+        assert!(!verify_chain(&initial, &forged));
+
+        // a step contributed on top of the wrong predecessor
+        let mut skipped = chain.clone();
+        skipped.remove(1);
+        assert!(!verify_chain(&initial, &skipped));
+
+        let mut tampered = chain.clone();
+        tampered[2].proof.h.response += 1;
+        assert!(!verify_chain(&initial, &tampered));
+
+        // a contribution may not switch the modulus
+        let other = crate::setup::fast_test_setup_seeded(1);
+        let (params, proof) = Contribution::contribute(&other, b"x");
+        assert!(!verify_chain(&initial, &[Contribution::new(params, proof)]));
+    }
+}
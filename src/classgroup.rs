@@ -0,0 +1,397 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use crate::group::Group;
+use crate::primality::{is_probable_prime, PrimalityConfig};
+
+/// Smallest discriminant accepted by `ClassGroupParams::from_seed` (keeps generator primes below sqrt|D|)
+pub const MIN_DISCRIMINANT_BITS: usize = 64;
+
+/// Bits of the primes whose ideal classes become g and h
+const GENERATOR_PRIME_BITS: usize = 48;
+
+/// Binary quadratic form a x^2 + b x y + c y^2 of negative discriminant b^2 - 4ac
+/// Positive definite forms of a fixed discriminant D, up to equivalence, form the class group
+/// Cl(D); every class has exactly one reduced form, which is what all operations return
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuadraticForm {
+    pub a: BigInt,
+    pub b: BigInt,
+    pub c: BigInt,
+}
+
+impl QuadraticForm {
+    /// Form (a, b, c) with c recomputed from the discriminant; None if no integral c exists
+    pub fn from_ab(a: BigInt, b: BigInt, discriminant: &BigInt) -> Option<Self> {
+        if !a.is_positive() { return None; }
+        let (c, rem) = (&b * &b - discriminant).div_rem(&(&a * 4));
+        if !rem.is_zero() { return None; }
+        Some(QuadraticForm { a, b, c })
+    }
+
+    pub fn discriminant(&self) -> BigInt {
+        &self.b * &self.b - 4 * &self.a * &self.c
+    }
+
+    /// |b| <= a <= c, and b >= 0 whenever |b| = a or a = c
+    pub fn is_reduced(&self) -> bool {
+        let abs_b = self.b.abs();
+        if abs_b > self.a || self.a > self.c { return false; }
+        if (abs_b == self.a || self.a == self.c) && self.b.is_negative() { return false; }
+        true
+    }
+
+    /// Move b into (-a, a] with an equivalent form (translation x -> x + k y)
+    fn normalize(&mut self) {
+        let two_a = &self.a * 2;
+        let k = (&self.a - &self.b).div_floor(&two_a);
+        if k.is_zero() { return; }
+        // c' = a k^2 + b k + c, b' = b + 2 a k
+        self.c = &self.a * &k * &k + &self.b * &k + &self.c;
+        self.b += &two_a * k;
+    }
+
+    /// The unique reduced form equivalent to this positive definite form
+    pub fn reduce(mut self) -> Self {
+        self.normalize();
+        while self.a > self.c || (self.a == self.c && self.b.is_negative()) {
+            // (a, b, c) ~ (c, -b, a) via (x, y) -> (-y, x)
+            std::mem::swap(&mut self.a, &mut self.c);
+            self.b = -self.b;
+            self.normalize();
+        }
+        self
+    }
+}
+
+/// Cohen, "A Course in Computational Algebraic Number Theory", Algorithm 5.4.7 (composition);
+/// the result is reduced
+fn compose(f1: &QuadraticForm, f2: &QuadraticForm, discriminant: &BigInt) -> QuadraticForm {
+    let (f1, f2) = if f1.a > f2.a { (f2, f1) } else { (f1, f2) };
+    let s: BigInt = (&f1.b + &f2.b) / 2;
+    let n = &f2.b - &s;
+
+    let (d, y1) = if (&f2.a % &f1.a).is_zero() {
+        (f1.a.clone(), BigInt::zero())
+    } else {
+        let e = f2.a.extended_gcd(&f1.a);
+        (e.gcd, e.x)
+    };
+    let (d1, x2, y2) = if (&s % &d).is_zero() {
+        (d, BigInt::zero(), -BigInt::one())
+    } else {
+        let e = s.extended_gcd(&d);
+        (e.gcd, e.x, -e.y)
+    };
+    let v1 = &f1.a / &d1;
+    let v2 = &f2.a / &d1;
+    let r = (&y1 * &y2 * &n - &x2 * &f2.c).mod_floor(&v1);
+    let b3 = &f2.b + 2 * &v2 * &r;
+    let a3 = &v1 * &v2;
+    QuadraticForm::from_ab(a3, b3, discriminant)
+        .expect("composition of forms of discriminant D has discriminant D")
+        .reduce()
+}
+
+/// Class group Cl(D) of a negative prime discriminant D = -p, p = 7 mod 8, derived from a public seed
+///
+/// Nobody knows the class number h(D), and computing it is believed to be as hard as the
+/// factoring problem behind the RSA backend, so binding holds without any trusted dealer: D,
+/// g and h are all recomputable from `seed`. With D prime, h(D) is odd, so there are no
+/// elements of order 2. Around 1665 bits of discriminant are commonly quoted for 128-bit
+/// security; group operations are much slower than modular multiplication at equal security
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassGroupParams {
+    discriminant: BigInt,
+    abs_discriminant: BigInt,
+    g: QuadraticForm,
+    h: QuadraticForm,
+    seed: String,
+    bits: usize,
+}
+
+impl ClassGroupParams {
+    /// Derive D with exactly `discriminant_bits` bits and the generators from `seed`
+    /// - returns: None when discriminant_bits is below MIN_DISCRIMINANT_BITS
+    /// - usage: via `setup::classgroup_params`; the same seed and size always give the same group
+    pub fn from_seed(seed: &str, discriminant_bits: usize) -> Option<Self> {
+        if discriminant_bits < MIN_DISCRIMINANT_BITS { return None; }
+        let mut rng = ChaCha20Rng::from_seed(hash(&[b"cuproof/classgroup/rng", seed.as_bytes()]));
+        let config = PrimalityConfig::default();
+        let top = BigUint::one() << (discriminant_bits - 1);
+        let p = (0u64..)
+            .map(|counter| {
+                let x = expand(&format!("{}/discriminant/{}", seed, discriminant_bits), counter, discriminant_bits);
+                // exactly `discriminant_bits` bits and p = 7 mod 8
+                (x | &top) | BigUint::from(7u32)
+            })
+            .find(|p| is_probable_prime(p, &config, &mut rng))
+            .expect("the candidate stream is unbounded");
+        let discriminant = -BigInt::from_biguint(Sign::Plus, p.clone());
+        let g = hash_to_form(&format!("{}/g", seed), &discriminant, &p, &mut rng);
+        let mut h = hash_to_form(&format!("{}/h", seed), &discriminant, &p, &mut rng);
+        let mut counter = 0;
+        while h == g {
+            counter += 1;
+            h = hash_to_form(&format!("{}/h/{}", seed, counter), &discriminant, &p, &mut rng);
+        }
+        let abs_discriminant = BigInt::from_biguint(Sign::Plus, p);
+        Some(ClassGroupParams { discriminant, abs_discriminant, g, h, seed: seed.to_string(), bits: discriminant_bits })
+    }
+
+    pub fn discriminant(&self) -> &BigInt { &self.discriminant }
+    pub fn seed(&self) -> &str { &self.seed }
+    pub fn bits(&self) -> usize { self.bits }
+
+    /// Width of the b field in `encode`; 2a < 2^width for every reduced form
+    fn encoding_width(&self) -> u64 {
+        self.abs_discriminant.bits() / 2 + 2
+    }
+
+    /// f^-1 = (a, -b, c)
+    pub fn inverse(&self, f: &QuadraticForm) -> QuadraticForm {
+        QuadraticForm { a: f.a.clone(), b: -&f.b, c: f.c.clone() }.reduce()
+    }
+}
+
+impl Group for ClassGroupParams {
+    type Element = QuadraticForm;
+
+    fn g(&self) -> &QuadraticForm { &self.g }
+    fn h(&self) -> &QuadraticForm { &self.h }
+
+    /// The principal form (1, 1, (1 - D) / 4)
+    fn identity(&self) -> QuadraticForm {
+        QuadraticForm::from_ab(BigInt::one(), BigInt::one(), &self.discriminant).expect("D = 1 mod 4")
+    }
+
+    fn op(&self, x: &QuadraticForm, y: &QuadraticForm) -> QuadraticForm {
+        compose(x, y, &self.discriminant)
+    }
+
+    /// Square and multiply; a negative exponent raises the inverse
+    fn pow(&self, base: &QuadraticForm, e: &BigInt) -> QuadraticForm {
+        let base = if e.is_negative() { self.inverse(base) } else { base.clone() };
+        let e = e.magnitude();
+        let mut acc = self.identity();
+        for i in (0..e.bits()).rev() {
+            acc = compose(&acc, &acc, &self.discriminant);
+            if e.bit(i) { acc = compose(&acc, &base, &self.discriminant); }
+        }
+        acc
+    }
+
+    /// a * 2^w + (a + b) with w = encoding_width(); injective on reduced forms
+    fn encode(&self, x: &QuadraticForm) -> BigInt {
+        (&x.a << self.encoding_width()) + &x.a + &x.b
+    }
+
+    fn decode(&self, x: &BigInt) -> Option<QuadraticForm> {
+        if !x.is_positive() { return None; }
+        let w = self.encoding_width();
+        let a = x >> w;
+        let b = (x - (&a << w)) - &a;
+        let f = QuadraticForm::from_ab(a, b, &self.discriminant)?;
+        f.is_reduced().then_some(f)
+    }
+
+    fn challenge_modulus(&self) -> &BigInt { &self.abs_discriminant }
+
+    /// Hash of D and the encoded generators
+    fn fingerprint(&self) -> [u8; 32] {
+        let (_, d) = self.discriminant.to_bytes_be();
+        let (g, h) = (self.encode(&self.g).to_bytes_be().1, self.encode(&self.h).to_bytes_be().1);
+        hash(&[b"cuproof/classgroup", &d, &g, &h])
+    }
+}
+
+fn hash(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// `bits` bits of SHA-256 output over (label, counter, block)
+fn expand(label: &str, counter: u64, bits: usize) -> BigUint {
+    let mut wide = Vec::with_capacity(bits.div_ceil(256) * 32);
+    for block in 0..bits.div_ceil(256) as u32 {
+        wide.extend_from_slice(&hash(&[label.as_bytes(), &counter.to_be_bytes(), &block.to_be_bytes()]));
+    }
+    BigUint::from_bytes_be(&wide) >> (wide.len() * 8 - bits)
+}
+
+/// Class of a prime ideal above a hash-derived prime l = 3 mod 4 that splits in Q(sqrt D)
+/// b = D^((l+1)/4) mod l is a square root of D mod l, flipped to be odd so b^2 = D mod 4l
+fn hash_to_form(label: &str, discriminant: &BigInt, p: &BigUint, rng: &mut ChaCha20Rng) -> QuadraticForm {
+    let config = PrimalityConfig::default();
+    let top = BigUint::one() << (GENERATOR_PRIME_BITS - 1);
+    for counter in 0u64.. {
+        let l = (expand(label, counter, GENERATOR_PRIME_BITS) | &top) | BigUint::from(3u32);
+        if !is_probable_prime(&l, &config, rng) { continue; }
+        let d_mod_l = (&l - (p % &l)) % &l;
+        let b = d_mod_l.modpow(&((&l + 1u32) >> 2), &l);
+        if (&b * &b) % &l != d_mod_l || b.is_zero() { continue; }
+        let b = if b.is_odd() { b } else { &l - b };
+        let form = QuadraticForm::from_ab(BigInt::from_biguint(Sign::Plus, l), BigInt::from_biguint(Sign::Plus, b), discriminant)
+            .expect("b^2 = D mod 4l by construction")
+            .reduce();
+        if !form.a.is_one() { return form; }
+    }
+    unreachable!("the prime stream is unbounded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All reduced forms of discriminant d (small |d| only)
+    fn reduced_forms(d: i64) -> Vec<QuadraticForm> {
+        let disc = BigInt::from(d);
+        let mut forms = Vec::new();
+        for a in 1i64.. {
+            if 3 * a * a > -d { break; }
+            for b in -a + 1..=a {
+                forms.extend(QuadraticForm::from_ab(BigInt::from(a), BigInt::from(b), &disc).filter(QuadraticForm::is_reduced));
+            }
+        }
+        forms
+    }
+
+    fn form(a: i64, b: i64, c: i64) -> QuadraticForm {
+        QuadraticForm { a: BigInt::from(a), b: BigInt::from(b), c: BigInt::from(c) }
+    }
+
+    fn small_group(d: i64) -> ClassGroupParams {
+        let discriminant = BigInt::from(d);
+        let forms = reduced_forms(d);
+        ClassGroupParams {
+            abs_discriminant: -&discriminant,
+            g: forms[1].clone(),
+            h: forms[forms.len() - 1].clone(),
+            discriminant,
+            seed: String::new(),
+            bits: 0,
+        }
+    }
+
+    // Purpose: ensure reduction returns the unique reduced representative of each class
+    // Params: hand-reduced examples for D = -23, -20 and -39, translated and swapped copies of every reduced form
+    // Output: pinned results; each variant reduces back to its form, keeping the discriminant
+    // Usage: `cargo test -- src::classgroup` or `cargo test --features classgroup`
+    #[test]
+    fn reduction_is_canonical() {
+        assert_eq!(form(6, 5, 2).reduce(), form(2, -1, 3));
+        assert_eq!(form(2, -2, 6).reduce(), form(2, 2, 6));
+        assert_eq!(form(4, 3, 3).reduce(), form(3, 3, 4));
+        assert_eq!(reduced_forms(-23), vec![form(1, 1, 6), form(2, -1, 3), form(2, 1, 3)]);
+        for d in [-23i64, -47, -71, -199, -1031] {
+            for f in reduced_forms(d) {
+                assert!(f.is_reduced());
+                for k in [-3i64, 1, 7] {
+                    // (a, b, c) under x -> x + k y, then under (x, y) -> (-y, x)
+                    let (a, b, c) = (&f.a, &f.b, &f.c);
+                    let shifted = QuadraticForm { a: a.clone(), b: b + 2 * a * k, c: a * k * k + b * k + c };
+                    let swapped = QuadraticForm { a: shifted.c.clone(), b: -&shifted.b, c: shifted.a.clone() };
+                    assert_eq!(shifted.discriminant(), BigInt::from(d));
+                    assert_eq!(shifted.clone().reduce(), f);
+                    assert_eq!(swapped.reduce(), f);
+                }
+            }
+        }
+    }
+
+    // Purpose: ensure composition is the class group law, checked against known class numbers
+    // Params: D = -23, -47, -71, -199, -1031 (class numbers 3, 5, 7, 9, 35) with every pair of reduced forms
+    // Output: closure, identity, inverses, commutativity, associativity; x^h(D) = 1; pow matches repeated op
+    // Usage: `cargo test -- src::classgroup` or `cargo test --features classgroup`
+    #[test]
+    fn composition_is_the_group_law() {
+        for (d, class_number) in [(-23i64, 3usize), (-47, 5), (-71, 7), (-199, 9), (-1031, 35)] {
+            let group = small_group(d);
+            let forms = reduced_forms(d);
+            assert_eq!(forms.len(), class_number, "h({})", d);
+            let one = group.identity();
+            for x in &forms {
+                assert_eq!(group.op(x, &one), *x);
+                assert_eq!(group.op(x, &group.inverse(x)), one);
+                assert_eq!(group.pow(x, &BigInt::from(class_number)), one);
+                let mut acc = one.clone();
+                for e in 0..12 {
+                    assert_eq!(group.pow(x, &BigInt::from(e)), acc);
+                    acc = group.op(&acc, x);
+                }
+                assert_eq!(group.pow(x, &BigInt::from(-5)), group.inverse(&group.pow(x, &BigInt::from(5))));
+                for y in &forms {
+                    let xy = group.op(x, y);
+                    assert!(forms.contains(&xy));
+                    assert_eq!(xy, group.op(y, x));
+                    for z in forms.iter().take(4) {
+                        assert_eq!(group.op(&xy, z), group.op(x, &group.op(y, z)));
+                    }
+                }
+            }
+            // Cl(-23) = Z/3 generated by (2, 1, 3)
+            if d == -23 {
+                let f = form(2, 1, 3);
+                assert_eq!(group.op(&f, &f), form(2, -1, 3));
+            }
+        }
+    }
+
+    // Purpose: ensure seeded groups are deterministic, well-formed and their encodings roundtrip
+    // Params: seeds "cuproof" and "other" at 128 and 256 bits
+    // Output: exact discriminant size, D = 1 mod 8, reduced distinct generators, decode(encode(x)) = x
+    // Usage: `cargo test -- src::classgroup` or `cargo test --features classgroup`
+    #[test]
+    fn seeded_groups_are_deterministic() {
+        let group = ClassGroupParams::from_seed("cuproof", 256).unwrap();
+        assert_eq!(group, ClassGroupParams::from_seed("cuproof", 256).unwrap());
+        assert_ne!(group.discriminant(), ClassGroupParams::from_seed("other", 256).unwrap().discriminant());
+        assert_eq!(group.discriminant().bits(), 256);
+        assert_eq!(group.discriminant().mod_floor(&BigInt::from(8)), BigInt::one());
+        for f in [group.g(), group.h()] {
+            assert!(f.is_reduced());
+            assert_eq!(&f.discriminant(), group.discriminant());
+            assert_ne!(f, &group.identity());
+        }
+        assert_ne!(group.g(), group.h());
+        let x = group.pow(group.g(), &BigInt::from(123456789u64));
+        assert_eq!(group.decode(&group.encode(&x)), Some(x.clone()));
+        assert_eq!(group.decode(&(group.encode(&x) + 1)), None);
+        assert_eq!(group.decode(&BigInt::zero()), None);
+        assert!(ClassGroupParams::from_seed("cuproof", 32).is_none());
+        assert_eq!(ClassGroupParams::from_seed("cuproof", 128).unwrap().discriminant().bits(), 128);
+    }
+
+    // Purpose: ensure range proofs run end to end over a seeded class group
+    // Params: setup::classgroup_params("cuproof-test", 256), v = 42 in [10, 100]
+    // Output: verify true; false after tampering, under a different seed and for a non-form commitment
+    // Usage: `cargo test -- src::classgroup` or `cargo test --features classgroup`
+    #[test]
+    fn range_proof_round_trip() {
+        use crate::range_proof::prove;
+        use crate::verify::{verify, cuproof_verify_with_range};
+        let group = crate::setup::classgroup_params("cuproof-test", 256).unwrap();
+        let (a, b, v) = (BigInt::from(10), BigInt::from(100), BigInt::from(42));
+        let proof = prove(&v, &BigInt::from(7), &a, &b, &group).unwrap();
+        assert!(verify(&proof, &group));
+        assert!(cuproof_verify_with_range(&proof, &group, &a, &b));
+        assert!(group.is_element(&proof.C));
+
+        let mut tampered = proof.clone();
+        tampered.t1 += 1;
+        assert!(!verify(&tampered, &group));
+        let mut not_a_form = proof.clone();
+        not_a_form.C_v1 = BigInt::from(-3);
+        assert!(!verify(&not_a_form, &group));
+        let other = crate::setup::classgroup_params("another-seed", 256).unwrap();
+        assert!(!verify(&proof, &other));
+        assert!(crate::setup::classgroup_params("cuproof-test", 16).is_err());
+    }
+}
@@ -1,11 +1,19 @@
-use num_bigint::BigInt;
-use num_traits::Zero;
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::Signed;
+use crate::group::Group;
+use crate::montgomery::Montgomery;
+use crate::setup::Params;
 
 /// Modular exponentiation: base^exp mod modulus
 pub fn mod_exp(base: &BigInt, exp: &BigInt, modulus: &BigInt) -> BigInt {
-    let base_pos = if base < &BigInt::zero() { -base } else { base.clone() };
-    let exp_pos = if exp < &BigInt::zero() { -exp } else { exp.clone() };
-    base_pos.modpow(&exp_pos, modulus)
+    match (base.is_negative(), exp.is_negative()) {
+        (false, false) => base.modpow(exp, modulus),
+        (true, false) => (-base).modpow(exp, modulus),
+        (false, true) => base.modpow(&-exp, modulus),
+        (true, true) => (-base).modpow(&-exp, modulus),
+    }
 }
 
 /// Pedersen Commitment over RSA group
@@ -24,40 +32,180 @@ pub fn mod_exp(base: &BigInt, exp: &BigInt, modulus: &BigInt) -> BigInt {
 /// - Hiding: commitment reveals no information about m
 /// - Binding: computationally infeasible to find (m', r') ≠ (m, r) with H(m', r') = H(m, r)
 /// - Homomorphic: H(m1 + m2, r1 + r2) = H(m1, r1) * H(m2, r2)
+/// 
+/// g^|m| and h^|r| come from one interleaved exponentiation, which squares once per bit of the
+/// longer exponent instead of once per bit of each (see simultaneous_pow)
 pub fn pedersen_commit(g: &BigInt, h: &BigInt, m: &BigInt, r: &BigInt, n: &BigInt) -> BigInt {
-    mod_exp(g, m, n) * mod_exp(h, r, n) % n
+    let Some(mont) = Montgomery::new(n.magnitude()) else {
+        let mut c = mod_exp(g, m, n);
+        c *= mod_exp(h, r, n);
+        c %= n;
+        return c;
+    };
+    simultaneous_pow(&mont, &[(g, m), (h, r)])
+}
+
+/// prod |base_i|^|exp_i| mod n by interleaved sliding windows: the bases share one squaring per
+/// bit of the longest exponent, and each multiplies in an odd power from its own table once per
+/// window of its exponent, window_bits wide
+fn simultaneous_pow(mont: &Montgomery, terms: &[(&BigInt, &BigInt)]) -> BigInt {
+    let k = mont.limbs();
+    let mut scratch = vec![0u64; mont.scratch_len()];
+    let mut product = vec![0u64; k];
+    // per term: base^1, base^3, ..., base^(2^w - 1) and the windows as (lowest bit, table index), highest first
+    let mut tables = Vec::with_capacity(terms.len());
+    let mut windows = Vec::with_capacity(terms.len());
+    for (base, exp) in terms {
+        let w = window_bits(exp.bits());
+        let base = mont.to_mont(base.magnitude());
+        let mut square = vec![0u64; k];
+        mont.mul(&base, &base, &mut square, &mut scratch);
+        let mut table = base;
+        for i in 1..1 << (w - 1) {
+            mont.mul(&table[(i - 1) * k..i * k], &square, &mut product, &mut scratch);
+            table.extend_from_slice(&product);
+        }
+        tables.push(table);
+        windows.push(sliding_windows(exp.magnitude(), w));
+    }
+    let bits = terms.iter().map(|(_, exp)| exp.bits()).max().unwrap_or(0);
+    let mut next = vec![0; terms.len()];
+    let mut acc = mont.one().to_vec();
+    for bit in (0..bits).rev() {
+        mont.mul(&acc, &acc, &mut product, &mut scratch);
+        acc.copy_from_slice(&product);
+        for ((table, windows), next) in tables.iter().zip(&windows).zip(&mut next) {
+            let Some(&(low, index)) = windows.get(*next) else { continue };
+            if low != bit { continue; }
+            mont.mul(&acc, &table[index * k..(index + 1) * k], &mut product, &mut scratch);
+            acc.copy_from_slice(&product);
+            *next += 1;
+        }
+    }
+    BigInt::from_biguint(Sign::Plus, mont.out_of_mont(&acc, &mut scratch))
+}
+
+/// Window width for an exponent of `bits` bits: the table's 2^(w-1) products against about
+/// bits / (w + 1) multiplications by it
+fn window_bits(bits: u64) -> usize {
+    match bits {
+        0..=80 => 3,
+        81..=240 => 4,
+        241..=672 => 5,
+        _ => 6,
+    }
+}
+
+/// The windows of `e` from its highest bit down: each starts at a set bit, spans at most `w` bits
+/// and ends at a set bit, and is given as (its lowest bit, (its value - 1) / 2)
+fn sliding_windows(e: &BigUint, w: usize) -> Vec<(u64, usize)> {
+    let mut windows = Vec::new();
+    let mut bit = e.bits();
+    while bit > 0 {
+        bit -= 1;
+        if !e.bit(bit) { continue; }
+        let mut low = bit.saturating_sub(w as u64 - 1);
+        while !e.bit(low) { low += 1; }
+        let value = (low..=bit).rev().fold(0usize, |value, i| value << 1 | usize::from(e.bit(i)));
+        windows.push((low, value >> 1));
+        bit = low;
+    }
+    windows
+}
+
+/// Pedersen commitment g^m * h^r mod n under `params`
+/// - params: params public parameters, m message, r blinding
+/// - returns: commitment, identical to pedersen_commit with the same generators
+/// - usage: preferred entry point; uses the lazily built fixed-base tables in Params
+pub fn commit(params: &Params, m: &BigInt, r: &BigInt) -> BigInt {
+    params.gh_pow(m, r)
+}
+
+/// Whether `c` is a commitment in canonical form: a nonzero residue below n, the only form commit
+/// produces and load_commitment accepts
+/// - usage: reject C + n and other encodings of the same residue before comparing commitments
+pub fn is_canonical_commitment(params: &Params, c: &BigInt) -> bool {
+    c.is_positive() && c < params.n()
+}
+
+/// Pedersen commitment g^m * h^r in any `Group` backend
+/// - params: group generators and law, m message, r blinding
+/// - returns: the commitment as a group element (use `Group::commit` for its encoding)
+/// - usage: class group commitments; for RSA params this equals pedersen_commit
+pub(crate) fn pedersen_commit_in<G: Group + ?Sized>(group: &G, m: &BigInt, r: &BigInt) -> G::Element {
+    group.op(&group.pow(group.g(), m), &group.pow(group.h(), r))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::setup::fast_test_setup;
+    use crate::setup::insecure_test_setup;
     use num_bigint::BigInt;
 
-    // Purpose: sanity checks for Pedersen commitment basic properties on small RSA modulus from fast_test_setup
+    // Purpose: sanity checks for Pedersen commitment basic properties on small RSA modulus from insecure_test_setup
     // Params: generated (g,h,n), small messages and randomness
     // Output: asserts hold; no return
     // Usage: `cargo test -- src::commitment` or `cargo test`
     #[test]
     fn pedersen_basic_properties() {
-        let (g, h, n) = fast_test_setup();
+        let params = insecure_test_setup(512);
+        let (g, h, n) = (params.g(), params.h(), params.n());
         let m1 = BigInt::from(5);
         let r1 = BigInt::from(7);
-        let c1 = pedersen_commit(&g, &h, &m1, &r1, &n);
-        // same inputs -> same commitment
-        let c1_again = pedersen_commit(&g, &h, &m1, &r1, &n);
+        let c1 = pedersen_commit(g, h, &m1, &r1, n);
+        // same inputs -> same commitment, with or without the precomputed tables
+        let c1_again = commit(&params, &m1, &r1);
         assert_eq!(c1, c1_again);
 
         // different randomness -> different commitment (with overwhelming probability)
-        let c1_diff_r = pedersen_commit(&g, &h, &m1, &BigInt::from(8), &n);
+        let c1_diff_r = pedersen_commit(g, h, &m1, &BigInt::from(8), n);
         assert_ne!(c1, c1_diff_r);
 
         // homomorphism: C(m1,r1) * C(m2,r2) = C(m1+m2, r1+r2)
         let m2 = BigInt::from(11);
         let r2 = BigInt::from(3);
-        let c2 = pedersen_commit(&g, &h, &m2, &r2, &n);
-        let lhs = c1 * c2 % &n;
-        let rhs = pedersen_commit(&g, &h, &(m1.clone()+m2.clone()), &(r1.clone()+r2.clone()), &n);
+        let c2 = pedersen_commit(g, h, &m2, &r2, n);
+        let lhs = c1 * c2 % n;
+        let rhs = pedersen_commit(g, h, &(m1.clone()+m2.clone()), &(r1.clone()+r2.clone()), n);
         assert_eq!(lhs, rhs);
     }
+
+    // Purpose: pin the canonical form of commitments
+    // Params: insecure 512-bit params, a commitment and other representatives of its residue
+    // Output: only the reduced, nonzero value is canonical
+    // Usage: `cargo test -- src::commitment` or `cargo test`
+    #[test]
+    fn only_reduced_commitments_are_canonical() {
+        let params = insecure_test_setup(512);
+        let c = commit(&params, &BigInt::from(42), &BigInt::from(7));
+        assert!(is_canonical_commitment(&params, &c));
+        assert!(!is_canonical_commitment(&params, &(&c + params.n())));
+        assert!(!is_canonical_commitment(&params, &(&c - params.n())));
+        assert!(!is_canonical_commitment(&params, &BigInt::from(0)));
+        assert!(!is_canonical_commitment(&params, params.n()));
+    }
+
+    // Purpose: ensure the interleaved exponentiation computes exactly the two separate modpows
+    // Params: 512-bit params; zero, negative, window-boundary and seeded random exponents up to 2100 bits;
+    //         a base above n; an even modulus, which has no Montgomery form
+    // Output: pedersen_commit equals mod_exp(g, m) * mod_exp(h, r) mod n in every case
+    // Usage: `cargo test -- src::commitment` or `cargo test`
+    #[test]
+    fn pedersen_commit_matches_separate_modpows() {
+        use num_bigint::RandBigInt;
+        use rand::SeedableRng;
+        let params = insecure_test_setup(512);
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        let separate = |g: &BigInt, h: &BigInt, m: &BigInt, r: &BigInt, n: &BigInt| mod_exp(g, m, n) * mod_exp(h, r, n) % n;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(181);
+        let mut exponents: Vec<BigInt> = [0i64, 1, 2, 3, -5, 31, 32, 63, 64, 0xff, -0x1_0001].iter().map(|&e| BigInt::from(e)).collect();
+        exponents.extend((0..24).map(|i| rng.gen_bigint(1 + i * 90)));
+        for (m, r) in exponents.iter().zip(exponents.iter().rev()) {
+            assert_eq!(pedersen_commit(g, h, m, r, n), separate(g, h, m, r, n), "m = {}, r = {}", m, r);
+        }
+        let (big, m, r) = (g + n * 3, BigInt::from(1000), rng.gen_bigint(256));
+        assert_eq!(pedersen_commit(&big, h, &m, &r, n), separate(&big, h, &m, &r, n));
+        let even = n + 1;
+        assert_eq!(pedersen_commit(g, h, &m, &r, &even), separate(g, h, &m, &r, &even));
+    }
 }
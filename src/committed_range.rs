@@ -0,0 +1,118 @@
+//! Range proofs with hidden bounds: v lies in [a, b] where only commitments to a and b are public
+//!
+//! Given C = commit(v, r_v), C_a = commit(a, r_a) and C_b = commit(b, r_b), the quotients C / C_a
+//! and C_b / C commit to v - a and b - v, so a <= v <= b is two comparisons::prove_difference
+//! proofs that each difference lies in [0, 2^bits - 1], each about a difference commitment both
+//! sides compute from the three commitments. The prover knows all three openings; the verifier
+//! learns only that v - a and b - v fit in `bits` bits, neither bound nor the value.
+
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use crate::comparison::{prove_difference, verify_difference, MAX_COMPARISON_BITS};
+use crate::range_proof::{Cuproof, ProveError};
+use crate::setup::Params;
+use crate::verify::VerifyError;
+
+/// A proof that the value of one commitment lies between the values of two others
+#[derive(Clone)]
+pub struct HiddenRangeProof {
+    /// v - a and b - v are each proved to lie in [0, 2^bits - 1]
+    pub bits: u32,
+    /// h^(2^lower_shift) keeps the blinding of C / C_a positive
+    pub lower_shift: u32,
+    /// the range proof of v - a, about C / C_a * h^(2^lower_shift)
+    pub lower: Cuproof,
+    /// h^(2^upper_shift) keeps the blinding of C_b / C positive
+    pub upper_shift: u32,
+    /// the range proof of b - v, about C_b / C * h^(2^upper_shift)
+    pub upper: Cuproof,
+}
+
+/// Prove a <= v <= b for C = commit(v, r_v), C_a = commit(a, r_a) and C_b = commit(b, r_b)
+/// - params: the three openings, bits the width v - a and b - v must fit, params
+/// - returns: HiddenRangeProof; EmptyRange when a > b, ValueOutOfRange for v outside [a, b] or a
+///   negative a, DifferenceTooWide when v - a or b - v >= 2^bits, InvalidDimension for bits 0 or
+///   above MAX_COMPARISON_BITS and CommitmentMismatch for an r_a or r_v wider than that
+/// - usage: a sealed bid within a reserve and a ceiling the auctioneer committed to; the verifier
+///   passes the three commitments to verify_in_committed_range
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_in_committed_range(v: &BigInt, r_v: &BigInt, a: &BigInt, r_a: &BigInt, b: &BigInt, r_b: &BigInt, bits: u32, params: &Params) -> Result<HiddenRangeProof, ProveError> {
+    prove_in_committed_range_with_rng(v, r_v, a, r_a, b, r_b, bits, params, &mut OsRng)
+}
+
+/// prove_in_committed_range drawing every blinding of the two range proofs from `rng`
+#[allow(clippy::too_many_arguments)]
+pub fn prove_in_committed_range_with_rng<R: RngCore + CryptoRng>(
+    v: &BigInt, r_v: &BigInt, a: &BigInt, r_a: &BigInt, b: &BigInt, r_b: &BigInt, bits: u32, params: &Params, rng: &mut R,
+) -> Result<HiddenRangeProof, ProveError> {
+    if bits == 0 || bits > MAX_COMPARISON_BITS { return Err(ProveError::InvalidDimension(bits as usize)); }
+    if a > b { return Err(ProveError::EmptyRange); }
+    // commitments take values by absolute value, so a negative bound would not divide out
+    if a.sign() == Sign::Minus || v < a || v > b { return Err(ProveError::ValueOutOfRange); }
+    if (v - a).bits() > u64::from(bits) || (b - v).bits() > u64::from(bits) { return Err(ProveError::DifferenceTooWide { bits }); }
+    let zero = BigInt::zero();
+    let (lower_shift, lower) = prove_difference((a, r_a), (v, r_v), &zero, bits, params, rng)?;
+    let (upper_shift, upper) = prove_difference((v, r_v), (b, r_b), &zero, bits, params, rng)?;
+    Ok(HiddenRangeProof { bits, lower_shift, lower, upper_shift, upper })
+}
+
+/// Verify that the value of `c_v` lies between the values of `c_a` and `c_b`
+/// - params: c_v, c_a, c_b the commitments as the verifier holds them, proof, params
+/// - returns: Ok(()) if both differences are proved to lie in [0, 2^bits - 1]; the errors of
+///   comparison::verify_less_than otherwise. Swapping any two commitments rejects
+pub fn verify_in_committed_range(c_v: &BigInt, c_a: &BigInt, c_b: &BigInt, proof: &HiddenRangeProof, params: &Params) -> Result<(), VerifyError> {
+    let zero = BigInt::zero();
+    verify_difference(c_a, c_v, &zero, proof.bits, proof.lower_shift, &proof.lower, params)?;
+    verify_difference(c_v, c_b, &zero, proof.bits, proof.upper_shift, &proof.upper, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use crate::group::Group;
+    use crate::range_proof::random_blinding;
+    use crate::setup::insecure_test_setup;
+
+    fn prove_and_check(v: u64, a: u64, b: u64, seed: u64) -> Result<(), ProveError> {
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let (v, a, b) = (BigInt::from(v), BigInt::from(a), BigInt::from(b));
+        let (r_v, r_a, r_b) = (random_blinding(&mut rng), random_blinding(&mut rng), random_blinding(&mut rng));
+        let proof = prove_in_committed_range_with_rng(&v, &r_v, &a, &r_a, &b, &r_b, 16, &params, &mut rng)?;
+        let (c_v, c_a, c_b) = (params.commit(&v, &r_v), params.commit(&a, &r_a), params.commit(&b, &r_b));
+        assert_eq!(verify_in_committed_range(&c_v, &c_a, &c_b, &proof, &params), Ok(()));
+        assert!(verify_in_committed_range(&c_v, &c_b, &c_a, &proof, &params).is_err());
+        assert!(verify_in_committed_range(&c_a, &c_v, &c_b, &proof, &params).is_err());
+        Ok(())
+    }
+
+    // Purpose: ensure values inside and on both ends of a hidden range prove and verify, and only
+    //          against the commitments in their own roles
+    // Params: insecure 512-bit params; a = 100, b = 200 with v = 150, v = a and v = b; bits 16
+    // Output: Ok for (C, C_a, C_b); an error with the bounds swapped or C and C_a swapped
+    // Usage: `cargo test -- committed_range`
+    #[test]
+    fn values_in_and_on_the_bounds_verify() {
+        for (v, seed) in [(150, 0), (100, 1), (200, 2)] {
+            assert_eq!(prove_and_check(v, 100, 200, seed), Ok(()));
+        }
+    }
+
+    // Purpose: ensure witnesses that do not satisfy a <= v <= b are refused at prove time
+    // Params: a = 200 > b = 100; v below a; v above b; b - v = 2^16 under bits 16
+    // Output: EmptyRange, ValueOutOfRange twice and DifferenceTooWide { bits: 16 }
+    // Usage: `cargo test -- committed_range`
+    #[test]
+    fn unsatisfied_relations_are_refused() {
+        assert_eq!(prove_and_check(150, 200, 100, 3), Err(ProveError::EmptyRange));
+        assert_eq!(prove_and_check(99, 100, 200, 4), Err(ProveError::ValueOutOfRange));
+        assert_eq!(prove_and_check(201, 100, 200, 5), Err(ProveError::ValueOutOfRange));
+        assert_eq!(prove_and_check(0, 0, 1 << 16, 6), Err(ProveError::DifferenceTooWide { bits: 16 }));
+    }
+}
@@ -0,0 +1,188 @@
+//! Proofs that one committed value is below another (`cuproof prove-lt`, `verify-lt`)
+//!
+//! For C1 = g^v1 h^r1 and C2 = g^v2 h^r2, the quotient C2 / C1 commits to d = v2 - v1 under
+//! r2 - r1, so v1 < v2 is the range statement 1 <= d <= 2^bits - 1 about a commitment both sides
+//! can compute. Exponents act by absolute value (commitment::mod_exp), so r2 - r1 may not be
+//! negative: the difference commitment is C_d = C2 * C1^-1 * h^(2^shift) mod n, blinded by
+//! r2 - r1 + 2^shift, with shift at least BLINDING_BITS and the width of r1. A ComparisonProof is
+//! an ordinary range proof about C_d, with bits and shift beside it; verify_less_than recomputes
+//! C_d from the caller's C1 and C2 and checks the proof against that statement. Neither value nor
+//! their difference is revealed, only that it fits in `bits` bits.
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use crate::commitment::mod_exp;
+use crate::group::Group;
+use crate::range_proof::{cuproof_prove_for_commitment_with_rng, Cuproof, ProveError, Statement, BLINDING_BITS};
+use crate::setup::Params;
+use crate::util::MAX_PROOF_INT_BYTES;
+use crate::verify::{verify_statement, VerifyError};
+
+/// IPP vector dimension of comparison proofs, as prove uses
+pub const COMPARISON_DIMENSION: usize = 64;
+
+/// Widest difference, and largest shift, a comparison proof may carry: the width of the largest
+/// integer a proof from outside may hold
+pub const MAX_COMPARISON_BITS: u32 = 8 * MAX_PROOF_INT_BYTES as u32;
+
+/// A proof that the value of one commitment is less than the value of another
+#[derive(Clone)]
+pub struct ComparisonProof {
+    /// the difference v2 - v1 is proved to lie in [1, 2^bits - 1]
+    pub bits: u32,
+    /// h^(2^shift) keeps the blinding of the difference commitment positive
+    pub shift: u32,
+    /// the range proof about the difference commitment, whose C is C_d
+    pub proof: Cuproof,
+}
+
+/// Prove v1 < v2 for C1 = commit(v1, r1) and C2 = commit(v2, r2), without revealing either
+/// - params: v1, r1 and v2, r2 the openings, bits the width the difference v2 - v1 must fit, params
+/// - returns: ComparisonProof; NotLessThan when v1 >= v2, DifferenceTooWide when v2 - v1 >= 2^bits,
+///   ValueOutOfRange for a negative v1, InvalidDimension for bits 0 or above MAX_COMPARISON_BITS
+///   and CommitmentMismatch for an r1 wider than MAX_COMPARISON_BITS
+/// - usage: a bid below a committed budget: the bidder holds both openings, the verifier only the
+///   commitments, which it passes to verify_less_than
+#[cfg(feature = "std")]
+pub fn prove_less_than(v1: &BigInt, r1: &BigInt, v2: &BigInt, r2: &BigInt, bits: u32, params: &Params) -> Result<ComparisonProof, ProveError> {
+    prove_less_than_with_rng(v1, r1, v2, r2, bits, params, &mut OsRng)
+}
+
+/// prove_less_than drawing every blinding of the range proof from `rng`
+pub fn prove_less_than_with_rng<R: RngCore + CryptoRng>(v1: &BigInt, r1: &BigInt, v2: &BigInt, r2: &BigInt, bits: u32, params: &Params, rng: &mut R) -> Result<ComparisonProof, ProveError> {
+    if bits == 0 || bits > MAX_COMPARISON_BITS { return Err(ProveError::InvalidDimension(bits as usize)); }
+    if v1.sign() == Sign::Minus { return Err(ProveError::ValueOutOfRange); }
+    if v1 >= v2 { return Err(ProveError::NotLessThan); }
+    let d = v2 - v1;
+    if d.bits() > u64::from(bits) { return Err(ProveError::DifferenceTooWide { bits }); }
+    let (shift, proof) = prove_difference((v1, r1), (v2, r2), &BigInt::one(), bits, params, rng)?;
+    Ok(ComparisonProof { bits, shift, proof })
+}
+
+/// The range proof that v2 - v1 lies in [min, 2^bits - 1], about C_d = C2 / C1 * h^(2^shift)
+/// - returns: the shift and the proof; CommitmentMismatch for an r1 wider than MAX_COMPARISON_BITS
+/// - usage: prove_less_than (min 1) and committed_range::prove_in_committed_range (min 0), after
+///   they checked v1 >= 0, bits and the width of the difference
+pub(crate) fn prove_difference<R: RngCore + CryptoRng>(
+    (v1, r1): (&BigInt, &BigInt), (v2, r2): (&BigInt, &BigInt), min: &BigInt, bits: u32, params: &Params, rng: &mut R,
+) -> Result<(u32, Cuproof), ProveError> {
+    let shift = r1.bits().max(BLINDING_BITS as u64);
+    let shift = u32::try_from(shift).ok().filter(|&s| s <= MAX_COMPARISON_BITS).ok_or(ProveError::CommitmentMismatch)?;
+    let commitment = difference_commitment(&params.commit(v1, r1), &params.commit(v2, r2), shift, params)
+        .ok_or(ProveError::CommitmentMismatch)?;
+    // commit takes blindings by absolute value, so the difference is of the magnitudes
+    let r_d = BigInt::from(r2.magnitude().clone()) - BigInt::from(r1.magnitude().clone()) + (BigInt::one() << shift);
+    let proof = cuproof_prove_for_commitment_with_rng(&commitment, &(v2 - v1), &r_d, min, &upper_bound(bits), params, rng)?;
+    Ok((shift, proof))
+}
+
+/// Verify that the value of `c1` is less than the value of `c2`
+/// - params: c1, c2 the commitments as the verifier holds them, proof, params
+/// - returns: Ok(()) if the proof is for C_d = c2 / c1 * h^(2^shift) and 1 <= d <= 2^bits - 1;
+///   InvalidProof for bits or shift out of range and a c1 with no inverse mod n, otherwise the
+///   errors of verify_statement. Swapping c1 and c2 rejects
+pub fn verify_less_than(c1: &BigInt, c2: &BigInt, proof: &ComparisonProof, params: &Params) -> Result<(), VerifyError> {
+    verify_difference(c1, c2, &BigInt::one(), proof.bits, proof.shift, &proof.proof, params)
+}
+
+/// Check a prove_difference proof against the caller's C1 and C2
+/// - returns: InvalidProof for bits or shift out of range and a c1 with no inverse mod n,
+///   otherwise the errors of verify_statement against [min, 2^bits - 1] about C_d
+pub(crate) fn verify_difference(c1: &BigInt, c2: &BigInt, min: &BigInt, bits: u32, shift: u32, proof: &Cuproof, params: &Params) -> Result<(), VerifyError> {
+    if bits == 0 || bits > MAX_COMPARISON_BITS { return Err(VerifyError::InvalidProof); }
+    if (shift as usize) < BLINDING_BITS || shift > MAX_COMPARISON_BITS { return Err(VerifyError::InvalidProof); }
+    let commitment = difference_commitment(c1, c2, shift, params).ok_or(VerifyError::InvalidProof)?;
+    let expected = Statement::new(&commitment, min, &upper_bound(bits), COMPARISON_DIMENSION, params);
+    verify_statement(proof, params, &expected)
+}
+
+/// C_d = c2 * c1^-1 * h^(2^shift) mod n; None when c1 has no inverse
+fn difference_commitment(c1: &BigInt, c2: &BigInt, shift: u32, params: &Params) -> Option<BigInt> {
+    let n = params.n();
+    let inverse = c1.modinv(n)?;
+    let offset = mod_exp(params.h(), &(BigInt::one() << shift), n);
+    let commitment = c2 * inverse % n * offset % n;
+    (!commitment.is_zero()).then_some(commitment)
+}
+
+/// 2^bits - 1, the upper end of the difference's range
+fn upper_bound(bits: u32) -> BigInt {
+    (BigInt::one() << bits) - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use crate::range_proof::random_blinding;
+    use crate::setup::insecure_test_setup;
+
+    fn openings(v1: u64, v2: u64, rng: &mut ChaCha20Rng) -> (BigInt, BigInt, BigInt, BigInt) {
+        (BigInt::from(v1), random_blinding(rng), BigInt::from(v2), random_blinding(rng))
+    }
+
+    // Purpose: ensure the smallest strict inequality proves and verifies, and only in its own direction
+    // Params: insecure 512-bit params; v1 = 41, v2 = 42 with random blindings; bits 8
+    // Output: Ok against (C1, C2); InvalidProof or a statement mismatch against (C2, C1) and
+    //         against another commitment to 42
+    // Usage: `cargo test -- comparison`
+    #[test]
+    fn adjacent_values_verify() {
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(190);
+        let (v1, r1, v2, r2) = openings(41, 42, &mut rng);
+        let (c1, c2) = (params.commit(&v1, &r1), params.commit(&v2, &r2));
+        let proof = prove_less_than_with_rng(&v1, &r1, &v2, &r2, 8, &params, &mut rng).unwrap();
+        assert_eq!(verify_less_than(&c1, &c2, &proof, &params), Ok(()));
+        assert!(verify_less_than(&c2, &c1, &proof, &params).is_err());
+        let other = params.commit(&v2, &random_blinding(&mut rng));
+        assert!(verify_less_than(&c1, &other, &proof, &params).is_err());
+    }
+
+    // Purpose: ensure strict inequality is enforced and the width bounds the difference at prove time
+    // Params: v1 = v2 = 42; v1 = 43 > v2 = 42; v2 - v1 = 256 under bits 8 and 9; bits 0
+    // Output: NotLessThan for equal and reversed values; DifferenceTooWide { bits: 8 } for 256
+    //         under 8 bits, which 9 bits prove; InvalidDimension(0)
+    // Usage: `cargo test -- comparison`
+    #[test]
+    fn equal_values_and_wide_differences_are_refused() {
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let (v, r1, _, r2) = openings(42, 42, &mut rng);
+        assert_eq!(prove_less_than_with_rng(&v, &r1, &v, &r2, 64, &params, &mut rng).err(), Some(ProveError::NotLessThan));
+        assert_eq!(prove_less_than_with_rng(&(&v + 1), &r1, &v, &r2, 64, &params, &mut rng).err(), Some(ProveError::NotLessThan));
+
+        let (v1, r1, v2, r2) = openings(100, 356, &mut rng);
+        assert_eq!(prove_less_than_with_rng(&v1, &r1, &v2, &r2, 8, &params, &mut rng).err(), Some(ProveError::DifferenceTooWide { bits: 8 }));
+        let proof = prove_less_than_with_rng(&v1, &r1, &v2, &r2, 9, &params, &mut rng).unwrap();
+        assert_eq!(verify_less_than(&params.commit(&v1, &r1), &params.commit(&v2, &r2), &proof, &params), Ok(()));
+        assert_eq!(prove_less_than_with_rng(&v1, &r1, &v2, &r2, 0, &params, &mut rng).err(), Some(ProveError::InvalidDimension(0)));
+    }
+
+    // Purpose: ensure the shift keeps the difference blinding positive when r1 exceeds r2, and
+    //          that the width and shift a proof carries are the ones checked
+    // Params: r1 = 2^300 + 5 wider than BLINDING_BITS, r2 = 3; the proof with bits and shift altered
+    // Output: Ok for the honest proof with shift 301; InvalidProof or a mismatch for bits + 1, shift + 1
+    //         and shift below BLINDING_BITS
+    // Usage: `cargo test -- comparison`
+    #[test]
+    fn wide_first_blinding_and_altered_header() {
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let (v1, v2) = (BigInt::from(7), BigInt::from(1000));
+        let (r1, r2) = ((BigInt::one() << 300) + 5, BigInt::from(3));
+        let (c1, c2) = (params.commit(&v1, &r1), params.commit(&v2, &r2));
+        let proof = prove_less_than_with_rng(&v1, &r1, &v2, &r2, 16, &params, &mut rng).unwrap();
+        assert_eq!(proof.shift, 301);
+        assert_eq!(verify_less_than(&c1, &c2, &proof, &params), Ok(()));
+        for alter in [|p: &mut ComparisonProof| p.bits += 1, |p: &mut ComparisonProof| p.shift += 1, |p: &mut ComparisonProof| p.shift = 8] {
+            let mut altered = proof.clone();
+            alter(&mut altered);
+            assert!(verify_less_than(&c1, &c2, &altered, &params).is_err());
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Passphrase-encrypted files for secrets at rest (`encryption` feature): blindings, offline
+//! prover states and openings manifests
+//!
+//! The passphrase is stretched with Argon2id over a random 16-byte salt into a 32-byte key and a
+//! 16-byte check value. The plaintext is encrypted with XChaCha20-Poly1305 under the key and a
+//! random 24-byte nonce, with the whole header as associated data. Layout, integers big-endian:
+//!
+//! ```text
+//! magic "CUENCRYP" | u16 version | u32 memory KiB | u32 passes | u32 lanes | salt (16) | nonce (24)
+//!   | check (16) | SHA-256 of the preceding header bytes (32) | ciphertext | Poly1305 tag (16)
+//! ```
+//!
+//! The checks run in that order, so failures are told apart: a header whose digest does not match
+//! was altered (Tampered); a check value the derived key does not reproduce means another
+//! passphrase (WrongPassphrase); a tag that fails under the right key means an altered ciphertext
+//! or header (Tampered). Someone who rewrites the salt or KDF settings and recomputes the digest
+//! makes the file read as WrongPassphrase, since the derived key changes with them. The declared
+//! KDF settings are bounded, so a forged header cannot make the reader allocate or hash without end.
+
+use std::fmt;
+use argon2::{Algorithm, Argon2, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+use crate::secret::Secret;
+use crate::util::ct_eq_bytes;
+
+/// First bytes of every encrypted file
+pub const ENCRYPTED_MAGIC: &[u8; 8] = b"CUENCRYP";
+
+/// Format version of the files encrypt writes
+pub const ENCRYPTED_FORMAT_VERSION: u16 = 1;
+
+/// Bytes of random salt per file
+pub const SALT_LEN: usize = 16;
+
+/// Bytes of the XChaCha20-Poly1305 nonce
+pub const NONCE_LEN: usize = 24;
+
+/// Bytes of the check value that tells a wrong passphrase from an altered file
+const CHECK_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+const DIGEST_LEN: usize = 32;
+/// magic, version, the three KDF settings, salt, nonce and check value
+const FIELDS_LEN: usize = 8 + 2 + 3 * 4 + SALT_LEN + NONCE_LEN + CHECK_LEN;
+
+/// Bytes before the ciphertext: the header fields and their digest
+pub const ENCRYPTED_HEADER_LEN: usize = FIELDS_LEN + DIGEST_LEN;
+
+/// Most Argon2 memory, in KiB, a file may declare (1 GiB)
+pub const MAX_KDF_MEMORY_KIB: u32 = 1 << 20;
+
+/// Most Argon2 passes a file may declare
+pub const MAX_KDF_PASSES: u32 = 64;
+
+/// Most Argon2 lanes a file may declare
+pub const MAX_KDF_LANES: u32 = 16;
+
+/// Argon2id settings of a newly encrypted file; they are written to its header, so a file is read
+/// back with the settings it was written with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// memory cost in KiB
+    pub memory_kib: u32,
+    /// time cost: passes over the memory
+    pub passes: u32,
+    /// degree of parallelism
+    pub lanes: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB, 3 passes, 1 lane
+    fn default() -> KdfParams {
+        KdfParams { memory_kib: 64 * 1024, passes: 3, lanes: 1 }
+    }
+}
+
+impl KdfParams {
+    /// Check the settings against the bounds a reader accepts and the minimums of Argon2
+    /// - returns: EncryptionError::KdfParams naming the first setting out of range
+    pub fn check(&self) -> Result<(), EncryptionError> {
+        if self.lanes == 0 || self.lanes > MAX_KDF_LANES { return Err(EncryptionError::KdfParams("lanes")); }
+        if self.passes == 0 || self.passes > MAX_KDF_PASSES { return Err(EncryptionError::KdfParams("passes")); }
+        if self.memory_kib < 8 * self.lanes || self.memory_kib > MAX_KDF_MEMORY_KIB { return Err(EncryptionError::KdfParams("memory")); }
+        Ok(())
+    }
+}
+
+/// Why an encrypted file could not be written or opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    /// the file does not start with ENCRYPTED_MAGIC
+    NotEncrypted,
+    /// the version is not ENCRYPTED_FORMAT_VERSION
+    UnsupportedVersion(u16),
+    /// the file ends inside the header or the tag
+    Truncated,
+    /// a KDF setting lies outside the bounds a reader accepts: "memory", "passes" or "lanes"
+    KdfParams(&'static str),
+    /// the passphrase derives another key than the one the file was encrypted under
+    WrongPassphrase,
+    /// the header digest or the authentication tag does not match: the file was altered
+    Tampered,
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::NotEncrypted => write!(f, "not a cuproof encrypted file"),
+            EncryptionError::UnsupportedVersion(v) => write!(f, "unsupported encrypted file version {}", v),
+            EncryptionError::Truncated => write!(f, "encrypted file is truncated"),
+            EncryptionError::KdfParams(which) => write!(
+                f, "encrypted file declares Argon2 {} outside the accepted bounds (at most {} KiB, {} passes, {} lanes)",
+                which, MAX_KDF_MEMORY_KIB, MAX_KDF_PASSES, MAX_KDF_LANES
+            ),
+            EncryptionError::WrongPassphrase => write!(f, "wrong passphrase"),
+            EncryptionError::Tampered => write!(f, "encrypted file was altered: authentication failed"),
+        }
+    }
+}
+
+impl core::error::Error for EncryptionError {}
+
+/// True if `bytes` start like a file written by encrypt
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(ENCRYPTED_MAGIC)
+}
+
+/// The header of an encrypted file, as far as it can be read without the passphrase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedHeader {
+    pub version: u16,
+    pub kdf: KdfParams,
+    pub salt: [u8; SALT_LEN],
+    pub nonce: [u8; NONCE_LEN],
+    check: [u8; CHECK_LEN],
+    /// bytes of ciphertext and tag after the header
+    pub ciphertext_len: usize,
+}
+
+impl EncryptedHeader {
+    /// Parse and check the header of `bytes`: magic, version, digest and KDF bounds
+    /// - returns: the header; NotEncrypted, UnsupportedVersion, Truncated, Tampered (digest) or KdfParams
+    /// - usage: decrypt, and inspect, which reports the settings without a passphrase
+    pub fn parse(bytes: &[u8]) -> Result<EncryptedHeader, EncryptionError> {
+        if !is_encrypted(bytes) { return Err(EncryptionError::NotEncrypted); }
+        if bytes.len() < ENCRYPTED_HEADER_LEN + TAG_LEN { return Err(EncryptionError::Truncated); }
+        let version = u16::from_be_bytes([bytes[8], bytes[9]]);
+        if version != ENCRYPTED_FORMAT_VERSION { return Err(EncryptionError::UnsupportedVersion(version)); }
+        let digest: [u8; DIGEST_LEN] = Sha256::digest(&bytes[..FIELDS_LEN]).into();
+        if !ct_eq_bytes(&digest, &bytes[FIELDS_LEN..ENCRYPTED_HEADER_LEN]) { return Err(EncryptionError::Tampered); }
+        let u32_at = |at: usize| u32::from_be_bytes(bytes[at..at + 4].try_into().expect("4 bytes"));
+        let kdf = KdfParams { memory_kib: u32_at(10), passes: u32_at(14), lanes: u32_at(18) };
+        kdf.check()?;
+        let mut at = 22;
+        let mut take = |n: usize| { let field = &bytes[at..at + n]; at += n; field };
+        Ok(EncryptedHeader {
+            version,
+            kdf,
+            salt: take(SALT_LEN).try_into().expect("salt length"),
+            nonce: take(NONCE_LEN).try_into().expect("nonce length"),
+            check: take(CHECK_LEN).try_into().expect("check length"),
+            ciphertext_len: bytes.len() - ENCRYPTED_HEADER_LEN,
+        })
+    }
+}
+
+/// Encrypt `plaintext` under `passphrase` with the default KdfParams
+/// - returns: the bytes of an encrypted file (see the module docs)
+/// - usage: util::save_encrypted; salt and nonce come from `rng`, e.g. OsRng
+pub fn encrypt<R: RngCore + CryptoRng>(plaintext: &[u8], passphrase: &[u8], rng: &mut R) -> Result<Vec<u8>, EncryptionError> {
+    encrypt_with(plaintext, passphrase, &KdfParams::default(), rng)
+}
+
+/// encrypt with the Argon2id settings `kdf`, which must pass KdfParams::check
+pub fn encrypt_with<R: RngCore + CryptoRng>(plaintext: &[u8], passphrase: &[u8], kdf: &KdfParams, rng: &mut R) -> Result<Vec<u8>, EncryptionError> {
+    kdf.check()?;
+    let (mut salt, mut nonce) = ([0u8; SALT_LEN], [0u8; NONCE_LEN]);
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut nonce);
+    let derived = derive(passphrase, &salt, kdf)?;
+    let mut bytes = Vec::with_capacity(ENCRYPTED_HEADER_LEN + plaintext.len() + TAG_LEN);
+    bytes.extend_from_slice(ENCRYPTED_MAGIC);
+    bytes.extend_from_slice(&ENCRYPTED_FORMAT_VERSION.to_be_bytes());
+    for setting in [kdf.memory_kib, kdf.passes, kdf.lanes] { bytes.extend_from_slice(&setting.to_be_bytes()); }
+    bytes.extend_from_slice(&salt);
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&derived[KEY_LEN..]);
+    let digest = Sha256::digest(&bytes);
+    bytes.extend_from_slice(&digest);
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&derived[..KEY_LEN]));
+    let ciphertext = cipher.encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad: &bytes }).map_err(|_| EncryptionError::Tampered)?;
+    bytes.extend_from_slice(&ciphertext);
+    Ok(bytes)
+}
+
+/// Decrypt the encrypted file `bytes` with `passphrase`
+/// - returns: the plaintext, wiped on drop; WrongPassphrase when the passphrase derives another
+///   key, Tampered when the header digest or the tag does not match, and the header errors of
+///   EncryptedHeader::parse
+pub fn decrypt(bytes: &[u8], passphrase: &[u8]) -> Result<Secret<Vec<u8>>, EncryptionError> {
+    let header = EncryptedHeader::parse(bytes)?;
+    let derived = derive(passphrase, &header.salt, &header.kdf)?;
+    if !ct_eq_bytes(&derived[KEY_LEN..], &header.check) { return Err(EncryptionError::WrongPassphrase); }
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&derived[..KEY_LEN]));
+    let payload = Payload { msg: &bytes[ENCRYPTED_HEADER_LEN..], aad: &bytes[..ENCRYPTED_HEADER_LEN] };
+    cipher.decrypt(XNonce::from_slice(&header.nonce), payload).map(Secret::new).map_err(|_| EncryptionError::Tampered)
+}
+
+/// Argon2id of `passphrase` over `salt`: the key, then the check value
+fn derive(passphrase: &[u8], salt: &[u8], kdf: &KdfParams) -> Result<Zeroizing<[u8; KEY_LEN + CHECK_LEN]>, EncryptionError> {
+    let params = argon2::Params::new(kdf.memory_kib, kdf.passes, kdf.lanes, Some(KEY_LEN + CHECK_LEN)).map_err(|_| EncryptionError::KdfParams("memory"))?;
+    let mut out = Zeroizing::new([0u8; KEY_LEN + CHECK_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, salt, out.as_mut())
+        .map_err(|_| EncryptionError::KdfParams("memory"))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    /// Small settings, so the tests do not spend 64 MiB per derivation
+    const TEST_KDF: KdfParams = KdfParams { memory_kib: 64, passes: 1, lanes: 1 };
+
+    fn encrypted(plaintext: &[u8], passphrase: &[u8]) -> Vec<u8> {
+        encrypt_with(plaintext, passphrase, &TEST_KDF, &mut ChaCha20Rng::seed_from_u64(7)).unwrap()
+    }
+
+    // Purpose: ensure decrypt returns what encrypt sealed, and that the header records the settings
+    // Params: a short plaintext, the passphrase "correct horse", TEST_KDF
+    // Output: the plaintext back; the header parses with TEST_KDF and a ciphertext of plaintext plus tag
+    // Usage: `cargo test --features encryption encrypted::`
+    #[test]
+    fn round_trip() {
+        let bytes = encrypted(b"blinding 1234", b"correct horse");
+        assert!(is_encrypted(&bytes));
+        assert_eq!(decrypt(&bytes, b"correct horse").unwrap().expose().as_slice(), b"blinding 1234");
+        let header = EncryptedHeader::parse(&bytes).unwrap();
+        assert_eq!(header.kdf, TEST_KDF);
+        assert_eq!(header.ciphertext_len, 13 + TAG_LEN);
+    }
+
+    // Purpose: ensure a wrong passphrase and an altered file fail with different errors
+    // Params: a file encrypted under "correct horse"; the passphrase "battery staple"; the file with
+    //         one bit flipped in the ciphertext, in the nonce, and in the digest
+    // Output: WrongPassphrase for the other passphrase; Tampered for each altered file
+    // Usage: `cargo test --features encryption encrypted::`
+    #[test]
+    fn wrong_passphrase_is_not_tampering() {
+        let bytes = encrypted(b"blinding 1234", b"correct horse");
+        assert_eq!(decrypt(&bytes, b"battery staple").unwrap_err(), EncryptionError::WrongPassphrase);
+        for at in [ENCRYPTED_HEADER_LEN + 2, 30, FIELDS_LEN + 1, bytes.len() - 1] {
+            let mut altered = bytes.clone();
+            altered[at] ^= 1;
+            assert_eq!(decrypt(&altered, b"correct horse").unwrap_err(), EncryptionError::Tampered, "byte {}", at);
+        }
+    }
+
+    // Purpose: ensure a header cannot make the reader derive with unbounded settings, and that
+    //          short or foreign files are refused before any derivation
+    // Params: headers declaring 2 GiB of memory and zero passes (digest recomputed); a truncated
+    //         file; a file with another magic
+    // Output: KdfParams("memory"), KdfParams("passes"), Truncated, NotEncrypted
+    // Usage: `cargo test --features encryption encrypted::`
+    #[test]
+    fn header_is_checked_before_deriving() {
+        let bytes = encrypted(b"x", b"pw");
+        let forged = |at: usize, value: u32| {
+            let mut forged = bytes.clone();
+            forged[at..at + 4].copy_from_slice(&value.to_be_bytes());
+            let digest = Sha256::digest(&forged[..FIELDS_LEN]);
+            forged[FIELDS_LEN..ENCRYPTED_HEADER_LEN].copy_from_slice(&digest);
+            forged
+        };
+        assert_eq!(decrypt(&forged(10, 2 << 20), b"pw").unwrap_err(), EncryptionError::KdfParams("memory"));
+        assert_eq!(decrypt(&forged(14, 0), b"pw").unwrap_err(), EncryptionError::KdfParams("passes"));
+        assert_eq!(decrypt(&bytes[..ENCRYPTED_HEADER_LEN], b"pw").unwrap_err(), EncryptionError::Truncated);
+        assert_eq!(decrypt(b"CUBLIND\0 not this", b"pw").unwrap_err(), EncryptionError::NotEncrypted);
+    }
+}
@@ -0,0 +1,745 @@
+//! Crate-wide error type
+//!
+//! Every fallible public function returns one of the sub-errors below (or a narrower error that
+//! converts into one of them); `Error` wraps them all, so embedding code can use `?` across
+//! setup, proving, verification and IO.
+//!
+//! Path-based loaders such as `util::load_params` still return `io::Error` with the typed error
+//! inside; `Error::from(io::Error)` recovers it, so `load_params(path)?` in a function returning
+//! `cuproof::Error` yields `Error::Params(..)` for params that fail validation rather than an
+//! opaque IO error.
+
+use std::fmt;
+use std::io;
+use crate::metadata::MetadataError;
+use crate::util::{DecodeError, EncodeError, FormatError, LimitError, MigrationError, ParamsLoadError, ParseError, ProofLoadError};
+use crate::wire::WireError;
+use crate::batch::ManifestError;
+#[cfg(feature = "armor")]
+use crate::armor::ArmorError;
+#[cfg(feature = "cbor")]
+use crate::cbor::CborError;
+#[cfg(feature = "proto")]
+use crate::proto::ProtoError;
+#[cfg(feature = "borsh")]
+use crate::borsh::BorshError;
+#[cfg(feature = "encryption")]
+use crate::encrypted::EncryptionError;
+
+pub use crate::range_proof::ProveError;
+pub use crate::setup::{ParamsError, SetupError};
+pub use crate::verify::VerifyError;
+
+/// Why params or a proof could not be read, written or decoded
+#[derive(Debug)]
+pub enum SerializationError {
+    /// the underlying reader, writer or file failed
+    Io(io::Error),
+    /// a hex string did not parse
+    Hex(ParseError),
+    /// the container framing is broken
+    Format(FormatError),
+    /// a length field or integer exceeds the decoder's limits
+    Limit(LimitError),
+    /// the input exceeds the configured read limit
+    TooLarge { limit: usize },
+    /// the framing is fine but the content does not decode
+    Invalid(String),
+    /// the metadata section fails validate_metadata
+    Metadata(MetadataError),
+    /// a value does not fit its fixed-width field
+    Encode(EncodeError),
+    /// a fixed-width field is malformed
+    Decode(DecodeError),
+    /// the wire encoding is malformed
+    Wire(WireError),
+    /// the CBOR document is malformed
+    #[cfg(feature = "cbor")]
+    Cbor(CborError),
+    /// the ASCII armor is malformed
+    #[cfg(feature = "armor")]
+    Armor(ArmorError),
+    /// the protobuf message is malformed or fails validation
+    #[cfg(feature = "proto")]
+    Proto(ProtoError),
+    /// the borsh document is malformed or fails validation
+    #[cfg(feature = "borsh")]
+    Borsh(BorshError),
+    /// a passphrase-encrypted file could not be opened: wrong passphrase, altered file or bad header
+    #[cfg(feature = "encryption")]
+    Encryption(EncryptionError),
+}
+
+impl fmt::Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializationError::Io(e) => write!(f, "{}", e),
+            SerializationError::Hex(e) => write!(f, "{}", e),
+            SerializationError::Format(e) => write!(f, "{}", e),
+            SerializationError::Limit(e) => write!(f, "{}", e),
+            SerializationError::TooLarge { limit } => write!(f, "input exceeds the {}-byte read limit", limit),
+            SerializationError::Invalid(msg) => write!(f, "{}", msg),
+            SerializationError::Metadata(e) => write!(f, "{}", e),
+            SerializationError::Encode(e) => write!(f, "{}", e),
+            SerializationError::Decode(e) => write!(f, "{}", e),
+            SerializationError::Wire(e) => write!(f, "{}", e),
+            #[cfg(feature = "cbor")]
+            SerializationError::Cbor(e) => write!(f, "{}", e),
+            #[cfg(feature = "armor")]
+            SerializationError::Armor(e) => write!(f, "{}", e),
+            #[cfg(feature = "proto")]
+            SerializationError::Proto(e) => write!(f, "{}", e),
+            #[cfg(feature = "borsh")]
+            SerializationError::Borsh(e) => write!(f, "{}", e),
+            #[cfg(feature = "encryption")]
+            SerializationError::Encryption(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Display is transparent, so the source is the wrapped error's own source
+impl core::error::Error for SerializationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SerializationError::Io(e) => e.source(),
+            SerializationError::Hex(e) => e.source(),
+            SerializationError::Format(e) => e.source(),
+            SerializationError::Limit(e) => e.source(),
+            SerializationError::Metadata(e) => e.source(),
+            SerializationError::Encode(e) => e.source(),
+            SerializationError::Decode(e) => e.source(),
+            SerializationError::Wire(e) => e.source(),
+            #[cfg(feature = "cbor")]
+            SerializationError::Cbor(e) => e.source(),
+            #[cfg(feature = "armor")]
+            SerializationError::Armor(e) => e.source(),
+            #[cfg(feature = "proto")]
+            SerializationError::Proto(e) => e.source(),
+            #[cfg(feature = "borsh")]
+            SerializationError::Borsh(e) => e.source(),
+            #[cfg(feature = "encryption")]
+            SerializationError::Encryption(e) => e.source(),
+            SerializationError::TooLarge { .. } | SerializationError::Invalid(_) => None,
+        }
+    }
+}
+
+/// Any error raised by this crate; displays as the wrapped error, the variant names its category
+#[derive(Debug)]
+pub enum Error {
+    /// generating public parameters failed
+    Setup(SetupError),
+    /// no proof exists for the given inputs
+    Prove(ProveError),
+    /// a proof was rejected
+    Verify(VerifyError),
+    /// params or a proof could not be read, written or decoded
+    Serialization(SerializationError),
+    /// params decoded but failed validation
+    Params(ParamsError),
+}
+
+impl Error {
+    /// Process exit status the CLI uses for this error
+    /// - returns: 1 Verify, 2 Serialization, 3 Params, 4 Setup, 5 Prove
+    /// - usage: `std::process::exit(e.exit_code())` after printing the error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Verify(_) => 1,
+            Error::Serialization(_) => 2,
+            Error::Params(_) => 3,
+            Error::Setup(_) => 4,
+            Error::Prove(_) => 5,
+        }
+    }
+
+    /// Lowercase name of the variant, stable across releases
+    /// - returns: "verify", "serialization", "params", "setup" or "prove"
+    /// - usage: the `kind` of errors in the CLI's --json output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Verify(_) => "verify",
+            Error::Serialization(_) => "serialization",
+            Error::Params(_) => "params",
+            Error::Setup(_) => "setup",
+            Error::Prove(_) => "prove",
+        }
+    }
+}
+
+/// Every value of `Error::code`, in the order of the variants; a code never changes meaning once released
+pub const ERROR_CODES: &[&str] = &[
+    "setup.insecure_modulus_size", "setup.unsupported_modulus_size", "setup.composite_prime", "setup.equal_primes",
+    "setup.undersized_prime", "setup.oversized_prime", "setup.primes_too_close", "setup.modulus_size_mismatch",
+    "setup.invalid_key", "setup.invalid_modulus", "setup.checkpoint",
+    "prove.empty_range", "prove.value_out_of_range", "prove.invalid_dimension", "prove.commitment_mismatch", "prove.aggregate",
+    "prove.challenge_mismatch", "prove.state_spent", "prove.not_less_than", "prove.difference_too_wide",
+    "verify.epoch_mismatch", "verify.invalid_proof", "verify.missing_statement", "verify.statement_mismatch",
+    "verify.aggregate_statement_mismatch", "verify.cancelled",
+    "serialization.io", "serialization.hex", "serialization.format", "serialization.limit", "serialization.too_large",
+    "serialization.invalid", "serialization.metadata", "serialization.encode", "serialization.decode", "serialization.wire",
+    "serialization.cbor", "serialization.armor", "serialization.proto", "serialization.borsh",
+    "serialization.wrong_passphrase", "serialization.tampered", "serialization.encryption",
+    "params.even_modulus", "params.modulus_below_declared_size", "params.small_factor", "params.perfect_power",
+    "params.generator_out_of_range", "params.generator_is_minus_one", "params.generator_not_coprime",
+    "params.equal_generators", "params.modulus_too_small", "params.insecure_params", "params.modulus_too_large",
+    "params.generators_for_other_params", "params.generator_vector_mismatch",
+];
+
+impl Error {
+    /// Stable machine-readable name of the error: its kind and the variant of the wrapped error
+    /// - returns: one of ERROR_CODES, e.g. "params.even_modulus", "serialization.hex" or "verify.invalid_proof"
+    /// - usage: the `kind` of the CLI's structured errors on stderr (`--errors json`)
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Setup(e) => match e {
+                SetupError::InsecureModulusSize { .. } => "setup.insecure_modulus_size",
+                SetupError::UnsupportedModulusSize(_) => "setup.unsupported_modulus_size",
+                SetupError::CompositePrime => "setup.composite_prime",
+                SetupError::EqualPrimes => "setup.equal_primes",
+                SetupError::UndersizedPrime { .. } => "setup.undersized_prime",
+                SetupError::OversizedPrime { .. } => "setup.oversized_prime",
+                SetupError::PrimesTooClose => "setup.primes_too_close",
+                SetupError::ModulusSizeMismatch { .. } => "setup.modulus_size_mismatch",
+                SetupError::InvalidKey(_) => "setup.invalid_key",
+                SetupError::InvalidModulus(_) => "setup.invalid_modulus",
+                SetupError::Checkpoint(_) => "setup.checkpoint",
+            },
+            Error::Prove(e) => match e {
+                ProveError::EmptyRange => "prove.empty_range",
+                ProveError::ValueOutOfRange => "prove.value_out_of_range",
+                ProveError::InvalidDimension(_) => "prove.invalid_dimension",
+                ProveError::CommitmentMismatch => "prove.commitment_mismatch",
+                ProveError::Aggregate { .. } => "prove.aggregate",
+                ProveError::ChallengeMismatch => "prove.challenge_mismatch",
+                ProveError::StateSpent => "prove.state_spent",
+                ProveError::NotLessThan => "prove.not_less_than",
+                ProveError::DifferenceTooWide { .. } => "prove.difference_too_wide",
+            },
+            Error::Verify(e) => match e {
+                VerifyError::EpochMismatch { .. } => "verify.epoch_mismatch",
+                VerifyError::InvalidProof => "verify.invalid_proof",
+                VerifyError::MissingStatement => "verify.missing_statement",
+                VerifyError::StatementMismatch(_) => "verify.statement_mismatch",
+                VerifyError::AggregateStatementMismatch { .. } => "verify.aggregate_statement_mismatch",
+                VerifyError::Cancelled => "verify.cancelled",
+            },
+            Error::Serialization(e) => match e {
+                SerializationError::Io(_) => "serialization.io",
+                SerializationError::Hex(_) => "serialization.hex",
+                SerializationError::Format(_) => "serialization.format",
+                SerializationError::Limit(_) => "serialization.limit",
+                SerializationError::TooLarge { .. } => "serialization.too_large",
+                SerializationError::Invalid(_) => "serialization.invalid",
+                SerializationError::Metadata(_) => "serialization.metadata",
+                SerializationError::Encode(_) => "serialization.encode",
+                SerializationError::Decode(_) => "serialization.decode",
+                SerializationError::Wire(_) => "serialization.wire",
+                #[cfg(feature = "cbor")]
+                SerializationError::Cbor(_) => "serialization.cbor",
+                #[cfg(feature = "armor")]
+                SerializationError::Armor(_) => "serialization.armor",
+                #[cfg(feature = "proto")]
+                SerializationError::Proto(_) => "serialization.proto",
+                #[cfg(feature = "borsh")]
+                SerializationError::Borsh(_) => "serialization.borsh",
+                #[cfg(feature = "encryption")]
+                SerializationError::Encryption(EncryptionError::WrongPassphrase) => "serialization.wrong_passphrase",
+                #[cfg(feature = "encryption")]
+                SerializationError::Encryption(EncryptionError::Tampered) => "serialization.tampered",
+                #[cfg(feature = "encryption")]
+                SerializationError::Encryption(_) => "serialization.encryption",
+            },
+            Error::Params(e) => match e {
+                ParamsError::EvenModulus => "params.even_modulus",
+                ParamsError::ModulusBelowDeclaredSize { .. } => "params.modulus_below_declared_size",
+                ParamsError::SmallFactor(_) => "params.small_factor",
+                ParamsError::PerfectPower { .. } => "params.perfect_power",
+                ParamsError::GeneratorOutOfRange(_) => "params.generator_out_of_range",
+                ParamsError::GeneratorIsMinusOne(_) => "params.generator_is_minus_one",
+                ParamsError::GeneratorNotCoprime(_) => "params.generator_not_coprime",
+                ParamsError::EqualGenerators => "params.equal_generators",
+                ParamsError::ModulusTooSmall { .. } => "params.modulus_too_small",
+                ParamsError::InsecureParams => "params.insecure_params",
+                ParamsError::ModulusTooLarge { .. } => "params.modulus_too_large",
+                ParamsError::GeneratorsForOtherParams => "params.generators_for_other_params",
+                ParamsError::GeneratorVectorMismatch { .. } => "params.generator_vector_mismatch",
+            },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Setup(e) => write!(f, "{}", e),
+            Error::Prove(e) => write!(f, "{}", e),
+            Error::Verify(e) => write!(f, "{}", e),
+            Error::Serialization(e) => write!(f, "{}", e),
+            Error::Params(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Display is transparent, so the source is the wrapped error's own source
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Setup(e) => e.source(),
+            Error::Prove(e) => e.source(),
+            Error::Verify(e) => e.source(),
+            Error::Serialization(e) => e.source(),
+            Error::Params(e) => e.source(),
+        }
+    }
+}
+
+impl From<SetupError> for Error {
+    fn from(e: SetupError) -> Error { Error::Setup(e) }
+}
+
+impl From<ProveError> for Error {
+    fn from(e: ProveError) -> Error { Error::Prove(e) }
+}
+
+impl From<VerifyError> for Error {
+    fn from(e: VerifyError) -> Error { Error::Verify(e) }
+}
+
+impl From<ParamsError> for Error {
+    fn from(e: ParamsError) -> Error { Error::Params(e) }
+}
+
+impl From<SerializationError> for Error {
+    fn from(e: SerializationError) -> Error { Error::Serialization(e) }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error { Error::Serialization(SerializationError::Hex(e)) }
+}
+
+impl From<FormatError> for Error {
+    fn from(e: FormatError) -> Error { Error::Serialization(SerializationError::Format(e)) }
+}
+
+impl From<LimitError> for Error {
+    fn from(e: LimitError) -> Error { Error::Serialization(SerializationError::Limit(e)) }
+}
+
+impl From<MetadataError> for Error {
+    fn from(e: MetadataError) -> Error { Error::Serialization(SerializationError::Metadata(e)) }
+}
+
+impl From<EncodeError> for Error {
+    fn from(e: EncodeError) -> Error { Error::Serialization(SerializationError::Encode(e)) }
+}
+
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Error { Error::Serialization(SerializationError::Decode(e)) }
+}
+
+/// Params that decode but fail validate_params surface as Error::Params
+impl From<WireError> for Error {
+    fn from(e: WireError) -> Error {
+        match e {
+            WireError::InvalidParams(e) => Error::Params(e),
+            other => Error::Serialization(SerializationError::Wire(other)),
+        }
+    }
+}
+
+/// Params that decode but fail validate_params surface as Error::Params
+#[cfg(feature = "cbor")]
+impl From<CborError> for Error {
+    fn from(e: CborError) -> Error {
+        match e {
+            CborError::InvalidParams(e) => Error::Params(e),
+            other => Error::Serialization(SerializationError::Cbor(other)),
+        }
+    }
+}
+
+#[cfg(feature = "armor")]
+impl From<ArmorError> for Error {
+    fn from(e: ArmorError) -> Error { Error::Serialization(SerializationError::Armor(e)) }
+}
+
+/// Params that decode but fail validate_params surface as Error::Params
+#[cfg(feature = "proto")]
+impl From<ProtoError> for Error {
+    fn from(e: ProtoError) -> Error {
+        match e {
+            ProtoError::InvalidParams(e) => Error::Params(e),
+            other => Error::Serialization(SerializationError::Proto(other)),
+        }
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl From<BorshError> for Error {
+    fn from(e: BorshError) -> Error { Error::Serialization(SerializationError::Borsh(e)) }
+}
+
+#[cfg(feature = "encryption")]
+impl From<EncryptionError> for Error {
+    fn from(e: EncryptionError) -> Error { Error::Serialization(SerializationError::Encryption(e)) }
+}
+
+impl From<ProofLoadError> for Error {
+    fn from(e: ProofLoadError) -> Error {
+        Error::Serialization(match e {
+            ProofLoadError::Io(e) => SerializationError::Io(e),
+            ProofLoadError::TooLarge { limit } => SerializationError::TooLarge { limit },
+            ProofLoadError::Format(e) => SerializationError::Format(e),
+            ProofLoadError::Limit(e) => SerializationError::Limit(e),
+            ProofLoadError::Invalid(msg) => SerializationError::Invalid(msg),
+        })
+    }
+}
+
+impl From<ParamsLoadError> for Error {
+    fn from(e: ParamsLoadError) -> Error {
+        Error::Serialization(match e {
+            ParamsLoadError::Params(e) => return Error::Params(e),
+            ParamsLoadError::Io(e) => SerializationError::Io(e),
+            ParamsLoadError::TooLarge { limit } => SerializationError::TooLarge { limit },
+            ParamsLoadError::Format(e) => SerializationError::Format(e),
+            ParamsLoadError::Limit(e) => SerializationError::Limit(e),
+            ParamsLoadError::Invalid(msg) => SerializationError::Invalid(msg),
+            ParamsLoadError::Metadata(e) => SerializationError::Metadata(e),
+        })
+    }
+}
+
+impl From<MigrationError> for Error {
+    fn from(e: MigrationError) -> Error {
+        Error::Serialization(match e {
+            MigrationError::Params(e) => return Error::Params(e),
+            MigrationError::Format(e) => SerializationError::Format(e),
+            MigrationError::Limit(e) => SerializationError::Limit(e),
+            MigrationError::Invalid(msg) => SerializationError::Invalid(msg),
+            MigrationError::Metadata(e) => SerializationError::Metadata(e),
+        })
+    }
+}
+
+/// Recovers the typed error the path-based loaders wrap in an io::Error; any other io::Error
+/// becomes SerializationError::Io
+impl From<ManifestError> for Error {
+    fn from(e: ManifestError) -> Error {
+        match e {
+            ManifestError::Io(e) => e.into(),
+            ManifestError::Number { error, .. } => error.into(),
+            other => Error::Serialization(SerializationError::Invalid(other.to_string())),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        if let Some(inner) = e.get_ref() {
+            if let Some(e) = inner.downcast_ref::<ParamsError>() { return Error::Params(e.clone()); }
+            if let Some(e) = inner.downcast_ref::<MetadataError>() { return e.clone().into(); }
+            if let Some(e) = inner.downcast_ref::<FormatError>() { return e.clone().into(); }
+            if let Some(e) = inner.downcast_ref::<LimitError>() { return e.clone().into(); }
+            if let Some(e) = inner.downcast_ref::<ParseError>() { return e.clone().into(); }
+            if let Some(e) = inner.downcast_ref::<SetupError>() { return e.clone().into(); }
+            #[cfg(feature = "armor")]
+            if let Some(e) = inner.downcast_ref::<ArmorError>() { return e.clone().into(); }
+            #[cfg(feature = "cbor")]
+            if let Some(e) = inner.downcast_ref::<CborError>() { return e.clone().into(); }
+            #[cfg(feature = "encryption")]
+            if let Some(e) = inner.downcast_ref::<EncryptionError>() { return e.clone().into(); }
+        }
+        Error::Serialization(SerializationError::Io(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_proof::{prove, Cuproof};
+    use crate::setup::{insecure_test_setup, Params};
+    use crate::util::{self, ReadLimits};
+    use crate::wire;
+    use num_bigint::BigInt;
+    use rand::{Rng, RngCore, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    /// Random byte strings, plus truncations and single-byte flips of each valid encoding
+    fn garbage(valid: &[Vec<u8>], rng: &mut ChaCha20Rng) -> Vec<Vec<u8>> {
+        let mut out = vec![Vec::new(), vec![0], vec![0xff; 64], b"-".to_vec(), b"0x".to_vec()];
+        for _ in 0..64 {
+            let mut bytes = vec![0u8; rng.gen_range(0..512)];
+            rng.fill_bytes(&mut bytes);
+            out.push(bytes);
+        }
+        for bytes in valid {
+            for _ in 0..24 {
+                out.push(bytes[..rng.gen_range(0..bytes.len())].to_vec());
+                let mut flipped = bytes.clone();
+                let i = rng.gen_range(0..flipped.len());
+                flipped[i] ^= 1 << rng.gen_range(0..8);
+                out.push(flipped);
+            }
+        }
+        out
+    }
+
+    /// A flipped byte can still decode; such proofs must then be safe to verify
+    fn check_proof(result: Result<Cuproof, Error>, params: &Params) {
+        if let Ok(proof) = result {
+            let _ = crate::verify::verify(&proof, params);
+        }
+    }
+
+    // Purpose: no load or parse entry point panics on untrusted input
+    // Params: seeded random bytes, truncations and bit flips of valid encodings, hex strings
+    // Output: random bytes are rejected with Err; mutated encodings either Err or decode to a
+    //         proof that verify handles without panicking
+    // Usage: `cargo test -- src::error` or `cargo test --all-features`
+    #[test]
+    fn garbage_input_is_an_error_not_a_panic() {
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(30), &BigInt::from(42), &BigInt::from(10), &BigInt::from(100), &params).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(135);
+        let (proof_container, params_container) = (util::encode_proof(&proof).unwrap(), util::encode_params(&params).unwrap());
+        // the container payload is the legacy text format, which bypasses the checksum
+        let legacy = |bytes: &[u8]| bytes[14..bytes.len() - 32].to_vec();
+        let proof_encodings = vec![
+            legacy(&proof_container),
+            proof_container,
+            wire::proof_to_wire(&proof).unwrap(),
+            #[cfg(feature = "json")]
+            include_bytes!("../testdata/proof_v1.json").to_vec(),
+            #[cfg(feature = "cbor")]
+            proof.to_cbor(),
+            #[cfg(feature = "armor")]
+            proof.to_armored().into_bytes(),
+            #[cfg(feature = "proto")]
+            crate::proto::proof_to_proto(&proof),
+            #[cfg(feature = "borsh")]
+            crate::borsh::proof_to_borsh(&proof).unwrap(),
+        ];
+        let params_encodings = vec![
+            legacy(&params_container),
+            params_container,
+            wire::params_to_wire(&params).unwrap(),
+            #[cfg(feature = "json")]
+            include_bytes!("../testdata/params_v1.json").to_vec(),
+            #[cfg(feature = "cbor")]
+            params.to_cbor(),
+            #[cfg(feature = "proto")]
+            crate::proto::params_to_proto(&params),
+        ];
+        let proofs = garbage(&proof_encodings, &mut rng);
+        let all_params = garbage(&params_encodings, &mut rng);
+
+        let limits = ReadLimits::default();
+        for bytes in &proofs {
+            check_proof(util::read_proof(&mut bytes.as_slice(), &limits).map_err(Error::from), &params);
+            check_proof(wire::proof_from_wire(bytes).map_err(Error::from), &params);
+            check_proof(util::migrate_proof(bytes).map_err(Error::from), &params);
+            #[cfg(feature = "cbor")]
+            check_proof(Cuproof::from_cbor(bytes).map_err(Error::from), &params);
+            #[cfg(feature = "armor")]
+            check_proof(Cuproof::from_armored(&String::from_utf8_lossy(bytes)).map_err(Error::from), &params);
+            #[cfg(feature = "proto")]
+            check_proof(crate::proto::proof_from_proto(bytes).map_err(Error::from), &params);
+            #[cfg(feature = "borsh")]
+            check_proof(crate::borsh::proof_from_borsh(bytes).map_err(Error::from), &params);
+            let _ = util::scalar_from_be_bytes(bytes);
+            let text = String::from_utf8_lossy(bytes);
+            let _ = util::hex_to_bigint(&text);
+            let _ = util::hex_to_bigint_signed(&text);
+        }
+        for bytes in &all_params {
+            let _ = util::read_params(&mut bytes.as_slice(), &limits);
+            let _ = wire::params_from_wire(bytes);
+            let _ = util::migrate_params(bytes);
+            #[cfg(feature = "cbor")]
+            let _ = Params::from_cbor(bytes);
+            #[cfg(feature = "proto")]
+            let _ = crate::proto::params_from_proto(bytes);
+        }
+
+        let path = std::env::temp_dir().join(format!("cuproof_garbage_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        for bytes in proofs.iter().chain(&all_params) {
+            std::fs::write(path, bytes).unwrap();
+            let _ = util::load_params(path);
+            let _ = util::load_params_unchecked(path);
+            let _ = util::load_params_min_bits(path, 512);
+            check_proof(util::load_proof(path).map_err(Error::from), &params);
+            let _ = util::load_setup_transcript(path);
+            let _ = util::load_contribution_proof(path);
+            #[cfg(feature = "json")]
+            {
+                let _ = util::load_params_json(path);
+                check_proof(util::load_proof_json(path).map_err(Error::from), &params);
+            }
+        }
+        let _ = std::fs::remove_file(path);
+
+        // purely random bytes never decode
+        for _ in 0..32 {
+            let mut bytes = vec![0u8; rng.gen_range(1..256)];
+            rng.fill_bytes(&mut bytes);
+            assert!(util::read_proof(&mut bytes.as_slice(), &limits).is_err());
+            assert!(util::read_params(&mut bytes.as_slice(), &limits).is_err());
+            assert!(wire::proof_from_wire(&bytes).is_err());
+            assert!(wire::params_from_wire(&bytes).is_err());
+        }
+    }
+
+    // Purpose: typed errors keep their category through io::Error and the stream loaders
+    // Params: a params file failing validation, a truncated proof stream, a bad hex string
+    // Output: Error::Params, Error::Serialization(Format) and Error::Serialization(Hex) with
+    //         their exit codes and kinds
+    // Usage: `cargo test -- src::error` or `cargo test`
+    #[test]
+    fn conversions_keep_the_category() {
+        let e = Error::from(io::Error::new(io::ErrorKind::InvalidData, ParamsError::EvenModulus));
+        assert!(matches!(e, Error::Params(ParamsError::EvenModulus)));
+        assert_eq!((e.exit_code(), e.kind()), (3, "params"));
+
+        let bytes = util::encode_proof(&prove(&BigInt::from(5), &BigInt::from(1), &BigInt::from(0), &BigInt::from(9), &insecure_test_setup(512)).unwrap()).unwrap();
+        let e = Error::from(util::read_proof(&mut &bytes[..bytes.len() - 1], &ReadLimits::default()).err().unwrap());
+        assert!(matches!(e, Error::Serialization(SerializationError::Format(_))));
+        assert_eq!((e.exit_code(), e.kind()), (2, "serialization"));
+        let e = Error::from(io::Error::from(util::read_proof(&mut &bytes[..4], &ReadLimits::default()).err().unwrap()));
+        assert!(matches!(e, Error::Serialization(SerializationError::Format(_))));
+
+        let e = Error::from(util::hex_to_bigint("xyz").unwrap_err());
+        assert_eq!(e.to_string(), util::hex_to_bigint("xyz").unwrap_err().to_string());
+        assert!(matches!(e, Error::Serialization(SerializationError::Hex(_))));
+
+        let e = Error::from(ProveError::ValueOutOfRange);
+        assert_eq!((e.exit_code(), e.kind()), (5, "prove"));
+        assert!(matches!(Error::from(io::Error::other("disk full")), Error::Serialization(SerializationError::Io(_))));
+    }
+
+    /// One error of every variant; the matches fail to compile when a variant is added without a sample
+    fn one_of_each() -> Vec<Error> {
+        let setup = [
+            SetupError::InsecureModulusSize { bits: 512, min_bits: 2048 }, SetupError::UnsupportedModulusSize(7),
+            SetupError::CompositePrime, SetupError::EqualPrimes, SetupError::UndersizedPrime { bits: 1, expected: 2 },
+            SetupError::OversizedPrime { bits: 3, expected: 2 }, SetupError::PrimesTooClose,
+            SetupError::ModulusSizeMismatch { bits: 1, expected: 2 }, SetupError::InvalidKey(String::new()),
+            SetupError::InvalidModulus(ParamsError::EvenModulus), SetupError::Checkpoint(String::new()),
+        ];
+        for e in &setup {
+            match e {
+                SetupError::InsecureModulusSize { .. } | SetupError::UnsupportedModulusSize(_) | SetupError::CompositePrime
+                | SetupError::EqualPrimes | SetupError::UndersizedPrime { .. } | SetupError::OversizedPrime { .. }
+                | SetupError::PrimesTooClose | SetupError::ModulusSizeMismatch { .. } | SetupError::InvalidKey(_)
+                | SetupError::InvalidModulus(_) | SetupError::Checkpoint(_) => {}
+            }
+        }
+        let prove = [
+            ProveError::EmptyRange, ProveError::ValueOutOfRange, ProveError::InvalidDimension(0), ProveError::CommitmentMismatch,
+            ProveError::Aggregate { index: 0, error: Box::new(ProveError::EmptyRange) }, ProveError::ChallengeMismatch,
+            ProveError::StateSpent, ProveError::NotLessThan, ProveError::DifferenceTooWide { bits: 0 },
+        ];
+        for e in &prove {
+            match e {
+                ProveError::EmptyRange | ProveError::ValueOutOfRange | ProveError::InvalidDimension(_)
+                | ProveError::CommitmentMismatch | ProveError::Aggregate { .. } | ProveError::ChallengeMismatch
+                | ProveError::StateSpent | ProveError::NotLessThan | ProveError::DifferenceTooWide { .. } => {}
+            }
+        }
+        let verify = [
+            VerifyError::EpochMismatch { proof: 0, params: 1 }, VerifyError::InvalidProof, VerifyError::MissingStatement,
+            VerifyError::StatementMismatch("range"), VerifyError::AggregateStatementMismatch { index: 0, field: "range" },
+            VerifyError::Cancelled,
+        ];
+        for e in &verify {
+            match e {
+                VerifyError::EpochMismatch { .. } | VerifyError::InvalidProof | VerifyError::MissingStatement
+                | VerifyError::StatementMismatch(_) | VerifyError::AggregateStatementMismatch { .. } | VerifyError::Cancelled => {}
+            }
+        }
+        #[allow(unused_mut)]
+        let mut serialization = vec![
+            SerializationError::Io(io::Error::other("disk full")), SerializationError::Hex(ParseError::Empty),
+            SerializationError::Format(FormatError::ChecksumMismatch), SerializationError::Limit(LimitError::TooManyRounds(99)),
+            SerializationError::TooLarge { limit: 1 }, SerializationError::Invalid(String::new()),
+            SerializationError::Metadata(MetadataError::Missing), SerializationError::Encode(EncodeError::Negative),
+            SerializationError::Decode(DecodeError::LeadingZero), SerializationError::Wire(WireError::TrailingBytes(1)),
+        ];
+        #[cfg(feature = "cbor")]
+        serialization.push(SerializationError::Cbor(CborError::MissingKey(0)));
+        #[cfg(feature = "armor")]
+        serialization.push(SerializationError::Armor(ArmorError::MissingBegin));
+        #[cfg(feature = "proto")]
+        serialization.push(SerializationError::Proto(ProtoError::Malformed("")));
+        #[cfg(feature = "borsh")]
+        serialization.push(SerializationError::Borsh(BorshError::ParamsMismatch));
+        #[cfg(feature = "encryption")]
+        serialization.extend([EncryptionError::WrongPassphrase, EncryptionError::Tampered, EncryptionError::Truncated].map(SerializationError::Encryption));
+        for e in &serialization {
+            match e {
+                SerializationError::Io(_) | SerializationError::Hex(_) | SerializationError::Format(_)
+                | SerializationError::Limit(_) | SerializationError::TooLarge { .. } | SerializationError::Invalid(_)
+                | SerializationError::Metadata(_) | SerializationError::Encode(_) | SerializationError::Decode(_)
+                | SerializationError::Wire(_) => {}
+                #[cfg(feature = "cbor")]
+                SerializationError::Cbor(_) => {}
+                #[cfg(feature = "armor")]
+                SerializationError::Armor(_) => {}
+                #[cfg(feature = "proto")]
+                SerializationError::Proto(_) => {}
+                #[cfg(feature = "borsh")]
+                SerializationError::Borsh(_) => {}
+                #[cfg(feature = "encryption")]
+                SerializationError::Encryption(_) => {}
+            }
+        }
+        let params = [
+            ParamsError::EvenModulus, ParamsError::ModulusBelowDeclaredSize { bits: 1, declared: 2 }, ParamsError::SmallFactor(3),
+            ParamsError::PerfectPower { exponent: 2 }, ParamsError::GeneratorOutOfRange("g"), ParamsError::GeneratorIsMinusOne("g"),
+            ParamsError::GeneratorNotCoprime("h"), ParamsError::EqualGenerators, ParamsError::ModulusTooSmall { bits: 1, min_bits: 2 },
+            ParamsError::InsecureParams, ParamsError::ModulusTooLarge { bits: 2, max_bits: 1 },
+            ParamsError::GeneratorsForOtherParams, ParamsError::GeneratorVectorMismatch { index: 0 },
+        ];
+        for e in &params {
+            match e {
+                ParamsError::EvenModulus | ParamsError::ModulusBelowDeclaredSize { .. } | ParamsError::SmallFactor(_)
+                | ParamsError::PerfectPower { .. } | ParamsError::GeneratorOutOfRange(_) | ParamsError::GeneratorIsMinusOne(_)
+                | ParamsError::GeneratorNotCoprime(_) | ParamsError::EqualGenerators | ParamsError::ModulusTooSmall { .. }
+                | ParamsError::InsecureParams | ParamsError::ModulusTooLarge { .. } | ParamsError::GeneratorsForOtherParams
+                | ParamsError::GeneratorVectorMismatch { .. } => {}
+            }
+        }
+        let mut all: Vec<Error> = setup.into_iter().map(Error::from).collect();
+        all.extend(prove.into_iter().map(Error::from));
+        all.extend(verify.into_iter().map(Error::from));
+        all.extend(serialization.into_iter().map(Error::from));
+        all.extend(params.into_iter().map(Error::from));
+        all
+    }
+
+    // Purpose: every error variant has its own stable code, listed in ERROR_CODES
+    // Params: one error of each variant of every sub-error (feature-gated ones when enabled)
+    // Output: codes are distinct, start with the error's kind, and cover ERROR_CODES but for the
+    //         variants of disabled features
+    // Usage: `cargo test -- src::error` or `cargo test --all-features`
+    #[test]
+    fn every_variant_has_a_code() {
+        let errors = one_of_each();
+        let codes: Vec<&str> = errors.iter().map(Error::code).collect();
+        for (e, code) in errors.iter().zip(&codes) {
+            assert!(ERROR_CODES.contains(code), "{} is not in ERROR_CODES", code);
+            assert_eq!(code.split('.').next(), Some(e.kind()), "{}", code);
+            assert_eq!(codes.iter().filter(|c| *c == code).count(), 1, "{} names two variants", code);
+        }
+        let gated = [("serialization.cbor", cfg!(feature = "cbor")), ("serialization.armor", cfg!(feature = "armor")),
+            ("serialization.proto", cfg!(feature = "proto")), ("serialization.borsh", cfg!(feature = "borsh")),
+            ("serialization.wrong_passphrase", cfg!(feature = "encryption")), ("serialization.tampered", cfg!(feature = "encryption")),
+            ("serialization.encryption", cfg!(feature = "encryption"))];
+        for code in ERROR_CODES {
+            let enabled = gated.iter().find(|(gated, _)| gated == code).is_none_or(|(_, enabled)| *enabled);
+            assert_eq!(codes.contains(code), enabled, "{} has no error", code);
+        }
+    }
+}
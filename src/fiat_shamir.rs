@@ -1,13 +1,149 @@
-use num_bigint::BigInt;
+use alloc::vec::Vec;
+use num_bigint::{BigInt, Sign};
 use sha2::{Digest, Sha256};
 
+/// SHA-256 over the encodings of `inputs`, as a non-negative integer
+/// Each input is a sign byte (1 for negative, else 0), the length of its magnitude as 4 bytes
+/// big-endian, and the magnitude big-endian without leading zeros; the lengths keep [1, 23] and
+/// [12, 3] apart. The bytes are written into one scratch buffer shared by all inputs
 pub fn fiat_shamir(inputs: &[&BigInt]) -> BigInt {
     let mut hasher = Sha256::new();
+    let mut scratch = Vec::with_capacity(5 + inputs.iter().map(|x| x.bits()).max().unwrap_or(0).div_ceil(8) as usize);
     for i in inputs {
-        hasher.update(i.to_str_radix(10).as_bytes());
+        hasher.update(encode(i, &mut scratch));
     }
     let hash = hasher.finalize();
-    BigInt::from_bytes_be(num_bigint::Sign::Plus, &hash)
+    BigInt::from_bytes_be(Sign::Plus, &hash)
+}
+
+/// The transcript encoding of `x` written over `out`, as fiat_shamir hashes it
+fn encode<'a>(x: &BigInt, out: &'a mut Vec<u8>) -> &'a [u8] {
+    out.clear();
+    out.push(u8::from(x.sign() == Sign::Minus));
+    out.extend_from_slice(&[0; 4]);
+    for (i, digit) in x.magnitude().iter_u64_digits().rev().enumerate() {
+        let bytes = digit.to_be_bytes();
+        let skip = if i == 0 { digit.leading_zeros() as usize / 8 } else { 0 };
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    let len = (out.len() - 5) as u32;
+    out[1..5].copy_from_slice(&len.to_be_bytes());
+    out
+}
+
+/// Known-answer vectors for fiat_shamir: inputs in decimal and the SHA-256 they must hash to
+/// Computed independently of this crate (Python's hashlib over the encoding fiat_shamir documents),
+/// so a build whose transcript drifts fails `cuproof selftest`
+pub const KNOWN_ANSWERS: [(&[&str], &str); 8] = [
+    (&[], "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+    (&["0"], "8855508aade16ec573d21e6a485dfd0a7624085c1a14b5ecdd6485de0c6839a4"),
+    (&["-1"], "71ca9703af0fda42b802aa93ef5ff20cc9d02353e1b2d514acae2ec02f2c7278"),
+    (&["1000000000"], "1fecf76634ff5c69ad570f2a6d06a837f69f04f72736180b3923dbbb438c7bea"),
+    (&["57896044618658097711785492504343953926634992332820282019728792003956564819949"], "9ec3fd30ef6a69ebbeaad3d0b5f757b6caacf76c1bf150d76cd9bba1c570e933"),
+    (
+        &["42", "-7", "6864797660130609714981900799081393217269435300143305409394463459185543183397656052122559640661454554977296311391480858037121987999716643812574028291115057151"],
+        "b9e0f5af4ddcfda6731c84c3182cd46418aaf1d7247c13a448884e9a0a2002fe",
+    ),
+    (&["1", "23"], "3c9c21332573e618c8248a2efb1ac88a8970c2a3ea2ed89a0761149f840f84ce"),
+    (&["12", "3"], "b42dad316bffea9762b25ccc4ede82eec458b2583a15eea423059db38617e181"),
+];
+
+/// Run fiat_shamir on every KNOWN_ANSWERS vector
+/// - returns: Ok(()) or the index of the first vector whose hash differs
+pub(crate) fn check_known_answers() -> Result<(), usize> {
+    for (i, (inputs, expected)) in KNOWN_ANSWERS.iter().enumerate() {
+        let inputs: Vec<BigInt> = inputs.iter().map(|s| s.parse().expect("known-answer inputs are decimal")).collect();
+        let expected = BigInt::parse_bytes(expected.as_bytes(), 16).expect("known answers are hex");
+        if fiat_shamir(&inputs.iter().collect::<Vec<_>>()) != expected { return Err(i); }
+    }
+    Ok(())
+}
+
+/// Domain separator of the contexts (label and nonce) FiatShamir binds
+const CONTEXT_DOMAIN: &[u8] = b"cuproof verifier context v2";
+
+/// Where the prover and verify draw the challenges y, z and x, each reduced modulo `n`, the
+/// challenge modulus of the params
+/// FiatShamir hashes the proof's messages; testing::MockTranscript plays back scripted challenges
+pub trait Transcript {
+    /// y and z for the first message [A, S, C, C_v1, C_v2]
+    fn first_round(&mut self, message: [&BigInt; 5], n: &BigInt) -> (BigInt, BigInt);
+    /// x for the commitments T1 and T2 to the polynomial's coefficients
+    fn polynomial(&mut self, t1: &BigInt, t2: &BigInt, n: &BigInt) -> BigInt;
+    /// The prime l of a proof of exponentiation over `message` (see poe), of poe::PRIME_BITS bits
+    fn prime(&mut self, message: &[&BigInt]) -> BigInt {
+        crate::poe::hash_to_prime(message)
+    }
+    /// The digest the challenges of a batch of openings are drawn from (see openings), over the
+    /// params, every commitment and the announcement
+    fn openings(&mut self, message: &[&BigInt]) -> BigInt {
+        fiat_shamir(message)
+    }
+}
+
+/// The Fiat–Shamir transcript of prove and verify, bound to a context label and a verifier's
+/// nonce when a nonce is given
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FiatShamir<'a> {
+    label: &'a str,
+    nonce: &'a [u8],
+}
+
+impl<'a> FiatShamir<'a> {
+    /// The transcript every proof without a nonce was made under
+    pub fn new() -> FiatShamir<'static> {
+        FiatShamir { label: "", nonce: &[] }
+    }
+
+    /// FiatShamir::with_context with an empty label
+    pub fn with_nonce(nonce: &'a [u8]) -> FiatShamir<'a> {
+        FiatShamir::with_context("", nonce)
+    }
+
+    /// The transcript with the context absorbed first in the first message's hash and in the hash
+    /// of T1, T2: SHA-256 over CONTEXT_DOMAIN, the label and then the nonce, each after its length
+    /// as 4 bytes big-endian, so both reach the t_hat check through x
+    /// An empty nonce is FiatShamir::new whatever the label: proofs made without a nonce carry
+    /// labels set after proving (Cuproof::with_label), which their challenges never saw
+    pub fn with_context(label: &'a str, nonce: &'a [u8]) -> FiatShamir<'a> {
+        FiatShamir { label, nonce }
+    }
+
+    /// fiat_shamir of `inputs`, after the context digest when there is a nonce
+    fn hash(&self, inputs: &[&BigInt]) -> BigInt {
+        if self.nonce.is_empty() { return fiat_shamir(inputs); }
+        let mut hasher = Sha256::new();
+        hasher.update(CONTEXT_DOMAIN);
+        for part in [self.label.as_bytes(), self.nonce] {
+            hasher.update((part.len() as u32).to_be_bytes());
+            hasher.update(part);
+        }
+        let context = BigInt::from_bytes_be(Sign::Plus, &hasher.finalize());
+        let mut transcript = Vec::with_capacity(1 + inputs.len());
+        transcript.push(&context);
+        transcript.extend_from_slice(inputs);
+        fiat_shamir(&transcript)
+    }
+}
+
+impl Transcript for FiatShamir<'_> {
+    fn first_round(&mut self, message: [&BigInt; 5], n: &BigInt) -> (BigInt, BigInt) {
+        let y = self.hash(&message) % n;
+        let z = fiat_shamir(&[&y]) % n;
+        (y, z)
+    }
+
+    fn polynomial(&mut self, t1: &BigInt, t2: &BigInt, n: &BigInt) -> BigInt {
+        self.hash(&[t1, t2]) % n
+    }
+
+    fn prime(&mut self, message: &[&BigInt]) -> BigInt {
+        crate::poe::prime_from_seed(&self.hash(message))
+    }
+
+    fn openings(&mut self, message: &[&BigInt]) -> BigInt {
+        self.hash(message)
+    }
 }
 
 #[cfg(test)]
@@ -34,4 +170,46 @@ mod tests {
         let h3 = fiat_shamir(&[&a, &c]);
         assert_ne!(h1, h3);
     }
-}
\ No newline at end of file
+
+    // Purpose: ensure fiat_shamir reproduces the known answers computed outside the crate
+    // Params: KNOWN_ANSWERS, and the same vectors with one input changed
+    // Output: Ok(()); a changed input no longer hashes to its answer
+    // Usage: `cargo test -- src::fiat_shamir` or `cargo test`
+    #[test]
+    fn known_answers_hold() {
+        assert_eq!(check_known_answers(), Ok(()));
+        let expected = BigInt::parse_bytes(KNOWN_ANSWERS[2].1.as_bytes(), 16).unwrap();
+        assert_ne!(fiat_shamir(&[&BigInt::from(1)]), expected);
+    }
+
+    // Purpose: ensure the scratch-buffer encoding is the documented one, so proofs verify across builds
+    // Params: zero, limb boundaries, negative values and seeded random values up to 4096 bits
+    // Output: encode equals sign byte, 4-byte length and to_bytes_be; fiat_shamir equals the allocating transcript
+    // Usage: `cargo test -- src::fiat_shamir` or `cargo test`
+    #[test]
+    fn encoding_matches_to_bytes_be() {
+        use num_bigint::RandBigInt;
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(143);
+        let mut values = vec![
+            BigInt::from(0), BigInt::from(7), BigInt::from(-7), BigInt::from(255), BigInt::from(256),
+            BigInt::from(u64::MAX), BigInt::from(u64::MAX) + 1, -BigInt::from(u64::MAX),
+        ];
+        values.extend((0..64).map(|i| rng.gen_bigint(1 + i * 64)));
+        let reference = |x: &BigInt| {
+            let magnitude = if x.magnitude().bits() == 0 { Vec::new() } else { x.magnitude().to_bytes_be() };
+            let mut bytes = vec![u8::from(x.sign() == Sign::Minus)];
+            bytes.extend_from_slice(&(magnitude.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(&magnitude);
+            bytes
+        };
+        let mut scratch = Vec::new();
+        for x in &values {
+            assert_eq!(encode(x, &mut scratch), &reference(x)[..]);
+        }
+        let mut hasher = Sha256::new();
+        for x in &values { hasher.update(reference(x)); }
+        let expected = BigInt::from_bytes_be(Sign::Plus, &hasher.finalize());
+        assert_eq!(fiat_shamir(&values.iter().collect::<Vec<_>>()), expected);
+    }
+}
@@ -0,0 +1,109 @@
+//! Generator vectors pinned in a file (`cuproof generators`)
+//!
+//! `Params::derive_generators` recomputes a vector from the params fingerprint, a label and a
+//! count whenever it is needed, so nothing but the params has to be distributed. A GeneratorVector
+//! records one such derivation with its seed, so the vector can be published, compared by
+//! fingerprint between parties and checked against the params it claims to come from before
+//! proving or verifying with them
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use num_bigint::BigInt;
+use crate::setup::{FingerprintHex, Params, ParamsError};
+
+/// Most generators a vector file may hold; the decoder refuses larger counts before allocating
+pub const MAX_GENERATORS: usize = 1 << 16;
+
+/// The generators `Params::derive_generators(label, count)` returns, with what they were derived from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorVector {
+    /// `Params::fingerprint` of the params the vector was derived from
+    pub params_fingerprint: [u8; 32],
+    pub label: String,
+    pub generators: Vec<BigInt>,
+}
+
+impl GeneratorVector {
+    /// Derive `count` generators under `label` from `params`
+    /// - usage: `GeneratorVector::derive(&params, AGGREGATE_GENERATOR_LABEL, m)` gives the vector an
+    ///   aggregated proof of m statements commits under
+    pub fn derive(params: &Params, label: &str, count: usize) -> GeneratorVector {
+        GeneratorVector {
+            params_fingerprint: params.fingerprint(),
+            label: label.to_string(),
+            generators: params.derive_generators(label, count).to_vec(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.generators.len()
+    }
+
+    /// "<params fingerprint hex>/<label>/<count>": generator i is hash_to_group("<seed>/<i>", n)
+    pub fn seed(&self) -> String {
+        format!("{}/{}/{}", FingerprintHex(self.params_fingerprint), self.label, self.count())
+    }
+
+    /// SHA-256 identifying the vector: its params fingerprint, label, count and every generator
+    /// - usage: parties compare it to agree on a vector without exchanging the file
+    pub fn fingerprint(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(b"cuproof/generators");
+        hasher.update(self.params_fingerprint);
+        hasher.update((self.label.len() as u32).to_be_bytes());
+        hasher.update(self.label.as_bytes());
+        hasher.update((self.count() as u64).to_be_bytes());
+        for x in &self.generators {
+            let (_sign, bytes) = x.to_bytes_be();
+            hasher.update((bytes.len() as u32).to_be_bytes());
+            hasher.update(&bytes);
+        }
+        hasher.finalize().into()
+    }
+
+    /// The fingerprint as a value that displays as 64 lowercase hex digits
+    pub fn fingerprint_hex(&self) -> FingerprintHex {
+        FingerprintHex(self.fingerprint())
+    }
+
+    /// Check the vector is what `params.derive_generators(label, count)` produces
+    /// - returns: GeneratorsForOtherParams if the recorded fingerprint is not that of `params`,
+    ///   GeneratorVectorMismatch naming the first generator that differs otherwise
+    /// - usage: before trusting a vector read from a file, e.g. `cuproof verify --generators`
+    pub fn check(&self, params: &Params) -> Result<(), ParamsError> {
+        if self.params_fingerprint != params.fingerprint() { return Err(ParamsError::GeneratorsForOtherParams); }
+        let derived = params.derive_generators(&self.label, self.count());
+        match self.generators.iter().zip(derived.iter()).position(|(stored, derived)| stored != derived) {
+            Some(index) => Err(ParamsError::GeneratorVectorMismatch { index }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+
+    // Purpose: a derived vector checks against its params and no other, and an altered entry is named
+    // Params: insecure 512-bit and 1024-bit params, a vector of 4 under "agg/g"
+    // Output: Ok against its params; GeneratorsForOtherParams against the 1024-bit ones;
+    //         GeneratorVectorMismatch { index: 2 } and a new fingerprint once generator 2 is changed
+    // Usage: `cargo test -- generators`
+    #[test]
+    fn derived_vectors_check_against_their_params() {
+        let (params, other) = (insecure_test_setup(512), insecure_test_setup(1024));
+        let vector = GeneratorVector::derive(&params, "agg/g", 4);
+        assert_eq!(vector.count(), 4);
+        assert_eq!(vector.seed(), format!("{}/agg/g/4", params.fingerprint_hex()));
+        assert_eq!(vector.check(&params), Ok(()));
+        assert_eq!(vector.check(&other), Err(ParamsError::GeneratorsForOtherParams));
+
+        let mut altered = vector.clone();
+        altered.generators[2] += 1;
+        assert_eq!(altered.check(&params), Err(ParamsError::GeneratorVectorMismatch { index: 2 }));
+        assert_ne!(altered.fingerprint(), vector.fingerprint());
+    }
+}
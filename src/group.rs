@@ -0,0 +1,94 @@
+use num_bigint::BigInt;
+use num_traits::Zero;
+use crate::commitment::{commit, mod_exp, pedersen_commit_in};
+use crate::setup::Params;
+
+/// A group of unknown order carrying the Cuproof commitments
+///
+/// Elements leave the group as `BigInt` encodings: those are what `Cuproof` stores and what
+/// Fiat-Shamir hashes, so proofs keep one shape whatever the backend. Implemented by the RSA
+/// `Params` (elements of Z_n^*, encoded as themselves) and, with the `classgroup` feature, by
+/// `classgroup::ClassGroupParams` (reduced binary quadratic forms)
+pub trait Group {
+    type Element: Clone + PartialEq + core::fmt::Debug;
+
+    /// First commitment generator
+    fn g(&self) -> &Self::Element;
+    /// Second commitment generator, with no known discrete log relation to g
+    fn h(&self) -> &Self::Element;
+    fn identity(&self) -> Self::Element;
+    /// The group law
+    fn op(&self, x: &Self::Element, y: &Self::Element) -> Self::Element;
+    /// base^e; see each backend for how a negative e is treated
+    fn pow(&self, base: &Self::Element, e: &BigInt) -> Self::Element;
+    fn encode(&self, x: &Self::Element) -> BigInt;
+    /// Inverse of encode; None if `x` does not encode a group element
+    fn decode(&self, x: &BigInt) -> Option<Self::Element>;
+    /// Modulus Fiat-Shamir challenges are reduced by
+    fn challenge_modulus(&self) -> &BigInt;
+
+    /// Encoded Pedersen commitment g^m * h^r
+    fn commit(&self, m: &BigInt, r: &BigInt) -> BigInt {
+        self.encode(&pedersen_commit_in(self, m, r))
+    }
+
+    /// Generator epoch recorded in proofs; backends without generator rotation stay at 0
+    fn epoch(&self) -> u64 { 0 }
+
+    /// Digest identifying these parameters, recorded in proof statements
+    fn fingerprint(&self) -> [u8; 32];
+
+    /// True if `x` is an acceptable encoded element in a proof
+    fn is_element(&self, x: &BigInt) -> bool {
+        self.decode(x).is_some()
+    }
+}
+
+/// Z_n^* with the semantics `commitment::mod_exp` has always had: exponents act by absolute value
+/// and an encoding is accepted unless it is 0 mod n
+impl Group for Params {
+    type Element = BigInt;
+
+    fn g(&self) -> &BigInt { Params::g(self) }
+    fn h(&self) -> &BigInt { Params::h(self) }
+    fn identity(&self) -> BigInt { BigInt::from(1) }
+    fn op(&self, x: &BigInt, y: &BigInt) -> BigInt { x * y % self.n() }
+    fn pow(&self, base: &BigInt, e: &BigInt) -> BigInt { mod_exp(base, e, self.n()) }
+    fn encode(&self, x: &BigInt) -> BigInt { x.clone() }
+
+    fn decode(&self, x: &BigInt) -> Option<BigInt> {
+        if (x % self.n()).is_zero() { None } else { Some(x.clone()) }
+    }
+
+    fn challenge_modulus(&self) -> &BigInt { self.n() }
+    fn epoch(&self) -> u64 { Params::epoch(self) }
+    fn fingerprint(&self) -> [u8; 32] { Params::fingerprint(self) }
+
+    /// Uses the fixed-base tables; equal to the generic g^m * h^r
+    fn commit(&self, m: &BigInt, r: &BigInt) -> BigInt {
+        commit(self, m, r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+
+    // Purpose: ensure the RSA backend of Group matches the existing commitment functions
+    // Params: fast params, small messages and blindings
+    // Output: table-based and generic commitments agree; decode rejects multiples of n
+    // Usage: `cargo test -- src::group` or `cargo test`
+    #[test]
+    fn rsa_group_matches_commitment() {
+        let params = insecure_test_setup(512);
+        let (m, r) = (BigInt::from(1234), BigInt::from(98765));
+        let generic = Group::encode(&params, &pedersen_commit_in(&params, &m, &r));
+        assert_eq!(Group::commit(&params, &m, &r), generic);
+        assert_eq!(generic, crate::commitment::pedersen_commit(params.g(), params.h(), &m, &r, params.n()));
+        let a = params.pow(Group::g(&params), &BigInt::from(3));
+        assert_eq!(params.op(&a, &params.identity()), a);
+        assert!(params.decode(params.n()).is_none());
+        assert!(params.is_element(&a));
+    }
+}
@@ -0,0 +1,763 @@
+//! Structural inspection of cuproof files, without verifying anything (`cuproof info`)
+//!
+//! inspect tells proofs, aggregated proofs, comparison proofs, proof bundles, openings proofs, params, statements, commitments,
+//! blindings, generator vectors, the files of offline proving, (with the mmap feature) proof
+//! archives and (with the encryption feature) the header of encrypted files apart by their
+//! first bytes, then parses as much of the file as it can, inflating compressed params and proofs
+//! (compression feature) first, with the framing and size checks of the loaders but none of the
+//! group arithmetic: no modpow, no
+//! primality tests, no verification. A file that fails a check is still described, with every
+//! component parsed before the failure and the point where parsing stopped.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use num_bigint::BigInt;
+use crate::aggregate::MAX_AGGREGATED_STATEMENTS;
+use crate::comparison::MAX_COMPARISON_BITS;
+use crate::generators::MAX_GENERATORS;
+use crate::openings::{MAX_BATCH_OPENINGS, MAX_OPENING_BITS};
+use crate::range_proof::{Cuproof, Statement};
+use crate::setup::{Params, MAX_MODULUS_BITS};
+use crate::util::{
+    aggregated_proof_from_lines, bundle_from_lines, container_version, encode_aggregated_proof, first_message_from_lines, generators_from_lines, hex_field, is_armored, is_json, is_legacy_text, offline_state_from_lines, open_framed, decompress_payload, params_from_json, params_from_lines,
+    proof_from_armored, proof_from_json, proof_from_lines, statement_from_lines, FileKind, FormatError, CONTAINER_HEADER_LEN,
+    MAX_IPP_ROUNDS, MAX_PROOF_INT_BYTES, PROOF_FORMAT_VERSION, STATEMENT_HEADER,
+};
+
+/// What a file was recognised as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InspectedKind {
+    Proof,
+    Params,
+    /// a statement section on its own (Statement::to_bytes)
+    Statement,
+    Archive,
+    Commitment,
+    Blinding,
+    AggregatedProof,
+    /// a generator vector (`cuproof generators`)
+    Generators,
+    /// the first message of an offline proof (`cuproof prove-offline-commit`)
+    FirstMessage,
+    /// the challenges for a first message (`cuproof prove-offline-challenge`)
+    ChallengeBundle,
+    /// the prover state of an offline proof (`cuproof prove-offline-commit`); its secrets are not shown
+    OfflineState,
+    /// a proof that one committed value is below another (`cuproof prove-lt`)
+    ComparisonProof,
+    /// a proof with its statement and params (`cuproof bundle create`)
+    ProofBundle,
+    /// a proof of knowledge of the openings of a list of commitments (`cuproof prove-openings`)
+    OpeningsProof,
+    /// a file encrypted under a passphrase (util::save_encrypted); only its header can be read
+    Encrypted,
+    /// none of the above
+    Unknown,
+}
+
+impl fmt::Display for InspectedKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InspectedKind::Proof => "proof",
+            InspectedKind::Params => "params",
+            InspectedKind::Statement => "statement",
+            InspectedKind::Archive => "archive",
+            InspectedKind::Commitment => "commitment",
+            InspectedKind::Blinding => "blinding",
+            InspectedKind::AggregatedProof => "aggregated proof",
+            InspectedKind::Generators => "generators",
+            InspectedKind::FirstMessage => "first message",
+            InspectedKind::ChallengeBundle => "challenge bundle",
+            InspectedKind::OfflineState => "offline state",
+            InspectedKind::ComparisonProof => "comparison proof",
+            InspectedKind::ProofBundle => "proof bundle",
+            InspectedKind::OpeningsProof => "openings proof",
+            InspectedKind::Encrypted => "encrypted",
+            InspectedKind::Unknown => "unknown",
+        })
+    }
+}
+
+/// A fact found in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Detail {
+    Text(String),
+    Number(u64),
+    Flag(bool),
+}
+
+impl fmt::Display for Detail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Detail::Text(s) => write!(f, "{}", s),
+            Detail::Number(n) => write!(f, "{}", n),
+            Detail::Flag(b) => write!(f, "{}", if *b { "yes" } else { "no" }),
+        }
+    }
+}
+
+/// One encoded integer (or archive entry) and its size in bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub name: String,
+    pub bytes: usize,
+}
+
+/// Where and why parsing stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stopped {
+    /// the component or section that failed, e.g. `T1`, `L[3]`, `container`, `load checks`
+    pub at: String,
+    pub reason: String,
+}
+
+/// Everything inspect learned about a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    pub kind: InspectedKind,
+    /// `container`, `legacy text`, `json`, `armor`, `statement section`, `archive`, `encrypted` or `unknown`
+    pub encoding: &'static str,
+    pub size: usize,
+    /// container or archive format version; 0 for legacy text, None when the file has none
+    pub version: Option<u16>,
+    /// the components parsed, in file order
+    pub components: Vec<Component>,
+    /// named facts in a fixed order per kind: e.g. ipp_rounds, epoch, statement fields and
+    /// canonical for proofs; bits, modulus_bits, fingerprint and insecure for params
+    pub details: Vec<(&'static str, Detail)>,
+    /// None when the whole file passed the structural checks
+    pub stopped: Option<Stopped>,
+}
+
+impl FileInfo {
+    /// True when the kind was recognised and the file passed every structural check; says
+    /// nothing about whether a proof verifies
+    pub fn is_well_formed(&self) -> bool {
+        self.kind != InspectedKind::Unknown && self.stopped.is_none()
+    }
+
+    fn new(kind: InspectedKind, encoding: &'static str, size: usize) -> FileInfo {
+        FileInfo { kind, encoding, size, version: None, components: Vec::new(), details: Vec::new(), stopped: None }
+    }
+
+    fn detail(&mut self, key: &'static str, value: Detail) {
+        self.details.push((key, value));
+    }
+
+    fn component(&mut self, name: impl Into<String>, x: &BigInt) {
+        self.components.push(Component { name: name.into(), bytes: x.magnitude().to_bytes_be().len() });
+    }
+}
+
+fn stopped(at: impl Into<String>, reason: impl fmt::Display) -> Stopped {
+    Stopped { at: at.into(), reason: reason.to_string() }
+}
+
+/// Inspect the file at `path`
+/// - returns: its FileInfo, or the error of reading it; files that fail to parse are described, not errors
+/// - usage: `cuproof info <file>`
+pub fn inspect_file(path: &str) -> io::Result<FileInfo> {
+    Ok(inspect(&fs::read(path)?))
+}
+
+/// Recognise `bytes` and parse them as far as the structural checks allow (see the module docs)
+/// - returns: a FileInfo; kind Unknown when nothing matched the first bytes
+pub fn inspect(bytes: &[u8]) -> FileInfo {
+    let container = [
+        FileKind::Proof, FileKind::Params, FileKind::Commitment, FileKind::Blinding, FileKind::AggregatedProof, FileKind::Generators,
+        FileKind::FirstMessage, FileKind::ChallengeBundle, FileKind::OfflineState, FileKind::ComparisonProof, FileKind::ProofBundle,
+        FileKind::OpeningsProof,
+    ]
+        .into_iter()
+        .find(|kind| bytes.starts_with(kind.magic()));
+    if let Some(kind) = container { return inspect_container(bytes, kind); }
+    #[cfg(feature = "mmap")]
+    if bytes.starts_with(crate::archive::ARCHIVE_MAGIC) { return inspect_archive(bytes); }
+    #[cfg(feature = "encryption")]
+    if crate::encrypted::is_encrypted(bytes) { return inspect_encrypted(bytes); }
+    if bytes.starts_with(STATEMENT_HEADER.as_bytes()) { return inspect_statement(bytes); }
+    if is_armored(bytes) {
+        let mut info = FileInfo::new(InspectedKind::Proof, "armor", bytes.len());
+        match proof_from_armored(bytes) {
+            Ok(proof) => describe_decoded_proof(&mut info, &proof),
+            Err(e) => info.stopped = Some(stopped("armor", e)),
+        }
+        return info;
+    }
+    if is_json(bytes) { return inspect_json(bytes); }
+    if is_legacy_text(bytes) { return inspect_legacy(bytes); }
+    let mut info = FileInfo::new(InspectedKind::Unknown, "unknown", bytes.len());
+    info.stopped = Some(stopped("magic", "not a cuproof file"));
+    info
+}
+
+/// Proof, params, commitment, blinding, aggregated proof, comparison proof, bundle, openings proof, generator vector and offline proving containers; a container with broken framing is still
+/// walked as far as its payload goes, but the framing error is what is reported
+fn inspect_container(bytes: &[u8], kind: FileKind) -> FileInfo {
+    let inspected = match kind {
+        FileKind::Proof => InspectedKind::Proof,
+        FileKind::Params => InspectedKind::Params,
+        FileKind::Commitment => InspectedKind::Commitment,
+        FileKind::Blinding => InspectedKind::Blinding,
+        FileKind::AggregatedProof => InspectedKind::AggregatedProof,
+        FileKind::Generators => InspectedKind::Generators,
+        FileKind::FirstMessage => InspectedKind::FirstMessage,
+        FileKind::ChallengeBundle => InspectedKind::ChallengeBundle,
+        FileKind::OfflineState => InspectedKind::OfflineState,
+        FileKind::ComparisonProof => InspectedKind::ComparisonProof,
+        FileKind::ProofBundle => InspectedKind::ProofBundle,
+        FileKind::OpeningsProof => InspectedKind::OpeningsProof,
+    };
+    let mut info = FileInfo::new(inspected, "container", bytes.len());
+    info.version = container_version(bytes, kind);
+    let inflated;
+    let (payload, framing) = match open_framed(bytes, kind) {
+        Ok((_, false, payload)) => (payload, None),
+        Ok((_, true, compressed)) => {
+            info.detail("compression", Detail::Text("zstd".to_string()));
+            info.detail("compressed_bytes", Detail::Number(compressed.len() as u64));
+            inflated = match decompress_payload(compressed) {
+                Ok(inflated) => inflated,
+                Err(e) => {
+                    info.stopped = Some(stopped("compression", e));
+                    return info;
+                }
+            };
+            (&inflated[..], None)
+        }
+        Err(e @ FormatError::TruncatedHeader) => {
+            info.stopped = Some(stopped("container", e));
+            return info;
+        }
+        Err(e) => {
+            let declared = u32::from_be_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) as usize;
+            let end = bytes.len().min(CONTAINER_HEADER_LEN.saturating_add(declared));
+            (&bytes[CONTAINER_HEADER_LEN..end], Some(stopped("container", e)))
+        }
+    };
+    let text = String::from_utf8_lossy(payload);
+    let lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let with_statement = info.version == Some(PROOF_FORMAT_VERSION);
+    let walked = match kind {
+        FileKind::Proof => walk_proof(&mut info, &lines, payload, with_statement),
+        FileKind::Params => walk_params(&mut info, &lines, payload),
+        FileKind::Commitment => walk_commitment(&mut info, &lines),
+        FileKind::Blinding => walk_blinding(&mut info, &lines),
+        FileKind::AggregatedProof => walk_aggregated_proof(&mut info, &lines, payload),
+        FileKind::Generators => walk_generators(&mut info, &lines),
+        FileKind::FirstMessage => walk_first_message(&mut info, &lines, false),
+        FileKind::ChallengeBundle => walk_first_message(&mut info, &lines, true),
+        FileKind::OfflineState => walk_offline_state(&mut info, &lines),
+        FileKind::ComparisonProof => walk_comparison_proof(&mut info, &lines, payload),
+        FileKind::ProofBundle => walk_bundle(&mut info, &lines),
+        FileKind::OpeningsProof => walk_openings_proof(&mut info, &lines),
+    };
+    info.stopped = framing.or(walked.err());
+    info
+}
+
+/// Legacy text files are proofs or params; the proof layout is tried first since params files
+/// are too short for it
+fn inspect_legacy(bytes: &[u8]) -> FileInfo {
+    let text = String::from_utf8_lossy(bytes);
+    let lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut info = FileInfo::new(InspectedKind::Proof, "legacy text", bytes.len());
+    info.version = Some(0);
+    if lines.len() > 15 {
+        info.stopped = walk_proof(&mut info, &lines, &[], false).err();
+    } else {
+        info.kind = InspectedKind::Params;
+        info.stopped = walk_params(&mut info, &lines, &[]).err();
+    }
+    info
+}
+
+fn inspect_json(bytes: &[u8]) -> FileInfo {
+    let mut info = FileInfo::new(InspectedKind::Proof, "json", bytes.len());
+    match (proof_from_json(bytes), params_from_json(bytes)) {
+        (Ok(proof), _) => describe_decoded_proof(&mut info, &proof),
+        (_, Ok(params)) => {
+            info.kind = InspectedKind::Params;
+            for (name, x) in [("g", params.g()), ("h", params.h()), ("n", params.n())] { info.component(name, x); }
+            describe_params(&mut info, &params, None);
+        }
+        (Err(proof_err), Err(_)) => {
+            info.kind = InspectedKind::Unknown;
+            info.stopped = Some(stopped("json", proof_err));
+        }
+    }
+    info
+}
+
+fn inspect_statement(bytes: &[u8]) -> FileInfo {
+    let mut info = FileInfo::new(InspectedKind::Statement, "statement section", bytes.len());
+    let text = String::from_utf8_lossy(bytes);
+    let lines: Vec<String> = text.lines().map(str::to_string).collect();
+    match statement_from_lines(&lines) {
+        Ok(statement) => {
+            for (name, x) in [("commitment", &statement.commitment), ("a", &statement.a), ("b", &statement.b)] { info.component(name, x); }
+            describe_statement(&mut info, &statement);
+            info.detail("canonical", Detail::Flag(statement.to_bytes() == bytes));
+        }
+        Err(e) => info.stopped = Some(stopped("statement", e)),
+    }
+    info
+}
+
+/// The lines of a payload, handed out one at a time
+struct Walk<'a> {
+    lines: &'a [String],
+    next: usize,
+}
+
+impl<'a> Walk<'a> {
+    fn new(lines: &'a [String]) -> Walk<'a> {
+        Walk { lines, next: 0 }
+    }
+
+    fn line(&mut self, at: &str) -> Result<&'a str, Stopped> {
+        let line = self.lines.get(self.next).ok_or_else(|| stopped(at, "unexpected end of file"))?;
+        self.next += 1;
+        Ok(line)
+    }
+
+    /// The next line as a hex integer of at most `max_bytes` bytes, recorded as a component
+    fn int(&mut self, info: &mut FileInfo, name: String, max_bytes: usize) -> Result<BigInt, Stopped> {
+        let line = self.line(&name)?;
+        let x = hex_field(line, "integer", max_bytes).map_err(|e| stopped(name.as_str(), e))?;
+        info.component(name, &x);
+        Ok(x)
+    }
+}
+
+/// The proof fields before the inner-product vectors, in file order
+const PROOF_SCALARS: [&str; 15] = ["A", "S", "T1", "T2", "tau_x", "mu", "t_hat", "C", "C_v1", "C_v2", "t0", "t1", "t2", "tau1", "tau2"];
+
+/// Walk a proof payload field by field, then run the loader's own checks; `payload` is empty for
+/// legacy text, which is never canonical
+fn walk_proof(info: &mut FileInfo, lines: &[String], payload: &[u8], with_statement: bool) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    for name in PROOF_SCALARS { walk.int(info, name.to_string(), MAX_PROOF_INT_BYTES)?; }
+    let mut rounds = [0usize; 2];
+    for (side, count) in ["L", "R"].into_iter().zip(&mut rounds) {
+        let line = walk.line(side)?;
+        *count = line.trim().parse().map_err(|_| stopped(side, format!("invalid {} length", side)))?;
+        if *count == 0 || *count > MAX_IPP_ROUNDS {
+            return Err(stopped(side, format!("{} rounds, expected 1 to {}", count, MAX_IPP_ROUNDS)));
+        }
+        for k in 0..*count { walk.int(info, format!("{}[{}]", side, k), MAX_PROOF_INT_BYTES)?; }
+    }
+    if rounds[0] != rounds[1] { return Err(stopped("R", "L and R length mismatch")); }
+    info.detail("ipp_rounds", Detail::Number(rounds[0] as u64));
+    for name in ["ipp a", "ipp b"] { walk.int(info, name.to_string(), MAX_PROOF_INT_BYTES)?; }
+    let proof = proof_from_lines(lines, with_statement).map_err(|e| stopped("load checks", e))?;
+    describe_proof(info, &proof);
+    info.detail("canonical", Detail::Flag(!payload.is_empty() && proof.to_bytes() == payload));
+    Ok(())
+}
+
+/// Walk the commitments of every statement, the shared fields and the inner-product vectors of an
+/// aggregated proof, then run the loader's own checks, which also parse the statement sections
+fn walk_aggregated_proof(info: &mut FileInfo, lines: &[String], payload: &[u8]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    let line = walk.line("statements")?;
+    let count: usize = line.trim().parse().map_err(|_| stopped("statements", "invalid statement count"))?;
+    if count == 0 || count > MAX_AGGREGATED_STATEMENTS {
+        return Err(stopped("statements", format!("{} statements, expected 1 to {}", count, MAX_AGGREGATED_STATEMENTS)));
+    }
+    info.detail("statements", Detail::Number(count as u64));
+    for j in 0..count {
+        for name in ["C", "C_v1", "C_v2"] { walk.int(info, format!("{}[{}]", name, j), MAX_PROOF_INT_BYTES)?; }
+    }
+    for name in PROOF_SCALARS.into_iter().filter(|name| !["C", "C_v1", "C_v2"].contains(name)) {
+        walk.int(info, name.to_string(), MAX_PROOF_INT_BYTES)?;
+    }
+    let mut rounds = [0usize; 2];
+    for (side, count) in ["L", "R"].into_iter().zip(&mut rounds) {
+        let line = walk.line(side)?;
+        *count = line.trim().parse().map_err(|_| stopped(side, format!("invalid {} length", side)))?;
+        if *count == 0 || *count > MAX_IPP_ROUNDS {
+            return Err(stopped(side, format!("{} rounds, expected 1 to {}", count, MAX_IPP_ROUNDS)));
+        }
+        for k in 0..*count { walk.int(info, format!("{}[{}]", side, k), MAX_PROOF_INT_BYTES)?; }
+    }
+    if rounds[0] != rounds[1] { return Err(stopped("R", "L and R length mismatch")); }
+    info.detail("ipp_rounds", Detail::Number(rounds[0] as u64));
+    for name in ["ipp a", "ipp b"] { walk.int(info, name.to_string(), MAX_PROOF_INT_BYTES)?; }
+    let proof = aggregated_proof_from_lines(lines).map_err(|e| stopped("load checks", e))?;
+    info.detail("epoch", Detail::Number(proof.epoch));
+    info.detail("params_fingerprint", Detail::Text(hex::encode(proof.statements[0].params_fingerprint)));
+    let matching = proof.statements.iter().zip(&proof.commitments).filter(|(statement, c)| statement.commitment == c.C).count();
+    info.detail("statements_match_commitments", Detail::Flag(matching == count));
+    let canonical = encode_aggregated_proof(&proof).is_ok_and(|bytes| bytes[CONTAINER_HEADER_LEN..bytes.len() - 32] == *payload);
+    info.detail("canonical", Detail::Flag(canonical));
+    Ok(())
+}
+
+/// The details of a proof decoded from JSON or armor, whose components come from the struct
+fn describe_decoded_proof(info: &mut FileInfo, proof: &Cuproof) {
+    let scalars = [
+        &proof.A, &proof.S, &proof.T1, &proof.T2, &proof.tau_x, &proof.mu, &proof.t_hat, &proof.C, &proof.C_v1, &proof.C_v2,
+        &proof.t0, &proof.t1, &proof.t2, &proof.tau1, &proof.tau2,
+    ];
+    for (name, x) in PROOF_SCALARS.into_iter().zip(scalars) { info.component(name, x); }
+    for (side, vec) in [("L", &proof.ipp_proof.L), ("R", &proof.ipp_proof.R)] {
+        for (k, x) in vec.iter().enumerate() { info.component(format!("{}[{}]", side, k), x); }
+    }
+    info.component("ipp a", &proof.ipp_proof.a);
+    info.component("ipp b", &proof.ipp_proof.b);
+    info.detail("ipp_rounds", Detail::Number(proof.ipp_proof.L.len() as u64));
+    describe_proof(info, proof);
+}
+
+fn describe_proof(info: &mut FileInfo, proof: &Cuproof) {
+    info.detail("epoch", Detail::Number(proof.epoch));
+    match &proof.statement {
+        Some(statement) => {
+            describe_statement(info, statement);
+            info.detail("statement_matches_commitment", Detail::Flag(statement.commitment == proof.C));
+        }
+        None => info.detail("statement", Detail::Text("none recorded".to_string())),
+    }
+}
+
+fn describe_statement(info: &mut FileInfo, statement: &Statement) {
+    info.detail("commitment", Detail::Text(crate::util::bigint_to_hex(&statement.commitment)));
+    info.detail("range", Detail::Text(format!(
+        "[{}, {}]", crate::util::bigint_to_signed_hex(&statement.a), crate::util::bigint_to_signed_hex(&statement.b)
+    )));
+    info.detail("bits", Detail::Number(statement.bits as u64));
+    info.detail("label", Detail::Text(statement.label.clone()));
+    info.detail("params_fingerprint", Detail::Text(hex::encode(statement.params_fingerprint)));
+}
+
+/// Walk g, h and n, then the rest of the params payload with the unvalidated loader
+fn walk_params(info: &mut FileInfo, lines: &[String], payload: &[u8]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    for name in ["g", "h", "n"] { walk.int(info, name.to_string(), MAX_MODULUS_BITS / 8)?; }
+    let params = params_from_lines(lines).map_err(|e| stopped("params fields", e))?;
+    describe_params(info, &params, (!payload.is_empty()).then(|| params.to_bytes() == payload));
+    Ok(())
+}
+
+fn describe_params(info: &mut FileInfo, params: &Params, canonical: Option<bool>) {
+    info.detail("bits", Detail::Number(params.bits() as u64));
+    info.detail("modulus_bits", Detail::Number(params.n().bits()));
+    info.detail("fingerprint", Detail::Text(params.fingerprint_hex().to_string()));
+    info.detail("epoch", Detail::Number(params.epoch()));
+    info.detail("generator_seed", Detail::Text(params.generator_seed().unwrap_or("").to_string()));
+    info.detail("insecure", Detail::Flag(params.is_insecure()));
+    info.detail("metadata", Detail::Flag(params.metadata().is_some()));
+    info.detail("canonical", Detail::Flag(canonical.unwrap_or(false)));
+}
+
+fn walk_commitment(info: &mut FileInfo, lines: &[String]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    walk.int(info, "commitment".to_string(), MAX_MODULUS_BITS / 8)?;
+    let fingerprint = walk.line("params fingerprint")?.trim();
+    if fingerprint.len() != 64 || hex::decode(fingerprint).is_err() {
+        return Err(stopped("params fingerprint", "not 32 bytes of hex"));
+    }
+    info.detail("params_fingerprint", Detail::Text(fingerprint.to_string()));
+    if walk.next < lines.len() { return Err(stopped("commitment", "commitment file must hold two lines")); }
+    Ok(())
+}
+
+/// Only the size of a blinding is reported: the value opens the commitment
+fn walk_blinding(info: &mut FileInfo, lines: &[String]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    walk.int(info, "blinding".to_string(), MAX_PROOF_INT_BYTES)?;
+    if walk.next < lines.len() { return Err(stopped("blinding", "blinding file must hold one line")); }
+    Ok(())
+}
+
+/// Walk the derivation seed and every generator of a vector; whether they match their params
+/// needs the params (GeneratorVector::check)
+fn walk_generators(info: &mut FileInfo, lines: &[String]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    let fingerprint = walk.line("params fingerprint")?.trim();
+    if fingerprint.len() != 64 || hex::decode(fingerprint).is_err() {
+        return Err(stopped("params fingerprint", "not 32 bytes of hex"));
+    }
+    info.detail("params_fingerprint", Detail::Text(fingerprint.to_string()));
+    let label = hex::decode(walk.line("label")?.trim()).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| stopped("label", "not UTF-8 as hex"))?;
+    info.detail("label", Detail::Text(label));
+    let count: usize = walk.line("count")?.trim().parse().map_err(|_| stopped("count", "invalid generator count"))?;
+    if count > MAX_GENERATORS { return Err(stopped("count", format!("{} generators, expected at most {}", count, MAX_GENERATORS))); }
+    info.detail("count", Detail::Number(count as u64));
+    for i in 0..count { walk.int(info, format!("g[{}]", i), MAX_MODULUS_BITS / 8)?; }
+    if walk.next < lines.len() { return Err(stopped("generators", "more generators than the count")); }
+    let vector = generators_from_lines(lines).map_err(|e| stopped("load checks", e))?;
+    info.detail("fingerprint", Detail::Text(vector.fingerprint_hex().to_string()));
+    Ok(())
+}
+
+/// Walk the id and commitments of a first message, and the challenges of a bundle, then its statement
+fn walk_first_message(info: &mut FileInfo, lines: &[String], with_challenges: bool) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    let id = walk.line("id")?.trim();
+    if id.len() != 32 || hex::decode(id).is_err() { return Err(stopped("id", "not 16 bytes of hex")); }
+    info.detail("id", Detail::Text(id.to_string()));
+    for name in ["A", "S", "C_v1", "C_v2"] { walk.int(info, name.to_string(), MAX_PROOF_INT_BYTES)?; }
+    if with_challenges {
+        for name in ["y", "z"] { walk.int(info, name.to_string(), MAX_PROOF_INT_BYTES)?; }
+        let nonce = walk.line("nonce")?.trim();
+        if hex::decode(nonce).is_err() { return Err(stopped("nonce", "not hex")); }
+        info.detail("nonce", Detail::Text(nonce.to_string()));
+    }
+    let (message, _) = first_message_from_lines(lines, with_challenges).map_err(|e| stopped("load checks", e))?;
+    describe_statement(info, &message.statement);
+    Ok(())
+}
+
+/// Walk the bit width and shift of a comparison proof, then its range proof as walk_proof does
+fn walk_comparison_proof(info: &mut FileInfo, lines: &[String], payload: &[u8]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    for name in ["bits", "shift"] {
+        let width: u32 = walk.line(name)?.trim().parse().map_err(|_| stopped(name, format!("invalid {}", name)))?;
+        if width == 0 || width > MAX_COMPARISON_BITS {
+            return Err(stopped(name, format!("{} {}, expected 1 to {}", name, width, MAX_COMPARISON_BITS)));
+        }
+        info.detail(name, Detail::Number(u64::from(width)));
+    }
+    let proof_payload = payload.splitn(3, |&b| b == b'\n').nth(2).unwrap_or_default();
+    walk_proof(info, &lines[2..], proof_payload, true)
+}
+
+/// Walk the commitments of an openings proof, then its width, announcement and responses
+fn walk_openings_proof(info: &mut FileInfo, lines: &[String]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    let count: usize = walk.line("count")?.trim().parse().map_err(|_| stopped("count", "invalid commitment count"))?;
+    if count > MAX_BATCH_OPENINGS { return Err(stopped("count", format!("{} commitments, expected at most {}", count, MAX_BATCH_OPENINGS))); }
+    info.detail("commitments", Detail::Number(count as u64));
+    for i in 0..count { walk.int(info, format!("C[{}]", i), MAX_MODULUS_BITS / 8)?; }
+    let bits: u32 = walk.line("bits")?.trim().parse().map_err(|_| stopped("bits", "invalid bits"))?;
+    if bits > MAX_OPENING_BITS { return Err(stopped("bits", format!("bits {}, expected at most {}", bits, MAX_OPENING_BITS))); }
+    info.detail("bits", Detail::Number(u64::from(bits)));
+    walk.int(info, "announcement".to_string(), MAX_MODULUS_BITS / 8)?;
+    for name in ["z_m", "z_r"] { walk.int(info, name.to_string(), MAX_PROOF_INT_BYTES)?; }
+    if walk.next < lines.len() { return Err(stopped("z_r", "lines after the responses")); }
+    Ok(())
+}
+
+/// The params fingerprint, whether params are embedded and the metadata of a bundle, then its
+/// statement and proof as the loader reads them; the components are the proof's
+fn walk_bundle(info: &mut FileInfo, lines: &[String]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    let fingerprint = walk.line("params fingerprint")?.trim();
+    if fingerprint.len() != 64 || hex::decode(fingerprint).is_err() {
+        return Err(stopped("params fingerprint", "not 32 bytes of hex"));
+    }
+    let bundle = bundle_from_lines(lines).map_err(|e| stopped("load checks", e))?;
+    info.detail("embedded_params", Detail::Flag(bundle.params.is_some()));
+    info.detail("metadata", Detail::Number(bundle.metadata.len() as u64));
+    describe_decoded_proof(info, &bundle.proof);
+    // the bundle's own statement last, so its fields are the ones a reader sees
+    describe_statement(info, &bundle.statement);
+    info.detail("statement_matches_commitment", Detail::Flag(bundle.statement.commitment == bundle.proof.C));
+    Ok(())
+}
+
+/// Only the id and dimension of an offline state are reported; the rest is the witness's secrets
+fn walk_offline_state(info: &mut FileInfo, lines: &[String]) -> Result<(), Stopped> {
+    let mut walk = Walk::new(lines);
+    let id = walk.line("id")?.trim();
+    if id.len() != 32 || hex::decode(id).is_err() { return Err(stopped("id", "not 16 bytes of hex")); }
+    info.detail("id", Detail::Text(id.to_string()));
+    let state = offline_state_from_lines(lines).map_err(|e| stopped("load checks", e))?;
+    info.detail("dimension", Detail::Number(state.round.d.expose().len() as u64));
+    Ok(())
+}
+
+/// Only the header of an encrypted file is reported: its Argon2id settings and the ciphertext size
+#[cfg(feature = "encryption")]
+fn inspect_encrypted(bytes: &[u8]) -> FileInfo {
+    let mut info = FileInfo::new(InspectedKind::Encrypted, "encrypted", bytes.len());
+    match crate::encrypted::EncryptedHeader::parse(bytes) {
+        Ok(header) => {
+            info.version = Some(header.version);
+            info.detail("kdf", Detail::Text("argon2id".to_string()));
+            info.detail("memory_kib", Detail::Number(header.kdf.memory_kib.into()));
+            info.detail("passes", Detail::Number(header.kdf.passes.into()));
+            info.detail("lanes", Detail::Number(header.kdf.lanes.into()));
+            info.detail("ciphertext_bytes", Detail::Number(header.ciphertext_len as u64));
+        }
+        Err(e) => info.stopped = Some(stopped("header", e)),
+    }
+    info
+}
+
+/// The archive header and index, and the container framing of every entry; entries are not decoded
+#[cfg(feature = "mmap")]
+fn inspect_archive(bytes: &[u8]) -> FileInfo {
+    use crate::archive::{ARCHIVE_FORMAT_VERSION, ARCHIVE_HEADER_LEN, INDEX_CHECKSUM_LEN, INDEX_ENTRY_LEN};
+    use sha2::{Digest, Sha256};
+    let mut info = FileInfo::new(InspectedKind::Archive, "archive", bytes.len());
+    if bytes.len() < ARCHIVE_HEADER_LEN {
+        info.stopped = Some(stopped("header", "archive ends inside its header"));
+        return info;
+    }
+    let version = u16::from_be_bytes([bytes[8], bytes[9]]);
+    info.version = Some(version);
+    if version != ARCHIVE_FORMAT_VERSION {
+        info.stopped = Some(stopped("header", format!("unsupported archive version {}", version)));
+        return info;
+    }
+    let count = u32::from_be_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]) as usize;
+    info.detail("entries", Detail::Number(count as u64));
+    let index_end = ARCHIVE_HEADER_LEN.saturating_add(count.saturating_mul(INDEX_ENTRY_LEN));
+    if bytes.len() < index_end.saturating_add(INDEX_CHECKSUM_LEN) {
+        info.stopped = Some(stopped("index", format!("archive ends inside its index of {} entries", count)));
+        return info;
+    }
+    if Sha256::digest(&bytes[..index_end])[..] != bytes[index_end..index_end + INDEX_CHECKSUM_LEN] {
+        info.stopped = Some(stopped("index", "index checksum mismatch"));
+        return info;
+    }
+    for index in 0..count {
+        let at = ARCHIVE_HEADER_LEN + index * INDEX_ENTRY_LEN;
+        let offset = u64::from_be_bytes(bytes[at..at + 8].try_into().expect("8-byte slice"));
+        let len = u32::from_be_bytes(bytes[at + 8..at + 12].try_into().expect("4-byte slice"));
+        let name = format!("entry {}", index);
+        let Some(entry) = usize::try_from(offset).ok().and_then(|start| bytes.get(start..start.checked_add(len as usize)?)) else {
+            info.stopped = Some(stopped(name, "entry lies outside the file"));
+            return info;
+        };
+        info.components.push(Component { name: name.clone(), bytes: entry.len() });
+        if let Err(e) = crate::util::open_container(entry, FileKind::Proof) {
+            info.stopped = Some(stopped(name, e));
+            return info;
+        }
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_proof::prove;
+    use crate::setup::insecure_test_setup;
+    use crate::util::{encode_proof, random_bigint};
+
+    fn detail<'a>(info: &'a FileInfo, key: &str) -> Option<&'a Detail> {
+        info.details.iter().find(|(k, _)| *k == key).map(|(_, d)| d)
+    }
+
+    // Purpose: ensure well-formed proofs, params and statements are recognised and fully described
+    // Params: fast 512-bit params and a labelled proof of 42 in [10, 100], encoded as containers, as a
+    //         statement section, with the mmap feature twice in an archive, with the encryption
+    //         feature encrypted under a passphrase, and with the compression feature compressed
+    // Output: kinds, versions, every component in order, ipp_rounds, the statement details and
+    //         canonical = yes; for the encrypted proof only its header settings; the compressed
+    //         proof is described like the plain one, with compression = zstd
+    // Usage: `cargo test -- src::inspect` or `cargo test`
+    #[test]
+    fn describes_well_formed_files() {
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(42), &random_bigint(128), &BigInt::from(10), &BigInt::from(100), &params)
+            .unwrap()
+            .with_label("audit");
+        let bytes = encode_proof(&proof).unwrap();
+        let info = inspect(&bytes);
+        assert!(info.is_well_formed(), "{:?}", info.stopped);
+        assert_eq!((info.kind, info.encoding, info.version, info.size), (InspectedKind::Proof, "container", Some(PROOF_FORMAT_VERSION), bytes.len()));
+        let rounds = proof.ipp_proof.L.len();
+        assert_eq!(info.components.len(), 15 + 2 * rounds + 2);
+        assert_eq!(info.components[0].name, "A");
+        assert_eq!(info.components[15].name, "L[0]");
+        assert_eq!(detail(&info, "ipp_rounds"), Some(&Detail::Number(rounds as u64)));
+        assert_eq!(detail(&info, "bits"), Some(&Detail::Number(64)));
+        assert_eq!(detail(&info, "label"), Some(&Detail::Text("audit".to_string())));
+        assert_eq!(detail(&info, "params_fingerprint"), Some(&Detail::Text(params.fingerprint_hex().to_string())));
+        assert_eq!(detail(&info, "canonical"), Some(&Detail::Flag(true)));
+
+        let info = inspect(&crate::util::encode_params(&params).unwrap());
+        assert!(info.is_well_formed(), "{:?}", info.stopped);
+        assert_eq!(info.kind, InspectedKind::Params);
+        assert_eq!(info.components.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["g", "h", "n"]);
+        assert_eq!(detail(&info, "bits"), Some(&Detail::Number(512)));
+        assert_eq!(detail(&info, "insecure"), Some(&Detail::Flag(true)));
+        assert_eq!(detail(&info, "canonical"), Some(&Detail::Flag(true)));
+
+        let info = inspect(&proof.statement.as_ref().unwrap().to_bytes());
+        assert!(info.is_well_formed(), "{:?}", info.stopped);
+        assert_eq!(info.kind, InspectedKind::Statement);
+
+        #[cfg(feature = "mmap")]
+        {
+            let path = std::env::temp_dir().join(format!("cuproof_inspect_{}.arch", std::process::id()));
+            let path = path.to_str().unwrap();
+            crate::archive::ProofArchive::create(path, &[proof.clone(), proof]).unwrap();
+            let info = inspect_file(path).unwrap();
+            assert!(info.is_well_formed(), "{:?}", info.stopped);
+            assert_eq!((info.kind, info.components.len()), (InspectedKind::Archive, 2));
+            assert_eq!(info.components[0].bytes, bytes.len());
+            let _ = fs::remove_file(path);
+        }
+
+        #[cfg(feature = "encryption")]
+        {
+            use crate::encrypted::{encrypt_with, KdfParams};
+            let kdf = KdfParams { memory_kib: 64, passes: 1, lanes: 1 };
+            let encrypted = encrypt_with(&bytes, b"pw", &kdf, &mut rand::rngs::OsRng).unwrap();
+            let info = inspect(&encrypted);
+            assert!(info.is_well_formed(), "{:?}", info.stopped);
+            assert_eq!((info.kind, info.encoding), (InspectedKind::Encrypted, "encrypted"));
+            assert_eq!(detail(&info, "memory_kib"), Some(&Detail::Number(64)));
+            assert!(info.components.is_empty());
+        }
+
+        #[cfg(feature = "compression")]
+        {
+            let compressed = crate::util::compress_container(&bytes, crate::util::DEFAULT_COMPRESSION_LEVEL).unwrap();
+            let info = inspect(&compressed);
+            assert!(info.is_well_formed(), "{:?}", info.stopped);
+            assert_eq!((info.kind, info.version), (InspectedKind::Proof, Some(PROOF_FORMAT_VERSION)));
+            assert_eq!(info.components.len(), 15 + 2 * rounds + 2);
+            assert_eq!(detail(&info, "compression"), Some(&Detail::Text("zstd".to_string())));
+            assert_eq!(detail(&info, "canonical"), Some(&Detail::Flag(true)));
+        }
+    }
+
+    // Purpose: ensure damaged files are described up to the point where parsing stopped
+    // Params: a proof container cut after its first fields, a proof with a bad hex digit in T2, a
+    //         proof whose header claims too few bytes, random bytes and an empty file
+    // Output: the truncated and corrupted proofs keep the components before the damage and name
+    //         where they stopped; random bytes are Unknown
+    // Usage: `cargo test -- src::inspect` or `cargo test`
+    #[test]
+    fn reports_how_far_parsing_got() {
+        use rand::{RngCore, SeedableRng};
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(42), &random_bigint(128), &BigInt::from(10), &BigInt::from(100), &params).unwrap();
+        let bytes = encode_proof(&proof).unwrap();
+
+        let truncated = inspect(&bytes[..CONTAINER_HEADER_LEN + 300]);
+        assert_eq!(truncated.kind, InspectedKind::Proof);
+        assert_eq!(truncated.stopped.as_ref().map(|s| s.at.as_str()), Some("container"));
+        assert!(!truncated.components.is_empty() && truncated.components.len() < 15, "{:?}", truncated.components);
+
+        let text = String::from_utf8_lossy(&bytes[CONTAINER_HEADER_LEN..bytes.len() - 32]).to_string();
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        lines[3] = "zz".to_string();
+        let info = inspect(lines.join("\n").as_bytes());
+        assert_eq!((info.kind, info.encoding), (InspectedKind::Proof, "legacy text"));
+        assert_eq!(info.components.len(), 3);
+        assert_eq!(info.stopped.as_ref().map(|s| s.at.as_str()), Some("T2"));
+
+        let mut short = bytes.clone();
+        short[13] -= 1;
+        let info = inspect(&short);
+        assert!(!info.is_well_formed());
+        assert!(info.stopped.as_ref().unwrap().reason.contains("unexpected bytes"), "{:?}", info.stopped);
+
+        let mut noise = vec![0u8; 4096];
+        rand_chacha::ChaCha20Rng::seed_from_u64(153).fill_bytes(&mut noise);
+        for bytes in [&noise[..], &[]] {
+            let info = inspect(bytes);
+            assert_eq!((info.kind, info.stopped.as_ref().map(|s| s.at.as_str())), (InspectedKind::Unknown, Some("magic")));
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use num_bigint::BigInt;
+use crate::setup::{params_from_modulus, Params, SetupError};
+
+/// RSA-2048 from the RSA Factoring Challenge (RSA Laboratories, 1991), decimal as published
+/// The challenge was withdrawn in 2007 unsolved; RSA Laboratories stated the key was generated
+/// offline and the machine's disk destroyed, so no party is known to hold its factors
+pub const RSA_2048_DECIMAL: &str = "25195908475657893494027183240048398571429282126204032027777137836043662020707595556264018525880784406918290641249515082189298559149176184502808489120072844992687392807287776735971418347270261896375014971824691165077613379859095700097330459748808428401797429100642458691817195118746121515172654632282216869987549182422433637259085141865462043576798423387184774447920739934236584823824281198163815010674810451660377306056201619676256133844143603833904414952634432190114657544454178424020924616515723350778707749817125772467962926386356373289912154831438167899885040445364023527381951378636564391212010397122822120720357";
+
+/// Names accepted by `known_modulus`
+pub const KNOWN_MODULUS_NAMES: [&str; 1] = ["rsa-2048"];
+
+/// The RSA-2048 challenge modulus
+pub fn rsa_2048() -> BigInt {
+    BigInt::parse_bytes(RSA_2048_DECIMAL.as_bytes(), 10).expect("RSA_2048_DECIMAL is a decimal integer")
+}
+
+/// Look up a shipped modulus by name (see `KNOWN_MODULUS_NAMES`)
+/// - returns: the modulus and its size in bits, or None for unknown names
+pub fn known_modulus(name: &str) -> Option<(BigInt, usize)> {
+    match name {
+        "rsa-2048" => Some((rsa_2048(), 2048)),
+        _ => None,
+    }
+}
+
+/// Dealer-free Params over the RSA-2048 challenge modulus with hash-derived generators
+pub fn rsa_2048_params() -> Result<Params, SetupError> {
+    params_from_modulus(&rsa_2048(), 2048)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Purpose: ensure the shipped RSA-2048 constant is intact and yields valid params
+    // Params: RSA_2048_DECIMAL
+    // Output: published digit count, bit length and hex prefix; params_from_modulus succeeds
+    // Usage: `cargo test --features known-moduli -- src::known_moduli`
+    #[test]
+    fn rsa_2048_constant_and_params() {
+        assert_eq!(RSA_2048_DECIMAL.len(), 617);
+        let n = rsa_2048();
+        assert_eq!(n.bits(), 2048);
+        assert!(crate::util::bigint_to_hex(&n).starts_with("c7970ceedcc3b0754490201a"));
+        let params = rsa_2048_params().unwrap();
+        assert_eq!(params.n(), &n);
+        assert!(params.generators_match_seed());
+        assert_eq!(known_modulus("rsa-2048"), Some((n, 2048)));
+        assert_eq!(known_modulus("rsa-4096"), None);
+    }
+}
@@ -1,23 +1,33 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use num_bigint::{BigInt, ToBigInt};
-use num_traits::{One, ToPrimitive};
+use num_integer::Roots;
+use num_traits::{One, Signed, ToPrimitive};
 
-pub fn find_4_squares(n: &BigInt) -> Vec<BigInt> {
-	let n_u = n.to_u64().unwrap_or(0);
+/// Brute-force a, b, c, d with a² + b² + c² + d² = n
+/// - returns: None when n is negative or does not fit in 32 bits
+pub fn find_4_squares(n: &BigInt) -> Option<Vec<BigInt>> {
+	let n_u = n.to_u32()? as u64;
 	for a in 0..=n_u {
+		if a*a > n_u { break; }
 		for b in 0..=a {
+			if a*a + b*b > n_u { break; }
 			for c in 0..=b {
-				let rem = n_u - a*a - b*b - c*c;
-				let d = (rem as f64).sqrt().floor() as u64;
+				let Some(rem) = n_u.checked_sub(a*a + b*b + c*c) else { break };
+				let d = rem.sqrt();
 				if a*a + b*b + c*c + d*d == n_u {
-					return vec![a, b, c, d].into_iter().map(|x| x.to_bigint().unwrap()).collect();
+					return Some(vec![a, b, c, d].into_iter().map(|x| x.to_bigint().unwrap()).collect());
 				}
 			}
 		}
 	}
-	panic!("Cannot find 4 squares for {}", n);
+	None
 }
 
-pub fn find_3_squares(n: &BigInt) -> Vec<BigInt> {
+/// Three squares summing to n (exact for small n, a demo heuristic for large n)
+/// - returns: None when n is negative
+pub fn find_3_squares(n: &BigInt) -> Option<Vec<BigInt>> {
+	if n.is_negative() { return None; }
 	// For large numbers, use a simplified approach
 	// Since we're dealing with numbers of form 4x+1, we can use known patterns
 	
@@ -29,9 +39,9 @@ pub fn find_3_squares(n: &BigInt) -> Vec<BigInt> {
 					let ab = a*a + b*b;
 					if ab > n_u { break; }
 					let rem = n_u - ab;
-					let c = (rem as f64).sqrt().floor() as u64;
+					let c = rem.sqrt();
 					if a*a + b*b + c*c == n_u {
-						return vec![a, b, c].into_iter().map(|x| x.to_bigint().unwrap()).collect();
+						return Some(vec![a, b, c].into_iter().map(|x| x.to_bigint().unwrap()).collect());
 					}
 				}
 			}
@@ -52,7 +62,7 @@ pub fn find_3_squares(n: &BigInt) -> Vec<BigInt> {
 		let sum = term1 * term1 + term2 * term2 + &one;
 		
 		if &sum == n {
-			return vec![term1.clone(), term2.clone(), one.clone()];
+			return Some(vec![term1.clone(), term2.clone(), one.clone()]);
 		}
 		
 		if &sum > n {
@@ -75,11 +85,11 @@ pub fn find_3_squares(n: &BigInt) -> Vec<BigInt> {
 	let sum_squares = a_sq + b_sq + c_sq;
 	
 	if sum_squares <= *n {
-		return vec![a.clone(), b.clone(), c.clone()];
+		return Some(vec![a.clone(), b.clone(), c.clone()]);
 	}
 	
 	// Last resort: use small values
-	vec![BigInt::from(1u32), BigInt::from(1u32), BigInt::from(1u32)]
+	Some(vec![BigInt::from(1u32), BigInt::from(1u32), BigInt::from(1u32)])
 }
 
 #[cfg(test)]
@@ -94,15 +104,19 @@ mod tests {
     #[test]
     fn small_numbers_have_valid_decompositions() {
         // 4-squares should always return 4 components
-        let four = find_4_squares(&BigInt::from(30));
+        let four = find_4_squares(&BigInt::from(30)).unwrap();
         assert_eq!(four.len(), 4);
         let sum4: u128 = four.iter().map(|x| x.to_u128().unwrap()).map(|x| x*x).sum();
         assert_eq!(sum4, 30u128);
 
         // 3-squares heuristic should return 3 components for 4k+1 (e.g., 29 = 4*7+1)
-        let three = find_3_squares(&BigInt::from(29));
+        let three = find_3_squares(&BigInt::from(29)).unwrap();
         assert_eq!(three.len(), 3);
         let sum3: u128 = three.iter().map(|x| x.to_u128().unwrap()).map(|x| x*x).sum();
         assert_eq!(sum3, 29u128);
+
+        // negative inputs are rejected instead of panicking
+        assert!(find_3_squares(&BigInt::from(-3)).is_none());
+        assert!(find_4_squares(&BigInt::from(-3)).is_none());
     }
 }
\ No newline at end of file
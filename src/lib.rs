@@ -1,21 +1,237 @@
+//! Cuproof range proofs: prove that a Pedersen-committed value lies in [a, b] over an RSA group
+//!
+//! The root of the crate is the stable surface: the items re-exported below keep their names and
+//! signatures across minor versions, and tests/public_api.rs fails when one of them changes.
+//!
+//! - parameters: [`Params`], checked by [`ParamsError`], generated by [`trusted_setup`] or
+//!   failing with [`SetupError`]
+//! - proving: [`RangeProver`], or [`prove`] / [`prove_with_rng`] for one-off proofs, blinded with
+//!   [`random_blinding`]
+//! - verifying: [`Verifier`], or [`verify`] / [`verify_statement`]
+//! - the proof, [`Cuproof`], and the claim it makes, [`Statement`]
+//! - files and streams (std): [`save_params`] / [`load_params`], [`write_params`] /
+//!   [`read_params`] and the same four for proofs, with [`ReadLimits`]
+//! - the wire format: [`proof_to_wire`] / [`proof_from_wire`], [`params_to_wire`] /
+//!   [`params_from_wire`], and the fixed-width integer fields it is built on:
+//!   [`bigint_to_be_bytes_padded`] / [`bigint_from_be_bytes`], [`scalar_to_be_bytes`] /
+//!   [`scalar_from_be_bytes`], failing with [`EncodeError`] / [`DecodeError`]
+//! - errors: [`Error`] and its categories, each with a stable code (error::ERROR_CODES)
+//!
+//! [`prelude`] imports all of it at once. The free functions were `cuproof_prove`,
+//! `cuproof_prove_with_rng`, `cuproof_verify` and `cuproof_verify_statement` before; those names
+//! remain as deprecated aliases for one release.
+//!
+//! Params, RangeProver, Verifier and Cuproof are Send + Sync, checked at compile time below:
+//! build one Verifier at startup and share it, or its `Arc<Params>`, across worker threads.
+//!
+//! The modules stay public for the rest (aggregation, comparisons, hidden-bound ranges, offline
+//! proving, futures for async callers, ceremonies, the other serialization formats and their
+//! features); their items may still change between minor versions. The command-line tool is the
+//! separate `cuproof-cli` crate.
+//!
+//! ```
+//! use cuproof::{prove, verify};
+//! use num_bigint::BigInt;
+//!
+//! let params = cuproof::setup::insecure_test_setup(512);
+//! let (v, r, a, b) = (BigInt::from(30), BigInt::from(42), BigInt::from(10), BigInt::from(100));
+//! let proof = prove(&v, &r, &a, &b, &params).unwrap();
+//! assert!(verify(&proof, &params));
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod error;
 pub mod setup;
+#[cfg(feature = "std")]
+pub mod setup_transcript;
+#[cfg(feature = "std")]
+mod setup_checkpoint;
+pub mod primality;
+pub mod metadata;
 pub mod commitment;
 pub mod fiat_shamir;
 pub mod lagrange;
+mod montgomery;
 pub mod range_proof;
+pub mod scalar;
 pub mod verify;
+pub mod poe;
+pub mod aggregate;
+pub mod comparison;
+pub mod committed_range;
+pub mod openings;
+pub mod generators;
+pub mod offline;
+pub mod secret;
 pub mod util;
+pub mod wire;
+#[cfg(feature = "std")]
 pub mod benchmark;
+pub mod timing;
+pub mod trace;
+pub mod prelude;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod workers;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+#[cfg(feature = "std")]
+pub mod selftest;
+#[cfg(feature = "std")]
+pub mod vectors;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod inspect;
+#[cfg(feature = "std")]
+pub mod ceremony;
+#[cfg(feature = "std")]
+pub mod bundle;
+pub mod group;
+#[cfg(feature = "classgroup")]
+pub mod classgroup;
+#[cfg(feature = "rsa-import")]
+pub mod rsa_import;
+#[cfg(feature = "openssl-interop")]
+pub mod openssl_interop;
+#[cfg(feature = "known-moduli")]
+pub mod known_moduli;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "armor")]
+pub mod armor;
+#[cfg(feature = "proto")]
+pub mod proto;
+#[cfg(feature = "borsh")]
+pub mod borsh;
+#[cfg(feature = "encryption")]
+pub mod encrypted;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "mmap")]
+pub mod archive;
+
+pub use group::Group;
+pub use range_proof::{prove_with_rng, random_blinding, Cuproof, ProveError, RangeProver, Statement};
+#[cfg(feature = "std")]
+pub use range_proof::prove;
+pub use setup::{Params, ParamsError, SetupError};
+#[cfg(feature = "std")]
+pub use setup::trusted_setup;
+pub use verify::{verify, verify_statement, Verifier, VerifyError};
+// the former names of prove, prove_with_rng, verify and verify_statement, for one release
+#[allow(deprecated)]
+pub use range_proof::cuproof_prove_with_rng;
+#[cfg(feature = "std")]
+#[allow(deprecated)]
+pub use range_proof::cuproof_prove;
+#[allow(deprecated)]
+pub use verify::{cuproof_verify, cuproof_verify_statement};
+pub use wire::{params_from_wire, params_to_wire, proof_from_wire, proof_to_wire, WireError};
+pub use util::{bigint_from_be_bytes, bigint_to_be_bytes_padded, scalar_from_be_bytes, scalar_to_be_bytes, DecodeError, EncodeError};
+#[cfg(feature = "std")]
+pub use error::{Error, SerializationError};
+#[cfg(feature = "std")]
+pub use util::{
+    load_params, load_proof, read_params, read_proof, save_params, save_proof, write_params, write_proof,
+    ParamsLoadError, ProofLoadError, ReadLimits,
+};
+
+// Servers share these between threads; a field that is not Send or Sync fails the build here
+const _: () = {
+    const fn shareable<T: Send + Sync>() {}
+    shareable::<Params>();
+    shareable::<RangeProver>();
+    shareable::<Verifier>();
+    shareable::<Cuproof>();
+    shareable::<Statement>();
+    shareable::<ProveError>();
+    shareable::<VerifyError>();
+    shareable::<ParamsError>();
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use num_bigint::ToBigInt;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
     use std::time::Instant;
 
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// System allocator counting the allocations (and reallocations) of each thread, so tests
+    /// running in parallel do not see each other's
+    struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// Allocations the current thread makes while running `f`
+    fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+        let before = ALLOCATIONS.with(Cell::get);
+        let result = f();
+        (result, ALLOCATIONS.with(Cell::get) - before)
+    }
+
+    // Purpose: pin the allocations of one prove and one verify so regressions show up in review
+    // Params: insecure 512-bit params (tables warmed up by a first proof), a seeded rng
+    // Output: the counted proof is byte-identical to the first; counts at or below
+    //   PROVE_ALLOCATIONS / VERIFY_ALLOCATIONS; lower them when an optimisation lands, raise them
+    //   only with a reason
+    // Usage: `cargo test -- allocations --nocapture` prints the counts
+    #[test]
+    fn prove_and_verify_allocations_are_pinned() {
+        use rand::SeedableRng;
+        // 1715 before the prover folded its inner-product vectors in place, 1688 before the
+        // transcript encoded its inputs in one byte buffer instead of three decimal ones
+        const PROVE_ALLOCATIONS: usize = 1668;
+        // 48 while verify skipped the t_hat commitment check, whose two commitments allocate one each
+        const VERIFY_ALLOCATIONS: usize = 50;
+        let params = setup::insecure_test_setup(512);
+        let (v, r, a, b) = (30.to_bigint().unwrap(), 42.to_bigint().unwrap(), 10.to_bigint().unwrap(), 100.to_bigint().unwrap());
+        let prove = || {
+            let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(143);
+            range_proof::prove_with_rng(&v, &r, &a, &b, &params, 64, &mut rng).unwrap()
+        };
+        let first = prove();
+        let (proof, prove_count) = count_allocations(prove);
+        assert_eq!(first.to_bytes(), proof.to_bytes());
+        let (valid, verify_count) = count_allocations(|| verify::verify(&proof, &params));
+        assert!(valid);
+        println!("allocations: prove {}, verify {}", prove_count, verify_count);
+        assert!(prove_count <= PROVE_ALLOCATIONS, "prove made {} allocations, pinned at {}", prove_count, PROVE_ALLOCATIONS);
+        assert!(verify_count <= VERIFY_ALLOCATIONS, "verify made {} allocations, pinned at {}", verify_count, VERIFY_ALLOCATIONS);
+    }
+
     #[test]
     fn test_basic_range_proof() {
-        let (g, h, n) = setup::trusted_setup(512);
+        let params = setup::trusted_setup_with(512, true).unwrap();
         let a = 10.to_bigint().unwrap();
         let b = 100.to_bigint().unwrap();
         let v = 30.to_bigint().unwrap();
@@ -23,12 +239,12 @@ mod tests {
 
         // Đo thời gian tạo proof
         let start_prove = Instant::now();
-        let proof = range_proof::cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
+        let proof = range_proof::prove(&v, &r, &a, &b, &params).unwrap();
         let prove_duration = start_prove.elapsed();
 
         // Đo thời gian chứng minh proof
         let start_verify = Instant::now();
-        let is_valid = verify::cuproof_verify(&proof, &g, &h, &n);
+        let is_valid = verify::verify(&proof, &params);
         let verify_duration = start_verify.elapsed();
 
         println!("Basic Range Proof Timing:");
@@ -40,7 +256,7 @@ mod tests {
 
     #[test]
     fn test_multiple_values() {
-        let (g, h, n) = setup::trusted_setup(512);
+        let params = setup::trusted_setup_with(512, true).unwrap();
         let a = 0.to_bigint().unwrap();
         let b = 1000.to_bigint().unwrap();
         let r = 123.to_bigint().unwrap();
@@ -56,13 +272,13 @@ mod tests {
             
             // Đo thời gian tạo proof
             let start_prove = Instant::now();
-            let proof = range_proof::cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
+            let proof = range_proof::prove(&v, &r, &a, &b, &params).unwrap();
             let prove_duration = start_prove.elapsed();
             total_prove_time += prove_duration;
             
             // Đo thời gian chứng minh proof
             let start_verify = Instant::now();
-            let is_valid = verify::cuproof_verify(&proof, &g, &h, &n);
+            let is_valid = verify::verify(&proof, &params);
             let verify_duration = start_verify.elapsed();
             total_verify_time += verify_duration;
             
@@ -80,7 +296,7 @@ mod tests {
 
     #[test]
     fn test_different_ranges() {
-        let (g, h, n) = setup::trusted_setup(512);
+        let params = setup::trusted_setup_with(512, true).unwrap();
         let r = 42.to_bigint().unwrap();
 
         let test_ranges = vec![
@@ -101,13 +317,13 @@ mod tests {
 
             // Đo thời gian tạo proof
             let start_prove = Instant::now();
-            let proof = range_proof::cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
+            let proof = range_proof::prove(&v, &r, &a, &b, &params).unwrap();
             let prove_duration = start_prove.elapsed();
             total_prove_time += prove_duration;
 
             // Đo thời gian chứng minh proof
             let start_verify = Instant::now();
-            let is_valid = verify::cuproof_verify(&proof, &g, &h, &n);
+            let is_valid = verify::verify(&proof, &params);
             let verify_duration = start_verify.elapsed();
             total_verify_time += verify_duration;
 
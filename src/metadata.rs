@@ -0,0 +1,193 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::primality::{AuxiliaryCertificate, PrimalityConfig};
+#[cfg(feature = "std")]
+use crate::setup::Params;
+
+/// How far in the future `created_at` may lie before validate_metadata rejects it (clock skew)
+#[cfg(feature = "std")]
+const CLOCK_SKEW_SECS: u64 = 600;
+
+/// Evidence of how a parameter set was generated, saved with it by save_params
+///
+/// Everything here is public. The certificates prove the auxiliary primes of a provable setup
+/// (see `PrimalityCertificate::auxiliary` for why the links reaching p and q are withheld); p and
+/// q themselves are never recorded. Params equality and the fingerprint ignore the metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamsMetadata {
+    /// Seconds since the Unix epoch when the params were generated
+    pub created_at: u64,
+    /// `CARGO_PKG_VERSION` of the crate that generated them
+    pub crate_version: String,
+    /// Declared modulus size
+    pub bits: usize,
+    /// How p and q were tested; None when only the modulus was supplied
+    pub primality: Option<PrimalityConfig>,
+    /// Generator derivation seed of every epoch, oldest first (see `Params::rotate_generators`)
+    pub generator_seeds: Vec<String>,
+    /// Auxiliary prime certificates for p and q, empty unless `primality.provable`
+    pub certificates: Vec<AuxiliaryCertificate>,
+}
+
+impl ParamsMetadata {
+    /// Metadata stamped with the current time and crate version
+    /// - params: bits declared size, primality test configuration if p, q were tested,
+    ///   generator_seed derivation seed of the params, certificates auxiliary certificates
+    /// - usage: setup functions attach it with `Params::with_metadata`
+    #[cfg(feature = "std")]
+    pub fn new(bits: usize, primality: Option<PrimalityConfig>, generator_seed: Option<&str>, certificates: Vec<AuxiliaryCertificate>) -> ParamsMetadata {
+        ParamsMetadata {
+            created_at: unix_now(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            bits,
+            primality,
+            generator_seeds: generator_seed.map(str::to_string).into_iter().collect(),
+            certificates,
+        }
+    }
+
+    /// The same metadata with one more generator epoch recorded
+    pub(crate) fn rotated(&self, seed: &str) -> ParamsMetadata {
+        let mut next = self.clone();
+        next.generator_seeds.push(seed.to_string());
+        next
+    }
+}
+
+#[cfg(feature = "std")]
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Reasons validate_metadata rejects the metadata of a parameter set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataError {
+    /// the params carry no metadata (files written before it existed, imported or test params)
+    Missing,
+    /// created_at lies in the future
+    CreatedInFuture(u64),
+    /// the recorded size differs from `Params::bits`
+    BitsMismatch { metadata: usize, params: usize },
+    /// the latest recorded seed does not derive g and h, or differs from the params' seed
+    GeneratorSeedMismatch,
+    /// the number of recorded seeds does not match the generator epoch
+    EpochMismatch { seeds: usize, epoch: u64 },
+    /// certificates are present although the primes were not generated provably
+    UnexpectedCertificates,
+    /// a provable setup must carry one certificate per prime
+    CertificateCount(usize),
+    /// the certificate at this index does not verify
+    InvalidCertificate(usize),
+    /// the certificate at this index proves a prime big enough to leak information about p or q
+    CertificateTooLarge(usize),
+}
+
+impl fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataError::Missing => write!(f, "params carry no metadata"),
+            MetadataError::CreatedInFuture(t) => write!(f, "metadata timestamp {} lies in the future", t),
+            MetadataError::BitsMismatch { metadata, params } => {
+                write!(f, "metadata records {} bits but the params declare {}", metadata, params)
+            }
+            MetadataError::GeneratorSeedMismatch => write!(f, "recorded generator seed does not derive g and h"),
+            MetadataError::EpochMismatch { seeds, epoch } => {
+                write!(f, "metadata records {} generator seeds but the params are at epoch {}", seeds, epoch)
+            }
+            MetadataError::UnexpectedCertificates => write!(f, "certificates recorded for non-provable primes"),
+            MetadataError::CertificateCount(count) => write!(f, "expected 2 prime certificates, found {}", count),
+            MetadataError::InvalidCertificate(i) => write!(f, "prime certificate {} does not verify", i),
+            MetadataError::CertificateTooLarge(i) => write!(f, "prime certificate {} reaches too close to p or q", i),
+        }
+    }
+}
+
+impl core::error::Error for MetadataError {}
+
+/// Re-check everything in the params metadata that can be checked without p and q
+/// - params: params whose metadata to check
+/// - returns: Ok(()) or the first MetadataError found; Missing for params without metadata
+/// - usage: auditors, and load_params for files that have a metadata section. Checks the timestamp
+///   is not in the future, the size, that the latest seed re-derives g and h with one seed per epoch,
+///   and that each certificate verifies and proves only primes below a quarter of the modulus size
+#[cfg(feature = "std")]
+pub fn validate_metadata(params: &Params) -> Result<(), MetadataError> {
+    let metadata = params.metadata().ok_or(MetadataError::Missing)?;
+    if metadata.created_at > unix_now() + CLOCK_SKEW_SECS { return Err(MetadataError::CreatedInFuture(metadata.created_at)); }
+    if metadata.bits != params.bits() {
+        return Err(MetadataError::BitsMismatch { metadata: metadata.bits, params: params.bits() });
+    }
+
+    if let Some(latest) = metadata.generator_seeds.last() {
+        if params.generator_seed() != Some(latest.as_str()) || !params.generators_match_seed() {
+            return Err(MetadataError::GeneratorSeedMismatch);
+        }
+        if metadata.generator_seeds.len() as u64 != params.epoch() + 1 {
+            return Err(MetadataError::EpochMismatch { seeds: metadata.generator_seeds.len(), epoch: params.epoch() });
+        }
+    }
+
+    if !metadata.certificates.is_empty() && !metadata.primality.is_some_and(|config| config.provable) {
+        return Err(MetadataError::UnexpectedCertificates);
+    }
+    for (i, certificate) in metadata.certificates.iter().enumerate() {
+        if !certificate.verify() { return Err(MetadataError::InvalidCertificate(i)); }
+        if certificate.top().bits() as usize * 4 >= metadata.bits { return Err(MetadataError::CertificateTooLarge(i)); }
+    }
+    if metadata.primality.is_some_and(|config| config.provable) && metadata.certificates.len() != 2 {
+        return Err(MetadataError::CertificateCount(metadata.certificates.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::{insecure_test_setup, trusted_setup_with_config};
+
+    // Purpose: ensure setup metadata is recorded, re-checkable and catches tampering
+    // Params: 256-bit provable and probable trusted setups, fast params without metadata
+    // Output: provable setups carry two verifying certificates; rotation keeps the metadata valid;
+    //         each tampered field maps to its MetadataError
+    // Usage: `cargo test -- src::metadata` or `cargo test`
+    #[test]
+    fn metadata_records_and_rechecks_setup_evidence() {
+        assert_eq!(validate_metadata(&insecure_test_setup(512)), Err(MetadataError::Missing));
+
+        let provable = PrimalityConfig { provable: true, ..PrimalityConfig::default() };
+        let (params, _) = trusted_setup_with_config(256, true, &provable).unwrap();
+        let metadata = params.metadata().unwrap();
+        assert_eq!(metadata.bits, 256);
+        assert_eq!(metadata.primality, Some(provable));
+        assert_eq!(metadata.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.generator_seeds, [params.generator_seed().unwrap()]);
+        assert_eq!(metadata.certificates.len(), 2);
+        assert_eq!(validate_metadata(&params), Ok(()));
+
+        let rotated = params.rotate_generators("audit");
+        assert_eq!(rotated.metadata().unwrap().generator_seeds.len(), 2);
+        assert_eq!(validate_metadata(&rotated), Ok(()));
+
+        let (probable, _) = trusted_setup_with_config(256, true, &PrimalityConfig::default()).unwrap();
+        assert!(probable.metadata().unwrap().certificates.is_empty());
+        assert_eq!(validate_metadata(&probable), Ok(()));
+
+        let tampered = |edit: &dyn Fn(&mut ParamsMetadata)| {
+            let mut m = metadata.clone();
+            edit(&mut m);
+            validate_metadata(&params.clone().with_metadata(Some(m)))
+        };
+        assert_eq!(tampered(&|m| m.created_at = u64::MAX), Err(MetadataError::CreatedInFuture(u64::MAX)));
+        assert_eq!(tampered(&|m| m.bits = 2048), Err(MetadataError::BitsMismatch { metadata: 2048, params: 256 }));
+        assert_eq!(tampered(&|m| m.generator_seeds = vec!["other".into()]), Err(MetadataError::GeneratorSeedMismatch));
+        assert_eq!(tampered(&|m| m.generator_seeds.insert(0, "older".into())), Err(MetadataError::EpochMismatch { seeds: 2, epoch: 0 }));
+        assert_eq!(tampered(&|m| m.primality = Some(PrimalityConfig::default())), Err(MetadataError::UnexpectedCertificates));
+        assert_eq!(tampered(&|m| { m.certificates.pop(); }), Err(MetadataError::CertificateCount(1)));
+        assert_eq!(tampered(&|m| m.certificates[1].base += 2u32), Err(MetadataError::InvalidCertificate(1)));
+        let oversized = crate::primality::provable_prime(512, &mut rand::rngs::OsRng).1.auxiliary().unwrap();
+        assert_eq!(tampered(&|m| m.certificates[0] = oversized.clone()), Err(MetadataError::CertificateTooLarge(0)));
+    }
+}
@@ -0,0 +1,156 @@
+//! Montgomery multiplication modulo an odd n on u64 limbs, for the fixed-base tables in Params
+//!
+//! Operands are little-endian limb slices of exactly `limbs()` words holding x * R mod n with
+//! R = 2^(64 * limbs). A product takes a caller-provided scratch buffer, so an exponentiation runs
+//! without allocating per multiplication.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+
+/// Constants for Montgomery arithmetic modulo one odd modulus
+#[derive(Debug, Clone)]
+pub(crate) struct Montgomery {
+    modulus: BigUint,
+    n: Vec<u64>,
+    /// -n^(-1) mod 2^64
+    n0_inv: u64,
+    /// R mod n, the Montgomery form of 1
+    one: Vec<u64>,
+}
+
+/// a + b * c + carry as (low, high) words
+#[inline]
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + (b as u128) * (c as u128) + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+impl Montgomery {
+    /// None for even moduli and n <= 1, which have no Montgomery form
+    pub(crate) fn new(n: &BigUint) -> Option<Montgomery> {
+        if n.bits() <= 1 || !n.bit(0) { return None; }
+        let limbs: Vec<u64> = n.iter_u64_digits().collect();
+        // Newton iteration doubles the correct low bits of n^(-1) mod 2^64 each step
+        let mut inv = 1u64;
+        for _ in 0..6 { inv = inv.wrapping_mul(2u64.wrapping_sub(limbs[0].wrapping_mul(inv))); }
+        let mut m = Montgomery { modulus: n.clone(), n: limbs, n0_inv: inv.wrapping_neg(), one: Vec::new() };
+        m.one = m.to_limbs(&((BigUint::from(1u32) << (64 * m.limbs())) % n));
+        Some(m)
+    }
+
+    /// Words per operand
+    pub(crate) fn limbs(&self) -> usize {
+        self.n.len()
+    }
+
+    /// Length of the scratch buffer mul needs
+    pub(crate) fn scratch_len(&self) -> usize {
+        self.limbs() + 2
+    }
+
+    /// The Montgomery form of 1
+    pub(crate) fn one(&self) -> &[u64] {
+        &self.one
+    }
+
+    fn to_limbs(&self, x: &BigUint) -> Vec<u64> {
+        let mut limbs: Vec<u64> = x.iter_u64_digits().collect();
+        limbs.resize(self.limbs(), 0);
+        limbs
+    }
+
+    /// Montgomery form of x (any size; reduced mod n first)
+    pub(crate) fn to_mont(&self, x: &BigUint) -> Vec<u64> {
+        let n = &self.modulus;
+        self.to_limbs(&(((x % n) << (64 * self.limbs())) % n))
+    }
+
+    /// out = a * b / R mod n for a, b < n in Montgomery form (CIOS)
+    pub(crate) fn mul(&self, a: &[u64], b: &[u64], out: &mut [u64], t: &mut [u64]) {
+        let k = self.limbs();
+        t[..k + 2].fill(0);
+        for &bi in &b[..k] {
+            let mut carry = 0;
+            for j in 0..k { (t[j], carry) = mac(t[j], a[j], bi, carry); }
+            let (s, overflow) = t[k].overflowing_add(carry);
+            t[k] = s;
+            t[k + 1] = overflow as u64;
+
+            let m = t[0].wrapping_mul(self.n0_inv);
+            let (_, mut carry) = mac(t[0], m, self.n[0], 0);
+            for j in 1..k { (t[j - 1], carry) = mac(t[j], m, self.n[j], carry); }
+            let (s, overflow) = t[k].overflowing_add(carry);
+            t[k - 1] = s;
+            t[k] = t[k + 1] + overflow as u64;
+        }
+        out[..k].copy_from_slice(&t[..k]);
+        if t[k] != 0 || !less_than(out, &self.n) { sub_in_place(out, &self.n); }
+    }
+
+    /// The integer a * R^(-1) mod n for a in Montgomery form
+    pub(crate) fn out_of_mont(&self, a: &[u64], t: &mut [u64]) -> BigUint {
+        let k = self.limbs();
+        let mut unit = vec![0u64; 2 * k];
+        unit[0] = 1;
+        let (one, out) = unit.split_at_mut(k);
+        self.mul(a, one, out, t);
+        BigUint::new(out.iter().flat_map(|&w| [w as u32, (w >> 32) as u32]).collect())
+    }
+}
+
+/// a < b for equal-length little-endian limbs
+fn less_than(a: &[u64], b: &[u64]) -> bool {
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y { return x < y; }
+    }
+    false
+}
+
+/// a -= b modulo 2^(64 * len)
+fn sub_in_place(a: &mut [u64], b: &[u64]) {
+    let mut borrow = false;
+    for (x, &y) in a.iter_mut().zip(b) {
+        let (d, b1) = x.overflowing_sub(y);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        *x = d;
+        borrow = b1 || b2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use num_bigint::RandBigInt;
+
+    // Purpose: ensure Montgomery products match BigUint arithmetic, including carries at the limb edges
+    // Params: the 512-bit test modulus, a one-limb and a three-limb modulus, seeded random operands
+    // Output: out_of_mont(mul(to_mont(x), to_mont(y))) == x * y mod n; even moduli are refused
+    // Usage: `cargo test -- src::montgomery` or `cargo test`
+    #[test]
+    fn products_match_biguint() {
+        let mut rng = ChaCha20Rng::seed_from_u64(143);
+        let moduli = [
+            insecure_test_setup(512).n().magnitude().clone(),
+            BigUint::from(u64::MAX),
+            (BigUint::from(1u32) << 192) - 1u32,
+            BigUint::from(3u32),
+        ];
+        for n in &moduli {
+            let m = Montgomery::new(n).unwrap();
+            let mut t = vec![0u64; m.scratch_len()];
+            let mut out = vec![0u64; m.limbs()];
+            for x in [BigUint::from(0u32), BigUint::from(1u32), n - 1u32, rng.gen_biguint_below(n), rng.gen_biguint(700)] {
+                let y = rng.gen_biguint_below(n);
+                m.mul(&m.to_mont(&x), &m.to_mont(&y), &mut out, &mut t);
+                assert_eq!(m.out_of_mont(&out, &mut t), &x * &y % n);
+            }
+            assert_eq!(m.out_of_mont(m.one(), &mut t), BigUint::from(1u32) % n);
+        }
+        assert!(Montgomery::new(&BigUint::from(10u32)).is_none());
+        assert!(Montgomery::new(&BigUint::from(1u32)).is_none());
+    }
+}
@@ -0,0 +1,251 @@
+//! Two-stage proving for an air-gapped prover (`prove-offline-commit`, `prove-offline-finish`)
+//!
+//! The prover's first message (A, S and the commitments to v, v1 and v2) is all a verifier needs
+//! to derive the challenges y and z, so proving can be split around it. On the cold machine
+//! `offline_commit` runs the first round and returns the FirstMessage, which may leave the
+//! machine, and an OfflineState holding the witness-dependent secrets, which never does; its file
+//! is as secret as a blinding file, and the CLI only writes it encrypted (see encrypted). Online, `ChallengeBundle::new` derives y and z from the message by
+//! the Fiat–Shamir transcript of verify under the verifier's nonce, if any, and attaches the
+//! verifier's context as the statement label. Back on the cold machine `offline_finish` checks
+//! the bundle against the state, recomputes the challenges under the same nonce and answers them;
+//! the result is an ordinary proof, which verifies under verify::verify_with_nonce with that
+//! nonce (plain verify when there is none). The remaining randomness comes from a seed drawn in the first round, so
+//! finishing a state twice could only repeat the same proof, and a SpentLog lets the CLI refuse a
+//! state that was already used
+
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+use core::fmt;
+use num_bigint::BigInt;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use crate::fiat_shamir::FiatShamir;
+use crate::group::Group;
+use crate::secret::Secret;
+use crate::range_proof::{first_round_challenges, prove_first_round, Cuproof, FirstRound, ProveError, Statement};
+
+/// IPP vector dimension of offline proofs, as prove uses
+pub const OFFLINE_DIMENSION: usize = 64;
+
+/// The prover's first message, safe to move off the cold machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(non_snake_case)]
+pub struct FirstMessage {
+    /// random id shared with the OfflineState it came from
+    pub id: [u8; 16],
+    pub A: BigInt,
+    pub S: BigInt,
+    pub C_v1: BigInt,
+    pub C_v2: BigInt,
+    /// what will be proved; its commitment is C and its label is empty
+    pub statement: Statement,
+}
+
+/// The secrets between the two rounds: the square decomposition, the blindings of A and S and
+/// the seed of the second round's randomness. As secret as v itself, and meant to be used once;
+/// the secrets are wiped on drop and Debug shows only the id
+#[derive(Clone)]
+pub struct OfflineState {
+    pub(crate) id: [u8; 16],
+    pub(crate) params_fingerprint: [u8; 32],
+    pub(crate) a: BigInt,
+    pub(crate) b: BigInt,
+    pub(crate) finish_seed: Secret<[u8; 32]>,
+    pub(crate) round: FirstRound,
+}
+
+impl OfflineState {
+    pub fn id(&self) -> [u8; 16] {
+        self.id
+    }
+}
+
+impl fmt::Debug for OfflineState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OfflineState").field("id", &hex::encode(self.id)).finish_non_exhaustive()
+    }
+}
+
+/// The challenges y and z for a first message, drawn under the verifier's context label and nonce,
+/// with the label in the statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeBundle {
+    pub message: FirstMessage,
+    /// the nonce the verifier issued, bound with the label into every challenge (FiatShamir::with_context); empty for none
+    pub nonce: Vec<u8>,
+    pub y: BigInt,
+    pub z: BigInt,
+}
+
+impl ChallengeBundle {
+    /// The challenges of `message` under `params` and `nonce`, labelling its statement with `label`
+    /// - returns: the bundle, or ChallengeMismatch when the message was made under other params
+    /// - usage: on the online machine, e.g. `ChallengeBundle::new(&message, &params, "session/7", &nonce)`;
+    ///   the verifier later checks the proof with verify --nonce and the label with --statement
+    pub fn new<G: Group>(message: &FirstMessage, params: &G, label: &str, nonce: &[u8]) -> Result<ChallengeBundle, ProveError> {
+        if message.statement.params_fingerprint != params.fingerprint() { return Err(ProveError::ChallengeMismatch); }
+        let mut transcript = FiatShamir::with_context(label, nonce);
+        let (y, z) = first_round_challenges(&message.A, &message.S, &message.statement.commitment, &message.C_v1, &message.C_v2, params, &mut transcript);
+        let message = FirstMessage { statement: message.statement.clone().with_label(label), ..message.clone() };
+        Ok(ChallengeBundle { message, nonce: nonce.to_vec(), y, z })
+    }
+}
+
+/// First round of an offline proof of a <= v <= b, with C = g^v * h^r
+/// - returns: the first message and the state to keep on the cold machine, or the errors of prove
+/// - usage: on the cold machine; `rng` draws every blinding, the id and the second round's seed
+pub fn offline_commit<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, rng: &mut R) -> Result<(FirstMessage, OfflineState), ProveError> {
+    let round = prove_first_round(v, r, a, b, params, OFFLINE_DIMENSION, rng)?;
+    let mut id = [0u8; 16];
+    rng.fill_bytes(&mut id);
+    let mut finish_seed = Secret::new([0u8; 32]);
+    rng.fill_bytes(finish_seed.expose_mut());
+    let message = FirstMessage {
+        id,
+        A: round.A.clone(),
+        S: round.S.clone(),
+        C_v1: round.C_v1.clone(),
+        C_v2: round.C_v2.clone(),
+        statement: Statement::new(&round.C, a, b, OFFLINE_DIMENSION, params),
+    };
+    let state = OfflineState { id, params_fingerprint: params.fingerprint(), a: a.clone(), b: b.clone(), finish_seed, round };
+    Ok((message, state))
+}
+
+/// offline_commit for an existing commitment, e.g. one written by `cuproof commit`
+/// - returns: CommitmentMismatch when (v, r) does not open `commitment`, before any proving work
+pub fn offline_commit_for_commitment<G: Group, R: RngCore + CryptoRng>(commitment: &BigInt, v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, rng: &mut R) -> Result<(FirstMessage, OfflineState), ProveError> {
+    if params.commit(v, r) != *commitment { return Err(ProveError::CommitmentMismatch); }
+    offline_commit(v, r, a, b, params, rng)
+}
+
+/// Second round: answer the challenges of `bundle` with `state`
+/// - returns: the proof, verifiable by verify_with_nonce under the bundle's nonce and carrying the
+///   bundle's label; ChallengeMismatch when the bundle is for another state, statement or params,
+///   or its challenges are not the Fiat–Shamir challenges of its message under its nonce
+/// - usage: consumes the state; the CLI also records it in a SpentLog so a copy cannot be reused
+pub fn offline_finish<G: Group>(state: OfflineState, bundle: &ChallengeBundle, params: &G) -> Result<Cuproof, ProveError> {
+    let message = &bundle.message;
+    let statement = &message.statement;
+    let round = &state.round;
+    let matches = state.params_fingerprint == params.fingerprint()
+        && message.id == state.id
+        && [&message.A, &message.S, &statement.commitment, &message.C_v1, &message.C_v2] == [&round.A, &round.S, &round.C, &round.C_v1, &round.C_v2]
+        && statement.params_fingerprint == state.params_fingerprint
+        && (&statement.a, &statement.b, statement.bits) == (&state.a, &state.b, round.d.expose().len());
+    if !matches { return Err(ProveError::ChallengeMismatch); }
+    if round.challenges(params, &mut FiatShamir::with_context(&statement.label, &bundle.nonce)) != (bundle.y.clone(), bundle.z.clone()) { return Err(ProveError::ChallengeMismatch); }
+    let OfflineState { a, b, finish_seed, round, .. } = state;
+    let mut transcript = FiatShamir::with_context(&statement.label, &bundle.nonce);
+    let proof = round.finish(&a, &b, &bundle.y, &bundle.z, params, &mut transcript, &mut ChaCha20Rng::from_seed(*finish_seed.expose()));
+    Ok(proof.with_label(&statement.label))
+}
+
+/// Ids of the offline states already finished, one hex id per line of a text file
+/// - usage: `prove-offline-finish` checks and records the id before answering the challenges
+#[cfg(feature = "std")]
+pub struct SpentLog {
+    path: String,
+}
+
+#[cfg(feature = "std")]
+impl SpentLog {
+    pub fn new(path: &str) -> SpentLog {
+        SpentLog { path: path.to_string() }
+    }
+
+    /// True when `id` was recorded; a missing log holds nothing
+    pub fn contains(&self, id: &[u8; 16]) -> io::Result<bool> {
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let id = hex::encode(id);
+        for line in io::BufReader::new(file).lines() {
+            if line?.trim() == id { return Ok(true); }
+        }
+        Ok(false)
+    }
+
+    /// Append `id` and flush it to disk
+    pub fn record(&self, id: &[u8; 16]) -> io::Result<()> {
+        if let Some(parent) = Path::new(&self.path).parent().filter(|p| !p.as_os_str().is_empty()) { fs::create_dir_all(parent)?; }
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", hex::encode(id))?;
+        file.sync_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+    use crate::verify::{verify, verify_statement, verify_with_nonce};
+
+    // Purpose: ensure the two rounds compose into a proof verify accepts, bound to the bundle's
+    //         label and nonce
+    // Params: insecure 512-bit params, v = 42 in [10, 100], a seeded rng; bundles without and with
+    //         a nonce; bundles altered one field at a time
+    // Output: a valid proof whose statement carries the label; finishing twice gives the same proof;
+    //         the nonce bundle's proof verifies under its nonce only; ChallengeMismatch for another
+    //         state, altered challenges, another nonce, and params of another size
+    // Usage: `cargo test -- offline`
+    #[test]
+    fn offline_rounds_compose_into_a_valid_proof() {
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(176);
+        let (v, r, a, b) = (BigInt::from(42), BigInt::from(0x1234), BigInt::from(10), BigInt::from(100));
+        let (message, state) = offline_commit(&v, &r, &a, &b, &params, &mut rng).unwrap();
+        assert_eq!(message.statement.commitment, params.commit(&v, &r));
+        let bundle = ChallengeBundle::new(&message, &params, "session/7", &[]).unwrap();
+        let proof = offline_finish(state.clone(), &bundle, &params).unwrap();
+        assert!(verify(&proof, &params));
+        let expected = Statement::new(&params.commit(&v, &r), &a, &b, OFFLINE_DIMENSION, &params).with_label("session/7");
+        assert_eq!(verify_statement(&proof, &params, &expected), Ok(()));
+        assert_eq!(offline_finish(state.clone(), &bundle, &params).unwrap().to_bytes(), proof.to_bytes());
+
+        let nonce = [0x4f, 0x1c];
+        let bound = ChallengeBundle::new(&message, &params, "session/7", &nonce).unwrap();
+        assert_ne!((&bound.y, &bound.z), (&bundle.y, &bundle.z));
+        let proof = offline_finish(state.clone(), &bound, &params).unwrap();
+        assert_eq!(verify_with_nonce(&proof, &params, &nonce), Ok(()));
+        assert!(!verify(&proof, &params));
+        assert!(verify_with_nonce(&proof, &params, &[0x4f]).is_err());
+        let renonced = ChallengeBundle { nonce: vec![0x4f], ..bound.clone() };
+        assert_eq!(offline_finish(state.clone(), &renonced, &params).err(), Some(ProveError::ChallengeMismatch));
+
+        let (_, other) = offline_commit(&v, &r, &a, &b, &params, &mut rng).unwrap();
+        assert_eq!(offline_finish(other, &bundle, &params).err(), Some(ProveError::ChallengeMismatch));
+        let mut altered = bundle.clone();
+        altered.y += 1;
+        assert_eq!(offline_finish(state.clone(), &altered, &params).err(), Some(ProveError::ChallengeMismatch));
+        let larger = insecure_test_setup(1024);
+        assert_eq!(ChallengeBundle::new(&message, &larger, "", &[]).err(), Some(ProveError::ChallengeMismatch));
+        assert_eq!(offline_finish(state, &bundle, &larger).err(), Some(ProveError::ChallengeMismatch));
+        let wrong = offline_commit_for_commitment(&params.commit(&v, &r), &(&v + 1), &r, &a, &b, &params, &mut rng);
+        assert_eq!(wrong.err(), Some(ProveError::CommitmentMismatch));
+    }
+
+    // Purpose: ensure an offline state formats without its secrets
+    // Params: insecure 512-bit params, v = 42 in [10, 100], a seeded rng
+    // Output: Debug shows the id and nothing of the seed or the first round
+    // Usage: `cargo test -- offline`
+    #[test]
+    fn offline_state_debug_redacts() {
+        let params = insecure_test_setup(512);
+        let (v, r, a, b) = (BigInt::from(42), BigInt::from(0x1234), BigInt::from(10), BigInt::from(100));
+        let (_, state) = offline_commit(&v, &r, &a, &b, &params, &mut ChaCha20Rng::seed_from_u64(182)).unwrap();
+        let debug = format!("{:?}", state);
+        assert_eq!(debug, format!("OfflineState {{ id: {:?}, .. }}", hex::encode(state.id())));
+        assert!(!debug.contains(&hex::encode(state.finish_seed.expose())));
+        assert!(!debug.contains(&state.round.alpha.expose().to_str_radix(16)));
+    }
+}
@@ -0,0 +1,259 @@
+//! One proof that the prover knows an opening of every commitment in a list
+//!
+//! For commitments C_i = g^m_i h^r_i the prover draws k_m and k_r and sends the announcement
+//! A = g^k_m h^k_r. The transcript hashes n, g, h, the number of commitments, every C_i and A into
+//! one digest, and commitment i gets the challenge c_i of CHALLENGE_BITS bits hashed from the
+//! digest and i. The responses z_m = k_m + sum c_i m_i and z_r = k_r + sum c_i r_i are integers,
+//! never reduced, as the order of the group is unknown, and the verifier checks
+//!
+//!   g^z_m h^z_r = A * prod C_i^c_i mod n
+//!
+//! with one multi-exponentiation. Two accepting answers to challenges that differ in c_j alone
+//! give g^dz_m h^dz_r = C_j^dc_j, which under the strong RSA assumption yields an opening of C_j:
+//! a prover missing one opening among hundreds fails as surely as with a proof per commitment. As
+//! for every proof over Z_n^*, the opening is of C_j up to an element of order two, so only
+//! commitments in canonical form are accepted.
+//!
+//! k_m and k_r have NONCE_SLACK_BITS bits more than the largest sum c_i m_i can, so the responses
+//! hide the openings statistically. The proof records the widest m_i or r_i, which bounds the
+//! responses the verifier accepts.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use num_bigint::BigInt;
+use num_traits::{One, Signed};
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use crate::commitment::{commit, is_canonical_commitment};
+use crate::fiat_shamir::{fiat_shamir, FiatShamir, Transcript};
+use crate::poe::multi_exp;
+use crate::range_proof::ProveError;
+use crate::secret::Secret;
+use crate::setup::Params;
+use crate::util::random_below;
+use crate::verify::VerifyError;
+
+/// Most commitments in one batch
+pub const MAX_BATCH_OPENINGS: usize = 1 << 16;
+
+/// Widest m_i or r_i a batch accepts
+pub const MAX_OPENING_BITS: u32 = 8192;
+
+/// Size of the challenge of each commitment
+const CHALLENGE_BITS: usize = 128;
+
+/// Bits of the nonces beyond the largest sum c_i m_i, so the responses hide the openings
+const NONCE_SLACK_BITS: u64 = 128;
+
+/// A proof of knowledge of an opening of each commitment of a list, in the order of the list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOpeningProof {
+    /// every m_i and r_i has at most this many bits
+    pub bits: u32,
+    /// A = g^k_m h^k_r
+    pub announcement: BigInt,
+    /// z_m = k_m + sum c_i m_i
+    pub z_m: BigInt,
+    /// z_r = k_r + sum c_i r_i
+    pub z_r: BigInt,
+}
+
+/// Prove knowledge of `openings[i] = (m_i, r_i)` for each `commitments[i] = commit(m_i, r_i)`
+/// - params: openings, commitments in the same order, params
+/// - returns: BatchOpeningProof; InvalidDimension when the lists differ in length or exceed
+///   MAX_BATCH_OPENINGS, and Aggregate { index, .. } holding ValueOutOfRange for a negative
+///   m_i or r_i or one over MAX_OPENING_BITS, or CommitmentMismatch for an opening of another commitment
+/// - usage: publish the commitments with one proof instead of one sigma proof each; the verifier
+///   runs verify_openings_batch against its own copy of the list
+#[cfg(feature = "std")]
+pub fn prove_openings_batch(openings: &[(BigInt, BigInt)], commitments: &[BigInt], params: &Params) -> Result<BatchOpeningProof, ProveError> {
+    prove_openings_batch_with_transcript(openings, commitments, params, &mut FiatShamir::new(), &mut OsRng)
+}
+
+/// prove_openings_batch drawing the challenges from `transcript` and the nonces from `rng`
+/// - usage: `&mut FiatShamir::with_nonce(nonce)` binds the proof to a verifier's nonce
+pub fn prove_openings_batch_with_transcript<R: RngCore + CryptoRng>(
+    openings: &[(BigInt, BigInt)], commitments: &[BigInt], params: &Params, transcript: &mut dyn Transcript, rng: &mut R,
+) -> Result<BatchOpeningProof, ProveError> {
+    if openings.len() != commitments.len() || openings.len() > MAX_BATCH_OPENINGS { return Err(ProveError::InvalidDimension(openings.len())); }
+    for (index, ((m, r), c)) in openings.iter().zip(commitments).enumerate() {
+        let error = if m.is_negative() || r.is_negative() || m.bits().max(r.bits()) > u64::from(MAX_OPENING_BITS) {
+            ProveError::ValueOutOfRange
+        } else if commit(params, m, r) != *c {
+            ProveError::CommitmentMismatch
+        } else {
+            continue;
+        };
+        return Err(ProveError::Aggregate { index, error: Box::new(error) });
+    }
+    let bits = openings.iter().map(|(m, r)| m.bits().max(r.bits())).max().unwrap_or(0) as u32;
+    let bound = BigInt::one() << nonce_bits(bits, openings.len());
+    let mut k_m = Secret::new(random_below(&bound, rng));
+    let mut k_r = Secret::new(random_below(&bound, rng));
+    let announcement = commit(params, k_m.expose(), k_r.expose());
+    let challenges = challenges(commitments, &announcement, params, transcript);
+    for ((m, r), c) in openings.iter().zip(&challenges) {
+        *k_m.expose_mut() += c * m;
+        *k_r.expose_mut() += c * r;
+    }
+    let (z_m, z_r) = (k_m.expose().clone(), k_r.expose().clone());
+    Ok(BatchOpeningProof { bits, announcement, z_m, z_r })
+}
+
+/// Verify that whoever made `proof` knows an opening of every commitment of `commitments`
+/// - params: commitments as the verifier holds them, in the prover's order, proof, params
+/// - returns: Ok(()), or InvalidProof: for a commitment with a wrong opening, a list changed,
+///   reordered or cut short, a commitment or announcement not in canonical form and responses
+///   outside the bound the proof's width sets
+pub fn verify_openings_batch(commitments: &[BigInt], proof: &BatchOpeningProof, params: &Params) -> Result<(), VerifyError> {
+    verify_openings_batch_with_transcript(commitments, proof, params, &mut FiatShamir::new())
+}
+
+/// verify_openings_batch drawing the challenges from `transcript`, which must be the prover's
+pub fn verify_openings_batch_with_transcript(commitments: &[BigInt], proof: &BatchOpeningProof, params: &Params, transcript: &mut dyn Transcript) -> Result<(), VerifyError> {
+    if commitments.len() > MAX_BATCH_OPENINGS || proof.bits > MAX_OPENING_BITS { return Err(VerifyError::InvalidProof); }
+    if !commitments.iter().chain([&proof.announcement]).all(|c| is_canonical_commitment(params, c)) { return Err(VerifyError::InvalidProof); }
+    let max_bits = nonce_bits(proof.bits, commitments.len()) + 1;
+    if [&proof.z_m, &proof.z_r].iter().any(|z| z.is_negative() || z.bits() > max_bits) { return Err(VerifyError::InvalidProof); }
+    let challenges = challenges(commitments, &proof.announcement, params, transcript);
+    let terms: Vec<(&BigInt, &BigInt)> = commitments.iter().zip(&challenges).collect();
+    let expected = multi_exp(&terms, params.n()) * &proof.announcement % params.n();
+    if commit(params, &proof.z_m, &proof.z_r) == expected { Ok(()) } else { Err(VerifyError::InvalidProof) }
+}
+
+/// Bits of k_m and k_r for `count` openings of at most `bits` bits
+fn nonce_bits(bits: u32, count: usize) -> u64 {
+    u64::from(bits) + CHALLENGE_BITS as u64 + u64::from(usize::BITS - count.leading_zeros()) + NONCE_SLACK_BITS
+}
+
+/// c_i for each commitment, from the transcript's digest of the params, the list and A
+fn challenges(commitments: &[BigInt], announcement: &BigInt, params: &Params, transcript: &mut dyn Transcript) -> Vec<BigInt> {
+    let count = BigInt::from(commitments.len());
+    let mut message: Vec<&BigInt> = Vec::with_capacity(5 + commitments.len());
+    message.extend([params.n(), params.g(), params.h(), &count]);
+    message.extend(commitments);
+    message.push(announcement);
+    let digest = transcript.openings(&message);
+    (0..commitments.len()).map(|i| fiat_shamir(&[&digest, &BigInt::from(i)]) >> (256 - CHALLENGE_BITS)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use crate::range_proof::random_blinding;
+    use crate::setup::insecure_test_setup;
+
+    fn openings(count: u64, rng: &mut ChaCha20Rng) -> Vec<(BigInt, BigInt)> {
+        (0..count).map(|i| (BigInt::from(1000 + 7 * i), random_blinding(rng))).collect()
+    }
+
+    fn prove(openings: &[(BigInt, BigInt)], commitments: &[BigInt], params: &Params, seed: u64) -> Result<BatchOpeningProof, ProveError> {
+        prove_openings_batch_with_transcript(openings, commitments, params, &mut FiatShamir::new(), &mut ChaCha20Rng::seed_from_u64(seed))
+    }
+
+    // Purpose: ensure a batch of 100 verifies, and that one wrong opening among them is caught
+    // Params: insecure 512-bit params; 100 openings; entry 37 with m + 1
+    // Output: Ok(()) for the batch; the prover refuses the wrong entry with Aggregate { index: 37 };
+    //         the proof of the corrupted openings, a list with one commitment replaced, a
+    //         reordered list and a changed response are InvalidProof
+    // Usage: `cargo test -- src::openings` or `cargo test`
+    #[test]
+    fn one_wrong_opening_among_a_hundred_is_rejected() {
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(203);
+        let openings = openings(100, &mut rng);
+        let commitments: Vec<BigInt> = openings.iter().map(|(m, r)| commit(&params, m, r)).collect();
+        let proof = prove(&openings, &commitments, &params, 1).unwrap();
+        assert_eq!(verify_openings_batch(&commitments, &proof, &params), Ok(()));
+
+        let mut corrupted = openings.clone();
+        corrupted[37].0 += 1;
+        match prove(&corrupted, &commitments, &params, 2) {
+            Err(ProveError::Aggregate { index: 37, error }) => assert_eq!(*error, ProveError::CommitmentMismatch),
+            other => panic!("{:?}", other),
+        }
+        // a prover claiming the corrupted openings proves them for the commitments they open
+        let claimed: Vec<BigInt> = corrupted.iter().map(|(m, r)| commit(&params, m, r)).collect();
+        let forged = prove(&corrupted, &claimed, &params, 3).unwrap();
+        assert_eq!(verify_openings_batch(&commitments, &forged, &params), Err(VerifyError::InvalidProof));
+
+        let mut replaced = commitments.clone();
+        replaced[37] = claimed[37].clone();
+        let mut reordered = commitments.clone();
+        reordered.swap(3, 4);
+        for list in [&replaced[..], &reordered, &commitments[..99]] {
+            assert_eq!(verify_openings_batch(list, &proof, &params), Err(VerifyError::InvalidProof));
+        }
+        let tampered = BatchOpeningProof { z_m: &proof.z_m + 1, ..proof.clone() };
+        assert_eq!(verify_openings_batch(&commitments, &tampered, &params), Err(VerifyError::InvalidProof));
+    }
+
+    // Purpose: ensure the empty and single-element batches prove and verify, and bad inputs are refused
+    // Params: insecure 512-bit params; no openings; one opening; lists of different lengths; a
+    //         negative m; responses over the bound; a verifier nonce
+    // Output: Ok(()) for the empty and the single batch, each only for its own list;
+    //         InvalidDimension and Aggregate(ValueOutOfRange) from the prover; InvalidProof for
+    //         oversized responses and under another nonce
+    // Usage: `cargo test -- src::openings` or `cargo test`
+    #[test]
+    fn empty_and_single_batches() {
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(204);
+        let empty = prove(&[], &[], &params, 4).unwrap();
+        assert_eq!(empty.bits, 0);
+        assert_eq!(verify_openings_batch(&[], &empty, &params), Ok(()));
+
+        let single = openings(1, &mut rng);
+        let commitment = vec![commit(&params, &single[0].0, &single[0].1)];
+        let proof = prove(&single, &commitment, &params, 5).unwrap();
+        assert_eq!(verify_openings_batch(&commitment, &proof, &params), Ok(()));
+        assert_eq!(verify_openings_batch(&[], &proof, &params), Err(VerifyError::InvalidProof));
+        assert_eq!(verify_openings_batch(&commitment, &empty, &params), Err(VerifyError::InvalidProof));
+
+        assert_eq!(prove(&single, &[], &params, 6), Err(ProveError::InvalidDimension(1)));
+        let negative = vec![(BigInt::from(-5), single[0].1.clone())];
+        assert!(matches!(prove(&negative, &commitment, &params, 7), Err(ProveError::Aggregate { index: 0, error }) if *error == ProveError::ValueOutOfRange));
+
+        // a response past the bound is refused before any exponentiation, even with the width raised to cover it
+        let shift = BigInt::one() << (nonce_bits(proof.bits, 1) + 1);
+        let oversized = BatchOpeningProof { z_m: &proof.z_m + &shift, ..proof.clone() };
+        assert_eq!(verify_openings_batch(&commitment, &oversized, &params), Err(VerifyError::InvalidProof));
+        let widened = BatchOpeningProof { bits: MAX_OPENING_BITS + 1, ..proof.clone() };
+        assert_eq!(verify_openings_batch(&commitment, &widened, &params), Err(VerifyError::InvalidProof));
+
+        let mut rng = ChaCha20Rng::seed_from_u64(8);
+        let bound = prove_openings_batch_with_transcript(&single, &commitment, &params, &mut FiatShamir::with_nonce(b"session 1"), &mut rng).unwrap();
+        assert_eq!(verify_openings_batch_with_transcript(&commitment, &bound, &params, &mut FiatShamir::with_nonce(b"session 1")), Ok(()));
+        assert_eq!(verify_openings_batch(&commitment, &bound, &params), Err(VerifyError::InvalidProof));
+    }
+
+    // Purpose: ensure proofs survive their files
+    // Params: insecure 512-bit params; 3 openings and their proof saved with the commitments
+    // Output: the loaded commitments and proof equal the saved ones and verify; a commitment file
+    //         does not load as an openings proof
+    // Usage: `cargo test -- src::openings` or `cargo test`
+    #[test]
+    fn proof_files_round_trip() {
+        use crate::util::{load_openings_proof, save_commitment, save_openings_proof};
+        let params = insecure_test_setup(512);
+        let mut rng = ChaCha20Rng::seed_from_u64(205);
+        let openings = openings(3, &mut rng);
+        let commitments: Vec<BigInt> = openings.iter().map(|(m, r)| commit(&params, m, r)).collect();
+        let proof = prove(&openings, &commitments, &params, 9).unwrap();
+        let dir = std::env::temp_dir();
+        let (proof_path, commitment_path) = (dir.join(format!("cuproof_openings_{}.proof", std::process::id())), dir.join(format!("cuproof_openings_{}.c", std::process::id())));
+        let (proof_path, commitment_path) = (proof_path.to_str().unwrap(), commitment_path.to_str().unwrap());
+
+        save_openings_proof(proof_path, &commitments, &proof).unwrap();
+        let (loaded_commitments, loaded) = load_openings_proof(proof_path).unwrap();
+        assert_eq!((&loaded_commitments, &loaded), (&commitments, &proof));
+        assert_eq!(verify_openings_batch(&loaded_commitments, &loaded, &params), Ok(()));
+
+        save_commitment(commitment_path, &commitments[0], &params).unwrap();
+        assert!(load_openings_proof(commitment_path).is_err());
+        for path in [proof_path, commitment_path] { let _ = std::fs::remove_file(path); }
+    }
+}
@@ -0,0 +1,216 @@
+//! Integers and RSA public keys as OpenSSL tooling writes them (`openssl-interop` feature)
+//!
+//! Commitments and moduli exported by OpenSSL come as BIGNUMs or DER INTEGERs, whose sign and
+//! leading-zero rules are easy to get wrong through hex: `bigint_from_bignum` and
+//! `bigint_to_bignum` convert an `openssl::bn::BigNum` directly, `bigint_from_der` accepts exactly
+//! the one DER encoding of each value and `bigint_to_der` writes it. `params_from_public_key_pem`
+//! builds params over the modulus of an RSA public key, in either PEM encoding `openssl rsa` writes.
+
+use num_bigint::{BigInt, Sign};
+use openssl::bn::{BigNum, BigNumRef};
+use openssl::error::ErrorStack;
+use crate::rsa_import::{check_integer_contents, invalid, pem_body, Der, RSA_ENCRYPTION_OID};
+use crate::setup::{params_from_modulus_with, Params, SetupError};
+
+/// An OpenSSL BIGNUM as a BigInt, sign included
+/// - usage: `bigint_from_bignum(&bn)` for a commitment or modulus held by OpenSSL tooling, instead
+///   of printing it in hex
+pub fn bigint_from_bignum(bn: &BigNumRef) -> BigInt {
+    let sign = if bn.is_negative() { Sign::Minus } else { Sign::Plus };
+    BigInt::from_bytes_be(sign, &bn.to_vec())
+}
+
+/// `x` as an OpenSSL BIGNUM, the value bigint_from_bignum reads back
+/// - returns: the BIGNUM; the ErrorStack of OpenSSL when it cannot allocate it
+pub fn bigint_to_bignum(x: &BigInt) -> Result<BigNum, ErrorStack> {
+    let (sign, magnitude) = x.to_bytes_be();
+    let mut bn = BigNum::from_slice(&magnitude)?;
+    bn.set_negative(sign == Sign::Minus);
+    Ok(bn)
+}
+
+/// The contents of a DER INTEGER, big-endian two's complement, as a BigInt
+/// - returns: the value, negative when the top bit is set; InvalidKey for empty contents or a
+///   redundant leading byte (see bigint_from_der)
+pub fn bigint_from_der_contents(bytes: &[u8]) -> Result<BigInt, SetupError> {
+    check_integer_contents(bytes)?;
+    Ok(BigInt::from_signed_bytes_be(bytes))
+}
+
+/// One DER INTEGER, tag and length included, as a BigInt
+/// - params: der exactly one INTEGER, e.g. the output of `openssl asn1parse -genstr INTEGER:..` or
+///   of i2d_ASN1_INTEGER
+/// - returns: the value; InvalidKey for another tag, trailing bytes, or contents that are not the
+///   one DER encoding of their value: 0x00 pads only a value whose top bit is set (128 is 02 02 00 80,
+///   but 1 is 02 01 01, never 02 02 00 01)
+/// - usage: `bigint_from_der(&der)?` for a commitment or modulus exported by OpenSSL tooling,
+///   instead of printing it in hex
+pub fn bigint_from_der(der: &[u8]) -> Result<BigInt, SetupError> {
+    let mut reader = Der { buf: der };
+    let contents = reader.read(0x02)?;
+    if !reader.buf.is_empty() { return Err(invalid("trailing bytes after INTEGER")); }
+    bigint_from_der_contents(contents)
+}
+
+/// `x` as a DER INTEGER, the encoding bigint_from_der reads back
+pub fn bigint_to_der(x: &BigInt) -> Vec<u8> {
+    let contents = x.to_signed_bytes_be();
+    let len = contents.len();
+    let mut der = vec![0x02];
+    if len < 0x80 {
+        der.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let skip = len_bytes.iter().take_while(|&&b| b == 0).count();
+        der.push(0x80 | (len_bytes.len() - skip) as u8);
+        der.extend_from_slice(&len_bytes[skip..]);
+    }
+    der.extend_from_slice(&contents);
+    der
+}
+
+/// The modulus of a DER RSA public key
+/// - params: der a SubjectPublicKeyInfo ("PUBLIC KEY", `openssl rsa -pubout`) or a PKCS#1
+///   RSAPublicKey ("RSA PUBLIC KEY", `openssl rsa -RSAPublicKey_out`)
+/// - returns: n, or InvalidKey for other keys, a negative modulus or malformed DER
+pub fn modulus_from_public_key_der(der: &[u8]) -> Result<BigInt, SetupError> {
+    let mut outer = Der { buf: der };
+    let mut seq = Der { buf: outer.read(0x30)? };
+    let rsa_public_key = if seq.buf.first() == Some(&0x30) {
+        // SubjectPublicKeyInfo: AlgorithmIdentifier, then RSAPublicKey in a BIT STRING without unused bits
+        let mut alg = Der { buf: seq.read(0x30)? };
+        if alg.read(0x06)? != RSA_ENCRYPTION_OID { return Err(invalid("not an RSA key")); }
+        match seq.read(0x03)? {
+            [0x00, key @ ..] => key,
+            _ => return Err(invalid("bad BIT STRING")),
+        }
+    } else {
+        der
+    };
+    let mut key = Der { buf: rsa_public_key };
+    let mut seq = Der { buf: key.read(0x30)? };
+    let n = seq.integer()?;
+    let _e = seq.integer()?;
+    Ok(n)
+}
+
+/// Build Params over the modulus of a DER RSA public key, with params_from_modulus_with
+/// - params: der as in modulus_from_public_key_der, allow_insecure permits keys below 2048 bits (tests only)
+/// - returns: Params over n with hash-derived generators
+/// - usage: whoever holds the private key knows the factorization of n and can open a commitment
+///   to any value; import a public key only when its holder is the party trusted with the setup
+pub fn params_from_public_key_der(der: &[u8], allow_insecure: bool) -> Result<Params, SetupError> {
+    let n = modulus_from_public_key_der(der)?;
+    params_from_modulus_with(&n, n.bits() as usize, allow_insecure)
+}
+
+/// params_from_public_key_der for a PEM public key ("PUBLIC KEY" or "RSA PUBLIC KEY")
+pub fn params_from_public_key_pem(pem: &str, allow_insecure: bool) -> Result<Params, SetupError> {
+    let der = pem_body(pem, &["-----BEGIN PUBLIC KEY-----", "-----BEGIN RSA PUBLIC KEY-----"])
+        .ok_or_else(|| invalid("no PEM public key block"))??;
+    params_from_public_key_der(&der, allow_insecure)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsa_import::{self, params_from_rsa_pem};
+
+    const SPKI_512: &str = "-----BEGIN PUBLIC KEY-----
+MFwwDQYJKoZIhvcNAQEBBQADSwAwSAJBAMgS463oes9tB+BU3d1xPqKWv1IFktvL
+/UbE8QtkDz5lyuoQcMPQ94gqljrMFRNg8C3w+zdjOJiA2rezDNawTUsCAwEAAQ==
+-----END PUBLIC KEY-----";
+
+    const PKCS1_PUBLIC_512: &str = "-----BEGIN RSA PUBLIC KEY-----
+MEgCQQDIEuOt6HrPbQfgVN3dcT6ilr9SBZLby/1GxPELZA8+ZcrqEHDD0PeIKpY6
+zBUTYPAt8Ps3YziYgNq3swzWsE1LAgMBAAE=
+-----END RSA PUBLIC KEY-----";
+
+    // Purpose: ensure DER INTEGERs decode as OpenSSL encodes them and only in their one encoding
+    // Params: `openssl asn1parse -genstr INTEGER:v` output for values around the sign boundaries and
+    //         a 128-bit value; seeded random values up to 4096 bits of both signs; padded, empty,
+    //         mistagged and trailing inputs
+    // Output: the OpenSSL bytes decode to v and bigint_to_der reproduces them; random values round
+    //         trip; every malformed input is InvalidKey
+    // Usage: `cargo test --features openssl-interop -- src::openssl_interop`
+    #[test]
+    fn der_integers_match_openssl() {
+        let openssl = [
+            ("0", "020100"), ("127", "02017f"), ("128", "02020080"), ("255", "020200ff"), ("256", "02020100"),
+            ("-1", "0201ff"), ("-128", "020180"), ("-129", "0202ff7f"), ("-256", "0202ff00"),
+            ("1512366075204170929049582354406559215", "02100123456789abcdef0123456789abcdef"),
+        ];
+        for (value, der) in openssl {
+            let (value, der) = (value.parse::<BigInt>().unwrap(), hex::decode(der).unwrap());
+            assert_eq!(bigint_from_der(&der).unwrap(), value);
+            assert_eq!(bigint_to_der(&value), der);
+        }
+
+        use num_bigint::RandBigInt;
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(200);
+        for bits in [1, 7, 8, 9, 127, 1016, 1024, 4096] {
+            let x = rng.gen_bigint(bits);
+            assert_eq!(bigint_from_der(&bigint_to_der(&x)).unwrap(), x);
+            assert_eq!(bigint_from_der(&bigint_to_der(&-&x)).unwrap(), -x);
+        }
+        // long-form lengths: 251 and 501 content bytes
+        assert_eq!(&bigint_to_der(&(BigInt::from(1) << 2000))[..4], [0x02, 0x81, 0xfb, 0x01]);
+        assert_eq!(&bigint_to_der(&(BigInt::from(1) << 4000))[..5], [0x02, 0x82, 0x01, 0xf5, 0x01]);
+
+        for bad in ["02020001", "0202ff80", "0200", "03020080", "02017f00", "020280"] {
+            assert!(matches!(bigint_from_der(&hex::decode(bad).unwrap()), Err(SetupError::InvalidKey(_))), "{}", bad);
+        }
+    }
+
+    // Purpose: ensure BigInts and OpenSSL BIGNUMs convert both ways without losing sign or value
+    // Params: zero, values around the byte boundaries of both signs; seeded random values up to
+    //         4096 bits of both signs; the modulus of SPKI_512 as OpenSSL parses the key
+    // Output: each value round trips, and OpenSSL prints the BIGNUM as the same decimal; the
+    //         modulus equals the one modulus_from_public_key_der reads
+    // Usage: `cargo test --features openssl-interop -- src::openssl_interop`
+    #[test]
+    fn bignums_round_trip_through_openssl() {
+        let check = |x: &BigInt| {
+            let bn = bigint_to_bignum(x).unwrap();
+            assert_eq!(bn.to_dec_str().unwrap().to_string(), x.to_string());
+            assert_eq!(&bigint_from_bignum(&bn), x);
+            assert_eq!(BigNum::from_dec_str(&x.to_string()).map(|bn| bigint_from_bignum(&bn)).unwrap(), *x);
+        };
+        for value in ["0", "1", "-1", "127", "128", "255", "256", "-128", "-129", "-256", "18446744073709551616"] {
+            check(&value.parse::<BigInt>().unwrap());
+        }
+
+        use num_bigint::RandBigInt;
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(200);
+        for bits in [1, 7, 8, 9, 63, 64, 65, 127, 1016, 1024, 2048, 4096] {
+            let x = rng.gen_bigint(bits);
+            check(&x);
+            check(&-x);
+        }
+
+        let key = openssl::rsa::Rsa::public_key_from_pem(SPKI_512.as_bytes()).unwrap();
+        let der = pem_body(SPKI_512, &["-----BEGIN PUBLIC KEY-----"]).unwrap().unwrap();
+        assert_eq!(bigint_from_bignum(key.n()), modulus_from_public_key_der(&der).unwrap());
+    }
+
+    // Purpose: ensure RSA public keys import their modulus in both PEM encodings
+    // Params: the public halves of PKCS1_512, from `openssl rsa -pubout` and `-RSAPublicKey_out`; a
+    //         key with a negative modulus; a private key given as a public one
+    // Output: equal params over the private key's modulus; InsecureModulusSize without
+    //         allow_insecure; InvalidKey for the negative modulus and the private key
+    // Usage: `cargo test --features openssl-interop -- src::openssl_interop`
+    #[test]
+    fn import_public_keys() {
+        let spki = params_from_public_key_pem(SPKI_512, true).unwrap();
+        let pkcs1 = params_from_public_key_pem(PKCS1_PUBLIC_512, true).unwrap();
+        assert_eq!(spki, pkcs1);
+        assert_eq!(spki.n(), params_from_rsa_pem(rsa_import::tests::PKCS1_512, true).unwrap().n());
+        assert_eq!(spki, params_from_modulus_with(spki.n(), 512, true).unwrap());
+
+        assert!(matches!(params_from_public_key_pem(SPKI_512, false), Err(SetupError::InsecureModulusSize { .. })));
+        assert!(matches!(modulus_from_public_key_der(&[0x30, 0x06, 0x02, 0x01, 0xff, 0x02, 0x01, 0x03]), Err(SetupError::InvalidKey(_))));
+        assert!(matches!(params_from_public_key_pem(rsa_import::tests::PKCS8_512, true), Err(SetupError::InvalidKey(_))));
+    }
+}
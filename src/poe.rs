@@ -0,0 +1,336 @@
+//! Wesolowski proofs of exponentiation (PoE) over Z_n^*, and the VerifierAssist a proof carries
+//! to use them
+//!
+//! To show result = base^exp mod n without the verifier raising base to exp, the prover sends
+//! Q = base^(exp / l) for a prime l drawn from the transcript after base, exp and result; the
+//! verifier checks Q^l base^(exp mod l) = result, two exponents of PRIME_BITS bits. Several bases
+//! are proved at once the same way: Q = prod base_i^(exp_i / l) against prod base_i^(exp_i mod l).
+//!
+//! Soundness rests on the adaptive root assumption: no one can take l-th roots of elements they
+//! chose before l was drawn. Z_n^* has the element -1 of order two, whose l-th power is itself, so
+//! a proof for result also passes for -result (Q negated): the check shows result = ±base^exp. An
+//! attacker knowing any other element u of low order gets the same slack for u; finding one is
+//! believed as hard as factoring n.
+//!
+//! VerifierAssist carries the quotients for T1 = g^t1 h^tau1 and T2 = g^t2 h^tau2, the two large
+//! exponentiations of verify; verify::cuproof_verify_fast checks them through it. It divides by 2l
+//! rather than l, so Q enters its check squared and the sign slack above is gone: a negated T1 or
+//! T2 passes only with an element whose 2l-th power is -1, of order four or 4l, one of the
+//! low-order elements believed as hard to find as the factors of n. Anyone holding the proof and
+//! the params can compute the assist, so it needs no secret and adds nothing to what a proof
+//! reveals.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed};
+use crate::commitment::mod_exp;
+use crate::fiat_shamir::{fiat_shamir, FiatShamir, Transcript};
+use crate::montgomery::Montgomery;
+use crate::primality::baillie_psw;
+use crate::range_proof::Cuproof;
+use crate::setup::Params;
+
+/// Bits of the prime challenge l: its top bit is set, so every l has exactly this many
+pub const PRIME_BITS: u64 = 128;
+
+/// The prime challenge of `inputs`: prime_from_seed of their fiat_shamir hash
+/// - usage: the default Transcript::prime, e.g. `hash_to_prime(&[n, result, base, exp])`
+pub fn hash_to_prime(inputs: &[&BigInt]) -> BigInt {
+    prime_from_seed(&fiat_shamir(inputs))
+}
+
+/// The first of fiat_shamir(seed, 0), fiat_shamir(seed, 1), ... that is prime once truncated to
+/// PRIME_BITS bits with its top and bottom bits set; primality is Baillie-PSW, deterministic, so
+/// the prover and the verifier find the same l. About PRIME_BITS ln 2 / 2 = 44 candidates on
+/// average, most of them ruled out by SIEVE_PRIMES before Baillie-PSW runs
+pub(crate) fn prime_from_seed(seed: &BigInt) -> BigInt {
+    let top = 1u128 << (PRIME_BITS - 1);
+    (0u64..)
+        .map(|counter| {
+            let digest = fiat_shamir(&[seed, &BigInt::from(counter)]);
+            let low = digest.magnitude().iter_u64_digits().take(2).rev().fold(0u128, |acc, limb| (acc << 64) | u128::from(limb));
+            low | top | 1
+        })
+        .find(|&candidate| SIEVE_PRIMES.iter().all(|&p| candidate % u128::from(p) != 0) && baillie_psw(&BigUint::from(candidate)))
+        .map(BigInt::from)
+        .expect("the counter does not run out before a prime is found")
+}
+
+/// The odd primes below 1024, dividing out which leaves a sixth of odd candidates for Baillie-PSW
+const SIEVE_PRIMES: [u16; 171] = odd_primes_below_1024();
+
+const fn odd_primes_below_1024() -> [u16; 171] {
+    let mut primes = [0u16; 171];
+    let (mut count, mut candidate) = (0, 3u16);
+    while candidate < 1024 {
+        let mut d = 3;
+        while d * d <= candidate && candidate % d != 0 { d += 2; }
+        if d * d > candidate {
+            primes[count] = candidate;
+            count += 1;
+        }
+        candidate += 2;
+    }
+    primes
+}
+
+/// Q = base^(exp / l) mod n for result = base^exp, with l = transcript.prime over n, result, base, exp
+/// - params: exp non-negative; result what verify will be given, as it is hashed into l
+/// - returns: Q, to send with result
+pub fn prove(base: &BigInt, exp: &BigInt, result: &BigInt, n: &BigInt, transcript: &mut dyn Transcript) -> BigInt {
+    prove_multi(&[(base, exp)], result, n, transcript)
+}
+
+/// Whether `q` shows result = ±base^exp mod n (see the module docs)
+/// - returns: false for a negative exp, and for any q when result is not base^exp up to sign
+pub fn verify(base: &BigInt, exp: &BigInt, result: &BigInt, n: &BigInt, q: &BigInt, transcript: &mut dyn Transcript) -> bool {
+    verify_multi(&[(base, exp)], result, n, q, transcript)
+}
+
+/// Q = prod base_i^(exp_i / l) mod n for result = prod base_i^exp_i
+/// - params: terms the (base, exp) pairs, exponents non-negative
+/// - usage: `prove_multi(&[(g, &t1), (h, &tau1)], &T1, n, &mut FiatShamir::new())` for a commitment
+pub fn prove_multi(terms: &[(&BigInt, &BigInt)], result: &BigInt, n: &BigInt, transcript: &mut dyn Transcript) -> BigInt {
+    quotient(terms, &challenge(terms, result, n, transcript), n)
+}
+
+/// prod base_i^(exp_i / l) mod n
+fn quotient(terms: &[(&BigInt, &BigInt)], l: &BigInt, n: &BigInt) -> BigInt {
+    terms.iter().fold(BigInt::one(), |q, (base, exp)| q * mod_exp(base, &exp.div_floor(l), n) % n)
+}
+
+/// Whether `q` shows result = ±prod base_i^exp_i mod n: one simultaneous exponentiation of q and
+/// the bases by exponents of PRIME_BITS bits, instead of one per base by exp_i
+pub fn verify_multi(terms: &[(&BigInt, &BigInt)], result: &BigInt, n: &BigInt, q: &BigInt, transcript: &mut dyn Transcript) -> bool {
+    if terms.iter().any(|(_, exp)| exp.is_negative()) { return false; }
+    let l = challenge(terms, result, n, transcript);
+    let residues: Vec<BigInt> = terms.iter().map(|(_, exp)| exp.mod_floor(&l)).collect();
+    let mut powers = Vec::with_capacity(1 + terms.len());
+    powers.push((q, &l));
+    powers.extend(terms.iter().zip(&residues).map(|((base, _), r)| (*base, r)));
+    multi_exp(&powers, n) == *result
+}
+
+/// prod |base_i|^exp_i mod n for non-negative exponents, by Straus' method in Montgomery form:
+/// one squaring per bit of the longest exponent, and one multiplication per bit for each group of
+/// four bases, from a table of the group's 16 products. Even moduli fall back to mod_exp
+pub(crate) fn multi_exp(terms: &[(&BigInt, &BigInt)], n: &BigInt) -> BigInt {
+    let Some(mont) = Montgomery::new(n.magnitude()) else {
+        return terms.iter().fold(BigInt::one() % n, |acc, (base, exp)| acc * mod_exp(base, exp, n) % n);
+    };
+    let k = mont.limbs();
+    let mut scratch = vec![0u64; mont.scratch_len()];
+    let mut product = vec![0u64; k];
+    let tables: Vec<Vec<u64>> = terms.chunks(4).map(|group| {
+        let mut table = mont.one().to_vec();
+        for (base, _) in group {
+            let base = mont.to_mont(base.magnitude());
+            for i in 0..table.len() / k {
+                mont.mul(&table[i * k..(i + 1) * k], &base, &mut product, &mut scratch);
+                table.extend_from_slice(&product);
+            }
+        }
+        table
+    }).collect();
+    let bits = terms.iter().map(|(_, exp)| exp.bits()).max().unwrap_or(0);
+    let mut acc = mont.one().to_vec();
+    for bit in (0..bits).rev() {
+        mont.mul(&acc, &acc, &mut product, &mut scratch);
+        acc.copy_from_slice(&product);
+        for (group, table) in terms.chunks(4).zip(&tables) {
+            let index = group.iter().enumerate().fold(0, |index, (j, (_, exp))| index | (usize::from(exp.bit(bit)) << j));
+            if index == 0 { continue; }
+            mont.mul(&acc, &table[index * k..(index + 1) * k], &mut product, &mut scratch);
+            acc.copy_from_slice(&product);
+        }
+    }
+    BigInt::from_biguint(Sign::Plus, mont.out_of_mont(&acc, &mut scratch))
+}
+
+/// l for `terms` and `result` under n, drawn from `transcript`
+fn challenge(terms: &[(&BigInt, &BigInt)], result: &BigInt, n: &BigInt, transcript: &mut dyn Transcript) -> BigInt {
+    let mut message: Vec<&BigInt> = Vec::with_capacity(2 + 2 * terms.len());
+    message.extend([n, result]);
+    for (base, exp) in terms { message.extend([*base, *exp]); }
+    transcript.prime(&message)
+}
+
+/// Proofs of exponentiation for the two commitments verify recomputes, T1 = g^t1 h^tau1 and
+/// T2 = g^t2 h^tau2, one each under a prime l hashed from T1, T2 and their openings. Each quotient
+/// is taken by 2l, so its check holds for the canonical T1 and T2 exactly, not up to sign (see the
+/// module docs)
+/// - usage: `proof.with_verifier_assist(&params)`, then verify::cuproof_verify_fast. Proof files
+///   keep the assist in a section of their own after the statement; the other encodings drop it,
+///   and verify ignores it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierAssist {
+    /// Q1 = g^(t1 / 2l) h^(tau1 / 2l)
+    pub q1: BigInt,
+    /// Q2 = g^(t2 / 2l) h^(tau2 / 2l)
+    pub q2: BigInt,
+}
+
+impl VerifierAssist {
+    /// The assist for `proof` under `params`; as costly as the two commitments it replaces
+    /// - returns: None when an opening is negative, which no honest proof has
+    pub fn new(proof: &Cuproof, params: &Params) -> Option<VerifierAssist> {
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        if [&proof.t1, &proof.tau1, &proof.t2, &proof.tau2].iter().any(|x| x.is_negative()) { return None; }
+        let divisor = assist_challenge(proof, params) << 1;
+        Some(VerifierAssist {
+            q1: quotient(&[(g, &proof.t1), (h, &proof.tau1)], &divisor, n),
+            q2: quotient(&[(g, &proof.t2), (h, &proof.tau2)], &divisor, n),
+        })
+    }
+
+    /// Whether the assist shows T1 = g^t1 h^tau1 and T2 = g^t2 h^tau2 mod n for `proof`, with T1
+    /// and T2 as the proof holds them: two simultaneous exponentiations by exponents of
+    /// PRIME_BITS + 1 bits. A T1 or T2 that is not reduced mod n, or is negated, fails
+    /// - returns: false as well for negative openings
+    pub fn check(&self, proof: &Cuproof, params: &Params) -> bool {
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        if [&proof.t1, &proof.tau1, &proof.t2, &proof.tau2].iter().any(|x| x.is_negative()) { return false; }
+        let divisor = assist_challenge(proof, params) << 1;
+        let holds = |q: &BigInt, t: &BigInt, tau: &BigInt, commitment: &BigInt| {
+            let (t, tau) = (t.mod_floor(&divisor), tau.mod_floor(&divisor));
+            multi_exp(&[(q, &divisor), (g, &t), (h, &tau)], n) == *commitment
+        };
+        holds(&self.q1, &proof.t1, &proof.tau1, &proof.T1) && holds(&self.q2, &proof.t2, &proof.tau2, &proof.T2)
+    }
+}
+
+/// l of the assist: the FiatShamir::new() prime over the params fingerprint and the fiat_shamir
+/// hash of T1, T2, t1, tau1, t2 and tau2
+fn assist_challenge(proof: &Cuproof, params: &Params) -> BigInt {
+    let digest = fiat_shamir(&[&proof.T1, &proof.T2, &proof.t1, &proof.tau1, &proof.t2, &proof.tau2]);
+    let fingerprint = BigInt::from_bytes_be(Sign::Plus, &params.fingerprint());
+    FiatShamir::new().prime(&[&fingerprint, &digest])
+}
+
+impl Cuproof {
+    /// This proof with a VerifierAssist for `params`, replacing any it had (none when VerifierAssist::new has none)
+    pub fn with_verifier_assist(mut self, params: &Params) -> Cuproof {
+        self.assist = VerifierAssist::new(&self, params);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment::commit;
+    use crate::testing::sample_proof;
+
+    // Purpose: ensure hash_to_prime is deterministic and lands on primes of PRIME_BITS bits, and
+    //          pin the sieve and multi_exp
+    // Params: a few small inputs; five bases with exponents of up to 70 bits, mod 1000003 and
+    //         mod the even 1000000
+    // Output: the same prime twice for one input, different primes for different inputs, each
+    //         of exactly 128 bits and passing Baillie-PSW; SIEVE_PRIMES from 3 to 1021; multi_exp
+    //         equal to the product of mod_exp for both moduli
+    // Usage: `cargo test -- src::poe` or `cargo test`
+    #[test]
+    fn hash_to_prime_is_deterministic() {
+        let (one, two) = (BigInt::from(1), BigInt::from(2));
+        let l = hash_to_prime(&[&one, &two]);
+        assert_eq!(l, hash_to_prime(&[&one, &two]));
+        assert_ne!(l, hash_to_prime(&[&two, &one]));
+        for l in [l, hash_to_prime(&[]), hash_to_prime(&[&two])] {
+            assert_eq!(l.bits(), PRIME_BITS);
+            assert!(baillie_psw(l.magnitude()));
+        }
+        assert_eq!((SIEVE_PRIMES[0], SIEVE_PRIMES[170]), (3, 1021));
+
+        let n = BigInt::from(1_000_003);
+        let bases: Vec<BigInt> = [2, 3, 5, 999_999, 1_234_567].map(BigInt::from).to_vec();
+        let exps: Vec<BigInt> = [0u128, 1, 1 << 70, 12345, (1 << 69) + 7].map(BigInt::from).to_vec();
+        let terms: Vec<(&BigInt, &BigInt)> = bases.iter().zip(&exps).collect();
+        let expected = terms.iter().fold(BigInt::one(), |acc, (b, e)| acc * mod_exp(b, e, &n) % &n);
+        assert_eq!(multi_exp(&terms, &n), expected);
+        assert_eq!(multi_exp(&[], &n), BigInt::one());
+        let even = BigInt::from(1_000_000);
+        assert_eq!(multi_exp(&terms, &even), terms.iter().fold(BigInt::one(), |acc, (b, e)| acc * mod_exp(b, e, &even) % &even));
+    }
+
+    // Purpose: ensure a PoE verifies for its own exponentiation only
+    // Params: tiny params; g^e for a 3000-bit e, and g^t h^r
+    // Output: true for the honest statements and for result negated (the order-two slack of the
+    //         module docs); false for another exponent, another result, another Q, a negative
+    //         exponent and a Q made under a nonce transcript
+    // Usage: `cargo test -- src::poe` or `cargo test`
+    #[test]
+    fn wrong_exponent_or_result_is_rejected() {
+        let (params, _, _) = sample_proof(202);
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        let e = (BigInt::one() << 3000) + 12345;
+        let result = mod_exp(g, &e, n);
+        let q = prove(g, &e, &result, n, &mut FiatShamir::new());
+        assert!(verify(g, &e, &result, n, &q, &mut FiatShamir::new()));
+        let negated = n - &result;
+        let q_negated = n - prove(g, &e, &negated, n, &mut FiatShamir::new());
+        assert!(verify(g, &e, &negated, n, &q_negated, &mut FiatShamir::new()));
+
+        assert!(!verify(g, &(&e + 1), &result, n, &q, &mut FiatShamir::new()));
+        let other = mod_exp(g, &(&e + 1), n);
+        assert!(!verify(g, &e, &other, n, &q, &mut FiatShamir::new()));
+        assert!(!verify(g, &(&e + 1), &other, n, &q, &mut FiatShamir::new()));
+        assert!(!verify(g, &e, &result, n, &(&q * g % n), &mut FiatShamir::new()));
+        assert!(!verify(g, &-&e, &result, n, &q, &mut FiatShamir::new()));
+        let bound = prove(g, &e, &result, n, &mut FiatShamir::with_nonce(b"session"));
+        assert!(!verify(g, &e, &result, n, &bound, &mut FiatShamir::new()));
+
+        let (t, r) = (BigInt::from(7) << 2500, (BigInt::from(3) << 1800) + 1);
+        let commitment = commit(&params, &t, &r);
+        let q = prove_multi(&[(g, &t), (h, &r)], &commitment, n, &mut FiatShamir::new());
+        assert!(verify_multi(&[(g, &t), (h, &r)], &commitment, n, &q, &mut FiatShamir::new()));
+        assert!(!verify_multi(&[(g, &r), (h, &t)], &commitment, n, &q, &mut FiatShamir::new()));
+    }
+
+    // Purpose: ensure VerifierAssist checks a proof's T1 and T2 exactly and catches each being changed
+    // Params: sample_proof(202) with its assist; T1 replaced by another commitment, t2 raised by
+    //         one, tau1 negated, each with the honest assist and with one made for the changed proof;
+    //         T1, then T2, negated with t_hat recomputed for the new x and Q1, Q2 or both negated;
+    //         T1 + n
+    // Output: true for the proof, false for every change, the negations and the unreduced T1
+    // Usage: `cargo test -- src::poe` or `cargo test`
+    #[test]
+    fn assist_checks_the_commitments() {
+        let (params, _, proof) = sample_proof(202);
+        let proof = proof.with_verifier_assist(&params);
+        let assist = proof.assist.clone().unwrap();
+        assert!(assist.check(&proof, &params));
+
+        let changes = [
+            Cuproof { T1: commit(&params, &BigInt::from(5), &BigInt::from(6)), ..proof.clone() },
+            Cuproof { t2: &proof.t2 + 1, ..proof.clone() },
+            Cuproof { tau1: -&proof.tau1, ..proof.clone() },
+        ];
+        for changed in &changes {
+            assert!(!assist.check(changed, &params));
+            assert!(VerifierAssist::new(changed, &params).is_none_or(|a| !a.check(changed, &params)));
+        }
+
+        let n = params.n();
+        let negated_t1 = Cuproof { T1: n - &proof.T1, ..proof.clone() };
+        let negated_t2 = Cuproof { T2: n - &proof.T2, ..proof.clone() };
+        for mut negated in [negated_t1, negated_t2] {
+            let x = crate::verify::proof_challenges(&negated, &params).x;
+            negated.t_hat = &negated.t0 + &negated.t1 * &x + &negated.t2 * &x * &x;
+            let assist = VerifierAssist::new(&negated, &params).unwrap();
+            let (q1, q2) = (n - &assist.q1, n - &assist.q2);
+            for forged in [
+                assist.clone(),
+                VerifierAssist { q1: q1.clone(), ..assist.clone() },
+                VerifierAssist { q2: q2.clone(), ..assist.clone() },
+                VerifierAssist { q1, q2 },
+            ] {
+                assert!(!forged.check(&negated, &params));
+            }
+        }
+
+        let unreduced = Cuproof { T1: &proof.T1 + n, ..proof.clone() };
+        assert!(!assist.check(&unreduced, &params));
+    }
+}
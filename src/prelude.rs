@@ -0,0 +1,41 @@
+//! The stable surface in one import: `use cuproof::prelude::*;`
+//!
+//! The types of a commit, prove and verify round, the Group trait their commitments and
+//! fingerprints come from, the error types, the wire format and, with std, setup and the file and
+//! stream functions. Every item is also re-exported at the crate root; the prelude only saves
+//! naming them. The serialization traits of the `borsh` and `proto` features come along when
+//! those are enabled.
+//!
+//! ```
+//! use cuproof::prelude::*;
+//! use num_bigint::BigInt;
+//!
+//! let params = cuproof::setup::insecure_test_setup(512);
+//! let (v, a, b) = (BigInt::from(30), BigInt::from(10), BigInt::from(100));
+//! let r = random_blinding(&mut rand::rngs::OsRng);
+//! let commitment = params.commit(&v, &r);
+//!
+//! let proof = prove(&v, &r, &a, &b, &params)?;
+//! assert_eq!(proof.C, commitment);
+//!
+//! // the verifier checks the proof against its own record of the commitment and range
+//! let expected = Statement::new(&commitment, &a, &b, 64, &params);
+//! verify_statement(&proof, &params, &expected)?;
+//! # Ok::<(), Error>(())
+//! ```
+
+pub use crate::{
+    bigint_from_be_bytes, bigint_to_be_bytes_padded, params_from_wire, params_to_wire, proof_from_wire, proof_to_wire,
+    prove_with_rng, random_blinding, scalar_from_be_bytes, scalar_to_be_bytes, verify, verify_statement, Cuproof,
+    DecodeError, EncodeError, Group, Params, ParamsError, ProveError, RangeProver, SetupError, Statement, Verifier,
+    VerifyError, WireError,
+};
+#[cfg(feature = "std")]
+pub use crate::{
+    load_params, load_proof, prove, read_params, read_proof, save_params, save_proof, trusted_setup, write_params,
+    write_proof, Error, ParamsLoadError, ProofLoadError, ReadLimits, SerializationError,
+};
+#[cfg(feature = "borsh")]
+pub use ::borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "proto")]
+pub use crate::proto::Message;
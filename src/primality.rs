@@ -0,0 +1,420 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::{BigInt, BigUint, Sign};
+#[cfg(feature = "std")]
+use num_bigint::RandBigInt;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::RngCore;
+use crate::setup::SecretInt;
+
+/// How candidate primes are tested during setup and when importing primes
+///
+/// - `mr_rounds`: Miller-Rabin rounds with random bases (error <= 4^-rounds per composite)
+/// - `use_baillie_psw`: also run Baillie-PSW (strong base-2 test plus strong Lucas test, Selfridge
+///   parameters); no composite is known to pass it
+/// - `provable`: trusted setup generates primes with the Shawe-Taylor method and checks a Pocklington
+///   certificate for each. Certificates contain the primes, so they are verified and then reduced to
+///   `PrimalityAttestation::certified` plus the `AuxiliaryCertificate` kept in the params metadata.
+///   Supplied primes (`params_from_primes`) cannot be certified and are always tested probabilistically
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimalityConfig {
+    pub mr_rounds: u32,
+    pub use_baillie_psw: bool,
+    pub provable: bool,
+}
+
+impl Default for PrimalityConfig {
+    /// Baillie-PSW plus 64 Miller-Rabin rounds, probable primes
+    fn default() -> Self {
+        PrimalityConfig { mr_rounds: 64, use_baillie_psw: true, provable: false }
+    }
+}
+
+/// What a setup established about its primes; safe to publish
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimalityAttestation {
+    /// The configuration the primes were tested with
+    pub config: PrimalityConfig,
+    /// True if both primes came with a Pocklington certificate that verified
+    pub certified: bool,
+}
+
+const SMALL_PRIMES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Some(answer) when n is decided by the small primes alone
+fn small_prime_check(n: &BigUint) -> Option<bool> {
+    if *n < BigUint::from(2u32) { return Some(false); }
+    for p in SMALL_PRIMES {
+        if *n == BigUint::from(p) { return Some(true); }
+        if (n % p).is_zero() { return Some(false); }
+    }
+    None
+}
+
+pub(crate) fn miller_rabin_with_rng<R: RngCore>(n: &BigUint, k: u32, rng: &mut R) -> bool {
+    if let Some(answer) = small_prime_check(n) { return answer; }
+
+    // write n-1 = d * 2^r; n - 1, d and x all reveal n, so they are wiped like n itself
+    let one = BigUint::one();
+    let n_minus_one = SecretInt::new(n - &one);
+    let mut d = SecretInt::new(n_minus_one.expose().clone());
+    let mut r = 0u32;
+    while d.expose().is_even() { *d.expose_mut() >>= 1; r += 1; }
+
+    'witness: for _ in 0..k {
+        // pick random a in [2, n-2]
+        let two = BigUint::from(2u32);
+        let n_minus_two = SecretInt::new(n - &two);
+        if *n_minus_two.expose() <= two { return true; }
+        // sample a by rejection using bytes length
+        let mut a;
+        loop {
+            let mut buf = vec![0u8; n.bits() as usize / 8 + 1];
+            rng.fill_bytes(&mut buf);
+            a = BigUint::from_bytes_be(&buf);
+            a = two.clone() + (a % (n_minus_two.expose() - &two + &one));
+            if a >= two && a <= *n_minus_two.expose() { break; }
+        }
+
+        // x = a^d mod n
+        let mut x = SecretInt::new(a.modpow(d.expose(), n));
+        if x.ct_eq(&one) | x.ct_eq(n_minus_one.expose()) { continue 'witness; }
+        for _ in 0..(r-1) {
+            x = SecretInt::new(x.expose().modpow(&two, n));
+            if x.ct_eq(n_minus_one.expose()) { continue 'witness; }
+        }
+        return false;
+    }
+    true
+}
+
+/// True if base a proves the odd number n composite in the Miller-Rabin test
+pub(crate) fn is_miller_rabin_witness(n: &BigUint, a: &BigUint) -> bool {
+    let one = BigUint::one();
+    if *n < BigUint::from(5u32) || n.is_even() { return false; }
+    let n_minus_one = SecretInt::new(n - &one);
+    if a <= &one || a >= n_minus_one.expose() { return false; }
+    let mut d = SecretInt::new(n_minus_one.expose().clone());
+    let mut r = 0u32;
+    while d.expose().is_even() { *d.expose_mut() >>= 1; r += 1; }
+    let mut x = SecretInt::new(a.modpow(d.expose(), n));
+    if x.ct_eq(&one) | x.ct_eq(n_minus_one.expose()) { return false; }
+    for _ in 1..r {
+        x = SecretInt::new(x.expose().modpow(&BigUint::from(2u32), n));
+        if x.ct_eq(n_minus_one.expose()) { return false; }
+    }
+    true
+}
+
+/// Jacobi symbol (a / n) for odd positive n
+fn jacobi(a: &BigInt, n: &BigUint) -> i32 {
+    let mut n = n.clone();
+    let mut a = a.mod_floor(&BigInt::from_biguint(Sign::Plus, n.clone())).to_biguint().expect("reduced mod n");
+    let mut result = 1;
+    while !a.is_zero() {
+        while a.is_even() {
+            a >>= 1;
+            let r = &n % 8u32;
+            if r == BigUint::from(3u32) || r == BigUint::from(5u32) { result = -result; }
+        }
+        core::mem::swap(&mut a, &mut n);
+        if (&a % 4u32) == BigUint::from(3u32) && (&n % 4u32) == BigUint::from(3u32) { result = -result; }
+        a %= &n;
+    }
+    if n.is_one() { result } else { 0 }
+}
+
+/// x / 2 mod n for odd n, with x already reduced
+fn half_mod(x: BigUint, n: &BigUint) -> BigUint {
+    if x.is_even() { x >> 1 } else { (x + n) >> 1 }
+}
+
+/// Strong Lucas probable prime test with Selfridge's method A parameters (P = 1, Q = (1 - D) / 4)
+fn strong_lucas_probable_prime(n: &BigUint) -> bool {
+    if n.is_even() { return *n == BigUint::from(2u32); }
+    let root = n.sqrt();
+    if &root * &root == *n { return false; }
+
+    // first D in 5, -7, 9, -11, ... with (D / n) = -1
+    let mut d: i64 = 5;
+    loop {
+        match jacobi(&BigInt::from(d), n) {
+            -1 => break,
+            0 if BigUint::from(d.unsigned_abs()) != *n => return false,
+            _ => d = if d > 0 { -(d + 2) } else { -d + 2 },
+        }
+    }
+    let n_int = BigInt::from_biguint(Sign::Plus, n.clone());
+    let to_mod = |x: BigInt| x.mod_floor(&n_int).to_biguint().expect("reduced mod n");
+    let d_mod = to_mod(BigInt::from(d));
+    let q = to_mod(BigInt::from((1 - d) / 4));
+
+    // n + 1 = k * 2^s with k odd
+    let mut k = SecretInt::new(n + 1u32);
+    let mut s = 0u32;
+    while k.expose().is_even() { *k.expose_mut() >>= 1; s += 1; }
+
+    // binary ladder for U_k, V_k, Q^k starting from index 1
+    let (mut u, mut v, mut qk) = (BigUint::one(), BigUint::one(), q.clone());
+    for i in (0..k.expose().bits() - 1).rev() {
+        // index j -> 2j
+        u = &u * &v % n;
+        v = to_mod(BigInt::from_biguint(Sign::Plus, &v * &v) - BigInt::from_biguint(Sign::Plus, &qk << 1));
+        qk = &qk * &qk % n;
+        if k.expose().bit(i) {
+            // index 2j -> 2j + 1 with P = 1
+            let u_next = half_mod((&u + &v) % n, n);
+            let v_next = half_mod((&d_mod * &u + &v) % n, n);
+            u = u_next;
+            v = v_next;
+            qk = &qk * &q % n;
+        }
+    }
+    if u.is_zero() || v.is_zero() { return true; }
+    for _ in 1..s {
+        v = to_mod(BigInt::from_biguint(Sign::Plus, &v * &v) - BigInt::from_biguint(Sign::Plus, &qk << 1));
+        qk = &qk * &qk % n;
+        if v.is_zero() { return true; }
+    }
+    false
+}
+
+/// Baillie-PSW: strong probable prime to base 2 and strong Lucas probable prime
+pub fn baillie_psw(n: &BigUint) -> bool {
+    if let Some(answer) = small_prime_check(n) { return answer; }
+    !is_miller_rabin_witness(n, &BigUint::from(2u32)) && strong_lucas_probable_prime(n)
+}
+
+/// Test n under `config` (the `provable` flag does not apply to a single number)
+/// - params: n candidate, config tests to run, rng source of Miller-Rabin bases
+/// - returns: true if n passes every configured test
+/// - usage: setup candidate testing and validation of supplied primes
+pub fn is_probable_prime<R: RngCore>(n: &BigUint, config: &PrimalityConfig, rng: &mut R) -> bool {
+    if let Some(answer) = small_prime_check(n) { return answer; }
+    if config.use_baillie_psw && !baillie_psw(n) { return false; }
+    miller_rabin_with_rng(n, config.mr_rounds, rng)
+}
+
+/// Largest size handled by trial division at the bottom of the Shawe-Taylor recursion
+const DIRECT_PRIME_BITS: usize = 32;
+
+/// One Pocklington step: `prime - 1 = 2 t * factor` with `factor` prime, factor^2 > prime, and
+/// witness^(prime-1) = 1, gcd(witness^(2t) - 1, prime) = 1
+#[cfg(feature = "std")]
+struct PocklingtonStep {
+    prime: SecretInt,
+    witness: BigUint,
+}
+
+/// Certificate chain from a trial-division prime up to the final prime; holds secrets, never leaves the crate
+#[cfg(feature = "std")]
+pub(crate) struct PrimalityCertificate {
+    base: SecretInt,
+    steps: Vec<PocklingtonStep>,
+}
+
+#[cfg(feature = "std")]
+impl PrimalityCertificate {
+    /// Check every link of the chain and that it ends at `p`
+    pub(crate) fn verify(&self, p: &BigUint) -> bool {
+        let base = self.base.expose();
+        if base.bits() as usize > DIRECT_PRIME_BITS || !is_prime_by_trial_division(base) { return false; }
+        let mut factor = base;
+        for step in &self.steps {
+            if !pocklington_step_holds(factor, step.prime.expose(), &step.witness) { return false; }
+            factor = step.prime.expose();
+        }
+        factor == p
+    }
+
+    /// The publishable start of the chain, or None if the chain is too short to have one
+    /// The last two links are withheld: the final prime is p itself, and the one before it is a prime
+    /// factor F of p - 1 with F^2 > p; p = 1 mod 2F would put p within reach of Coppersmith's method.
+    /// What remains tops out near p^(1/4) and only divides F - 1
+    pub(crate) fn auxiliary(&self) -> Option<AuxiliaryCertificate> {
+        let published = self.steps.len().checked_sub(2)?;
+        Some(AuxiliaryCertificate {
+            base: self.base.expose().clone(),
+            steps: self.steps[..published]
+                .iter()
+                .map(|step| AuxiliaryStep { prime: step.prime.expose().clone(), witness: step.witness.clone() })
+                .collect(),
+        })
+    }
+}
+
+/// Pocklington's criterion for `c` given a prime `factor` of c - 1 with factor^2 > c
+fn pocklington_step_holds(factor: &BigUint, c: &BigUint, witness: &BigUint) -> bool {
+    let one = BigUint::one();
+    let c_minus_one = SecretInt::new(c - &one);
+    if factor * factor <= *c || !(c_minus_one.expose() % factor).is_zero() { return false; }
+    if !witness.modpow(c_minus_one.expose(), c).is_one() { return false; }
+    let z = SecretInt::new(witness.modpow(&(c_minus_one.expose() / factor), c));
+    !z.expose().is_zero() && (z.expose() - &one).gcd(c).is_one()
+}
+
+/// One published link of an `AuxiliaryCertificate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxiliaryStep {
+    pub prime: BigUint,
+    pub witness: BigUint,
+}
+
+/// Pocklington certificate for the auxiliary primes of a Shawe-Taylor prime, safe to publish
+/// Starts at a prime of at most 32 bits and proves each following prime from the one before;
+/// the links reaching the secret prime are left out (see the params metadata)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxiliaryCertificate {
+    pub base: BigUint,
+    pub steps: Vec<AuxiliaryStep>,
+}
+
+impl AuxiliaryCertificate {
+    /// Largest prime the certificate proves
+    pub fn top(&self) -> &BigUint {
+        self.steps.last().map_or(&self.base, |step| &step.prime)
+    }
+
+    /// Re-check the base by trial division and every Pocklington link
+    pub fn verify(&self) -> bool {
+        if self.base.bits() as usize > DIRECT_PRIME_BITS || !is_prime_by_trial_division(&self.base) { return false; }
+        let mut factor = &self.base;
+        for step in &self.steps {
+            if !pocklington_step_holds(factor, &step.prime, &step.witness) { return false; }
+            factor = &step.prime;
+        }
+        true
+    }
+}
+
+fn is_prime_by_trial_division(n: &BigUint) -> bool {
+    let n = match n.to_u64_digits().as_slice() {
+        [] => 0,
+        [x] => *x,
+        _ => return false,
+    };
+    if n < 2 { return false; }
+    (2u64..).take_while(|d| d * d <= n).all(|d| n % d != 0)
+}
+
+/// Shawe-Taylor style provable prime of exactly `bits` bits with the top two bits set
+/// Primes are not uniform over the interval (each has a large known factor of p - 1), which does
+/// not matter for factoring hardness of n = p * q at these sizes
+#[cfg(feature = "std")]
+pub(crate) fn provable_prime<R: RngCore>(bits: usize, rng: &mut R) -> (SecretInt, PrimalityCertificate) {
+    let low = (BigUint::from(3u32) << (bits - 2)) as BigUint;
+    let high = BigUint::one() << bits;
+    if bits <= DIRECT_PRIME_BITS {
+        loop {
+            let c = SecretInt::new(rng.gen_biguint_range(&low, &high) | BigUint::one());
+            if is_prime_by_trial_division(c.expose()) {
+                let base = SecretInt::new(c.expose().clone());
+                return (c, PrimalityCertificate { base, steps: Vec::new() });
+            }
+        }
+    }
+    let (factor, mut cert) = provable_prime(bits.div_ceil(2) + 1, rng);
+    // c = 2 t factor + 1 in [3 * 2^(bits-2), 2^bits)
+    let two_f = factor.expose() << 1;
+    let t_low = (&low - 1u32).div_ceil(&two_f);
+    let t_high = (&high - 2u32) / &two_f + 1u32;
+    let two = BigUint::from(2u32);
+    loop {
+        let t = SecretInt::new(rng.gen_biguint_range(&t_low, &t_high));
+        let c = SecretInt::new(&two_f * t.expose() + 1u32);
+        if small_prime_check(c.expose()) == Some(false) { continue; }
+        let a = rng.gen_biguint_range(&two, &(c.expose() - 1u32));
+        let z = SecretInt::new(a.modpow(&(t.expose() << 1), c.expose()));
+        if (z.expose() - 1u32).gcd(c.expose()).is_one() && z.expose().modpow(factor.expose(), c.expose()).is_one() {
+            cert.steps.push(PocklingtonStep { prime: SecretInt::new(c.expose().clone()), witness: a });
+            return (c, cert);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    // Purpose: ensure Baillie-PSW agrees with a sieve and rejects known pseudoprimes
+    // Params: all n below 20000; the strong Lucas pseudoprimes 5459, 5777, 10877; the base-2
+    //         strong pseudoprime 3215031751; Mersenne primes
+    // Output: BPSW matches trial division; each pseudoprime fools only its half of the test
+    // Usage: `cargo test -- src::primality` or `cargo test`
+    #[test]
+    fn baillie_psw_matches_sieve_and_rejects_pseudoprimes() {
+        for n in 0u32..20000 {
+            assert_eq!(baillie_psw(&BigUint::from(n)), is_prime_by_trial_division(&BigUint::from(n)), "n = {}", n);
+        }
+        for n in [5459u32, 5777, 10877] {
+            assert!(strong_lucas_probable_prime(&BigUint::from(n)));
+            assert!(!baillie_psw(&BigUint::from(n)));
+        }
+        let spsp = BigUint::from(3215031751u64);
+        assert!(!is_miller_rabin_witness(&spsp, &BigUint::from(2u32)));
+        assert!(!baillie_psw(&spsp));
+        for e in [61u32, 89, 107, 127] {
+            assert!(baillie_psw(&((BigUint::one() << e) - 1u32)));
+        }
+        assert!(!baillie_psw(&(((BigUint::one() << 61) - 1u32) * ((BigUint::one() << 89) - 1u32))));
+    }
+
+    // Purpose: ensure each PrimalityConfig path behaves as configured, including a weakened one
+    // Params: a 128-bit semiprime, a Carmichael number, default / MR-only / BPSW-only / empty configs
+    // Output: composites rejected by real configs; the empty config lets a composite through
+    // Usage: `cargo test -- src::primality` or `cargo test`
+    #[test]
+    fn configs_exercise_each_path() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let p = (BigUint::one() << 61) - 1u32;
+        let q = (BigUint::one() << 89) - 1u32;
+        let semiprime = &p * &q;
+        let carmichael = BigUint::from(41041u32); // 7 * 11 * 13 * 41
+        let mr_only = PrimalityConfig { mr_rounds: 20, use_baillie_psw: false, provable: false };
+        let bpsw_only = PrimalityConfig { mr_rounds: 0, use_baillie_psw: true, provable: false };
+        let weakened = PrimalityConfig { mr_rounds: 0, use_baillie_psw: false, provable: false };
+        for config in [PrimalityConfig::default(), mr_only, bpsw_only] {
+            assert!(is_probable_prime(&q, &config, &mut rng));
+            assert!(!is_probable_prime(&semiprime, &config, &mut rng));
+            assert!(!is_probable_prime(&carmichael, &config, &mut rng));
+        }
+        assert!(is_probable_prime(&semiprime, &weakened, &mut rng));
+        assert!(!is_probable_prime(&BigUint::from(35u32), &weakened, &mut rng));
+    }
+
+    // Purpose: ensure Shawe-Taylor primes have the requested size and a certificate that verifies only for them
+    // Params: sizes from the trial-division base case up to 256 bits
+    // Output: exact bit length, top two bits set, certificate verification true/false; auxiliary
+    //         certificates verify, stay below half the prime's size and fail when tampered
+    // Usage: `cargo test -- src::primality` or `cargo test`
+    #[test]
+    fn provable_primes_carry_valid_certificates() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        for bits in [20usize, 33, 64, 128, 256] {
+            let (p, cert) = provable_prime(bits, &mut rng);
+            assert_eq!(p.expose().bits() as usize, bits);
+            assert!(p.expose().bit(bits as u64 - 2));
+            assert!(cert.verify(p.expose()));
+            assert!(baillie_psw(p.expose()));
+            assert!(!cert.verify(&(p.expose() + 2u32)));
+            // the published part stops two links short of p and still verifies on its own
+            match cert.auxiliary() {
+                Some(aux) => {
+                    assert_eq!(aux.steps.len() + 2, cert.steps.len());
+                    assert!(aux.verify());
+                    assert!((aux.top().bits() as usize) * 2 < bits);
+                }
+                None => assert!(cert.steps.len() < 2),
+            }
+        }
+        let mut aux = provable_prime(256, &mut rng).1.auxiliary().unwrap();
+        aux.steps[0].witness = BigUint::one();
+        assert!(!aux.verify());
+        let (p, mut cert) = provable_prime(128, &mut rng);
+        cert.steps.last_mut().unwrap().witness = BigUint::one();
+        assert!(!cert.verify(p.expose()));
+    }
+}
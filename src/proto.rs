@@ -0,0 +1,352 @@
+//! Protobuf messages of proto/cuproof.proto, with conversions to and from the crate's types
+//!
+//! The message structs are generated by prost from the schema at build time (build.rs), so the
+//! schema is the single definition of the wire format. `Message::decode` follows protobuf
+//! semantics: fields may come in any order, unknown fields are skipped, absent fields take their
+//! default and the last occurrence of a singular field wins. Validation happens in the `TryFrom`
+//! conversions, which accept only minimal integer encodings and run the checks of load_proof
+//! (proofs) or validate_params (params).
+
+use num_bigint::BigInt;
+use num_traits::{Signed, Zero};
+use std::fmt;
+use crate::range_proof::IPPProof;
+use crate::setup::{validate_params, ParamsError};
+use crate::util::{check_header_nonzero, check_ipp_lengths, check_proof_int};
+
+/// Value of the version field of every message written by this module
+pub const PROTO_FORMAT_VERSION: u32 = 1;
+
+pub use prost::Message;
+
+include!(concat!(env!("OUT_DIR"), "/cuproof.v1.rs"));
+
+/// Why a protobuf message was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtoError {
+    /// the bytes are not a well-formed message of the expected type
+    Decode(prost::DecodeError),
+    /// a field holds a value the conversion does not accept
+    Malformed(&'static str),
+    /// the version field is not PROTO_FORMAT_VERSION
+    UnsupportedVersion(u32),
+    /// a required message field is absent
+    MissingField(&'static str),
+    /// the named big integer is not the minimal two's-complement encoding
+    NonCanonicalInteger(&'static str),
+    /// the converted proof fails the checks of load_proof
+    InvalidProof(&'static str),
+    /// the converted params fail validate_params
+    InvalidParams(ParamsError),
+    /// the statement names other params than the ones given
+    ParamsMismatch,
+    /// the statement's lower bound exceeds its upper bound
+    EmptyRange,
+}
+
+impl fmt::Display for ProtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtoError::Decode(e) => write!(f, "malformed protobuf: {}", e),
+            ProtoError::Malformed(msg) => write!(f, "malformed protobuf: {}", msg),
+            ProtoError::UnsupportedVersion(v) => write!(f, "unsupported protobuf format version {}", v),
+            ProtoError::MissingField(name) => write!(f, "missing protobuf field {}", name),
+            ProtoError::NonCanonicalInteger(name) => write!(f, "field {} is not a minimal two's-complement integer", name),
+            ProtoError::InvalidProof(msg) => write!(f, "invalid proof: {}", msg),
+            ProtoError::InvalidParams(e) => write!(f, "invalid params: {}", e),
+            ProtoError::ParamsMismatch => write!(f, "statement was made for other params"),
+            ProtoError::EmptyRange => write!(f, "statement range is empty"),
+        }
+    }
+}
+
+impl core::error::Error for ProtoError {}
+
+impl From<prost::DecodeError> for ProtoError {
+    fn from(e: prost::DecodeError) -> ProtoError {
+        ProtoError::Decode(e)
+    }
+}
+
+/// Minimal two's-complement big-endian bytes; zero is empty
+fn int_bytes(x: &BigInt) -> Vec<u8> {
+    if x.is_zero() { Vec::new() } else { x.to_signed_bytes_be() }
+}
+
+/// Inverse of int_bytes for the field `name`, accepting only its output
+fn int_from(bytes: &[u8], name: &'static str) -> Result<BigInt, ProtoError> {
+    let redundant = match bytes {
+        [0x00] => true,
+        [0x00, next, ..] => *next < 0x80,
+        [0xff, next, ..] => *next >= 0x80,
+        _ => false,
+    };
+    if redundant { return Err(ProtoError::NonCanonicalInteger(name)); }
+    Ok(if bytes.is_empty() { BigInt::zero() } else { BigInt::from_signed_bytes_be(bytes) })
+}
+
+/// int_from for a proof field, with the load_proof size limit
+fn proof_int(bytes: &[u8], name: &'static str) -> Result<BigInt, ProtoError> {
+    let x = int_from(bytes, name)?;
+    check_proof_int(&x).map_err(ProtoError::InvalidProof)?;
+    Ok(x)
+}
+
+/// int_from for a params field; params integers are non-negative and held to the proof size limit
+fn params_int(bytes: &[u8], name: &'static str) -> Result<BigInt, ProtoError> {
+    let x = int_from(bytes, name)?;
+    if x.is_negative() { return Err(ProtoError::Malformed("params integers must be non-negative")); }
+    check_proof_int(&x).map_err(|_| ProtoError::Malformed("params integer exceeds MAX_PROOF_INT_BYTES"))?;
+    Ok(x)
+}
+
+fn check_version(version: u32) -> Result<(), ProtoError> {
+    if version == PROTO_FORMAT_VERSION { Ok(()) } else { Err(ProtoError::UnsupportedVersion(version)) }
+}
+
+impl From<&crate::range_proof::Cuproof> for Cuproof {
+    fn from(proof: &crate::range_proof::Cuproof) -> Cuproof {
+        let ipp = &proof.ipp_proof;
+        Cuproof {
+            version: PROTO_FORMAT_VERSION,
+            epoch: proof.epoch,
+            a_commitment: int_bytes(&proof.A),
+            s_commitment: int_bytes(&proof.S),
+            t1_commitment: int_bytes(&proof.T1),
+            t2_commitment: int_bytes(&proof.T2),
+            tau_x: int_bytes(&proof.tau_x),
+            mu: int_bytes(&proof.mu),
+            t_hat: int_bytes(&proof.t_hat),
+            c: int_bytes(&proof.C),
+            c_v1: int_bytes(&proof.C_v1),
+            c_v2: int_bytes(&proof.C_v2),
+            t0: int_bytes(&proof.t0),
+            t1: int_bytes(&proof.t1),
+            t2: int_bytes(&proof.t2),
+            tau1: int_bytes(&proof.tau1),
+            tau2: int_bytes(&proof.tau2),
+            ipp: Some(IppProof {
+                l: ipp.L.iter().map(int_bytes).collect(),
+                r: ipp.R.iter().map(int_bytes).collect(),
+                a: int_bytes(&ipp.a),
+                b: int_bytes(&ipp.b),
+            }),
+        }
+    }
+}
+
+/// Runs the checks of load_proof
+impl TryFrom<Cuproof> for crate::range_proof::Cuproof {
+    type Error = ProtoError;
+
+    #[allow(non_snake_case)]
+    fn try_from(m: Cuproof) -> Result<Self, ProtoError> {
+        check_version(m.version)?;
+        let ipp = m.ipp.ok_or(ProtoError::MissingField("ipp"))?;
+        check_ipp_lengths(ipp.l.len(), ipp.r.len()).map_err(ProtoError::InvalidProof)?;
+        let A = proof_int(&m.a_commitment, "a_commitment")?;
+        let S = proof_int(&m.s_commitment, "s_commitment")?;
+        let T1 = proof_int(&m.t1_commitment, "t1_commitment")?;
+        let T2 = proof_int(&m.t2_commitment, "t2_commitment")?;
+        for x in [&A, &S, &T1, &T2] { check_header_nonzero(x).map_err(ProtoError::InvalidProof)?; }
+        let ipp_proof = IPPProof {
+            L: ipp.l.iter().map(|x| proof_int(x, "ipp.l")).collect::<Result<_, _>>()?,
+            R: ipp.r.iter().map(|x| proof_int(x, "ipp.r")).collect::<Result<_, _>>()?,
+            a: proof_int(&ipp.a, "ipp.a")?,
+            b: proof_int(&ipp.b, "ipp.b")?,
+        };
+        Ok(crate::range_proof::Cuproof {
+            A, S, T1, T2,
+            tau_x: proof_int(&m.tau_x, "tau_x")?,
+            mu: proof_int(&m.mu, "mu")?,
+            t_hat: proof_int(&m.t_hat, "t_hat")?,
+            C: proof_int(&m.c, "c")?,
+            C_v1: proof_int(&m.c_v1, "c_v1")?,
+            C_v2: proof_int(&m.c_v2, "c_v2")?,
+            t0: proof_int(&m.t0, "t0")?,
+            t1: proof_int(&m.t1, "t1")?,
+            t2: proof_int(&m.t2, "t2")?,
+            tau1: proof_int(&m.tau1, "tau1")?,
+            tau2: proof_int(&m.tau2, "tau2")?,
+            ipp_proof,
+            epoch: m.epoch,
+            statement: None,
+            assist: None,
+        })
+    }
+}
+
+impl From<&crate::setup::Params> for Params {
+    fn from(params: &crate::setup::Params) -> Params {
+        Params {
+            version: PROTO_FORMAT_VERSION,
+            bits: u32::try_from(params.bits()).unwrap_or(u32::MAX),
+            n: int_bytes(params.n()),
+            g: int_bytes(params.g()),
+            h: int_bytes(params.h()),
+            generator_seed: params.generator_seed().unwrap_or_default().to_string(),
+            epoch: params.epoch(),
+            insecure: params.is_insecure(),
+        }
+    }
+}
+
+/// Runs validate_params
+impl TryFrom<Params> for crate::setup::Params {
+    type Error = ProtoError;
+
+    fn try_from(m: Params) -> Result<Self, ProtoError> {
+        check_version(m.version)?;
+        let (n, g, h) = (params_int(&m.n, "n")?, params_int(&m.g, "g")?, params_int(&m.h, "h")?);
+        let seed = Some(m.generator_seed).filter(|seed| !seed.is_empty());
+        let params = crate::setup::Params::new(g, h, n, m.bits as usize).with_generator_seed(seed).with_epoch(m.epoch);
+        let params = if m.insecure { params.mark_insecure() } else { params };
+        validate_params(&params).map_err(ProtoError::InvalidParams)?;
+        Ok(params)
+    }
+}
+
+impl Statement {
+    /// The statement a <= v <= b under `params`
+    pub fn new(a: &BigInt, b: &BigInt, params: &crate::setup::Params) -> Statement {
+        Statement {
+            version: PROTO_FORMAT_VERSION,
+            a: int_bytes(a),
+            b: int_bytes(b),
+            params_fingerprint: params.fingerprint().to_vec(),
+        }
+    }
+
+    /// The bounds (a, b) of a statement made for `params`
+    /// - returns: (a, b), or ParamsMismatch when the fingerprint names other params and EmptyRange for a > b
+    /// - usage: pass to verify::cuproof_verify_with_range together with the proof
+    pub fn bounds(&self, params: &crate::setup::Params) -> Result<(BigInt, BigInt), ProtoError> {
+        check_version(self.version)?;
+        if self.params_fingerprint != params.fingerprint() { return Err(ProtoError::ParamsMismatch); }
+        let (a, b) = (proof_int(&self.a, "a")?, proof_int(&self.b, "b")?);
+        if a > b { return Err(ProtoError::EmptyRange); }
+        Ok((a, b))
+    }
+}
+
+/// Encode a proof as a Cuproof message
+pub fn proof_to_proto(proof: &crate::range_proof::Cuproof) -> Vec<u8> {
+    Cuproof::from(proof).encode_to_vec()
+}
+
+/// Decode a Cuproof message and run the checks of load_proof
+pub fn proof_from_proto(bytes: &[u8]) -> Result<crate::range_proof::Cuproof, ProtoError> {
+    Cuproof::decode(bytes)?.try_into()
+}
+
+/// Encode params as a Params message
+pub fn params_to_proto(params: &crate::setup::Params) -> Vec<u8> {
+    Params::from(params).encode_to_vec()
+}
+
+/// Decode a Params message and run validate_params
+pub fn params_from_proto(bytes: &[u8]) -> Result<crate::setup::Params, ProtoError> {
+    Params::decode(bytes)?.try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_proof::Cuproof as RangeProof;
+    use crate::setup::insecure_test_setup;
+
+    // A small hand-written proof whose encoding is checked in as testdata/proof_v1.pb
+    fn golden_proof() -> RangeProof {
+        let int = |x: i64| BigInt::from(x);
+        RangeProof {
+            A: int(1), S: int(2), T1: int(3), T2: int(4), tau_x: int(0), mu: int(-6), t_hat: int(7),
+            C: int(8), C_v1: int(9), C_v2: int(10), t0: int(11), t1: int(12), t2: int(13), tau1: int(14), tau2: int(0x80),
+            ipp_proof: IPPProof { L: vec![int(16), int(17)], R: vec![int(18), int(19)], a: int(20), b: int(21) },
+            epoch: 2,
+            statement: None,
+            assist: None,
+        }
+    }
+
+    // Purpose: ensure the encoding stays byte-stable and every message round-trips through validation
+    // Params: the golden proof, insecure 512-bit params (also rotated), an honest proof, a statement
+    // Output: the golden encoding equals testdata/proof_v1.pb; decoded values re-encode identically,
+    //         the honest proof verifies and the statement yields its bounds
+    // Usage: `cargo test --features proto -- src::proto`
+    #[test]
+    fn proto_matches_golden_and_round_trips() {
+        let golden = include_bytes!("../testdata/proof_v1.pb");
+        assert_eq!(proof_to_proto(&golden_proof()), golden);
+        let decoded = proof_from_proto(golden).unwrap();
+        assert_eq!((decoded.mu.clone(), decoded.tau_x.clone(), decoded.tau2.clone()), (BigInt::from(-6), BigInt::zero(), BigInt::from(0x80)));
+        assert_eq!(proof_to_proto(&decoded), golden);
+        assert_eq!(Cuproof::decode(&golden[..]).unwrap().encode_to_vec(), golden);
+
+        let params = insecure_test_setup(512);
+        assert_eq!(params_from_proto(&params_to_proto(&params)).unwrap(), params);
+        let rotated = params.rotate_generators("proto");
+        assert_eq!(params_from_proto(&params_to_proto(&rotated)).unwrap(), rotated);
+
+        let (a, b) = (BigInt::from(1), BigInt::from(100));
+        let proof = crate::range_proof::prove(&BigInt::from(42), &crate::util::random_bigint(128), &a, &b, &params).unwrap();
+        let decoded = proof_from_proto(&proof_to_proto(&proof)).unwrap();
+        assert_eq!(proof_to_proto(&decoded), proof_to_proto(&proof));
+
+        let statement = Statement::decode(&Statement::new(&a, &b, &params).encode_to_vec()[..]).unwrap();
+        let (a, b) = statement.bounds(&params).unwrap();
+        assert!(crate::verify::cuproof_verify_with_range(&decoded, &params, &a, &b));
+        assert_eq!(statement.bounds(&rotated).err(), Some(ProtoError::ParamsMismatch));
+        assert_eq!(Statement::new(&b, &a, &params).bounds(&params).err(), Some(ProtoError::EmptyRange));
+    }
+
+    // Purpose: ensure out-of-range and malformed fields fail conversion with a typed error
+    // Params: the golden message with one field changed at a time, broken wire bytes, bad params
+    // Output: the specific ProtoError for each; unknown fields are skipped as protobuf requires
+    // Usage: `cargo test --features proto -- src::proto`
+    #[test]
+    fn proto_rejects_out_of_range_fields() {
+        let golden = Cuproof::decode(&include_bytes!("../testdata/proof_v1.pb")[..]).unwrap();
+        let convert = |edit: &dyn Fn(&mut Cuproof)| {
+            let mut m = golden.clone();
+            edit(&mut m);
+            RangeProof::try_from(m).err()
+        };
+        assert_eq!(convert(&|m| m.version = 2), Some(ProtoError::UnsupportedVersion(2)));
+        assert_eq!(convert(&|m| m.ipp = None), Some(ProtoError::MissingField("ipp")));
+        assert_eq!(convert(&|m| m.a_commitment.clear()), Some(ProtoError::InvalidProof("zero scalar in header")));
+        assert_eq!(convert(&|m| m.mu = vec![0x00, 0x05]), Some(ProtoError::NonCanonicalInteger("mu")));
+        assert_eq!(convert(&|m| m.mu = vec![0xff, 0xfa]), Some(ProtoError::NonCanonicalInteger("mu")));
+        assert_eq!(convert(&|m| m.t0 = vec![0x00]), Some(ProtoError::NonCanonicalInteger("t0")));
+        assert_eq!(convert(&|m| m.t_hat = vec![0x01; crate::util::MAX_PROOF_INT_BYTES + 1]), Some(ProtoError::InvalidProof("integer exceeds MAX_PROOF_INT_BYTES")));
+        assert_eq!(
+            convert(&|m| m.ipp.as_mut().unwrap().l.push(vec![0x01])),
+            Some(ProtoError::InvalidProof("L and R length mismatch"))
+        );
+        assert_eq!(
+            convert(&|m| { let ipp = m.ipp.as_mut().unwrap(); ipp.l = vec![vec![1]; 65]; ipp.r = vec![vec![1]; 65]; }),
+            Some(ProtoError::InvalidProof("too many inner-product rounds"))
+        );
+
+        let bytes = golden.encode_to_vec();
+        // unknown field 99 (varint) before the rest is skipped
+        let mut extra = vec![0x98, 0x06, 0x01];
+        extra.extend_from_slice(&bytes);
+        assert!(proof_from_proto(&extra).is_ok());
+        let malformed = |bytes: &[u8]| match proof_from_proto(bytes) {
+            Err(ProtoError::Decode(e)) => e.to_string(),
+            other => panic!("expected a decode error, got {:?}", other.err()),
+        };
+        assert!(malformed(&bytes[..bytes.len() - 1]).ends_with("Cuproof.ipp: buffer underflow"));
+        // version sent as a length-delimited field
+        assert!(malformed(&[0x0a, 0x00]).ends_with("invalid wire type: LengthDelimited (expected Varint)"));
+        assert!(malformed(&[0x08, 0xff]).ends_with("Cuproof.version: invalid varint"));
+        assert!(malformed(&[0x0b]).contains("invalid wire type: StartGroup"));
+        assert!(malformed(&[0x00, 0x00]).ends_with("invalid tag value: 0"));
+
+        let mut params = Params::from(&insecure_test_setup(512));
+        params.g.clear();
+        assert_eq!(crate::setup::Params::try_from(params.clone()).err(), Some(ProtoError::InvalidParams(ParamsError::GeneratorOutOfRange("g"))));
+        params.g = vec![0xff];
+        assert_eq!(crate::setup::Params::try_from(params).err(), Some(ProtoError::Malformed("params integers must be non-negative")));
+    }
+}
@@ -1,8 +1,33 @@
 use crate::{util::*, lagrange::*, commitment::*, fiat_shamir::*};
+use crate::group::Group;
+use crate::setup::{validate_params, Params, ParamsError, MIN_SECURE_MODULUS_BITS};
+#[cfg(feature = "tracing")]
+use crate::setup::FingerprintHex;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 use num_bigint::BigInt;
-use num_traits::Zero;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+use crate::poe::VerifierAssist;
+use crate::scalar::ScalarBound;
+use crate::secret::Secret;
+use crate::timing::{phase, Phase};
+use crate::trace::traced;
+
+pub mod math;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::serde_support::IppProofWire", try_from = "crate::serde_support::IppProofWire"))]
 pub struct IPPProof {
 	pub L: Vec<BigInt>,  // Left commitments at each level
 	pub R: Vec<BigInt>,  // Right commitments at each level
@@ -11,23 +36,118 @@ pub struct IPPProof {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cuproof {
+	#[cfg_attr(feature = "serde", serde(deserialize_with = "crate::serde_support::bigint::deserialize_nonzero", serialize_with = "crate::serde_support::bigint::serialize"))]
 	pub A: BigInt,
+	#[cfg_attr(feature = "serde", serde(deserialize_with = "crate::serde_support::bigint::deserialize_nonzero", serialize_with = "crate::serde_support::bigint::serialize"))]
 	pub S: BigInt,
+	#[cfg_attr(feature = "serde", serde(deserialize_with = "crate::serde_support::bigint::deserialize_nonzero", serialize_with = "crate::serde_support::bigint::serialize"))]
 	pub T1: BigInt,
+	#[cfg_attr(feature = "serde", serde(deserialize_with = "crate::serde_support::bigint::deserialize_nonzero", serialize_with = "crate::serde_support::bigint::serialize"))]
 	pub T2: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub tau_x: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub mu: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub t_hat: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub C: BigInt,  // Commitment to value v
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub C_v1: BigInt,  // Commitment to v1 = 4v - 4a + 1
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub C_v2: BigInt,  // Commitment to v2 = 4b - 4v + 1
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub t0: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub t1: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub t2: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub tau1: BigInt,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bigint"))]
 	pub tau2: BigInt,
 	pub ipp_proof: IPPProof,  // Inner Product Argument proof
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub epoch: u64,  // Generator epoch of the params the proof was made under
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub statement: Option<Statement>,  // What the proof claims; None for proof files from before statements were recorded and for the other encodings
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub assist: Option<VerifierAssist>,  // Proofs of exponentiation for verify::cuproof_verify_fast; None unless added with Cuproof::with_verifier_assist, and in the other encodings
+}
+
+/// What a proof claims, saved with it in proof files: the value committed in `commitment` lies in
+/// [a, b]. `bits` is the IPP vector dimension the proof was made with (64 for prove),
+/// `label` the application context the prover attached (empty unless set with Cuproof::with_label)
+/// and `params_fingerprint` the Group::fingerprint of the params
+///
+/// verify cross-checks every field it can against the proof and params. The Fiat-Shamir
+/// transcript hashes the commitment but not the bounds or label, so those are claims of whoever
+/// wrote the file until a verifier compares them with its own expectations
+/// (verify::verify_statement)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+	pub commitment: BigInt,
+	pub a: BigInt,
+	pub b: BigInt,
+	pub bits: usize,
+	pub label: String,
+	pub params_fingerprint: [u8; 32],
+}
+
+impl Statement {
+	/// The statement of a proof with commitment `commitment` over [a, b], made with `bits`-dimensional vectors under `params`
+	pub fn new<G: Group>(commitment: &BigInt, a: &BigInt, b: &BigInt, bits: usize, params: &G) -> Statement {
+		Statement {
+			commitment: commitment.clone(),
+			a: a.clone(),
+			b: b.clone(),
+			bits,
+			label: String::new(),
+			params_fingerprint: params.fingerprint(),
+		}
+	}
+
+	/// The same statement with another context label
+	pub fn with_label(mut self, label: &str) -> Statement {
+		self.label = label.to_string();
+		self
+	}
+}
+
+impl Cuproof {
+	/// Set the context label of the embedded statement; proofs without a statement are returned unchanged
+	/// - usage: `prove(..)?.with_label("payroll/2026")`, before saving the proof
+	pub fn with_label(mut self, label: &str) -> Cuproof {
+		self.statement = self.statement.map(|statement| statement.with_label(label));
+		self
+	}
+
+	/// Reduce every group element into [0, n): A, S, T1, T2, C, C_v1, C_v2, the inner-product L
+	/// and R, the statement's commitment and the assist's elements
+	/// - params: n the modulus of the params the proof is for
+	/// - usage: before comparing, hashing or caching proofs. C and C + n are one group element but
+	///   two integers, so a proof and its variant differ in to_bytes, ==, Hash and proof_id until
+	///   both are canonicalized. The scalars need no step: a BigInt holds each integer in one form,
+	///   which to_bytes writes in one way
+	pub fn canonicalize(&mut self, n: &BigInt) {
+		let elements = [&mut self.A, &mut self.S, &mut self.T1, &mut self.T2, &mut self.C, &mut self.C_v1, &mut self.C_v2];
+		let rounds = self.ipp_proof.L.iter_mut().chain(self.ipp_proof.R.iter_mut());
+		let statement = self.statement.as_mut().map(|statement| &mut statement.commitment);
+		let assist = self.assist.iter_mut().flat_map(|assist| [&mut assist.q1, &mut assist.q2]);
+		for element in elements.into_iter().chain(rounds).chain(statement).chain(assist) {
+			if element.is_negative() || &*element >= n { *element = element.mod_floor(n); }
+		}
+	}
+
+	/// The scalars verify bounds by scalar::ScalarBound, named as in the proof files
+	pub fn scalars(&self) -> [(&'static str, &BigInt); 10] {
+		[
+			("t0", &self.t0), ("t1", &self.t1), ("t2", &self.t2), ("tau1", &self.tau1), ("tau2", &self.tau2),
+			("t_hat", &self.t_hat), ("tau_x", &self.tau_x), ("mu", &self.mu), ("ipp a", &self.ipp_proof.a), ("ipp b", &self.ipp_proof.b),
+		]
+	}
 }
 
 // Interactive Proof Protocol Structures
@@ -55,9 +175,7 @@ pub struct ProverState {
 
 #[derive(Clone)]
 pub struct VerifierState {
-	pub g: BigInt,
-	pub h: BigInt,
-	pub n: BigInt,
+	pub params: Params,
 	pub A: BigInt,
 	pub S: BigInt,
 	pub T1: BigInt,
@@ -67,94 +185,165 @@ pub struct VerifierState {
 	pub x: BigInt,
 }
 
+/// Size of the prover's blinding scalars
+pub const BLINDING_BITS: usize = 256;
+
+/// Draw a blinding scalar uniform over [0, 2^BLINDING_BITS) from `rng`
+pub fn random_blinding<R: RngCore + CryptoRng>(rng: &mut R) -> BigInt {
+	random_below(&(BigInt::one() << BLINDING_BITS), rng)
+}
+
+/// Why a proof could not be produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProveError {
+	/// a > b, so no value lies in the range
+	EmptyRange,
+	/// v lies outside [a, b]; a proof would not verify
+	ValueOutOfRange,
+	/// the IPP vector dimension is zero, a comparison width is zero or above
+	/// comparison::MAX_COMPARISON_BITS, or a batch of openings has as many openings as this and
+	/// another number of commitments, or more than openings::MAX_BATCH_OPENINGS
+	InvalidDimension(usize),
+	/// the commitment a proof was requested for does not open to v under the given blinding
+	CommitmentMismatch,
+	/// entry `index` of an aggregated proof cannot be proved
+	Aggregate { index: usize, error: Box<ProveError> },
+	/// a challenge bundle is for another offline state, statement or params, or its challenges
+	/// are not those of its first message (offline::offline_finish)
+	ChallengeMismatch,
+	/// the offline state was already used to finish a proof
+	StateSpent,
+	/// a comparison proof was asked for v1 < v2 but v1 >= v2 (comparison::prove_less_than)
+	NotLessThan,
+	/// v2 - v1 does not fit the bit width of a comparison proof
+	DifferenceTooWide { bits: u32 },
+}
+
+impl fmt::Display for ProveError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ProveError::EmptyRange => write!(f, "empty range: the lower bound exceeds the upper bound"),
+			ProveError::ValueOutOfRange => write!(f, "value lies outside the range"),
+			ProveError::InvalidDimension(d) => write!(f, "invalid proof dimension {}", d),
+			ProveError::CommitmentMismatch => write!(f, "the commitment does not open to the value under the given blinding"),
+			ProveError::Aggregate { index, error } => write!(f, "statement {}: {}", index, error),
+			ProveError::ChallengeMismatch => write!(f, "the challenge bundle does not match the prover state"),
+			ProveError::StateSpent => write!(f, "the prover state was already used; run prove-offline-commit again"),
+			ProveError::NotLessThan => write!(f, "the first value is not less than the second"),
+			ProveError::DifferenceTooWide { bits } => write!(f, "the difference of the values does not fit in {} bits", bits),
+		}
+	}
+}
+
+impl core::error::Error for ProveError {}
+
+/// Reject inputs the prover cannot prove, before any square decomposition runs
+pub(crate) fn check_prove_inputs(v: &BigInt, a: &BigInt, b: &BigInt, dimension: usize) -> Result<(), ProveError> {
+	if a > b { return Err(ProveError::EmptyRange); }
+	if v < a || v > b { return Err(ProveError::ValueOutOfRange); }
+	if dimension == 0 { return Err(ProveError::InvalidDimension(dimension)); }
+	Ok(())
+}
+
+/// Six integers whose squares sum to v1 (first three) and v2 (last three)
+pub(crate) fn square_decomposition(v1: &BigInt, v2: &BigInt) -> Result<Vec<BigInt>, ProveError> {
+	let d1 = find_3_squares(v1).ok_or(ProveError::ValueOutOfRange)?;
+	let d2 = find_3_squares(v2).ok_or(ProveError::ValueOutOfRange)?;
+	Ok([d1, d2].concat())
+}
+
 // Helper function to compute commitment to a value
-fn commit_value(g: &BigInt, h: &BigInt, value: &BigInt, n: &BigInt) -> (BigInt, BigInt) {
-	let r = random_bigint(256);
-	let commitment = pedersen_commit(g, h, value, &r, n);
+pub(crate) fn commit_value<G: Group, R: RngCore + CryptoRng>(params: &G, value: &BigInt, rng: &mut R) -> (BigInt, BigInt) {
+	let r = random_blinding(rng);
+	let commitment = params.commit(value, &r);
 	(commitment, r)
 }
 
-// Full Inner Product Argument implementation
-fn inner_product_argument_recursive(
-	l_vec: &[BigInt], 
-	r_vec: &[BigInt], 
-	g: &BigInt, 
-	h: &BigInt, 
-	n: &BigInt,
-	level: usize
+// Full Inner Product Argument implementation: halves l and r in place, round by round, and
+// returns L and R deepest round first, the order the verifier folds them in
+fn inner_product_argument_in_place<G: Group, R: RngCore + CryptoRng>(
+	mut l_vec: Vec<BigInt>,
+	mut r_vec: Vec<BigInt>,
+	params: &G,
+	rng: &mut R,
 ) -> (BigInt, BigInt, Vec<BigInt>, Vec<BigInt>) {
-	if l_vec.len() == 1 {
-		return (l_vec[0].clone(), r_vec[0].clone(), vec![], vec![]);
+	let rounds = usize::BITS as usize - l_vec.len().leading_zeros() as usize;
+	let mut L_vec = Vec::with_capacity(rounds);
+	let mut R_vec = Vec::with_capacity(rounds);
+	let n = params.challenge_modulus();
+	while l_vec.len() > 1 {
+		let mid = l_vec.len() / 2;
+		let (l_left, l_right) = l_vec.split_at_mut(mid);
+		let (r_left, r_right) = r_vec.split_at_mut(mid);
+
+		let c_L = inner_product(l_left, r_right);
+		let c_R = inner_product(l_right, r_left);
+
+		// Create commitments to c_L and c_R
+		let r_L = random_blinding(rng);
+		let r_R = random_blinding(rng);
+		let L = params.commit(&c_L, &r_L);
+		let R = params.commit(&c_R, &r_R);
+
+		let y = fiat_shamir(&[&L, &R]) % n;
+
+		// l' = l_left + y * l_right ; r' = r_right + y * r_left, written over the left halves
+		for (l, r) in l_left.iter_mut().zip(l_right.iter()) {
+			*l += &y * r;
+		}
+		for (l, r) in r_left.iter_mut().zip(r_right.iter()) {
+			*l *= &y;
+			*l += r;
+		}
+		l_vec.truncate(mid);
+		r_vec.truncate(mid);
+
+		L_vec.push(L);
+		R_vec.push(R);
 	}
-	
-	
-	let mid = l_vec.len() / 2;
-	let l_left = &l_vec[..mid];
-	let l_right = &l_vec[mid..];
-	let r_left = &r_vec[..mid];  // Fixed: use r_vec instead of l_vec
-	let r_right = &r_vec[mid..]; // Fixed: use r_vec instead of l_vec
-	
-	let c_L = inner_product(l_left, r_right);
-	let c_R = inner_product(l_right, r_left);
-	
-	// Create commitments to c_L and c_R
-	let r_L = random_bigint(256);
-	let r_R = random_bigint(256);
-	let L = pedersen_commit(g, h, &c_L, &r_L, n);
-	let R = pedersen_commit(g, h, &c_R, &r_R, n);
-	
-	let y = fiat_shamir(&[&L, &R]) % n;
-	
-	let l_new: Vec<BigInt> = l_left.iter().zip(l_right.iter())
-		.map(|(l, r)| l + &(&y * r))
-		.collect();
-	let r_new: Vec<BigInt> = r_left.iter().zip(r_right.iter())
-		.map(|(l, r)| r + &(&y * l))
-		.collect();
-	
-	let (a, b, mut L_vec, mut R_vec) = inner_product_argument_recursive(&l_new, &r_new, g, h, n, level + 1);
-	
-	// Add current level commitments
-	L_vec.push(L);
-	R_vec.push(R);
-	
-	(a, b, L_vec, R_vec)
+	L_vec.reverse();
+	R_vec.reverse();
+
+	(core::mem::take(&mut l_vec[0]), core::mem::take(&mut r_vec[0]), L_vec, R_vec)
 }
 
 // Interactive Proof Protocol Implementation
-pub fn interactive_prove_step1(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, g: &BigInt, h: &BigInt, n: &BigInt) -> (ProverState, BigInt, BigInt) {
+#[cfg(feature = "std")]
+pub fn interactive_prove_step1(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &Params) -> Result<(ProverState, BigInt, BigInt), ProveError> {
 	// Fixed optimal dimension for interactive protocol
 	let dimension = 16;
+	check_prove_inputs(v, a, b, dimension)?;
 	
 	// Step 1: Calculate v1 and v2
 	let v1 = 4 * v - 4 * a + 1;
 	let v2 = 4 * b - 4 * v + 1;
 
 	// Step 2: Find six integers d = (d1, d2, d3, d4, d5, d6) using Lagrange's theorem
-	let d1 = find_3_squares(&v1);  // v1 = d1² + d2² + d3²
-	let d2 = find_3_squares(&v2);  // v2 = d4² + d5² + d6²
-	let d_base = [d1, d2].concat(); // length 6
+	// v1 = d1² + d2² + d3², v2 = d4² + d5² + d6²
+	let d_base = square_decomposition(&v1, &v2)?; // length 6
 
 	// Expand d to the fixed dimension by repeating the base pattern
 	let d = (0..dimension)
 		.map(|i| d_base[i % d_base.len()].clone())
 		.collect::<Vec<_>>();
 
+	let rng = &mut OsRng;
+
 	// Step 3: Create Pedersen commitment A for values d with random value α
-	let alpha = random_bigint(256);
-	let A = pedersen_commit(g, h, &d.iter().sum::<BigInt>(), &alpha, n);
+	let alpha = random_blinding(rng);
+	let A = commit(params, &d.iter().sum::<BigInt>(), &alpha);
 
 	// Step 4: Create commitment S using values sL and sR
-	let rho = random_bigint(256);
-	let sL = (0..dimension).map(|_| random_bigint(256)).collect::<Vec<_>>();
-	let sR = (0..dimension).map(|_| random_bigint(256)).collect::<Vec<_>>();
+	let rho = random_blinding(rng);
+	let sL = (0..dimension).map(|_| random_blinding(rng)).collect::<Vec<_>>();
+	let sR = (0..dimension).map(|_| random_blinding(rng)).collect::<Vec<_>>();
 	let sum_s = sL.iter().sum::<BigInt>() + sR.iter().sum::<BigInt>();
-	let S = pedersen_commit(g, h, &sum_s, &rho, n);
+	let S = commit(params, &sum_s, &rho);
 
 	// Create commitments to v, v1, v2
-	let (C, _r_v) = commit_value(g, h, v, n);
-	let (C_v1, _r_v1) = commit_value(g, h, &v1, n);
-	let (C_v2, _r_v2) = commit_value(g, h, &v2, n);
+	let (C, _r_v) = commit_value(params, v, rng);
+	let (C_v1, _r_v1) = commit_value(params, &v1, rng);
+	let (C_v2, _r_v2) = commit_value(params, &v2, rng);
 
 	// Calculate l0 and r0 for later use
 	let l0 = d.iter().map(|di| di.clone()).collect::<Vec<_>>();
@@ -166,42 +355,43 @@ pub fn interactive_prove_step1(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, g
 		+ r0.iter().zip(&sL).map(|(r0i, sLi)| r0i * sLi).sum::<BigInt>();
 	let t2 = inner_product(&sL, &sR);
 
-	let tau1 = random_bigint(256);
-	let tau2 = random_bigint(256);
+	let tau1 = random_blinding(rng);
+	let tau2 = random_blinding(rng);
 
 	let prover_state = ProverState {
 		v: v.clone(), a: a.clone(), b: b.clone(), r: r.clone(),
 		alpha, rho, sL, sR, d, v1, v2, l0, r0, t0, t1, t2, tau1, tau2,
 	};
 
-	(prover_state, A, S)
+	Ok((prover_state, A, S))
 }
 
-pub fn interactive_prove_step2(prover_state: &ProverState, y: &BigInt, z: &BigInt, g: &BigInt, h: &BigInt, n: &BigInt) -> (BigInt, BigInt) {
+pub fn interactive_prove_step2(prover_state: &ProverState, y: &BigInt, z: &BigInt, params: &Params) -> (BigInt, BigInt) {
 	// Step 7: Use challenges y and z to compute vectors l(x) and r(x)
-	let l0 = prover_state.l0.iter().map(|di| z * di + y).collect::<Vec<_>>();
-	let r0 = prover_state.r0.iter().map(|di| z * di + y).collect::<Vec<_>>();
+	let l0 = math::l0(&prover_state.l0, y, z);
+	let r0 = math::l0(&prover_state.r0, y, z);
 
 	// Step 8: Calculate T1 and T2 as Pedersen commitments for coefficients t1 and t2
-	let T1 = pedersen_commit(g, h, &prover_state.t1, &prover_state.tau1, n);
-	let T2 = pedersen_commit(g, h, &prover_state.t2, &prover_state.tau2, n);
+	let T1 = commit(params, &prover_state.t1, &prover_state.tau1);
+	let T2 = commit(params, &prover_state.t2, &prover_state.tau2);
 
 	(T1, T2)
 }
 
-pub fn interactive_prove_step3(prover_state: &ProverState, x: &BigInt, g: &BigInt, h: &BigInt, n: &BigInt) -> (BigInt, BigInt, BigInt, BigInt, BigInt) {
+#[cfg(feature = "std")]
+pub fn interactive_prove_step3(prover_state: &ProverState, x: &BigInt, params: &Params) -> (BigInt, BigInt, BigInt, BigInt, BigInt) {
+	let rng = &mut OsRng;
+
 	// Step 11: Calculate final values
-	let l_vec = prover_state.l0.iter().zip(&prover_state.sL)
-		.map(|(l0i, sLi)| l0i + &(sLi * x)).collect::<Vec<_>>();
-	let r_vec = prover_state.r0.iter().zip(&prover_state.sR)
-		.map(|(r0i, sRi)| r0i + &(sRi * x)).collect::<Vec<_>>();
+	let l_vec = math::vector_poly_eval(prover_state.l0.clone(), &prover_state.sL, x);
+	let r_vec = math::vector_poly_eval(prover_state.r0.clone(), &prover_state.sR, x);
 
 	let t_hat = inner_product(&l_vec, &r_vec);
 	let mu = &prover_state.alpha + &(&prover_state.rho * x);
 	let tau_x = &prover_state.tau2 * x * x + &prover_state.tau1 * x;
 
 	// Generate IPP proof for l_vec and r_vec
-	let (a_final, b_final, L_vec, R_vec) = inner_product_argument_recursive(&l_vec, &r_vec, g, h, n, 0);
+	let (a_final, b_final, L_vec, R_vec) = inner_product_argument_in_place(l_vec, r_vec, params, rng);
 	
 			let ipp_proof = IPPProof {
 			L: L_vec,
@@ -211,9 +401,10 @@ pub fn interactive_prove_step3(prover_state: &ProverState, x: &BigInt, g: &BigIn
 		};
 
 	// Create final proof
-	let C = pedersen_commit(g, h, &prover_state.v, &prover_state.r, n);
-	let C_v1 = pedersen_commit(g, h, &prover_state.v1, &random_bigint(256), n);
-	let C_v2 = pedersen_commit(g, h, &prover_state.v2, &random_bigint(256), n);
+	let C = commit(params, &prover_state.v, &prover_state.r);
+	let statement = Statement::new(&C, &prover_state.a, &prover_state.b, prover_state.d.len(), params);
+	let C_v1 = commit(params, &prover_state.v1, &random_blinding(rng));
+	let C_v2 = commit(params, &prover_state.v2, &random_blinding(rng));
 
 	let final_proof = Cuproof {
 		A: BigInt::from(0), // Will be set by caller
@@ -232,21 +423,25 @@ pub fn interactive_prove_step3(prover_state: &ProverState, x: &BigInt, g: &BigIn
 		tau1: prover_state.tau1.clone(),
 		tau2: prover_state.tau2.clone(),
 		ipp_proof,
+		epoch: params.epoch(),
+		statement: Some(statement),
+		assist: None,
 	};
 
 	(t_hat, mu, tau_x, a_final, b_final)
 }
 
 // Interactive Verification Protocol
-pub fn interactive_verify_step1(g: &BigInt, h: &BigInt, n: &BigInt) -> (VerifierState, BigInt, BigInt) {
+#[cfg(feature = "std")]
+pub fn interactive_verify_step1(params: &Params) -> (VerifierState, BigInt, BigInt) {
 	// Step 6: Verifier chooses natural values y', z' and computes y = g^(y'), z = g^(z')
-	let y_prime = random_bigint(256);
-	let z_prime = random_bigint(256);
-	let y = g.modpow(&y_prime, n);
-	let z = g.modpow(&z_prime, n);
+	let y_prime = random_blinding(&mut OsRng);
+	let z_prime = random_blinding(&mut OsRng);
+	let y = params.g_pow(&y_prime);
+	let z = params.g_pow(&z_prime);
 
 	let verifier_state = VerifierState {
-		g: g.clone(), h: h.clone(), n: n.clone(),
+		params: params.clone(),
 		A: BigInt::from(0), S: BigInt::from(0), T1: BigInt::from(0), T2: BigInt::from(0),
 		y: y.clone(), z: z.clone(), x: BigInt::from(0),
 	};
@@ -266,15 +461,16 @@ pub fn interactive_verify_step3(verifier_state: &mut VerifierState, T1: &BigInt,
 	verifier_state.T2 = T2.clone();
 }
 
-pub fn interactive_verify_step4(verifier_state: &mut VerifierState, g: &BigInt, n: &BigInt) -> BigInt {
+#[cfg(feature = "std")]
+pub fn interactive_verify_step4(verifier_state: &mut VerifierState) -> BigInt {
 	// Step 10: Verifier chooses natural value x' and computes x = g^(x')
-	let x_prime = random_bigint(256);
-	let x = g.modpow(&x_prime, n);
+	let x_prime = random_blinding(&mut OsRng);
+	let x = verifier_state.params.g_pow(&x_prime);
 	verifier_state.x = x.clone();
 	x
 }
 
-pub fn interactive_verify_final(verifier_state: &VerifierState, t_hat: &BigInt, mu: &BigInt, tau_x: &BigInt, a_final: &BigInt, b_final: &BigInt, g: &BigInt, h: &BigInt, n: &BigInt) -> bool {
+pub fn interactive_verify_final(verifier_state: &VerifierState, t_hat: &BigInt, mu: &BigInt, tau_x: &BigInt, a_final: &BigInt, b_final: &BigInt) -> bool {
 	// Step 12: Verifier performs verification checks
 	
 	// Check 1: Verify that commitments A and S are not zero (basic validation)
@@ -312,71 +508,269 @@ pub fn interactive_verify_final(verifier_state: &VerifierState, t_hat: &BigInt,
 }
 
 // Original non-interactive proof (kept for compatibility)
-pub fn cuproof_prove_with_dimension(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, g: &BigInt, h: &BigInt, n: &BigInt, dimension: usize) -> Cuproof {
-	let v1 = 4 * v - 4 * a + 1;
-	let v2 = 4 * b - 4 * v + 1;
+#[cfg(feature = "std")]
+pub fn cuproof_prove_with_dimension<G: Group>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, dimension: usize) -> Result<Cuproof, ProveError> {
+	prove_with_rng(v, r, a, b, params, dimension, &mut OsRng)
+}
+
+/// cuproof_prove_with_dimension drawing every blinding but r from `rng` instead of the OS
+/// - returns: the proof, whose C commits v under r so the caller can open it later, or ProveError
+///   when a > b, v is outside [a, b] or dimension is 0
+/// - usage: the single place prover randomness enters, so a seeded CSPRNG reproduces a proof exactly
+pub fn prove_with_rng<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, dimension: usize, rng: &mut R) -> Result<Cuproof, ProveError> {
+	prove_inner(v, r, a, b, params, dimension, &mut FiatShamir::new(), rng)
+}
+
+/// Former name of prove_with_rng, kept for one release
+#[deprecated(note = "renamed to `prove_with_rng`")]
+pub fn cuproof_prove_with_rng<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, dimension: usize, rng: &mut R) -> Result<Cuproof, ProveError> {
+	prove_with_rng(v, r, a, b, params, dimension, rng)
+}
+
+/// Prove a <= v <= b for an existing commitment = g^v * h^r, e.g. one written by `cuproof commit`
+/// - params: commitment and its opening (v, r), range [a, b], params the commitment was made under
+/// - returns: a proof whose C (and statement commitment) is `commitment`; CommitmentMismatch when
+///   (v, r) does not open it, or the errors of prove
+/// - usage: commit to v first, publish the commitment, prove its range later without changing it
+#[cfg(feature = "std")]
+pub fn cuproof_prove_for_commitment<G: Group>(commitment: &BigInt, v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G) -> Result<Cuproof, ProveError> {
+	cuproof_prove_for_commitment_with_rng(commitment, v, r, a, b, params, &mut OsRng)
+}
+
+/// cuproof_prove_for_commitment drawing every blinding from `rng` instead of the OS, as
+/// prove_with_rng does for fresh commitments
+pub fn cuproof_prove_for_commitment_with_rng<G: Group, R: RngCore + CryptoRng>(commitment: &BigInt, v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, rng: &mut R) -> Result<Cuproof, ProveError> {
+	if params.commit(v, r) != *commitment { return Err(ProveError::CommitmentMismatch); }
+	prove_inner(v, r, a, b, params, 64, &mut FiatShamir::new(), rng)
+}
+
+/// Domain separator of the seeds synthetic_nonce_rng derives
+const SYNTHETIC_NONCE_DOMAIN: &[u8] = b"cuproof synthetic nonce v1";
+
+/// The prover randomness of cuproof_prove_deterministic: a ChaCha20 stream seeded with SHA-256 over
+/// SYNTHETIC_NONCE_DOMAIN, the params fingerprint and the length-prefixed two's complement bytes of
+/// a, b, v and r
+/// - returns: the same stream for the same statement and witness, an unrelated one when any differs
+/// - usage: pass to prove_with_rng or cuproof_prove_for_commitment_with_rng. The nonces are
+///   as secret as (v, r), so r must itself be secret and random: a guessable r exposes v through C
+///   whatever the prover's randomness
+pub fn synthetic_nonce_rng<G: Group>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G) -> ChaCha20Rng {
+	let mut hasher = Sha256::new();
+	hasher.update(SYNTHETIC_NONCE_DOMAIN);
+	hasher.update(params.fingerprint());
+	for x in [a, b, v, r] {
+		let bytes = Zeroizing::new(x.to_signed_bytes_be());
+		hasher.update((bytes.len() as u64).to_be_bytes());
+		hasher.update(&bytes[..]);
+	}
+	let seed = Zeroizing::new(<[u8; 32]>::from(hasher.finalize()));
+	ChaCha20Rng::from_seed(*seed)
+}
+
+/// prove with its randomness derived from the witness (synthetic_nonce_rng)
+/// - returns: byte-identical proofs for identical inputs, or the errors of prove
+/// - usage: reproducing a proof from its inputs alone, e.g. to confirm a reported prover bug
+pub fn cuproof_prove_deterministic<G: Group>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G) -> Result<Cuproof, ProveError> {
+	prove_inner(v, r, a, b, params, 64, &mut FiatShamir::new(), &mut synthetic_nonce_rng(v, r, a, b, params))
+}
+
+/// prove for a verifier that issued `nonce`: the proof verifies only under verify::verify_with_nonce
+/// with the same bytes, so it cannot be replayed into a session with another nonce
+/// - params: as prove, plus the verifier's nonce; an empty nonce gives an ordinary proof
+/// - returns: the proof or the errors of prove
+/// - usage: fetch a fresh nonce per session, e.g. `prove_with_nonce(&v, &r, &a, &b, &params, &nonce)`;
+///   prove_with_context binds a context label as well
+#[cfg(feature = "std")]
+pub fn prove_with_nonce<G: Group>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, nonce: &[u8]) -> Result<Cuproof, ProveError> {
+	prove_with_nonce_and_rng(v, r, a, b, params, nonce, &mut OsRng)
+}
+
+/// prove_with_nonce drawing every blinding but r from `rng`, as prove_with_rng does at dimension 64
+pub fn prove_with_nonce_and_rng<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, nonce: &[u8], rng: &mut R) -> Result<Cuproof, ProveError> {
+	prove_with_context_and_rng(v, r, a, b, params, "", nonce, rng)
+}
+
+/// prove_with_nonce under a context label: the label, then the nonce, are absorbed into the
+/// transcript (FiatShamir::with_context) and the label is set in the embedded statement
+/// - returns: the proof, which verify_with_nonce and verify_statement_with_nonce accept only with
+///   this label and nonce, or the errors of prove. With an empty nonce the label is set but not
+///   bound, as Cuproof::with_label sets it
+/// - usage: `prove_with_context(&v, &r, &a, &b, &params, "withdraw", &nonce)`
+#[cfg(feature = "std")]
+pub fn prove_with_context<G: Group>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, label: &str, nonce: &[u8]) -> Result<Cuproof, ProveError> {
+	prove_with_context_and_rng(v, r, a, b, params, label, nonce, &mut OsRng)
+}
+
+/// prove_with_context drawing every blinding but r from `rng`, as prove_with_rng does at dimension 64
+#[allow(clippy::too_many_arguments)]
+pub fn prove_with_context_and_rng<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, label: &str, nonce: &[u8], rng: &mut R) -> Result<Cuproof, ProveError> {
+	Ok(prove_inner(v, r, a, b, params, 64, &mut FiatShamir::with_context(label, nonce), rng)?.with_label(label))
+}
+
+/// prove_with_rng at dimension 64 drawing y, z and x from `transcript` instead of FiatShamir
+/// - returns: the proof, which verify::verify_with_transcript accepts under a transcript that gives
+///   the same challenges, or the errors of prove
+/// - usage: protocol unit tests with testing::MockTranscript, which scripts the challenges
+pub fn prove_with_transcript<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, transcript: &mut dyn Transcript, rng: &mut R) -> Result<Cuproof, ProveError> {
+	prove_inner(v, r, a, b, params, 64, transcript, rng)
+}
+
+/// The prover inside a `prove` span, ending with an event carrying the proof id and duration
+#[allow(clippy::too_many_arguments)]
+fn prove_inner<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, dimension: usize, transcript: &mut dyn Transcript, rng: &mut R) -> Result<Cuproof, ProveError> {
+	traced!(
+		tracing::debug_span!("prove", bits = dimension, params = %FingerprintHex(params.fingerprint())),
+		prove_unlogged(v, r, a, b, params, dimension, transcript, rng),
+		|proof, elapsed_ms| match proof {
+			Ok(proof) => tracing::debug!(proof_id = %proof_id(proof), elapsed_ms, "proof created"),
+			Err(e) => tracing::debug!(error = %e, elapsed_ms, "proof refused"),
+		}
+	)
+}
+
+/// The prover; C commits v under `r`, every other blinding comes from `rng`, the challenges from `transcript`
+#[allow(clippy::too_many_arguments)]
+fn prove_unlogged<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, dimension: usize, transcript: &mut dyn Transcript, rng: &mut R) -> Result<Cuproof, ProveError> {
+	let round = prove_first_round(v, r, a, b, params, dimension, rng)?;
+	let (y, z) = round.challenges(params, transcript);
+	Ok(round.finish(a, b, &y, &z, params, transcript, rng))
+}
+
+/// The prover's first message (A, S and the commitments to v, v1 and v2) with the secrets behind
+/// it, up to the challenges y and z; the secrets are wiped on drop (see secret)
+#[derive(Clone)]
+#[allow(non_snake_case)]
+pub(crate) struct FirstRound {
+	pub d: Secret<Vec<BigInt>>,
+	pub alpha: Secret<BigInt>,
+	pub rho: Secret<BigInt>,
+	pub sL: Secret<Vec<BigInt>>,
+	pub sR: Secret<Vec<BigInt>>,
+	pub A: BigInt,
+	pub S: BigInt,
+	pub C: BigInt,
+	pub C_v1: BigInt,
+	pub C_v2: BigInt,
+}
+
+/// The first half of the prover: decompose v1 and v2 into squares and commit; C commits v under
+/// `r`, every other blinding comes from `rng`
+pub(crate) fn prove_first_round<G: Group, R: RngCore + CryptoRng>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G, dimension: usize, rng: &mut R) -> Result<FirstRound, ProveError> {
+	check_prove_inputs(v, a, b, dimension)?;
+	let v1 = Secret::new(4 * v - 4 * a + 1);
+	let v2 = Secret::new(4 * b - 4 * v + 1);
 
 	// Use 3-squares for numbers of the form 4x+1
-	let d1 = find_3_squares(&v1);
-	let d2 = find_3_squares(&v2);
-	let d_base = [d1, d2].concat(); // length 6
+	let d_base = Secret::new(square_decomposition(v1.expose(), v2.expose())?); // length 6
+	let d_base = d_base.expose();
 
 	// Expand d to the requested dimension by repeating the base pattern
-	let d = (0..dimension)
+	let d = Secret::new((0..dimension)
 		.map(|i| d_base[i % d_base.len()].clone())
-		.collect::<Vec<_>>();
+		.collect::<Vec<_>>());
 
 	// Create commitments to v, v1, v2
-	let (C, _r_v) = commit_value(g, h, v, n);
-	let (C_v1, _r_v1) = commit_value(g, h, &v1, n);
-	let (C_v2, _r_v2) = commit_value(g, h, &v2, n);
+	let (C, C_v1, C_v2) = phase(Phase::Commitments, || {
+		(params.commit(v, r), commit_value(params, v1.expose(), rng).0, commit_value(params, v2.expose(), rng).0)
+	});
 
-	let alpha = random_bigint(256);
-	let rho = random_bigint(256);
-	let sL = (0..dimension).map(|_| random_bigint(256)).collect::<Vec<_>>();
-	let sR = (0..dimension).map(|_| random_bigint(256)).collect::<Vec<_>>();
+	let alpha = Secret::new(random_blinding(rng));
+	let rho = Secret::new(random_blinding(rng));
+	let sL = Secret::new((0..dimension).map(|_| random_blinding(rng)).collect::<Vec<_>>());
+	let sR = Secret::new((0..dimension).map(|_| random_blinding(rng)).collect::<Vec<_>>());
 
 	// Commit A and S (demo-style, sum-based)
-	let sum_d = d.iter().sum();
-	let A = pedersen_commit(g, h, &sum_d, &alpha, n);
-	let sum_s = sL.iter().sum::<BigInt>() + sR.iter().sum::<BigInt>();
-	let S = pedersen_commit(g, h, &sum_s, &rho, n);
+	let sum_d = Secret::new(d.expose().iter().sum());
+	let sum_s = Secret::new(sL.expose().iter().sum::<BigInt>() + sR.expose().iter().sum::<BigInt>());
+	let (A, S) = phase(Phase::Commitments, || (params.commit(sum_d.expose(), alpha.expose()), params.commit(sum_s.expose(), rho.expose())));
+	Ok(FirstRound { d, alpha, rho, sL, sR, A, S, C, C_v1, C_v2 })
+}
+
+/// The challenges y and z of a first message from `transcript`, as verify recomputes them
+#[allow(non_snake_case)]
+pub(crate) fn first_round_challenges<G: Group>(A: &BigInt, S: &BigInt, C: &BigInt, C_v1: &BigInt, C_v2: &BigInt, params: &G, transcript: &mut dyn Transcript) -> (BigInt, BigInt) {
+	phase(Phase::Challenges, || transcript.first_round([A, S, C, C_v1, C_v2], params.challenge_modulus()))
+}
 
-	// Fiat–Shamir challenges
-	let y = fiat_shamir(&[&A, &S, &C, &C_v1, &C_v2]) % n;
-	let z = fiat_shamir(&[&y]) % n;
+impl FirstRound {
+	pub(crate) fn challenges<G: Group>(&self, params: &G, transcript: &mut dyn Transcript) -> (BigInt, BigInt) {
+		first_round_challenges(&self.A, &self.S, &self.C, &self.C_v1, &self.C_v2, params, transcript)
+	}
 
-	// l0 = z*d + y ; r0 = z*d + y
-	let l0 = d.iter().map(|di| &z * di + &y).collect::<Vec<_>>();
-	let r0 = d.iter().map(|di| &z * di + &y).collect::<Vec<_>>();
+	/// The proof of [a, b] answering the challenges y and z, with x drawn from `transcript`
+	#[allow(clippy::too_many_arguments)]
+	pub(crate) fn finish<G: Group, R: RngCore + CryptoRng>(self, a: &BigInt, b: &BigInt, y: &BigInt, z: &BigInt, params: &G, transcript: &mut dyn Transcript, rng: &mut R) -> Cuproof {
+		let FirstRound { d, alpha, rho, sL, sR, A, S, C, C_v1, C_v2 } = self;
+		let Polynomial { T1, T2, t0, t1, t2, tau1, tau2, t_hat, mu, tau_x, ipp_proof } =
+			prove_polynomial(d.expose(), sL.expose(), sR.expose(), alpha.expose(), rho.expose(), y, z, params, transcript, rng);
+		let statement = Statement::new(&C, a, b, d.expose().len(), params);
+		let proof = Cuproof {
+			A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof,
+			epoch: params.epoch(),
+			statement: Some(statement),
+			assist: None,
+		};
+		// BLINDING_BITS-bit blindings keep every response below the bound verify enforces
+		debug_assert!(ScalarBound::for_range(params, a, b, proof.ipp_proof.L.len() as u32).check(proof.scalars()).is_ok());
+		proof
+	}
+}
 
-	// Coefficients of t(x) = <l(x), r(x)> = t0 + t1 x + t2 x^2
-	let t0 = inner_product(&l0, &r0);
-	let t1 = l0.iter().zip(&sR).map(|(l0i, sRi)| l0i * sRi).sum::<BigInt>()
-		+ r0.iter().zip(&sL).map(|(r0i, sLi)| r0i * sLi).sum::<BigInt>();
-	let t2 = inner_product(&sL, &sR);
+/// The second half of the prover, after the challenges y and z: t(x) = <l(x), r(x)>, its
+/// commitments T1 and T2, the challenge x and the inner-product argument over l(x) and r(x)
+pub(crate) struct Polynomial {
+	pub T1: BigInt,
+	pub T2: BigInt,
+	pub t0: BigInt,
+	pub t1: BigInt,
+	pub t2: BigInt,
+	pub tau1: BigInt,
+	pub tau2: BigInt,
+	pub t_hat: BigInt,
+	pub mu: BigInt,
+	pub tau_x: BigInt,
+	pub ipp_proof: IPPProof,
+}
+
+/// Prove the polynomial half for the vector `d` blinded by sL, sR (committed in A and S under
+/// alpha and rho); shared by single and aggregated proofs. The challenge x comes from `transcript`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prove_polynomial<G: Group, R: RngCore + CryptoRng>(d: &[BigInt], sL: &[BigInt], sR: &[BigInt], alpha: &BigInt, rho: &BigInt, y: &BigInt, z: &BigInt, params: &G, transcript: &mut dyn Transcript, rng: &mut R) -> Polynomial {
+	let n = params.challenge_modulus();
+	// l0 = z*d + y ; r0 = z*d + y, the same vector, so it is computed once
+	let l0 = math::l0(d, y, z);
+
+	// Coefficients of t(x) = <l(x), r(x)> = t0 + t1 x + t2 x^2; t1 = <l0, sR> + <r0, sL> = <l0, sL + sR>
+	let t0 = inner_product(&l0, &l0);
+	let mut s_sum = BigInt::zero();
+	let t1 = l0.iter().zip(sL.iter().zip(sR)).fold(BigInt::zero(), |mut t1, (l0i, (sLi, sRi))| {
+		s_sum.clone_from(sLi);
+		s_sum += sRi;
+		t1 += l0i * &s_sum;
+		t1
+	});
+	let t2 = inner_product(sL, sR);
 
 	// Commit T1 = Commit(t1, tau1), T2 = Commit(t2, tau2)
-	let tau1 = random_bigint(256);
-	let tau2 = random_bigint(256);
-	let T1 = pedersen_commit(g, h, &t1, &tau1, n);
-	let T2 = pedersen_commit(g, h, &t2, &tau2, n);
+	let tau1 = random_blinding(rng);
+	let tau2 = random_blinding(rng);
+	let (T1, T2) = phase(Phase::Commitments, || (params.commit(&t1, &tau1), params.commit(&t2, &tau2)));
 
 	// Challenge x
-	let x = fiat_shamir(&[&T1, &T2]) % n;
+	let x = phase(Phase::Challenges, || transcript.polynomial(&T1, &T2, n));
 
 	// Evaluate t_hat at x
-	let t_hat = &t0 + &(&t1 * &x) + &(&t2 * &x * &x);
+	let t_hat = math::poly_eval(&[&t0, &t1, &t2], &x);
 
 	// Aggregate blinding terms (demo-style): μ = α + ρ x ; τx = τ2 x^2 + τ1 x
-	let mu = &alpha + &(&rho * &x);
+	let mu = alpha + &(rho * &x);
 	let tau_x = &tau2 * &x * &x + &tau1 * &x;
 
-	// Generate IPP proof for l_vec and r_vec
-	let l_vec = l0.iter().zip(&sL).map(|(l0i, sLi)| l0i + &(sLi * &x)).collect::<Vec<_>>();
-	let r_vec = r0.iter().zip(&sR).map(|(r0i, sRi)| r0i + &(sRi * &x)).collect::<Vec<_>>();
-	
-	let (a_final, b_final, L_vec, R_vec) = inner_product_argument_recursive(&l_vec, &r_vec, g, h, n, 0);
+	// l(x) = l0 + sL x and r(x) = r0 + sR x, grown from l0 in place
+	let r_vec = math::vector_poly_eval(l0.clone(), sR, &x);
+	let l_vec = math::vector_poly_eval(l0, sL, &x);
+
+	let (a_final, b_final, L_vec, R_vec) = phase(Phase::Ipp, || inner_product_argument_in_place(l_vec, r_vec, params, rng));
 	
 	let ipp_proof = IPPProof {
 		L: L_vec,
@@ -385,15 +779,80 @@ pub fn cuproof_prove_with_dimension(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigI
 		b: b_final,
 	};
 
-	Cuproof {
-		A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof,
-	}
+
+	Polynomial { T1, T2, t0, t1, t2, tau1, tau2, t_hat, mu, tau_x, ipp_proof }
 }
 
 // Backward-compatible wrapper that defaults to larger dimension for IPP
-pub fn cuproof_prove(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, g: &BigInt, h: &BigInt, n: &BigInt) -> Cuproof {
+#[cfg(feature = "std")]
+pub fn prove<G: Group>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G) -> Result<Cuproof, ProveError> {
 	// Use larger dimension to ensure enough recursion levels for IPP
-	cuproof_prove_with_dimension(v, r, a, b, g, h, n, 64) // Reduced from 1024 to 64
+	cuproof_prove_with_dimension(v, r, a, b, params, 64) // Reduced from 1024 to 64
+}
+
+/// Former name of prove, kept for one release
+#[cfg(feature = "std")]
+#[deprecated(note = "renamed to `prove`")]
+pub fn cuproof_prove<G: Group>(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, params: &G) -> Result<Cuproof, ProveError> {
+	prove(v, r, a, b, params)
+}
+
+/// Prover bound to a parameter set that passed its constructor's checks
+///
+/// Send + Sync: one prover (or its clones, which share the params and their tables) serves any
+/// number of threads at once
+#[derive(Clone, Debug)]
+pub struct RangeProver {
+	params: Arc<Params>,
+}
+
+impl RangeProver {
+	/// Build a prover over `params` after `Params::insecure_check` and `Params::security_check(MIN_SECURE_MODULUS_BITS)`
+	/// - params: Params, or an `Arc<Params>` already shared with verifiers or other services
+	/// - returns: RangeProver or ParamsError (InsecureParams or ModulusTooSmall for test-sized params)
+	pub fn new<P: Into<Arc<Params>>>(params: P) -> Result<RangeProver, ParamsError> {
+		let params = params.into();
+		params.insecure_check()?;
+		params.security_check(MIN_SECURE_MODULUS_BITS)?;
+		Ok(RangeProver { params })
+	}
+
+	/// Build a prover over params of any size that pass validate_params
+	/// - usage: tests and demos over insecure_test_setup (after `allow_insecure_params`) or reduced
+	///   trusted setups only
+	pub fn new_insecure<P: Into<Arc<Params>>>(params: P) -> Result<RangeProver, ParamsError> {
+		let params = params.into();
+		params.insecure_check()?;
+		validate_params(&params)?;
+		Ok(RangeProver { params })
+	}
+
+	pub fn params(&self) -> &Params { &self.params }
+
+	/// The params, to hand to another RangeProver or a Verifier without copying them
+	pub fn shared_params(&self) -> &Arc<Params> { &self.params }
+
+	/// Prove a <= v <= b for value v with blinding r (see prove)
+	#[cfg(feature = "std")]
+	pub fn prove(&self, v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt) -> Result<Cuproof, ProveError> {
+		prove(v, r, a, b, self.params())
+	}
+}
+
+/// Tuple-based shim kept for one release; the positional (g, h, n) order is easy to swap
+#[cfg(feature = "std")]
+#[deprecated(note = "build a setup::Params and call prove")]
+pub fn cuproof_prove_with_generators(v: &BigInt, r: &BigInt, a: &BigInt, b: &BigInt, g: &BigInt, h: &BigInt, n: &BigInt) -> Result<Cuproof, ProveError> {
+	let params = Params::new(g.clone(), h.clone(), n.clone(), n.bits() as usize);
+	prove(v, r, a, b, &params)
+}
+
+/// The id of a proof: SHA-256 of its to_bytes encoding, in hex
+/// - usage: naming a proof in logs and reports (`cuproof prove` prints it) without its contents;
+///   for a canonicalized proof (Cuproof::canonicalize, load_proof_checked) the hash of its
+///   canonical form, so proofs equal under == share one id
+pub fn proof_id(proof: &Cuproof) -> String {
+	hex::encode(Sha256::digest(proof.to_bytes()))
 }
 
 fn bigint_size_bytes(x: &BigInt) -> usize {
@@ -432,30 +891,115 @@ pub fn proof_size_bytes(proof: &Cuproof) -> usize {
 mod tests {
     use super::*;
     use num_bigint::BigInt;
-    use crate::setup::fast_test_setup;
+    use crate::setup::insecure_test_setup;
     use crate::util::random_bigint;
 
     // Purpose: smoke test proof generation returns non-zero-sized proof with consistent fields
     // Params: small demo range and random r
-    // Output: asserts on non-zero size and non-empty IPP vectors
+    // Output: asserts on non-zero size and non-empty IPP vectors; C opens with (v, r)
     // Usage: `cargo test -- src::range_proof` or `cargo test`
     #[test]
     fn prove_smoke_nonzero_size() {
-        let (g, h, n) = fast_test_setup();
+        let params = insecure_test_setup(512);
         let a = BigInt::from(1);
         let b = BigInt::from(100);
         let v = BigInt::from(42);
         let r = random_bigint(128);
-        let proof = cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
+        let proof = prove(&v, &r, &a, &b, &params).unwrap();
         let sz = proof_size_bytes(&proof);
         assert!(sz > 0);
         assert_eq!(proof.ipp_proof.L.len(), proof.ipp_proof.R.len());
         assert!(proof.ipp_proof.L.len() > 0);
+        assert_eq!(proof.C, crate::commitment::commit(&params, &v, &r));
+    }
+
+    // Purpose: ensure all prover randomness flows through the supplied RNG
+    // Params: two ChaCha20 streams with the same seed and one with another seed
+    // Output: identical proofs from equal seeds, different A from different seeds; both verify
+    // Usage: `cargo test -- src::range_proof` or `cargo test`
+    #[test]
+    fn prove_with_rng_is_reproducible() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+        let params = insecure_test_setup(512);
+        let (a, b, v, r) = (BigInt::from(1), BigInt::from(100), BigInt::from(42), BigInt::from(7));
+        let prove = |seed| prove_with_rng(&v, &r, &a, &b, &params, 64, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap();
+        let (first, second, other) = (prove(1), prove(1), prove(2));
+        for (x, y) in [(&first.A, &second.A), (&first.S, &second.S), (&first.C_v1, &second.C_v1), (&first.mu, &second.mu), (&first.tau_x, &second.tau_x)] {
+            assert_eq!(x, y);
+        }
+        assert_eq!(first.ipp_proof.L, second.ipp_proof.L);
+        assert_ne!(first.A, other.A);
+        assert!(crate::verify::verify(&first, &params));
+        assert!(crate::verify::verify(&other, &params));
+    }
+
+    // Purpose: ensure deterministic proofs depend on the whole witness and statement, and on nothing else
+    // Params: one (v, r, a, b) proved twice, then with v, r and b changed in turn
+    // Output: byte-identical encodings for the repeated inputs, a different A for each change; all verify
+    // Usage: `cargo test -- src::range_proof` or `cargo test`
+    #[test]
+    fn deterministic_proofs_follow_the_witness() {
+        let params = insecure_test_setup(512);
+        let (a, b, v, r) = (BigInt::from(1), BigInt::from(100), BigInt::from(42), BigInt::from(7));
+        let first = cuproof_prove_deterministic(&v, &r, &a, &b, &params).unwrap();
+        assert_eq!(first.to_bytes(), cuproof_prove_deterministic(&v, &r, &a, &b, &params).unwrap().to_bytes());
+        for (v, r, b) in [(BigInt::from(43), r.clone(), b.clone()), (v.clone(), BigInt::from(8), b.clone()), (v.clone(), r.clone(), BigInt::from(101))] {
+            let other = cuproof_prove_deterministic(&v, &r, &a, &b, &params).unwrap();
+            assert_ne!(first.A, other.A);
+            assert!(crate::verify::verify(&other, &params));
+        }
+        assert!(crate::verify::verify(&first, &params));
+    }
+
+    // Purpose: ensure a proof and a variant with unreduced elements are told apart until both are
+    //          canonicalized, then compare, hash and load as one proof
+    // Params: a deterministic proof; a copy with A + n, L[0] + 2n and the statement's C + n
+    // Output: != and two HashSet entries before; ==, one entry, equal proof_id and a verifying
+    //         proof after; load_proof_checked of the saved variant equals the original
+    // Usage: `cargo test -- src::range_proof` or `cargo test`
+    #[test]
+    fn canonical_proofs_compare_and_hash_equal() {
+        use std::collections::HashSet;
+        let params = insecure_test_setup(512);
+        let n = params.n();
+        let proof = cuproof_prove_deterministic(&BigInt::from(42), &BigInt::from(7), &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+        let mut variant = proof.clone();
+        variant.A += n;
+        variant.ipp_proof.L[0] += n * 2;
+        if let Some(statement) = variant.statement.as_mut() { statement.commitment += n; }
+        assert!(variant != proof);
+        assert_eq!(HashSet::from([proof.clone(), variant.clone()]).len(), 2);
+
+        let path = std::env::temp_dir().join(format!("cuproof_canonical_{}.txt", std::process::id()));
+        crate::util::save_proof(path.to_str().unwrap(), &variant).unwrap();
+        let loaded = crate::util::load_proof_checked(path.to_str().unwrap(), &params).unwrap();
+        let _ = std::fs::remove_file(&path);
+        variant.canonicalize(n);
+        assert!(variant == proof && loaded == proof);
+        assert_eq!(HashSet::from([proof.clone(), variant.clone()]).len(), 1);
+        assert_eq!(proof_id(&variant), proof_id(&proof));
+        assert!(crate::verify::verify(&variant, &params));
+    }
+
+    // Purpose: inputs no proof exists for are reported as ProveError instead of panicking
+    // Params: v below a, v above b, a > b, dimension 0
+    // Output: the matching ProveError variant for each
+    // Usage: `cargo test -- src::range_proof` or `cargo test`
+    #[test]
+    fn prove_rejects_unprovable_inputs() {
+        let params = insecure_test_setup(512);
+        let (a, b, r) = (BigInt::from(10), BigInt::from(100), BigInt::from(7));
+        assert_eq!(prove(&BigInt::from(9), &r, &a, &b, &params).err(), Some(ProveError::ValueOutOfRange));
+        assert_eq!(prove(&BigInt::from(101), &r, &a, &b, &params).err(), Some(ProveError::ValueOutOfRange));
+        assert_eq!(prove(&BigInt::from(50), &r, &b, &a, &params).err(), Some(ProveError::EmptyRange));
+        assert_eq!(cuproof_prove_with_dimension(&BigInt::from(50), &r, &a, &b, &params, 0).err(), Some(ProveError::InvalidDimension(0)));
+        assert_eq!(interactive_prove_step1(&BigInt::from(9), &r, &a, &b, &params).err(), Some(ProveError::ValueOutOfRange));
     }
 }
 
 // Inner Product Argument (simplified version - kept for reference)
-pub fn inner_product_argument(l_vec: &[BigInt], r_vec: &[BigInt], g: &BigInt, h: &BigInt, n: &BigInt) -> (BigInt, BigInt) {
+pub fn inner_product_argument(l_vec: &[BigInt], r_vec: &[BigInt], params: &Params) -> (BigInt, BigInt) {
 	if l_vec.len() == 1 {
 		return (l_vec[0].clone(), r_vec[0].clone());
 	}
@@ -469,7 +1013,7 @@ pub fn inner_product_argument(l_vec: &[BigInt], r_vec: &[BigInt], g: &BigInt, h:
 	let c_L = inner_product(l_left, r_right);
 	let c_R = inner_product(l_right, l_left);
 	
-	let y = fiat_shamir(&[&c_L, &c_R]) % n;
+	let y = fiat_shamir(&[&c_L, &c_R]) % params.n();
 	
 	let l_new: Vec<BigInt> = l_left.iter().zip(l_right.iter())
 		.map(|(l, r)| l + &(&y * r))
@@ -478,5 +1022,5 @@ pub fn inner_product_argument(l_vec: &[BigInt], r_vec: &[BigInt], g: &BigInt, h:
 		.map(|(l, r)| r + &(&y * l))
 		.collect();
 	
-	inner_product_argument(&l_new, &r_new, g, h, n)
+	inner_product_argument(&l_new, &r_new, params)
 }
@@ -0,0 +1,133 @@
+//! The protocol's intermediate quantities as pure functions, for the prover, verify and auditors
+//!
+//! The prover builds l(x) = l0 + sL x and r(x) = l0 + sR x from l0 = z d + y, with d the four
+//! squares of v1 = 4(v - a) + 1 and v2 = 4(b - v) + 1, and t(x) = <l(x), r(x)> = t0 + t1 x + t2 x^2;
+//! verify checks t_hat = t(x) with poly_eval. powers and delta are the y^n / 2^n vectors and the
+//! correction term of the two-sided bit decomposition (Bulletproofs, section 4.3, over v - a and
+//! b - v), whose t0 is (z^2 - z^3) v + delta(y, z, bits, a, b, n); they let an auditor recompute
+//! that form independently, and are what a verifier of the bit-decomposition equations needs.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// [1, base, base^2, ..., base^(count - 1)] modulo n
+/// - params: base any integer, count the length, n a positive modulus
+/// - returns: count powers, each in [0, n); empty for count 0
+/// - usage: `powers(y, 2 * bits, n)` and `powers(&2.into(), bits, n)` for the y^n and 2^n vectors
+pub fn powers(base: &BigInt, count: usize, n: &BigInt) -> Vec<BigInt> {
+    let base = base.mod_floor(n);
+    let mut out = Vec::with_capacity(count);
+    let mut power = BigInt::one().mod_floor(n);
+    for _ in 0..count {
+        let next = (&power * &base) % n;
+        out.push(core::mem::replace(&mut power, next));
+    }
+    out
+}
+
+/// The correction term of the two-sided range constraint a <= v <= b over `bits`-bit differences:
+/// (z - z^2) <1, y^(2 bits)> - (z^3 + z^4) <1, 2^bits> + z^3 b - z^2 a, modulo n
+/// - params: challenges y, z; bits the width of v - a and b - v; the public range [a, b]; n the
+///   challenge modulus
+/// - returns: delta in [0, n), so that t0 = (z^2 - z^3) v + delta modulo n for honest vectors
+/// - usage: recomputing t0 without the witness, e.g. `delta(&y, &z, 32, &a, &b, n)`
+pub fn delta(y: &BigInt, z: &BigInt, bits: usize, a: &BigInt, b: &BigInt, n: &BigInt) -> BigInt {
+    let sum_y: BigInt = powers(y, 2 * bits, n).iter().sum();
+    let sum_2: BigInt = powers(&BigInt::from(2), bits, n).iter().sum();
+    let z2 = z * z;
+    let z3 = &z2 * z;
+    let z4 = &z3 * z;
+    ((z - &z2) * sum_y - (&z3 + z4) * sum_2 + z3 * b - z2 * a).mod_floor(n)
+}
+
+/// c0 + c1 x + c2 x^2 + ... over the integers, by Horner's rule
+/// - params: coefficients lowest degree first, owned or borrowed; empty is the zero polynomial
+/// - usage: t_hat = poly_eval(&[&t0, &t1, &t2], x), computed by the prover and checked by verify
+pub fn poly_eval<C: Borrow<BigInt>>(coefficients: &[C], x: &BigInt) -> BigInt {
+    coefficients.iter().rev().fold(BigInt::zero(), |acc, c| acc * x + c.borrow())
+}
+
+/// l0 = z d + y, componentwise: the constant term of l(x) and r(x)
+pub fn l0(d: &[BigInt], y: &BigInt, z: &BigInt) -> Vec<BigInt> {
+    d.iter().map(|di| z * di + y).collect()
+}
+
+/// The vector polynomial `constant + linear x` at x, componentwise, reusing `constant`'s storage
+/// - params: constant and linear of equal length, e.g. l0 and sL for l(x)
+/// - returns: the evaluated vector, as long as the shorter input
+pub fn vector_poly_eval(mut constant: Vec<BigInt>, linear: &[BigInt], x: &BigInt) -> Vec<BigInt> {
+    constant.truncate(linear.len());
+    for (c, l) in constant.iter_mut().zip(linear) {
+        *c += l * x;
+    }
+    constant
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::inner_product;
+
+    fn ints(values: &[i64]) -> Vec<BigInt> {
+        values.iter().map(|&v| BigInt::from(v)).collect()
+    }
+
+    // Purpose: pin powers, poly_eval, l0 and vector_poly_eval to hand-computed values
+    // Params: small bases, moduli, coefficients and vectors, negative inputs included
+    // Output: exact vectors and integers
+    // Usage: `cargo test -- range_proof::math`
+    #[test]
+    fn helpers_match_hand_computed_values() {
+        assert_eq!(powers(&BigInt::from(2), 5, &BigInt::from(100)), ints(&[1, 2, 4, 8, 16]));
+        assert_eq!(powers(&BigInt::from(3), 4, &BigInt::from(10)), ints(&[1, 3, 9, 7]));
+        assert_eq!(powers(&BigInt::from(-1), 3, &BigInt::from(7)), ints(&[1, 6, 1]));
+        assert_eq!(powers(&BigInt::from(5), 2, &BigInt::from(1)), ints(&[0, 0]));
+        assert!(powers(&BigInt::from(5), 0, &BigInt::from(7)).is_empty());
+
+        assert_eq!(poly_eval(&ints(&[1, 2, 3]), &BigInt::from(10)), BigInt::from(321));
+        assert_eq!(poly_eval(&ints(&[1, 2, 3]), &BigInt::from(-1)), BigInt::from(2));
+        assert_eq!(poly_eval(&ints(&[5]), &BigInt::from(9)), BigInt::from(5));
+        assert_eq!(poly_eval::<BigInt>(&[], &BigInt::from(9)), BigInt::zero());
+
+        let l = l0(&ints(&[0, 1, 4]), &BigInt::from(2), &BigInt::from(3));
+        assert_eq!(l, ints(&[2, 5, 14]));
+        assert_eq!(vector_poly_eval(l, &ints(&[1, -1, 0]), &BigInt::from(10)), ints(&[12, -5, 14]));
+        assert_eq!(vector_poly_eval(ints(&[1, 2]), &ints(&[1]), &BigInt::from(3)), ints(&[4]));
+    }
+
+    // Purpose: pin delta to hand-computed values
+    // Params: y = 2, z = 3, one bit, [0, 1], n = 1000; y = z = 1, two bits, [5, 7], n = 101
+    // Output: (3 - 9) * 3 - 108 * 1 + 27 = -99 = 901 mod 1000; 0 - 2 * 3 + 7 - 5 = -4 = 97 mod 101
+    // Usage: `cargo test -- range_proof::math`
+    #[test]
+    fn delta_matches_hand_computed_values() {
+        let int = BigInt::from;
+        assert_eq!(delta(&int(2), &int(3), 1, &int(0), &int(1), &int(1000)), int(901));
+        assert_eq!(delta(&int(1), &int(1), 2, &int(5), &int(7), &int(101)), int(97));
+    }
+
+    // Purpose: ensure delta is the witness-free part of t0 for the two-sided bit decomposition
+    // Params: v = 9 in [3, 14] over 4-bit differences; y = 5, z = 7 modulo the prime 1000003
+    // Output: <l0, r0> = (z^2 - z^3) v + delta modulo n, with l0 = aL - z and
+    //         r0 = y^8 o (aR + z) + (z^2 2^4 || z^3 2^4)
+    // Usage: `cargo test -- range_proof::math`
+    #[test]
+    fn delta_completes_t0() {
+        let (n, y, z) = (BigInt::from(1_000_003), BigInt::from(5), BigInt::from(7));
+        let (v, a, b, bits) = (9u32, 3u32, 14u32, 4usize);
+        let bits_of = |x: u32| (0..bits).map(move |i| BigInt::from((x >> i) & 1));
+        let a_l: Vec<BigInt> = bits_of(v - a).chain(bits_of(b - v)).collect();
+        let (y_n, two_n) = (powers(&y, 2 * bits, &n), powers(&BigInt::from(2), bits, &n));
+        let z2 = &z * &z;
+        let z3 = &z2 * &z;
+        let shifts: Vec<BigInt> = two_n.iter().map(|p| &z2 * p).chain(two_n.iter().map(|p| &z3 * p)).collect();
+        let l: Vec<BigInt> = a_l.iter().map(|bit| bit - &z).collect();
+        let r: Vec<BigInt> = a_l.iter().zip(&y_n).zip(&shifts).map(|((bit, yi), s)| yi * (bit - 1 + &z) + s).collect();
+        let t0 = inner_product(&l, &r).mod_floor(&n);
+        let expected = ((&z2 - &z3) * BigInt::from(v) + delta(&y, &z, bits, &BigInt::from(a), &BigInt::from(b), &n)).mod_floor(&n);
+        assert_eq!(t0, expected);
+    }
+}
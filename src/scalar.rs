@@ -0,0 +1,197 @@
+//! The public bound B on proof scalars, and Scalar, an integer checked against it
+//!
+//! The order of the group is unknown, so the prover's responses (t0, t1, t2, t_hat, tau1, tau2,
+//! tau_x, mu and the inner-product a and b) are integers that are never reduced, and verify's
+//! identities hold over the integers. A prover free to send any integer can satisfy them with
+//! oversized values no honest run produces (t0 and t_hat shifted together, a tau_x of any size),
+//! and makes the verifier exponentiate by them. Every scalar must therefore lie in [0, B), with B
+//! fixed by the protocol rather than by the proof.
+//!
+//! B follows from the sizes of the honest values, with c the bits of the challenge modulus (y, z
+//! and x are below it), w the bits of the range, s = BLINDING_BITS and k the inner-product rounds:
+//! - d holds square roots of v1, v2 <= 4(b - a) + 1, so each entry has at most ceil((w + 3) / 2) bits
+//! - the entries of l(x) = z d + y + sL x and r(x) = z d + y + sR x are below 2^e, with
+//!   e = c + max(ceil((w + 3) / 2) + 1, s) + 1
+//! - t_hat = <l(x), r(x)> is below 2^(2e + k), and t0, t1 x and t2 x^2 are at most t_hat
+//! - each round of the inner-product argument multiplies by a challenge and adds, so a and b are
+//!   below 2^(e + k (c + 1))
+//! - mu < 2^(s + c + 1) and tau_x < 2^(s + 2c + 1) are below both
+//!
+//! so B = 2^max(2e + k, e + k (c + 1)). The blindings drawn by random_blinding are what keep the
+//! honest responses under it; a statement's w is taken from its bounds, as the bits of the wider
+//! one plus one.
+
+use alloc::borrow::Cow;
+use core::fmt;
+use num_bigint::BigInt;
+use num_traits::Signed;
+use crate::group::Group;
+use crate::range_proof::BLINDING_BITS;
+
+/// The bound B = 2^bits every scalar of a proof lies below
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScalarBound {
+    bits: u64,
+}
+
+impl ScalarBound {
+    /// The bound for challenges of `challenge_bits` bits, ranges of `value_bits` bits and
+    /// `rounds` inner-product rounds, as derived in the module docs
+    pub fn new(challenge_bits: u64, value_bits: u64, rounds: u32) -> ScalarBound {
+        let (c, k) = (challenge_bits, u64::from(rounds));
+        let e = c + ((value_bits + 3).div_ceil(2) + 1).max(BLINDING_BITS as u64) + 1;
+        ScalarBound { bits: (2 * e + k).max(e + k * (c + 1)) }
+    }
+
+    /// The bound for proofs of [a, b] under `params` with `rounds` inner-product rounds
+    /// - usage: `ScalarBound::for_range(&params, &statement.a, &statement.b, 6)`
+    pub fn for_range<G: Group>(params: &G, a: &BigInt, b: &BigInt, rounds: u32) -> ScalarBound {
+        ScalarBound::new(params.challenge_modulus().bits(), a.bits().max(b.bits()) + 1, rounds)
+    }
+
+    /// log2(B)
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Whether 0 <= x < B
+    pub fn contains(&self, x: &BigInt) -> bool {
+        !x.is_negative() && x.bits() <= self.bits
+    }
+
+    /// `value` as a Scalar under this bound
+    /// - params: field the name reported when value is outside [0, B)
+    pub fn scalar<'a>(&self, field: &'static str, value: &'a BigInt) -> Result<Scalar<'a>, ScalarError> {
+        if value.is_negative() { return Err(ScalarError::Negative(field)); }
+        if value.bits() > self.bits { return Err(ScalarError::OutOfBound { field, bits: value.bits(), bound_bits: self.bits }); }
+        Ok(Scalar { value: Cow::Borrowed(value), bound: *self })
+    }
+
+    /// Check named scalars, in order
+    /// - returns: Ok(()), or the ScalarError of the first outside [0, B)
+    pub fn check<'a>(&self, scalars: impl IntoIterator<Item = (&'static str, &'a BigInt)>) -> Result<(), ScalarError> {
+        scalars.into_iter().try_for_each(|(field, value)| self.scalar(field, value).map(|_| ()))
+    }
+}
+
+/// An integer in [0, B) whose arithmetic is checked against B
+/// - usage: the verifier's equations over proof scalars, so a result outside the bound is an error
+///   rather than a value some forged input was tuned to reach
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scalar<'a> {
+    value: Cow<'a, BigInt>,
+    bound: ScalarBound,
+}
+
+impl Scalar<'_> {
+    pub fn value(&self) -> &BigInt {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> BigInt {
+        self.value.into_owned()
+    }
+
+    pub fn bound(&self) -> ScalarBound {
+        self.bound
+    }
+
+    /// self + other, or None when the sum is not below self's bound
+    pub fn checked_add(&self, other: &Scalar<'_>) -> Option<Scalar<'static>> {
+        self.within(&*self.value + &*other.value)
+    }
+
+    /// self * other, or None when the product is not below self's bound
+    pub fn checked_mul(&self, other: &Scalar<'_>) -> Option<Scalar<'static>> {
+        self.within(&*self.value * &*other.value)
+    }
+
+    /// c0 + c1 x + c2 x^2 + ... by Horner's rule, every step checked against x's bound
+    /// - returns: None when an intermediate value reaches the bound; math::poly_eval otherwise
+    pub fn checked_poly_eval(coefficients: &[Scalar<'_>], x: &Scalar<'_>) -> Option<Scalar<'static>> {
+        let mut acc = Scalar { value: Cow::Owned(BigInt::default()), bound: x.bound };
+        for c in coefficients.iter().rev() {
+            acc = acc.checked_mul(x)?.checked_add(c)?;
+        }
+        Some(acc)
+    }
+
+    fn within(&self, value: BigInt) -> Option<Scalar<'static>> {
+        self.bound.contains(&value).then_some(Scalar { value: Cow::Owned(value), bound: self.bound })
+    }
+}
+
+/// Why a value is not a Scalar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScalarError {
+    /// the named scalar is negative
+    Negative(&'static str),
+    /// the named scalar has `bits` bits, more than the bound's
+    OutOfBound { field: &'static str, bits: u64, bound_bits: u64 },
+}
+
+impl ScalarError {
+    /// The scalar that was rejected
+    pub fn field(&self) -> &'static str {
+        match self {
+            ScalarError::Negative(field) | ScalarError::OutOfBound { field, .. } => field,
+        }
+    }
+}
+
+impl fmt::Display for ScalarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalarError::Negative(field) => write!(f, "scalar {} is negative", field),
+            ScalarError::OutOfBound { field, bits, bound_bits } => {
+                write!(f, "scalar {} has {} bits, more than the bound of {}", field, bits, bound_bits)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ScalarError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_proof;
+    use num_traits::One;
+
+    // Purpose: pin the bound's formula and Scalar's checked arithmetic
+    // Params: c = 2048, w = 64, k = 6; a 10-bit bound for the arithmetic
+    // Output: e = 2048 + 256 + 1 = 2305 and B = 2^max(4616, 2305 + 6 * 2049) = 2^14599;
+    //         sums and products reaching 2^10 are None, negative values and values of 11 bits rejected
+    // Usage: `cargo test -- src::scalar` or `cargo test`
+    #[test]
+    fn bound_and_arithmetic() {
+        assert_eq!(ScalarBound::new(2048, 64, 6).bits(), 14599);
+        assert_eq!(ScalarBound::new(2048, 8192, 0).bits(), 2 * (2048 + 4099 + 1));
+
+        let bound = ScalarBound { bits: 10 };
+        let (x, y, max, over) = (BigInt::from(31), BigInt::from(34), BigInt::from(1023), BigInt::from(1024));
+        let (x, y, max) = (bound.scalar("x", &x).unwrap(), bound.scalar("y", &y).unwrap(), bound.scalar("max", &max).unwrap());
+        assert_eq!(x.checked_mul(&x).map(Scalar::into_inner), Some(BigInt::from(961)));
+        assert_eq!(x.checked_mul(&y), None);
+        assert_eq!(max.checked_add(&bound.scalar("one", &BigInt::one()).unwrap()), None);
+        assert_eq!(bound.scalar("over", &over), Err(ScalarError::OutOfBound { field: "over", bits: 11, bound_bits: 10 }));
+        assert_eq!(bound.scalar("minus", &BigInt::from(-1)), Err(ScalarError::Negative("minus")));
+        assert_eq!(bound.check([("x", x.value()), ("over", &over)]).map_err(|e| e.field()), Err("over"));
+
+        let (two, three) = (BigInt::from(2), BigInt::from(3));
+        let coefficients = [x.clone(), bound.scalar("2", &two).unwrap()];
+        assert_eq!(Scalar::checked_poly_eval(&coefficients, &bound.scalar("3", &three).unwrap()).map(Scalar::into_inner), Some(BigInt::from(37)));
+        assert_eq!(Scalar::checked_poly_eval(&coefficients, &max), None);
+    }
+
+    // Purpose: ensure honest proofs stay below the bound of their statement
+    // Params: sample_proof(199)
+    // Output: every scalar of the proof within ScalarBound::for_range of its statement
+    // Usage: `cargo test -- src::scalar` or `cargo test`
+    #[test]
+    fn honest_proofs_are_within_the_bound() {
+        let (params, statement, proof) = sample_proof(199);
+        let bound = ScalarBound::for_range(&params, &statement.a, &statement.b, proof.ipp_proof.L.len() as u32);
+        assert_eq!(bound.check(proof.scalars()), Ok(()));
+    }
+}
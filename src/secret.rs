@@ -0,0 +1,297 @@
+//! Secret material wiped on drop (`zeroize` feature, on by default)
+//!
+//! `Secret<T>` holds what must not outlive its use: the value being proved, every blinding, seed
+//! material, and the intermediate byte and text buffers they pass through while being parsed or
+//! stored. It implements `Zeroize`; with the `zeroize`
+//! feature it also wipes itself when dropped (without it, it only redacts). Its Debug prints
+//! `Secret(<redacted>)`, and neither it nor any other secret type of this crate implements
+//! Display, so a secret cannot reach a log line or error message by formatting. Clones are
+//! Secrets too, each wiped on its own drop.
+//!
+//! num-bigint has no zeroize support and the orphan rule rules out `impl Zeroize for BigInt`
+//! here, so the wrapper wipes through the crate's own `Wipe` trait instead, which overwrites
+//! the digits in place before the allocation is released (setup::SecretInt does the same for the
+//! primes of trusted setup).
+//!
+//! Where secrets are covered, by module:
+//! - range_proof: v1 = 4(v - a) + 1 and v2, their square decomposition d, the blindings alpha,
+//!   rho, sL and sR of the first round; synthetic_nonce_rng's transcript buffers and seed
+//! - offline: the whole OfflineState (the first round above and the second round's seed)
+//! - util: the container bytes and payload lines of save_offline_state / read_offline_state, the
+//!   hex line and container bytes of save_blinding / read_blinding
+//! - setup, ceremony, primality, rsa_import: the primes and everything derived from them, in
+//!   SecretInt
+//! - cli: v and the blinding from every source (argument, stdin, environment, blinding file),
+//!   the text they were parsed from, a drawn blinding and the openings of prove-openings
+//!
+//! Residual gaps, which no wrapper can close:
+//! - BigInt arithmetic reallocates: each `+`, `*` or `%` on a secret allocates a result and
+//!   frees its operands' old buffers unwiped, and a digit buffer that grew or shrank leaves its
+//!   previous allocation behind unwiped. Wiping a BigInt clears the digits it holds now, nothing
+//!   that num-bigint copied before, and not the spare capacity beyond its length
+//! - the public prover API takes `&BigInt`, so the caller's own copies of v and r (and
+//!   util::random_bigint or range_proof::random_blinding results held in plain BigInts) are the
+//!   caller's to wrap
+//! - the second round (prove_polynomial: l(x), r(x), the inner-product vectors) works on plain
+//!   BigInts; its secrets are combinations with fresh challenges, but they are not wiped
+//! - a value or `prove --seed` passed as a CLI argument lives in the process arguments and
+//!   clap's parsed copy; env::var copies the environment string; stdin passes through the buffer
+//!   of the stdin handle
+//! - `Vec<u8>` and `String` buffers that grew while being written were reallocated; only the
+//!   final allocation is wiped
+//! - nothing here stops the OS from swapping a page out or writing it to a core dump
+//! - save_blinding and save_offline_state write their secrets in the clear; encrypting them at
+//!   rest is util::save_encrypted (encryption feature, Argon2id with XChaCha20-Poly1305), whose
+//!   derived key is wiped on drop; the passphrase is the caller's to wrap
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use num_bigint::{BigInt, BigUint};
+use zeroize::Zeroize;
+#[cfg(feature = "zeroize")]
+use zeroize::ZeroizeOnDrop;
+
+/// Overwrite a secret in place, keeping its allocation until it is dropped
+///
+/// For the types Secret can hold: `Zeroize` where the zeroize crate has it, otherwise the crate's
+/// own in-place overwrite (BigInt, BigUint)
+pub trait Wipe {
+    fn wipe(&mut self);
+}
+
+impl Wipe for BigUint {
+    /// Clears the set bits from the lowest up: the length only drops once every digit is zero,
+    /// so the buffer num-bigint frees when it shrinks holds zeros, and no allocation is needed
+    fn wipe(&mut self) {
+        for bit in 0..self.bits() {
+            if self.bit(bit) { self.set_bit(bit, false); }
+        }
+        core::hint::black_box(&*self);
+    }
+}
+
+impl Wipe for BigInt {
+    fn wipe(&mut self) {
+        let (_, mut magnitude) = core::mem::take(self).into_parts();
+        magnitude.wipe();
+        *self = BigInt::from(magnitude);
+    }
+}
+
+impl Wipe for Vec<BigInt> {
+    fn wipe(&mut self) {
+        self.iter_mut().for_each(Wipe::wipe);
+        self.clear();
+    }
+}
+
+impl Wipe for Vec<(BigInt, BigInt)> {
+    fn wipe(&mut self) {
+        for (m, r) in self.iter_mut() {
+            m.wipe();
+            r.wipe();
+        }
+        self.clear();
+    }
+}
+
+impl Wipe for Vec<u8> {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl Wipe for String {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl Wipe for Vec<String> {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<const N: usize> Wipe for [u8; N] {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// A secret, wiped on drop with the `zeroize` feature; see the module docs
+#[derive(Clone, Default)]
+pub struct Secret<T: Wipe>(T);
+
+impl<T: Wipe> Secret<T> {
+    pub fn new(x: T) -> Self {
+        Secret(x)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Wipe> From<T> for Secret<T> {
+    fn from(x: T) -> Self {
+        Secret(x)
+    }
+}
+
+impl<T: Wipe> Zeroize for Secret<T> {
+    fn zeroize(&mut self) {
+        self.0.wipe();
+    }
+}
+
+impl<T: Wipe> Drop for Secret<T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        self.0.wipe();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: Wipe> ZeroizeOnDrop for Secret<T> {}
+
+impl<T: Wipe> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::marker::PhantomData;
+    #[cfg(feature = "zeroize")]
+    use std::cell::Cell;
+    #[cfg(feature = "zeroize")]
+    use std::rc::Rc;
+    use num_traits::Zero;
+
+    /// Whether a type implements Display, by autoref: the method of ViaDisplay applies to
+    /// `&Probe<T>` directly and wins when T: Display, the fallback needs one more reference
+    struct Probe<T>(PhantomData<T>);
+
+    trait ViaDisplay {
+        fn is_display(&self) -> bool { true }
+    }
+    impl<T: fmt::Display> ViaDisplay for Probe<T> {}
+
+    trait Fallback {
+        fn is_display(&self) -> bool { false }
+    }
+    impl<T> Fallback for &Probe<T> {}
+
+    macro_rules! is_display {
+        ($t:ty) => { (&Probe::<$t>(PhantomData)).is_display() };
+    }
+
+    // Purpose: ensure no secret type can be formatted with {} and Debug redacts each of them
+    // Params: every secret type of the crate; BigInt and String as controls for the probe
+    // Output: is_display false for the secret types, true for the controls; Debug holds no digits
+    // Usage: `cargo test -- src::secret` or `cargo test`
+    #[test]
+    fn secret_types_are_not_display() {
+        assert!(is_display!(BigInt) && is_display!(String));
+        assert!(!is_display!(Secret<BigInt>));
+        assert!(!is_display!(Secret<BigUint>));
+        assert!(!is_display!(Secret<Vec<BigInt>>));
+        assert!(!is_display!(Secret<Vec<u8>>));
+        assert!(!is_display!(Secret<String>));
+        assert!(!is_display!(Secret<Vec<String>>));
+        assert!(!is_display!(Secret<[u8; 32]>));
+        assert!(!is_display!(crate::setup::SecretInt));
+        assert!(!is_display!(crate::range_proof::FirstRound));
+        assert!(!is_display!(crate::offline::OfflineState));
+
+        let secret = Secret::new(BigInt::from(0x5ec2e7u32));
+        assert_eq!(format!("{:?}", secret), "Secret(<redacted>)");
+        assert_eq!(format!("{:?}", Secret::new(String::from("hunter2"))), "Secret(<redacted>)");
+        assert!(!format!("{:?}", Some(&secret)).contains(&0x5ec2e7u32.to_string()));
+    }
+
+    // Purpose: ensure zeroize overwrites the buffers a Secret exposes, not just its length
+    // Params: byte, text and array secrets read back through their buffer after zeroize;
+    //         BigInt and BigUint of 2048 bits, negative and zero
+    // Output: the whole capacity of the byte and text buffers is zero; the integers are zero
+    // Usage: `cargo test -- src::secret` or `cargo test`
+    #[test]
+    fn zeroize_wipes_exposed_buffers() {
+        let mut bytes = Secret::new(vec![0xa5u8; 48]);
+        let (ptr, capacity) = (bytes.expose().as_ptr(), bytes.expose().capacity());
+        bytes.zeroize();
+        assert!(bytes.expose().is_empty());
+        // the allocation is still owned by `bytes`, and zeroize wrote every byte of it
+        assert!(unsafe { std::slice::from_raw_parts(ptr, capacity) }.iter().all(|&b| b == 0));
+
+        let mut text = Secret::new(String::from("correct horse battery staple"));
+        let (ptr, capacity) = (text.expose().as_ptr(), text.expose().capacity());
+        text.zeroize();
+        assert!(unsafe { std::slice::from_raw_parts(ptr, capacity) }.iter().all(|&b| b == 0));
+
+        let mut key = Secret::new([0x3cu8; 32]);
+        key.zeroize();
+        assert_eq!(key.expose(), &[0u8; 32]);
+
+        let mut lines = Secret::new(vec![String::from("00ff"), String::from("1234")]);
+        lines.zeroize();
+        assert!(lines.expose().is_empty());
+
+        let big: BigInt = (BigInt::from(1) << 2047) + 12345;
+        for x in [big.clone(), -big, BigInt::zero()] {
+            let mut secret = Secret::new(x);
+            secret.zeroize();
+            assert!(secret.expose().is_zero());
+        }
+        let mut magnitude = Secret::new((BigUint::from(1u32) << 2047u32) - 1u32);
+        magnitude.zeroize();
+        assert!(magnitude.expose().is_zero() && magnitude.expose().bits() == 0);
+
+        let mut vector = Secret::new(vec![BigInt::from(7), BigInt::from(-9)]);
+        vector.zeroize();
+        assert!(vector.expose().is_empty());
+    }
+
+    /// Records whether it was wiped in a flag that outlives it; a clone gets a flag of its own
+    #[cfg(feature = "zeroize")]
+    struct Tracked(Rc<Cell<bool>>);
+
+    #[cfg(feature = "zeroize")]
+    impl Wipe for Tracked {
+        fn wipe(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            Tracked(Rc::new(Cell::new(false)))
+        }
+    }
+
+    // Purpose: ensure a Secret wipes itself when dropped, clones included
+    // Params: a Secret over a type recording its wipe, dropped and cloned
+    // Output: the original and the clone are each wiped exactly when they drop
+    // Usage: `cargo test -- src::secret` or `cargo test`
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn drop_wipes() {
+        let wiped = Rc::new(Cell::new(false));
+        let secret = Secret::new(Tracked(wiped.clone()));
+        let copy = secret.clone();
+        let copy_wiped = copy.expose().0.clone();
+        drop(secret);
+        assert!(wiped.get() && !copy_wiped.get());
+        drop(copy);
+        assert!(copy_wiped.get());
+    }
+}
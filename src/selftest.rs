@@ -0,0 +1,120 @@
+//! Health checks for a deployed binary and its params, run by `cuproof selftest`
+//!
+//! One commitment and one proof for a random value, so the whole run stays within a few seconds
+//! at 2048 bits: the proof must verify, survive an encode / decode round trip and be rejected
+//! once corrupted. The Fiat-Shamir transcript is checked against fiat_shamir::KNOWN_ANSWERS.
+
+use std::time::{Duration, Instant};
+use num_bigint::BigInt;
+use rand::{CryptoRng, RngCore};
+use crate::commitment::commit;
+use crate::fiat_shamir::{check_known_answers, KNOWN_ANSWERS};
+use crate::range_proof::{prove, random_blinding, Cuproof};
+use crate::setup::{validate_params, Params};
+use crate::util::ReadLimits;
+use crate::verify::{verify, cuproof_verify_checked, cuproof_verify_with_range};
+
+/// The outcome of one check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    /// Err with what went wrong
+    pub result: Result<(), String>,
+    pub elapsed: Duration,
+}
+
+/// Run every check against `params`, drawing the value, range and blinding from `rng`
+/// - returns: the checks in the order they ran; those needing the proof fail with it when proving fails
+/// - usage: `cuproof selftest`; insecure_test_setup(512) stands in for params the caller has none of
+pub fn run_selftest<R: RngCore + CryptoRng>(params: &Params, rng: &mut R) -> Vec<Check> {
+    let mut checks = vec![
+        timed("params", || validate_params(params).map_err(|e| e.to_string())),
+        timed("transcript known answers", || {
+            check_known_answers().map_err(|i| format!("vector {} of {} hashes differently", i, KNOWN_ANSWERS.len()))
+        }),
+    ];
+    let v = BigInt::from(rng.next_u64());
+    let (a, b) = (&v - rng.next_u32() - 1u32, &v + rng.next_u32() + 1u32);
+    let r = random_blinding(rng);
+    checks.push(timed("commitment", || {
+        let c = commit(params, &v, &r);
+        if c != commit(params, &v, &r) { return Err("commit is not deterministic".to_string()); }
+        if c == commit(params, &(&v + 1u32), &r) || c == commit(params, &v, &(&r + 1u32)) {
+            return Err("commitments to other openings collide".to_string());
+        }
+        Ok(())
+    }));
+    let started = Instant::now();
+    let proved = prove(&v, &r, &a, &b, params);
+    checks.push(Check { name: "prove", result: proved.as_ref().map(|_| ()).map_err(|e| e.to_string()), elapsed: started.elapsed() });
+    let proof = match proved {
+        Ok(proof) => proof,
+        Err(e) => {
+            for name in ["verify", "encoding round trip", "tampered proofs rejected"] {
+                checks.push(Check { name, result: Err(format!("no proof: {}", e)), elapsed: Duration::ZERO });
+            }
+            return checks;
+        }
+    };
+    checks.push(timed("verify", || {
+        cuproof_verify_checked(&proof, params).map_err(|e| e.to_string())?;
+        if !cuproof_verify_with_range(&proof, params, &a, &b) { return Err("the proof fails for its own range".to_string()); }
+        if proof.C != commit(params, &v, &r) { return Err("the proof is for another commitment".to_string()); }
+        Ok(())
+    }));
+    checks.push(timed("encoding round trip", || {
+        let decoded = Cuproof::from_bytes(&proof.to_bytes(), &ReadLimits::default()).map_err(|e| e.to_string())?;
+        if decoded.to_bytes() != proof.to_bytes() { return Err("the decoded proof encodes differently".to_string()); }
+        if !verify(&decoded, params) { return Err("the decoded proof does not verify".to_string()); }
+        Ok(())
+    }));
+    checks.push(timed("tampered proofs rejected", || {
+        let tampered: [(_, fn(&mut Cuproof)); 4] = [
+            ("t_hat", |p| p.t_hat += 1),
+            ("T1", |p| p.T1 += 1),
+            ("t0", |p| p.t0 -= 1),
+            ("C", |p| p.C += 1),
+        ];
+        for (field, corrupt) in tampered {
+            let mut bad = proof.clone();
+            corrupt(&mut bad);
+            if verify(&bad, params) { return Err(format!("a proof with {} altered verifies", field)); }
+        }
+        if cuproof_verify_with_range(&proof, params, &(&a - 1u32), &b) {
+            return Err("the proof verifies for a range it does not claim".to_string());
+        }
+        Ok(())
+    }));
+    checks
+}
+
+fn timed(name: &'static str, f: impl FnOnce() -> Result<(), String>) -> Check {
+    let started = Instant::now();
+    let result = f();
+    Check { name, result, elapsed: started.elapsed() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+    use rand::SeedableRng;
+
+    // Purpose: ensure every check passes on sound params and the params check catches broken ones
+    // Params: insecure 512-bit params with a seeded rng; a copy with g = h
+    // Output: seven passing checks in order; with g = h the params check fails
+    // Usage: `cargo test -- src::selftest` or `cargo test`
+    #[test]
+    fn checks_pass_on_sound_params() {
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(163);
+        let params = insecure_test_setup(512);
+        let checks = run_selftest(&params, &mut rng);
+        let names: Vec<_> = checks.iter().map(|c| c.name).collect();
+        assert_eq!(names, ["params", "transcript known answers", "commitment", "prove", "verify", "encoding round trip", "tampered proofs rejected"]);
+        for c in &checks { assert_eq!(c.result, Ok(()), "{}", c.name); }
+
+        let broken = Params::new(params.g().clone(), params.g().clone(), params.n().clone(), params.bits());
+        let checks = run_selftest(&broken, &mut rng);
+        assert!(checks[0].result.is_err());
+    }
+}
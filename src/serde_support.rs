@@ -0,0 +1,508 @@
+use num_bigint::BigInt;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use crate::range_proof::IPPProof;
+#[cfg(feature = "json")]
+use crate::range_proof::Cuproof;
+use crate::setup::{validate_params, Params, PARAMS_VERSION};
+use crate::util::{check_header_nonzero, check_ipp_lengths, check_proof_int};
+#[cfg(feature = "json")]
+use crate::util::{bigint_from_hex_strict, bigint_to_hex_padded};
+
+/// A BigInt as `-`-prefixed lowercase hex of its magnitude in human-readable formats (JSON, TOML),
+/// and as its minimal two's complement big-endian bytes in binary ones (bincode length-prefixes them)
+///
+/// Decoding accepts only the encoding `serialize` produces, so every value has exactly one wire
+/// form, and rejects values above `util::MAX_PROOF_INT_BYTES`
+pub(crate) mod bigint {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(x: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let (sign, magnitude) = x.to_bytes_be();
+            let prefix = if sign == num_bigint::Sign::Minus { "-" } else { "" };
+            serializer.serialize_str(&format!("{}{}", prefix, hex::encode(magnitude)))
+        } else {
+            serializer.serialize_bytes(&x.to_signed_bytes_be())
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        let x = if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            parse_canonical_hex(&s).map_err(de::Error::custom)?
+        } else {
+            let bytes = deserializer.deserialize_byte_buf(ByteBufVisitor)?;
+            let x = BigInt::from_signed_bytes_be(&bytes);
+            if bytes.is_empty() || x.to_signed_bytes_be() != bytes {
+                return Err(de::Error::custom("integer bytes are not minimal two's complement"));
+            }
+            x
+        };
+        check_proof_int(&x).map_err(de::Error::custom)?;
+        Ok(x)
+    }
+
+    /// deserialize for A, S, T1 and T2, which must also be non-zero
+    pub fn deserialize_nonzero<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        let x = deserialize(deserializer)?;
+        check_header_nonzero(&x).map_err(de::Error::custom)?;
+        Ok(x)
+    }
+
+    pub(crate) fn parse_canonical_hex(s: &str) -> Result<BigInt, &'static str> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        if digits.is_empty() || digits.len() % 2 != 0 { return Err("hex integer must have an even, non-zero number of digits"); }
+        if digits.bytes().any(|c| !matches!(c, b'0'..=b'9' | b'a'..=b'f')) { return Err("hex integer must be lowercase hex"); }
+        let bytes = hex::decode(digits).map_err(|_| "invalid hex integer")?;
+        if bytes.len() > 1 && bytes[0] == 0 { return Err("hex integer has a leading zero byte"); }
+        if negative && bytes == [0] { return Err("negative zero"); }
+        let magnitude = BigInt::from_bytes_be(num_bigint::Sign::Plus, &bytes);
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+
+    struct ByteBufVisitor;
+
+    impl<'de> de::Visitor<'de> for ByteBufVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("big-endian integer bytes")
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> { Ok(v.to_vec()) }
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> { Ok(v) }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes = Vec::new();
+            while let Some(b) = seq.next_element()? {
+                if bytes.len() > crate::util::MAX_PROOF_INT_BYTES { return Err(de::Error::custom("integer exceeds MAX_PROOF_INT_BYTES")); }
+                bytes.push(b);
+            }
+            Ok(bytes)
+        }
+    }
+}
+
+/// `bigint` for vectors
+pub(crate) mod bigint_vec {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Wire(#[serde(with = "super::bigint")] BigInt);
+
+    pub fn serialize<S: Serializer>(xs: &[BigInt], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(xs.iter().map(|x| Wire(x.clone())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<BigInt>, D::Error> {
+        Ok(Vec::<Wire>::deserialize(deserializer)?.into_iter().map(|w| w.0).collect())
+    }
+}
+
+/// An unsigned counter as a canonical decimal string (no sign, no leading zeros), so JSON documents
+/// never hold a number that a reader with 53-bit floats would round
+pub(crate) mod decimal {
+    use super::*;
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T: Display, S: Serializer>(x: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(x)
+    }
+
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) || (s.len() > 1 && s.starts_with('0')) {
+            return Err(de::Error::custom("expected a canonical decimal string"));
+        }
+        s.parse().map_err(|_| de::Error::custom("decimal string out of range"))
+    }
+}
+
+/// Wire form of IPPProof; converting it back runs the length checks of load_proof
+#[derive(Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub(crate) struct IppProofWire {
+    #[serde(with = "bigint_vec")]
+    L: Vec<BigInt>,
+    #[serde(with = "bigint_vec")]
+    R: Vec<BigInt>,
+    #[serde(with = "bigint")]
+    a: BigInt,
+    #[serde(with = "bigint")]
+    b: BigInt,
+}
+
+impl From<IPPProof> for IppProofWire {
+    fn from(p: IPPProof) -> Self {
+        IppProofWire { L: p.L, R: p.R, a: p.a, b: p.b }
+    }
+}
+
+impl TryFrom<IppProofWire> for IPPProof {
+    type Error = &'static str;
+
+    fn try_from(w: IppProofWire) -> Result<Self, Self::Error> {
+        check_ipp_lengths(w.L.len(), w.R.len())?;
+        Ok(IPPProof { L: w.L, R: w.R, a: w.a, b: w.b })
+    }
+}
+
+/// Wire form of Params: the public values save_params writes, without the metadata section
+/// Converting it back runs the checks of load_params; the `allow_insecure_params`
+/// acknowledgement is not carried, like in params files
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ParamsWire {
+    #[serde(with = "bigint")]
+    g: BigInt,
+    #[serde(with = "bigint")]
+    h: BigInt,
+    #[serde(with = "bigint")]
+    n: BigInt,
+    bits: usize,
+    version: u32,
+    #[serde(default)]
+    generator_seed: Option<String>,
+    #[serde(default)]
+    epoch: u64,
+    #[serde(default)]
+    insecure: bool,
+}
+
+impl From<Params> for ParamsWire {
+    fn from(p: Params) -> Self {
+        ParamsWire {
+            g: p.g().clone(),
+            h: p.h().clone(),
+            n: p.n().clone(),
+            bits: p.bits(),
+            version: p.version(),
+            generator_seed: p.generator_seed().map(str::to_string),
+            epoch: p.epoch(),
+            insecure: p.is_insecure(),
+        }
+    }
+}
+
+impl TryFrom<ParamsWire> for Params {
+    type Error = String;
+
+    fn try_from(w: ParamsWire) -> Result<Self, Self::Error> {
+        if w.version != PARAMS_VERSION { return Err(format!("unsupported params version {}", w.version)); }
+        if (w.n.bits() as usize) < w.bits { return Err("modulus smaller than declared size".to_string()); }
+        let params = Params::new(w.g, w.h, w.n, w.bits).with_generator_seed(w.generator_seed).with_epoch(w.epoch);
+        let params = if w.insecure { params.mark_insecure() } else { params };
+        validate_params(&params).map_err(|e| e.to_string())?;
+        Ok(params)
+    }
+}
+
+/// Version of the JSON documents written by util::save_proof_json and util::save_params_json
+/// Version 1 documents, whose group elements are minimal `bigint` hex, still load
+#[cfg(feature = "json")]
+pub(crate) const JSON_FORMAT_VERSION: u32 = 2;
+
+#[cfg(feature = "json")]
+const JSON_PROOF_FORMAT: &str = "cuproof-proof";
+
+#[cfg(feature = "json")]
+const JSON_PARAMS_FORMAT: &str = "cuproof-params";
+
+/// Check the `format` and `version` fields every JSON document starts with
+#[cfg(feature = "json")]
+fn check_json_header(format: &str, expected: &str, version: u32) -> Result<(), String> {
+    if format != expected { return Err(format!("expected a {} document, found {:?}", expected, format)); }
+    if !(1..=JSON_FORMAT_VERSION).contains(&version) { return Err(format!("unsupported JSON format version {}", version)); }
+    Ok(())
+}
+
+/// A group element of a JSON document: lowercase hex padded to the byte length of a `bits`-bit
+/// modulus (util::bigint_to_hex_padded), so every element string of a document has the same length
+#[cfg(feature = "json")]
+fn element_to_json(x: &BigInt, bits: usize, field: &str) -> Result<String, String> {
+    bigint_to_hex_padded(x, bits.div_ceil(8)).map_err(|e| format!("{}: {}", field, e))
+}
+
+/// Inverse of element_to_json; version 1 documents hold canonical `bigint` hex instead
+/// - returns: the element, or an error naming `field`; runs the check_proof_int size limit
+#[cfg(feature = "json")]
+fn element_from_json(s: &str, version: u32, bits: usize, field: &str) -> Result<BigInt, String> {
+    let parsed = if version == 1 {
+        bigint::parse_canonical_hex(s).map_err(str::to_string)
+    } else if s.bytes().any(|c| c.is_ascii_uppercase()) {
+        Err("group element must be lowercase hex".to_string())
+    } else {
+        bigint_from_hex_strict(s, bits.div_ceil(8)).map_err(|e| e.to_string())
+    };
+    let x = parsed.map_err(|e| format!("{}: {}", field, e))?;
+    check_proof_int(&x).map_err(|e| format!("{}: {}", field, e))?;
+    Ok(x)
+}
+
+/// element_from_json for A, S, T1 and T2, which must also be non-zero
+#[cfg(feature = "json")]
+fn nonzero_element_from_json(s: &str, version: u32, bits: usize, field: &str) -> Result<BigInt, String> {
+    let x = element_from_json(s, version, bits, field)?;
+    check_header_nonzero(&x).map_err(|e| format!("{}: {}", field, e))?;
+    Ok(x)
+}
+
+/// JSON proof document, schema version 2:
+///
+/// ```text
+/// { "format": "cuproof-proof", "version": "2", "bits": "<modulus bits>", "epoch": "<generator epoch>",
+///   "A": "<element>", "S", "T1", "T2": "<element>", "tau_x", "mu", "t_hat": "<hex>",
+///   "C", "C_v1", "C_v2": "<element>", "t0", "t1", "t2", "tau1", "tau2": "<hex>",
+///   "ipp": { "L": ["<element>", ...], "R": ["<element>", ...], "a": "<hex>", "b": "<hex>" } }
+/// ```
+///
+/// Every value is a string: group elements are lowercase hex padded to `2 * ceil(bits / 8)` digits,
+/// other integers are the canonical hex of `bigint` and counters are `decimal`. Version 1 wrote
+/// group elements as canonical hex too and is still read. Decoding rejects unknown fields and runs
+/// the checks of load_proof
+#[cfg(feature = "json")]
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(non_snake_case)]
+pub(crate) struct ProofJson {
+    format: String,
+    #[serde(with = "decimal")]
+    version: u32,
+    #[serde(with = "decimal")]
+    bits: usize,
+    #[serde(with = "decimal")]
+    epoch: u64,
+    A: String,
+    S: String,
+    T1: String,
+    T2: String,
+    #[serde(with = "bigint")]
+    tau_x: BigInt,
+    #[serde(with = "bigint")]
+    mu: BigInt,
+    #[serde(with = "bigint")]
+    t_hat: BigInt,
+    C: String,
+    C_v1: String,
+    C_v2: String,
+    #[serde(with = "bigint")]
+    t0: BigInt,
+    #[serde(with = "bigint")]
+    t1: BigInt,
+    #[serde(with = "bigint")]
+    t2: BigInt,
+    #[serde(with = "bigint")]
+    tau1: BigInt,
+    #[serde(with = "bigint")]
+    tau2: BigInt,
+    ipp: IppJson,
+}
+
+/// The `ipp` object of ProofJson
+#[cfg(feature = "json")]
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(non_snake_case)]
+struct IppJson {
+    L: Vec<String>,
+    R: Vec<String>,
+    #[serde(with = "bigint")]
+    a: BigInt,
+    #[serde(with = "bigint")]
+    b: BigInt,
+}
+
+#[cfg(feature = "json")]
+impl ProofJson {
+    /// - returns: the document, or an error naming the first group element that is negative or
+    ///   does not fit in `bits` bits
+    pub(crate) fn new(proof: &Cuproof, bits: usize) -> Result<Self, String> {
+        let p = proof.clone();
+        let element = |x: &BigInt, field: &str| element_to_json(x, bits, field);
+        let elements = |xs: &[BigInt], field: &str| xs.iter().map(|x| element(x, field)).collect::<Result<Vec<_>, _>>();
+        Ok(ProofJson {
+            format: JSON_PROOF_FORMAT.to_string(), version: JSON_FORMAT_VERSION, bits, epoch: p.epoch,
+            A: element(&p.A, "A")?, S: element(&p.S, "S")?, T1: element(&p.T1, "T1")?, T2: element(&p.T2, "T2")?,
+            tau_x: p.tau_x, mu: p.mu, t_hat: p.t_hat,
+            C: element(&p.C, "C")?, C_v1: element(&p.C_v1, "C_v1")?, C_v2: element(&p.C_v2, "C_v2")?,
+            t0: p.t0, t1: p.t1, t2: p.t2, tau1: p.tau1, tau2: p.tau2,
+            ipp: IppJson { L: elements(&p.ipp_proof.L, "L")?, R: elements(&p.ipp_proof.R, "R")?, a: p.ipp_proof.a, b: p.ipp_proof.b },
+        })
+    }
+
+    pub(crate) fn into_proof(self) -> Result<Cuproof, String> {
+        check_json_header(&self.format, JSON_PROOF_FORMAT, self.version)?;
+        let (version, bits) = (self.version, self.bits);
+        let element = |s: &str, field: &str| element_from_json(s, version, bits, field);
+        let nonzero = |s: &str, field: &str| nonzero_element_from_json(s, version, bits, field);
+        let ProofJson { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp, epoch, .. } = self;
+        check_ipp_lengths(ipp.L.len(), ipp.R.len())?;
+        let ipp_proof = IPPProof {
+            L: ipp.L.iter().map(|x| element(x, "L")).collect::<Result<_, _>>()?,
+            R: ipp.R.iter().map(|x| element(x, "R")).collect::<Result<_, _>>()?,
+            a: ipp.a,
+            b: ipp.b,
+        };
+        Ok(Cuproof {
+            A: nonzero(&A, "A")?, S: nonzero(&S, "S")?, T1: nonzero(&T1, "T1")?, T2: nonzero(&T2, "T2")?,
+            tau_x, mu, t_hat, C: element(&C, "C")?, C_v1: element(&C_v1, "C_v1")?, C_v2: element(&C_v2, "C_v2")?,
+            t0, t1, t2, tau1, tau2, ipp_proof, epoch, statement: None, assist: None,
+        })
+    }
+}
+
+/// JSON params document, schema version 2:
+///
+/// ```text
+/// { "format": "cuproof-params", "version": "2", "bits": "<modulus bits>",
+///   "g": "<element>", "h": "<element>", "n": "<element>",
+///   "generator_seed": "<label>" (omitted for params without one), "epoch": "<generator epoch>",
+///   "insecure": true (omitted unless set) }
+/// ```
+///
+/// g, h and n are padded to `2 * ceil(bits / 8)` digits like the group elements of ProofJson
+/// (canonical hex in version 1). Like ParamsWire it carries neither the metadata section nor the
+/// `allow_insecure_params` acknowledgement. Converting it back does not validate; load_params does
+/// that for every format
+#[cfg(feature = "json")]
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ParamsJson {
+    format: String,
+    #[serde(with = "decimal")]
+    version: u32,
+    #[serde(with = "decimal")]
+    bits: usize,
+    g: String,
+    h: String,
+    n: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generator_seed: Option<String>,
+    #[serde(with = "decimal")]
+    epoch: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    insecure: bool,
+}
+
+#[cfg(feature = "json")]
+impl ParamsJson {
+    /// - returns: the document, or an error if n is wider than `params.bits()` bits
+    pub(crate) fn new(params: &Params) -> Result<Self, String> {
+        let bits = params.bits();
+        Ok(ParamsJson {
+            format: JSON_PARAMS_FORMAT.to_string(),
+            version: JSON_FORMAT_VERSION,
+            bits,
+            g: element_to_json(params.g(), bits, "g")?,
+            h: element_to_json(params.h(), bits, "h")?,
+            n: element_to_json(params.n(), bits, "n")?,
+            generator_seed: params.generator_seed().map(str::to_string),
+            epoch: params.epoch(),
+            insecure: params.is_insecure(),
+        })
+    }
+
+    pub(crate) fn into_params(self) -> Result<Params, String> {
+        check_json_header(&self.format, JSON_PARAMS_FORMAT, self.version)?;
+        let element = |s: &str, field: &str| element_from_json(s, self.version, self.bits, field);
+        let (g, h, n) = (element(&self.g, "g")?, element(&self.h, "h")?, element(&self.n, "n")?);
+        let params = Params::new(g, h, n, self.bits).with_generator_seed(self.generator_seed).with_epoch(self.epoch);
+        Ok(if self.insecure { params.mark_insecure() } else { params })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::range_proof::{prove, Cuproof};
+    use crate::setup::{insecure_test_setup, Params};
+    use crate::verify::verify;
+    use crate::util::random_bigint;
+    use num_bigint::BigInt;
+
+    type Edit<'a> = &'a dyn Fn(&mut serde_json::Value);
+
+    fn sample() -> (Params, Cuproof) {
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(42), &random_bigint(128), &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+        (params, proof)
+    }
+
+    // Purpose: ensure proofs and params survive serde_json and bincode unchanged
+    // Params: insecure 512-bit params and an honest proof
+    // Output: decoded params equal the original; decoded proofs re-encode identically and verify;
+    //         JSON integers are canonical hex strings
+    // Usage: `cargo test --features serde -- src::serde_support`
+    #[test]
+    fn round_trips_through_json_and_bincode() {
+        let (params, proof) = sample();
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["A"], serde_json::Value::String(crate::util::bigint_to_hex(&proof.A)));
+        let decoded: Cuproof = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+        assert!(verify(&decoded, &params));
+
+        let bytes = bincode::serialize(&proof).unwrap();
+        let decoded: Cuproof = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+        assert!(verify(&decoded, &params));
+
+        let negative = BigInt::from(-0x1234);
+        let mut signed = proof.clone();
+        signed.mu = negative.clone();
+        assert_eq!(serde_json::from_str::<Cuproof>(&serde_json::to_string(&signed).unwrap()).unwrap().mu, negative);
+        assert_eq!(bincode::deserialize::<Cuproof>(&bincode::serialize(&signed).unwrap()).unwrap().mu, negative);
+
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(serde_json::from_str::<Params>(&json).unwrap(), params);
+        assert_eq!(bincode::deserialize::<Params>(&bincode::serialize(&params).unwrap()).unwrap(), params);
+        let rotated = params.rotate_generators("serde");
+        assert_eq!(serde_json::from_str::<Params>(&serde_json::to_string(&rotated).unwrap()).unwrap(), rotated);
+    }
+
+    // Purpose: ensure decoding applies the load_proof / load_params checks instead of trusting the wire
+    // Params: JSON encodings of an honest proof and params, edited field by field
+    // Output: each malformed document is rejected with an error naming the problem
+    // Usage: `cargo test --features serde -- src::serde_support`
+    #[test]
+    fn rejects_malformed_json() {
+        let (params, proof) = sample();
+        let json: serde_json::Value = serde_json::to_value(&proof).unwrap();
+        let decode = |edit: Edit| {
+            let mut v = json.clone();
+            edit(&mut v);
+            serde_json::from_value::<Cuproof>(v).map(|_| ()).map_err(|e| e.to_string())
+        };
+        assert!(decode(&|_| {}).is_ok());
+        let cases: Vec<(Edit, &str)> = vec![
+            (&|v| v["A"] = "00".into(), "zero scalar"),
+            (&|v| v["mu"] = "0x12".into(), "lowercase hex"),
+            (&|v| v["mu"] = "ABCD".into(), "lowercase hex"),
+            (&|v| v["mu"] = "abc".into(), "even"),
+            (&|v| v["mu"] = "00ab".into(), "leading zero"),
+            (&|v| v["mu"] = "-00".into(), "negative zero"),
+            (&|v| v["mu"] = 5.into(), "invalid type"),
+            (&|v| v["mu"] = "ab".repeat(crate::util::MAX_PROOF_INT_BYTES + 1).into(), "MAX_PROOF_INT_BYTES"),
+            (&|v| v["ipp_proof"]["L"] = serde_json::json!([]), "L length"),
+            (&|v| { v["ipp_proof"]["R"].as_array_mut().unwrap().pop(); }, "mismatch"),
+            (&|v| { v.as_object_mut().unwrap().remove("C"); }, "missing field"),
+        ];
+        for (edit, expected) in cases {
+            let err = decode(edit).unwrap_err();
+            assert!(err.contains(expected), "{} does not mention {}", err, expected);
+        }
+        assert!(serde_json::from_str::<Cuproof>("{\"A\": \"01\"").is_err());
+
+        let mut bad = serde_json::to_value(&params).unwrap();
+        bad["g"] = "01".into();
+        assert!(serde_json::from_value::<Params>(bad).unwrap_err().to_string().contains("generator g"));
+        let mut bad = serde_json::to_value(&params).unwrap();
+        bad["version"] = 99.into();
+        assert!(serde_json::from_value::<Params>(bad).is_err());
+    }
+}
@@ -1,125 +1,1411 @@
-use num_bigint::{BigInt, RandBigInt, Sign, BigUint};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+use num_bigint::{BigInt, Sign, BigUint};
 use num_traits::{Signed, Zero, One};
 use num_integer::Integer;
-use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::{rngs::OsRng, RngCore, SeedableRng};
+#[cfg(feature = "std")]
+use rand_chacha::ChaCha20Rng;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::{OnceLock, RwLock};
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+#[cfg(feature = "std")]
+use std::time::Instant;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+#[cfg(feature = "std")]
+use zeroize::Zeroizing;
+#[cfg(feature = "std")]
+use crate::primality::{is_probable_prime, provable_prime, PrimalityAttestation, PrimalityConfig};
+#[cfg(feature = "std")]
+use crate::setup_transcript::SetupTranscript;
+use crate::metadata::ParamsMetadata;
+use crate::secret::Wipe;
+use crate::util::ct_eq_magnitudes;
+use crate::montgomery::Montgomery;
+#[cfg(feature = "std")]
+use crate::workers::Workers;
+#[cfg(feature = "std")]
+use crate::trace::{traced, SetupPhases};
 
-fn miller_rabin(n: &BigUint, k: u32) -> bool {
-    if *n < BigUint::from(2u32) { return false; }
-    // small primes quick check
-    for p in [2u32,3,5,7,11,13,17,19,23,29,31,37] {
-        let p_b = BigUint::from(p);
-        if &p_b == n { return true; }
-        if n % &p_b == BigUint::zero() { return false; }
-    }
+/// Draw one random prime candidate with the top two bits set (so p*q has exactly 2*bits bits) and odd
+/// Reducing the random bytes mod `high` is unbiased because `high` is a power of two dividing
+/// 2^(8 * buf.len()), so this does not go through util::random_below and keeps the digits in
+/// zeroized buffers instead
+#[cfg(feature = "std")]
+fn random_candidate<R: RngCore>(bits: usize, rng: &mut R) -> SecretInt {
+    let high = BigUint::one() << (bits.saturating_sub(1) as u32);
+    let second = BigUint::one() << (bits.saturating_sub(2) as u32);
+    let mut buf = Zeroizing::new(vec![0u8; bits.saturating_sub(1) / 8 + 1]);
+    rng.fill_bytes(&mut buf);
+    let lower = SecretInt::new(BigUint::from_bytes_be(&buf));
+    let mut cand = SecretInt::new(high.clone() + (lower.expose() % &high));
+    cand.0 |= &second;
+    if cand.expose() % 2u32 == BigUint::zero() { cand.0 += BigUint::one(); }
+    cand
+}
 
-    // write n-1 = d * 2^r
-    let one = BigUint::one();
-    let n_minus_one = n - &one;
-    let mut d = n_minus_one.clone();
-    let mut r = 0u32;
-    while &d % 2u32 == BigUint::zero() { d >>= 1; r += 1; }
-
-    let mut rng = OsRng;
-    'witness: for _ in 0..k {
-        // pick random a in [2, n-2]
-        let two = BigUint::from(2u32);
-        let n_minus_two = n - &two;
-        if n_minus_two <= two { return true; }
-        use rand::RngCore;
-        // sample a by rejection using bytes length
-        let mut a;
-        loop {
-            let mut buf = vec![0u8; n.bits() as usize / 8 + 1];
-            rng.fill_bytes(&mut buf);
-            a = BigUint::from_bytes_be(&buf);
-            a = two.clone() + (a % (&n_minus_two - &two + &one));
-            if a >= two && a <= n_minus_two { break; }
-        }
-
-        // x = a^d mod n
-        let mut x = a.modpow(&d, n);
-        if x == one || x == n_minus_one { continue 'witness; }
-        for _ in 0..(r-1) {
-            x = x.modpow(&two, n);
-            if x == n_minus_one { continue 'witness; }
-        }
-        return false;
-    }
-    true
-}
-
-fn generate_probable_prime(bits: usize) -> BigUint {
-    let mut rng = OsRng;
+/// Primes used to sieve candidates before Miller-Rabin
+#[cfg(feature = "std")]
+const SIEVE_BOUND: u32 = 2000;
+
+/// Trial division of a candidate by the primes below SIEVE_BOUND
+#[cfg(feature = "std")]
+fn passes_sieve(cand: &BigUint) -> bool {
+    static PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+    PRIMES.get_or_init(|| small_primes(SIEVE_BOUND)).iter().all(|&p| !(cand % p).is_zero())
+}
+
+#[cfg(feature = "std")]
+fn search_prime_serial<R: RngCore>(bits: usize, rng: &mut R, testing: SetupPhase, monitor: &mut SetupMonitor, config: &PrimalityConfig) -> SecretInt {
     loop {
-        // ensure top bit set and odd
-        let high = BigUint::one() << (bits.saturating_sub(1) as u32);
-        let lower = BigUint::from_bytes_be(&{
-            let mut buf = vec![0u8; bits.saturating_sub(1) / 8 + 1];
-            use rand::RngCore; rng.fill_bytes(&mut buf); buf
-        });
-        let mut cand = high.clone() + (lower % &high);
-        if &cand % 2u32 == BigUint::zero() { cand += BigUint::one(); }
-        if miller_rabin(&cand, 16) { return cand; }
-    }
-}
-
-pub fn trusted_setup(bits: usize) -> (BigInt, BigInt, BigInt) {
-    let mut rng = OsRng;
-
-    // RSA-style modulus n = p * q where p and q are 1024-bit primes
-    // For 2048-bit modulus, we need 1024-bit primes
-    let prime_bits = 1024; // Fixed: always generate 1024-bit primes
-    let p = generate_probable_prime(prime_bits);
-    let mut q = generate_probable_prime(prime_bits);
-    while q == p { q = generate_probable_prime(prime_bits); }
-    let n_u = &p * &q;
-    let n = BigInt::from_biguint(Sign::Plus, n_u.clone());
-
-    // choose g, h uniformly in Z_n^* (co-prime with n)
+        if let Some(prime) = try_candidate(bits, rng, testing, &monitor.tracker, config) { return prime; }
+        monitor.poll();
+    }
+}
+
+/// Draw and test one candidate; the serial and resumable searches share it so both consume the RNG identically
+#[cfg(feature = "std")]
+fn try_candidate<R: RngCore>(bits: usize, rng: &mut R, testing: SetupPhase, tracker: &SetupTracker, config: &PrimalityConfig) -> Option<SecretInt> {
+    tracker.enter(SetupPhase::Sieving);
+    let cand = random_candidate(bits, rng);
+    tracker.candidate();
+    if !passes_sieve(cand.expose()) { return None; }
+    tracker.enter(testing);
+    is_probable_prime(cand.expose(), config, rng).then_some(cand)
+}
+
+/// Search for a prime with every worker of `pool` testing its own uniformly drawn candidates
+/// Each worker seeds an independent ChaCha20 stream from the OS, so neither the workers nor
+/// successive primes share randomness. The winner is a fresh uniform draw that passed the test,
+/// so racing only decides which worker's candidate is kept, not which candidates are likely.
+/// With a progress callback the search runs on a scoped thread while the caller polls the tracker
+#[cfg(feature = "parallel")]
+fn search_prime_parallel(bits: usize, pool: &rayon::ThreadPool, testing: SetupPhase, monitor: &mut SetupMonitor, config: &PrimalityConfig) -> SecretInt {
+    use rayon::prelude::*;
+    use std::sync::atomic::AtomicBool;
+    let SetupMonitor { tracker, reporter } = monitor;
+    let tracker = &*tracker;
+    let found = AtomicBool::new(false);
+    let search = || pool.install(|| {
+        (0..pool.current_num_threads()).into_par_iter().find_map_any(|_| {
+            let mut rng = ChaCha20Rng::from_rng(OsRng).expect("OS randomness is available");
+            while !found.load(Ordering::Relaxed) {
+                tracker.enter(SetupPhase::Sieving);
+                let mut cand = random_candidate(bits, &mut rng);
+                tracker.candidate();
+                if !passes_sieve(cand.expose()) { continue; }
+                tracker.enter(testing);
+                if is_probable_prime(cand.expose(), config, &mut rng) {
+                    found.store(true, Ordering::Relaxed);
+                    // hand the digits over without copying them; the emptied SecretInt drops here
+                    return Some(std::mem::take(&mut cand.0));
+                }
+            }
+            None
+        })
+    });
+    let prime = match reporter {
+        None => search(),
+        Some(reporter) => std::thread::scope(|scope| {
+            let handle = scope.spawn(search);
+            while !handle.is_finished() {
+                reporter.poll(tracker);
+                std::thread::sleep(PROGRESS_INTERVAL / 4);
+            }
+            handle.join().expect("prime search thread panicked")
+        }),
+    };
+    SecretInt::new(prime.expect("a worker only stops once some worker found a prime"))
+}
+
+/// Phase of a trusted setup run, as reported to the progress callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SetupPhase {
+    /// Drawing random candidates and discarding those with a small factor
+    Sieving,
+    /// Miller-Rabin testing a candidate for p
+    TestingP,
+    /// Miller-Rabin testing a candidate for q
+    TestingQ,
+    /// Hashing g and h into the group
+    DerivingGenerators,
+}
+
+#[cfg(feature = "std")]
+impl SetupPhase {
+    const ALL: [SetupPhase; 4] = [SetupPhase::Sieving, SetupPhase::TestingP, SetupPhase::TestingQ, SetupPhase::DerivingGenerators];
+}
+
+impl fmt::Display for SetupPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SetupPhase::Sieving => "sieving",
+            SetupPhase::TestingP => "testing p",
+            SetupPhase::TestingQ => "testing q",
+            SetupPhase::DerivingGenerators => "deriving generators",
+        })
+    }
+}
+
+/// Progress snapshot passed to the `trusted_setup_with_progress` callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetupProgress {
+    pub phase: SetupPhase,
+    /// Candidates drawn so far across both primes and all workers
+    pub candidates_tested: u64,
+    pub elapsed: Duration,
+}
+
+/// Candidates a trusted setup of `bits` draws on average, for progress estimates
+/// The odd k-bit candidates are prime with probability about 2 / (k ln 2) and n takes two primes of
+/// bits/2 bits; parallel workers draw a few more, since every worker is busy when one finds a prime
+pub fn expected_setup_candidates(bits: usize) -> f64 {
+    bits as f64 * core::f64::consts::LN_2 / 2.0
+}
+
+/// Minimum time between two reports of the same phase
+#[cfg(feature = "std")]
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Counters shared between the prime search workers and the progress reporter
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct SetupTracker {
+    candidates: AtomicU64,
+    phase: AtomicU8,
+    /// Bit i set once phase SetupPhase::ALL[i] has been entered
+    seen: AtomicU8,
+}
+
+#[cfg(feature = "std")]
+impl SetupTracker {
+    fn enter(&self, phase: SetupPhase) {
+        self.phase.store(phase as u8, Ordering::Relaxed);
+        self.seen.fetch_or(1 << phase as u8, Ordering::Relaxed);
+    }
+
+    fn candidate(&self) {
+        self.candidates.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Caller-side half of the progress machinery: turns tracker state into callbacks
+/// Invoked only from the calling thread and only with copies of the counters, so the
+/// callback never sees or touches candidates or RNG state
+#[cfg(feature = "std")]
+struct ProgressReporter<'a> {
+    callback: &'a mut dyn FnMut(SetupProgress),
+    start: Instant,
+    last: Instant,
+    reported: u8,
+}
+
+#[cfg(feature = "std")]
+impl ProgressReporter<'_> {
+    /// Report each newly entered phase once, otherwise the current phase every PROGRESS_INTERVAL
+    fn poll(&mut self, tracker: &SetupTracker) {
+        let now = Instant::now();
+        let seen = tracker.seen.load(Ordering::Relaxed);
+        let fresh = seen & !self.reported;
+        if fresh == 0 && now.duration_since(self.last) < PROGRESS_INTERVAL { return; }
+        let candidates_tested = tracker.candidates.load(Ordering::Relaxed);
+        let elapsed = now.duration_since(self.start);
+        if fresh == 0 {
+            let phase = SetupPhase::ALL[tracker.phase.load(Ordering::Relaxed) as usize];
+            (self.callback)(SetupProgress { phase, candidates_tested, elapsed });
+        }
+        for phase in SetupPhase::ALL.into_iter().filter(|&phase| fresh & (1 << phase as u8) != 0) {
+            (self.callback)(SetupProgress { phase, candidates_tested, elapsed });
+        }
+        self.reported |= seen;
+        self.last = now;
+    }
+}
+
+/// Tracker plus an optional reporter for one setup run
+#[cfg(feature = "std")]
+struct SetupMonitor<'a> {
+    tracker: SetupTracker,
+    reporter: Option<ProgressReporter<'a>>,
+}
+
+#[cfg(feature = "std")]
+impl<'a> SetupMonitor<'a> {
+    fn silent() -> Self {
+        SetupMonitor { tracker: SetupTracker::default(), reporter: None }
+    }
+
+    fn reporting(callback: &'a mut dyn FnMut(SetupProgress)) -> Self {
+        let now = Instant::now();
+        SetupMonitor { tracker: SetupTracker::default(), reporter: Some(ProgressReporter { callback, start: now, last: now, reported: 0 }) }
+    }
+
+    fn poll(&mut self) {
+        if let Some(reporter) = &mut self.reporter { reporter.poll(&self.tracker); }
+    }
+
+    /// Candidates drawn so far, for the `setup phase finished` events
+    fn candidates(&self) -> u64 {
+        self.tracker.candidates.load(Ordering::Relaxed)
+    }
+}
+
+/// A secret non-negative integer (a setup prime or a value derived from one), wiped on drop
+/// Stands in for `Zeroizing<BigInt>`: num-bigint has no zeroize support and the orphan rule
+/// rules out implementing `Zeroize` for `BigInt` here. Deliberately not Clone, and Debug is redacted
+pub struct SecretInt(BigUint);
+
+impl SecretInt {
+    pub fn new(x: BigUint) -> Self {
+        #[cfg(feature = "secret-audit")]
+        secret_audit::LIVE.with(|live| live.set(live.get() + 1));
+        SecretInt(x)
+    }
+
+    /// Take ownership of a signed secret; returns None (after wiping it) if it is negative
+    pub fn from_bigint(x: BigInt) -> Option<Self> {
+        let (sign, magnitude) = x.into_parts();
+        let secret = SecretInt::new(magnitude);
+        if sign == Sign::Minus { None } else { Some(secret) }
+    }
+
+    pub fn expose(&self) -> &BigUint {
+        &self.0
+    }
+
+    pub(crate) fn expose_mut(&mut self) -> &mut BigUint {
+        &mut self.0
+    }
+
+    /// Compare with `other` in time independent of where they differ (see util::ct_eq_bigint)
+    pub fn ct_eq(&self, other: &BigUint) -> bool {
+        ct_eq_magnitudes(&self.0, other, 0)
+    }
+}
+
+impl Zeroize for SecretInt {
+    fn zeroize(&mut self) {
+        self.0.wipe();
+    }
+}
+
+impl Drop for SecretInt {
+    fn drop(&mut self) {
+        self.zeroize();
+        #[cfg(feature = "secret-audit")]
+        secret_audit::LIVE.with(|live| live.set(live.get() - 1));
+    }
+}
+
+impl ZeroizeOnDrop for SecretInt {}
+
+impl fmt::Debug for SecretInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretInt(<redacted>)")
+    }
+}
+
+/// Debug hook counting live `SecretInt`s on the current thread
+/// Setup entry points debug_assert that the count is back to its starting value before they
+/// return, i.e. that no prime or prime-derived intermediate outlives the call
+#[cfg(feature = "secret-audit")]
+pub mod secret_audit {
+    use std::cell::Cell;
+
+    thread_local! {
+        pub(super) static LIVE: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Number of `SecretInt` values currently alive on this thread
+    pub fn live_secrets() -> usize {
+        LIVE.with(Cell::get)
+    }
+}
+
+/// Smallest modulus size accepted without the insecure escape hatch
+pub const MIN_SECURE_MODULUS_BITS: usize = 2048;
+
+/// Modulus sizes accepted by `trusted_setup`
+pub const SUPPORTED_MODULUS_BITS: [usize; 3] = [2048, 3072, 4096];
+
+/// Security level in bits of a factoring-based modulus of `modulus_bits` bits, after NIST SP 800-57
+/// Part 1 table 2: the level of the largest listed size not above it
+/// - returns: 80, 112, 128, 192 or 256; 0 below 1024 bits
+/// - usage: reported by `cuproof setup`; says nothing about params whose factorization is known
+pub fn security_level_bits(modulus_bits: usize) -> usize {
+    const LEVELS: [(usize, usize); 5] = [(15360, 256), (7680, 192), (3072, 128), (2048, 112), (1024, 80)];
+    LEVELS.iter().find(|(bits, _)| modulus_bits >= *bits).map_or(0, |(_, level)| *level)
+}
+
+/// Largest modulus validate_params accepts; its small-factor and perfect-power checks grow with n,
+/// so untrusted files with a huge modulus are refused before they run
+pub const MAX_MODULUS_BITS: usize = 16384;
+
+/// Smallest modulus size accepted even with `allow_insecure` (keeps each prime past the small-prime sieve)
+const MIN_INSECURE_MODULUS_BITS: usize = 128;
+
+/// Errors raised while generating public parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetupError {
+    /// Requested modulus is below `MIN_SECURE_MODULUS_BITS` and `allow_insecure` was not set
+    InsecureModulusSize { bits: usize, min_bits: usize },
+    /// Requested modulus size is not one of the supported sizes (or cannot be split into two primes)
+    UnsupportedModulusSize(usize),
+    /// A supplied prime failed the Miller–Rabin test
+    CompositePrime,
+    /// The two supplied primes are equal
+    EqualPrimes,
+    /// A supplied prime is shorter than half the modulus size
+    UndersizedPrime { bits: usize, expected: usize },
+    /// A supplied prime is longer than half the modulus size
+    OversizedPrime { bits: usize, expected: usize },
+    /// |p - q| is small enough for Fermat factorization
+    PrimesTooClose,
+    /// The modulus (p * q, or an imported n) does not have exactly the requested number of bits
+    ModulusSizeMismatch { bits: usize, expected: usize },
+    /// An RSA private key could not be parsed
+    InvalidKey(String),
+    /// An imported modulus failed `validate_params`
+    InvalidModulus(ParamsError),
+    /// A setup checkpoint could not be read, written or does not match the requested setup
+    Checkpoint(String),
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupError::InsecureModulusSize { bits, min_bits } => {
+                write!(f, "modulus size {} bits is below the secure minimum of {} bits", bits, min_bits)
+            }
+            SetupError::UnsupportedModulusSize(bits) => {
+                write!(f, "unsupported modulus size {} bits (expected one of {:?})", bits, SUPPORTED_MODULUS_BITS)
+            }
+            SetupError::CompositePrime => write!(f, "supplied prime is composite"),
+            SetupError::EqualPrimes => write!(f, "supplied primes are equal"),
+            SetupError::UndersizedPrime { bits, expected } => {
+                write!(f, "supplied prime has {} bits, expected {}", bits, expected)
+            }
+            SetupError::OversizedPrime { bits, expected } => {
+                write!(f, "supplied prime has {} bits, expected {}", bits, expected)
+            }
+            SetupError::PrimesTooClose => write!(f, "supplied primes are too close together"),
+            SetupError::ModulusSizeMismatch { bits, expected } => {
+                write!(f, "modulus has {} bits, expected {}", bits, expected)
+            }
+            SetupError::InvalidKey(msg) => write!(f, "invalid RSA private key: {}", msg),
+            SetupError::InvalidModulus(e) => write!(f, "modulus rejected: {}", e),
+            SetupError::Checkpoint(msg) => write!(f, "setup checkpoint: {}", msg),
+        }
+    }
+}
+
+impl core::error::Error for SetupError {}
+
+/// Check a requested modulus size against the setup policy
+/// - params: bits requested modulus size, allow_insecure escape hatch for tests
+/// - returns: Ok(()) or the SetupError describing the rejection
+/// - usage: shared by trusted_setup and the CLI before doing any prime search
+pub fn check_modulus_bits(bits: usize, allow_insecure: bool) -> Result<(), SetupError> {
+    if SUPPORTED_MODULUS_BITS.contains(&bits) { return Ok(()); }
+    if bits < MIN_SECURE_MODULUS_BITS && !allow_insecure {
+        return Err(SetupError::InsecureModulusSize { bits, min_bits: MIN_SECURE_MODULUS_BITS });
+    }
+    if !allow_insecure || bits < MIN_INSECURE_MODULUS_BITS || !bits.is_multiple_of(2) {
+        return Err(SetupError::UnsupportedModulusSize(bits));
+    }
+    Ok(())
+}
+
+/// Current version of the public parameter layout
+pub const PARAMS_VERSION: u32 = 1;
+
+/// Fixed-base tables: pows[i] = base^(2^i) mod n, kept in Montgomery form so an exponentiation
+/// multiplies table entries into one scratch buffer instead of allocating per exponent bit
+#[derive(Debug)]
+struct PrecomputedTables {
+    mont: Montgomery,
+    /// table_bits entries of `mont.limbs()` words each
+    g_pows: Vec<u64>,
+    h_pows: Vec<u64>,
+}
+
+/// Generator vectors by (label, count), filled by `Params::derive_generators`
+#[cfg(feature = "std")]
+type GeneratorCache = HashMap<(String, usize), Arc<Vec<BigInt>>>;
+
+/// Public parameters of the commitment scheme: generators g, h of Z_n^* and the modulus n
+///
+/// Fields are private so generators can only be reached by name (`g()`, `h()`), which
+/// rules out the positional g/h swaps the tuple-based API allowed. Clones share the
+/// lazily built precomputation tables.
+///
+/// Send + Sync: the tables are built once behind a OnceLock and the generator vectors are cached
+/// behind an RwLock, so one Params (usually in an Arc, see `Verifier::new`) serves every thread.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::serde_support::ParamsWire", try_from = "crate::serde_support::ParamsWire"))]
+pub struct Params {
+    g: BigInt,
+    h: BigInt,
+    n: BigInt,
+    bits: usize,
+    version: u32,
+    generator_seed: Option<String>,
+    epoch: u64,
+    metadata: Option<ParamsMetadata>,
+    insecure: bool,
+    insecure_allowed: bool,
+    /// None for moduli without a Montgomery form (even n, which validate_params rejects)
+    tables: Arc<TablesCell>,
+    #[cfg(feature = "std")]
+    vectors: Arc<RwLock<GeneratorCache>>,
+}
+
+/// Built once per Params and its clones; without std, a OnceBox that lets racing threads build
+/// the tables twice and keeps the first
+#[cfg(feature = "std")]
+type TablesCell = OnceLock<Option<PrecomputedTables>>;
+#[cfg(not(feature = "std"))]
+type TablesCell = once_cell::race::OnceBox<Option<PrecomputedTables>>;
+
+impl Params {
+    /// Assemble parameters from their public components
+    /// - params: g, h generators, n modulus, bits declared modulus size
+    /// - returns: Params at the current PARAMS_VERSION
+    /// - usage: deserialization and setup functions; prefer the setup functions otherwise
+    pub fn new(g: BigInt, h: BigInt, n: BigInt, bits: usize) -> Params {
+        Params {
+            g, h, n, bits,
+            version: PARAMS_VERSION,
+            generator_seed: None,
+            epoch: 0,
+            metadata: None,
+            insecure: false,
+            insecure_allowed: false,
+            tables: Arc::new(TablesCell::new()),
+            #[cfg(feature = "std")]
+            vectors: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Build parameters whose generators are derived transparently from a public seed
+    /// - params: n modulus, bits declared size, seed public derivation seed (see `generator_labels`)
+    /// - returns: Params with g = hash_to_group("<seed>/g/v1", n), h = hash_to_group("<seed>/h/v1", n)
+    /// - usage: all setup functions; nobody can know log_g(h) for hash-derived generators
+    pub fn derive(n: BigInt, bits: usize, seed: &str) -> Params {
+        let (g_label, h_label) = generator_labels(seed);
+        let g = hash_to_group(&g_label, &n);
+        let h = hash_to_group(&h_label, &n);
+        Params { generator_seed: Some(seed.to_string()), ..Params::new(g, h, n, bits) }
+    }
+
+    /// Attach a recorded derivation seed to loaded parameters
+    pub fn with_generator_seed(mut self, seed: Option<String>) -> Params {
+        self.generator_seed = seed;
+        self
+    }
+
+    /// Attach a recorded generator epoch to loaded parameters
+    pub fn with_epoch(mut self, epoch: u64) -> Params {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Attach (or with None, drop) the generation evidence saved alongside the params
+    pub fn with_metadata(mut self, metadata: Option<ParamsMetadata>) -> Params {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Set the INSECURE marker: the factorization of n is reproducible by anyone
+    pub(crate) fn mark_insecure(mut self) -> Params {
+        self.insecure = true;
+        self
+    }
+
+    /// Acknowledge that insecure-marked params are being used on purpose
+    /// - returns: the same params, now accepted by RangeProver / Verifier constructors
+    /// - usage: tests and demos over `insecure_test_setup`; has no effect on unmarked params and
+    ///   is not saved by save_params
+    pub fn allow_insecure_params(mut self) -> Params {
+        self.insecure_allowed = true;
+        self
+    }
+
+    /// Refuse insecure-marked params unless `allow_insecure_params` was called
+    /// - returns: Ok(()) or ParamsError::InsecureParams
+    /// - usage: every RangeProver / Verifier constructor runs it first
+    pub fn insecure_check(&self) -> Result<(), ParamsError> {
+        if self.insecure && !self.insecure_allowed { Err(ParamsError::InsecureParams) } else { Ok(()) }
+    }
+
+    /// Replace g and h while keeping the modulus, for when the old generators must be retired
+    /// - params: label public rotation label, e.g. the date or an incident id
+    /// - returns: Params at epoch + 1 derived as `Params::derive(n, bits, "<label>/epoch/<epoch + 1>")`
+    /// - usage: `params rotate`; proofs record the epoch they were made under, so proofs from the
+    ///   previous epoch stop verifying against the rotated params (and vice versa)
+    pub fn rotate_generators(&self, label: &str) -> Params {
+        let epoch = self.epoch + 1;
+        let seed = format!("{}/epoch/{}", label, epoch);
+        let metadata = self.metadata.as_ref().map(|m| m.rotated(&seed));
+        let rotated = Params::derive(self.n.clone(), self.bits, &seed).with_epoch(epoch).with_metadata(metadata);
+        Params { insecure: self.insecure, ..rotated }
+    }
+
+    pub fn g(&self) -> &BigInt { &self.g }
+    pub fn h(&self) -> &BigInt { &self.h }
+    pub fn n(&self) -> &BigInt { &self.n }
+    pub fn bits(&self) -> usize { self.bits }
+    pub fn version(&self) -> u32 { self.version }
+    pub fn generator_seed(&self) -> Option<&str> { self.generator_seed.as_deref() }
+    /// Number of `rotate_generators` calls since setup; 0 for freshly generated params
+    pub fn epoch(&self) -> u64 { self.epoch }
+    /// How the params were generated, if recorded; see `metadata::validate_metadata`
+    pub fn metadata(&self) -> Option<&ParamsMetadata> { self.metadata.as_ref() }
+    /// True for params from `insecure_test_setup` and the other test setups
+    pub fn is_insecure(&self) -> bool { self.insecure }
+
+    /// Re-derive (g, h) from the recorded seed so auditors can check the generators
+    /// - returns: None when the params carry no derivation seed (e.g. legacy random generators)
+    pub fn rederive_generators(&self) -> Option<(BigInt, BigInt)> {
+        let (g_label, h_label) = generator_labels(self.generator_seed.as_deref()?);
+        Some((hash_to_group(&g_label, &self.n), hash_to_group(&h_label, &self.n)))
+    }
+
+    /// True when g and h are exactly what the recorded seed derives
+    pub fn generators_match_seed(&self) -> bool {
+        self.rederive_generators().is_some_and(|(g, h)| g == self.g && h == self.h)
+    }
+
+    /// SHA-256 over the version, declared size, epoch (when non-zero) and the big-endian bytes of g, h and n
+    /// - returns: 32-byte digest identifying these exact public parameters
+    /// - usage: binds derived generator vectors to the params they belong to; stable across
+    ///   save_params / load_params since the generator seed is not part of it. Epoch 0 is left
+    ///   out so fingerprints of params predating rotation are unchanged
+    pub fn fingerprint(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(b"cuproof/params");
+        hasher.update(self.version.to_be_bytes());
+        hasher.update((self.bits as u64).to_be_bytes());
+        if self.epoch != 0 {
+            hasher.update(b"epoch");
+            hasher.update(self.epoch.to_be_bytes());
+        }
+        for x in [&self.g, &self.h, &self.n] {
+            let (_sign, bytes) = x.to_bytes_be();
+            hasher.update((bytes.len() as u32).to_be_bytes());
+            hasher.update(&bytes);
+        }
+        hasher.finalize().into()
+    }
+
+    /// The fingerprint as a value that displays as 64 lowercase hex digits
+    /// - usage: logs, proof bundles and config files, e.g. `println!("{}", params.fingerprint_hex())`
+    pub fn fingerprint_hex(&self) -> FingerprintHex {
+        FingerprintHex(self.fingerprint())
+    }
+
+    /// Enforce a minimum declared modulus size and the full validate_params suite
+    /// - params: min_bits smallest acceptable `bits()`, e.g. `MIN_SECURE_MODULUS_BITS`
+    /// - returns: Ok(()) or the first ParamsError found (size first)
+    /// - usage: RangeProver::new and Verifier::new run it with MIN_SECURE_MODULUS_BITS
+    pub fn security_check(&self, min_bits: usize) -> Result<(), ParamsError> {
+        if self.bits < min_bits { return Err(ParamsError::ModulusTooSmall { bits: self.bits, min_bits }); }
+        validate_params(self)
+    }
+
+    /// Derive `count` independent generators for vector commitments under `label`
+    /// - params: label domain-separation string (e.g. "agg/g"), count vector length
+    /// - returns: hash_to_group("<fingerprint hex>/<label>/<count>/<i>", n) for i in 0..count
+    /// - usage: aggregated provers and verifiers call this with the same label and count and obtain
+    ///   the same vector without it ever being stored in the params file. Vectors for different
+    ///   labels or counts are derived from distinct inputs, and results are cached per (label, count)
+    ///   and shared with the caller rather than copied; without std nothing is cached
+    pub fn derive_generators(&self, label: &str, count: usize) -> Arc<Vec<BigInt>> {
+        #[cfg(feature = "std")]
+        let key = (label.to_string(), count);
+        #[cfg(feature = "std")]
+        if let Some(cached) = self.vectors.read().expect("generator cache poisoned").get(&key) {
+            return Arc::clone(cached);
+        }
+        let prefix = format!("{}/{}/{}", self.fingerprint_hex(), label, count);
+        let derived: Arc<Vec<BigInt>> = Arc::new((0..count).map(|i| hash_to_group(&format!("{}/{}", prefix, i), &self.n)).collect());
+        #[cfg(feature = "std")]
+        self.vectors.write().expect("generator cache poisoned").insert(key, Arc::clone(&derived));
+        derived
+    }
+
+    /// Exponent bits served from the tables; commitments to t1, t2 reach ~2*bits + 256, IPP
+    /// commitments go beyond and square past the end of the table
+    fn table_bits(&self) -> usize {
+        2 * self.n.bits() as usize + 320
+    }
+
+    fn tables(&self) -> Option<&PrecomputedTables> {
+        let build = || {
+            let mont = Montgomery::new(self.n.magnitude())?;
+            let len = self.table_bits();
+            let g_pows = power_table(&mont, &self.g, len);
+            let h_pows = power_table(&mont, &self.h, len);
+            Some(PrecomputedTables { mont, g_pows, h_pows })
+        };
+        #[cfg(feature = "std")]
+        let tables = self.tables.get_or_init(build);
+        #[cfg(not(feature = "std"))]
+        let tables = self.tables.get_or_init(|| alloc::boxed::Box::new(build()));
+        tables.as_ref()
+    }
+
+    /// g^|m| * h^|r| mod n
+    /// Bits below table_bits multiply table entries into the accumulator; higher bits (the inner
+    /// product argument grows its values each round) continue the table by repeated squaring.
+    /// Either way all arithmetic happens in one buffer allocated up front
+    fn fixed_base_pow(&self, m: &BigInt, r: &BigInt) -> BigInt {
+        let Some(tables) = self.tables() else {
+            let n = &self.n;
+            return self.g.abs().modpow(&m.abs(), n) * self.h.abs().modpow(&r.abs(), n) % n;
+        };
+        let mont = &tables.mont;
+        let k = mont.limbs();
+        let len = tables.g_pows.len() / k;
+        // accumulator, product, running power beyond the table and multiplication scratch
+        let mut buf = vec![0u64; 3 * k + mont.scratch_len()];
+        let (acc, rest) = buf.split_at_mut(k);
+        let (product, rest) = rest.split_at_mut(k);
+        let (power, scratch) = rest.split_at_mut(k);
+        acc.copy_from_slice(mont.one());
+        for (table, e) in [(&tables.g_pows, m), (&tables.h_pows, r)] {
+            power.copy_from_slice(&table[(len - 1) * k..]);
+            let mut squared_to = len - 1;
+            for (word_idx, word) in e.magnitude().iter_u64_digits().enumerate() {
+                let mut w = word;
+                while w != 0 {
+                    let bit = word_idx * 64 + w.trailing_zeros() as usize;
+                    let factor = if bit < len {
+                        &table[bit * k..(bit + 1) * k]
+                    } else {
+                        while squared_to < bit {
+                            mont.mul(power, power, product, scratch);
+                            power.copy_from_slice(product);
+                            squared_to += 1;
+                        }
+                        &*power
+                    };
+                    mont.mul(acc, factor, product, scratch);
+                    acc.copy_from_slice(product);
+                    w &= w - 1;
+                }
+            }
+        }
+        BigInt::from_biguint(Sign::Plus, mont.out_of_mont(acc, scratch))
+    }
+
+    /// g^|e| mod n using the precomputed table (same semantics as commitment::mod_exp)
+    pub fn g_pow(&self, e: &BigInt) -> BigInt {
+        self.fixed_base_pow(e, &BigInt::zero())
+    }
+
+    /// h^|e| mod n using the precomputed table (same semantics as commitment::mod_exp)
+    pub fn h_pow(&self, e: &BigInt) -> BigInt {
+        self.fixed_base_pow(&BigInt::zero(), e)
+    }
+
+    /// g^|m| * h^|r| mod n, equal to `g_pow(m) * h_pow(r) % n` without the intermediate values
+    /// - usage: commitment::commit; a commitment then allocates a few times instead of per exponent bit
+    pub fn gh_pow(&self, m: &BigInt, r: &BigInt) -> BigInt {
+        self.fixed_base_pow(m, r)
+    }
+}
+
+impl fmt::Debug for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Params")
+            .field("g", &self.g)
+            .field("h", &self.h)
+            .field("n", &self.n)
+            .field("bits", &self.bits)
+            .field("version", &self.version)
+            .field("generator_seed", &self.generator_seed)
+            .field("epoch", &self.epoch)
+            .field("metadata", &self.metadata)
+            .field("insecure", &self.insecure)
+            .finish()
+    }
+}
+
+/// Compares the public values only; metadata is evidence about the params, not part of them
+impl PartialEq for Params {
+    fn eq(&self, other: &Self) -> bool {
+        self.g == other.g && self.h == other.h && self.n == other.n
+            && self.bits == other.bits && self.version == other.version
+            && self.generator_seed == other.generator_seed && self.epoch == other.epoch
+            && self.insecure == other.insecure
+    }
+}
+
+impl Eq for Params {}
+
+/// `Params::fingerprint` wrapped for display as lowercase hex
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FingerprintHex(pub [u8; 32]);
+
+impl FingerprintHex {
+    /// The first SHORT_FINGERPRINT_BYTES in hex, for log lines and for reading out to other parties
+    pub fn short(&self) -> String {
+        hex::encode(&self.0[..SHORT_FINGERPRINT_BYTES])
+    }
+}
+
+/// Bytes of the fingerprint shown by `FingerprintHex::short`: 16 hex digits
+pub const SHORT_FINGERPRINT_BYTES: usize = 8;
+
+impl fmt::Display for FingerprintHex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+/// |base|^(2^i) mod n for i < len in Montgomery form, concatenated
+fn power_table(mont: &Montgomery, base: &BigInt, len: usize) -> Vec<u64> {
+    let k = mont.limbs();
+    let mut pows = Vec::with_capacity(len * k);
+    pows.extend_from_slice(&mont.to_mont(base.magnitude()));
+    let mut scratch = vec![0u64; mont.scratch_len()];
+    let mut next = vec![0u64; k];
+    for i in 1..len {
+        let cur = &pows[(i - 1) * k..i * k];
+        mont.mul(cur, cur, &mut next, &mut scratch);
+        pows.extend_from_slice(&next);
+    }
+    pows
+}
+
+/// Generate n = p * q with exactly `bits` bits and generators g, h in Z_n^*
+#[cfg(feature = "std")]
+fn generate_params<R: RngCore>(bits: usize, rng: &mut R) -> Params {
+    generate_params_from(bits, &mut SetupMonitor::silent(), |prime_bits, phase, monitor| search_prime_serial(prime_bits, rng, phase, monitor, &PrimalityConfig::default()), &mut |_, _, _| {})
+}
+
+/// Build n = p * q from two distinct primes of bits/2 bits drawn from `next_prime`
+/// p and q only ever live in `SecretInt`s scoped to this function, so they are wiped before it
+/// returns. `with_factors` sees them once n is known (the setup transcript derives phi(n) there,
+/// inside a SecretInt); only n reaches the returned Params
+#[cfg(feature = "std")]
+fn generate_params_from(
+    bits: usize,
+    monitor: &mut SetupMonitor,
+    mut next_prime: impl FnMut(usize, SetupPhase, &mut SetupMonitor) -> SecretInt,
+    with_factors: &mut dyn FnMut(&SecretInt, &SecretInt, &BigInt),
+) -> Params {
+    #[cfg(feature = "secret-audit")]
+    let live_before = secret_audit::live_secrets();
+    let mut phases = SetupPhases::start(monitor.candidates());
+    let n = {
+        let prime_bits = bits / 2;
+        let p = next_prime(prime_bits, SetupPhase::TestingP, monitor);
+        phases.finish("p", monitor.candidates());
+        let mut q = next_prime(prime_bits, SetupPhase::TestingQ, monitor);
+        while q.ct_eq(p.expose()) { q = next_prime(prime_bits, SetupPhase::TestingQ, monitor); }
+        phases.finish("q", monitor.candidates());
+        let n = BigInt::from_biguint(Sign::Plus, p.expose() * q.expose());
+        with_factors(&p, &q, &n);
+        n
+    };
+    #[cfg(feature = "secret-audit")]
+    debug_assert_eq!(secret_audit::live_secrets(), live_before, "a setup secret outlived generate_params");
+
+    monitor.tracker.enter(SetupPhase::DerivingGenerators);
+    monitor.poll();
+    let params = Params::derive(n, bits, DEFAULT_GENERATOR_SEED);
+    phases.finish("generators", monitor.candidates());
+    params
+}
+
+/// Bound for the trial-division check in `validate_params`
+const SMALL_FACTOR_BOUND: u32 = 10_000;
+
+/// Reasons a parameter set is rejected by `validate_params`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamsError {
+    /// n is even
+    EvenModulus,
+    /// n has fewer bits than the declared modulus size
+    ModulusBelowDeclaredSize { bits: usize, declared: usize },
+    /// n has a prime factor below 10^4
+    SmallFactor(u32),
+    /// n = x^k for some k >= 2
+    PerfectPower { exponent: u32 },
+    /// the named generator is outside [2, n - 1]
+    GeneratorOutOfRange(&'static str),
+    /// the named generator equals n - 1
+    GeneratorIsMinusOne(&'static str),
+    /// the named generator shares a factor with n
+    GeneratorNotCoprime(&'static str),
+    /// g == h
+    EqualGenerators,
+    /// the declared modulus size is below the caller's minimum (see `Params::security_check`)
+    ModulusTooSmall { bits: usize, min_bits: usize },
+    /// the params are marked INSECURE and `allow_insecure_params` was not called
+    InsecureParams,
+    /// n has more than MAX_MODULUS_BITS bits
+    ModulusTooLarge { bits: usize, max_bits: usize },
+    /// a generator vector file was derived from params with another fingerprint
+    GeneratorsForOtherParams,
+    /// entry `index` of a generator vector file differs from what derive_generators produces
+    GeneratorVectorMismatch { index: usize },
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamsError::EvenModulus => write!(f, "modulus is even"),
+            ParamsError::ModulusBelowDeclaredSize { bits, declared } => {
+                write!(f, "modulus has {} bits but {} were declared", bits, declared)
+            }
+            ParamsError::SmallFactor(p) => write!(f, "modulus is divisible by {}", p),
+            ParamsError::PerfectPower { exponent } => write!(f, "modulus is a perfect {}-th power", exponent),
+            ParamsError::GeneratorOutOfRange(name) => write!(f, "generator {} is outside [2, n-1]", name),
+            ParamsError::GeneratorIsMinusOne(name) => write!(f, "generator {} equals n-1", name),
+            ParamsError::GeneratorNotCoprime(name) => write!(f, "generator {} is not coprime to n", name),
+            ParamsError::EqualGenerators => write!(f, "generators g and h are equal"),
+            ParamsError::ModulusTooSmall { bits, min_bits } => {
+                write!(f, "modulus size {} bits is below the required {} bits", bits, min_bits)
+            }
+            ParamsError::InsecureParams => {
+                write!(f, "params are marked INSECURE (test setup); call allow_insecure_params() to use them anyway")
+            }
+            ParamsError::ModulusTooLarge { bits, max_bits } => {
+                write!(f, "modulus has {} bits, more than the {} accepted", bits, max_bits)
+            }
+            ParamsError::GeneratorsForOtherParams => write!(f, "generator vector was derived from other params"),
+            ParamsError::GeneratorVectorMismatch { index } => {
+                write!(f, "generator {} of the vector does not match its derivation", index)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParamsError {}
+
+fn small_primes(bound: u32) -> Vec<u32> {
+    let mut sieve = vec![true; bound as usize + 1];
+    let mut primes = Vec::new();
+    for i in 2..=bound as usize {
+        if sieve[i] {
+            primes.push(i as u32);
+            for j in (i * i..=bound as usize).step_by(i) { sieve[j] = false; }
+        }
+    }
+    primes
+}
+
+/// Structural checks on public parameters
+/// - params: params to check
+/// - returns: Ok(()) or the first ParamsError found (checks run cheapest first)
+/// - usage: called by load_params and the RangeProver/Verifier constructors; catches corrupted or
+///   malicious files (even n, g = 0, h = 1, ...) before they reach proving or verification
+pub fn validate_params(params: &Params) -> Result<(), ParamsError> {
+    let n = params.n();
+    let one = BigInt::one();
+    let n_bits = n.bits() as usize;
+    if n_bits > MAX_MODULUS_BITS { return Err(ParamsError::ModulusTooLarge { bits: n_bits, max_bits: MAX_MODULUS_BITS }); }
+    if n.is_even() { return Err(ParamsError::EvenModulus); }
+    if n_bits < params.bits() {
+        return Err(ParamsError::ModulusBelowDeclaredSize { bits: n_bits, declared: params.bits() });
+    }
+    let primes = small_primes(SMALL_FACTOR_BOUND);
+    for &p in &primes {
+        let p_big = BigInt::from(p);
+        if &p_big >= n { break; }
+        if (n % &p_big).is_zero() { return Err(ParamsError::SmallFactor(p)); }
+    }
+    // with no factor below 10^4, x^k = n forces x > 10^4, so k < |n| / log2(10^4)
+    for &k in primes.iter().take_while(|&&k| (k as usize) * 13 <= n_bits) {
+        let root = n.nth_root(k);
+        if &root.pow(k) == n { return Err(ParamsError::PerfectPower { exponent: k }); }
+    }
     let two = BigInt::from(2u32);
+    let n_minus_one = n - &one;
+    for (name, x) in [("g", params.g()), ("h", params.h())] {
+        if x < &two || x > &n_minus_one { return Err(ParamsError::GeneratorOutOfRange(name)); }
+        if x == &n_minus_one { return Err(ParamsError::GeneratorIsMinusOne(name)); }
+        if x.gcd(n) != one { return Err(ParamsError::GeneratorNotCoprime(name)); }
+    }
+    if params.g() == params.h() { return Err(ParamsError::EqualGenerators); }
+    Ok(())
+}
+
+/// Default public seed for generator derivation
+pub const DEFAULT_GENERATOR_SEED: &str = "cuproof";
+
+/// Hash-to-group labels for g and h under a derivation seed
+pub fn generator_labels(seed: &str) -> (String, String) {
+    (format!("{}/g/v1", seed), format!("{}/h/v1", seed))
+}
+
+/// Hash a public label into the quadratic residues of Z_n^*
+/// - params: label domain-separation string, n modulus
+/// - returns: an element u^2 mod n with gcd(u^2, n) = 1 and u^2 != 1
+/// - usage: nothing-up-my-sleeve generators. Expands SHA-256(label || n || counter || block) to
+///   |n| + 128 bits, reduces mod n and squares, retrying with the next counter until the result is valid
+pub fn hash_to_group(label: &str, n: &BigInt) -> BigInt {
+    use sha2::{Digest, Sha256};
+    let (_sign, n_bytes) = n.to_bytes_be();
+    let blocks = (n.bits() as usize + 128).div_ceil(256);
     let one = BigInt::one();
-    let mut g;
+    let mut counter = 0u32;
     loop {
-        g = rng.gen_bigint_range(&two, &n);
-        if g.gcd(&n) == one { break; }
+        let mut wide = Vec::with_capacity(blocks * 32);
+        for block in 0..blocks as u32 {
+            let mut hasher = Sha256::new();
+            hasher.update(label.as_bytes());
+            hasher.update(&n_bytes);
+            hasher.update(counter.to_be_bytes());
+            hasher.update(block.to_be_bytes());
+            wide.extend_from_slice(&hasher.finalize());
+        }
+        let u = BigInt::from_bytes_be(Sign::Plus, &wide) % n;
+        let g = &u * &u % n;
+        if g > one && g.gcd(n) == one { return g; }
+        counter += 1;
     }
-    let mut h;
-    loop {
-        h = rng.gen_bigint_range(&two, &n);
-        if h.gcd(&n) == one && h != g { break; }
+}
+
+/// Validate externally generated primes and build Params over n = p * q
+#[cfg(feature = "std")]
+fn params_from_prime_parts(p: &BigUint, q: &BigUint, bits: usize, allow_insecure: bool, config: &PrimalityConfig) -> Result<Params, SetupError> {
+    check_modulus_bits(bits, allow_insecure)?;
+    let expected = bits / 2;
+    for x in [p, q] {
+        let xb = x.bits() as usize;
+        if xb < expected { return Err(SetupError::UndersizedPrime { bits: xb, expected }); }
+        if xb > expected { return Err(SetupError::OversizedPrime { bits: xb, expected }); }
+    }
+    if ct_eq_magnitudes(p, q, expected.div_ceil(8)) { return Err(SetupError::EqualPrimes); }
+    if !is_probable_prime(p, config, &mut OsRng) || !is_probable_prime(q, config, &mut OsRng) { return Err(SetupError::CompositePrime); }
+    // FIPS 186-4 B.3.1: |p - q| > 2^(bits/2 - 100)
+    let diff = SecretInt::new(if p > q { p - q } else { q - p });
+    if diff.expose().bits() as usize <= expected.saturating_sub(100) { return Err(SetupError::PrimesTooClose); }
+
+    let n_u = p * q;
+    if n_u.bits() as usize != bits {
+        return Err(SetupError::ModulusSizeMismatch { bits: n_u.bits() as usize, expected: bits });
+    }
+    let n = BigInt::from_biguint(Sign::Plus, n_u);
+    let metadata = ParamsMetadata::new(bits, Some(*config), Some(DEFAULT_GENERATOR_SEED), Vec::new());
+    Ok(Params::derive(n, bits, DEFAULT_GENERATOR_SEED).with_metadata(Some(metadata)))
+}
+
+/// Build Params from externally generated primes (e.g. from an audited HSM process)
+/// - params: p, q primes of exactly bits/2 bits each, bits modulus size from `SUPPORTED_MODULUS_BITS`
+/// - returns: Params with hash-derived g, h, or the SetupError naming the failed check
+/// - usage: operators who must not rely on this crate's RNG for the modulus; p and q are taken
+///   by value and wiped before returning, build them with `SecretInt::from_bigint`
+#[cfg(feature = "std")]
+pub fn params_from_primes(p: SecretInt, q: SecretInt, bits: usize) -> Result<Params, SetupError> {
+    params_from_primes_with(p, q, bits, false)
+}
+
+/// params_from_primes with the same insecure-size escape hatch as `trusted_setup_with`
+#[cfg(feature = "std")]
+pub fn params_from_primes_with(p: SecretInt, q: SecretInt, bits: usize, allow_insecure: bool) -> Result<Params, SetupError> {
+    params_from_primes_with_config(p, q, bits, allow_insecure, &PrimalityConfig::default())
+}
+
+/// params_from_primes_with using `config` to test p and q
+/// Supplied primes are always tested probabilistically: `config.provable` only affects generation,
+/// since no certificate comes with them
+#[cfg(feature = "std")]
+pub fn params_from_primes_with_config(p: SecretInt, q: SecretInt, bits: usize, allow_insecure: bool, config: &PrimalityConfig) -> Result<Params, SetupError> {
+    params_from_prime_parts(p.expose(), q.expose(), bits, allow_insecure, config)
+}
+
+/// Build Params over an externally agreed modulus whose factorization nobody is known to hold
+/// - params: n modulus (e.g. an RSA challenge number), bits its declared size from `SUPPORTED_MODULUS_BITS`
+/// - returns: Params with g, h from hash_to_group, or SetupError for size or structural defects
+/// - usage: dealer-free parameters. n must have exactly `bits` bits and pass validate_params
+///   (odd, no factor below 10^4, not a perfect power); see `known_moduli` for shipped values
+#[cfg(feature = "std")]
+pub fn params_from_modulus(n: &BigInt, bits: usize) -> Result<Params, SetupError> {
+    params_from_modulus_with(n, bits, false)
+}
+
+/// params_from_modulus with the same insecure-size escape hatch as `trusted_setup_with`
+#[cfg(feature = "std")]
+pub fn params_from_modulus_with(n: &BigInt, bits: usize, allow_insecure: bool) -> Result<Params, SetupError> {
+    check_modulus_bits(bits, allow_insecure)?;
+    if n.bits() as usize != bits {
+        return Err(SetupError::ModulusSizeMismatch { bits: n.bits() as usize, expected: bits });
+    }
+    let metadata = ParamsMetadata::new(bits, None, Some(DEFAULT_GENERATOR_SEED), Vec::new());
+    let params = Params::derive(n.clone(), bits, DEFAULT_GENERATOR_SEED).with_metadata(Some(metadata));
+    validate_params(&params).map_err(SetupError::InvalidModulus)?;
+    Ok(params)
+}
+
+/// Trusted setup producing an RSA-style modulus of exactly `bits` bits
+/// - params: bits modulus size, one of `SUPPORTED_MODULUS_BITS`
+/// - returns: Params or SetupError for sizes outside the policy
+/// - usage: production parameter generation; see `trusted_setup_with` for tests
+#[cfg(feature = "std")]
+pub fn trusted_setup(bits: usize) -> Result<Params, SetupError> {
+    trusted_setup_with(bits, false)
+}
+
+/// Trusted setup with an explicit escape hatch for reduced sizes
+/// - params: bits modulus size, allow_insecure permits even sizes below 2048 bits
+/// - returns: Params or SetupError
+/// - usage: tests and experiments that cannot afford 2048-bit prime generation
+#[cfg(feature = "std")]
+pub fn trusted_setup_with(bits: usize, allow_insecure: bool) -> Result<Params, SetupError> {
+    trusted_setup_with_threads(bits, allow_insecure, None)
+}
+
+/// Trusted setup with an explicit worker count for the prime search
+/// - params: bits, allow_insecure as in `trusted_setup_with`; threads worker count, None for all cores
+/// - returns: Params or SetupError
+/// - usage: with the `parallel` feature the candidate search runs on a dedicated rayon pool of
+///   `threads` workers; without it (or with Some(1)) the search is serial and `threads` is ignored
+#[cfg(feature = "std")]
+pub fn trusted_setup_with_threads(bits: usize, allow_insecure: bool, threads: Option<usize>) -> Result<Params, SetupError> {
+    run_trusted_setup(bits, allow_insecure, Some(&workers_for(threads)), &PrimalityConfig::default(), &mut SetupMonitor::silent(), &mut |_, _, _| {}).map(|(params, _)| params)
+}
+
+/// Trusted setup with an explicit primality configuration
+/// - params: bits, allow_insecure as in `trusted_setup_with`; config tests applied to the prime candidates
+/// - returns: Params and the PrimalityAttestation describing how p, q were established, or SetupError
+/// - usage: `PrimalityConfig::default()` matches `trusted_setup`. With `provable` the primes are
+///   built serially by the Shawe-Taylor method and each Pocklington certificate is checked; only
+///   `certified` and the publishable auxiliary certificates in `Params::metadata` survive
+#[cfg(feature = "std")]
+pub fn trusted_setup_with_config(bits: usize, allow_insecure: bool, config: &PrimalityConfig) -> Result<(Params, PrimalityAttestation), SetupError> {
+    run_trusted_setup(bits, allow_insecure, Some(&workers_for(None)), config, &mut SetupMonitor::silent(), &mut |_, _, _| {})
+}
+
+/// Trusted setup that reports its progress
+/// - params: bits as in `trusted_setup`, progress callback receiving `SetupProgress` snapshots
+/// - returns: Params or SetupError
+/// - usage: long-running CLI setups. The callback is called once when each phase starts and
+///   then about every 100ms; it only observes counters and cannot affect the parameters
+#[cfg(feature = "std")]
+pub fn trusted_setup_with_progress(bits: usize, progress: impl FnMut(SetupProgress)) -> Result<Params, SetupError> {
+    trusted_setup_reporting(bits, false, None, progress)
+}
+
+/// trusted_setup_with_progress with the size escape hatch and worker count of `trusted_setup_with_threads`
+#[cfg(feature = "std")]
+pub fn trusted_setup_reporting(bits: usize, allow_insecure: bool, threads: Option<usize>, mut progress: impl FnMut(SetupProgress)) -> Result<Params, SetupError> {
+    run_trusted_setup(bits, allow_insecure, Some(&workers_for(threads)), &PrimalityConfig::default(), &mut SetupMonitor::reporting(&mut progress), &mut |_, _, _| {})
+        .map(|(params, _)| params)
+}
+
+/// A dedicated pool of `threads` workers, every core for None
+#[cfg(feature = "std")]
+fn workers_for(threads: Option<usize>) -> Workers {
+    Workers::new(threads.unwrap_or_else(Workers::available))
+}
+
+/// Trusted setup that also returns a transcript proving n is well-formed
+/// - params: bits, allow_insecure and progress as in `trusted_setup_reporting`; workers searching
+///   for the primes, None to search serially on the calling thread
+/// - returns: Params and their SetupTranscript, or SetupError
+/// - usage: dealers publishing params for third parties; see `setup_transcript` for what is proven
+#[cfg(feature = "std")]
+pub fn trusted_setup_with_transcript(
+    bits: usize,
+    allow_insecure: bool,
+    workers: Option<&Workers>,
+    mut progress: impl FnMut(SetupProgress),
+) -> Result<(Params, SetupTranscript), SetupError> {
+    let mut transcript = None;
+    let (params, _) = run_trusted_setup(bits, allow_insecure, workers, &PrimalityConfig::default(), &mut SetupMonitor::reporting(&mut progress), &mut |p, q, n| {
+        transcript = Some(SetupTranscript::prove(p, q, n));
+    })?;
+    Ok((params, transcript.expect("generate_params_from hands the factors to the transcript")))
+}
+
+/// Run a setup inside a `setup` span, ending with an event carrying the fingerprint and duration
+#[cfg(feature = "std")]
+fn logged_setup<T>(bits: usize, setup: impl FnOnce() -> Result<(Params, T), SetupError>) -> Result<(Params, T), SetupError> {
+    #[cfg(not(feature = "tracing"))]
+    let _ = bits;
+    traced!(
+        tracing::debug_span!("setup", bits),
+        setup(),
+        |result, elapsed_ms| match result {
+            Ok((params, _)) => tracing::debug!(params = %params.fingerprint_hex(), elapsed_ms, "params generated"),
+            Err(e) => tracing::debug!(error = %e, elapsed_ms, "setup failed"),
+        }
+    )
+}
+
+#[cfg(feature = "std")]
+fn run_trusted_setup(
+    bits: usize,
+    allow_insecure: bool,
+    workers: Option<&Workers>,
+    config: &PrimalityConfig,
+    monitor: &mut SetupMonitor,
+    with_factors: &mut dyn FnMut(&SecretInt, &SecretInt, &BigInt),
+) -> Result<(Params, PrimalityAttestation), SetupError> {
+    logged_setup(bits, || search_trusted_setup(bits, allow_insecure, workers, config, monitor, with_factors))
+}
+
+#[cfg(feature = "std")]
+fn search_trusted_setup(
+    bits: usize,
+    allow_insecure: bool,
+    workers: Option<&Workers>,
+    config: &PrimalityConfig,
+    monitor: &mut SetupMonitor,
+    with_factors: &mut dyn FnMut(&SecretInt, &SecretInt, &BigInt),
+) -> Result<(Params, PrimalityAttestation), SetupError> {
+    check_modulus_bits(bits, allow_insecure)?;
+    let attestation = PrimalityAttestation { config: *config, certified: false };
+    let with_metadata = |params: Params, certificates| {
+        let metadata = ParamsMetadata::new(bits, Some(*config), params.generator_seed(), certificates);
+        params.with_metadata(Some(metadata))
+    };
+    if config.provable {
+        let mut certified = true;
+        // [p, q]; a q equal to p is redrawn and replaces its certificate
+        let mut certificates = [None, None];
+        let params = generate_params_from(bits, monitor, |prime_bits, phase, monitor| {
+            monitor.tracker.enter(phase);
+            let (prime, certificate) = provable_prime(prime_bits, &mut OsRng);
+            certified &= certificate.verify(prime.expose());
+            certificates[usize::from(phase == SetupPhase::TestingQ)] = certificate.auxiliary();
+            monitor.tracker.candidate();
+            monitor.poll();
+            prime
+        }, with_factors);
+        let params = with_metadata(params, certificates.into_iter().flatten().collect());
+        return Ok((params, PrimalityAttestation { certified, ..attestation }));
     }
+    #[cfg(feature = "parallel")]
+    if let Some(pool) = workers.and_then(Workers::pool) {
+        let params = generate_params_from(bits, monitor, |prime_bits, phase, monitor| search_prime_parallel(prime_bits, pool, phase, monitor, config), with_factors);
+        return Ok((with_metadata(params, Vec::new()), attestation));
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = workers;
+    let params = generate_params_from(bits, monitor, |prime_bits, phase, monitor| search_prime_serial(prime_bits, &mut OsRng, phase, monitor, config), with_factors);
+    Ok((with_metadata(params, Vec::new()), attestation))
+}
+
+/// How often `trusted_setup_resumable` persists its state
+#[cfg(feature = "std")]
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
 
-    (g, h, n)
+/// Trusted setup that can be killed and restarted without losing its progress
+/// - params: bits as in `trusted_setup`, checkpoint_path file holding the resumable state
+/// - returns: Params or SetupError (`SetupError::Checkpoint` for unreadable or mismatched checkpoints)
+/// - usage: long 4096-bit setups. Starts fresh if checkpoint_path does not exist, otherwise continues
+///   from it; the result equals an uninterrupted run from the same seed. The checkpoint holds the
+///   RNG seed (which determines p and q), is written owner-only and is deleted on success
+#[cfg(feature = "std")]
+pub fn trusted_setup_resumable(bits: usize, checkpoint_path: &str) -> Result<Params, SetupError> {
+    trusted_setup_resumable_with_transcript(bits, false, checkpoint_path, |_| {}).map(|(params, _)| params)
+}
+
+/// trusted_setup_resumable with the size escape hatch, progress callback and transcript of `trusted_setup_with_transcript`
+/// The search is serial: a single ChaCha20 stream is what makes the run replayable
+#[cfg(feature = "std")]
+pub fn trusted_setup_resumable_with_transcript(
+    bits: usize,
+    allow_insecure: bool,
+    checkpoint_path: &str,
+    mut progress: impl FnMut(SetupProgress),
+) -> Result<(Params, SetupTranscript), SetupError> {
+    logged_setup(bits, || {
+        let mut transcript = None;
+        let params = run_resumable(bits, allow_insecure, checkpoint_path, None, CHECKPOINT_INTERVAL, None, &mut SetupMonitor::reporting(&mut progress), &mut |p, q, n| {
+            transcript = Some(SetupTranscript::prove(p, q, n));
+        })?
+        .expect("an uncapped run only returns once it has finished");
+        let metadata = ParamsMetadata::new(bits, Some(PrimalityConfig::default()), params.generator_seed(), Vec::new());
+        Ok((params.with_metadata(Some(metadata)), transcript.expect("run_resumable hands the factors to the transcript")))
+    })
+}
+
+/// Resumable setup driver
+/// `seed` seeds a fresh run (OS randomness if None) and is ignored when a checkpoint exists.
+/// `stop_after` caps the candidates drawn by this call and returns Ok(None) once it is hit,
+/// without a final checkpoint, which is how tests simulate a killed process
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments)]
+fn run_resumable(
+    bits: usize,
+    allow_insecure: bool,
+    checkpoint_path: &str,
+    seed: Option<[u8; 32]>,
+    checkpoint_every: Duration,
+    stop_after: Option<u64>,
+    monitor: &mut SetupMonitor,
+    with_factors: &mut dyn FnMut(&SecretInt, &SecretInt, &BigInt),
+) -> Result<Option<Params>, SetupError> {
+    use crate::setup_checkpoint::SetupCheckpoint;
+    check_modulus_bits(bits, allow_insecure)?;
+    let io_error = |e: std::io::Error| SetupError::Checkpoint(format!("{}: {}", checkpoint_path, e));
+    let mut state = if std::path::Path::new(checkpoint_path).exists() {
+        let state = SetupCheckpoint::load(checkpoint_path).map_err(io_error)?;
+        if state.bits != bits {
+            return Err(SetupError::Checkpoint(format!("{} belongs to a {}-bit setup, not {} bits", checkpoint_path, state.bits, bits)));
+        }
+        state
+    } else {
+        let mut fresh = Zeroizing::new(seed.unwrap_or_default());
+        if seed.is_none() { OsRng.fill_bytes(&mut *fresh); }
+        let state = SetupCheckpoint { bits, seed: fresh, word_pos: 0, candidates: 0, p: None };
+        state.save(checkpoint_path).map_err(io_error)?;
+        state
+    };
+    let mut rng = ChaCha20Rng::from_seed(*state.seed);
+    rng.set_word_pos(state.word_pos);
+    monitor.tracker.candidates.store(state.candidates, Ordering::Relaxed);
+
+    let config = PrimalityConfig::default();
+    let prime_bits = bits / 2;
+    let mut drawn = 0u64;
+    let mut last_save = Instant::now();
+    let mut phases = SetupPhases::start(monitor.candidates());
+    let n = loop {
+        let testing = if state.p.is_some() { SetupPhase::TestingQ } else { SetupPhase::TestingP };
+        if stop_after.is_some_and(|cap| drawn >= cap) { return Ok(None); }
+        let prime = try_candidate(prime_bits, &mut rng, testing, &monitor.tracker, &config);
+        drawn += 1;
+        monitor.poll();
+        let found = match (prime, &state.p) {
+            (Some(q), Some(p)) if !q.ct_eq(p.expose()) => Some(q),
+            (Some(p), None) => {
+                state.p = Some(p);
+                phases.finish("p", monitor.candidates());
+                None
+            }
+            _ => None,
+        };
+        if let Some(q) = found {
+            let p = state.p.take().expect("q is only searched once p is known");
+            let n = BigInt::from_biguint(Sign::Plus, p.expose() * q.expose());
+            with_factors(&p, &q, &n);
+            phases.finish("q", monitor.candidates());
+            break n;
+        }
+        if (testing == SetupPhase::TestingP && state.p.is_some()) || last_save.elapsed() >= checkpoint_every {
+            state.word_pos = rng.get_word_pos();
+            state.candidates = monitor.tracker.candidates.load(Ordering::Relaxed);
+            state.save(checkpoint_path).map_err(io_error)?;
+            last_save = Instant::now();
+        }
+    };
+    drop(state);
+    std::fs::remove_file(checkpoint_path).map_err(io_error)?;
+
+    monitor.tracker.enter(SetupPhase::DerivingGenerators);
+    monitor.poll();
+    let params = Params::derive(n, bits, DEFAULT_GENERATOR_SEED);
+    phases.finish("generators", monitor.candidates());
+    Ok(Some(params))
+}
+
+/// Trustless parameters: the class group of a discriminant derived from a public seed
+/// - params: seed public string (e.g. a block hash), discriminant_bits size of |D|
+/// - returns: ClassGroupParams, or UnsupportedModulusSize below `classgroup::MIN_DISCRIMINANT_BITS`
+/// - usage: pass to prove / verify in place of Params; no dealer and no
+///   transcript are needed since anyone can rederive the group from the seed
+#[cfg(feature = "classgroup")]
+pub fn classgroup_params(seed: &str, discriminant_bits: usize) -> Result<crate::classgroup::ClassGroupParams, SetupError> {
+    crate::classgroup::ClassGroupParams::from_seed(seed, discriminant_bits)
+        .ok_or(SetupError::UnsupportedModulusSize(discriminant_bits))
+}
+
+/// Sizes accepted by `insecure_test_setup`
+pub const INSECURE_TEST_MODULUS_BITS: [usize; 2] = [512, 1024];
+
+/// Deterministic test parameters, marked INSECURE
+/// - params: bits modulus size, one of `INSECURE_TEST_MODULUS_BITS`
+/// - returns: Params with `is_insecure()` set; every call with the same size returns the same Params
+/// - usage: tests only; 1024 bits exercises multi-limb arithmetic closer to production sizes. The
+///   primes come from a fixed public seed, so anyone can recompute the factorization of n, and
+///   RangeProver / Verifier refuse the result unless `Params::allow_insecure_params` is called.
+///   Panics for other sizes
+#[cfg(feature = "std")]
+pub fn insecure_test_setup(bits: usize) -> Params {
+    assert!(INSECURE_TEST_MODULUS_BITS.contains(&bits), "insecure_test_setup supports {:?} bits, not {}", INSECURE_TEST_MODULUS_BITS, bits);
+    generate_params(bits, &mut ChaCha20Rng::seed_from_u64(0)).mark_insecure()
+}
+
+/// Parameters over a random odd `bits`-bit modulus nobody factored or tested, marked INSECURE
+/// - params: bits modulus size, at least 64; rng source of n
+/// - returns: Params with hash-derived generators over n. n is not a product of two primes and
+///   may have small factors, so proofs over these params prove nothing
+/// - usage: timing prove and verify at a modulus size without paying for a prime search (`cuproof
+///   bench`); modular arithmetic costs the same for every odd modulus of a given size
+#[cfg(feature = "std")]
+pub fn throwaway_params<R: RngCore>(bits: usize, rng: &mut R) -> Params {
+    assert!(bits >= 64, "throwaway_params needs at least 64 bits, not {}", bits);
+    let mut buf = vec![0u8; bits.div_ceil(8)];
+    rng.fill_bytes(&mut buf);
+    let mut n = BigUint::from_bytes_be(&buf) >> (buf.len() * 8 - bits);
+    n.set_bit(bits as u64 - 1, true);
+    n.set_bit(0, true);
+    Params::derive(BigInt::from_biguint(Sign::Plus, n), bits, DEFAULT_GENERATOR_SEED).mark_insecure()
 }
 
 /// Fast test setup for development/testing purposes
 /// Uses smaller primes for quick testing while maintaining RSA structure
-pub fn fast_test_setup() -> (BigInt, BigInt, BigInt) {
-    let mut rng = OsRng;
+/// Delegates to `insecure_test_setup(512)`, so every call returns the same parameters
+#[cfg(feature = "std")]
+#[deprecated(note = "use insecure_test_setup(512), which names what these params are")]
+pub fn fast_test_setup() -> Params {
+    insecure_test_setup(512)
+}
 
+/// Deterministic fast test setup: a 512-bit modulus and generators derived from `seed`
+/// - params: seed selects the parameter set; the same seed gives the same Params on every machine
+/// - returns: Params marked insecure; seed 0 gives `insecure_test_setup(512)`
+/// - usage: reproducible fixtures in tests. INSECURE: anyone who knows the seed can recompute
+///   the factorization of n, so these parameters must never be used outside tests
+#[cfg(feature = "std")]
+pub fn fast_test_setup_seeded(seed: u64) -> Params {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
     // Use smaller primes for fast testing: 256-bit primes -> 512-bit modulus
-    let prime_bits = 256; // Much faster than 1024-bit
-    let p = generate_probable_prime(prime_bits);
-    let mut q = generate_probable_prime(prime_bits);
-    while q == p { q = generate_probable_prime(prime_bits); }
-    let n_u = &p * &q;
-    let n = BigInt::from_biguint(Sign::Plus, n_u.clone());
-
-    // choose g, h uniformly in Z_n^* (co-prime with n)
-    let two = BigInt::from(2u32);
-    let one = BigInt::one();
-    let mut g;
-    loop {
-        g = rng.gen_bigint_range(&two, &n);
-        if g.gcd(&n) == one { break; }
-    }
-    let mut h;
-    loop {
-        h = rng.gen_bigint_range(&two, &n);
-        if h.gcd(&n) == one && h != g { break; }
-    }
+    generate_params(512, &mut rng).mark_insecure()
+}
 
-    (g, h, n)
+/// Deterministic INSECURE parameters of any test size, derived from `seed`
+/// - params: bits modulus size, even and at least 128 bits; seed any bytes, hashed into the ChaCha20 seed
+/// - returns: Params marked insecure, the same for the same bits and seed on every machine, or
+///   SetupError::UnsupportedModulusSize
+/// - usage: `cuproof setup --mode fast --seed`; like fast_test_setup_seeded, anyone who knows the
+///   seed can recompute the factorization of n
+#[cfg(feature = "std")]
+pub fn insecure_seeded_setup(bits: usize, seed: &[u8]) -> Result<Params, SetupError> {
+    use sha2::{Digest, Sha256};
+    check_modulus_bits(bits, true)?;
+    let mut rng = ChaCha20Rng::from_seed(Sha256::digest(seed).into());
+    Ok(generate_params(bits, &mut rng).mark_insecure())
+}
+
+/// fast_test_setup_seeded together with its setup transcript; the Params are identical
+#[cfg(feature = "std")]
+pub fn fast_test_setup_with_transcript(seed: u64) -> (Params, SetupTranscript) {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let mut transcript = None;
+    let params = generate_params_from(512, &mut SetupMonitor::silent(), |prime_bits, phase, monitor| search_prime_serial(prime_bits, &mut rng, phase, monitor, &PrimalityConfig::default()), &mut |p, q, n| {
+        transcript = Some(SetupTranscript::prove(p, q, n));
+    });
+    (params.mark_insecure(), transcript.expect("generate_params_from hands the factors to the transcript"))
 }
 
 #[cfg(test)]
@@ -127,16 +1413,449 @@ mod tests {
     use super::*;
     use num_traits::Zero;
 
+    fn generate_probable_prime_with_rng<R: RngCore>(bits: usize, rng: &mut R) -> SecretInt {
+        search_prime_serial(bits, rng, SetupPhase::TestingP, &mut SetupMonitor::silent(), &PrimalityConfig::default())
+    }
+
+    fn generate_probable_prime(bits: usize) -> BigUint {
+        generate_probable_prime_with_rng(bits, &mut OsRng).expose().clone()
+    }
+
     // Purpose: ensure generated (g,h) are in Z*_n, non-equal, and gcd(g,n)=gcd(h,n)=1
     // Params: none
     // Output: assertions on coprimality and distinctness
     // Usage: `cargo test -- src::setup` or `cargo test`
     #[test]
     fn fast_setup_generates_valid_params() {
-        let (g, h, n) = fast_test_setup();
-        assert!(g.gcd(&n).is_one());
-        assert!(h.gcd(&n).is_one());
+        let params = insecure_test_setup(512);
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        assert!(g.gcd(n).is_one());
+        assert!(h.gcd(n).is_one());
         assert_ne!(g, h);
         assert!(!n.is_zero());
+        assert_eq!(params.bits(), 512);
+        assert_eq!(params.version(), PARAMS_VERSION);
+    }
+
+    // Purpose: ensure the modulus size policy rejects small sizes unless explicitly allowed
+    // Params: a range of requested bit sizes
+    // Output: assertions on accepted/rejected sizes and the exact bit length of n
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn modulus_size_policy_and_exact_bits() {
+        assert_eq!(trusted_setup(1024).unwrap_err(), SetupError::InsecureModulusSize { bits: 1024, min_bits: 2048 });
+        assert_eq!(trusted_setup(2560).unwrap_err(), SetupError::UnsupportedModulusSize(2560));
+        assert_eq!(trusted_setup_with(255, true).unwrap_err(), SetupError::UnsupportedModulusSize(255));
+        assert_eq!(trusted_setup_with(64, true).unwrap_err(), SetupError::UnsupportedModulusSize(64));
+        for bits in SUPPORTED_MODULUS_BITS { assert!(check_modulus_bits(bits, false).is_ok()); }
+
+        assert_eq!(trusted_setup_with(256, true).unwrap().n().bits(), 256);
+        assert_eq!(insecure_test_setup(512).n().bits(), 512);
+    }
+
+    // Purpose: ensure table-based fixed-base exponentiation matches plain modpow
+    // Params: fast params, exponents inside and beyond the table range
+    // Output: equality assertions against commitment::mod_exp
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn precomputed_tables_match_modpow() {
+        let params = insecure_test_setup(512);
+        let big = BigInt::one() << (params.table_bits() + 5);
+        // IPP commitments use exponents several times the table length
+        let huge = crate::util::random_bigint(4 * params.table_bits()) | BigInt::one();
+        for e in [BigInt::zero(), BigInt::one(), BigInt::from(-77), BigInt::from(0xdead_beef_u64) << 300, big, huge] {
+            assert_eq!(params.g_pow(&e), crate::commitment::mod_exp(params.g(), &e, params.n()));
+            assert_eq!(params.h_pow(&e), crate::commitment::mod_exp(params.h(), &e, params.n()));
+            let r = BigInt::from(12345) - &e;
+            assert_eq!(params.gh_pow(&e, &r), crate::commitment::pedersen_commit(params.g(), params.h(), &e, &r, params.n()));
+        }
+        // clones share the tables built by the original
+        let cloned = params.clone();
+        assert!(Arc::ptr_eq(&params.tables, &cloned.tables));
+        assert_eq!(cloned, params);
+    }
+
+    // Purpose: ensure generators are re-derivable from the recorded seed and labels separate domains
+    // Params: fast params and a reduced-size trusted setup
+    // Output: re-derivation equality; tampered generators no longer match the seed
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn generators_rederive_from_seed() {
+        for params in [insecure_test_setup(512), trusted_setup_with(256, true).unwrap()] {
+            assert_eq!(params.generator_seed(), Some(DEFAULT_GENERATOR_SEED));
+            let (g, h) = params.rederive_generators().unwrap();
+            assert_eq!((&g, &h), (params.g(), params.h()));
+            assert!(params.generators_match_seed());
+            assert_eq!(&hash_to_group("cuproof/h/v1", params.n()), params.h());
+        }
+        let params = insecure_test_setup(512);
+        assert_ne!(hash_to_group("other/g/v1", params.n()), *params.g());
+        let swapped = Params::new(params.h().clone(), params.g().clone(), params.n().clone(), params.bits())
+            .with_generator_seed(Some(DEFAULT_GENERATOR_SEED.to_string()));
+        assert!(!swapped.generators_match_seed());
+        assert!(Params::new(params.g().clone(), params.h().clone(), params.n().clone(), 512).rederive_generators().is_none());
+    }
+
+    // Purpose: ensure the fingerprint survives save/load, tracks every hashed field, and security_check enforces size
+    // Params: fast params, a params file roundtrip, copies with one field changed
+    // Output: equal fingerprints after load, distinct ones per change; 64-digit hex display; ModulusTooSmall
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn fingerprint_and_security_check() {
+        let params = insecure_test_setup(512);
+        let path = std::env::temp_dir().join(format!("cuproof_fingerprint_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        crate::util::save_params(path, &params).unwrap();
+        let loaded = crate::util::load_params(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        assert_eq!(loaded.fingerprint(), params.fingerprint());
+        // the generator seed is not hashed: g and h already commit to it
+        assert_eq!(params.clone().with_generator_seed(None).fingerprint(), params.fingerprint());
+
+        let shown = params.fingerprint_hex().to_string();
+        assert_eq!(shown.len(), 64);
+        assert_eq!(shown, hex::encode(params.fingerprint()));
+        assert_eq!(params.fingerprint_hex().short(), shown[..16]);
+
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        let variants = [
+            Params::new(g + 1, h.clone(), n.clone(), params.bits()),
+            Params::new(g.clone(), h + 1, n.clone(), params.bits()),
+            Params::new(g.clone(), h.clone(), n + 2, params.bits()),
+            Params::new(g.clone(), h.clone(), n.clone(), params.bits() - 1),
+            Params::new(h.clone(), g.clone(), n.clone(), params.bits()),
+        ];
+        let mut seen = std::collections::HashSet::new();
+        seen.insert(params.fingerprint());
+        for variant in &variants { assert!(seen.insert(variant.fingerprint())); }
+
+        assert_eq!(params.security_check(512), Ok(()));
+        assert_eq!(params.security_check(MIN_SECURE_MODULUS_BITS), Err(ParamsError::ModulusTooSmall { bits: 512, min_bits: 2048 }));
+        let even = Params::new(g.clone(), h.clone(), n + 1, params.bits());
+        assert_eq!(even.security_check(512), Err(ParamsError::EvenModulus));
+    }
+
+    // Purpose: ensure derived generator vectors are stable, shared by independent parties and disjoint
+    // Params: n = (2^89 - 1)(2^107 - 1) with seed-derived g, h; labels "agg/g", "agg/h"; counts 4 and 5
+    // Output: pinned first three generators; equal vectors from separately built params; disjoint sets
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn derive_generators_pinned_and_disjoint() {
+        let n: BigInt = ((BigInt::one() << 89u32) - 1) * ((BigInt::one() << 107u32) - 1);
+        let prover = Params::derive(n.clone(), 196, DEFAULT_GENERATOR_SEED);
+        let gs = prover.derive_generators("agg/g", 4);
+        let pinned = [
+            "0c3b062bd0f536aa250ad45eb85006f361811169acdc006983",
+            "035618f11e7eac307aefce77cbb142780b6b369880488c800a",
+            "0818fa1359648508e2f342b2e155949f039f22062f508e2136",
+        ];
+        for (x, hex) in gs.iter().zip(pinned) { assert_eq!(crate::util::bigint_to_hex(x), hex); }
+
+        // the verifier rebuilds params on its own and gets the same vector; repeat calls hit the cache
+        let verifier = Params::derive(n, 196, DEFAULT_GENERATOR_SEED);
+        assert_eq!(verifier.derive_generators("agg/g", 4), gs);
+        assert_eq!(prover.derive_generators("agg/g", 4), gs);
+        assert!(Arc::ptr_eq(&prover.vectors, &prover.clone().vectors));
+
+        let hs = prover.derive_generators("agg/h", 4);
+        let gs5 = prover.derive_generators("agg/g", 5);
+        let mut all: Vec<&BigInt> = gs.iter().chain(hs.iter()).chain(gs5.iter()).chain([prover.g(), prover.h()]).collect();
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 4 + 4 + 5 + 2);
+        assert!(prover.derive_generators("agg/g", 0).is_empty());
+        assert_ne!(insecure_test_setup(512).derive_generators("agg/g", 4), gs);
+    }
+
+    // Purpose: ensure validate_params accepts honest params and flags each structural defect
+    // Params: fast params with one component replaced at a time
+    // Output: Ok for honest params, the matching ParamsError for each defect
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn validate_params_rejects_defects() {
+        let params = insecure_test_setup(512);
+        assert_eq!(validate_params(&params), Ok(()));
+        assert_eq!(validate_params(&trusted_setup_with(256, true).unwrap()), Ok(()));
+        let (g, h, n) = (params.g().clone(), params.h().clone(), params.n().clone());
+        let with = |g: &BigInt, h: &BigInt, n: &BigInt, bits: usize| validate_params(&Params::new(g.clone(), h.clone(), n.clone(), bits));
+
+        let p = BigInt::from_biguint(Sign::Plus, generate_probable_prime(128));
+        assert_eq!(with(&g, &h, &(&n * 2), 512), Err(ParamsError::EvenModulus));
+        assert_eq!(with(&g, &h, &n, 1024), Err(ParamsError::ModulusBelowDeclaredSize { bits: 512, declared: 1024 }));
+        assert_eq!(with(&g, &h, &(&n * 7919), 512), Err(ParamsError::SmallFactor(7919)));
+        assert_eq!(with(&BigInt::from(2), &BigInt::from(3), &(&p * &p), 256), Err(ParamsError::PerfectPower { exponent: 2 }));
+        assert_eq!(with(&BigInt::from(2), &BigInt::from(3), &p.pow(3), 256), Err(ParamsError::PerfectPower { exponent: 3 }));
+        assert_eq!(with(&BigInt::zero(), &h, &n, 512), Err(ParamsError::GeneratorOutOfRange("g")));
+        assert_eq!(with(&g, &BigInt::one(), &n, 512), Err(ParamsError::GeneratorOutOfRange("h")));
+        assert_eq!(with(&g, &n, &n, 512), Err(ParamsError::GeneratorOutOfRange("h")));
+        assert_eq!(with(&(&n - 1), &h, &n, 512), Err(ParamsError::GeneratorIsMinusOne("g")));
+        let factor = BigInt::from_biguint(Sign::Plus, generate_probable_prime(256));
+        let n2 = &factor * BigInt::from_biguint(Sign::Plus, generate_probable_prime(256));
+        assert_eq!(with(&BigInt::from(5), &factor, &n2, 512), Err(ParamsError::GeneratorNotCoprime("h")));
+        assert_eq!(with(&g, &g, &n, 512), Err(ParamsError::EqualGenerators));
+        let huge = (BigInt::one() << MAX_MODULUS_BITS) + 1;
+        assert_eq!(with(&g, &h, &huge, 512), Err(ParamsError::ModulusTooLarge { bits: MAX_MODULUS_BITS + 1, max_bits: MAX_MODULUS_BITS }));
+    }
+
+    // Purpose: ensure seeded setups are reproducible and distinct seeds give unrelated params
+    // Params: seeds 0 and 1
+    // Output: stability/inequality assertions and cross-params verification failure
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn seeded_setup_is_deterministic() {
+        let p0 = fast_test_setup_seeded(0);
+        let p1 = fast_test_setup_seeded(1);
+        assert_eq!(p0, fast_test_setup_seeded(0));
+        assert_eq!(p1, fast_test_setup_seeded(1));
+        assert_eq!(p0, insecure_test_setup(512));
+        assert_ne!(p0.n(), p1.n());
+
+        let (a, b, v) = (BigInt::from(1), BigInt::from(100), BigInt::from(42));
+        let proof = crate::range_proof::prove(&v, &BigInt::from(7), &a, &b, &p0).unwrap();
+        assert!(crate::verify::verify(&proof, &p0));
+        assert!(!crate::verify::verify(&proof, &p1));
+    }
+
+    // Purpose: ensure insecure_seeded_setup is reproducible per (bits, seed) and refuses unusable sizes
+    // Params: 256-bit params from two seeds, a 384-bit one, odd and tiny sizes
+    // Output: equal params for equal inputs, different moduli otherwise, exact sizes, insecure marker set
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn insecure_seeded_setup_is_deterministic_per_size() {
+        let p = insecure_seeded_setup(256, b"fixture").unwrap();
+        assert_eq!(p, insecure_seeded_setup(256, b"fixture").unwrap());
+        assert!(p.is_insecure() && p.n().bits() == 256);
+        assert_ne!(p.n(), insecure_seeded_setup(256, b"other").unwrap().n());
+        assert_eq!(insecure_seeded_setup(384, b"fixture").unwrap().n().bits(), 384);
+        assert_eq!(insecure_seeded_setup(257, b"x"), Err(SetupError::UnsupportedModulusSize(257)));
+        assert_eq!(insecure_seeded_setup(64, b"x"), Err(SetupError::UnsupportedModulusSize(64)));
+        assert_eq!([1023, 1024, 2048, 3072, 4096, 16384].map(security_level_bits), [0, 80, 112, 128, 128, 256]);
+    }
+
+    // Purpose: ensure params_from_primes accepts good primes and reports each bad input distinctly
+    // Params: 128-bit primes from the internal generator, hand-built composites and near primes
+    // Output: assertions on the resulting modulus and on each SetupError variant
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn params_from_primes_validates_inputs() {
+        let secret = |x: &BigUint| SecretInt::new(x.clone());
+        let p = generate_probable_prime(128);
+        let mut q = generate_probable_prime(128);
+        while q == p { q = generate_probable_prime(128); }
+
+        let params = params_from_primes_with(secret(&p), secret(&q), 256, true).unwrap();
+        assert_eq!(params.n(), &BigInt::from_biguint(Sign::Plus, &p * &q));
+        assert_eq!(params.bits(), 256);
+        assert!(params.g().gcd(params.n()).is_one() && params.h().gcd(params.n()).is_one());
+        assert_ne!(params.g(), params.h());
+        // generators are derived, so the same primes always give the same params
+        assert_eq!(params_from_primes_with(secret(&q), secret(&p), 256, true).unwrap(), params);
+
+        // policy still applies without the escape hatch
+        assert_eq!(params_from_primes(secret(&p), secret(&q), 256).unwrap_err(), SetupError::InsecureModulusSize { bits: 256, min_bits: 2048 });
+
+        assert_eq!(params_from_primes_with(secret(&p), secret(&p), 256, true).unwrap_err(), SetupError::EqualPrimes);
+
+        let small = generate_probable_prime(120);
+        assert_eq!(params_from_primes_with(secret(&small), secret(&q), 256, true).unwrap_err(), SetupError::UndersizedPrime { bits: 120, expected: 128 });
+        let large = generate_probable_prime(136);
+        assert_eq!(params_from_primes_with(secret(&p), secret(&large), 256, true).unwrap_err(), SetupError::OversizedPrime { bits: 136, expected: 128 });
+
+        let composite = loop {
+            let c = generate_probable_prime(64) * generate_probable_prime(64);
+            if c.bits() == 128 { break c; }
+        };
+        assert_eq!(params_from_primes_with(secret(&composite), secret(&q), 256, true).unwrap_err(), SetupError::CompositePrime);
+        assert!(SecretInt::from_bigint(BigInt::from(-7)).is_none());
+        assert_eq!(SecretInt::from_bigint(BigInt::from(7)).unwrap().expose(), &BigUint::from(7u32));
+
+        let mut next = &p + BigUint::from(2u32);
+        while !is_probable_prime(&next, &PrimalityConfig::default(), &mut OsRng) { next += BigUint::from(2u32); }
+        assert_eq!(params_from_primes_with(secret(&p), secret(&next), 256, true).unwrap_err(), SetupError::PrimesTooClose);
+    }
+
+    // Purpose: ensure PrimalityConfig reaches trusted setup and params_from_primes
+    // Params: reduced 256-bit setups (probable and provable), a 128-bit composite factor with a weakened config
+    // Output: valid params with matching attestations; the composite only passes with no tests configured
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn primality_config_is_threaded_through_setup() {
+        let probable = PrimalityConfig { mr_rounds: 8, ..PrimalityConfig::default() };
+        let (params, attestation) = trusted_setup_with_config(256, true, &probable).unwrap();
+        assert_eq!(params.n().bits(), 256);
+        assert_eq!(validate_params(&params), Ok(()));
+        assert_eq!(attestation, PrimalityAttestation { config: probable, certified: false });
+
+        let provable = PrimalityConfig { provable: true, ..PrimalityConfig::default() };
+        let (params, attestation) = trusted_setup_with_config(256, true, &provable).unwrap();
+        assert_eq!(params.n().bits(), 256);
+        assert_eq!(validate_params(&params), Ok(()));
+        assert!(attestation.certified);
+        assert_eq!(trusted_setup_with_config(256, false, &provable).unwrap_err(), SetupError::InsecureModulusSize { bits: 256, min_bits: 2048 });
+
+        let secret = |x: &BigUint| SecretInt::new(x.clone());
+        let q = generate_probable_prime(128);
+        let composite = loop {
+            let c = generate_probable_prime(64) * generate_probable_prime(64);
+            if c.bits() == 128 { break c; }
+        };
+        let weakened = PrimalityConfig { mr_rounds: 0, use_baillie_psw: false, provable: false };
+        assert!(params_from_primes_with_config(secret(&composite), secret(&q), 256, true, &weakened).is_ok());
+        for config in [PrimalityConfig::default(), PrimalityConfig { use_baillie_psw: true, ..weakened }, PrimalityConfig { mr_rounds: 1, ..weakened }] {
+            assert_eq!(params_from_primes_with_config(secret(&composite), secret(&q), 256, true, &config).unwrap_err(), SetupError::CompositePrime);
+        }
+    }
+
+    // Purpose: ensure a killed resumable setup continues to the same params as an uninterrupted run
+    // Params: 256-bit runs from a fixed seed, killed after a few candidates and resumed, with a checkpoint
+    //         after every candidate and with only the initial / p-found checkpoints
+    // Output: params equal to the seeded reference; checkpoint marked sensitive and removed at the end
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn resumable_setup_survives_kills() {
+        let seed = [7u8; 32];
+        let reference = generate_params(256, &mut ChaCha20Rng::from_seed(seed));
+        let path = std::env::temp_dir().join(format!("cuproof_setup_checkpoint_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        for every in [Duration::ZERO, Duration::from_secs(3600)] {
+            let mut kills = 0;
+            let params = loop {
+                // with the long interval progress since the last checkpoint is lost, so each restart gets a larger budget
+                let cap = if every.is_zero() { 7 } else { 7 * (kills + 1) };
+                let run = run_resumable(256, true, path, Some(seed), every, Some(cap), &mut SetupMonitor::silent(), &mut |_, _, _| {});
+                match run.unwrap() {
+                    Some(params) => break params,
+                    None => {
+                        kills += 1;
+                        let content = std::fs::read_to_string(path).unwrap();
+                        assert!(content.starts_with("SENSITIVE"));
+                    }
+                }
+            };
+            assert!(kills >= 2, "run finished after {} kills", kills);
+            assert_eq!(params, reference);
+            assert!(!std::path::Path::new(path).exists());
+        }
+
+        // a checkpoint is only resumed for the setup it was written by
+        assert!(run_resumable(256, true, path, Some(seed), Duration::ZERO, Some(1), &mut SetupMonitor::silent(), &mut |_, _, _| {}).unwrap().is_none());
+        assert!(matches!(trusted_setup_resumable_with_transcript(192, true, path, |_| {}), Err(SetupError::Checkpoint(_))));
+        let (params, transcript) = trusted_setup_resumable_with_transcript(256, true, path, |_| {}).unwrap();
+        assert_eq!(params, reference);
+        assert!(crate::setup_transcript::verify_setup_transcript(&params, &transcript));
+        assert!(!std::path::Path::new(path).exists());
+
+        std::fs::write(path, "not a checkpoint").unwrap();
+        assert!(matches!(trusted_setup_resumable_with_transcript(256, true, path, |_| {}), Err(SetupError::Checkpoint(_))));
+        let _ = std::fs::remove_file(path);
+        assert_eq!(trusted_setup_resumable(256, path).unwrap_err(), SetupError::InsecureModulusSize { bits: 256, min_bits: 2048 });
+    }
+
+    // Purpose: ensure the prime search gives valid, independent params for any worker count
+    // Params: reduced 256-bit setups with 1 (serial), 3 and all workers
+    // Output: exact modulus size, validate_params Ok, distinct moduli across runs
+    // Usage: `cargo test -- src::setup` or `cargo test --no-default-features -- src::setup`
+    #[test]
+    fn setup_threads_produce_valid_params() {
+        let mut moduli = Vec::new();
+        for threads in [Some(1), Some(3), None] {
+            let params = trusted_setup_with_threads(256, true, threads).unwrap();
+            assert_eq!(params.n().bits(), 256);
+            assert_eq!(validate_params(&params), Ok(()));
+            moduli.push(params.n().clone());
+        }
+        assert!(moduli[0] != moduli[1] && moduli[1] != moduli[2] && moduli[0] != moduli[2]);
+        assert_eq!(trusted_setup_with_threads(1024, false, Some(2)).unwrap_err(), SetupError::InsecureModulusSize { bits: 1024, min_bits: 2048 });
+    }
+
+    // Purpose: ensure the progress callback sees every phase and cannot change the result
+    // Params: reduced 256-bit setups (serial and parallel) with a counting callback; a seeded run
+    // Output: each SetupPhase reported at least once with monotone counters; seeded params unchanged
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn setup_progress_reports_every_phase() {
+        for threads in [Some(1), Some(2)] {
+            let mut reports: Vec<SetupProgress> = Vec::new();
+            let params = trusted_setup_reporting(256, true, threads, |p| reports.push(p)).unwrap();
+            assert_eq!(validate_params(&params), Ok(()));
+            for phase in SetupPhase::ALL {
+                assert!(reports.iter().any(|r| r.phase == phase), "{:?} never reported", phase);
+            }
+            assert_eq!(reports.last().unwrap().phase, SetupPhase::DerivingGenerators);
+            assert!(reports.windows(2).all(|w| w[0].candidates_tested <= w[1].candidates_tested && w[0].elapsed <= w[1].elapsed));
+            assert!(reports.last().unwrap().candidates_tested >= 2);
+        }
+
+        let seeded = |report: bool| {
+            let mut rng = ChaCha20Rng::seed_from_u64(9);
+            let mut calls = 0usize;
+            let mut count = |_: SetupProgress| calls += 1;
+            let mut monitor = if report { SetupMonitor::reporting(&mut count) } else { SetupMonitor::silent() };
+            generate_params_from(256, &mut monitor, |bits, phase, monitor| search_prime_serial(bits, &mut rng, phase, monitor, &PrimalityConfig::default()), &mut |_, _, _| {})
+        };
+        assert_eq!(seeded(true), seeded(false));
+        assert!(trusted_setup_with_progress(1024, |_| {}).is_err());
+    }
+
+    // Purpose: ensure params_from_modulus derives generators for good moduli and rejects bad ones
+    // Params: a 256-bit product of generated primes, its even and small-factor variants
+    // Output: Ok with seed-derived generators; ModulusSizeMismatch / InvalidModulus otherwise
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn params_from_modulus_validates_input() {
+        let n = trusted_setup_with(256, true).unwrap().n().clone();
+        let params = params_from_modulus_with(&n, 256, true).unwrap();
+        assert_eq!(params.n(), &n);
+        assert!(params.generators_match_seed());
+        assert_eq!(params_from_modulus(&n, 256).unwrap_err(), SetupError::InsecureModulusSize { bits: 256, min_bits: 2048 });
+        assert_eq!(params_from_modulus_with(&n, 384, true).unwrap_err(), SetupError::ModulusSizeMismatch { bits: 256, expected: 384 });
+
+        let even: BigInt = &n + 1;
+        assert_eq!(params_from_modulus_with(&even, 256, true).unwrap_err(), SetupError::InvalidModulus(ParamsError::EvenModulus));
+        let q = BigInt::from_biguint(Sign::Plus, generate_probable_prime(243));
+        let small_factor: BigInt = &q * 7919;
+        assert_eq!(small_factor.bits(), 256);
+        assert_eq!(params_from_modulus_with(&small_factor, 256, true).unwrap_err(), SetupError::InvalidModulus(ParamsError::SmallFactor(7919)));
+    }
+
+    // Purpose: ensure Params carries only public values and secrets are redacted and wiped
+    // Params: fast params, a SecretInt holding a fresh prime
+    // Output: the exact public field list of Params; no field shares a factor with n; wiped SecretInt
+    // Usage: `cargo test -- src::setup` or `cargo test`
+    #[test]
+    fn params_hold_only_public_values() {
+        let params = insecure_test_setup(512);
+        let debug = format!("{:?}", params);
+        let inner = &debug[debug.find('{').unwrap() + 1..debug.rfind('}').unwrap()];
+        let fields: Vec<&str> = inner.split(", ").filter_map(|f| f.split_once(": ")).map(|(name, _)| name.trim()).collect();
+        assert_eq!(fields, ["g", "h", "n", "bits", "version", "generator_seed", "epoch", "metadata", "insecure"]);
+        for x in [params.g(), params.h()] { assert!(x.gcd(params.n()).is_one()); }
+
+        let mut secret = generate_probable_prime_with_rng(128, &mut OsRng);
+        assert_eq!(format!("{:?}", secret), "SecretInt(<redacted>)");
+        assert!(!format!("{:?}", secret).contains(&secret.expose().to_string()));
+        secret.zeroize();
+        assert!(secret.expose().is_zero());
+    }
+
+    // Purpose: ensure no prime or prime-derived intermediate outlives the setup entry points
+    // Params: fast setup, reduced trusted setup and params_from_primes on this thread
+    // Output: the live SecretInt count returns to its starting value after each call
+    // Usage: `cargo test --features secret-audit -- src::setup`
+    #[cfg(feature = "secret-audit")]
+    #[test]
+    fn setup_leaves_no_live_secrets() {
+        let before = secret_audit::live_secrets();
+        let _ = fast_test_setup_seeded(3);
+        assert_eq!(secret_audit::live_secrets(), before);
+        let _ = trusted_setup_with(256, true).unwrap();
+        assert_eq!(secret_audit::live_secrets(), before);
+        let p = generate_probable_prime_with_rng(128, &mut OsRng);
+        let q = generate_probable_prime_with_rng(128, &mut OsRng);
+        assert_eq!(secret_audit::live_secrets(), before + 2);
+        let _ = params_from_primes_with(p, q, 256, true);
+        assert_eq!(secret_audit::live_secrets(), before);
     }
 }
@@ -0,0 +1,69 @@
+use num_bigint::BigUint;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use zeroize::Zeroizing;
+use crate::setup::SecretInt;
+
+/// Version of the checkpoint file layout
+const CHECKPOINT_VERSION: u32 = 1;
+
+/// First line of every checkpoint file; load refuses files without it
+const SENSITIVE_MARKER: &str = "SENSITIVE cuproof setup checkpoint: the RNG seed below determines p and q, delete after use";
+
+/// State of an interrupted `trusted_setup_resumable` run
+/// The ChaCha20 seed and word position pin down every remaining candidate, so resuming replays
+/// exactly what an uninterrupted run would have drawn. That also means the file is as sensitive
+/// as the factorization itself: it is written owner-only, marked on its first line and removed
+/// when the setup completes
+pub(crate) struct SetupCheckpoint {
+    pub bits: usize,
+    pub seed: Zeroizing<[u8; 32]>,
+    pub word_pos: u128,
+    pub candidates: u64,
+    /// p once it has been found; stored because replaying its search would repeat the work
+    pub p: Option<SecretInt>,
+}
+
+impl SetupCheckpoint {
+    /// Write the checkpoint via a temporary file and rename, so a kill mid-write keeps the previous one
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut content = Zeroizing::new(String::new());
+        content.push_str(SENSITIVE_MARKER);
+        content.push('\n');
+        content.push_str(&format!("{}\n{}\n{}\n{}\n", CHECKPOINT_VERSION, self.bits, self.word_pos, self.candidates));
+        content.push_str(&Zeroizing::new(hex::encode(*self.seed)));
+        content.push('\n');
+        if let Some(p) = &self.p { content.push_str(&Zeroizing::new(p.expose().to_str_radix(16))); }
+
+        if let Some(parent) = Path::new(path).parent() { fs::create_dir_all(parent)?; }
+        let tmp = format!("{}.tmp", path);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut f = options.open(&tmp)?;
+        f.write_all(content.as_bytes())?;
+        f.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        let content = Zeroizing::new(fs::read_to_string(path)?);
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let lines: Vec<&str> = content.lines().map(str::trim).collect();
+        if lines.first() != Some(&SENSITIVE_MARKER) { return Err(invalid("not a setup checkpoint")); }
+        if lines.len() < 6 { return Err(invalid("checkpoint file too short")); }
+        if lines[1].parse::<u32>().ok() != Some(CHECKPOINT_VERSION) { return Err(invalid("unsupported checkpoint version")); }
+        let bits = lines[2].parse().map_err(|_| invalid("invalid modulus size"))?;
+        let word_pos = lines[3].parse().map_err(|_| invalid("invalid RNG position"))?;
+        let candidates = lines[4].parse().map_err(|_| invalid("invalid candidate count"))?;
+        let mut seed = Zeroizing::new([0u8; 32]);
+        hex::decode_to_slice(lines[5], &mut *seed).map_err(|_| invalid("invalid RNG seed"))?;
+        let p = match lines.get(6).filter(|s| !s.is_empty()) {
+            Some(s) => Some(SecretInt::new(BigUint::parse_bytes(s.as_bytes(), 16).ok_or_else(|| invalid("invalid prime"))?)),
+            None => None,
+        };
+        Ok(SetupCheckpoint { bits, seed, word_pos, candidates, p })
+    }
+}
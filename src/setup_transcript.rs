@@ -0,0 +1,145 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use crate::fiat_shamir::fiat_shamir;
+use crate::primality::is_miller_rabin_witness;
+use crate::setup::{validate_params, Params, SecretInt};
+
+/// Version tag bound into the challenges and written to transcript files
+pub const SETUP_TRANSCRIPT_VERSION: u32 = 1;
+
+/// Number of n-th root challenges. validate_params trial-divides n up to 10^4, so a square factor
+/// p^2 | n has p > 10^4 and each challenge catches it with probability >= 1 - 1/p;
+/// ceil(128 / log2(10^4)) = 10 challenges give 128-bit soundness
+pub const SQUARE_FREE_ROUNDS: usize = 10;
+
+/// Non-interactive evidence that a modulus is well-formed, produced by the dealer during setup
+///
+/// What a verifying transcript proves about n, together with `validate_params`:
+/// - n is odd, has at least the declared number of bits and no prime factor below 10^4
+/// - n is not a perfect power
+/// - n is composite: `composite_witness` is a Miller-Rabin witness for n
+/// - n is square-free: `roots` are n-th roots of Fiat-Shamir challenges, which exist for all
+///   challenges only if gcd(n, phi(n)) = 1 (Gennaro-Micciancio-Rabin style)
+///
+/// What it does not prove: that n has exactly two prime factors, that the factors are of similar
+/// size, or that the dealer discarded them. A square-free n with three or more large prime factors
+/// would also verify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupTranscript {
+    pub roots: Vec<BigInt>,
+    pub composite_witness: BigInt,
+}
+
+impl SetupTranscript {
+    /// Produce the transcript for n = p * q; called by the setup with the primes still in scope
+    pub(crate) fn prove(p: &SecretInt, q: &SecretInt, n: &BigInt) -> Self {
+        let one = BigUint::one();
+        let n_u = n.magnitude();
+        let phi = SecretInt::new((p.expose() - &one) * (q.expose() - &one));
+        // d = n^-1 mod phi(n) exists because p, q are distinct primes that do not divide p - 1, q - 1
+        let d = SecretInt::new(n_u.modinv(phi.expose()).expect("n is invertible mod phi(n) for distinct primes"));
+        let roots = (0..SQUARE_FREE_ROUNDS)
+            .map(|i| BigInt::from_biguint(Sign::Plus, challenge(n, i).magnitude().modpow(d.expose(), n_u)))
+            .collect();
+        let composite_witness = (2u32..)
+            .map(BigInt::from)
+            .find(|a| is_miller_rabin_witness(n_u, a.magnitude()))
+            .expect("a composite n has a Miller-Rabin witness");
+        SetupTranscript { roots, composite_witness }
+    }
+}
+
+/// Challenge rho_i in Z_n^*, expanded from fiat_shamir over (version, n, i, counter, block)
+fn challenge(n: &BigInt, index: usize) -> BigInt {
+    let blocks = (n.bits() as usize + 128).div_ceil(256);
+    let (version, index) = (BigInt::from(SETUP_TRANSCRIPT_VERSION), BigInt::from(index));
+    let one = BigInt::one();
+    let mut counter = BigInt::zero();
+    loop {
+        let mut wide = BigInt::zero();
+        for block in 0..blocks {
+            wide = (wide << 256) + fiat_shamir(&[&version, n, &index, &counter, &BigInt::from(block)]);
+        }
+        let rho = wide % n;
+        if rho > one && rho.gcd(n) == one { return rho; }
+        counter += 1;
+    }
+}
+
+/// Check a setup transcript against the parameters it was issued with
+/// - params: params public parameters, transcript evidence produced by the dealer
+/// - returns: true if n passes validate_params, is composite and square-free (see SetupTranscript)
+/// - usage: run once when adopting a params file from a dealer, e.g. via `setup verify`
+pub fn verify_setup_transcript(params: &Params, transcript: &SetupTranscript) -> bool {
+    if validate_params(params).is_err() { return false; }
+    let n = params.n();
+    if transcript.roots.len() != SQUARE_FREE_ROUNDS { return false; }
+    let roots_ok = transcript.roots.iter().enumerate().all(|(i, sigma)| {
+        sigma > &BigInt::zero() && sigma < n && sigma.modpow(n, n) == challenge(n, i)
+    });
+    roots_ok && transcript.composite_witness > BigInt::zero()
+        && is_miller_rabin_witness(n.magnitude(), transcript.composite_witness.magnitude())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::{fast_test_setup_with_transcript, trusted_setup_with_transcript};
+
+    // Purpose: ensure honest transcripts verify and only for the params they were issued with
+    // Params: seeded fast setup and a reduced trusted setup, each with its transcript
+    // Output: verify true for matching pairs, false for swapped pairs
+    // Usage: `cargo test -- src::setup_transcript` or `cargo test`
+    #[test]
+    fn honest_transcript_verifies() {
+        let (params, transcript) = fast_test_setup_with_transcript(0);
+        assert!(verify_setup_transcript(&params, &transcript));
+        assert_eq!(params, crate::setup::insecure_test_setup(512));
+        let (other, other_transcript) = trusted_setup_with_transcript(256, true, None, |_| {}).unwrap();
+        assert!(verify_setup_transcript(&other, &other_transcript));
+        assert!(!verify_setup_transcript(&params, &other_transcript));
+        assert!(!verify_setup_transcript(&other, &transcript));
+
+        let path = std::env::temp_dir().join(format!("cuproof_setup_transcript_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        crate::util::save_setup_transcript(path, &transcript).unwrap();
+        assert_eq!(crate::util::load_setup_transcript(path).unwrap(), transcript);
+        let _ = std::fs::remove_file(path);
+    }
+
+    // Purpose: ensure malformed moduli and tampered transcripts are rejected
+    // Params: a prime "modulus", a modulus with a large square factor, edited transcripts
+    // Output: verify false in each case; the square-free check fails for p^2 * q
+    // Usage: `cargo test -- src::setup_transcript` or `cargo test`
+    #[test]
+    fn malformed_modulus_or_transcript_rejected() {
+        let (params, transcript) = fast_test_setup_with_transcript(0);
+
+        let mut tampered = transcript.clone();
+        tampered.roots[3] += 1;
+        assert!(!verify_setup_transcript(&params, &tampered));
+        let mut short = transcript.clone();
+        short.roots.pop();
+        assert!(!verify_setup_transcript(&params, &short));
+        let mut no_witness = transcript.clone();
+        no_witness.composite_witness = BigInt::one();
+        assert!(!verify_setup_transcript(&params, &no_witness));
+
+        // a prime n has no Miller-Rabin witness at all
+        let prime: BigInt = BigInt::from(2u64).pow(127) - 1;
+        assert!((2u32..200).all(|a| !is_miller_rabin_witness(prime.magnitude(), &BigUint::from(a))));
+
+        // n = p^2 * q: without phi-based roots the dealer cannot answer the challenges
+        let p: BigInt = BigInt::from(2u64).pow(61) - 1;
+        let q: BigInt = BigInt::from(2u64).pow(89) - 1;
+        let n = &p * &p * &q;
+        let cheat = Params::derive(n.clone(), n.bits() as usize, crate::setup::DEFAULT_GENERATOR_SEED);
+        assert_eq!(validate_params(&cheat), Ok(()));
+        let d = n.modinv(&((&p - 1) * (&q - 1))).unwrap();
+        let roots = (0..SQUARE_FREE_ROUNDS).map(|i| challenge(&n, i).modpow(&d, &n)).collect();
+        assert!(!verify_setup_transcript(&cheat, &SetupTranscript { roots, composite_witness: BigInt::from(2) }));
+        let forged = SetupTranscript { roots: transcript.roots.clone(), composite_witness: BigInt::from(2) };
+        assert!(!verify_setup_transcript(&cheat, &forged));
+    }
+}
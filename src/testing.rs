@@ -0,0 +1,314 @@
+//! Helpers for tests of this crate and of code embedding it (feature `testing`, off by default)
+//!
+//! Fixtures: tiny_params, sample_proof and MockTranscript give plumbing tests a proof in
+//! milliseconds and protocol tests scripted challenges (see fiat_shamir::Transcript);
+//! assert_roundtrip and assert_statement_roundtrip check every encoding a proof has without a
+//! feature.
+//!
+//! tamper_mutants turns a valid proof into the corpus of altered copies the tamper test runs
+//! through verify::cuproof_verify_detailed: every integer field replaced by 0, 1, n - 1, itself
+//! plus 1, itself plus n and a random value below n; the inner-product rounds truncated,
+//! extended, duplicated and swapped; the epoch advanced; and the statement bounds shifted by one.
+//! The test writes which check rejects each mutant to testdata/tamper_report.txt, so mutants that
+//! pass, or that only an incidental check catches (VerifyCheck::is_binding), stay visible.
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use num_bigint::{BigInt, RandBigInt};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use crate::fiat_shamir::Transcript;
+use crate::range_proof::{prove_with_rng, random_blinding, Cuproof, Statement};
+use crate::setup::{insecure_test_setup, Params};
+use crate::util::{read_proof, write_proof, ReadLimits};
+use crate::wire::{proof_from_wire, proof_to_wire};
+
+/// Params for tests: insecure_test_setup(512), fast, the same on every call and every machine
+/// - returns: Params marked insecure; their primes are public, so they protect nothing
+/// - usage: fixtures only; RangeProver and Verifier refuse them until Params::allow_insecure_params
+pub fn tiny_params() -> Params {
+    insecure_test_setup(512)
+}
+
+/// A proof under tiny_params, with its params and statement
+/// - params: seed picks the range [a, b], v in it and every blinding; the same seed gives the same proof
+/// - returns: (params, statement, proof); the proof verifies and embeds the statement
+/// - usage: plumbing tests that need a valid proof, e.g. for storage or transport, in milliseconds
+pub fn sample_proof(seed: u64) -> (Params, Statement, Cuproof) {
+    let params = tiny_params();
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let a = BigInt::from(rng.gen_range(0u32..1000));
+    let b = &a + rng.gen_range(1u32..1_000_000);
+    let v = rng.gen_bigint_range(&a, &(&b + 1));
+    let r = random_blinding(&mut rng);
+    let proof = prove_with_rng(&v, &r, &a, &b, &params, 64, &mut rng).expect("sample witnesses are in range");
+    let statement = proof.statement.clone().expect("prove embeds the statement");
+    (params, statement, proof)
+}
+
+/// A Transcript playing back scripted challenges, for protocol tests that must not depend on hashing
+/// range_proof::prove_with_transcript and verify::verify_with_transcript draw y and z, then x, per
+/// proof; give the verifier a clone of the prover's script and it sees the same challenges
+#[derive(Debug, Clone, Default)]
+pub struct MockTranscript {
+    challenges: VecDeque<BigInt>,
+    /// every message absorbed so far, in order: the five of each first message, then T1 and T2
+    pub absorbed: Vec<BigInt>,
+}
+
+impl MockTranscript {
+    /// A transcript handing out `challenges` in order, each reduced modulo the challenge modulus
+    /// as FiatShamir's are; drawing past the end panics
+    pub fn new<I: IntoIterator<Item = BigInt>>(challenges: I) -> MockTranscript {
+        MockTranscript { challenges: challenges.into_iter().collect(), absorbed: Vec::new() }
+    }
+
+    /// The scripted challenges not yet drawn
+    pub fn remaining(&self) -> usize {
+        self.challenges.len()
+    }
+
+    fn next(&mut self, n: &BigInt) -> BigInt {
+        self.challenges.pop_front().expect("MockTranscript ran out of scripted challenges") % n
+    }
+}
+
+impl Transcript for MockTranscript {
+    fn first_round(&mut self, message: [&BigInt; 5], n: &BigInt) -> (BigInt, BigInt) {
+        self.absorbed.extend(message.into_iter().cloned());
+        (self.next(n), self.next(n))
+    }
+
+    fn polynomial(&mut self, t1: &BigInt, t2: &BigInt, n: &BigInt) -> BigInt {
+        self.absorbed.extend([t1.clone(), t2.clone()]);
+        self.next(n)
+    }
+}
+
+/// Assert that `proof` survives every encoding available without a feature: Cuproof::to_bytes,
+/// the write_proof container and the wire format, which carries every field but the statement
+/// - panics: naming the first encoding that fails or decodes to another proof
+pub fn assert_roundtrip(proof: &Cuproof) {
+    let limits = ReadLimits::default();
+    let bytes = proof.to_bytes();
+    let decoded = Cuproof::from_bytes(&bytes, &limits).expect("to_bytes output decodes");
+    assert!(decoded == *proof, "to_bytes round trip changed the proof");
+    let mut container = Vec::new();
+    write_proof(&mut container, proof).expect("write_proof to memory");
+    let decoded = read_proof(&mut container.as_slice(), &limits).expect("write_proof output reads back");
+    assert!(decoded == *proof, "write_proof round trip changed the proof");
+    let wire = proof_to_wire(proof).expect("the proof fits the wire format");
+    let decoded = proof_from_wire(&wire).expect("proof_to_wire output decodes");
+    let bare = Cuproof { statement: None, ..proof.clone() };
+    assert!(decoded == bare, "wire round trip changed the proof");
+}
+
+/// Assert that Statement::from_bytes reads back what Statement::to_bytes wrote
+/// - panics: when the statement does not decode or decodes to another one
+pub fn assert_statement_roundtrip(statement: &Statement) {
+    let decoded = Statement::from_bytes(&statement.to_bytes(), &ReadLimits::default()).expect("Statement::to_bytes output decodes");
+    assert_eq!(decoded, *statement, "Statement round trip changed the statement");
+}
+
+/// A proof with one alteration, and what it was
+#[derive(Clone)]
+pub struct Mutant {
+    /// e.g. `A = 0`, `ipp.L[3] + n`, `statement.b - 1`
+    pub name: String,
+    pub proof: Cuproof,
+}
+
+/// Every altered copy of `proof` the tamper corpus checks, in a fixed order
+/// - params: proof a proof that verifies, n the modulus of its params, rng source of the random
+///   replacements (seeded for a reproducible corpus)
+/// - returns: the mutants; a replacement equal to the field it replaces is skipped, since the
+///   copy would be the proof itself
+/// - usage: the tamper test in this module; callers with their own verifier can run the same corpus
+pub fn tamper_mutants<R: Rng>(proof: &Cuproof, n: &BigInt, rng: &mut R) -> Vec<Mutant> {
+    let mut mutants = Vec::new();
+    let mut alter = |name: String, change: &dyn Fn(&mut Cuproof)| {
+        let mut altered = proof.clone();
+        change(&mut altered);
+        mutants.push(Mutant { name, proof: altered });
+    };
+
+    let mut original = proof.clone();
+    let names: Vec<String> = integer_fields(&mut original).into_iter().map(|(name, _)| name).collect();
+    for (k, field) in names.iter().enumerate() {
+        let value = integer_fields(&mut original).swap_remove(k).1.clone();
+        let replacements = [
+            (format!("{} = 0", field), BigInt::from(0)),
+            (format!("{} = 1", field), BigInt::from(1)),
+            (format!("{} = n - 1", field), n - 1),
+            (format!("{} + 1", field), &value + 1),
+            (format!("{} + n", field), &value + n),
+            (format!("{} = random", field), rng.gen_bigint_range(&BigInt::from(0), n)),
+        ];
+        for (name, replacement) in replacements {
+            if replacement == value { continue; }
+            alter(name, &|p| *integer_fields(p).swap_remove(k).1 = replacement.clone());
+        }
+    }
+
+    let rounds = proof.ipp_proof.L.len();
+    let (extra_l, extra_r) = (rng.gen_bigint_range(&BigInt::from(1), n), rng.gen_bigint_range(&BigInt::from(1), n));
+    alter("ipp rounds truncated".into(), &|p| { p.ipp_proof.L.pop(); p.ipp_proof.R.pop(); });
+    alter("ipp rounds extended".into(), &|p| { p.ipp_proof.L.push(extra_l.clone()); p.ipp_proof.R.push(extra_r.clone()); });
+    if rounds >= 2 {
+        alter("ipp round 0 duplicated over round 1".into(), &|p| {
+            p.ipp_proof.L[1] = p.ipp_proof.L[0].clone();
+            p.ipp_proof.R[1] = p.ipp_proof.R[0].clone();
+        });
+        alter("ipp rounds 0 and 1 swapped".into(), &|p| { p.ipp_proof.L.swap(0, 1); p.ipp_proof.R.swap(0, 1); });
+    }
+    alter("ipp L and R swapped".into(), &|p| core::mem::swap(&mut p.ipp_proof.L, &mut p.ipp_proof.R));
+    alter("epoch + 1".into(), &|p| p.epoch += 1);
+    if proof.statement.is_some() {
+        alter("statement.a - 1".into(), &|p| p.statement.as_mut().unwrap().a -= 1);
+        alter("statement.a + 1".into(), &|p| p.statement.as_mut().unwrap().a += 1);
+        alter("statement.b - 1".into(), &|p| p.statement.as_mut().unwrap().b -= 1);
+        alter("statement.b + 1".into(), &|p| p.statement.as_mut().unwrap().b += 1);
+    }
+    mutants
+}
+
+/// The integer fields of a proof, in the order of the save_proof container
+#[allow(non_snake_case)]
+fn integer_fields(proof: &mut Cuproof) -> Vec<(String, &mut BigInt)> {
+    let Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof, .. } = proof;
+    let mut fields: Vec<(String, &mut BigInt)> = [
+        ("A", A), ("S", S), ("T1", T1), ("T2", T2), ("tau_x", tau_x), ("mu", mu), ("t_hat", t_hat), ("C", C),
+        ("C_v1", C_v1), ("C_v2", C_v2), ("t0", t0), ("t1", t1), ("t2", t2), ("tau1", tau1), ("tau2", tau2),
+    ].into_iter().map(|(name, x)| (String::from(name), x)).collect();
+    fields.extend(ipp_proof.L.iter_mut().enumerate().map(|(i, x)| (format!("ipp.L[{}]", i), x)));
+    fields.extend(ipp_proof.R.iter_mut().enumerate().map(|(i, x)| (format!("ipp.R[{}]", i), x)));
+    fields.push((String::from("ipp.a"), &mut ipp_proof.a));
+    fields.push((String::from("ipp.b"), &mut ipp_proof.b));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write as _;
+    use crate::range_proof::prove_with_transcript;
+    use crate::verify::{cuproof_verify_detailed, verify, verify_with_transcript, VerifyCheck};
+
+    const REPORT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/tamper_report.txt");
+
+    // Purpose: every byte of a proof should be load-bearing; record which check rejects each
+    //   mutant of a valid proof and keep the matrix in testdata/tamper_report.txt
+    // Params: a seeded proof of 10 <= 37 <= 1000 under tiny_params, its
+    //   tamper_mutants drawn from a seeded rng
+    // Output: the fixture verifies; the report (mutation, rejecting check, binding / incidental /
+    //   ACCEPTED) equals the committed one. ACCEPTED rows and incidental-only rows are the known
+    //   soundness gaps: a verifier fix changes them, and the report is regenerated with the fix
+    // Usage: `cargo test -- testing`; `UPDATE_TAMPER_REPORT=1 cargo test -- testing` rewrites the
+    //   report after a verifier change
+    #[test]
+    fn tamper_corpus_matches_report() {
+        let params = tiny_params();
+        let mut rng = ChaCha20Rng::seed_from_u64(187);
+        let r = random_blinding(&mut rng);
+        let proof = prove_with_rng(&BigInt::from(37), &r, &BigInt::from(10), &BigInt::from(1000), &params, 64, &mut rng).unwrap();
+        assert_eq!(cuproof_verify_detailed(&proof, &params), Ok(()));
+
+        let mutants = tamper_mutants(&proof, params.n(), &mut rng);
+        let (mut binding, mut incidental, mut accepted) = (0, 0, Vec::new());
+        let mut rows = String::new();
+        for mutant in &mutants {
+            let (check, verdict) = match cuproof_verify_detailed(&mutant.proof, &params) {
+                Ok(()) => { accepted.push(mutant.name.as_str()); (String::from("-"), "ACCEPTED") }
+                Err(check) if check.is_binding() => { binding += 1; (check.to_string(), "binding") }
+                Err(check) => { incidental += 1; (check.to_string(), "incidental") }
+            };
+            writeln!(rows, "{:<36} {:<24} {}", mutant.name, check, verdict).unwrap();
+        }
+        let report = format!(
+            "# Tamper-rejection matrix: each mutant of a valid proof and the first check of\n\
+             # cuproof_verify_detailed that rejects it (src/testing.rs, tamper_corpus_matches_report).\n\
+             # binding: the check ties the altered value to the rest of the proof; incidental: it only\n\
+             # catches a malformed value; ACCEPTED: the mutant verifies.\n\
+             # {} mutants: {} binding, {} incidental only, {} accepted\n\
+             {}",
+            mutants.len(), binding, incidental, accepted.len(), rows
+        );
+        if std::env::var_os("UPDATE_TAMPER_REPORT").is_some() {
+            std::fs::write(REPORT, &report).unwrap();
+            return;
+        }
+        let committed = std::fs::read_to_string(REPORT).unwrap_or_default();
+        let changed: Vec<_> = report.lines().zip(committed.lines()).filter(|(new, old)| new != old).map(|(new, old)| format!("- {}\n+ {}", old, new)).collect();
+        assert!(
+            report == committed,
+            "the tamper matrix changed; review and rerun with UPDATE_TAMPER_REPORT=1 (accepted now: {:?}):\n{}",
+            accepted, changed.join("\n")
+        );
+    }
+
+    // Purpose: ensure the corpus covers every field and the structural alterations, and skips no-op copies
+    // Params: an honest proof with tau_x set to 1
+    // Output: no "tau_x = 1" mutant; the other replacements of every field, the structural and
+    //   the statement mutants are all there
+    // Usage: `cargo test -- testing`
+    #[test]
+    fn mutants_skip_unchanged_values() {
+        let params = tiny_params();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let r = random_blinding(&mut rng);
+        let mut proof = prove_with_rng(&BigInt::from(3), &r, &BigInt::from(0), &BigInt::from(9), &params, 64, &mut rng).unwrap();
+        proof.tau_x = BigInt::from(1);
+        let names: Vec<String> = tamper_mutants(&proof, params.n(), &mut rng).into_iter().map(|m| m.name).collect();
+        assert!(!names.iter().any(|name| name == "tau_x = 1"));
+        for name in ["tau_x = 0", "tau_x + 1", "ipp.R[5] + n", "ipp.b = random", "ipp rounds truncated", "epoch + 1", "statement.b + 1"] {
+            assert!(names.iter().any(|n| n == name), "missing {}", name);
+        }
+        // 29 integer fields with 6 replacements each but one, 5 inner-product, 1 epoch and 4 statement mutants
+        assert_eq!(names.len(), 29 * 6 - 1 + 5 + 1 + 4);
+    }
+
+    // Purpose: ensure sample_proof gives a reproducible, valid proof that survives every encoding
+    // Params: seeds 0 to 3, seed 1 twice
+    // Output: each proof verifies under its params, embeds its statement and round-trips; equal
+    //   seeds give equal proofs, different seeds different ones
+    // Usage: `cargo test -- testing`
+    #[test]
+    fn sample_proofs_verify_and_round_trip() {
+        for seed in 0..4 {
+            let (params, statement, proof) = sample_proof(seed);
+            assert_eq!(cuproof_verify_detailed(&proof, &params), Ok(()));
+            assert_eq!(proof.statement.as_ref(), Some(&statement));
+            assert!(statement.a <= statement.b);
+            assert_roundtrip(&proof);
+            assert_statement_roundtrip(&statement);
+        }
+        assert!(sample_proof(1).2 == sample_proof(1).2);
+        assert!(sample_proof(1).2 != sample_proof(2).2);
+    }
+
+    // Purpose: ensure MockTranscript feeds scripted challenges to the prover and verifier alike
+    // Params: tiny_params; a proof of 5 in [0, 9] made with the script y = 3, z = 5, x = 7
+    // Output: the transcript absorbed the first message and T1, T2; a clone of the script verifies
+    //   the proof, FiatShamir and another x do not; an exhausted script panics
+    // Usage: `cargo test -- testing`
+    #[test]
+    fn mock_transcript_scripts_the_challenges() {
+        let params = tiny_params();
+        let mut rng = ChaCha20Rng::seed_from_u64(196);
+        let script = MockTranscript::new([3, 5, 7].map(BigInt::from));
+        let mut prover = script.clone();
+        let proof = prove_with_transcript(&BigInt::from(5), &random_blinding(&mut rng), &BigInt::from(0), &BigInt::from(9), &params, &mut prover, &mut rng).unwrap();
+        assert_eq!(prover.remaining(), 0);
+        assert_eq!(prover.absorbed, [&proof.A, &proof.S, &proof.C, &proof.C_v1, &proof.C_v2, &proof.T1, &proof.T2].map(Clone::clone));
+        assert_eq!(proof.t_hat, &proof.t0 + &proof.t1 * 7 + &proof.t2 * 49);
+
+        assert_eq!(verify_with_transcript(&proof, &params, &mut script.clone()), Ok(()));
+        assert!(!verify(&proof, &params));
+        let mut other_x = MockTranscript::new([3, 5, 8].map(BigInt::from));
+        assert_eq!(verify_with_transcript(&proof, &params, &mut other_x), Err(VerifyCheck::PolynomialEvaluation));
+        let exhausted = std::panic::catch_unwind(|| verify_with_transcript(&proof, &params, &mut MockTranscript::new([BigInt::from(3)])));
+        assert!(exhausted.is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Per-phase timings of prove, verify and the loaders (`--time`)
+//!
+//! The instrumented code paths wrap their phases in `phase`, which checks a thread-local flag and
+//! otherwise just runs the closure; only inside `record`, on the same thread, are durations
+//! collected. Phases do not nest: the commitments and challenges of the inner-product argument
+//! count as its own time, so the phases of a report add up to at most its total. Without std
+//! there is no clock: `phase` only runs the closure and `record` is not available
+
+use core::fmt;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// What an instrumented stretch of code is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// decoding and checking params and proof files
+    Deserialize,
+    /// Fiat-Shamir hashing of y, z and x
+    Challenges,
+    /// Pedersen commitments, computed by the prover or recomputed by the verifier
+    Commitments,
+    /// the inner-product argument
+    Ipp,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::Deserialize, Phase::Challenges, Phase::Commitments, Phase::Ipp];
+
+    /// "deserialize", "challenges", "commitments" or "ipp"
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Deserialize => "deserialize",
+            Phase::Challenges => "challenges",
+            Phase::Commitments => "commitments",
+            Phase::Ipp => "ipp",
+        }
+    }
+}
+
+/// Time spent in each Phase while `record` ran, and the whole of it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    phases: [Duration; 4],
+    pub total: Duration,
+}
+
+impl PhaseTimings {
+    pub fn get(&self, phase: Phase) -> Duration {
+        self.phases[phase as usize]
+    }
+}
+
+impl fmt::Display for PhaseTimings {
+    /// `deserialize 0.41 ms, challenges 0.05 ms, commitments 38.20 ms, ipp 11.93 ms, total 52.87 ms`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for phase in Phase::ALL {
+            write!(f, "{} {:.2} ms, ", phase.name(), self.get(phase).as_secs_f64() * 1e3)?;
+        }
+        write!(f, "total {:.2} ms", self.total.as_secs_f64() * 1e3)
+    }
+}
+
+/// The phases being collected on this thread, and whether one of them is running
+#[cfg(feature = "std")]
+struct Recorder {
+    phases: [Duration; 4],
+    in_phase: bool,
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, adding the time of the phases it goes through on this thread, and its own, to `timings`
+/// - usage: `timing::record(&mut timings, || prove(&v, &r, &a, &b, &params))`; calls may
+///   be repeated to accumulate. A nested record collects into the outer one
+#[cfg(feature = "std")]
+pub fn record<T>(timings: &mut PhaseTimings, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let outer = RECORDER.with(|r| r.borrow().is_some());
+    if !outer { RECORDER.with(|r| *r.borrow_mut() = Some(Recorder { phases: [Duration::ZERO; 4], in_phase: false })); }
+    let result = f();
+    if !outer {
+        let recorder = RECORDER.with(|r| r.borrow_mut().take()).expect("record installed the recorder");
+        for (total, spent) in timings.phases.iter_mut().zip(recorder.phases) { *total += spent; }
+    }
+    timings.total += started.elapsed();
+    result
+}
+
+/// Run `f` as `phase`, timed when a `record` call on this thread is collecting and no other phase is running
+#[cfg(feature = "std")]
+pub(crate) fn phase<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    let timed = RECORDER.with(|r| match r.borrow_mut().as_mut() {
+        Some(recorder) if !recorder.in_phase => {
+            recorder.in_phase = true;
+            true
+        }
+        _ => false,
+    });
+    if !timed { return f(); }
+    let started = Instant::now();
+    let result = f();
+    let spent = started.elapsed();
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            recorder.phases[phase as usize] += spent;
+            recorder.in_phase = false;
+        }
+    });
+    result
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn phase<T>(_phase: Phase, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+/// Wall-clock time of a traced call, for the elapsed_ms field of its closing event (trace.rs)
+#[cfg(feature = "tracing")]
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "std")]
+    started: Instant,
+}
+
+#[cfg(feature = "tracing")]
+impl Stopwatch {
+    pub(crate) fn start() -> Stopwatch {
+        Stopwatch {
+            #[cfg(feature = "std")]
+            started: Instant::now(),
+        }
+    }
+
+    /// Milliseconds since `start`
+    #[cfg(feature = "std")]
+    pub(crate) fn elapsed_ms(&self) -> Option<f64> {
+        Some(self.started.elapsed().as_secs_f64() * 1e3)
+    }
+
+    /// None: without std there is no clock, and the event leaves the field out
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn elapsed_ms(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// Most resident memory this process has used, in KiB: VmHWM of /proc/self/status
+/// - returns: None where that is not available (anything but Linux)
+#[cfg(feature = "std")]
+pub fn peak_rss_kib() -> Option<u64> {
+    if !cfg!(target_os = "linux") { return None; }
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.trim_start_matches("VmHWM:").trim().trim_end_matches("kB").trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+    use num_bigint::BigInt;
+
+    // Purpose: ensure prove and verify report their phases under record and nothing outside it
+    // Params: insecure 512-bit params, one proof of 42 in [0, 100]
+    // Output: nonzero challenges, commitments and ipp time for the prover, phases summing to at
+    //         most the total; no deserialization in either; a phase run outside record is not kept
+    // Usage: `cargo test -- timing`
+    #[test]
+    fn phases_add_up_to_at_most_the_total() {
+        let params = insecure_test_setup(512);
+        let mut proving = PhaseTimings::default();
+        let proof = record(&mut proving, || {
+            crate::range_proof::prove(&BigInt::from(42), &BigInt::from(7), &BigInt::from(0), &BigInt::from(100), &params).unwrap()
+        });
+        for phase in [Phase::Challenges, Phase::Commitments, Phase::Ipp] { assert!(proving.get(phase) > Duration::ZERO, "{}", proving); }
+        assert_eq!(proving.get(Phase::Deserialize), Duration::ZERO);
+        assert!(Phase::ALL.iter().map(|&p| proving.get(p)).sum::<Duration>() <= proving.total, "{}", proving);
+
+        let mut verifying = PhaseTimings::default();
+        assert!(record(&mut verifying, || crate::verify::verify(&proof, &params)));
+        assert!(verifying.get(Phase::Commitments) > Duration::ZERO && verifying.total > Duration::ZERO, "{}", verifying);
+        phase(Phase::Ipp, || std::thread::sleep(Duration::from_millis(1)));
+        assert!(RECORDER.with(|r| r.borrow().is_none()));
+    }
+}
@@ -0,0 +1,102 @@
+//! The spans and events the library emits with the `tracing` feature
+//!
+//! Off by default. Without the feature the instrumented functions compile to their bodies alone:
+//! no span is built, no field is formatted and nothing is timed. With it every span and event
+//! below is at DEBUG level, its target the module that emits it (`cuproof::verify`, ...), and it
+//! nests under whatever span the caller has entered: a request span carrying a `request_id`
+//! encloses the `verify` span of the proof checked while serving it.
+//!
+//! Span names, event messages and field keys are stable; renaming one is a breaking change.
+//!
+//! | span | fields | entered by |
+//! |---|---|---|
+//! | `prove` | `bits`, `params` | prove and every other single-proof prover |
+//! | `aggregate_prove` | `statements`, `params` | aggregate::aggregate_prove_with_rng |
+//! | `verify` | `bits`, `params` | verify, cuproof_verify_checked, verify_statement, Verifier |
+//! | `aggregate_verify` | `statements`, `params` | aggregate::aggregate_verify |
+//! | `setup` | `bits` | the trusted setups of setup.rs |
+//! | `write_params`, `write_proof` | `params`, `proof_id` | write_params, write_proof and the save_* functions |
+//! | `read_params`, `read_proof`, `read_aggregated_proof` | `bytes` | the read_* and load_* functions |
+//! | `params_from_wire`, `proof_from_wire` | `bytes` | the wire decoders |
+//!
+//! | span | event | fields |
+//! |---|---|---|
+//! | `prove` | `proof created` / `proof refused` | `proof_id` / `error`, `elapsed_ms` |
+//! | `aggregate_prove` | `aggregated proof created` / `aggregated proof refused` | `rounds` / `error`, `elapsed_ms` |
+//! | `verify` | `proof verified` / `proof rejected` | `proof_id`, `check` on rejection, `elapsed_ms` |
+//! | `aggregate_verify` | `aggregated proof verified` / `aggregated proof rejected` | `error` on rejection, `elapsed_ms` |
+//! | `setup` | `setup phase finished`, once each for p, q and the generators | `phase`, `candidates`, `elapsed_ms` |
+//! | `setup` | `params generated` / `setup failed` | `params` / `error`, `elapsed_ms` |
+//! | `write_*` | `params written`, `proof written` | `bytes` |
+//! | `read_*` | `params read` / `proof read` / `aggregated proof read`, or `... refused` | `params` / `proof_id` / `statements`, or `error` |
+//! | `*_from_wire` | `params decoded` / `proof decoded`, or `... refused` | `params` / `proof_id`, or `error` |
+//!
+//! - `params`: the short hex fingerprint of the params (Params::fingerprint)
+//! - `proof_id`: range_proof::proof_id, the hash of the encoded proof
+//! - `bits`: the range width; `statements`: proofs aggregated; `rounds`: inner-product rounds
+//! - `check`: the verify::VerifyCheck that failed, e.g. `T1 commitment`
+//! - `phase`: `p`, `q` or `generators`; `candidates`: prime candidates drawn in that phase
+//! - `elapsed_ms`: wall time of the span, left out without std
+//! - `error`: the Display of the error returned to the caller
+//!
+//! Every field is public data. No value, blinding, prime, seed or other secret is ever recorded,
+//! and errors only describe what was wrong, never the witness.
+
+/// Run `$body` inside `$span`, then `$report` with a reference to the result and the elapsed
+/// milliseconds; `$report` emits the span's closing event. Without the feature only `$body` runs
+macro_rules! traced {
+    ($span:expr, $body:expr, |$result:pat_param, $elapsed_ms:pat_param| $report:expr) => {{
+        #[cfg(feature = "tracing")]
+        let result = {
+            let _entered = $span.entered();
+            let started = $crate::timing::Stopwatch::start();
+            let result = $body;
+            let $elapsed_ms = started.elapsed_ms();
+            let $result = &result;
+            $report;
+            result
+        };
+        #[cfg(not(feature = "tracing"))]
+        let result = $body;
+        result
+    }};
+}
+
+pub(crate) use traced;
+
+/// The `setup phase finished` events of one setup: each finish reports the candidates drawn and
+/// the time taken since the previous one
+#[cfg(feature = "std")]
+pub(crate) struct SetupPhases {
+    #[cfg(feature = "tracing")]
+    started: crate::timing::Stopwatch,
+    #[cfg(feature = "tracing")]
+    candidates: u64,
+}
+
+#[cfg(feature = "std")]
+impl SetupPhases {
+    /// Start timing the first phase, `candidates` the count drawn so far
+    pub(crate) fn start(candidates: u64) -> SetupPhases {
+        #[cfg(not(feature = "tracing"))]
+        let _ = candidates;
+        SetupPhases {
+            #[cfg(feature = "tracing")]
+            started: crate::timing::Stopwatch::start(),
+            #[cfg(feature = "tracing")]
+            candidates,
+        }
+    }
+
+    /// Emit the event of `phase` ("p", "q" or "generators") and start timing the next one
+    pub(crate) fn finish(&mut self, phase: &'static str, candidates: u64) {
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_ms = self.started.elapsed_ms();
+            tracing::debug!(phase, candidates = candidates.saturating_sub(self.candidates), elapsed_ms, "setup phase finished");
+            *self = SetupPhases::start(candidates);
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (phase, candidates);
+    }
+}
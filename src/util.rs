@@ -1,18 +1,110 @@
-use num_bigint::{BigInt, RandBigInt};
-use num_traits::Signed;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+#[cfg(feature = "std")]
 use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "std")]
 use std::fs;
-use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::io::{self, BufReader, BufWriter, Read, Write};
+#[cfg(feature = "std")]
 use std::path::Path;
-use crate::range_proof::Cuproof;
+use zeroize::Zeroizing;
+#[cfg(feature = "std")]
+use crate::bundle::{ProofBundle, MAX_BUNDLE_METADATA};
+#[cfg(feature = "std")]
+use crate::ceremony::{ContributionProof, DlogProof};
+#[cfg(feature = "std")]
+use crate::metadata::{validate_metadata, MetadataError, ParamsMetadata};
+#[cfg(feature = "std")]
+use crate::primality::{AuxiliaryCertificate, AuxiliaryStep, PrimalityConfig};
+#[cfg(feature = "std")]
+use crate::aggregate::{AggregatedCuproof, ValueCommitments, MAX_AGGREGATED_STATEMENTS};
+#[cfg(feature = "std")]
+use crate::comparison::{ComparisonProof, MAX_COMPARISON_BITS};
+#[cfg(feature = "std")]
+use crate::generators::{GeneratorVector, MAX_GENERATORS};
+#[cfg(feature = "std")]
+use crate::offline::{ChallengeBundle, FirstMessage, OfflineState};
+#[cfg(feature = "std")]
+use crate::openings::{BatchOpeningProof, MAX_BATCH_OPENINGS, MAX_OPENING_BITS};
+use crate::range_proof::{Cuproof, Statement};
+#[cfg(feature = "std")]
+use crate::poe::VerifierAssist;
+#[cfg(feature = "std")]
+use crate::range_proof::{FirstRound, IPPProof};
+#[cfg(all(feature = "std", feature = "tracing"))]
+use crate::range_proof::proof_id;
+#[cfg(feature = "std")]
+use crate::setup::{validate_params, Params, ParamsError, MAX_MODULUS_BITS, PARAMS_VERSION};
+#[cfg(feature = "std")]
+use crate::setup_transcript::{SetupTranscript, SETUP_TRANSCRIPT_VERSION};
+#[cfg(feature = "std")]
+use crate::secret::Secret;
+#[cfg(feature = "std")]
+use crate::timing::{phase, Phase};
+#[cfg(feature = "std")]
+use crate::trace::traced;
 
+/// Sample a uniform non-negative BigInt from the operating system CSPRNG
+/// - params: bits width of the range; must be nonzero
+/// - returns: BigInt uniform over [0, 2^bits), so the top bit is set only half the time
+/// - usage: prover blindings; panics on bits == 0 since the range would be empty of entropy.
+///   The result is a plain BigInt, not wiped on drop: hold a secret drawn here in a secret::Secret
+#[cfg(feature = "std")]
 pub fn random_bigint(bits: usize) -> BigInt {
-    let mut rng = OsRng;
-    rng.gen_bigint(bits as u64).abs()
+    assert!(bits > 0, "random_bigint needs at least 1 bit");
+    BigInt::from(OsRng.gen_biguint(bits as u64))
+}
+
+/// Sample a uniform BigInt below a positive bound from the operating system CSPRNG
+/// - params: n exclusive upper bound; must be positive
+/// - returns: BigInt uniform over [0, n)
+/// - usage: sampling mod n without the bias of random_bigint(k) % n; see random_below
+#[cfg(feature = "std")]
+pub fn random_bigint_below(n: &BigInt) -> BigInt {
+    random_below(n, &mut OsRng)
+}
+
+/// Sample a uniform BigInt below a positive bound from a caller-supplied CSPRNG
+/// - params: bound exclusive upper bound, must be positive; rng source of randomness
+/// - returns: BigInt uniform over [0, bound)
+/// - usage: every "random element of Z_n" or "scalar below a bound" in place of `% bound`, which
+///   is biased. Candidates have bound.bits() bits and are rejected when >= bound; each is accepted
+///   with probability bound / 2^bits > 1/2, so fewer than 2 draws are expected
+pub fn random_below<R: RngCore + CryptoRng>(bound: &BigInt, rng: &mut R) -> BigInt {
+    assert!(bound.is_positive(), "random_below needs a positive bound, got {}", bound);
+    let bound = bound.magnitude();
+    loop {
+        let candidate = rng.gen_biguint(bound.bits());
+        if &candidate < bound { return BigInt::from(candidate); }
+    }
+}
+
+/// Sample a uniform invertible element of Z_n from a caller-supplied CSPRNG
+/// - params: n modulus, must be greater than 1; rng source of randomness
+/// - returns: BigInt x in [1, n) with gcd(x, n) = 1
+/// - usage: blinding factors that must be units mod n; redraws via random_below until the gcd
+///   is 1, which for an RSA modulus fails with negligible probability
+pub fn random_coprime_to<R: RngCore + CryptoRng>(n: &BigInt, rng: &mut R) -> BigInt {
+    assert!(n > &BigInt::one(), "random_coprime_to needs a modulus above 1, got {}", n);
+    loop {
+        let x = random_below(n, rng);
+        if !x.is_zero() && x.gcd(n).is_one() { return x; }
+    }
 }
 
 pub fn inner_product(a: &[BigInt], b: &[BigInt]) -> BigInt {
-    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+    a.iter().zip(b.iter()).fold(BigInt::zero(), |mut acc, (x, y)| {
+        acc += x * y;
+        acc
+    })
 }
 
 /// Convert BigInt to hex string (two's complement positive representation)
@@ -24,31 +116,234 @@ pub fn bigint_to_hex(x: &BigInt) -> String {
     hex::encode(bytes)
 }
 
-/// Parse BigInt from hex string (no 0x prefix)
-/// - params: s hex string
-/// - returns: BigInt parsed as positive number
-/// - usage: load BigInt values from text files
-pub fn hex_to_bigint(s: &str) -> BigInt {
-    let bytes = hex::decode(s.trim()).unwrap_or_default();
-    BigInt::from_bytes_be(num_bigint::Sign::Plus, &bytes)
+/// Convert BigInt to hex string with a leading `-` for negative values
+/// - params: x reference to BigInt
+/// - returns: lowercase hex string without 0x prefix; inverse of hex_to_bigint_signed
+/// - usage: values whose sign matters, where bigint_to_hex would drop it
+pub fn bigint_to_signed_hex(x: &BigInt) -> String {
+    if x.is_negative() { format!("-{}", bigint_to_hex(x)) } else { bigint_to_hex(x) }
+}
+
+/// Why a hex string was rejected by hex_to_bigint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// no digits (also a bare `0x` or `-`)
+    Empty,
+    /// whitespace at this byte index; callers strip surrounding whitespace themselves if they allow it
+    Whitespace(usize),
+    /// a character that is not a hex digit, at this byte index
+    InvalidDigit { ch: char, index: usize },
+    /// a leading `-` where only non-negative values are accepted
+    NegativeNotAllowed,
+    /// a fixed-width field (bigint_from_hex_strict) with `found` digits instead of `expected`
+    WrongLength { expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty hex string"),
+            ParseError::Whitespace(i) => write!(f, "whitespace at position {} in hex string", i),
+            ParseError::InvalidDigit { ch, index } => write!(f, "invalid hex digit {:?} at position {}", ch, index),
+            ParseError::NegativeNotAllowed => write!(f, "negative values are not accepted here"),
+            ParseError::WrongLength { expected, found } => write!(f, "expected {} hex digits, found {}", expected, found),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+fn parse_hex(s: &str, allow_negative: bool) -> Result<BigInt, ParseError> {
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) if allow_negative => (true, rest),
+        Some(_) => return Err(ParseError::NegativeNotAllowed),
+        None => (false, s),
+    };
+    let digits = unsigned.strip_prefix("0x").or_else(|| unsigned.strip_prefix("0X")).unwrap_or(unsigned);
+    let offset = s.len() - digits.len();
+    if let Some((index, ch)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        let index = index + offset;
+        return Err(if ch.is_whitespace() { ParseError::Whitespace(index) } else { ParseError::InvalidDigit { ch, index } });
+    }
+    // all ASCII hex digits from here on
+    let magnitude = BigUint::parse_bytes(digits.as_bytes(), 16).ok_or(ParseError::Empty)?;
+    let x = BigInt::from(magnitude);
+    Ok(if negative { -x } else { x })
+}
+
+/// Parse a non-negative BigInt from a hex string
+/// - params: s hex digits in either case, optionally prefixed with 0x; odd lengths are fine
+/// - returns: BigInt, or ParseError for empty input, whitespace, non-hex characters or a `-` sign
+/// - usage: CLI arguments and file fields; inverse of bigint_to_hex for non-negative values
+pub fn hex_to_bigint(s: &str) -> Result<BigInt, ParseError> {
+    parse_hex(s, false)
+}
+
+/// Parse a BigInt from a hex string that may carry a leading `-`
+/// - params: s as for hex_to_bigint, optionally prefixed with `-` (before any 0x)
+/// - returns: BigInt or ParseError
+/// - usage: inverse of bigint_to_signed_hex, for callers that opt in to negative values
+pub fn hex_to_bigint_signed(s: &str) -> Result<BigInt, ParseError> {
+    parse_hex(s, true)
+}
+
+/// Convert a non-negative BigInt to exactly `2 * byte_width` lowercase hex digits
+/// - params: x, byte_width e.g. the byte length of n for group elements
+/// - returns: the zero-padded hex, or EncodeError for negative x or x >= 2^(8 * byte_width)
+/// - usage: strings whose length must not depend on the value (databases, JavaScript callers);
+///   inverse of bigint_from_hex_strict
+pub fn bigint_to_hex_padded(x: &BigInt, byte_width: usize) -> Result<String, EncodeError> {
+    Ok(hex::encode(bigint_to_be_bytes_padded(x, byte_width)?))
+}
+
+/// Parse exactly `2 * byte_width` hex digits as a non-negative BigInt
+/// - params: s digits in either case, no sign, prefix or whitespace; byte_width as for bigint_to_hex_padded
+/// - returns: BigInt, or ParseError::WrongLength for any other length and the hex_to_bigint errors otherwise
+pub fn bigint_from_hex_strict(s: &str, byte_width: usize) -> Result<BigInt, ParseError> {
+    if s.len() != 2 * byte_width { return Err(ParseError::WrongLength { expected: 2 * byte_width, found: s.len() }); }
+    if s.is_empty() { return Ok(BigInt::zero()); }
+    if s.starts_with("0x") || s.starts_with("0X") { return Err(ParseError::InvalidDigit { ch: 'x', index: 1 }); }
+    hex_to_bigint(s)
 }
 
-/// Strictly parse BigInt from hex string
-/// - params: s hex string without 0x
-/// - returns: io::Result<BigInt> or InvalidData on bad/empty input
+/// Parse a hex field of a params, proof or transcript file
+/// - params: s one line, surrounding whitespace ignored
+/// - returns: io::Result<BigInt>, InvalidData wrapping the ParseError on bad input
 /// - usage: robust file parsing to avoid silently accepting malformed data
+#[cfg(feature = "std")]
 fn hex_to_bigint_strict(s: &str) -> io::Result<BigInt> {
-    let t = s.trim();
-    if t.is_empty() { return Err(io::Error::new(io::ErrorKind::InvalidData, "empty hex")); }
-    let bytes = hex::decode(t).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid hex"))?;
-    if bytes.is_empty() { return Err(io::Error::new(io::ErrorKind::InvalidData, "zero-length bytes")); }
-    Ok(BigInt::from_bytes_be(num_bigint::Sign::Plus, &bytes))
+    hex_to_bigint(s.trim()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Why a BigInt has no encoding of the requested shape
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// the fixed-width and length-prefixed encodings are unsigned
+    Negative,
+    /// the value needs `needed` bytes but only `width` are available
+    TooWide { needed: usize, width: usize },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Negative => write!(f, "negative values have no unsigned encoding"),
+            EncodeError::TooWide { needed, width } => write!(f, "value needs {} bytes but the field holds {}", needed, width),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+/// Why scalar_from_be_bytes rejected its input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// the input ends inside the length prefix or the magnitude
+    Truncated { needed: usize, available: usize },
+    /// the magnitude starts with a zero byte, so the encoding is not the canonical one
+    LeadingZero,
+    /// the declared length exceeds MAX_PROOF_INT_BYTES
+    TooLarge(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { needed, available } => write!(f, "needed {} bytes but only {} remain", needed, available),
+            DecodeError::LeadingZero => write!(f, "scalar has a leading zero byte"),
+            DecodeError::TooLarge(len) => write!(f, "scalar of {} bytes exceeds MAX_PROOF_INT_BYTES", len),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Big-endian bytes of a non-negative BigInt, left-padded with zeros to exactly `width` bytes
+/// - params: x, width in bytes
+/// - returns: `width` bytes, or EncodeError for negative x or x >= 2^(8 * width); zero is all zeros
+/// - usage: fixed-width fields of the wire format, e.g. generators padded to the byte length of n
+pub fn bigint_to_be_bytes_padded(x: &BigInt, width: usize) -> Result<Vec<u8>, EncodeError> {
+    if x.is_negative() { return Err(EncodeError::Negative); }
+    let needed = (x.bits() as usize).div_ceil(8);
+    if needed > width { return Err(EncodeError::TooWide { needed, width }); }
+    let mut bytes = vec![0u8; width];
+    if needed > 0 { bytes[width - needed..].copy_from_slice(&x.magnitude().to_bytes_be()); }
+    Ok(bytes)
+}
+
+/// Non-negative BigInt from big-endian bytes
+/// - params: bytes of any length; leading zeros are ignored and the empty slice is zero
+/// - returns: BigInt; inverse of bigint_to_be_bytes_padded
+pub fn bigint_from_be_bytes(bytes: &[u8]) -> BigInt {
+    BigInt::from(BigUint::from_bytes_be(bytes))
+}
+
+/// A non-negative BigInt as a u32 big-endian byte length followed by that many bytes of its
+/// big-endian magnitude, without leading zeros (zero is the bare length 00 00 00 00)
+/// - params: x
+/// - returns: 4 + len bytes, or EncodeError for negative x or magnitudes longer than u32::MAX
+/// - usage: variable-width fields of the wire format (see the wire module)
+pub fn scalar_to_be_bytes(x: &BigInt) -> Result<Vec<u8>, EncodeError> {
+    if x.is_negative() { return Err(EncodeError::Negative); }
+    let needed = (x.bits() as usize).div_ceil(8);
+    let len = u32::try_from(needed).map_err(|_| EncodeError::TooWide { needed, width: u32::MAX as usize })?;
+    let mut bytes = Vec::with_capacity(4 + needed);
+    bytes.extend_from_slice(&len.to_be_bytes());
+    bytes.extend_from_slice(&bigint_to_be_bytes_padded(x, needed)?);
+    Ok(bytes)
+}
+
+/// Decode one scalar_to_be_bytes field from the front of `bytes`
+/// - params: bytes, possibly followed by further fields
+/// - returns: (value, bytes consumed), or DecodeError for truncated input, a leading zero byte or
+///   a length above MAX_PROOF_INT_BYTES; only the canonical encoding is accepted
+pub fn scalar_from_be_bytes(bytes: &[u8]) -> Result<(BigInt, usize), DecodeError> {
+    let prefix: [u8; 4] = bytes.get(..4).and_then(|p| p.try_into().ok()).ok_or(DecodeError::Truncated { needed: 4, available: bytes.len() })?;
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len > MAX_PROOF_INT_BYTES { return Err(DecodeError::TooLarge(len)); }
+    let magnitude = bytes.get(4..4 + len).ok_or(DecodeError::Truncated { needed: 4 + len, available: bytes.len() })?;
+    if magnitude.first() == Some(&0) { return Err(DecodeError::LeadingZero); }
+    Ok((bigint_from_be_bytes(magnitude), 4 + len))
+}
+
+/// Compare two byte strings in time that depends only on their lengths
+/// - params: a, b
+/// - returns: true if equal; different lengths are unequal (lengths are treated as public)
+/// - usage: comparisons of secret or secret-derived bytes. Comparisons of public data (proof
+///   fields, checksums, fingerprints) use `==` and are intentionally variable-time
+pub fn ct_eq_bytes(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() { return false; }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    core::hint::black_box(diff) == 0
+}
+
+/// Compare two BigInts over fixed-width big-endian buffers (see ct_eq_bytes)
+/// - params: a, b, width in bytes, at least the byte length of either operand's magnitude
+/// - returns: true if a == b. Operands wider than `width` are padded to the wider one, so only
+///   values within `width` bytes are compared in time independent of their size
+/// - usage: secret-derived BigInts; the buffers are wiped before returning
+pub fn ct_eq_bigint(a: &BigInt, b: &BigInt, width: usize) -> bool {
+    let same_sign = (a.is_negative() as u8) ^ (b.is_negative() as u8) == 0;
+    ct_eq_magnitudes(a.magnitude(), b.magnitude(), width) & same_sign
+}
+
+/// ct_eq_bigint for magnitudes; shared with SecretInt::ct_eq
+pub(crate) fn ct_eq_magnitudes(a: &BigUint, b: &BigUint, width: usize) -> bool {
+    let (a, b) = (Zeroizing::new(a.to_bytes_be()), Zeroizing::new(b.to_bytes_be()));
+    let width = width.max(a.len()).max(b.len());
+    let pad = |m: &[u8]| {
+        let mut out = Zeroizing::new(vec![0u8; width]);
+        out[width - m.len()..].copy_from_slice(m);
+        out
+    };
+    ct_eq_bytes(&pad(&a), &pad(&b))
 }
 
 /// Write all lines to a file, creating parent dirs if needed
 /// - params: path, lines
 /// - returns: io::Result
 /// - usage: helper for persisting params and proofs
+#[cfg(feature = "std")]
 fn write_lines(path: &str, lines: &[String]) -> io::Result<()> {
     if let Some(parent) = Path::new(path).parent() { fs::create_dir_all(parent)?; }
     let mut f = fs::File::create(path)?;
@@ -62,141 +357,3547 @@ fn write_lines(path: &str, lines: &[String]) -> io::Result<()> {
 /// Read all lines from a UTF-8 text file
 /// - params: path
 /// - returns: Vec of lines
-/// - usage: helper for loading params and proofs
+/// - usage: helper for loading transcripts and contribution proofs
+#[cfg(feature = "std")]
 fn read_lines(path: &str) -> io::Result<Vec<String>> {
     let content = fs::read_to_string(path)?;
     Ok(content.lines().map(|s| s.to_string()).collect())
 }
 
-/// Save public parameters (g, h, n) to a file as hex per line
-/// - params: path, g, h, n
-/// - returns: io::Result
-/// - usage: persist trusted/fast setup output for later proving/verifying
-pub fn save_params(path: &str, g: &BigInt, h: &BigInt, n: &BigInt) -> io::Result<()> {
-    let lines = vec![
-        bigint_to_hex(g),
-        bigint_to_hex(h),
-        bigint_to_hex(n),
+/// Container format version written by save_params / save_proof
+pub const FILE_FORMAT_VERSION: u16 = 1;
+
+/// Set in the version field of a params or proof container whose payload is zstd-compressed
+/// (compression feature); the rest of the field is the version of the decompressed payload
+///
+/// The compressed payload is the u32 big-endian length of the decompressed payload followed by one
+/// zstd frame. The checksum covers the compressed bytes, so it is checked before anything is inflated
+pub const COMPRESSED_FLAG: u16 = 0x8000;
+
+/// Container version of proof files carrying a statement section (Cuproof::statement); proofs
+/// without a statement are still written as FILE_FORMAT_VERSION
+pub const PROOF_FORMAT_VERSION: u16 = 2;
+
+/// Kind of file held by a container, identified by its 8-byte magic
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Params,
+    Proof,
+    Commitment,
+    Blinding,
+    AggregatedProof,
+    Generators,
+    /// the first message of an offline proof (offline::FirstMessage)
+    FirstMessage,
+    /// the challenges for a first message (offline::ChallengeBundle)
+    ChallengeBundle,
+    /// the prover state between the rounds of an offline proof (offline::OfflineState), as secret
+    /// as a blinding
+    OfflineState,
+    /// a proof that one committed value is below another (comparison::ComparisonProof)
+    ComparisonProof,
+    /// a proof with its statement and params fingerprint (bundle::ProofBundle)
+    ProofBundle,
+    /// a proof of knowledge of the openings of a list of commitments (openings::BatchOpeningProof)
+    OpeningsProof,
+}
+
+#[cfg(feature = "std")]
+impl FileKind {
+    const ALL: [FileKind; 12] = [
+        FileKind::Params, FileKind::Proof, FileKind::Commitment, FileKind::Blinding, FileKind::AggregatedProof, FileKind::Generators,
+        FileKind::FirstMessage, FileKind::ChallengeBundle, FileKind::OfflineState, FileKind::ComparisonProof, FileKind::ProofBundle,
+        FileKind::OpeningsProof,
     ];
-    write_lines(path, &lines)
+
+    pub fn magic(self) -> &'static [u8; 8] {
+        match self {
+            FileKind::Params => b"CUPARAMS",
+            FileKind::Proof => b"CUPROOF\0",
+            FileKind::Commitment => b"CUCOMMIT",
+            FileKind::Blinding => b"CUBLIND\0",
+            FileKind::AggregatedProof => b"CUAGGPRF",
+            FileKind::Generators => b"CUGENVEC",
+            FileKind::FirstMessage => b"CUFIRST\0",
+            FileKind::ChallengeBundle => b"CUCHALNG",
+            FileKind::OfflineState => b"CUOFFST\0",
+            FileKind::ComparisonProof => b"CUCMPPRF",
+            FileKind::ProofBundle => b"CUBUNDLE",
+            FileKind::OpeningsProof => b"CUOPNPRF",
+        }
+    }
+
+    /// The name with its indefinite article, as error messages use it
+    fn a_name(self) -> String {
+        let article = if matches!(self, FileKind::AggregatedProof | FileKind::OfflineState | FileKind::OpeningsProof) { "an" } else { "a" };
+        format!("{} {}", article, self.name())
+    }
+
+    /// "params", "proof", "commitment", "blinding", "aggregated proof", "generators", "first message",
+    /// "challenge bundle", "offline state", "comparison proof", "proof bundle" or "openings proof"
+    pub fn name(self) -> &'static str {
+        match self {
+            FileKind::Params => "params",
+            FileKind::Proof => "proof",
+            FileKind::Commitment => "commitment",
+            FileKind::Blinding => "blinding",
+            FileKind::AggregatedProof => "aggregated proof",
+            FileKind::Generators => "generators",
+            FileKind::FirstMessage => "first message",
+            FileKind::ChallengeBundle => "challenge bundle",
+            FileKind::OfflineState => "offline state",
+            FileKind::ComparisonProof => "comparison proof",
+            FileKind::ProofBundle => "proof bundle",
+            FileKind::OpeningsProof => "openings proof",
+        }
+    }
 }
 
-/// Load public parameters (g, h, n) from a file
-/// - params: path
-/// - returns: (g, h, n)
-/// - usage: restore parameters for proving and verifying
-pub fn load_params(path: &str) -> io::Result<(BigInt, BigInt, BigInt)> {
-    let lines = read_lines(path)?;
-    if lines.len() < 3 { return Err(io::Error::new(io::ErrorKind::InvalidData, "params file too short")); }
-    let g = hex_to_bigint_strict(&lines[0])?;
-    let h = hex_to_bigint_strict(&lines[1])?;
-    let n = hex_to_bigint_strict(&lines[2])?;
-    Ok((g, h, n))
+/// magic, u16 version, u32 payload length
+#[cfg(feature = "std")]
+pub(crate) const CONTAINER_HEADER_LEN: usize = 8 + 2 + 4;
+/// SHA-256 over header and payload
+#[cfg(feature = "std")]
+const CONTAINER_TRAILER_LEN: usize = 32;
+
+/// Why a params or proof file was rejected before its contents were parsed
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// the file is not the expected kind; `found` names the kind it is, if it is a cuproof file at all
+    BadMagic { expected: FileKind, found: Option<FileKind> },
+    /// the container version is not FILE_FORMAT_VERSION (or PROOF_FORMAT_VERSION for proofs)
+    UnsupportedVersion(u16),
+    /// the file ends inside the 14-byte header
+    TruncatedHeader,
+    /// the header declares more payload (plus checksum) than the file holds
+    LengthMismatch { declared: u32, available: usize },
+    /// the SHA-256 trailer does not match header and payload
+    ChecksumMismatch,
+    /// bytes follow the checksum
+    TrailingGarbage(usize),
+    /// the payload is zstd-compressed (COMPRESSED_FLAG) where it is not read: a reader other than
+    /// load_params / load_proof, or a build without the compression feature
+    Compressed,
+    /// the compressed payload does not inflate to the length it declares
+    BadCompression(String),
 }
 
-/// Save Cuproof to a file (simple line-based hex format)
-/// - params: path, proof
-/// - returns: io::Result
-/// - usage: send proof file to verifier
-pub fn save_proof(path: &str, proof: &Cuproof) -> io::Result<()> {
-    let mut lines = Vec::new();
-    // Scalars
-    lines.push(bigint_to_hex(&proof.A));
-    lines.push(bigint_to_hex(&proof.S));
-    lines.push(bigint_to_hex(&proof.T1));
-    lines.push(bigint_to_hex(&proof.T2));
-    lines.push(bigint_to_hex(&proof.tau_x));
-    lines.push(bigint_to_hex(&proof.mu));
-    lines.push(bigint_to_hex(&proof.t_hat));
-    lines.push(bigint_to_hex(&proof.C));
-    lines.push(bigint_to_hex(&proof.C_v1));
-    lines.push(bigint_to_hex(&proof.C_v2));
-    lines.push(bigint_to_hex(&proof.t0));
-    lines.push(bigint_to_hex(&proof.t1));
-    lines.push(bigint_to_hex(&proof.t2));
-    lines.push(bigint_to_hex(&proof.tau1));
-    lines.push(bigint_to_hex(&proof.tau2));
-    // IPP vectors sizes
-    lines.push(proof.ipp_proof.L.len().to_string());
-    for x in &proof.ipp_proof.L { lines.push(bigint_to_hex(x)); }
-    lines.push(proof.ipp_proof.R.len().to_string());
-    for x in &proof.ipp_proof.R { lines.push(bigint_to_hex(x)); }
-    // IPP scalars
-    lines.push(bigint_to_hex(&proof.ipp_proof.a));
-    lines.push(bigint_to_hex(&proof.ipp_proof.b));
-    write_lines(path, &lines)
+#[cfg(feature = "std")]
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::BadMagic { expected, found: Some(found) } => {
+                write!(f, "expected {} file, found {} file", expected.a_name(), found.a_name())
+            }
+            FormatError::BadMagic { expected, found: None } => write!(f, "not a cuproof {} file", expected.name()),
+            FormatError::UnsupportedVersion(v) => write!(f, "unsupported file format version {}", v),
+            FormatError::TruncatedHeader => write!(f, "file is truncated inside its header"),
+            FormatError::LengthMismatch { declared, available } => write!(
+                f, "header declares a {}-byte payload but only {} bytes follow (truncated file?)", declared, available
+            ),
+            FormatError::ChecksumMismatch => write!(f, "checksum mismatch: the file is corrupted"),
+            FormatError::TrailingGarbage(n) => write!(f, "{} unexpected bytes after the checksum", n),
+            FormatError::Compressed => write!(f, "the payload is zstd-compressed; load_params and load_proof read it with the compression feature"),
+            FormatError::BadCompression(reason) => write!(f, "corrupt compressed payload: {}", reason),
+        }
+    }
 }
 
-/// Load Cuproof from a file written by save_proof
-/// - params: path
-/// - returns: Cuproof
-/// - usage: verifier loads file to verify
-pub fn load_proof(path: &str) -> io::Result<Cuproof> {
-    let lines = read_lines(path)?;
-    let mut i = 0usize;
-    let take = |i: &mut usize| -> io::Result<String> {
-        let s = lines.get(*i).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unexpected end of file"))?.clone();
-        *i += 1;
-        Ok(s)
+#[cfg(feature = "std")]
+impl core::error::Error for FormatError {}
+
+#[cfg(feature = "std")]
+fn container_checksum(header_and_payload: &[u8]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(header_and_payload).into()
+}
+
+/// Wrap `lines` in a container of `kind` and write it
+#[cfg(test)]
+fn write_container(path: &str, kind: FileKind, lines: &[String]) -> io::Result<()> {
+    write_file(path, &container_bytes(kind, FILE_FORMAT_VERSION, lines)?)
+}
+
+/// Bytes of a container of `kind` and `version` holding `lines`
+#[cfg(feature = "std")]
+fn container_bytes(kind: FileKind, version: u16, lines: &[String]) -> io::Result<Vec<u8>> {
+    let mut encoder = Encoder::new();
+    encoder.begin();
+    for line in lines { encoder.line(line); }
+    encoder.finish(kind, version)?;
+    Ok(encoder.buf)
+}
+
+/// Container writer that builds header, payload and checksum in one buffer
+/// Integers are written as hex straight from their digits, so encoding a proof allocates the
+/// buffer (and its growth) instead of a byte vector and a String per value
+/// - usage: `Encoder::new().encode_proof(&proof)` gives the bytes save_proof writes; keep one
+///   Encoder around to encode many proofs into the same buffer
+#[derive(Debug, Default)]
+pub struct Encoder {
+    buf: Vec<u8>,
+    lines: usize,
+}
+
+impl Encoder {
+    pub fn new() -> Encoder {
+        Encoder::default()
+    }
+
+    /// Encode `proof` as save_proof would, reusing this encoder's buffer
+    /// - returns: the container bytes, valid until the next call
+    #[cfg(feature = "std")]
+    pub fn encode_proof(&mut self, proof: &Cuproof) -> io::Result<&[u8]> {
+        self.begin();
+        let version = self.proof_lines(proof);
+        self.finish(FileKind::Proof, version)?;
+        Ok(&self.buf)
+    }
+
+    /// Encode the payload of the save_proof container alone (Cuproof::to_bytes), reusing this encoder's buffer
+    /// - returns: the payload bytes, valid until the next call
+    pub fn encode_proof_payload(&mut self, proof: &Cuproof) -> &[u8] {
+        self.buf.clear();
+        self.lines = 0;
+        self.proof_lines(proof);
+        &self.buf
+    }
+
+    /// Write the payload lines of `proof`
+    /// - returns: the container version they belong in
+    fn proof_lines(&mut self, proof: &Cuproof) -> u16 {
+        for x in [&proof.A, &proof.S, &proof.T1, &proof.T2, &proof.tau_x, &proof.mu, &proof.t_hat, &proof.C, &proof.C_v1, &proof.C_v2] {
+            self.hex(x);
+        }
+        for x in [&proof.t0, &proof.t1, &proof.t2, &proof.tau1, &proof.tau2] { self.hex(x); }
+        // IPP vectors, each preceded by its length
+        self.display(proof.ipp_proof.L.len());
+        for x in &proof.ipp_proof.L { self.hex(x); }
+        self.display(proof.ipp_proof.R.len());
+        for x in &proof.ipp_proof.R { self.hex(x); }
+        self.hex(&proof.ipp_proof.a);
+        self.hex(&proof.ipp_proof.b);
+        self.display(proof.epoch);
+        let version = match &proof.statement {
+            // Statement section, only in PROOF_FORMAT_VERSION containers
+            Some(statement) => {
+                self.statement_lines(statement);
+                PROOF_FORMAT_VERSION
+            }
+            None => FILE_FORMAT_VERSION,
+        };
+        // Assist section, last, in containers of either version
+        if let Some(assist) = &proof.assist {
+            self.line(ASSIST_HEADER);
+            self.hex(&assist.q1);
+            self.hex(&assist.q2);
+        }
+        version
+    }
+
+    /// The statement section: header, commitment, signed a and b, bits, then the label as hex of
+    /// its UTF-8 bytes and the params fingerprint
+    fn statement_lines(&mut self, statement: &Statement) {
+        self.line(STATEMENT_HEADER);
+        self.hex(&statement.commitment);
+        self.signed_hex(&statement.a);
+        self.signed_hex(&statement.b);
+        self.display(statement.bits);
+        self.bytes_hex(statement.label.as_bytes());
+        self.bytes_hex(&statement.params_fingerprint);
+    }
+
+    /// Encode `proof` as save_aggregated_proof would, reusing this encoder's buffer
+    /// - returns: the container bytes, valid until the next call
+    #[cfg(feature = "std")]
+    pub fn encode_aggregated_proof(&mut self, proof: &AggregatedCuproof) -> io::Result<&[u8]> {
+        self.begin();
+        self.display(proof.commitments.len());
+        for c in &proof.commitments {
+            for x in [&c.C, &c.C_v1, &c.C_v2] { self.hex(x); }
+        }
+        for x in [&proof.A, &proof.S, &proof.T1, &proof.T2, &proof.tau_x, &proof.mu, &proof.t_hat] { self.hex(x); }
+        for x in [&proof.t0, &proof.t1, &proof.t2, &proof.tau1, &proof.tau2] { self.hex(x); }
+        self.display(proof.ipp_proof.L.len());
+        for x in &proof.ipp_proof.L { self.hex(x); }
+        self.display(proof.ipp_proof.R.len());
+        for x in &proof.ipp_proof.R { self.hex(x); }
+        self.hex(&proof.ipp_proof.a);
+        self.hex(&proof.ipp_proof.b);
+        self.display(proof.epoch);
+        for statement in &proof.statements { self.statement_lines(statement); }
+        self.finish(FileKind::AggregatedProof, FILE_FORMAT_VERSION)?;
+        Ok(&self.buf)
+    }
+
+    /// The id, A, S, C_v1 and C_v2 of a first message, then `challenges` and its statement section
+    #[cfg(feature = "std")]
+    fn first_message_lines(&mut self, message: &FirstMessage, challenges: Option<(&BigInt, &BigInt, &[u8])>) {
+        self.bytes_hex(&message.id);
+        for x in [&message.A, &message.S, &message.C_v1, &message.C_v2] { self.hex(x); }
+        if let Some((y, z, nonce)) = challenges {
+            self.hex(y);
+            self.hex(z);
+            self.bytes_hex(nonce);
+        }
+        self.statement_lines(&message.statement);
+    }
+
+    /// The payload of an offline state: id, params fingerprint, signed a and b, the
+    /// dimension and the second round's seed, then alpha, rho, A, S, C, C_v1, C_v2 and the
+    /// vectors d (signed), sL and sR
+    #[cfg(feature = "std")]
+    fn offline_state_lines(&mut self, state: &OfflineState) {
+        let round = &state.round;
+        self.bytes_hex(&state.id);
+        self.bytes_hex(&state.params_fingerprint);
+        self.signed_hex(&state.a);
+        self.signed_hex(&state.b);
+        self.display(round.d.expose().len());
+        self.bytes_hex(state.finish_seed.expose());
+        for x in [round.alpha.expose(), round.rho.expose(), &round.A, &round.S, &round.C, &round.C_v1, &round.C_v2] { self.hex(x); }
+        for x in round.d.expose() { self.signed_hex(x); }
+        for x in round.sL.expose().iter().chain(round.sR.expose()) { self.hex(x); }
+    }
+
+    /// The buffer, e.g. after encode_proof
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Start a container; the header is filled in by finish
+    #[cfg(feature = "std")]
+    fn begin(&mut self) {
+        self.buf.clear();
+        self.buf.resize(CONTAINER_HEADER_LEN, 0);
+        self.lines = 0;
+    }
+
+    /// Start a payload line; lines are joined by `\n` with none after the last
+    fn next_line(&mut self) {
+        if self.lines > 0 { self.buf.push(b'\n'); }
+        self.lines += 1;
+    }
+
+    fn line(&mut self, s: &str) {
+        self.next_line();
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    fn display(&mut self, x: impl fmt::Display) {
+        self.next_line();
+        fmt::Write::write_fmt(&mut TextBuf(&mut self.buf), format_args!("{}", x)).expect("writing to a Vec cannot fail");
+    }
+
+    /// Same text as bigint_to_hex: big-endian magnitude bytes, zero as "00"
+    fn hex(&mut self, x: &BigInt) {
+        self.next_line();
+        self.magnitude_hex(x);
+    }
+
+    /// Same text as bigint_to_signed_hex
+    fn signed_hex(&mut self, x: &BigInt) {
+        self.next_line();
+        if x.is_negative() { self.buf.push(b'-'); }
+        self.magnitude_hex(x);
+    }
+
+    fn bytes_hex(&mut self, bytes: &[u8]) {
+        self.next_line();
+        for &b in bytes { self.push_hex_byte(b); }
+    }
+
+    fn magnitude_hex(&mut self, x: &BigInt) {
+        let mut started = false;
+        for digit in x.magnitude().iter_u64_digits().rev() {
+            for b in digit.to_be_bytes() {
+                // leading zero bytes are skipped, as in to_bytes_be
+                if !started && b == 0 { continue; }
+                started = true;
+                self.push_hex_byte(b);
+            }
+        }
+        if !started { self.buf.extend_from_slice(b"00"); }
+    }
+
+    fn push_hex_byte(&mut self, b: u8) {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        self.buf.extend_from_slice(&[DIGITS[(b >> 4) as usize], DIGITS[(b & 0xf) as usize]]);
+    }
+
+    /// Fill in the header and append the checksum
+    #[cfg(feature = "std")]
+    fn finish(&mut self, kind: FileKind, version: u16) -> io::Result<()> {
+        let payload_len = self.buf.len() - CONTAINER_HEADER_LEN;
+        let len = u32::try_from(payload_len).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "payload exceeds 4 GiB"))?;
+        self.buf[..8].copy_from_slice(kind.magic());
+        self.buf[8..10].copy_from_slice(&version.to_be_bytes());
+        self.buf[10..CONTAINER_HEADER_LEN].copy_from_slice(&len.to_be_bytes());
+        let checksum = container_checksum(&self.buf);
+        self.buf.extend_from_slice(&checksum);
+        Ok(())
+    }
+}
+
+/// fmt::Write into an Encoder buffer, as io::Write for Vec is std-only
+struct TextBuf<'a>(&'a mut Vec<u8>);
+
+impl fmt::Write for TextBuf<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Write `bytes` to `path` atomically, creating parent dirs if needed
+#[cfg(feature = "std")]
+#[cfg(any(test, feature = "json"))]
+fn write_file(path: &str, bytes: &[u8]) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| w.write_all(bytes))
+}
+
+/// How save_params_with / save_proof_with replace an existing file, and whether they compress it
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SaveOptions {
+    backup: bool,
+    #[cfg(feature = "compression")]
+    compression: Option<i32>,
+}
+
+#[cfg(feature = "std")]
+impl SaveOptions {
+    /// Copy a pre-existing destination to `<path>.bak` before replacing it
+    pub fn with_backup(mut self) -> SaveOptions {
+        self.backup = true;
+        self
+    }
+
+    pub fn backup(&self) -> bool {
+        self.backup
+    }
+
+    /// Compress params and proof files with zstd at `level` (see compress_container)
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, level: i32) -> SaveOptions {
+        self.compression = Some(level);
+        self
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn compression(&self) -> Option<i32> {
+        self.compression
+    }
+}
+
+/// Distinguishes temporary files of concurrent writers in one process
+#[cfg(feature = "std")]
+static TEMP_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Replace `path` with what `write` produces, all or nothing
+/// - params: path, options, write fills a temporary file in the same directory
+/// - returns: io::Result; on any error the temporary file is removed and `path` keeps its old content
+/// - usage: save_params / save_proof. The temporary file is fsynced and then renamed over `path`
+///   (rename replaces atomically on Unix; on Windows std uses MoveFileExW with
+///   MOVEFILE_REPLACE_EXISTING), so a crash leaves either the old or the new file, never a truncated one
+#[cfg(feature = "std")]
+pub fn write_atomically<F>(path: &str, options: &SaveOptions, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let target = Path::new(path);
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
     };
+    fs::create_dir_all(dir)?;
+    let name = target.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let counter = TEMP_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let temp = dir.join(format!(".{}.{}-{}.tmp", name.to_string_lossy(), std::process::id(), counter));
+    let file = fs::OpenOptions::new().write(true).create_new(true).open(&temp)?;
+    let result = (|| {
+        let mut w = BufWriter::new(file);
+        write(&mut w)?;
+        let file = w.into_inner().map_err(io::IntoInnerError::into_error)?;
+        file.sync_all()?;
+        if options.backup() && target.exists() {
+            let mut backup = target.as_os_str().to_owned();
+            backup.push(".bak");
+            fs::copy(target, &backup)?;
+        }
+        fs::rename(&temp, target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+    // persist the rename itself; directories cannot be opened for syncing on Windows
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
 
-    // Scalars
-    let A = hex_to_bigint_strict(&take(&mut i)?)?;
-    let S = hex_to_bigint_strict(&take(&mut i)?)?;
-    let T1 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let T2 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let tau_x = hex_to_bigint_strict(&take(&mut i)?)?;
-    let mu = hex_to_bigint_strict(&take(&mut i)?)?;
-    let t_hat = hex_to_bigint_strict(&take(&mut i)?)?;
-    let C = hex_to_bigint_strict(&take(&mut i)?)?;
-    let C_v1 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let C_v2 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let t0 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let t1 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let t2 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let tau1 = hex_to_bigint_strict(&take(&mut i)?)?;
-    let tau2 = hex_to_bigint_strict(&take(&mut i)?)?;
+/// Check the container framing of `bytes` and return its version and payload; compressed
+/// payloads (COMPRESSED_FLAG) are refused with FormatError::Compressed
+#[cfg(feature = "std")]
+pub(crate) fn open_container(bytes: &[u8], kind: FileKind) -> Result<(u16, &[u8]), FormatError> {
+    match open_framed(bytes, kind)? {
+        (_, true, _) => Err(FormatError::Compressed),
+        (version, false, payload) => Ok((version, payload)),
+    }
+}
 
-    // IPP vectors sizes
-    let l_len: usize = take(&mut i)?.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid L length"))?;
-    if l_len == 0 { return Err(io::Error::new(io::ErrorKind::InvalidData, "L length must be > 0")); }
-    let mut L_vec = Vec::with_capacity(l_len);
-    for _ in 0..l_len { L_vec.push(hex_to_bigint_strict(&take(&mut i)?)?); }
-    let r_len: usize = take(&mut i)?.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid R length"))?;
-    if r_len == 0 { return Err(io::Error::new(io::ErrorKind::InvalidData, "R length must be > 0")); }
-    if r_len != l_len { return Err(io::Error::new(io::ErrorKind::InvalidData, "L and R length mismatch")); }
-    let mut R_vec = Vec::with_capacity(r_len);
-    for _ in 0..r_len { R_vec.push(hex_to_bigint_strict(&take(&mut i)?)?); }
+/// open_container, accepting params and proof containers with COMPRESSED_FLAG set
+/// - returns: the version without the flag, whether the flag was set, and the payload as stored
+#[cfg(feature = "std")]
+pub(crate) fn open_framed(bytes: &[u8], kind: FileKind) -> Result<(u16, bool, &[u8]), FormatError> {
+    if !bytes.starts_with(kind.magic()) {
+        let found = FileKind::ALL.into_iter().find(|k| bytes.starts_with(k.magic()));
+        return Err(FormatError::BadMagic { expected: kind, found });
+    }
+    if bytes.len() < CONTAINER_HEADER_LEN { return Err(FormatError::TruncatedHeader); }
+    let field = u16::from_be_bytes([bytes[8], bytes[9]]);
+    let (version, compressed) = (field & !COMPRESSED_FLAG, field & COMPRESSED_FLAG != 0);
+    let known = version == FILE_FORMAT_VERSION || (kind == FileKind::Proof && version == PROOF_FORMAT_VERSION);
+    if !known || (compressed && !matches!(kind, FileKind::Params | FileKind::Proof)) { return Err(FormatError::UnsupportedVersion(field)); }
+    let declared = u32::from_be_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]);
+    let available = bytes.len() - CONTAINER_HEADER_LEN;
+    let end = CONTAINER_HEADER_LEN + declared as usize;
+    if available < declared as usize + CONTAINER_TRAILER_LEN {
+        return Err(FormatError::LengthMismatch { declared, available });
+    }
+    if bytes.len() > end + CONTAINER_TRAILER_LEN { return Err(FormatError::TrailingGarbage(bytes.len() - end - CONTAINER_TRAILER_LEN)); }
+    if bytes[end..] != container_checksum(&bytes[..end]) { return Err(FormatError::ChecksumMismatch); }
+    Ok((version, compressed, &bytes[CONTAINER_HEADER_LEN..end]))
+}
 
-    // IPP scalars
-    let a = hex_to_bigint_strict(&take(&mut i)?)?;
-    let b = hex_to_bigint_strict(&take(&mut i)?)?;
-    let zero = BigInt::from(0);
-    if A == zero || S == zero || T1 == zero || T2 == zero { return Err(io::Error::new(io::ErrorKind::InvalidData, "zero scalar in header")); }
+/// Largest payload a compressed container may declare, the read limit of an uncompressed file;
+/// the declared length is checked before inflating, and the frame must inflate to exactly it
+#[cfg(feature = "std")]
+pub const MAX_DECOMPRESSED_BYTES: usize = DEFAULT_MAX_READ_BYTES;
+
+/// zstd level of compressed files when the caller has no preference
+#[cfg(feature = "compression")]
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// A params or proof container with its payload compressed with zstd (see COMPRESSED_FLAG)
+/// - params: bytes an uncompressed container, as write_params or write_proof write it; level a zstd
+///   level, e.g. DEFAULT_COMPRESSION_LEVEL (1 to 22, higher is smaller and slower)
+/// - returns: the compressed container, which load_params / load_proof read like the original;
+///   InvalidInput for other files, payloads over MAX_DECOMPRESSED_BYTES and levels zstd does not have
+/// - usage: SaveOptions::with_compression; `cuproof setup --compress` and `prove --compress`
+#[cfg(feature = "compression")]
+pub fn compress_container(bytes: &[u8], level: i32) -> io::Result<Vec<u8>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let kind = [FileKind::Params, FileKind::Proof].into_iter().find(|k| bytes.starts_with(k.magic()))
+        .ok_or_else(|| invalid("only params and proof containers are compressed".to_string()))?;
+    let (version, payload) = open_container(bytes, kind).map_err(|e| invalid(e.to_string()))?;
+    if payload.len() > MAX_DECOMPRESSED_BYTES { return Err(invalid(format!("payload exceeds the {} bytes a compressed file may hold", MAX_DECOMPRESSED_BYTES))); }
+    if !zstd::compression_level_range().contains(&level) { return Err(invalid(format!("zstd has no compression level {}", level))); }
+    let frame = zstd::bulk::compress(payload, level)?;
+    let mut encoder = Encoder::new();
+    encoder.begin();
+    encoder.buf.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    encoder.buf.extend_from_slice(&frame);
+    encoder.finish(kind, version | COMPRESSED_FLAG)?;
+    Ok(encoder.buf)
+}
+
+/// The payload of a container with COMPRESSED_FLAG set, inflated
+/// - returns: the payload; InvalidData wrapping LimitError::DecompressedTooLarge when it declares
+///   more than MAX_DECOMPRESSED_BYTES, or FormatError::BadCompression for a broken frame or one
+///   inflating to another length than declared
+#[cfg(feature = "compression")]
+pub(crate) fn decompress_payload(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let bad = |reason: String| io::Error::new(io::ErrorKind::InvalidData, FormatError::BadCompression(reason));
+    let (declared, frame) = payload.split_first_chunk::<4>().ok_or_else(|| bad("no decompressed length".to_string()))?;
+    let declared = u32::from_be_bytes(*declared) as usize;
+    if declared > MAX_DECOMPRESSED_BYTES { return Err(limit_exceeded(LimitError::DecompressedTooLarge { declared, max: MAX_DECOMPRESSED_BYTES })); }
+    // the capacity bounds the output: a frame inflating past it is an error, not an allocation
+    let inflated = zstd::bulk::decompress(frame, declared).map_err(|e| bad(e.to_string()))?;
+    if inflated.len() != declared { return Err(bad(format!("inflates to {} bytes, not the {} declared", inflated.len(), declared))); }
+    Ok(inflated)
+}
 
-    let ipp_proof = crate::range_proof::IPPProof { L: L_vec, R: R_vec, a, b };
-    Ok(Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof })
+#[cfg(all(feature = "std", not(feature = "compression")))]
+pub(crate) fn decompress_payload(_payload: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, FormatError::Compressed))
 }
 
+/// Text files from before the container: UTF-8 starting with a hex line
+#[cfg(feature = "std")]
+pub(crate) fn is_legacy_text(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok_and(|text| {
+        text.lines().next().is_some_and(|line| !line.trim().is_empty() && line.trim().bytes().all(|c| c.is_ascii_hexdigit()))
+    })
+}
+
+/// True if `path` holds a params or proof file in the line-based text format that predates the container
+/// - usage: the CLI points such files at `cuproof convert`; load_params / load_proof still read them
+#[cfg(feature = "std")]
+pub fn is_legacy_file(path: &str) -> io::Result<bool> {
+    Ok(is_legacy_text(&fs::read(path)?))
+}
+
+/// Format version of the file `bytes` of `kind`
+/// - returns: the container version, 0 for legacy text files, None for JSON and armored files and
+///   anything that is not a container of `kind`
+/// - usage: reporting what a loaded file was (the CLI's --json output); loaders check it themselves
+#[cfg(feature = "std")]
+pub fn container_version(bytes: &[u8], kind: FileKind) -> Option<u16> {
+    if is_legacy_text(bytes) { return Some(0); }
+    if !bytes.starts_with(kind.magic()) || bytes.len() < CONTAINER_HEADER_LEN { return None; }
+    Some(u16::from_be_bytes([bytes[8], bytes[9]]) & !COMPRESSED_FLAG)
+}
+
+/// True if `bytes` are a params or proof container whose payload is compressed (COMPRESSED_FLAG)
+#[cfg(feature = "std")]
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    [FileKind::Params, FileKind::Proof].into_iter().any(|kind| bytes.starts_with(kind.magic()))
+        && bytes.len() >= CONTAINER_HEADER_LEN
+        && u16::from_be_bytes([bytes[8], bytes[9]]) & COMPRESSED_FLAG != 0
+}
+
+/// Read the payload lines of a container file of `kind`
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use num_bigint::BigInt;
+fn read_container(path: &str, kind: FileKind) -> io::Result<Vec<String>> {
+    container_lines(fs::read(path)?, kind).map(|(_, lines)| lines)
+}
 
-    // Purpose: verify hex roundtrip and inner_product basic behavior
-    // Params: small vectors and integers
-    // Output: equality assertions
-    // Usage: `cargo test -- src::util` or `cargo test`
-    #[test]
-    fn hex_roundtrip_and_inner_product() {
-        let x = BigInt::from(123456789u64);
-        let hx = bigint_to_hex(&x);
-        let x2 = hex_to_bigint(&hx);
-        assert_eq!(x, x2);
+/// Container version and payload lines of the container `bytes` of `kind`, migrating legacy text
+/// files in memory (reported as version 0) and inflating compressed payloads
+/// - returns: (version, lines), or InvalidData wrapping a FormatError for files with broken framing
+#[cfg(feature = "std")]
+fn container_lines(bytes: Vec<u8>, kind: FileKind) -> io::Result<(u16, Vec<String>)> {
+    if is_legacy_text(&bytes) {
+        let text = String::from_utf8(bytes).expect("is_legacy_text checked UTF-8");
+        return Ok((0, text.lines().map(str::to_string).collect()));
+    }
+    let (version, compressed, payload) = open_framed(&bytes, kind).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if compressed { return Ok((version, payload_lines(&decompress_payload(payload)?)?)); }
+    Ok((version, payload_lines(payload)?))
+}
 
-        let a = vec![BigInt::from(1), BigInt::from(2), BigInt::from(3)];
-        let b = vec![BigInt::from(4), BigInt::from(5), BigInt::from(6)];
-        let ip = inner_product(&a, &b);
-        assert_eq!(ip, BigInt::from(32)); // 1*4 + 2*5 + 3*6
+/// The lines of a container payload
+#[cfg(feature = "std")]
+pub(crate) fn payload_lines(payload: &[u8]) -> io::Result<Vec<String>> {
+    let text = std::str::from_utf8(payload).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "payload is not UTF-8"))?;
+    Ok(text.lines().map(str::to_string).collect())
+}
+
+/// JSON documents (save_proof_json / save_params_json) are told apart from containers and legacy
+/// text files by their first non-whitespace byte
+#[cfg(feature = "std")]
+pub(crate) fn is_json(bytes: &[u8]) -> bool {
+    bytes.iter().find(|c| !c.is_ascii_whitespace()) == Some(&b'{')
+}
+
+/// Armored proofs (Cuproof::to_armored) start with their BEGIN line after optional whitespace
+#[cfg(feature = "std")]
+pub(crate) fn is_armored(bytes: &[u8]) -> bool {
+    let start = bytes.iter().position(|c| !c.is_ascii_whitespace()).unwrap_or(bytes.len());
+    bytes[start..].starts_with(b"-----BEGIN CUPROOF-----")
+}
+
+#[cfg(feature = "armor")]
+pub(crate) fn proof_from_armored(bytes: &[u8]) -> io::Result<Cuproof> {
+    let text = std::str::from_utf8(bytes).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "armored proof is not UTF-8"))?;
+    Cuproof::from_armored(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(feature = "std")]
+#[cfg(not(feature = "armor"))]
+pub(crate) fn proof_from_armored(_bytes: &[u8]) -> io::Result<Cuproof> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, "armored proofs need the armor feature"))
+}
+
+#[cfg(feature = "json")]
+fn write_json<T: serde::Serialize>(path: &str, document: &T) -> io::Result<()> {
+    write_file(path, json_text(document)?.as_bytes())
+}
+
+/// A document as save_params_json / save_proof_json write it: pretty-printed, newline-terminated
+#[cfg(feature = "json")]
+fn json_text<T: serde::Serialize>(document: &T) -> io::Result<String> {
+    let mut text = serde_json::to_string_pretty(document).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    text.push('\n');
+    Ok(text)
+}
+
+#[cfg(feature = "json")]
+pub(crate) fn params_from_json(bytes: &[u8]) -> io::Result<Params> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, format!("invalid JSON params: {}", msg));
+    let document: crate::serde_support::ParamsJson = serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+    document.into_params().map_err(invalid)
+}
+
+#[cfg(all(feature = "std", not(feature = "json")))]
+pub(crate) fn params_from_json(_bytes: &[u8]) -> io::Result<Params> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, "JSON params files need the json feature"))
+}
+
+#[cfg(feature = "json")]
+pub(crate) fn proof_from_json(bytes: &[u8]) -> io::Result<Cuproof> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, format!("invalid JSON proof: {}", msg));
+    let document: crate::serde_support::ProofJson = serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+    document.into_proof().map_err(invalid)
+}
+
+#[cfg(all(feature = "std", not(feature = "json")))]
+pub(crate) fn proof_from_json(_bytes: &[u8]) -> io::Result<Cuproof> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, "JSON proof files need the json feature"))
+}
+
+/// Save public parameters as a JSON document (schema in serde_support::ParamsJson)
+/// - params: path, params
+/// - returns: io::Result
+/// - usage: storing params where JSON tooling reads them; load_params detects the format.
+///   The metadata section is not written
+#[cfg(feature = "json")]
+pub fn save_params_json(path: &str, params: &Params) -> io::Result<()> {
+    let document = crate::serde_support::ParamsJson::new(params).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    write_json(path, &document)
+}
+
+/// Write params in the save_params_json format to any writer
+/// - params: w, params
+/// - returns: number of bytes written
+#[cfg(feature = "json")]
+pub fn write_params_json<W: Write + ?Sized>(w: &mut W, params: &Params) -> io::Result<usize> {
+    let document = crate::serde_support::ParamsJson::new(params).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let text = json_text(&document)?;
+    w.write_all(text.as_bytes())?;
+    Ok(text.len())
+}
+
+/// Load public parameters from a JSON document written by save_params_json and validate them
+/// - params: path
+/// - returns: Params; InvalidData for other formats and for params load_params would reject
+#[cfg(feature = "json")]
+pub fn load_params_json(path: &str) -> io::Result<Params> {
+    let params = params_from_json(&fs::read(path)?)?;
+    validate_params(&params).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(params)
+}
+
+/// Save a proof as a JSON document (schema in serde_support::ProofJson)
+/// - params: path, proof, bits size of the modulus the proof was made under; group elements are
+///   padded to its byte length
+/// - returns: io::Result; InvalidInput if a group element does not fit in `bits` bits
+/// - usage: storing proofs in JSON databases; load_proof detects the format
+#[cfg(feature = "json")]
+pub fn save_proof_json(path: &str, proof: &Cuproof, bits: usize) -> io::Result<()> {
+    let document = crate::serde_support::ProofJson::new(proof, bits).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    write_json(path, &document)
+}
+
+/// Write a proof in the save_proof_json format to any writer
+/// - params: w, proof, bits as for save_proof_json
+/// - returns: number of bytes written
+#[cfg(feature = "json")]
+pub fn write_proof_json<W: Write + ?Sized>(w: &mut W, proof: &Cuproof, bits: usize) -> io::Result<usize> {
+    let document = crate::serde_support::ProofJson::new(proof, bits).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let text = json_text(&document)?;
+    w.write_all(text.as_bytes())?;
+    Ok(text.len())
+}
+
+/// Load a proof from a JSON document written by save_proof_json
+/// - params: path
+/// - returns: Cuproof; InvalidData for other formats and for proofs failing the load_proof checks
+#[cfg(feature = "json")]
+pub fn load_proof_json(path: &str) -> io::Result<Cuproof> {
+    proof_from_json(&fs::read(path)?)
+}
+
+/// First line of the optional metadata section of a params file
+#[cfg(feature = "std")]
+const METADATA_HEADER: &str = "metadata 1";
+
+/// Line recording `Params::is_insecure`, written after the epoch
+#[cfg(feature = "std")]
+const INSECURE_MARKER: &str = "INSECURE test parameters: the factorization of n is public, never use in production";
+
+/// Save public parameters to a file: g, h, n as hex per line, then modulus size, version, generator seed
+/// and epoch, then the INSECURE marker and the metadata section if they apply, wrapped in a
+/// checksummed `CUPARAMS` container (see FormatError)
+/// - params: path, params
+/// - returns: io::Result
+/// - usage: persist trusted/fast setup output for later proving/verifying
+#[cfg(feature = "std")]
+pub fn save_params(path: &str, params: &Params) -> io::Result<()> {
+    save_params_with(path, params, &SaveOptions::default())
+}
+
+/// save_params with control over how an existing file is replaced and whether it is compressed
+/// - params: path, params, options e.g. `SaveOptions::default().with_backup()`, or with_compression(level)
+/// - returns: io::Result; the file at `path` is replaced atomically (see write_atomically)
+#[cfg(feature = "std")]
+pub fn save_params_with(path: &str, params: &Params, options: &SaveOptions) -> io::Result<()> {
+    #[cfg(feature = "compression")]
+    if let Some(level) = options.compression() {
+        let bytes = compress_container(&encode_params(params)?, level)?;
+        return write_atomically(path, options, |w| w.write_all(&bytes));
+    }
+    write_atomically(path, options, |w| write_params(w, params).map(|_| ()))
+}
+
+/// Write params in the save_params format to any writer
+/// - params: w, params
+/// - returns: number of bytes written
+/// - usage: params kept in object stores or sent over the network, without a temp file
+#[cfg(feature = "std")]
+pub fn write_params<W: Write + ?Sized>(w: &mut W, params: &Params) -> io::Result<usize> {
+    traced!(
+        tracing::debug_span!("write_params", params = %params.fingerprint_hex()),
+        encode_params(params).and_then(|bytes| w.write_all(&bytes).map(|()| bytes.len())),
+        |written, _| if let Ok(bytes) = written { tracing::debug!(bytes, "params written") }
+    )
+}
+
+/// The bytes save_params writes
+#[cfg(feature = "std")]
+pub(crate) fn encode_params(params: &Params) -> io::Result<Vec<u8>> {
+    container_bytes(FileKind::Params, FILE_FORMAT_VERSION, &params_lines(params))
+}
+
+/// The payload lines of a params container
+#[cfg(feature = "std")]
+fn params_lines(params: &Params) -> Vec<String> {
+    let mut lines = vec![
+        bigint_to_hex(params.g()),
+        bigint_to_hex(params.h()),
+        bigint_to_hex(params.n()),
+        params.bits().to_string(),
+        params.version().to_string(),
+        params.generator_seed().unwrap_or("").to_string(),
+        params.epoch().to_string(),
+    ];
+    if params.is_insecure() { lines.push(INSECURE_MARKER.to_string()); }
+    if let Some(metadata) = params.metadata() { lines.extend(metadata_lines(metadata)); }
+    lines
+}
+
+/// In-memory codec: exactly the payload of the save_params container, without its header and checksum
+#[cfg(feature = "std")]
+impl Params {
+    /// The payload save_params wraps in its container, metadata section included
+    /// - returns: bytes determined by the params alone, so equal params always encode identically
+    pub fn to_bytes(&self) -> Vec<u8> {
+        params_lines(self).join("\n").into_bytes()
+    }
+
+    /// Decode to_bytes output and validate it like load_params
+    /// - params: bytes a payload, limits as for read_params
+    /// - returns: Params, or a ParamsLoadError (TooLarge beyond `limits`)
+    pub fn from_bytes(bytes: &[u8], limits: &ReadLimits) -> Result<Params, ParamsLoadError> {
+        if bytes.len() > limits.max_bytes() { return Err(ParamsLoadError::TooLarge { limit: limits.max_bytes() }); }
+        let params = payload_lines(bytes).and_then(|lines| params_from_lines(&lines)).map_err(params_load_error)?;
+        checked_params(params)
+    }
+}
+
+/// The metadata section: a header, then one `key value` line per field (`generator_seed` and
+/// `certificate` repeat). A certificate line is the base prime followed by `prime:witness` links, in hex
+#[cfg(feature = "std")]
+fn metadata_lines(metadata: &ParamsMetadata) -> Vec<String> {
+    let mut lines = vec![
+        METADATA_HEADER.to_string(),
+        format!("created_at {}", metadata.created_at),
+        format!("crate_version {}", metadata.crate_version),
+        format!("bits {}", metadata.bits),
+    ];
+    if let Some(c) = metadata.primality {
+        lines.push(format!("primality {} {} {}", c.mr_rounds, u8::from(c.use_baillie_psw), u8::from(c.provable)));
+    }
+    lines.extend(metadata.generator_seeds.iter().map(|seed| format!("generator_seed {}", seed)));
+    for certificate in &metadata.certificates {
+        let mut line = format!("certificate {}", certificate.base.to_str_radix(16));
+        for step in &certificate.steps {
+            line.push_str(&format!(" {}:{}", step.prime.to_str_radix(16), step.witness.to_str_radix(16)));
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Parse the lines after METADATA_HEADER
+#[cfg(feature = "std")]
+fn parse_metadata(lines: &[String]) -> io::Result<ParamsMetadata> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("invalid metadata: {}", msg));
+    let hex = |s: &str| {
+        if s.len() > MAX_MODULUS_BITS / 4 {
+            return Err(limit_exceeded(LimitError::IntegerTooLarge { field: "certificate", max_bytes: MAX_MODULUS_BITS / 8 }));
+        }
+        BigUint::parse_bytes(s.as_bytes(), 16).ok_or_else(|| invalid("bad hex in certificate"))
+    };
+    let flag = |s: &str| match s { "0" => Ok(false), "1" => Ok(true), _ => Err(invalid("primality flag must be 0 or 1")) };
+    let (mut created_at, mut crate_version, mut bits) = (None, None, None);
+    let mut metadata = ParamsMetadata {
+        created_at: 0, crate_version: String::new(), bits: 0,
+        primality: None, generator_seeds: Vec::new(), certificates: Vec::new(),
+    };
+    for line in lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "created_at" => created_at = Some(value.parse().map_err(|_| invalid("created_at"))?),
+            "crate_version" => crate_version = Some(value.to_string()),
+            "bits" => bits = Some(value.parse().map_err(|_| invalid("bits"))?),
+            "primality" => {
+                let fields: Vec<&str> = value.split(' ').collect();
+                let [rounds, bpsw, provable] = fields.as_slice() else { return Err(invalid("primality needs 3 fields")); };
+                metadata.primality = Some(PrimalityConfig {
+                    mr_rounds: rounds.parse().map_err(|_| invalid("mr_rounds"))?,
+                    use_baillie_psw: flag(bpsw)?,
+                    provable: flag(provable)?,
+                });
+            }
+            "generator_seed" => metadata.generator_seeds.push(value.to_string()),
+            "certificate" => {
+                if metadata.certificates.len() == MAX_METADATA_CERTIFICATES {
+                    return Err(limit_exceeded(LimitError::TooManyEntries { what: "certificates", max: MAX_METADATA_CERTIFICATES }));
+                }
+                if value.split(' ').count() > MAX_CERTIFICATE_STEPS + 1 {
+                    return Err(limit_exceeded(LimitError::TooManyEntries { what: "certificate steps", max: MAX_CERTIFICATE_STEPS }));
+                }
+                let mut parts = value.split(' ');
+                let base = hex(parts.next().unwrap_or(""))?;
+                let steps = parts.map(|link| {
+                    let (prime, witness) = link.split_once(':').ok_or_else(|| invalid("certificate link needs prime:witness"))?;
+                    Ok(AuxiliaryStep { prime: hex(prime)?, witness: hex(witness)? })
+                }).collect::<io::Result<Vec<_>>>()?;
+                metadata.certificates.push(AuxiliaryCertificate { base, steps });
+            }
+            _ => return Err(invalid(&format!("unknown key {}", key))),
+        }
+    }
+    metadata.created_at = created_at.ok_or_else(|| invalid("missing created_at"))?;
+    metadata.crate_version = crate_version.ok_or_else(|| invalid("missing crate_version"))?;
+    metadata.bits = bits.ok_or_else(|| invalid("missing bits"))?;
+    Ok(metadata)
+}
+
+/// Load public parameters from a file written by save_params or save_params_json (told apart by
+/// the first byte), compressed or not, and run validate_params on them, plus validate_metadata when the file has a metadata section
+/// - params: path
+/// - returns: Params; InvalidData wrapping a setup::ParamsError or metadata::MetadataError when
+///   validation fails. Files without metadata load with `metadata()` None; files over DEFAULT_MAX_READ_BYTES are rejected
+/// - usage: restore parameters for proving and verifying
+#[cfg(feature = "std")]
+pub fn load_params(path: &str) -> io::Result<Params> {
+    Ok(read_params(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())?)
+}
+
+/// Load public parameters without running validate_params
+/// - params: path
+/// - returns: Params, even when n is below the declared size or the generators are degenerate; legacy 3-line files report the bit length of n as their size, files without
+///   an epoch line are at epoch 0 and files without a metadata section have no metadata. The
+///   INSECURE marker is restored, the `allow_insecure_params` acknowledgement is not
+/// - usage: tests and tooling that need to inspect deliberately broken params files
+#[cfg(feature = "std")]
+pub fn load_params_unchecked(path: &str) -> io::Result<Params> {
+    let limits = ReadLimits::default();
+    let bytes = read_limited(&mut BufReader::new(fs::File::open(path)?), &limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("params file exceeds {} bytes", limits.max_bytes())))?;
+    decode_params_unchecked(bytes)
+}
+
+/// load_params_unchecked over bytes already read
+#[cfg(feature = "std")]
+fn decode_params_unchecked(bytes: Vec<u8>) -> io::Result<Params> {
+    if is_json(&bytes) { return params_from_json(&bytes); }
+    let (_, lines) = container_lines(bytes, FileKind::Params)?;
+    params_from_lines(&lines)
+}
+
+/// Params from the payload lines of a params container, unvalidated
+#[cfg(feature = "std")]
+pub(crate) fn params_from_lines(lines: &[String]) -> io::Result<Params> {
+    if lines.len() < 3 { return Err(io::Error::new(io::ErrorKind::InvalidData, "params file too short")); }
+    let g = hex_field(&lines[0], "g", MAX_MODULUS_BITS / 8)?;
+    let h = hex_field(&lines[1], "h", MAX_MODULUS_BITS / 8)?;
+    let n = hex_field(&lines[2], "n", MAX_MODULUS_BITS / 8)?;
+    let bits = match lines.get(3).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => s.parse::<usize>().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid modulus size"))?,
+        None => n.bits() as usize,
+    };
+    if let Some(s) = lines.get(4).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let version = s.parse::<u32>().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid params version"))?;
+        if version != PARAMS_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported params version {}", version)));
+        }
+    }
+    let seed = lines.get(5).map(|s| s.trim()).filter(|s| !s.is_empty()).map(str::to_string);
+    let epoch = match lines.get(6).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => s.parse::<u64>().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid generator epoch"))?,
+        None => 0,
+    };
+    let insecure = lines.get(7).is_some_and(|s| s.trim() == INSECURE_MARKER);
+    let next = if insecure { 8 } else { 7 };
+    let metadata = match lines.get(next) {
+        Some(header) if header.trim() == METADATA_HEADER => Some(parse_metadata(&lines[next + 1..])?),
+        Some(other) if !other.trim().is_empty() => return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected content after params")),
+        _ => None,
+    };
+    let params = Params::new(g, h, n, bits).with_generator_seed(seed).with_epoch(epoch).with_metadata(metadata);
+    Ok(if insecure { params.mark_insecure() } else { params })
+}
+
+/// Load public parameters, rejecting files whose modulus is below `min_bits`
+/// - params: path, min_bits required modulus size
+/// - returns: Params or InvalidData when the recorded size is too small
+/// - usage: verifiers enforcing a security policy on externally supplied params
+#[cfg(feature = "std")]
+pub fn load_params_min_bits(path: &str, min_bits: usize) -> io::Result<Params> {
+    let params = load_params(path)?;
+    if params.bits() < min_bits {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("modulus size {} bits below required minimum {} bits", params.bits(), min_bits),
+        ));
+    }
+    Ok(params)
+}
+
+/// Save a setup transcript: version, root count, the roots and the composite witness as hex per line
+/// - params: path, transcript
+/// - returns: io::Result
+/// - usage: written next to the params file by `setup trusted`
+#[cfg(feature = "std")]
+pub fn save_setup_transcript(path: &str, transcript: &SetupTranscript) -> io::Result<()> {
+    let mut lines = vec![SETUP_TRANSCRIPT_VERSION.to_string(), transcript.roots.len().to_string()];
+    lines.extend(transcript.roots.iter().map(bigint_to_hex));
+    lines.push(bigint_to_hex(&transcript.composite_witness));
+    write_lines(path, &lines)
+}
+
+/// Load a setup transcript written by save_setup_transcript
+/// - params: path
+/// - returns: SetupTranscript or InvalidData for unknown versions and truncated files
+/// - usage: `setup verify` before adopting a dealer's params
+#[cfg(feature = "std")]
+pub fn load_setup_transcript(path: &str) -> io::Result<SetupTranscript> {
+    let lines = read_lines(path)?;
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let version: u32 = lines.first().and_then(|s| s.trim().parse().ok()).ok_or_else(|| invalid("invalid transcript version"))?;
+    if version != SETUP_TRANSCRIPT_VERSION { return Err(invalid("unsupported transcript version")); }
+    let count: usize = lines.get(1).and_then(|s| s.trim().parse().ok()).ok_or_else(|| invalid("invalid root count"))?;
+    if lines.len() < count + 3 { return Err(invalid("transcript file too short")); }
+    let roots = lines[2..2 + count].iter().map(|s| hex_to_bigint_strict(s)).collect::<io::Result<Vec<_>>>()?;
+    let composite_witness = hex_to_bigint_strict(&lines[2 + count])?;
+    Ok(SetupTranscript { roots, composite_witness })
+}
+
+/// Save a ceremony contribution proof: commitment and response for g, then for h, as hex per line
+/// - params: path, proof
+/// - returns: io::Result
+/// - usage: published next to the contributed params file by `ceremony contribute`
+#[cfg(feature = "std")]
+pub fn save_contribution_proof(path: &str, proof: &ContributionProof) -> io::Result<()> {
+    let lines = vec![
+        bigint_to_hex(&proof.g.commitment),
+        bigint_to_hex(&proof.g.response),
+        bigint_to_hex(&proof.h.commitment),
+        bigint_to_hex(&proof.h.response),
+    ];
+    write_lines(path, &lines)
+}
+
+/// Load a ceremony contribution proof written by save_contribution_proof
+/// - params: path
+/// - returns: ContributionProof
+/// - usage: `ceremony verify` pairs each proof with its params file
+#[cfg(feature = "std")]
+pub fn load_contribution_proof(path: &str) -> io::Result<ContributionProof> {
+    let lines = read_lines(path)?;
+    if lines.len() < 4 { return Err(io::Error::new(io::ErrorKind::InvalidData, "contribution proof file too short")); }
+    let dlog = |i: usize| -> io::Result<DlogProof> {
+        Ok(DlogProof { commitment: hex_to_bigint_strict(&lines[i])?, response: hex_to_bigint_strict(&lines[i + 1])? })
+    };
+    Ok(ContributionProof { g: dlog(0)?, h: dlog(2)? })
+}
+
+/// Save a Pedersen commitment: its hex and the fingerprint of the params it was made under, in a
+/// checksummed `CUCOMMIT` container
+/// - params: path, commitment, params the commitment was made under
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: written by `cuproof commit`, read back by `prove --commitment`
+#[cfg(feature = "std")]
+pub fn save_commitment(path: &str, commitment: &BigInt, params: &Params) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_commitment(w, commitment, params).map(|_| ()))
+}
+
+/// Write a commitment in the save_commitment format to any writer
+/// - params: w, commitment, params the commitment was made under
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_commitment<W: Write + ?Sized>(w: &mut W, commitment: &BigInt, params: &Params) -> io::Result<usize> {
+    let lines = [bigint_to_hex(commitment), hex::encode(params.fingerprint())];
+    let bytes = container_bytes(FileKind::Commitment, FILE_FORMAT_VERSION, &lines)?;
+    w.write_all(&bytes)?;
+    Ok(bytes.len())
+}
+
+/// Load a commitment written by save_commitment
+/// - params: path, params the commitment must have been made under
+/// - returns: the commitment; InvalidData for broken files, commitments not below n and files
+///   recording another params fingerprint
+#[cfg(feature = "std")]
+pub fn load_commitment(path: &str, params: &Params) -> io::Result<BigInt> {
+    read_commitment(&mut BufReader::new(fs::File::open(path)?), params, &ReadLimits::default())
+}
+
+/// Read a commitment in the save_commitment format from a stream
+/// - params: r any reader, params as for load_commitment, limits
+/// - returns: the commitment, or the errors of load_commitment; InvalidData beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_commitment<R: Read>(r: &mut R, params: &Params, limits: &ReadLimits) -> io::Result<BigInt> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let bytes = read_limited(r, limits)?.ok_or_else(|| invalid("commitment exceeds the read limit"))?;
+    let (_, payload) = open_container(&bytes, FileKind::Commitment).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let lines = payload_lines(payload)?;
+    let [commitment, fingerprint] = lines.as_slice() else { return Err(invalid("commitment file must hold two lines")) };
+    if fingerprint.trim() != hex::encode(params.fingerprint()) {
+        return Err(invalid("commitment was made under different params"));
+    }
+    let commitment = hex_field(commitment, "commitment", params.n().bits().div_ceil(8) as usize)?;
+    if !crate::commitment::is_canonical_commitment(params, &commitment) { return Err(invalid("commitment is not a nonzero residue modulo n")); }
+    Ok(commitment)
+}
+
+/// Save a commitment blinding as hex in a checksummed `CUBLIND\0` container
+/// - params: path, blinding
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: `commit --blinding-out`; the blinding opens the commitment, so the file is as secret as the value
+#[cfg(feature = "std")]
+pub fn save_blinding(path: &str, blinding: &BigInt) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_blinding(w, blinding).map(|_| ()))
+}
+
+/// Write a blinding in the save_blinding format to any writer
+/// - params: w, blinding
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_blinding<W: Write + ?Sized>(w: &mut W, blinding: &BigInt) -> io::Result<usize> {
+    let line = Secret::new(vec![bigint_to_hex(blinding)]);
+    let bytes = Secret::new(container_bytes(FileKind::Blinding, FILE_FORMAT_VERSION, line.expose())?);
+    w.write_all(bytes.expose())?;
+    Ok(bytes.expose().len())
+}
+
+/// Load a blinding written by save_blinding
+/// - params: path
+/// - returns: the blinding; InvalidData for broken files
+#[cfg(feature = "std")]
+pub fn load_blinding(path: &str) -> io::Result<BigInt> {
+    read_blinding(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())
+}
+
+/// Read a blinding in the save_blinding format from a stream
+/// - params: r any reader, limits
+/// - returns: the blinding; InvalidData for broken input and beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_blinding<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<BigInt> {
+    let bytes = Secret::new(read_limited(r, limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "blinding exceeds the read limit"))?);
+    let (_, payload) = open_container(bytes.expose(), FileKind::Blinding).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    match Secret::new(payload_lines(payload)?).expose().as_slice() {
+        [blinding] => hex_to_bigint_strict(blinding),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "blinding file must hold one line")),
+    }
+}
+
+/// Encrypt `bytes` under `passphrase` (see cuproof::encrypted) and save them
+/// - params: path, bytes e.g. what write_blinding or write_offline_state wrote to a buffer, passphrase
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: secrets at rest (`--encrypt` of the CLI); load_encrypted reads them back
+#[cfg(feature = "encryption")]
+pub fn save_encrypted(path: &str, bytes: &[u8], passphrase: &[u8]) -> io::Result<()> {
+    save_encrypted_with(path, bytes, passphrase, &crate::encrypted::KdfParams::default())
+}
+
+/// save_encrypted with the Argon2id settings `kdf`; InvalidInput wrapping an EncryptionError for
+/// settings outside the bounds load_encrypted accepts
+#[cfg(feature = "encryption")]
+pub fn save_encrypted_with(path: &str, bytes: &[u8], passphrase: &[u8], kdf: &crate::encrypted::KdfParams) -> io::Result<()> {
+    let encrypted = crate::encrypted::encrypt_with(bytes, passphrase, kdf, &mut OsRng).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    write_atomically(path, &SaveOptions::default(), |w| w.write_all(&encrypted))
+}
+
+/// Load and decrypt a file written by save_encrypted
+/// - params: path, passphrase
+/// - returns: the plaintext, wiped on drop; InvalidData wrapping an EncryptionError, which tells a
+///   wrong passphrase (WrongPassphrase) from an altered file (Tampered); `Error::from` recovers it
+#[cfg(feature = "encryption")]
+pub fn load_encrypted(path: &str, passphrase: &[u8]) -> io::Result<Secret<Vec<u8>>> {
+    let bytes = read_limited(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "encrypted file exceeds the read limit"))?;
+    crate::encrypted::decrypt(&bytes, passphrase).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Save a generator vector: the params fingerprint, the label as hex of its UTF-8 bytes and the
+/// count (together the derivation seed, see GeneratorVector::seed), then one generator per line, in
+/// a checksummed `CUGENVEC` container
+/// - params: path, vector
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: written by `cuproof generators`, read back by `aggregate --generators` and `verify --generators`
+#[cfg(feature = "std")]
+pub fn save_generators(path: &str, vector: &GeneratorVector) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_generators(w, vector).map(|_| ()))
+}
+
+/// Write a generator vector in the save_generators format to any writer
+/// - params: w, vector
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_generators<W: Write + ?Sized>(w: &mut W, vector: &GeneratorVector) -> io::Result<usize> {
+    let mut lines = vec![hex::encode(vector.params_fingerprint), hex::encode(vector.label.as_bytes()), vector.count().to_string()];
+    lines.extend(vector.generators.iter().map(bigint_to_hex));
+    let bytes = container_bytes(FileKind::Generators, FILE_FORMAT_VERSION, &lines)?;
+    w.write_all(&bytes)?;
+    Ok(bytes.len())
+}
+
+/// Load a generator vector written by save_generators
+/// - params: path
+/// - returns: the vector as stored; InvalidData for broken files and more than MAX_GENERATORS
+///   generators. Whether it matches its params is for GeneratorVector::check to tell
+#[cfg(feature = "std")]
+pub fn load_generators(path: &str) -> io::Result<GeneratorVector> {
+    read_generators(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())
+}
+
+/// Read a generator vector in the save_generators format from a stream
+/// - params: r any reader, limits
+/// - returns: the vector, or the errors of load_generators; InvalidData beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_generators<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<GeneratorVector> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let bytes = read_limited(r, limits)?.ok_or_else(|| invalid("generator vector exceeds the read limit"))?;
+    let (_, payload) = open_container(&bytes, FileKind::Generators).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    generators_from_lines(&payload_lines(payload)?)
+}
+
+/// A generator vector from the payload lines of its container
+#[cfg(feature = "std")]
+pub(crate) fn generators_from_lines(lines: &[String]) -> io::Result<GeneratorVector> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let [fingerprint, label, count, generators @ ..] = lines else { return Err(invalid("generator vector file too short")) };
+    let mut params_fingerprint = [0u8; 32];
+    hex::decode_to_slice(fingerprint.trim(), &mut params_fingerprint).map_err(|_| invalid("invalid params fingerprint"))?;
+    let label = hex::decode(label.trim()).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| invalid("invalid generator label"))?;
+    let count: usize = count.trim().parse().map_err(|_| invalid("invalid generator count"))?;
+    if count > MAX_GENERATORS { return Err(limit_exceeded(LimitError::TooManyGenerators(count))); }
+    if count != generators.len() {
+        return Err(limit_exceeded(LimitError::CountExceedsInput { declared: count, available: generators.len() }));
+    }
+    let generators = generators.iter().map(|line| hex_field(line, "generator", MAX_MODULUS_BITS / 8)).collect::<io::Result<Vec<_>>>()?;
+    Ok(GeneratorVector { params_fingerprint, label, generators })
+}
+
+/// Save the first message of an offline proof: its id, A, S, C_v1 and C_v2, then the statement
+/// section, in a checksummed `CUFIRST\0` container
+/// - params: path, message
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: written by `cuproof prove-offline-commit`, carried to the online machine
+#[cfg(feature = "std")]
+pub fn save_first_message(path: &str, message: &FirstMessage) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_first_message(w, message).map(|_| ()))
+}
+
+/// Write a first message in the save_first_message format to any writer
+/// - params: w, message
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_first_message<W: Write + ?Sized>(w: &mut W, message: &FirstMessage) -> io::Result<usize> {
+    let mut encoder = Encoder::new();
+    encoder.begin();
+    encoder.first_message_lines(message, None);
+    encoder.finish(FileKind::FirstMessage, FILE_FORMAT_VERSION)?;
+    w.write_all(&encoder.buf)?;
+    Ok(encoder.buf.len())
+}
+
+/// Load a first message written by save_first_message
+/// - params: path
+/// - returns: the message; InvalidData for broken files
+#[cfg(feature = "std")]
+pub fn load_first_message(path: &str) -> io::Result<FirstMessage> {
+    read_first_message(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())
+}
+
+/// Read a first message in the save_first_message format from a stream
+/// - params: r any reader, limits
+/// - returns: the message, or the errors of load_first_message; InvalidData beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_first_message<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<FirstMessage> {
+    let bytes = read_limited(r, limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "first message exceeds the read limit"))?;
+    let (_, payload) = open_container(&bytes, FileKind::FirstMessage).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    first_message_from_lines(&payload_lines(payload)?, false).map(|(message, _)| message)
+}
+
+/// Save a challenge bundle: the lines of its first message with y, z and the hex of the nonce
+/// (empty for none) after C_v2, in a checksummed `CUCHALNG` container
+/// - params: path, bundle
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: written online by `cuproof prove-offline-challenge`, read by `prove-offline-finish`
+#[cfg(feature = "std")]
+pub fn save_challenge_bundle(path: &str, bundle: &ChallengeBundle) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_challenge_bundle(w, bundle).map(|_| ()))
+}
+
+/// Write a challenge bundle in the save_challenge_bundle format to any writer
+/// - params: w, bundle
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_challenge_bundle<W: Write + ?Sized>(w: &mut W, bundle: &ChallengeBundle) -> io::Result<usize> {
+    let mut encoder = Encoder::new();
+    encoder.begin();
+    encoder.first_message_lines(&bundle.message, Some((&bundle.y, &bundle.z, &bundle.nonce)));
+    encoder.finish(FileKind::ChallengeBundle, FILE_FORMAT_VERSION)?;
+    w.write_all(&encoder.buf)?;
+    Ok(encoder.buf.len())
+}
+
+/// Load a challenge bundle written by save_challenge_bundle
+/// - params: path
+/// - returns: the bundle as stored; InvalidData for broken files. Whether its challenges are right
+///   is for offline_finish to tell
+#[cfg(feature = "std")]
+pub fn load_challenge_bundle(path: &str) -> io::Result<ChallengeBundle> {
+    read_challenge_bundle(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())
+}
+
+/// Read a challenge bundle in the save_challenge_bundle format from a stream
+/// - params: r any reader, limits
+/// - returns: the bundle, or the errors of load_challenge_bundle; InvalidData beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_challenge_bundle<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<ChallengeBundle> {
+    let bytes = read_limited(r, limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "challenge bundle exceeds the read limit"))?;
+    let (_, payload) = open_container(&bytes, FileKind::ChallengeBundle).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let (message, challenges) = first_message_from_lines(&payload_lines(payload)?, true)?;
+    let (y, z, nonce) = challenges.expect("first_message_from_lines reads the challenges when asked");
+    Ok(ChallengeBundle { message, nonce, y, z })
+}
+
+/// y, z and the nonce of a challenge bundle
+#[cfg(feature = "std")]
+type BundleChallenges = (BigInt, BigInt, Vec<u8>);
+
+/// A first message, and the challenges and nonce after C_v2 when `with_challenges`, from the payload lines of its container
+#[cfg(feature = "std")]
+pub(crate) fn first_message_from_lines(lines: &[String], with_challenges: bool) -> io::Result<(FirstMessage, Option<BundleChallenges>)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let (scalars, fields) = if with_challenges { (6, 7) } else { (4, 4) };
+    if lines.len() < 1 + fields { return Err(invalid("first message file too short")); }
+    let mut id = [0u8; 16];
+    hex::decode_to_slice(lines[0].trim(), &mut id).map_err(|_| invalid("invalid state id"))?;
+    let ints = lines[1..=scalars].iter().map(|line| hex_field(line, "first message", MAX_PROOF_INT_BYTES)).collect::<io::Result<Vec<_>>>()?;
+    let statement = statement_from_lines(&lines[1 + fields..])?;
+    let [a, s, c_v1, c_v2, challenges @ ..] = &ints[..] else { unreachable!("at least four integers were parsed") };
+    let message = FirstMessage { id, A: a.clone(), S: s.clone(), C_v1: c_v1.clone(), C_v2: c_v2.clone(), statement };
+    let challenges = match challenges {
+        [y, z] => {
+            let nonce = hex::decode(lines[1 + scalars].trim()).map_err(|_| invalid("invalid nonce"))?;
+            Some((y.clone(), z.clone(), nonce))
+        }
+        _ => None,
+    };
+    Ok((message, challenges))
+}
+
+/// Save an offline prover state in a checksummed `CUOFFST` container, in the clear
+/// - params: path, state
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: the file is as secret as v and never leaves the cold machine; `cuproof prove-offline-commit`
+///   writes it encrypted instead (the write_offline_state bytes, as save_encrypted does), and
+///   prove-offline-finish refuses a state in the clear
+#[cfg(feature = "std")]
+pub fn save_offline_state(path: &str, state: &OfflineState) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_offline_state(w, state).map(|_| ()))
+}
+
+/// Write an offline state in the save_offline_state format to any writer
+/// - params: w, state
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_offline_state<W: Write + ?Sized>(w: &mut W, state: &OfflineState) -> io::Result<usize> {
+    let mut encoder = Encoder::new();
+    encoder.begin();
+    encoder.offline_state_lines(state);
+    encoder.finish(FileKind::OfflineState, FILE_FORMAT_VERSION)?;
+    let bytes = Secret::new(encoder.into_bytes());
+    w.write_all(bytes.expose())?;
+    Ok(bytes.expose().len())
+}
+
+/// Load an offline state written by save_offline_state
+/// - params: path
+/// - returns: the state; InvalidData for broken files
+#[cfg(feature = "std")]
+pub fn load_offline_state(path: &str) -> io::Result<OfflineState> {
+    read_offline_state(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())
+}
+
+/// Read an offline state in the save_offline_state format from a stream
+/// - params: r any reader, limits
+/// - returns: the state, or the errors of load_offline_state; InvalidData beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_offline_state<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<OfflineState> {
+    let bytes = Secret::new(read_limited(r, limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "offline state exceeds the read limit"))?);
+    let (_, payload) = open_container(bytes.expose(), FileKind::OfflineState).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let lines = Secret::new(payload_lines(payload)?);
+    offline_state_from_lines(lines.expose())
+}
+
+/// An offline state from the lines offline_state_lines writes
+#[cfg(feature = "std")]
+pub(crate) fn offline_state_from_lines(lines: &[String]) -> io::Result<OfflineState> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let [id_line, fingerprint, a, b, dimension, seed, rest @ ..] = lines else { return Err(invalid("offline state too short")) };
+    let mut id = [0u8; 16];
+    hex::decode_to_slice(id_line.trim(), &mut id).map_err(|_| invalid("invalid state id"))?;
+    let mut params_fingerprint = [0u8; 32];
+    hex::decode_to_slice(fingerprint.trim(), &mut params_fingerprint).map_err(|_| invalid("invalid params fingerprint"))?;
+    let signed = |line: &str, field: &'static str| -> io::Result<BigInt> {
+        if line.trim().len() > 2 * MAX_PROOF_INT_BYTES + 3 {
+            return Err(limit_exceeded(LimitError::IntegerTooLarge { field, max_bytes: MAX_PROOF_INT_BYTES }));
+        }
+        hex_to_bigint_signed(line.trim()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    };
+    let (a, b) = (signed(a, "state a")?, signed(b, "state b")?);
+    let dimension: usize = dimension.trim().parse().map_err(|_| invalid("invalid state dimension"))?;
+    let mut finish_seed = Secret::new([0u8; 32]);
+    hex::decode_to_slice(seed.trim(), finish_seed.expose_mut()).map_err(|_| invalid("invalid state seed"))?;
+    let expected = dimension.checked_mul(3).and_then(|n| n.checked_add(7)).unwrap_or(usize::MAX);
+    if dimension == 0 || expected != rest.len() {
+        return Err(limit_exceeded(LimitError::CountExceedsInput { declared: dimension, available: rest.len().saturating_sub(7) / 3 }));
+    }
+    let int = |line: &String| hex_field(line, "state", MAX_PROOF_INT_BYTES);
+    let [alpha, rho, a_commit, s_commit, c, c_v1, c_v2] = [0, 1, 2, 3, 4, 5, 6].map(|i| int(&rest[i]));
+    let (alpha, rho) = (Secret::new(alpha?), Secret::new(rho?));
+    let d = Secret::new(rest[7..7 + dimension].iter().map(|line| signed(line, "state d")).collect::<io::Result<Vec<_>>>()?);
+    let s_l = Secret::new(rest[7 + dimension..7 + 2 * dimension].iter().map(int).collect::<io::Result<Vec<_>>>()?);
+    let s_r = Secret::new(rest[7 + 2 * dimension..].iter().map(int).collect::<io::Result<Vec<_>>>()?);
+    let round = FirstRound { d, alpha, rho, sL: s_l, sR: s_r, A: a_commit?, S: s_commit?, C: c?, C_v1: c_v1?, C_v2: c_v2? };
+    Ok(OfflineState { id, params_fingerprint, a, b, finish_seed, round })
+}
+
+/// Save Cuproof to a file (line-based hex format in a checksummed `CUPROOF\0` container)
+/// - params: path, proof
+/// - returns: io::Result
+/// - usage: send proof file to verifier
+#[cfg(feature = "std")]
+pub fn save_proof(path: &str, proof: &Cuproof) -> io::Result<()> {
+    save_proof_with(path, proof, &SaveOptions::default())
+}
+
+/// save_proof with control over how an existing file is replaced and whether it is compressed
+/// - params: path, proof, options e.g. `SaveOptions::default().with_backup()`, or with_compression(level)
+/// - returns: io::Result; the file at `path` is replaced atomically (see write_atomically)
+#[cfg(feature = "std")]
+pub fn save_proof_with(path: &str, proof: &Cuproof, options: &SaveOptions) -> io::Result<()> {
+    #[cfg(feature = "compression")]
+    if let Some(level) = options.compression() {
+        let bytes = compress_container(&encode_proof(proof)?, level)?;
+        return write_atomically(path, options, |w| w.write_all(&bytes));
+    }
+    write_atomically(path, options, |w| write_proof(w, proof).map(|_| ()))
+}
+
+/// Write a proof in the save_proof format to any writer
+/// - params: w, proof
+/// - returns: number of bytes written
+/// - usage: proofs sent over the network or kept in object stores, without a temp file
+#[cfg(feature = "std")]
+pub fn write_proof<W: Write + ?Sized>(w: &mut W, proof: &Cuproof) -> io::Result<usize> {
+    traced!(
+        tracing::debug_span!("write_proof", proof_id = %proof_id(proof)),
+        encode_proof(proof).and_then(|bytes| w.write_all(&bytes).map(|()| bytes.len())),
+        |written, _| if let Ok(bytes) = written { tracing::debug!(bytes, "proof written") }
+    )
+}
+
+/// The bytes save_proof writes
+#[cfg(feature = "std")]
+pub(crate) fn encode_proof(proof: &Cuproof) -> io::Result<Vec<u8>> {
+    let mut encoder = Encoder::new();
+    encoder.encode_proof(proof)?;
+    Ok(encoder.into_bytes())
+}
+
+/// In-memory codec: exactly the payload of the save_proof container, without its header and
+/// checksum, for proofs that go to a queue or a database rather than a file
+impl Cuproof {
+    /// The payload save_proof wraps in its container
+    /// - returns: bytes determined by the proof alone, so equal proofs always encode identically
+    ///   (content-addressed storage can hash them)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.encode_proof_payload(self);
+        encoder.into_bytes()
+    }
+
+    /// Decode to_bytes output, running the checks of load_proof
+    /// - params: bytes a payload, limits as for read_proof
+    /// - returns: Cuproof, or a ProofLoadError (TooLarge beyond `limits`, Limit or Invalid otherwise);
+    ///   the statement section is read when the payload has one
+    #[cfg(feature = "std")]
+    pub fn from_bytes(bytes: &[u8], limits: &ReadLimits) -> Result<Cuproof, ProofLoadError> {
+        if bytes.len() > limits.max_bytes() { return Err(ProofLoadError::TooLarge { limit: limits.max_bytes() }); }
+        let lines = payload_lines(bytes).map_err(proof_load_error)?;
+        let with_statement = lines.iter().any(|line| line == STATEMENT_HEADER);
+        proof_from_lines(&lines, with_statement).map_err(proof_load_error)
+    }
+}
+
+/// Equality of the to_bytes encodings: every field, the epoch, the statement and the assist. Proofs read from
+/// untrusted sources compare as one proof only after Cuproof::canonicalize
+impl PartialEq for Cuproof {
+    fn eq(&self, other: &Cuproof) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for Cuproof {}
+
+/// Hashes the to_bytes encoding, consistently with ==; a cache keyed by proofs should
+/// canonicalize them first, or a variant with C + n for C misses it
+impl core::hash::Hash for Cuproof {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state);
+    }
+}
+
+/// In-memory codec of statements: the statement section of a proof payload
+impl Statement {
+    /// The statement section as Cuproof::to_bytes writes it, header line included
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.statement_lines(self);
+        encoder.into_bytes()
+    }
+
+    /// Decode Statement::to_bytes output
+    /// - returns: Statement, or a ProofLoadError as for Cuproof::from_bytes
+    #[cfg(feature = "std")]
+    pub fn from_bytes(bytes: &[u8], limits: &ReadLimits) -> Result<Statement, ProofLoadError> {
+        if bytes.len() > limits.max_bytes() { return Err(ProofLoadError::TooLarge { limit: limits.max_bytes() }); }
+        statement_from_lines(&payload_lines(bytes).map_err(proof_load_error)?).map_err(proof_load_error)
+    }
+}
+
+/// First line of the statement section of a proof file
+pub(crate) const STATEMENT_HEADER: &str = "statement";
+
+/// First line of the verifier assist section of a proof file, followed by its q1 and q2
+pub(crate) const ASSIST_HEADER: &str = "assist";
+
+/// Lines of a statement section: the header and six fields
+#[cfg(feature = "std")]
+const STATEMENT_LINES: usize = 7;
+
+/// Save an aggregated proof: the count of statements, each statement's C, C_v1 and C_v2, the
+/// shared scalars and inner-product argument laid out as in save_proof, the epoch, then one
+/// statement section per statement, in a checksummed `CUAGGPRF` container
+/// - params: path, proof
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: written by `cuproof aggregate`, read back by `cuproof verify`
+#[cfg(feature = "std")]
+pub fn save_aggregated_proof(path: &str, proof: &AggregatedCuproof) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_aggregated_proof(w, proof).map(|_| ()))
+}
+
+/// Write an aggregated proof in the save_aggregated_proof format to any writer
+/// - params: w, proof
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_aggregated_proof<W: Write + ?Sized>(w: &mut W, proof: &AggregatedCuproof) -> io::Result<usize> {
+    let bytes = encode_aggregated_proof(proof)?;
+    w.write_all(&bytes)?;
+    Ok(bytes.len())
+}
+
+/// The bytes save_aggregated_proof writes
+#[cfg(feature = "std")]
+pub(crate) fn encode_aggregated_proof(proof: &AggregatedCuproof) -> io::Result<Vec<u8>> {
+    let mut encoder = Encoder::new();
+    encoder.encode_aggregated_proof(proof)?;
+    Ok(encoder.into_bytes())
+}
+
+/// Load an aggregated proof written by save_aggregated_proof
+/// - params: path
+/// - returns: AggregatedCuproof; InvalidData for broken files, files over DEFAULT_MAX_READ_BYTES
+///   and proofs failing the checks load_proof makes, with at most MAX_AGGREGATED_STATEMENTS statements
+#[cfg(feature = "std")]
+pub fn load_aggregated_proof(path: &str) -> io::Result<AggregatedCuproof> {
+    Ok(read_aggregated_proof(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())?)
+}
+
+/// Read an aggregated proof in the save_aggregated_proof format from a stream
+/// - params: r any reader, limits
+/// - returns: AggregatedCuproof, or a ProofLoadError as for read_proof
+#[cfg(feature = "std")]
+pub fn read_aggregated_proof<R: Read>(r: &mut R, limits: &ReadLimits) -> Result<AggregatedCuproof, ProofLoadError> {
+    let bytes = read_limited(r, limits)
+        .map_err(ProofLoadError::Io)?
+        .ok_or(ProofLoadError::TooLarge { limit: limits.max_bytes() })?;
+    traced!(
+        tracing::debug_span!("read_aggregated_proof", bytes = bytes.len()),
+        phase(Phase::Deserialize, || decode_aggregated_proof(&bytes).map_err(proof_load_error)),
+        |proof, _| match proof {
+            Ok(proof) => tracing::debug!(statements = proof.statements.len(), "aggregated proof read"),
+            Err(e) => tracing::debug!(error = %e, "aggregated proof refused"),
+        }
+    )
+}
+
+/// True if `bytes` start like an aggregated proof container, whatever follows
+/// - usage: `cuproof verify` choosing between load_proof and load_aggregated_proof
+#[cfg(feature = "std")]
+pub fn is_aggregated_proof(bytes: &[u8]) -> bool {
+    bytes.starts_with(FileKind::AggregatedProof.magic())
+}
+
+#[cfg(feature = "std")]
+fn decode_aggregated_proof(bytes: &[u8]) -> io::Result<AggregatedCuproof> {
+    let (_, payload) = open_container(bytes, FileKind::AggregatedProof).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    aggregated_proof_from_lines(&payload_lines(payload)?)
+}
+
+/// An aggregated proof from the payload lines of its container
+#[cfg(feature = "std")]
+#[allow(non_snake_case)]
+pub(crate) fn aggregated_proof_from_lines(lines: &[String]) -> io::Result<AggregatedCuproof> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut i = 0usize;
+    let take = |i: &mut usize| -> io::Result<&str> {
+        let s = lines.get(*i).ok_or_else(|| invalid("unexpected end of file"))?;
+        *i += 1;
+        Ok(s.trim())
+    };
+    let int = |i: &mut usize, field: &'static str| -> io::Result<BigInt> {
+        let x = hex_field(take(i)?, field, MAX_PROOF_INT_BYTES)?;
+        check_proof_int(&x).map_err(invalid)?;
+        Ok(x)
+    };
+    // the count is checked against MAX_AGGREGATED_STATEMENTS and the lines left before anything is
+    // allocated: each statement has three commitments here and a statement section at the end
+    let m: usize = take(&mut i)?.parse().map_err(|_| invalid("invalid statement count"))?;
+    if m > MAX_AGGREGATED_STATEMENTS { return Err(limit_exceeded(LimitError::TooManyStatements(m))); }
+    let available = lines.len() - i;
+    if m.saturating_mul(3 + STATEMENT_LINES) > available { return Err(limit_exceeded(LimitError::CountExceedsInput { declared: m, available })); }
+    if m == 0 { return Err(invalid("aggregated proof holds no statements")); }
+    let mut commitments = Vec::with_capacity(m);
+    for _ in 0..m {
+        commitments.push(ValueCommitments { C: int(&mut i, "C")?, C_v1: int(&mut i, "C_v1")?, C_v2: int(&mut i, "C_v2")? });
+    }
+    let A = int(&mut i, "A")?;
+    let S = int(&mut i, "S")?;
+    let T1 = int(&mut i, "T1")?;
+    let T2 = int(&mut i, "T2")?;
+    let tau_x = int(&mut i, "tau_x")?;
+    let mu = int(&mut i, "mu")?;
+    let t_hat = int(&mut i, "t_hat")?;
+    let t0 = int(&mut i, "t0")?;
+    let t1 = int(&mut i, "t1")?;
+    let t2 = int(&mut i, "t2")?;
+    let tau1 = int(&mut i, "tau1")?;
+    let tau2 = int(&mut i, "tau2")?;
+    let rounds = |i: &mut usize| -> io::Result<usize> {
+        let count: usize = take(i)?.parse().map_err(|_| invalid("invalid inner-product round count"))?;
+        if count > MAX_IPP_ROUNDS { return Err(limit_exceeded(LimitError::TooManyRounds(count))); }
+        Ok(count)
+    };
+    let l_len = rounds(&mut i)?;
+    let L = (0..l_len).map(|_| int(&mut i, "L")).collect::<io::Result<Vec<_>>>()?;
+    let r_len = rounds(&mut i)?;
+    check_ipp_lengths(l_len, r_len).map_err(invalid)?;
+    let R = (0..r_len).map(|_| int(&mut i, "R")).collect::<io::Result<Vec<_>>>()?;
+    let a = int(&mut i, "a")?;
+    let b = int(&mut i, "b")?;
+    let epoch = take(&mut i)?.parse::<u64>().map_err(|_| invalid("invalid generator epoch"))?;
+    for x in [&A, &S, &T1, &T2] { check_header_nonzero(x).map_err(invalid)?; }
+
+    let sections = &lines[i..];
+    if sections.len() != m * STATEMENT_LINES { return Err(invalid("aggregated proof must hold one statement section per statement")); }
+    let statements = sections.chunks(STATEMENT_LINES).map(statement_from_lines).collect::<io::Result<Vec<_>>>()?;
+    let ipp_proof = IPPProof { L, R, a, b };
+    Ok(AggregatedCuproof { commitments, A, S, T1, T2, tau_x, mu, t_hat, t0, t1, t2, tau1, tau2, ipp_proof, epoch, statements })
+}
+
+/// Save a comparison proof: its bit width and shift, then its range proof laid out as in
+/// save_proof, statement section included, in a checksummed `CUCMPPRF` container
+/// - params: path, proof
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: written by `cuproof prove-lt`, read back by `cuproof verify-lt`
+#[cfg(feature = "std")]
+pub fn save_comparison_proof(path: &str, proof: &ComparisonProof) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_comparison_proof(w, proof).map(|_| ()))
+}
+
+/// Write a comparison proof in the save_comparison_proof format to any writer
+/// - params: w, proof
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_comparison_proof<W: Write + ?Sized>(w: &mut W, proof: &ComparisonProof) -> io::Result<usize> {
+    let mut encoder = Encoder::new();
+    encoder.begin();
+    encoder.display(proof.bits);
+    encoder.display(proof.shift);
+    encoder.proof_lines(&proof.proof);
+    encoder.finish(FileKind::ComparisonProof, FILE_FORMAT_VERSION)?;
+    w.write_all(&encoder.buf)?;
+    Ok(encoder.buf.len())
+}
+
+/// Load a comparison proof written by save_comparison_proof
+/// - params: path
+/// - returns: ComparisonProof; InvalidData for broken files, files over DEFAULT_MAX_READ_BYTES,
+///   widths or shifts above MAX_COMPARISON_BITS and range proofs failing the checks of load_proof
+#[cfg(feature = "std")]
+pub fn load_comparison_proof(path: &str) -> io::Result<ComparisonProof> {
+    Ok(read_comparison_proof(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())?)
+}
+
+/// Read a comparison proof in the save_comparison_proof format from a stream
+/// - params: r any reader, limits
+/// - returns: ComparisonProof, or a ProofLoadError as for read_proof
+#[cfg(feature = "std")]
+pub fn read_comparison_proof<R: Read>(r: &mut R, limits: &ReadLimits) -> Result<ComparisonProof, ProofLoadError> {
+    let bytes = read_limited(r, limits)
+        .map_err(ProofLoadError::Io)?
+        .ok_or(ProofLoadError::TooLarge { limit: limits.max_bytes() })?;
+    let (_, payload) = open_container(&bytes, FileKind::ComparisonProof).map_err(ProofLoadError::Format)?;
+    comparison_proof_from_lines(&payload_lines(payload).map_err(proof_load_error)?).map_err(proof_load_error)
+}
+
+/// A comparison proof from the payload lines of its container; the range proof must carry its statement
+#[cfg(feature = "std")]
+pub(crate) fn comparison_proof_from_lines(lines: &[String]) -> io::Result<ComparisonProof> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let [bits, shift, proof @ ..] = lines else { return Err(invalid("comparison proof file too short")) };
+    let width = |line: &str, what: &str| match line.trim().parse::<u32>() {
+        Ok(x) if (1..=MAX_COMPARISON_BITS).contains(&x) => Ok(x),
+        _ => Err(invalid(&format!("invalid {}: expected 1 to {}", what, MAX_COMPARISON_BITS))),
+    };
+    let (bits, shift) = (width(bits, "bit width")?, width(shift, "shift")?);
+    Ok(ComparisonProof { bits, shift, proof: proof_from_lines(proof, true)? })
+}
+
+/// Save a proof bundle: the params fingerprint, the embedded params' line count and lines (0 when
+/// none are embedded), the metadata count and `key value` lines, the statement section and the
+/// proof payload, in a checksummed `CUBUNDLE` container
+/// - params: path, bundle
+/// - returns: io::Result; InvalidInput for a metadata key that is empty or holds whitespace, or a
+///   value that holds a line break. The file is replaced atomically
+/// - usage: written by `cuproof bundle create`, read by `cuproof bundle verify`
+#[cfg(feature = "std")]
+pub fn save_bundle(path: &str, bundle: &ProofBundle) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_bundle(w, bundle).map(|_| ()))
+}
+
+/// Write a proof bundle in the save_bundle format to any writer
+/// - params: w, bundle
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_bundle<W: Write + ?Sized>(w: &mut W, bundle: &ProofBundle) -> io::Result<usize> {
+    let bytes = container_bytes(FileKind::ProofBundle, FILE_FORMAT_VERSION, &bundle_lines(bundle)?)?;
+    w.write_all(&bytes)?;
+    Ok(bytes.len())
+}
+
+/// The payload lines of a bundle container
+#[cfg(feature = "std")]
+fn bundle_lines(bundle: &ProofBundle) -> io::Result<Vec<String>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let params = bundle.params.as_ref().map(params_lines).unwrap_or_default();
+    let mut lines = vec![hex::encode(bundle.params_fingerprint), params.len().to_string()];
+    lines.extend(params);
+    lines.push(bundle.metadata.len().to_string());
+    for (key, value) in &bundle.metadata {
+        if key.is_empty() || key.contains(char::is_whitespace) { return Err(invalid(format!("metadata key '{}' is empty or holds whitespace", key))); }
+        if value.contains(['\n', '\r']) { return Err(invalid(format!("metadata value of {} holds a line break", key))); }
+        lines.push(format!("{} {}", key, value));
+    }
+    for section in [bundle.statement.to_bytes(), bundle.proof.to_bytes()] {
+        lines.extend(payload_lines(&section)?);
+    }
+    Ok(lines)
+}
+
+/// Load a proof bundle written by save_bundle
+/// - params: path
+/// - returns: the bundle; InvalidData for broken files, files over DEFAULT_MAX_READ_BYTES, embedded
+///   params failing validate_params or another fingerprint than the recorded one, more than
+///   MAX_BUNDLE_METADATA metadata entries, and statements or proofs failing the load_proof checks.
+///   Whether the proof verifies is for ProofBundle::verify to tell
+#[cfg(feature = "std")]
+pub fn load_bundle(path: &str) -> io::Result<ProofBundle> {
+    read_bundle(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())
+}
+
+/// Read a proof bundle in the save_bundle format from a stream
+/// - params: r any reader, limits
+/// - returns: the bundle, or the errors of load_bundle; InvalidData beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_bundle<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<ProofBundle> {
+    let bytes = read_limited(r, limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "proof bundle exceeds the read limit"))?;
+    let (_, payload) = open_container(&bytes, FileKind::ProofBundle).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    bundle_from_lines(&payload_lines(payload)?)
+}
+
+/// A proof bundle from the payload lines of its container
+#[cfg(feature = "std")]
+pub(crate) fn bundle_from_lines(lines: &[String]) -> io::Result<ProofBundle> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    // a count is checked against the lines left before anything is parsed
+    let count = |at: usize, what: &str| -> io::Result<usize> {
+        let line = lines.get(at).ok_or_else(|| invalid("proof bundle too short"))?;
+        let count: usize = line.trim().parse().map_err(|_| invalid(&format!("invalid {} count", what)))?;
+        let available = lines.len() - at - 1;
+        if count > available { return Err(limit_exceeded(LimitError::CountExceedsInput { declared: count, available })); }
+        Ok(count)
+    };
+    let mut params_fingerprint = [0u8; 32];
+    hex::decode_to_slice(lines.first().ok_or_else(|| invalid("proof bundle too short"))?.trim(), &mut params_fingerprint)
+        .map_err(|_| invalid("invalid params fingerprint"))?;
+    let params_len = count(1, "params line")?;
+    let params = match params_len {
+        0 => None,
+        len => {
+            let params = checked_params(params_from_lines(&lines[2..2 + len])?).map_err(io::Error::from)?;
+            if params.fingerprint() != params_fingerprint { return Err(invalid("embedded params do not have the bundle's fingerprint")); }
+            Some(params)
+        }
+    };
+    let mut i = 2 + params_len;
+    let metadata_len = count(i, "metadata")?;
+    if metadata_len > MAX_BUNDLE_METADATA {
+        return Err(limit_exceeded(LimitError::TooManyEntries { what: "metadata entries", max: MAX_BUNDLE_METADATA }));
+    }
+    let metadata = lines[i + 1..i + 1 + metadata_len].iter()
+        .map(|line| line.split_once(' ').map(|(key, value)| (key.to_string(), value.to_string())).ok_or_else(|| invalid("metadata line needs a key and a value")))
+        .collect::<io::Result<_>>()?;
+    i += 1 + metadata_len;
+    let statement_end = i + STATEMENT_LINES;
+    if lines.len() < statement_end { return Err(invalid("proof bundle too short")); }
+    let statement = statement_from_lines(&lines[i..statement_end])?;
+    let proof_lines = &lines[statement_end..];
+    let proof = proof_from_lines(proof_lines, proof_lines.iter().any(|line| line == STATEMENT_HEADER))?;
+    Ok(ProofBundle { params_fingerprint, params, statement, proof, metadata })
+}
+
+/// Save a batched proof of openings with the commitments it is about: their count and hex lines,
+/// then the proof's width, announcement, z_m and z_r, in a checksummed `CUOPNPRF` container
+/// - params: path, commitments in the order they were proved, proof
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: written by `cuproof prove-openings`; a verifier checks the commitments against the
+///   ones it holds before trusting the proof
+#[cfg(feature = "std")]
+pub fn save_openings_proof(path: &str, commitments: &[BigInt], proof: &BatchOpeningProof) -> io::Result<()> {
+    write_atomically(path, &SaveOptions::default(), |w| write_openings_proof(w, commitments, proof).map(|_| ()))
+}
+
+/// Write a batched proof of openings in the save_openings_proof format to any writer
+/// - params: w, commitments, proof
+/// - returns: number of bytes written
+#[cfg(feature = "std")]
+pub fn write_openings_proof<W: Write + ?Sized>(w: &mut W, commitments: &[BigInt], proof: &BatchOpeningProof) -> io::Result<usize> {
+    let mut encoder = Encoder::new();
+    encoder.begin();
+    encoder.display(commitments.len());
+    for c in commitments { encoder.hex(c); }
+    encoder.display(proof.bits);
+    for x in [&proof.announcement, &proof.z_m, &proof.z_r] { encoder.hex(x); }
+    encoder.finish(FileKind::OpeningsProof, FILE_FORMAT_VERSION)?;
+    w.write_all(&encoder.buf)?;
+    Ok(encoder.buf.len())
+}
+
+/// Load a batched proof of openings written by save_openings_proof
+/// - params: path
+/// - returns: the commitments and the proof; InvalidData for broken files, more than
+///   MAX_BATCH_OPENINGS commitments and widths above MAX_OPENING_BITS
+#[cfg(feature = "std")]
+pub fn load_openings_proof(path: &str) -> io::Result<(Vec<BigInt>, BatchOpeningProof)> {
+    read_openings_proof(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())
+}
+
+/// Read a batched proof of openings in the save_openings_proof format from a stream
+/// - params: r any reader, limits
+/// - returns: the commitments and the proof, or the errors of load_openings_proof; InvalidData beyond `limits`
+#[cfg(feature = "std")]
+pub fn read_openings_proof<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<(Vec<BigInt>, BatchOpeningProof)> {
+    let bytes = read_limited(r, limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "openings proof exceeds the read limit"))?;
+    let (_, payload) = open_container(&bytes, FileKind::OpeningsProof).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    openings_proof_from_lines(&payload_lines(payload)?)
+}
+
+/// The commitments and proof of an openings proof from the payload lines of its container
+#[cfg(feature = "std")]
+fn openings_proof_from_lines(lines: &[String]) -> io::Result<(Vec<BigInt>, BatchOpeningProof)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let [count, rest @ ..] = lines else { return Err(invalid("openings proof file too short")) };
+    let count: usize = count.trim().parse().map_err(|_| invalid("invalid commitment count"))?;
+    if count > MAX_BATCH_OPENINGS { return Err(limit_exceeded(LimitError::TooManyOpenings(count))); }
+    if rest.len() != count + 4 {
+        return Err(limit_exceeded(LimitError::CountExceedsInput { declared: count, available: rest.len().saturating_sub(4) }));
+    }
+    let commitments = rest[..count].iter().map(|line| hex_field(line, "commitment", MAX_MODULUS_BITS / 8)).collect::<io::Result<Vec<_>>>()?;
+    let [bits, announcement, z_m, z_r] = &rest[count..] else { unreachable!("four lines follow the commitments") };
+    let bits = match bits.trim().parse::<u32>() {
+        Ok(bits) if bits <= MAX_OPENING_BITS => bits,
+        _ => return Err(invalid(&format!("invalid opening width: expected 0 to {}", MAX_OPENING_BITS))),
+    };
+    let announcement = hex_field(announcement, "announcement", MAX_MODULUS_BITS / 8)?;
+    let (z_m, z_r) = (hex_field(z_m, "z_m", MAX_PROOF_INT_BYTES)?, hex_field(z_r, "z_r", MAX_PROOF_INT_BYTES)?);
+    Ok((commitments, BatchOpeningProof { bits, announcement, z_m, z_r }))
+}
+
+/// Save statements one after another as Statement::to_bytes writes them, without a container
+/// - params: path, statements
+/// - returns: io::Result; the file is replaced atomically
+/// - usage: `cuproof aggregate --statements-out`, the record a verifier later hands to
+///   `cuproof verify --statements`; being plain text, it can be reviewed and edited as such
+#[cfg(feature = "std")]
+pub fn save_statements(path: &str, statements: &[Statement]) -> io::Result<()> {
+    let text = statements.iter().map(|statement| String::from_utf8(statement.to_bytes()).expect("statement sections are ASCII"))
+        .collect::<Vec<_>>().join("\n");
+    write_atomically(path, &SaveOptions::default(), |w| writeln!(w, "{}", text))
+}
+
+/// Load statements written by save_statements
+/// - params: path
+/// - returns: the statements in file order; InvalidData for files over DEFAULT_MAX_READ_BYTES,
+///   more than MAX_AGGREGATED_STATEMENTS statements or a section that does not parse
+#[cfg(feature = "std")]
+pub fn load_statements(path: &str) -> io::Result<Vec<Statement>> {
+    let limits = ReadLimits::default();
+    let bytes = read_limited(&mut BufReader::new(fs::File::open(path)?), &limits)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("statements file exceeds {} bytes", limits.max_bytes())))?;
+    let lines = payload_lines(&bytes)?;
+    if lines.len() % STATEMENT_LINES != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "statements file must hold whole statement sections"));
+    }
+    let count = lines.len() / STATEMENT_LINES;
+    if count > MAX_AGGREGATED_STATEMENTS { return Err(limit_exceeded(LimitError::TooManyStatements(count))); }
+    lines.chunks(STATEMENT_LINES).map(statement_from_lines).collect()
+}
+
+/// Largest integer accepted in a proof from outside, in bytes; values from 4096-bit params stay far below it
+pub const MAX_PROOF_INT_BYTES: usize = 4096;
+
+/// Most inner-product rounds accepted in a proof from outside
+pub const MAX_IPP_ROUNDS: usize = 64;
+
+/// Size limit on every integer of an untrusted proof (load_proof and the serde decoders)
+#[cfg(feature = "std")]
+pub(crate) fn check_proof_int(x: &BigInt) -> Result<(), &'static str> {
+    if x.bits() > (MAX_PROOF_INT_BYTES * 8) as u64 { Err("integer exceeds MAX_PROOF_INT_BYTES") } else { Ok(()) }
+}
+
+/// A, S, T1 and T2 of an untrusted proof must be non-zero
+pub(crate) fn check_header_nonzero(x: &BigInt) -> Result<(), &'static str> {
+    if x.is_zero() { Err("zero scalar in header") } else { Ok(()) }
+}
+
+/// L and R of an untrusted proof must be non-empty, of equal length and at most MAX_IPP_ROUNDS long
+pub(crate) fn check_ipp_lengths(l_len: usize, r_len: usize) -> Result<(), &'static str> {
+    if l_len == 0 { return Err("L length must be > 0"); }
+    if r_len == 0 { return Err("R length must be > 0"); }
+    if r_len != l_len { return Err("L and R length mismatch"); }
+    if l_len > MAX_IPP_ROUNDS { return Err("too many inner-product rounds"); }
+    Ok(())
+}
+
+/// Certificates a metadata section may list: one per prime of n
+#[cfg(feature = "std")]
+pub const MAX_METADATA_CERTIFICATES: usize = 2;
+
+/// Pocklington links a metadata certificate may have; honest chains from 32 bits to a quarter of
+/// a MAX_MODULUS_BITS modulus need about ten
+#[cfg(feature = "std")]
+pub const MAX_CERTIFICATE_STEPS: usize = 64;
+
+/// A length field or integer of an untrusted file exceeds what the decoders accept; raised before
+/// anything is allocated or parsed for it
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    /// the named integer field has more than `max_bytes` bytes
+    IntegerTooLarge { field: &'static str, max_bytes: usize },
+    /// L or R declares more than MAX_IPP_ROUNDS entries
+    TooManyRounds(usize),
+    /// a count declares more entries than lines remain in the file
+    CountExceedsInput { declared: usize, available: usize },
+    /// the metadata section lists more `what` than `max`
+    TooManyEntries { what: &'static str, max: usize },
+    /// an aggregated proof or statements file declares more than MAX_AGGREGATED_STATEMENTS statements
+    TooManyStatements(usize),
+    /// a generator vector file declares more than MAX_GENERATORS generators
+    TooManyGenerators(usize),
+    /// an openings proof declares more than MAX_BATCH_OPENINGS openings
+    TooManyOpenings(usize),
+    /// a compressed payload declares more than `max` bytes once inflated (MAX_DECOMPRESSED_BYTES)
+    DecompressedTooLarge { declared: usize, max: usize },
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::IntegerTooLarge { field, max_bytes } => write!(f, "{} exceeds {} bytes", field, max_bytes),
+            LimitError::TooManyRounds(n) => write!(f, "{} inner-product rounds exceed the {} accepted", n, MAX_IPP_ROUNDS),
+            LimitError::CountExceedsInput { declared, available } => {
+                write!(f, "count {} exceeds the {} lines left in the file", declared, available)
+            }
+            LimitError::TooManyEntries { what, max } => write!(f, "more than {} {} in metadata", max, what),
+            LimitError::TooManyStatements(n) => write!(f, "{} statements exceed the {} accepted", n, MAX_AGGREGATED_STATEMENTS),
+            LimitError::TooManyGenerators(n) => write!(f, "{} generators exceed the {} accepted", n, MAX_GENERATORS),
+            LimitError::TooManyOpenings(n) => write!(f, "{} openings exceed the {} accepted", n, MAX_BATCH_OPENINGS),
+            LimitError::DecompressedTooLarge { declared, max } => write!(f, "compressed payload declares {} bytes, above the {} accepted", declared, max),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for LimitError {}
+
+#[cfg(feature = "std")]
+fn limit_exceeded(e: LimitError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// hex_to_bigint_strict for an integer field of an untrusted file, refusing lines with more digits
+/// than `max_bytes` bytes need (plus a `0x` prefix) before parsing them
+#[cfg(feature = "std")]
+pub(crate) fn hex_field(s: &str, field: &'static str, max_bytes: usize) -> io::Result<BigInt> {
+    if s.trim().len() > 2 * max_bytes + 2 { return Err(limit_exceeded(LimitError::IntegerTooLarge { field, max_bytes })); }
+    hex_to_bigint_strict(s)
+}
+
+/// Load Cuproof from a file written by save_proof (compressed or not), save_proof_json or Cuproof::to_armored, told apart by its start
+/// - params: path
+/// - returns: Cuproof; InvalidData for truncated files, files over DEFAULT_MAX_READ_BYTES, compressed
+///   payloads declaring more than MAX_DECOMPRESSED_BYTES and proofs failing the checks above
+/// - usage: verifier loads file to verify
+#[cfg(feature = "std")]
+pub fn load_proof(path: &str) -> io::Result<Cuproof> {
+    Ok(read_proof(&mut BufReader::new(fs::File::open(path)?), &ReadLimits::default())?)
+}
+
+/// load_proof, with every group element of the proof reduced modulo n (Cuproof::canonicalize)
+/// - params: path, params the proof is for
+/// - returns: the canonical Cuproof, or the errors of load_proof
+/// - usage: verifiers that cache or deduplicate proofs by ==, Hash or proof_id
+#[cfg(feature = "std")]
+pub fn load_proof_checked(path: &str, params: &Params) -> io::Result<Cuproof> {
+    let mut proof = load_proof(path)?;
+    proof.canonicalize(params.n());
+    Ok(proof)
+}
+
+/// Default of ReadLimits::max_bytes; 4096-bit params with metadata and proofs take a few KiB
+#[cfg(feature = "std")]
+pub const DEFAULT_MAX_READ_BYTES: usize = 16 << 20;
+
+/// Caps read_proof / read_params apply to a stream before decoding it
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    max_bytes: usize,
+}
+
+#[cfg(feature = "std")]
+impl ReadLimits {
+    /// Accept streams of at most `max_bytes` bytes
+    pub fn new(max_bytes: usize) -> ReadLimits {
+        ReadLimits { max_bytes }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for ReadLimits {
+    fn default() -> ReadLimits {
+        ReadLimits::new(DEFAULT_MAX_READ_BYTES)
+    }
+}
+
+/// Why read_proof rejected a stream
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ProofLoadError {
+    /// the reader failed
+    Io(io::Error),
+    /// the stream holds more than ReadLimits::max_bytes
+    TooLarge { limit: usize },
+    /// the container framing is broken, e.g. the stream ends mid-field
+    Format(FormatError),
+    /// a length field or integer exceeds the decoder's limits
+    Limit(LimitError),
+    /// the framing is fine but the content is not a valid proof
+    Invalid(String),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ProofLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofLoadError::Io(e) => write!(f, "read failed: {}", e),
+            ProofLoadError::TooLarge { limit } => write!(f, "proof exceeds the {}-byte read limit", limit),
+            ProofLoadError::Format(e) => write!(f, "{}", e),
+            ProofLoadError::Limit(e) => write!(f, "{}", e),
+            ProofLoadError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for ProofLoadError {}
+
+/// Keeps the FormatError or LimitError inside, so path-based callers can still downcast it
+#[cfg(feature = "std")]
+impl From<ProofLoadError> for io::Error {
+    fn from(e: ProofLoadError) -> io::Error {
+        match e {
+            ProofLoadError::Io(e) => e,
+            ProofLoadError::Format(e) => io::Error::new(io::ErrorKind::InvalidData, e),
+            ProofLoadError::Limit(e) => io::Error::new(io::ErrorKind::InvalidData, e),
+            other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+        }
+    }
+}
+
+/// Why read_params rejected a stream
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ParamsLoadError {
+    /// the reader failed
+    Io(io::Error),
+    /// the stream holds more than ReadLimits::max_bytes
+    TooLarge { limit: usize },
+    /// the container framing is broken, e.g. the stream ends mid-field
+    Format(FormatError),
+    /// a length field or integer exceeds the decoder's limits
+    Limit(LimitError),
+    /// the framing is fine but the content does not parse as params
+    Invalid(String),
+    /// the params parse but fail validate_params
+    Params(ParamsError),
+    /// the metadata section fails validate_metadata
+    Metadata(MetadataError),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ParamsLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamsLoadError::Io(e) => write!(f, "read failed: {}", e),
+            ParamsLoadError::TooLarge { limit } => write!(f, "params exceed the {}-byte read limit", limit),
+            ParamsLoadError::Format(e) => write!(f, "{}", e),
+            ParamsLoadError::Limit(e) => write!(f, "{}", e),
+            ParamsLoadError::Invalid(msg) => write!(f, "{}", msg),
+            ParamsLoadError::Params(e) => write!(f, "{}", e),
+            ParamsLoadError::Metadata(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for ParamsLoadError {}
+
+/// Keeps the FormatError, LimitError, ParamsError or MetadataError inside, so path-based callers can still downcast it
+#[cfg(feature = "std")]
+impl From<ParamsLoadError> for io::Error {
+    fn from(e: ParamsLoadError) -> io::Error {
+        match e {
+            ParamsLoadError::Io(e) => e,
+            ParamsLoadError::Format(e) => io::Error::new(io::ErrorKind::InvalidData, e),
+            ParamsLoadError::Limit(e) => io::Error::new(io::ErrorKind::InvalidData, e),
+            ParamsLoadError::Params(e) => io::Error::new(io::ErrorKind::InvalidData, e),
+            ParamsLoadError::Metadata(e) => io::Error::new(io::ErrorKind::InvalidData, e),
+            other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+        }
+    }
+}
+
+/// Read `r` to its end, or None once it yields more than `limits.max_bytes()`; short reads and
+/// interrupted reads are retried, so only the total matters
+#[cfg(feature = "std")]
+fn read_limited<R: Read>(r: &mut R, limits: &ReadLimits) -> io::Result<Option<Vec<u8>>> {
+    let mut bytes = Vec::new();
+    r.take(limits.max_bytes() as u64 + 1).read_to_end(&mut bytes)?;
+    Ok((bytes.len() <= limits.max_bytes()).then_some(bytes))
+}
+
+/// The FormatError inside a decoding error, if the framing was what failed
+#[cfg(feature = "std")]
+fn format_error(e: &io::Error) -> Option<FormatError> {
+    e.get_ref().and_then(|inner| inner.downcast_ref::<FormatError>()).cloned()
+}
+
+/// The LimitError inside a decoding error, if a limit was what failed
+#[cfg(feature = "std")]
+fn limit_error(e: &io::Error) -> Option<LimitError> {
+    e.get_ref().and_then(|inner| inner.downcast_ref::<LimitError>()).cloned()
+}
+
+/// Read a proof in any format load_proof accepts from a stream
+/// - params: r any reader (unbuffered is fine: it is read to its end in one pass), limits
+/// - returns: Cuproof, or a ProofLoadError; a stream ending mid-field is Format(LengthMismatch)
+///   or Format(TruncatedHeader), never a panic
+/// - usage: proofs received over the network or fetched from object stores
+#[cfg(feature = "std")]
+pub fn read_proof<R: Read>(r: &mut R, limits: &ReadLimits) -> Result<Cuproof, ProofLoadError> {
+    let bytes = read_limited(r, limits)
+        .map_err(ProofLoadError::Io)?
+        .ok_or(ProofLoadError::TooLarge { limit: limits.max_bytes() })?;
+    traced!(
+        tracing::debug_span!("read_proof", bytes = bytes.len()),
+        phase(Phase::Deserialize, || decode_proof(bytes).map_err(proof_load_error)),
+        |proof, _| match proof {
+            Ok(proof) => tracing::debug!(proof_id = %proof_id(proof), "proof read"),
+            Err(e) => tracing::debug!(error = %e, "proof refused"),
+        }
+    )
+}
+
+#[cfg(feature = "std")]
+fn proof_load_error(e: io::Error) -> ProofLoadError {
+    match (format_error(&e), limit_error(&e)) {
+        (Some(f), _) => ProofLoadError::Format(f),
+        (_, Some(l)) => ProofLoadError::Limit(l),
+        _ => ProofLoadError::Invalid(e.to_string()),
+    }
+}
+
+/// Read params in any format load_params accepts from a stream and validate them like load_params
+/// - params: r any reader, limits
+/// - returns: Params, or a ParamsLoadError naming the framing or validation failure
+/// - usage: params received over the network or fetched from object stores
+#[cfg(feature = "std")]
+pub fn read_params<R: Read>(r: &mut R, limits: &ReadLimits) -> Result<Params, ParamsLoadError> {
+    let bytes = read_limited(r, limits)
+        .map_err(ParamsLoadError::Io)?
+        .ok_or(ParamsLoadError::TooLarge { limit: limits.max_bytes() })?;
+    traced!(
+        tracing::debug_span!("read_params", bytes = bytes.len()),
+        phase(Phase::Deserialize, || decode_params_unchecked(bytes).map_err(params_load_error).and_then(checked_params)),
+        |params, _| match params {
+            Ok(params) => tracing::debug!(params = %params.fingerprint_hex(), "params read"),
+            Err(e) => tracing::debug!(error = %e, "params refused"),
+        }
+    )
+}
+
+#[cfg(feature = "std")]
+fn params_load_error(e: io::Error) -> ParamsLoadError {
+    match (format_error(&e), limit_error(&e)) {
+        (Some(f), _) => ParamsLoadError::Format(f),
+        (_, Some(l)) => ParamsLoadError::Limit(l),
+        _ => ParamsLoadError::Invalid(e.to_string()),
+    }
+}
+
+/// The validation load_params runs: validate_params, plus validate_metadata when there is metadata
+#[cfg(feature = "std")]
+fn checked_params(params: Params) -> Result<Params, ParamsLoadError> {
+    validate_params(&params).map_err(ParamsLoadError::Params)?;
+    if params.metadata().is_some() {
+        validate_metadata(&params).map_err(ParamsLoadError::Metadata)?;
+    }
+    Ok(params)
+}
+
+/// load_proof over bytes already read
+#[cfg(feature = "std")]
+fn decode_proof(bytes: Vec<u8>) -> io::Result<Cuproof> {
+    if is_json(&bytes) { return proof_from_json(&bytes); }
+    if is_armored(&bytes) { return proof_from_armored(&bytes); }
+    decode_proof_container(bytes)
+}
+
+/// Read a proof from a container (or legacy text) as written by save_proof / encode_proof
+#[cfg(feature = "std")]
+pub(crate) fn decode_proof_container(bytes: Vec<u8>) -> io::Result<Cuproof> {
+    let (version, lines) = container_lines(bytes, FileKind::Proof)?;
+    proof_from_lines(&lines, version == PROOF_FORMAT_VERSION)
+}
+
+/// A proof from the payload lines of a proof container; `with_statement` requires the statement
+/// section after the epoch line, otherwise lines after it are ignored up to the assist section,
+/// read wherever it starts
+#[cfg(feature = "std")]
+pub(crate) fn proof_from_lines(lines: &[String], with_statement: bool) -> io::Result<Cuproof> {
+    // no hex line reads "assist", so its header marks where the section starts
+    let (lines, assist) = match lines.iter().position(|line| line == ASSIST_HEADER) {
+        Some(at) => (&lines[..at], Some(assist_from_lines(&lines[at..])?)),
+        None => (lines, None),
+    };
+    let mut i = 0usize;
+    let take = |i: &mut usize| -> io::Result<String> {
+        let s = lines.get(*i).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unexpected end of file"))?.clone();
+        *i += 1;
+        Ok(s)
+    };
+
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let int = |i: &mut usize, field: &'static str| -> io::Result<BigInt> {
+        let x = hex_field(&take(i)?, field, MAX_PROOF_INT_BYTES)?;
+        check_proof_int(&x).map_err(invalid)?;
+        Ok(x)
+    };
+    // a round count is checked against MAX_IPP_ROUNDS and the lines left before anything is allocated
+    let rounds = |i: &mut usize, what: &str| -> io::Result<usize> {
+        let count: usize = take(i)?.trim().parse().map_err(|_| invalid(&format!("invalid {} length", what)))?;
+        if count > MAX_IPP_ROUNDS { return Err(limit_exceeded(LimitError::TooManyRounds(count))); }
+        let available = lines.len() - *i;
+        if count > available { return Err(limit_exceeded(LimitError::CountExceedsInput { declared: count, available })); }
+        Ok(count)
+    };
+
+    // Scalars
+    let a_commit = int(&mut i, "A")?;
+    let s_commit = int(&mut i, "S")?;
+    let t1_commit = int(&mut i, "T1")?;
+    let t2_commit = int(&mut i, "T2")?;
+    let tau_x = int(&mut i, "tau_x")?;
+    let mu = int(&mut i, "mu")?;
+    let t_hat = int(&mut i, "t_hat")?;
+    let c = int(&mut i, "C")?;
+    let c_v1 = int(&mut i, "C_v1")?;
+    let c_v2 = int(&mut i, "C_v2")?;
+    let t0 = int(&mut i, "t0")?;
+    let t1 = int(&mut i, "t1")?;
+    let t2 = int(&mut i, "t2")?;
+    let tau1 = int(&mut i, "tau1")?;
+    let tau2 = int(&mut i, "tau2")?;
+
+    // IPP vectors sizes
+    let l_len = rounds(&mut i, "L")?;
+    if l_len == 0 { return Err(io::Error::new(io::ErrorKind::InvalidData, "L length must be > 0")); }
+    let mut l_vec = Vec::with_capacity(l_len);
+    for _ in 0..l_len { l_vec.push(int(&mut i, "L")?); }
+    let r_len = rounds(&mut i, "R")?;
+    check_ipp_lengths(l_len, r_len).map_err(invalid)?;
+    let mut r_vec = Vec::with_capacity(r_len);
+    for _ in 0..r_len { r_vec.push(int(&mut i, "R")?); }
+
+    // IPP scalars
+    let a = int(&mut i, "a")?;
+    let b = int(&mut i, "b")?;
+    // Generator epoch; absent in proofs written before Params::rotate_generators existed
+    let epoch = match lines.get(i).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => s.parse::<u64>().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid generator epoch"))?,
+        None => 0,
+    };
+    for x in [&a_commit, &s_commit, &t1_commit, &t2_commit] { check_header_nonzero(x).map_err(invalid)?; }
+
+    // Statement section; required in PROOF_FORMAT_VERSION containers, absent from older files
+    let statement = if with_statement {
+        i += 1;
+        if lines.get(i).map(String::as_str) != Some(STATEMENT_HEADER) { return Err(invalid("proof file has no statement section")); }
+        Some(statement_from_lines(&lines[i..])?)
+    } else {
+        None
+    };
+
+    let ipp_proof = crate::range_proof::IPPProof { L: l_vec, R: r_vec, a, b };
+    Ok(Cuproof { A: a_commit, S: s_commit, T1: t1_commit, T2: t2_commit, tau_x, mu, t_hat, C: c, C_v1: c_v1, C_v2: c_v2, t0, t1, t2, tau1, tau2, ipp_proof, epoch, statement, assist })
+}
+
+/// An assist section, from its header line to the end of `lines`
+#[cfg(feature = "std")]
+fn assist_from_lines(lines: &[String]) -> io::Result<VerifierAssist> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let [_, q1, q2] = lines else { return Err(invalid("the assist section needs q1 and q2 and nothing after them")) };
+    let element = |line: &str, field: &'static str| -> io::Result<BigInt> {
+        let x = hex_field(line.trim(), field, MAX_PROOF_INT_BYTES)?;
+        check_proof_int(&x).map_err(invalid)?;
+        Ok(x)
+    };
+    Ok(VerifierAssist { q1: element(q1, "assist q1")?, q2: element(q2, "assist q2")? })
+}
+
+/// A statement section, from its header line to the end of `lines`
+#[cfg(feature = "std")]
+pub(crate) fn statement_from_lines(lines: &[String]) -> io::Result<Statement> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    if lines.first().map(String::as_str) != Some(STATEMENT_HEADER) { return Err(invalid("missing statement header")); }
+    let mut fields = lines[1..].iter().map(|line| line.trim());
+    let mut take = || fields.next().ok_or_else(|| invalid("unexpected end of file"));
+    let commitment = hex_field(take()?, "statement commitment", MAX_PROOF_INT_BYTES)?;
+    check_proof_int(&commitment).map_err(invalid)?;
+    let mut bound = |field: &'static str| -> io::Result<BigInt> {
+        let line = take()?;
+        if line.len() > 2 * MAX_PROOF_INT_BYTES + 3 {
+            return Err(limit_exceeded(LimitError::IntegerTooLarge { field, max_bytes: MAX_PROOF_INT_BYTES }));
+        }
+        hex_to_bigint_signed(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    };
+    let (a, b) = (bound("statement a")?, bound("statement b")?);
+    let bits = take()?.parse::<usize>().map_err(|_| invalid("invalid statement bit width"))?;
+    let label = hex::decode(take()?).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| invalid("invalid statement label"))?;
+    let mut params_fingerprint = [0u8; 32];
+    hex::decode_to_slice(take()?, &mut params_fingerprint).map_err(|_| invalid("invalid params fingerprint"))?;
+    if fields.next().is_some() { return Err(invalid("unexpected lines after the statement section")); }
+    Ok(Statement { commitment, a, b, bits, label, params_fingerprint })
+}
+
+/// Why migrate_proof / migrate_params could not bring a file to the current format
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    /// the bytes are no file this crate ever wrote, a file of the other kind, or a container from
+    /// a newer FILE_FORMAT_VERSION (which cannot be migrated back)
+    Format(FormatError),
+    /// a length field or integer exceeds the decoder's limits
+    Limit(LimitError),
+    /// the layout is recognised but its content does not parse or fails the load checks
+    Invalid(String),
+    /// the migrated params fail validate_params
+    Params(ParamsError),
+    /// the metadata section fails validate_metadata
+    Metadata(MetadataError),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Format(e) => write!(f, "{}", e),
+            MigrationError::Limit(e) => write!(f, "{}", e),
+            MigrationError::Invalid(msg) => write!(f, "{}", msg),
+            MigrationError::Params(e) => write!(f, "{}", e),
+            MigrationError::Metadata(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for MigrationError {}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for MigrationError {
+    fn from(e: io::Error) -> MigrationError {
+        match (format_error(&e), limit_error(&e)) {
+            (Some(f), _) => MigrationError::Format(f),
+            (_, Some(l)) => MigrationError::Limit(l),
+            _ => MigrationError::Invalid(e.to_string()),
+        }
+    }
+}
+
+/// Bring a proof file of any earlier layout to the current one
+/// - params: old_bytes the whole file: legacy text (the unversioned line format from before the
+///   container), a current container, or anything else load_proof reads
+/// - returns: the Cuproof, ready for save_proof / write_proof to write canonically; current files
+///   come back unchanged, so migrating twice is harmless
+/// - usage: `cuproof convert`. Legacy text proofs keep every integer, and proofs from before
+///   generator rotation, which have no epoch line, get epoch 0, the epoch of every params file of
+///   that time. The Fiat-Shamir challenges are recomputed from those same integers, so a proof
+///   that verified before migrating verifies after it. A proof whose transcript differs from the
+///   current one could only be migrated by proving again with the witness; the only such inputs
+///   are containers from a newer FILE_FORMAT_VERSION, which fail with Format(UnsupportedVersion)
+#[cfg(feature = "std")]
+pub fn migrate_proof(old_bytes: &[u8]) -> Result<Cuproof, MigrationError> {
+    Ok(decode_proof(old_bytes.to_vec())?)
+}
+
+/// Bring a params file of any earlier layout to the current one and validate it like load_params
+/// - params: old_bytes the whole file, as for migrate_proof
+/// - returns: Params, ready for save_params / write_params
+/// - usage: `cuproof convert`. g, h and n are carried over, so proofs made under the legacy file
+///   verify under the migrated one. Missing trailing lines are filled in: the modulus size becomes
+///   the bit length of n, the version PARAMS_VERSION and the epoch 0. The fingerprint hashes the
+///   size, so it matches the dealer's only if n has the size the dealer meant
+#[cfg(feature = "std")]
+pub fn migrate_params(old_bytes: &[u8]) -> Result<Params, MigrationError> {
+    let params = decode_params_unchecked(old_bytes.to_vec())?;
+    validate_params(&params).map_err(MigrationError::Params)?;
+    if params.metadata().is_some() {
+        validate_metadata(&params).map_err(MigrationError::Metadata)?;
+    }
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+
+    // Purpose: verify hex roundtrip and inner_product basic behavior
+    // Params: small vectors and integers
+    // Output: equality assertions
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn hex_roundtrip_and_inner_product() {
+        let x = BigInt::from(123456789u64);
+        let hx = bigint_to_hex(&x);
+        let x2 = hex_to_bigint(&hx).unwrap();
+        assert_eq!(x, x2);
+
+        let a = vec![BigInt::from(1), BigInt::from(2), BigInt::from(3)];
+        let b = vec![BigInt::from(4), BigInt::from(5), BigInt::from(6)];
+        let ip = inner_product(&a, &b);
+        assert_eq!(ip, BigInt::from(32)); // 1*4 + 2*5 + 3*6
+    }
+
+    // Purpose: ensure the constant-time comparisons agree with == on bytes and BigInts
+    // Params: equal inputs, inputs differing in the first or last byte, different lengths, signs
+    //         and widths smaller than the operands
+    // Output: true only for equal inputs
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn constant_time_equality() {
+        let a = [0x12u8, 0x34, 0x56, 0x78];
+        assert!(ct_eq_bytes(&a, &a));
+        assert!(ct_eq_bytes(&[], &[]));
+        assert!(!ct_eq_bytes(&a, &[0x13, 0x34, 0x56, 0x78]));
+        assert!(!ct_eq_bytes(&a, &[0x12, 0x34, 0x56, 0x79]));
+        assert!(!ct_eq_bytes(&a, &a[..3]));
+        assert!(!ct_eq_bytes(&a[..1], &a));
+
+        let x = BigInt::from(0x1234_5678u64);
+        assert!(ct_eq_bigint(&x, &x.clone(), 4));
+        assert!(ct_eq_bigint(&x, &x.clone(), 32));
+        assert!(!ct_eq_bigint(&x, &BigInt::from(0x1334_5678u64), 4));
+        assert!(!ct_eq_bigint(&x, &BigInt::from(0x1234_5679u64), 4));
+        // magnitudes of different byte lengths, and widths too small for the operands
+        assert!(!ct_eq_bigint(&x, &BigInt::from(0x34_5678u64), 4));
+        assert!(!ct_eq_bigint(&x, &(&x << 64), 2));
+        assert!(ct_eq_bigint(&(&x << 64), &(&x << 64), 2));
+        // sign and zero
+        assert!(!ct_eq_bigint(&x, &-&x, 4));
+        assert!(ct_eq_bigint(&-&x, &-&x, 4));
+        assert!(ct_eq_bigint(&BigInt::zero(), &BigInt::zero(), 8));
+        assert!(!ct_eq_bigint(&BigInt::zero(), &BigInt::one(), 8));
+
+        let p = random_bigint(256);
+        assert!(ct_eq_bigint(&p, &p.clone(), 32));
+        assert!(!ct_eq_bigint(&p, &(&p ^ BigInt::one()), 32));
+    }
+
+    // Purpose: ensure fixed-width hex has a length independent of the value and round-trips exactly
+    // Params: seeded random widths and values with 0 to 8 leading zero bytes, zero, and boundary values
+    // Output: 2 * width lowercase digits that parse back to the value; other lengths, signs, prefixes
+    //         and values that do not fit are errors
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn padded_hex_round_trips() {
+        use num_bigint::RandBigInt;
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(144);
+        for case in 0..500 {
+            let width = rng.gen_range(1..=80usize);
+            let leading_zeros = rng.gen_range(0..=8usize.min(width));
+            // exactly `leading_zeros` zero bytes in front: the top bit of the remaining bytes is set
+            let bits = 8 * (width - leading_zeros) as u64;
+            let x = if case % 5 == 0 || bits == 0 { BigInt::zero() } else { (BigInt::one() << (bits - 1)) | BigInt::from(rng.gen_biguint(bits - 1)) };
+            let hx = bigint_to_hex_padded(&x, width).unwrap();
+            assert_eq!(hx.len(), 2 * width, "case {}", case);
+            assert!(hx.bytes().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f')));
+            if !x.is_zero() { assert_eq!(hx.bytes().take_while(|&c| c == b'0').count() / 2, leading_zeros, "case {}", case); }
+            assert_eq!(bigint_from_hex_strict(&hx, width), Ok(x.clone()), "case {}", case);
+            assert_eq!(bigint_from_hex_strict(&hx.to_uppercase(), width), Ok(x.clone()));
+            assert_eq!(bigint_from_hex_strict(&hx[1..], width), Err(ParseError::WrongLength { expected: 2 * width, found: 2 * width - 1 }));
+            assert_eq!(bigint_from_hex_strict(&format!("00{}", hx), width), Err(ParseError::WrongLength { expected: 2 * width, found: 2 * width + 2 }));
+        }
+
+        let max = (BigInt::one() << 32) - 1;
+        assert_eq!(bigint_to_hex_padded(&max, 4), Ok("ffffffff".to_string()));
+        assert_eq!(bigint_to_hex_padded(&(&max + 1), 4), Err(EncodeError::TooWide { needed: 5, width: 4 }));
+        assert_eq!(bigint_to_hex_padded(&BigInt::from(-1), 4), Err(EncodeError::Negative));
+        assert_eq!(bigint_to_hex_padded(&BigInt::zero(), 0), Ok(String::new()));
+        assert_eq!(bigint_from_hex_strict("", 0), Ok(BigInt::zero()));
+        assert_eq!(bigint_from_hex_strict("0x0f", 2), Err(ParseError::InvalidDigit { ch: 'x', index: 1 }));
+        assert_eq!(bigint_from_hex_strict("-00f", 2), Err(ParseError::NegativeNotAllowed));
+        assert_eq!(bigint_from_hex_strict("00 f", 2), Err(ParseError::Whitespace(2)));
+    }
+
+    // Purpose: ensure hex_to_bigint is strict and inverts bigint_to_hex / bigint_to_signed_hex
+    // Params: fixed edge cases, a 2048-bit value and random values of assorted sizes and signs
+    // Output: exact values for accepted inputs, the matching ParseError for rejected ones
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn hex_parsing_is_strict_and_round_trips() {
+        assert_eq!(hex_to_bigint("0"), Ok(BigInt::zero()));
+        assert_eq!(hex_to_bigint("00ff"), Ok(BigInt::from(255)));
+        assert_eq!(hex_to_bigint("0x00FF"), Ok(BigInt::from(255)));
+        assert_eq!(hex_to_bigint("0XaBc"), Ok(BigInt::from(0xabc)));
+        assert_eq!(bigint_to_hex(&BigInt::zero()), "00");
+        assert_eq!(bigint_to_hex(&BigInt::from(255)), "ff");
+
+        let big = (BigInt::from(1) << 2047) + BigInt::from(0xabcdefu32);
+        let hx = bigint_to_hex(&big);
+        assert_eq!(hx.len(), 512);
+        assert_eq!(hex_to_bigint(&hx), Ok(big.clone()));
+        assert_eq!(hex_to_bigint(&format!("0x{}", hx.to_uppercase())), Ok(big));
+
+        assert_eq!(hex_to_bigint(""), Err(ParseError::Empty));
+        assert_eq!(hex_to_bigint("0x"), Err(ParseError::Empty));
+        assert_eq!(hex_to_bigint_signed("-"), Err(ParseError::Empty));
+        assert_eq!(hex_to_bigint("0xZZ"), Err(ParseError::InvalidDigit { ch: 'Z', index: 2 }));
+        assert_eq!(hex_to_bigint("12g4"), Err(ParseError::InvalidDigit { ch: 'g', index: 2 }));
+        assert_eq!(hex_to_bigint("ab cd"), Err(ParseError::Whitespace(2)));
+        assert_eq!(hex_to_bigint(" ab"), Err(ParseError::Whitespace(0)));
+        assert_eq!(hex_to_bigint("ab\n"), Err(ParseError::Whitespace(2)));
+        assert_eq!(hex_to_bigint("-ff"), Err(ParseError::NegativeNotAllowed));
+        assert_eq!(hex_to_bigint("0x-ff"), Err(ParseError::InvalidDigit { ch: '-', index: 2 }));
+        assert_eq!(hex_to_bigint_signed("-0xff"), Ok(BigInt::from(-255)));
+        assert_eq!(hex_to_bigint_signed("ff"), Ok(BigInt::from(255)));
+
+        for bits in [1usize, 7, 8, 63, 64, 65, 255, 1024, 2048] {
+            for _ in 0..8 {
+                let x = random_bigint(bits);
+                assert_eq!(hex_to_bigint(&bigint_to_hex(&x)), Ok(x.clone()));
+                assert_eq!(hex_to_bigint(&format!("0x{}", bigint_to_hex(&x).to_uppercase())), Ok(x.clone()));
+                let neg = -x;
+                assert_eq!(hex_to_bigint_signed(&bigint_to_signed_hex(&neg)), Ok(neg));
+            }
+        }
+    }
+
+    // Chi-squared statistic of observed bucket counts against a uniform expectation
+    fn chi_squared(counts: &[u64], samples: u64) -> f64 {
+        let expected = samples as f64 / counts.len() as f64;
+        counts.iter().map(|&c| (c as f64 - expected).powi(2) / expected).sum()
+    }
+
+    // Purpose: ensure random_bigint is uniform over [0, 2^bits) rather than fixed-width or biased
+    // Params: 16000 samples of 64 and 3 bits, bucketed by their low 4 bits / full value
+    // Output: chi-squared below the 99.99% quantile (15 dof: 44.3, 7 dof: 31.3); top bit set about half the time
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn random_bigint_is_uniform() {
+        let samples = 16_000u64;
+        let mut low = [0u64; 16];
+        let mut top = 0u64;
+        for _ in 0..samples {
+            let x = random_bigint(64);
+            assert!(!x.is_negative() && x.bits() <= 64);
+            low[(x.iter_u64_digits().next().unwrap_or(0) & 15) as usize] += 1;
+            if x.bit(63) { top += 1; }
+        }
+        assert!(chi_squared(&low, samples) < 44.3, "low bits {:?}", low);
+        assert!((7_500..8_500).contains(&top), "top bit set {} of {} times", top, samples);
+
+        let mut small = [0u64; 8];
+        for _ in 0..samples {
+            let x = random_bigint(3);
+            small[x.iter_u64_digits().next().unwrap_or(0) as usize] += 1;
+        }
+        assert!(chi_squared(&small, samples) < 31.3, "3-bit values {:?}", small);
+    }
+
+    // Purpose: ensure random_bigint_below stays below its bound and is unbiased for non-power-of-two bounds
+    // Params: bound 10 (9 dof, 99.99% quantile 33.7) and a 2048-bit bound
+    // Output: every sample in range, chi-squared within the quantile
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn random_bigint_below_is_uniform() {
+        let samples = 10_000u64;
+        let mut counts = [0u64; 10];
+        for _ in 0..samples {
+            let x = random_bigint_below(&BigInt::from(10));
+            assert!(!x.is_negative() && x < BigInt::from(10));
+            counts[x.iter_u64_digits().next().unwrap_or(0) as usize] += 1;
+        }
+        assert!(chi_squared(&counts, samples) < 33.7, "counts {:?}", counts);
+
+        let n = (BigInt::from(1) << 2047) + BigInt::from(12345);
+        for _ in 0..64 {
+            let x = random_bigint_below(&n);
+            assert!(!x.is_negative() && x < n);
+        }
+        assert_eq!(random_bigint_below(&BigInt::from(1)), BigInt::zero());
+    }
+
+    // Purpose: ensure random_below and random_coprime_to respect their bounds and gcd condition
+    // Params: seeded ChaCha20 stream; bounds 1, 7, 2^64, 2^64 + 1 and an even composite 360
+    // Output: every sample in [0, bound); coprime samples in [1, n) with gcd 1
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn random_below_and_coprime_stay_in_range() {
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(7);
+        let two_64 = BigInt::one() << 64u32;
+        for bound in [BigInt::from(1), BigInt::from(7), two_64.clone(), two_64 + 1] {
+            for _ in 0..2_000 {
+                let x = random_below(&bound, &mut rng);
+                assert!(!x.is_negative() && x < bound);
+            }
+        }
+        for n in [BigInt::from(2), BigInt::from(360), BigInt::from(1_000_003)] {
+            for _ in 0..2_000 {
+                let x = random_coprime_to(&n, &mut rng);
+                assert!(x.is_positive() && x < n);
+                assert!(x.gcd(&n).is_one());
+            }
+        }
+        assert_eq!(random_coprime_to(&BigInt::from(2), &mut rng), BigInt::one());
+    }
+
+    // Purpose: ensure a zero-width request is refused rather than silently returning a constant
+    // Params: bits = 0
+    // Output: panic
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    #[should_panic(expected = "at least 1 bit")]
+    fn random_bigint_rejects_zero_bits() {
+        random_bigint(0);
+    }
+
+    // Purpose: ensure the metadata section round-trips, is validated on load and stays optional
+    // Params: 256-bit provable trusted setup saved to a temp file with and without metadata
+    // Output: identical metadata after reload; tampered certificates rejected by load_params only;
+    //         files without a metadata section load with metadata() None
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn params_metadata_round_trips() {
+        use crate::metadata::MetadataError;
+        let provable = PrimalityConfig { provable: true, ..PrimalityConfig::default() };
+        let (params, _) = crate::setup::trusted_setup_with_config(256, true, &provable).unwrap();
+        let path = std::env::temp_dir().join(format!("cuproof_params_metadata_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_params(path, &params).unwrap();
+        let loaded = load_params(path).unwrap();
+        assert_eq!(loaded.metadata(), params.metadata());
+        assert_eq!(validate_metadata(&loaded), Ok(()));
+
+        // a flipped certificate witness fails load_params but can still be inspected
+        let mut lines = read_container(path, FileKind::Params).unwrap();
+        let cert = lines.iter().position(|l| l.starts_with("certificate ")).unwrap();
+        let (head, last) = lines[cert].rsplit_once(':').unwrap();
+        let tampered = format!("{}:1", head);
+        assert_ne!(last, "1");
+        lines[cert] = tampered;
+        write_container(path, FileKind::Params, &lines).unwrap();
+        assert_eq!(load_params(path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(validate_metadata(&load_params_unchecked(path).unwrap()), Err(MetadataError::InvalidCertificate(0)));
+
+        lines[8] = "colour blue".to_string();
+        write_container(path, FileKind::Params, &lines).unwrap();
+        assert!(load_params_unchecked(path).is_err());
+
+        // files without the section, as written before it existed
+        let params = params.with_metadata(None);
+        save_params(path, &params).unwrap();
+        assert_eq!(read_container(path, FileKind::Params).unwrap().len(), 7);
+        let loaded = load_params(path).unwrap();
+        assert_eq!(loaded, params);
+        assert!(loaded.metadata().is_none());
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure legacy text files written by the line format migrate to the current container
+    // Params: testdata/params_legacy.txt and testdata/proof_legacy.txt (insecure 512-bit params and a
+    //         proof of 30 in [10, 100] over them, without the container), variants with the trailing
+    //         lines older files lack, current containers, and a container from a newer version
+    // Output: migrated values verify and re-encode canonically; migrating current files is the
+    //         identity; container_version reports 0 for legacy text; unknown versions and proofs
+    //         fed as params are MigrationErrors
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn legacy_files_migrate_to_the_container() {
+        let legacy_params = include_bytes!("../testdata/params_legacy.txt");
+        let legacy_proof = include_bytes!("../testdata/proof_legacy.txt");
+        assert!(is_legacy_text(legacy_params) && is_legacy_text(legacy_proof));
+        let params = migrate_params(legacy_params).unwrap();
+        assert_eq!(params, crate::setup::insecure_test_setup(512));
+        let proof = migrate_proof(legacy_proof).unwrap();
+        assert!(crate::verify::cuproof_verify_with_range(&proof, &params, &BigInt::from(10), &BigInt::from(100)));
+
+        let (params_bytes, proof_bytes) = (encode_params(&params).unwrap(), encode_proof(&proof).unwrap());
+        assert!(params_bytes.starts_with(FileKind::Params.magic()) && proof_bytes.starts_with(FileKind::Proof.magic()));
+        assert_eq!(&params_bytes[14..params_bytes.len() - 32], legacy_params);
+        assert_eq!(&proof_bytes[14..proof_bytes.len() - 32], legacy_proof);
+        assert_eq!(migrate_params(&params_bytes).unwrap(), params);
+        assert_eq!(encode_proof(&migrate_proof(&proof_bytes).unwrap()).unwrap(), proof_bytes);
+        assert_eq!(container_version(legacy_proof, FileKind::Proof), Some(0));
+        assert_eq!(container_version(&proof_bytes, FileKind::Proof), Some(FILE_FORMAT_VERSION));
+        assert_eq!(container_version(&params_bytes, FileKind::Proof), None);
+
+        // files from before generator epochs: the proof has no epoch line, the params only g, h and n
+        let text = std::str::from_utf8(legacy_proof).unwrap();
+        let without_epoch = &text[..text.trim_end().rfind('\n').unwrap()];
+        let old = migrate_proof(without_epoch.as_bytes()).unwrap();
+        assert_eq!(old.epoch, 0);
+        assert_eq!(encode_proof(&old).unwrap(), proof_bytes);
+        let three_lines = std::str::from_utf8(legacy_params).unwrap().lines().take(3).collect::<Vec<_>>().join("\n");
+        let old = migrate_params(three_lines.as_bytes()).unwrap();
+        assert_eq!((old.bits(), old.version(), old.epoch()), (params.n().bits() as usize, PARAMS_VERSION, 0));
+        assert!(crate::verify::verify(&proof, &old));
+
+        let mut newer = proof_bytes.clone();
+        newer[9] = 3;
+        assert_eq!(migrate_proof(&newer).err(), Some(MigrationError::Format(FormatError::UnsupportedVersion(3))));
+        assert!(matches!(migrate_params(legacy_proof), Err(MigrationError::Invalid(_))));
+        assert!(matches!(migrate_params(&params_bytes[..40]), Err(MigrationError::Format(FormatError::LengthMismatch { .. }))));
+    }
+
+    // Purpose: replay the minimized fuzzer inputs in testdata/fuzz/<target>/ through the decoders
+    //          those targets drive (see fuzz/)
+    // Params: every file under testdata/fuzz/fuzz_load_proof, fuzz_load_params and, with the armor
+    //         feature, fuzz_from_armored
+    // Output: each input is an error, never a panic or an allocation of its declared size; the
+    //         limit cases name the limit they hit
+    // Usage: `cargo test -- src::util`; add the minimized input of every new fuzzer finding here
+    #[test]
+    fn fuzz_regressions_are_rejected() {
+        let corpus = |target: &str| {
+            let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/fuzz").join(target);
+            let mut files: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+            files.sort();
+            assert!(!files.is_empty());
+            files.into_iter().map(|path| (path.display().to_string(), fs::read(path).unwrap()))
+        };
+        let limits = ReadLimits::default();
+        for (name, bytes) in corpus("fuzz_load_proof") {
+            assert!(read_proof(&mut bytes.as_slice(), &limits).is_err(), "{}", name);
+            assert!(migrate_proof(&bytes).is_err(), "{}", name);
+        }
+        for (name, bytes) in corpus("fuzz_load_params") {
+            assert!(read_params(&mut bytes.as_slice(), &limits).is_err(), "{}", name);
+            assert!(migrate_params(&bytes).is_err(), "{}", name);
+        }
+        #[cfg(feature = "armor")]
+        for (name, bytes) in corpus("fuzz_from_armored") {
+            assert!(Cuproof::from_armored(&String::from_utf8_lossy(&bytes)).is_err(), "{}", name);
+        }
+
+        let proof = |bytes: &[u8]| match read_proof(&mut &bytes[..], &limits) {
+            Err(ProofLoadError::Limit(e)) => e,
+            other => panic!("expected a LimitError, got {:?}", other.err()),
+        };
+        assert_eq!(proof(include_bytes!("../testdata/fuzz/fuzz_load_proof/round-count-huge")), LimitError::TooManyRounds(u32::MAX as usize));
+        assert_eq!(
+            proof(include_bytes!("../testdata/fuzz/fuzz_load_proof/round-count-past-input")),
+            LimitError::CountExceedsInput { declared: 64, available: 0 }
+        );
+        assert_eq!(
+            proof(include_bytes!("../testdata/fuzz/fuzz_load_proof/integer-too-long")),
+            LimitError::IntegerTooLarge { field: "A", max_bytes: MAX_PROOF_INT_BYTES }
+        );
+        let params = |bytes: &[u8]| read_params(&mut &bytes[..], &limits).unwrap_err();
+        assert!(matches!(
+            params(include_bytes!("../testdata/fuzz/fuzz_load_params/modulus-too-long")),
+            ParamsLoadError::Limit(LimitError::IntegerTooLarge { field: "n", .. })
+        ));
+        assert!(matches!(
+            params(include_bytes!("../testdata/fuzz/fuzz_load_params/modulus-over-max-bits")),
+            ParamsLoadError::Params(ParamsError::ModulusTooLarge { bits, .. }) if bits == MAX_MODULUS_BITS + 1
+        ));
+        assert!(matches!(
+            params(include_bytes!("../testdata/fuzz/fuzz_load_params/too-many-certificates")),
+            ParamsLoadError::Limit(LimitError::TooManyEntries { what: "certificates", max: MAX_METADATA_CERTIFICATES })
+        ));
+        assert!(matches!(
+            params(include_bytes!("../testdata/fuzz/fuzz_load_params/too-many-certificate-steps")),
+            ParamsLoadError::Limit(LimitError::TooManyEntries { what: "certificate steps", .. })
+        ));
+    }
+
+    // Purpose: ensure params and proof files are framed, checksummed and rejected with a precise FormatError
+    // Params: fast params saved to a temp file, then corrupted one way per case
+    // Output: each corruption maps to its FormatError variant; legacy text files are detected and still load
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn container_rejects_corrupt_files() {
+        let params = crate::setup::insecure_test_setup(512);
+        let path = std::env::temp_dir().join(format!("cuproof_container_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_params(path, &params).unwrap();
+        let good = fs::read(path).unwrap();
+        assert!(good.starts_with(b"CUPARAMS"));
+        assert!(!is_legacy_file(path).unwrap());
+        assert_eq!(load_params(path).unwrap(), params);
+
+        let format_error = |bytes: &[u8], kind: FileKind| {
+            fs::write(path, bytes).unwrap();
+            let err = read_container(path, kind).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            err.get_ref().and_then(|e| e.downcast_ref::<FormatError>()).cloned().unwrap()
+        };
+        let mut bad_magic = good.clone();
+        bad_magic[0] = 0xff;
+        assert_eq!(format_error(&bad_magic, FileKind::Params), FormatError::BadMagic { expected: FileKind::Params, found: None });
+        assert_eq!(format_error(&good, FileKind::Proof), FormatError::BadMagic { expected: FileKind::Proof, found: Some(FileKind::Params) });
+        let mut version = good.clone();
+        version[9] = 2;
+        assert_eq!(format_error(&version, FileKind::Params), FormatError::UnsupportedVersion(2));
+        assert_eq!(format_error(&good[..12], FileKind::Params), FormatError::TruncatedHeader);
+        let declared = u32::from_be_bytes(good[10..14].try_into().unwrap());
+        assert_eq!(
+            format_error(&good[..good.len() - 1], FileKind::Params),
+            FormatError::LengthMismatch { declared, available: good.len() - 15 }
+        );
+        let mut flipped = good.clone();
+        flipped[20] ^= 1;
+        assert_eq!(format_error(&flipped, FileKind::Params), FormatError::ChecksumMismatch);
+        let mut trailing = good.clone();
+        trailing.extend_from_slice(b"xyz");
+        assert_eq!(format_error(&trailing, FileKind::Params), FormatError::TrailingGarbage(3));
+
+        // proofs use their own magic and are not accepted as params
+        write_container(path, FileKind::Proof, &["01".to_string()]).unwrap();
+        let err = load_params(path).unwrap_err();
+        assert_eq!(err.to_string(), "expected a params file, found a proof file");
+
+        // text files written before the container are migrated in memory
+        fs::write(path, &good).unwrap();
+        let lines = read_container(path, FileKind::Params).unwrap();
+        write_lines(path, &lines).unwrap();
+        assert!(is_legacy_file(path).unwrap());
+        assert_eq!(load_params(path).unwrap(), params);
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure proof files carry the statement and a version-2 file without it is refused
+    // Params: fast params, a labelled proof saved to a temp file, then rewritten without its statement section
+    // Output: the statement survives save/load; the stripped file fails to load; v1 files load without one
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn proof_files_carry_the_statement() {
+        use crate::range_proof::{prove, Statement};
+        use crate::verify::{cuproof_verify_checked, verify_statement, VerifyError};
+        let params = crate::setup::insecure_test_setup(512);
+        let (a, b) = (BigInt::from(10), BigInt::from(1000));
+        let proof = prove(&BigInt::from(99), &random_bigint(128), &a, &b, &params).unwrap().with_label("payroll/2026");
+        let path = std::env::temp_dir().join(format!("cuproof_statement_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_proof(path, &proof).unwrap();
+        let bytes = fs::read(path).unwrap();
+        assert_eq!(u16::from_be_bytes([bytes[8], bytes[9]]), PROOF_FORMAT_VERSION);
+
+        let loaded = load_proof(path).unwrap();
+        let expected = Statement::new(&proof.C, &a, &b, 64, &params).with_label("payroll/2026");
+        assert_eq!(loaded.statement.as_ref(), Some(&expected));
+        assert!(verify_statement(&loaded, &params, &expected).is_ok());
+        assert_eq!(encode_proof(&loaded).unwrap(), bytes);
+
+        // the same payload without its statement lines, still claiming version 2
+        let (_, lines) = container_lines(bytes, FileKind::Proof).unwrap();
+        let stripped = &lines[..lines.len() - 7];
+        write_file(path, &container_bytes(FileKind::Proof, PROOF_FORMAT_VERSION, stripped).unwrap()).unwrap();
+        let err = load_proof(path).err().unwrap();
+        assert_eq!((err.kind(), err.to_string()), (io::ErrorKind::InvalidData, "proof file has no statement section".to_string()));
+
+        // as a version-1 file it is a proof from before statements were recorded
+        write_container(path, FileKind::Proof, stripped).unwrap();
+        let old = load_proof(path).unwrap();
+        assert!(old.statement.is_none());
+        assert!(cuproof_verify_checked(&old, &params).is_ok());
+        assert_eq!(verify_statement(&old, &params, &expected), Err(VerifyError::MissingStatement));
+
+        let multiline = proof.clone().with_label("two\nlines");
+        save_proof(path, &multiline).unwrap();
+        assert_eq!(load_proof(path).unwrap().statement, multiline.statement);
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure Encoder writes the same bytes as the String-per-line encoding and can be reused
+    // Params: boundary integers for the hex digits; one Encoder encoding a labelled and an unlabelled proof in turn
+    // Output: hex equals bigint_to_hex / bigint_to_signed_hex; reused output equals a fresh encoding and the container of the old lines
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn encoder_matches_line_encoding() {
+        use crate::range_proof::prove;
+        let mut encoder = Encoder::new();
+        let one = BigInt::from(1);
+        for x in [BigInt::from(0), one.clone(), BigInt::from(15), BigInt::from(256), (&one << 64u32) - 1, &one << 64u32, -(&one << 71u32) - 5, random_bigint(2048)] {
+            encoder.begin();
+            encoder.hex(&x);
+            encoder.signed_hex(&x);
+            let expected = format!("{}\n{}", bigint_to_hex(&x), bigint_to_signed_hex(&x));
+            assert_eq!(&encoder.buf[CONTAINER_HEADER_LEN..], expected.as_bytes());
+        }
+
+        let params = crate::setup::insecure_test_setup(512);
+        let labelled = prove(&BigInt::from(7), &random_bigint(128), &BigInt::from(0), &BigInt::from(50), &params).unwrap().with_label("x");
+        let mut unlabelled = labelled.clone();
+        unlabelled.statement = None;
+        for proof in [&labelled, &unlabelled, &labelled] {
+            let fresh = encode_proof(proof).unwrap();
+            assert_eq!(encoder.encode_proof(proof).unwrap(), &fresh[..]);
+            let (version, lines) = container_lines(fresh.clone(), FileKind::Proof).unwrap();
+            assert_eq!(container_bytes(FileKind::Proof, version, &lines).unwrap(), fresh);
+        }
+    }
+
+    // Purpose: ensure to_bytes / from_bytes are the file payloads, deterministic and checked like the loaders
+    // Params: tiny params with and without metadata, a labelled proof and the same proof without its statement
+    // Output: to_bytes equals the saved file minus header and checksum and is identical on every call;
+    //         every encoding round-trips (testing::assert_roundtrip); from_bytes enforces ReadLimits
+    //         and the load checks
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn in_memory_codecs_are_the_file_payloads() {
+        use crate::testing::{assert_roundtrip, assert_statement_roundtrip, sample_proof};
+        let payload = |file: &[u8]| file[CONTAINER_HEADER_LEN..file.len() - CONTAINER_TRAILER_LEN].to_vec();
+        let limits = ReadLimits::default();
+        let (params, _, proof) = sample_proof(7);
+        let labelled = proof.with_label("queue");
+        let mut bare = labelled.clone();
+        bare.statement = None;
+
+        for proof in [&labelled, &bare] {
+            let bytes = proof.to_bytes();
+            assert_eq!(proof.to_bytes(), bytes);
+            assert_eq!(payload(&encode_proof(proof).unwrap()), bytes);
+            assert_roundtrip(proof);
+            assert!(matches!(Cuproof::from_bytes(&bytes, &ReadLimits::new(bytes.len() - 1)), Err(ProofLoadError::TooLarge { .. })));
+            assert!(Cuproof::from_bytes(&bytes[..bytes.len() / 2], &limits).is_err());
+        }
+        let statement = labelled.statement.as_ref().unwrap();
+        assert_statement_roundtrip(statement);
+        assert!(labelled.to_bytes().ends_with(&statement.to_bytes()));
+
+        let with_metadata = crate::setup::trusted_setup_with(512, true).unwrap();
+        for p in [&params, &with_metadata] {
+            let bytes = p.to_bytes();
+            assert_eq!(p.to_bytes(), bytes);
+            assert_eq!(payload(&encode_params(p).unwrap()), bytes);
+            let decoded = Params::from_bytes(&bytes, &limits).unwrap();
+            assert_eq!(&decoded, p);
+            assert_eq!(decoded.metadata(), p.metadata());
+            assert_eq!(decoded.to_bytes(), bytes);
+        }
+        let degenerate = Params::new(BigInt::from(1), params.h().clone(), params.n().clone(), 512);
+        assert!(matches!(Params::from_bytes(&degenerate.to_bytes(), &limits), Err(ParamsLoadError::Params(_))));
+    }
+
+    // Purpose: ensure params files record the modulus size and load_params_min_bits enforces it
+    // Params: reduced-size insecure setup saved to a temp file
+    // Output: assertions on recorded bits and min-size rejection
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn params_record_size_and_enforce_minimum() {
+        let params = crate::setup::trusted_setup_with(256, true).unwrap();
+        let path = std::env::temp_dir().join(format!("cuproof_params_bits_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_params(path, &params).unwrap();
+
+        let loaded = load_params(path).unwrap();
+        assert_eq!(loaded, params);
+        assert_eq!(loaded.bits(), 256);
+        assert!(loaded.generators_match_seed());
+        assert!(load_params_min_bits(path, 256).is_ok());
+        let err = load_params_min_bits(path, 2048).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(loaded.epoch(), 0);
+
+        // the generator epoch survives a round trip
+        let rotated = params.rotate_generators("util-test");
+        save_params(path, &rotated).unwrap();
+        assert_eq!(load_params(path).unwrap(), rotated);
+
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        // declared size larger than the actual modulus is rejected
+        write_lines(path, &[bigint_to_hex(g), bigint_to_hex(h), bigint_to_hex(n), "2048".to_string()]).unwrap();
+        assert!(load_params(path).is_err());
+
+        // unknown versions are rejected
+        write_lines(path, &[bigint_to_hex(g), bigint_to_hex(h), bigint_to_hex(n), "256".to_string(), "99".to_string()]).unwrap();
+        assert!(load_params(path).is_err());
+
+        // legacy 3-line files fall back to the bit length of n
+        write_lines(path, &[bigint_to_hex(g), bigint_to_hex(h), bigint_to_hex(n)]).unwrap();
+        assert_eq!(load_params(path).unwrap().bits(), 256);
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure load_params rejects hand-crafted bad params files with the matching ParamsError
+    // Params: fast params with one defect written per file (even n, n below its declared size, g = 0,
+    //         h = n - 1, ...), and a truncated container
+    // Output: each file fails load_params with its variant and still loads unchecked; the truncated
+    //         file fails both with its FormatError
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn load_params_validates_crafted_files() {
+        use crate::setup::ParamsError;
+        let params = crate::setup::insecure_test_setup(512);
+        let (g, h, n) = (params.g(), params.h(), params.n());
+        let p = BigInt::from(65537u32) * BigInt::from(65537u32) * BigInt::from(65537u32);
+        // Mersenne primes: a modulus with no small factors and a known factor to reuse as g
+        let m127 = (BigInt::from(1) << 127u32) - 1;
+        let m107 = (BigInt::from(1) << 107u32) - 1;
+        let n_bits = n.bits() as usize;
+        let cases: Vec<(Vec<String>, ParamsError)> = vec![
+            (vec![bigint_to_hex(g), bigint_to_hex(h), bigint_to_hex(&(n * 2u32))], ParamsError::EvenModulus),
+            (
+                vec![bigint_to_hex(g), bigint_to_hex(h), bigint_to_hex(n), (2 * n_bits).to_string()],
+                ParamsError::ModulusBelowDeclaredSize { bits: n_bits, declared: 2 * n_bits },
+            ),
+            (vec![bigint_to_hex(g), bigint_to_hex(h), bigint_to_hex(&(n * 9973u32))], ParamsError::SmallFactor(9973)),
+            (vec!["02".into(), "03".into(), bigint_to_hex(&(&p * &p))], ParamsError::PerfectPower { exponent: 2 }),
+            (vec!["00".into(), bigint_to_hex(h), bigint_to_hex(n)], ParamsError::GeneratorOutOfRange("g")),
+            (vec!["01".into(), bigint_to_hex(h), bigint_to_hex(n)], ParamsError::GeneratorOutOfRange("g")),
+            (vec![bigint_to_hex(g), bigint_to_hex(&(n + 5u32)), bigint_to_hex(n)], ParamsError::GeneratorOutOfRange("h")),
+            (vec![bigint_to_hex(g), bigint_to_hex(&(n - 1u32)), bigint_to_hex(n)], ParamsError::GeneratorIsMinusOne("h")),
+            (vec![bigint_to_hex(&m127), "05".into(), bigint_to_hex(&(&m127 * &m107))], ParamsError::GeneratorNotCoprime("g")),
+            (vec![bigint_to_hex(g), bigint_to_hex(g), bigint_to_hex(n)], ParamsError::EqualGenerators),
+        ];
+        let path = std::env::temp_dir().join(format!("cuproof_params_bad_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        for (lines, expected) in cases {
+            write_lines(path, &lines).unwrap();
+            let err = load_params(path).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            let inner = err.get_ref().and_then(|e| e.downcast_ref::<ParamsError>()).cloned();
+            assert_eq!(inner, Some(expected));
+            assert!(load_params_unchecked(path).is_ok());
+        }
+
+        // a truncated container fails before validation, with the FormatError of the cut
+        save_params(path, &params).unwrap();
+        let bytes = fs::read(path).unwrap();
+        fs::write(path, &bytes[..bytes.len() - 40]).unwrap();
+        for err in [load_params(path).unwrap_err(), load_params_unchecked(path).unwrap_err()] {
+            let inner = err.get_ref().and_then(|e| e.downcast_ref::<FormatError>()).cloned();
+            assert!(matches!(inner, Some(FormatError::LengthMismatch { .. })), "{:?}", inner);
+        }
+        fs::write(path, &bytes[..10]).unwrap();
+        let inner = load_params(path).unwrap_err().get_ref().and_then(|e| e.downcast_ref::<FormatError>()).cloned();
+        assert_eq!(inner, Some(FormatError::TruncatedHeader));
+        let _ = fs::remove_file(path);
+    }
+
+    /// Yields at most one byte per read, with an Interrupted error before every other byte
+    struct Trickle<R> {
+        inner: R,
+        interrupt: bool,
+    }
+
+    impl<R: Read> Read for Trickle<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt { return Err(io::Error::from(io::ErrorKind::Interrupted)); }
+            let len = buf.len().min(1);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    // Purpose: ensure the stream readers and writers (proofs, params, commitments, blindings) work on arbitrary streams
+    // Params: fast params, an honest proof and its commitment and blinding, written to a Vec and read back from a Cursor, from a
+    //         reader yielding one byte at a time, and from truncated or oversized streams
+    // Output: round trips verify; truncation maps to its FormatError; the size cap gives TooLarge;
+    //         validation failures come back as ParamsLoadError::Params
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn streams_round_trip_and_enforce_limits() {
+        use std::io::Cursor;
+        let params = crate::setup::insecure_test_setup(512);
+        let proof = crate::range_proof::prove(&BigInt::from(30), &BigInt::from(42), &BigInt::from(10), &BigInt::from(100), &params).unwrap();
+        let limits = ReadLimits::default();
+
+        let mut proof_bytes = Vec::new();
+        assert_eq!(write_proof(&mut proof_bytes, &proof).unwrap(), proof_bytes.len());
+        let mut params_bytes = Vec::new();
+        assert_eq!(write_params(&mut params_bytes, &params).unwrap(), params_bytes.len());
+
+        let read = read_proof(&mut Cursor::new(&proof_bytes), &limits).unwrap();
+        assert!(crate::verify::verify(&read, &params));
+        assert_eq!(read_params(&mut Cursor::new(&params_bytes), &limits).unwrap(), params);
+
+        let read = read_proof(&mut Trickle { inner: Cursor::new(&proof_bytes), interrupt: false }, &limits).unwrap();
+        assert!(crate::verify::verify(&read, &params));
+        let mut trickle = Trickle { inner: Cursor::new(&params_bytes), interrupt: false };
+        assert_eq!(read_params(&mut trickle, &limits).unwrap(), params);
+
+        // the stream ends inside a field of the payload, then inside the header
+        let cut = &proof_bytes[..proof_bytes.len() / 2];
+        match read_proof(&mut Trickle { inner: Cursor::new(cut), interrupt: false }, &limits) {
+            Err(ProofLoadError::Format(FormatError::LengthMismatch { available, .. })) => assert_eq!(available, cut.len() - 14),
+            other => panic!("expected LengthMismatch, got {:?}", other.err()),
+        }
+        assert!(matches!(read_params(&mut Cursor::new(&params_bytes[..9]), &limits), Err(ParamsLoadError::Format(FormatError::TruncatedHeader))));
+        assert!(matches!(read_proof(&mut Cursor::new(&[] as &[u8]), &limits), Err(ProofLoadError::Format(FormatError::BadMagic { found: None, .. }))));
+
+        let tight = ReadLimits::new(proof_bytes.len() - 1);
+        assert!(matches!(read_proof(&mut Cursor::new(&proof_bytes), &tight), Err(ProofLoadError::TooLarge { limit }) if limit == proof_bytes.len() - 1));
+        assert!(read_proof(&mut Cursor::new(&proof_bytes), &ReadLimits::new(proof_bytes.len())).is_ok());
+        assert!(matches!(read_params(&mut io::repeat(b'0'), &limits), Err(ParamsLoadError::TooLarge { .. })));
+
+        let even = Params::new(params.g().clone(), params.h().clone(), params.n() * 2u32, params.bits());
+        let mut even_bytes = Vec::new();
+        write_params(&mut even_bytes, &even).unwrap();
+        assert!(matches!(read_params(&mut Cursor::new(&even_bytes), &limits), Err(ParamsLoadError::Params(ParamsError::EvenModulus))));
+
+        let (commitment, blinding) = (proof.C.clone(), BigInt::from(42));
+        let mut commitment_bytes = Vec::new();
+        assert_eq!(write_commitment(&mut commitment_bytes, &commitment, &params).unwrap(), commitment_bytes.len());
+        let mut trickle = Trickle { inner: Cursor::new(&commitment_bytes), interrupt: false };
+        assert_eq!(read_commitment(&mut trickle, &params, &limits).unwrap(), commitment);
+        assert!(read_commitment(&mut Cursor::new(&commitment_bytes), &params.rotate_generators("other"), &limits).is_err());
+        let mut blinding_bytes = Vec::new();
+        write_blinding(&mut blinding_bytes, &blinding).unwrap();
+        assert_eq!(read_blinding(&mut Cursor::new(&blinding_bytes), &limits).unwrap(), blinding);
+        assert!(read_blinding(&mut Cursor::new(&blinding_bytes), &ReadLimits::new(blinding_bytes.len() - 1)).is_err());
+    }
+
+    /// Passes `budget` bytes through, then fails every write
+    struct FailAfter<'a> {
+        inner: &'a mut dyn Write,
+        budget: usize,
+    }
+
+    impl Write for FailAfter<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.budget == 0 { return Err(io::Error::other("disk full")); }
+            let len = buf.len().min(self.budget);
+            self.budget -= len;
+            self.inner.write(&buf[..len])
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    // Purpose: ensure saves replace files all or nothing and keep an optional backup
+    // Params: a proof and params saved over an existing file, once through a writer that fails
+    //         partway and once normally, with and without backup
+    // Output: a failed save leaves the old bytes and no temporary file; a successful one leaves the
+    //         new bytes, plus the old ones in `.bak` when asked
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn saves_are_atomic() {
+        let params = crate::setup::insecure_test_setup(512);
+        let proof = crate::range_proof::prove(&BigInt::from(30), &BigInt::from(42), &BigInt::from(10), &BigInt::from(100), &params).unwrap();
+        let dir = std::env::temp_dir().join(format!("cuproof_atomic_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("proof.bin");
+        let path = path.to_str().unwrap();
+        let backup = format!("{}.bak", path);
+        let entries = || fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().into_string().unwrap()).collect::<Vec<_>>();
+
+        save_params(path, &params).unwrap();
+        let old = fs::read(path).unwrap();
+        for budget in [0, 20, old.len() / 2] {
+            let err = write_atomically(path, &SaveOptions::default().with_backup(), |w| {
+                write_proof(&mut FailAfter { inner: w, budget }, &proof).map(|_| ())
+            })
+            .unwrap_err();
+            assert_eq!(err.to_string(), "disk full");
+            assert_eq!(fs::read(path).unwrap(), old);
+            assert_eq!(entries(), vec!["proof.bin".to_string()]);
+        }
+
+        save_proof(path, &proof).unwrap();
+        assert!(crate::verify::verify(&load_proof(path).unwrap(), &params));
+        assert!(!Path::new(&backup).exists());
+        let new = fs::read(path).unwrap();
+
+        save_params_with(path, &params, &SaveOptions::default().with_backup()).unwrap();
+        assert_eq!(fs::read(&backup).unwrap(), new);
+        assert_eq!(load_params(path).unwrap(), params);
+        let mut names = entries();
+        names.sort();
+        assert_eq!(names, vec!["proof.bin".to_string(), "proof.bin.bak".to_string()]);
+
+        // a fresh destination gets no backup
+        let fresh = dir.join("sub").join("proof.bin");
+        save_proof_with(fresh.to_str().unwrap(), &proof, &SaveOptions::default().with_backup()).unwrap();
+        assert_eq!(fs::read_dir(fresh.parent().unwrap()).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // A small hand-written proof whose JSON encodings are checked in as testdata/proof_v1.json and proof_v2.json
+    #[cfg(feature = "json")]
+    fn golden_proof() -> Cuproof {
+        let int = |x: i64| BigInt::from(x);
+        Cuproof {
+            A: int(1), S: int(2), T1: int(3), T2: int(4), tau_x: int(5), mu: int(-6), t_hat: int(7),
+            C: int(8), C_v1: int(9), C_v2: int(10), t0: int(11), t1: int(12), t2: int(13), tau1: int(14), tau2: int(15),
+            ipp_proof: crate::range_proof::IPPProof { L: vec![int(16), int(17)], R: vec![int(18), int(0x1234)], a: int(20), b: int(21) },
+            epoch: 2,
+            statement: None,
+            assist: None,
+        }
+    }
+
+    // Purpose: ensure the JSON schema stays stable and JSON files load through load_proof / load_params
+    // Params: the golden proof, insecure 512-bit params, an honest proof over them
+    // Output: encodings equal the checked-in testdata files; auto-detected loads equal the originals
+    //         and the honest proof still verifies
+    // Usage: `cargo test --features json -- src::util`
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_files_match_golden_and_round_trip() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("cuproof_json_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        save_proof_json(path, &golden_proof(), 512).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), include_str!("../testdata/proof_v2.json"));
+        let loaded = load_proof(path).unwrap();
+        assert_eq!(loaded.mu, BigInt::from(-6));
+        save_proof_json(path, &loaded, 512).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), include_str!("../testdata/proof_v2.json"));
+        // version 1 documents, with unpadded group elements, still load
+        fs::write(path, include_str!("../testdata/proof_v1.json")).unwrap();
+        assert_eq!(encode_proof(&load_proof(path).unwrap()).unwrap(), encode_proof(&golden_proof()).unwrap());
+
+        let params = crate::setup::insecure_test_setup(512);
+        fs::write(path, include_str!("../testdata/params_v1.json")).unwrap();
+        assert_eq!(load_params(path).unwrap(), params);
+        save_params_json(path, &params).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), include_str!("../testdata/params_v2.json"));
+        assert_eq!(load_params(path).unwrap(), params);
+        assert_eq!(load_params_json(path).unwrap(), params);
+        assert!(load_proof(path).err().unwrap().to_string().contains("invalid JSON proof"));
+
+        let proof = crate::range_proof::prove(&BigInt::from(42), &random_bigint(128), &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+        save_proof_json(path, &proof, params.bits()).unwrap();
+        let text = fs::read_to_string(path).unwrap();
+        fs::write(path, format!("\n  {}", text)).unwrap();
+        let loaded = load_proof(path).unwrap();
+        assert!(crate::verify::verify(&loaded, &params));
+        assert!(load_params(path).is_err());
+
+        // counters are strings, and only canonical ones are accepted
+        assert!(!text.contains(": 512") && text.contains("\"bits\": \"512\""));
+        for (from, to) in [("\"bits\": \"512\"", "\"bits\": 512"), ("\"version\": \"2\"", "\"version\": \"02\""), ("\"version\": \"2\"", "\"version\": \"3\"")] {
+            fs::write(path, text.replacen(from, to, 1)).unwrap();
+            assert_eq!(load_proof(path).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        }
+        // group elements are exactly 128 digits for 512-bit params, other lengths are refused
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let elements = ["A", "S", "T1", "T2", "C", "C_v1", "C_v2"].iter().map(|k| &value[k]).chain(value["ipp"]["L"].as_array().unwrap());
+        assert!(elements.map(|v| v.as_str().unwrap().len()).all(|len| len == 128));
+        for (from, to) in [("\"bits\": \"512\"", "\"bits\": \"520\""), ("\"A\": \"", "\"A\": \"0"), ("\"C\": \"", "\"C\": \"00")] {
+            fs::write(path, text.replacen(from, to, 1)).unwrap();
+            assert!(load_proof(path).err().unwrap().to_string().contains("hex digits"), "{} -> {}", from, to);
+        }
+        assert_eq!(save_proof_json(path, &proof, 256).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        fs::write(path, text.replacen("{", "{\"extra\": \"1\",", 1)).unwrap();
+        assert!(load_proof(path).is_err());
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure aggregated proofs and statements files round-trip and untrusted counts are refused
+    // Params: insecure 512-bit params, an aggregated proof of three statements
+    // Output: byte-identical re-encoding and the same statements back; a count over
+    //         MAX_AGGREGATED_STATEMENTS, a count beyond the lines left and a proof file loaded as
+    //         an aggregated proof rejected with the matching error
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn aggregated_proofs_and_statements_roundtrip() {
+        use crate::aggregate::{aggregate_prove, AggregateEntry};
+        let path = std::env::temp_dir().join(format!("cuproof_aggregated_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        let params = crate::setup::insecure_test_setup(512);
+        let entries = (0..3).map(|k| AggregateEntry {
+            v: BigInt::from(10 * k), r: BigInt::from(k + 1), a: BigInt::from(-1), b: BigInt::from(100),
+        }).collect::<Vec<_>>();
+        let proof = aggregate_prove(&entries, &params).unwrap();
+        save_aggregated_proof(path, &proof).unwrap();
+        let bytes = fs::read(path).unwrap();
+        assert!(is_aggregated_proof(&bytes));
+        let loaded = load_aggregated_proof(path).unwrap();
+        assert_eq!(encode_aggregated_proof(&loaded).unwrap(), bytes);
+        assert_eq!(loaded.statements, proof.statements);
+
+        save_statements(path, &proof.statements).unwrap();
+        assert_eq!(load_statements(path).unwrap(), proof.statements);
+
+        let payload = |count: &str| {
+            let (_, payload) = open_container(&bytes, FileKind::AggregatedProof).unwrap();
+            let text = std::str::from_utf8(payload).unwrap();
+            format!("{}\n{}", count, text.split_once('\n').unwrap().1)
+        };
+        for (count, expected) in [
+            ((MAX_AGGREGATED_STATEMENTS + 1).to_string(), LimitError::TooManyStatements(MAX_AGGREGATED_STATEMENTS + 1)),
+            // 3 statements of 3 + 7 lines, 12 shared scalars, 2 * (1 + 8) rounds, a, b and the epoch
+            ("7".to_string(), LimitError::CountExceedsInput { declared: 7, available: 3 * 10 + 12 + 2 * 9 + 3 }),
+        ] {
+            let forged = container_bytes(FileKind::AggregatedProof, FILE_FORMAT_VERSION, &[payload(&count)]).unwrap();
+            match read_aggregated_proof(&mut &forged[..], &ReadLimits::default()) {
+                Err(ProofLoadError::Limit(e)) => assert_eq!(e, expected),
+                other => panic!("{} statements: {:?}", count, other.map(|p| p.statements.len())),
+            }
+        }
+
+        save_proof(path, &crate::range_proof::prove(&BigInt::from(1), &BigInt::from(2), &BigInt::from(0), &BigInt::from(9), &params).unwrap()).unwrap();
+        let e = load_aggregated_proof(path).err().unwrap();
+        assert!(e.to_string().contains("expected an aggregated proof file, found a proof file"), "{}", e);
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure comparison proofs round-trip and their width and shift are bounded on load
+    // Params: insecure 512-bit params, a proof of 41 < 42 under 8 bits
+    // Output: byte-identical re-encoding that still verifies; widths 0 and above MAX_COMPARISON_BITS
+    //         refused as invalid; the file loaded as a plain proof refused by its magic
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn comparison_proofs_roundtrip() {
+        use crate::comparison::{prove_less_than, verify_less_than};
+        let path = std::env::temp_dir().join(format!("cuproof_comparison_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        let params = crate::setup::insecure_test_setup(512);
+        let (v1, r1, v2, r2) = (BigInt::from(41), BigInt::from(5), BigInt::from(42), BigInt::from(3));
+        let proof = prove_less_than(&v1, &r1, &v2, &r2, 8, &params).unwrap();
+        save_comparison_proof(path, &proof).unwrap();
+        let bytes = fs::read(path).unwrap();
+        assert!(bytes.starts_with(FileKind::ComparisonProof.magic()));
+        let loaded = load_comparison_proof(path).unwrap();
+        let mut encoded = Vec::new();
+        write_comparison_proof(&mut encoded, &loaded).unwrap();
+        assert_eq!(encoded, bytes);
+        let (c1, c2) = (crate::commitment::commit(&params, &v1, &r1), crate::commitment::commit(&params, &v2, &r2));
+        assert_eq!(verify_less_than(&c1, &c2, &loaded, &params), Ok(()));
+
+        let (_, payload) = open_container(&bytes, FileKind::ComparisonProof).unwrap();
+        let rest = std::str::from_utf8(payload).unwrap().split_once('\n').unwrap().1;
+        for bits in ["0".to_string(), (MAX_COMPARISON_BITS + 1).to_string()] {
+            let forged = container_bytes(FileKind::ComparisonProof, FILE_FORMAT_VERSION, &[format!("{}\n{}", bits, rest)]).unwrap();
+            match read_comparison_proof(&mut &forged[..], &ReadLimits::default()) {
+                Err(ProofLoadError::Invalid(e)) => assert!(e.contains("invalid bit width"), "{}", e),
+                other => panic!("bits {}: {:?}", bits, other.map(|p| p.bits)),
+            }
+        }
+        let e = load_proof(path).err().unwrap();
+        assert!(e.to_string().contains("expected a proof file, found a comparison proof file"), "{}", e);
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure generator vectors round-trip with their seed and untrusted counts are refused
+    // Params: insecure 512-bit params, 5 generators under "agg/g"
+    // Output: the same vector and seed back; a count over MAX_GENERATORS and a count other than the
+    //         generator lines rejected as limits; a params file loaded as generators named as such
+    // Usage: `cargo test -- src::util` or `cargo test`
+    #[test]
+    fn generator_vectors_roundtrip() {
+        let path = std::env::temp_dir().join(format!("cuproof_generators_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        let params = crate::setup::insecure_test_setup(512);
+        let vector = GeneratorVector::derive(&params, "agg/g", 5);
+        save_generators(path, &vector).unwrap();
+        let loaded = load_generators(path).unwrap();
+        assert_eq!(loaded, vector);
+        assert_eq!(loaded.seed(), vector.seed());
+        assert_eq!(loaded.check(&params), Ok(()));
+
+        let bytes = fs::read(path).unwrap();
+        let (_, payload) = open_container(&bytes, FileKind::Generators).unwrap();
+        let mut lines: Vec<String> = std::str::from_utf8(payload).unwrap().lines().map(str::to_string).collect();
+        for (count, expected) in [
+            (MAX_GENERATORS + 1, LimitError::TooManyGenerators(MAX_GENERATORS + 1)),
+            (6, LimitError::CountExceedsInput { declared: 6, available: 5 }),
+        ] {
+            lines[2] = count.to_string();
+            let forged = container_bytes(FileKind::Generators, FILE_FORMAT_VERSION, &lines).unwrap();
+            let e = read_generators(&mut &forged[..], &ReadLimits::default()).err().unwrap();
+            assert_eq!(limit_error(&e), Some(expected));
+        }
+
+        save_params(path, &params).unwrap();
+        let e = load_generators(path).err().unwrap();
+        assert!(e.to_string().contains("expected a generators file, found a params file"), "{}", e);
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure an encrypted blinding file round-trips, and that a wrong passphrase and an
+    //          altered file are told apart once the io::Error becomes a cuproof::Error
+    // Params: a blinding file's bytes saved under "correct horse" with small Argon2id settings;
+    //         the passphrase "battery staple"; the file with its last byte flipped
+    // Output: the blinding back; serialization.wrong_passphrase and serialization.tampered
+    // Usage: `cargo test --features encryption -- src::util`
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_files_roundtrip() {
+        use crate::encrypted::KdfParams;
+        let path = std::env::temp_dir().join(format!("cuproof_encrypted_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut plaintext = Vec::new();
+        write_blinding(&mut plaintext, &BigInt::from(0x1234_5678u64)).unwrap();
+        save_encrypted_with(path, &plaintext, b"correct horse", &KdfParams { memory_kib: 64, passes: 1, lanes: 1 }).unwrap();
+        assert!(crate::encrypted::is_encrypted(&fs::read(path).unwrap()));
+        let loaded = load_encrypted(path, b"correct horse").unwrap();
+        assert_eq!(read_blinding(&mut loaded.expose().as_slice(), &ReadLimits::default()).unwrap(), BigInt::from(0x1234_5678u64));
+
+        let e = crate::Error::from(load_encrypted(path, b"battery staple").err().unwrap());
+        assert_eq!(e.code(), "serialization.wrong_passphrase");
+        let mut bytes = fs::read(path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(path, &bytes).unwrap();
+        let e = crate::Error::from(load_encrypted(path, b"correct horse").err().unwrap());
+        assert_eq!(e.code(), "serialization.tampered");
+        let _ = fs::remove_file(path);
+    }
+
+    // Purpose: ensure compressed params and proofs load like plain ones, plain and legacy files
+    //          still load, and a compressed payload declaring too much is refused before inflating
+    // Params: fast 512-bit params and the proof of testdata/proof_legacy.txt, saved with and without
+    //         with_compression; the legacy text proof; the compressed proof re-declaring
+    //         MAX_DECOMPRESSED_BYTES + 1 and one byte less than it holds; a compressed-flagged
+    //         blinding; level 99
+    // Output: equal values from every file, the compressed ones smaller; LimitError::DecompressedTooLarge
+    //         (serialization.limit), BadCompression, UnsupportedVersion and InvalidInput
+    // Usage: `cargo test --features compression -- src::util`
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_files_roundtrip() {
+        let path = std::env::temp_dir().join(format!("cuproof_compressed_{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        let compressed = SaveOptions::default().with_compression(DEFAULT_COMPRESSION_LEVEL);
+        let params = crate::setup::insecure_test_setup(512);
+        save_params_with(path, &params, &compressed).unwrap();
+        let bytes = fs::read(path).unwrap();
+        assert!(is_compressed(&bytes) && bytes.len() < encode_params(&params).unwrap().len());
+        assert_eq!(container_version(&bytes, FileKind::Params), Some(FILE_FORMAT_VERSION));
+        assert_eq!(load_params(path).unwrap(), params);
+
+        let legacy_proof = include_bytes!("../testdata/proof_legacy.txt");
+        let proof = migrate_proof(legacy_proof).unwrap();
+        save_proof_with(path, &proof, &compressed).unwrap();
+        let bytes = fs::read(path).unwrap();
+        assert!(is_compressed(&bytes) && bytes.len() < encode_proof(&proof).unwrap().len());
+        assert!(load_proof(path).unwrap() == proof);
+        save_proof(path, &proof).unwrap();
+        assert!(!is_compressed(&fs::read(path).unwrap()));
+        assert!(load_proof(path).unwrap() == proof);
+        fs::write(path, legacy_proof).unwrap();
+        assert!(load_proof(path).unwrap() == proof);
+
+        // the declared length is rewritten and the checksum recomputed, so only the length is wrong
+        let redeclared = |declared: usize| {
+            let mut file = bytes.clone();
+            file[CONTAINER_HEADER_LEN..CONTAINER_HEADER_LEN + 4].copy_from_slice(&(declared as u32).to_be_bytes());
+            let end = file.len() - CONTAINER_TRAILER_LEN;
+            let checksum = container_checksum(&file[..end]);
+            file[end..].copy_from_slice(&checksum);
+            file
+        };
+        let e = read_proof(&mut redeclared(MAX_DECOMPRESSED_BYTES + 1).as_slice(), &ReadLimits::default()).err().unwrap();
+        assert!(matches!(e, ProofLoadError::Limit(LimitError::DecompressedTooLarge { declared, .. }) if declared == MAX_DECOMPRESSED_BYTES + 1), "{:?}", e);
+        assert_eq!(crate::Error::from(io::Error::from(e)).code(), "serialization.limit");
+        let declared = u32::from_be_bytes(bytes[CONTAINER_HEADER_LEN..CONTAINER_HEADER_LEN + 4].try_into().unwrap()) as usize;
+        let e = read_proof(&mut redeclared(declared - 1).as_slice(), &ReadLimits::default()).err().unwrap();
+        assert!(matches!(e, ProofLoadError::Format(FormatError::BadCompression(_))), "{:?}", e);
+
+        let mut blinding = Vec::new();
+        write_blinding(&mut blinding, &BigInt::from(7)).unwrap();
+        blinding[8] |= (COMPRESSED_FLAG >> 8) as u8;
+        assert_eq!(open_container(&blinding, FileKind::Blinding), Err(FormatError::UnsupportedVersion(FILE_FORMAT_VERSION | COMPRESSED_FLAG)));
+        assert_eq!(compress_container(&encode_proof(&proof).unwrap(), 99).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(compress_container(&blinding, 3).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        let _ = fs::remove_file(path);
     }
 }
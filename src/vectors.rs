@@ -0,0 +1,285 @@
+//! Known-answer vectors for implementations outside this crate (tests/vectors/*.json)
+//!
+//! Every vector fixes what a prover would draw: the seeded 512-bit test params
+//! (setup::insecure_test_setup), the witness, and a ChaCha20 seed from which the blinding r and
+//! then the prover's randomness are drawn. It lists the params and their wire form, each
+//! commitment, every Fiat-Shamir challenge, every proof field, the statements and the encodings
+//! of the proof. Integers are the lowercase hex of their big-endian magnitude
+//! (util::bigint_to_hex), with a leading `-` where a sign is possible (range bounds); byte
+//! strings are lowercase hex.
+//!
+//! `cargo run --features vectors --bin cuproof-vectors` writes the files and tests/vectors/main.rs
+//! re-derives them. A change to the transcript, to how the prover draws its randomness or to an
+//! encoding changes the vectors, and must come with a VECTORS_VERSION bump: the generator
+//! refuses to rewrite a file of the current version with other contents.
+//!
+//! The verifier accepts 64-dimension inner-product arguments only, so the 32- and 64-bit cases
+//! differ in the width of [a, b], not in the number of rounds.
+
+use std::fmt::Write as _;
+use num_bigint::BigInt;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use crate::aggregate::{aggregate_prove_with_rng, aggregated_proof_challenges, AggregateEntry, AggregatedCuproof};
+use crate::range_proof::{prove_with_rng, random_blinding, Cuproof, IPPProof, ProveError, Statement};
+use crate::setup::{insecure_test_setup, Params};
+use crate::util::{bigint_to_hex, bigint_to_signed_hex, write_aggregated_proof, write_proof};
+use crate::verify::{proof_challenges, Challenges};
+use crate::wire::{params_to_wire, proof_to_wire};
+
+/// Version of the vectors; bump it with any change that alters them
+pub const VECTORS_VERSION: u32 = 2;
+
+/// Modulus size of the params every vector is made under
+pub const VECTORS_PARAMS_BITS: usize = 512;
+
+/// Vector dimension of the single proofs, the only one the verifier accepts
+pub const VECTORS_DIMENSION: usize = 64;
+
+/// a <= v <= b for one statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Witness {
+    pub a: u128,
+    pub b: u128,
+    pub v: u128,
+}
+
+/// One vector file: a single proof for one witness, an aggregated proof for several
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorCase {
+    /// file name without `.json`
+    pub name: &'static str,
+    pub description: &'static str,
+    pub witnesses: &'static [Witness],
+    pub prover_seed: u64,
+}
+
+const U32_MAX: u128 = u32::MAX as u128;
+const U64_MAX: u128 = u64::MAX as u128;
+
+/// Every published vector, in file name order
+pub const VECTOR_CASES: &[VectorCase] = &[
+    VectorCase {
+        name: "aggregated_pair",
+        description: "aggregated proof of a 32-bit range from 0 and a 64-bit range from 2^40",
+        witnesses: &[
+            Witness { a: 0, b: U32_MAX, v: 42 },
+            Witness { a: 1 << 40, b: (1 << 40) + U64_MAX, v: 1 << 41 },
+        ],
+        prover_seed: 5,
+    },
+    VectorCase {
+        name: "range32_from_0",
+        description: "single proof of 0 <= v <= 2^32 - 1",
+        witnesses: &[Witness { a: 0, b: U32_MAX, v: 3_000_000_000 }],
+        prover_seed: 1,
+    },
+    VectorCase {
+        name: "range32_offset",
+        description: "single proof of a <= v <= a + 2^32 - 1 for a = 1000000",
+        witnesses: &[Witness { a: 1_000_000, b: 1_000_000 + U32_MAX, v: 1_123_456_789 }],
+        prover_seed: 2,
+    },
+    VectorCase {
+        name: "range64_from_0",
+        description: "single proof of 0 <= v <= 2^64 - 1",
+        witnesses: &[Witness { a: 0, b: U64_MAX, v: (1 << 63) + 12_345 }],
+        prover_seed: 3,
+    },
+    VectorCase {
+        name: "range64_offset",
+        description: "single proof of a <= v <= a + 2^64 - 1 for a = 2^40",
+        witnesses: &[Witness { a: 1 << 40, b: (1 << 40) + U64_MAX, v: 1 << 64 }],
+        prover_seed: 4,
+    },
+];
+
+/// The params every vector is made under
+pub fn vectors_params() -> Params {
+    insecure_test_setup(VECTORS_PARAMS_BITS)
+}
+
+/// The JSON text of `case`, exactly as tests/vectors/<name>.json holds it
+/// - returns: the pretty-printed document with a trailing newline, or the prover's refusal
+/// - usage: the cuproof-vectors generator writes it, tests/vectors/main.rs compares it
+pub fn render_vector(case: &VectorCase, params: &Params) -> Result<String, ProveError> {
+    let mut rng = ChaCha20Rng::seed_from_u64(case.prover_seed);
+    let blindings: Vec<BigInt> = case.witnesses.iter().map(|_| random_blinding(&mut rng)).collect();
+    let witness: Vec<Json> = case.witnesses.iter().zip(&blindings).map(|(w, r)| {
+        Json::Object(vec![
+            ("v", int(&BigInt::from(w.v))),
+            ("r", int(r)),
+            ("a", signed(&BigInt::from(w.a))),
+            ("b", signed(&BigInt::from(w.b))),
+        ])
+    }).collect();
+    let (kind, proof, statements, challenges, encodings) = match case.witnesses {
+        [w] => {
+            let (a, b, v) = (BigInt::from(w.a), BigInt::from(w.b), BigInt::from(w.v));
+            let proof = prove_with_rng(&v, &blindings[0], &a, &b, params, VECTORS_DIMENSION, &mut rng)?;
+            let mut container = Vec::new();
+            write_proof(&mut container, &proof).expect("writing to a Vec cannot fail");
+            let wire = proof_to_wire(&proof).expect("proofs of the test params fit the wire format");
+            let encodings = vec![("container", bytes(&container)), ("wire", bytes(&wire))];
+            ("single", single_proof(&proof), proof.statement.iter().map(statement).collect(), proof_challenges(&proof, params), encodings)
+        }
+        _ => {
+            let entries: Vec<AggregateEntry> = case.witnesses.iter().zip(&blindings)
+                .map(|(w, r)| AggregateEntry { v: BigInt::from(w.v), r: r.clone(), a: BigInt::from(w.a), b: BigInt::from(w.b) })
+                .collect();
+            let proof = aggregate_prove_with_rng(&entries, params, &mut rng)?;
+            let mut container = Vec::new();
+            write_aggregated_proof(&mut container, &proof).expect("writing to a Vec cannot fail");
+            let challenges = aggregated_proof_challenges(&proof, params);
+            ("aggregated", aggregated_proof(&proof), proof.statements.iter().map(statement).collect(), challenges, vec![("container", bytes(&container))])
+        }
+    };
+    let document = Json::Object(vec![
+        ("version", Json::Number(VECTORS_VERSION.into())),
+        ("name", Json::String(case.name.into())),
+        ("description", Json::String(case.description.into())),
+        ("kind", Json::String(kind.into())),
+        ("params", Json::Object(vec![
+            ("bits", Json::Number(params.bits() as u64)),
+            ("epoch", Json::Number(params.epoch())),
+            ("n", int(params.n())),
+            ("g", int(params.g())),
+            ("h", int(params.h())),
+            ("fingerprint", Json::String(params.fingerprint_hex().to_string())),
+            ("wire", bytes(&params_to_wire(params).expect("the test params fit the wire format"))),
+        ])),
+        ("prover_seed", Json::Number(case.prover_seed)),
+        ("witness", Json::List(witness)),
+        ("proof", proof),
+        ("statements", Json::List(statements)),
+        ("challenges", Json::Object(challenge_fields(&challenges))),
+        ("encodings", Json::Object(encodings)),
+    ]);
+    let mut out = String::new();
+    document.write(&mut out, 0);
+    out.push('\n');
+    Ok(out)
+}
+
+/// The `version` a vector file records, None when it has none
+/// - usage: the generator, to refuse rewriting a current-version file with other contents
+pub fn vector_file_version(text: &str) -> Option<u32> {
+    let rest = &text[text.find("\"version\":")? + "\"version\":".len()..];
+    let digits: String = rest.trim_start().chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+#[allow(non_snake_case)]
+fn single_proof(proof: &Cuproof) -> Json {
+    let Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof, epoch, .. } = proof;
+    let mut fields = vec![("C", int(C)), ("C_v1", int(C_v1)), ("C_v2", int(C_v2))];
+    fields.extend(polynomial_fields([A, S, T1, T2, tau_x, mu, t_hat, t0, t1, t2, tau1, tau2], ipp_proof, *epoch));
+    Json::Object(fields)
+}
+
+fn aggregated_proof(proof: &AggregatedCuproof) -> Json {
+    let commitments = proof.commitments.iter()
+        .map(|c| Json::Object(vec![("C", int(&c.C)), ("C_v1", int(&c.C_v1)), ("C_v2", int(&c.C_v2))]))
+        .collect();
+    let mut fields = vec![("commitments", Json::List(commitments))];
+    let p = proof;
+    fields.extend(polynomial_fields([&p.A, &p.S, &p.T1, &p.T2, &p.tau_x, &p.mu, &p.t_hat, &p.t0, &p.t1, &p.t2, &p.tau1, &p.tau2], &p.ipp_proof, p.epoch));
+    Json::Object(fields)
+}
+
+/// The fields single and aggregated proofs share, in encoding order
+fn polynomial_fields(values: [&BigInt; 12], ipp: &IPPProof, epoch: u64) -> Vec<(&'static str, Json)> {
+    const NAMES: [&str; 12] = ["A", "S", "T1", "T2", "tau_x", "mu", "t_hat", "t0", "t1", "t2", "tau1", "tau2"];
+    let mut fields: Vec<_> = NAMES.into_iter().zip(values).map(|(name, x)| (name, int(x))).collect();
+    fields.push(("ipp", Json::Object(vec![
+        ("L", Json::List(ipp.L.iter().map(int).collect())),
+        ("R", Json::List(ipp.R.iter().map(int).collect())),
+        ("a", int(&ipp.a)),
+        ("b", int(&ipp.b)),
+    ])));
+    fields.push(("epoch", Json::Number(epoch)));
+    fields
+}
+
+fn statement(statement: &Statement) -> Json {
+    Json::Object(vec![
+        ("commitment", int(&statement.commitment)),
+        ("a", signed(&statement.a)),
+        ("b", signed(&statement.b)),
+        ("bits", Json::Number(statement.bits as u64)),
+        ("label", Json::String(statement.label.clone())),
+        ("params_fingerprint", bytes(&statement.params_fingerprint)),
+    ])
+}
+
+fn challenge_fields(challenges: &Challenges) -> Vec<(&'static str, Json)> {
+    vec![
+        ("y", int(&challenges.y)),
+        ("z", int(&challenges.z)),
+        ("x", int(&challenges.x)),
+        ("ipp", Json::List(challenges.ipp.iter().map(int).collect())),
+    ]
+}
+
+fn int(x: &BigInt) -> Json {
+    Json::String(bigint_to_hex(x))
+}
+
+fn signed(x: &BigInt) -> Json {
+    Json::String(bigint_to_signed_hex(x))
+}
+
+fn bytes(b: &[u8]) -> Json {
+    Json::String(hex::encode(b))
+}
+
+/// The JSON the vectors are made of, written with two-space indentation and keys in the order given
+enum Json {
+    String(String),
+    Number(u64),
+    List(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn write(&self, out: &mut String, indent: usize) {
+        let pad = |out: &mut String, depth: usize| out.extend(core::iter::repeat_n(' ', 2 * depth));
+        match self {
+            Json::String(s) => {
+                out.push('"');
+                for c in s.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        c if u32::from(c) < 0x20 => { let _ = write!(out, "\\u{:04x}", u32::from(c)); }
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+            Json::Number(n) => { let _ = write!(out, "{}", n); }
+            Json::List(items) if items.is_empty() => out.push_str("[]"),
+            Json::List(items) => {
+                out.push_str("[\n");
+                for (i, item) in items.iter().enumerate() {
+                    pad(out, indent + 1);
+                    item.write(out, indent + 1);
+                    out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                pad(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                out.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    pad(out, indent + 1);
+                    let _ = write!(out, "\"{}\": ", key);
+                    value.write(out, indent + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                pad(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
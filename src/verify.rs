@@ -1,60 +1,389 @@
-use crate::{util::*, fiat_shamir::*, commitment::*};
-use crate::range_proof::Cuproof;
+use crate::fiat_shamir::*;
+use crate::group::Group;
+use crate::range_proof::{Cuproof, Statement};
+use crate::scalar::{Scalar, ScalarBound};
+#[cfg(feature = "tracing")]
+use crate::range_proof::proof_id;
+use crate::setup::{validate_params, Params, ParamsError, MIN_SECURE_MODULUS_BITS};
+#[cfg(feature = "tracing")]
+use crate::setup::FingerprintHex;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
 use num_bigint::BigInt;
+use crate::timing::{phase, Phase};
+use crate::trace::traced;
 
-pub fn cuproof_verify(proof: &Cuproof, g: &BigInt, h: &BigInt, n: &BigInt) -> bool {
+/// Check a proof against params
+/// Every input is public (proof fields and params), so the comparisons below use `==` and are
+/// intentionally variable-time; util::ct_eq_bytes / ct_eq_bigint are for secret-derived values
+pub fn verify<G: Group>(proof: &Cuproof, params: &G) -> bool {
+	cuproof_verify_detailed(proof, params).is_ok()
+}
+
+/// Former name of verify, kept for one release
+#[deprecated(note = "renamed to `verify`")]
+pub fn cuproof_verify<G: Group>(proof: &Cuproof, params: &G) -> bool {
+	verify(proof, params)
+}
+
+/// The check of verify that rejected a proof, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerifyCheck {
+	/// the proof's generator epoch is not the params'
+	Epoch,
+	/// the embedded statement disagrees with the proof or params in the named field
+	Statement(&'static str),
+	/// a Fiat-Shamir challenge is zero
+	ZeroChallenge,
+	/// T1 is not commit(t1, tau1)
+	T1Commitment,
+	/// T2 is not commit(t2, tau2)
+	T2Commitment,
+	/// t_hat is not t0 + t1 x + t2 x^2
+	PolynomialEvaluation,
+	/// Commit(t_hat, tau_x) is not Commit(t0 + t1 x + t2 x^2, tau_x)
+	THatCommitment,
+	/// the inner-product argument does not have 6 rounds in both L and R
+	IppRounds,
+	/// the named scalar is negative or not below the scalar::ScalarBound of the proof
+	ScalarBound(&'static str),
+	/// the named commitment is not a group element
+	GroupElement(&'static str),
+	/// two of C, C_v1 and C_v2 are equal
+	DistinctCommitments,
+	/// the caller cancelled verification (verify_interruptible) before the remaining checks ran
+	Cancelled,
+}
+
+impl VerifyCheck {
+	/// Whether the check ties the rejected field to the rest of the proof; the others (zero
+	/// challenges, round count, scalar bound, group membership, distinct commitments) only catch malformed
+	/// values and say nothing about a well-formed substitute
+	pub fn is_binding(&self) -> bool {
+		matches!(
+			self,
+			VerifyCheck::Epoch | VerifyCheck::Statement(_) | VerifyCheck::T1Commitment | VerifyCheck::T2Commitment | VerifyCheck::PolynomialEvaluation | VerifyCheck::THatCommitment
+		)
+	}
+}
+
+impl fmt::Display for VerifyCheck {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			VerifyCheck::Epoch => write!(f, "epoch"),
+			VerifyCheck::Statement(field) => write!(f, "statement {}", field),
+			VerifyCheck::ZeroChallenge => write!(f, "zero challenge"),
+			VerifyCheck::T1Commitment => write!(f, "T1 commitment"),
+			VerifyCheck::T2Commitment => write!(f, "T2 commitment"),
+			VerifyCheck::PolynomialEvaluation => write!(f, "t_hat evaluation"),
+			VerifyCheck::THatCommitment => write!(f, "t_hat commitment"),
+			VerifyCheck::IppRounds => write!(f, "inner-product rounds"),
+			VerifyCheck::ScalarBound(field) => write!(f, "{} scalar bound", field),
+			VerifyCheck::GroupElement(field) => write!(f, "{} group element", field),
+			VerifyCheck::DistinctCommitments => write!(f, "distinct commitments"),
+			VerifyCheck::Cancelled => write!(f, "cancelled"),
+		}
+	}
+}
+
+/// verify naming the first check a rejected proof fails
+/// - returns: Ok(()) exactly when verify returns true, otherwise the VerifyCheck that failed
+/// - usage: the tamper corpus (testing::tamper_mutants), to see which check catches each mutation
+pub fn cuproof_verify_detailed<G: Group>(proof: &Cuproof, params: &G) -> Result<(), VerifyCheck> {
+	verify_traced(proof, params, &mut FiatShamir::new(), &|| false)
+}
+
+/// cuproof_verify_detailed with T1 and T2 checked through the proof's poe::VerifierAssist: one
+/// simultaneous exponentiation by exponents of poe::PRIME_BITS bits instead of two commitments
+/// - returns: Ok(()) when the other checks pass and the assist holds for T1 and T2 as the proof
+///   holds them. A proof without an assist, or whose assist does not hold, gets verify's own
+///   commitment checks, so a rejection names the check verify names
+/// - usage: verifiers that see many proofs, whose provers add `with_verifier_assist`
+pub fn cuproof_verify_fast(proof: &Cuproof, params: &Params) -> Result<(), VerifyCheck> {
+	let Some(assist) = &proof.assist else { return cuproof_verify_detailed(proof, params) };
+	verify_except_commitments(proof, params)?;
+	phase(Phase::Commitments, || {
+		if assist.check(proof, params) { return Ok(()); }
+		if params.commit(&proof.t1, &proof.tau1) != proof.T1 { return Err(VerifyCheck::T1Commitment); }
+		if params.commit(&proof.t2, &proof.tau2) != proof.T2 { return Err(VerifyCheck::T2Commitment); }
+		Ok(())
+	})
+}
+
+/// cuproof_verify_checked asking `cancelled` before each expensive step
+/// - returns: Err(Cancelled) as soon as `cancelled` returns true, otherwise as cuproof_verify_checked
+/// - usage: asynchronous::AsyncVerifier::verify_cancellable, which asks its CancellationToken
+#[cfg(any(feature = "async", test))]
+pub(crate) fn verify_interruptible<G: Group>(proof: &Cuproof, params: &G, cancelled: &dyn Fn() -> bool) -> Result<(), VerifyError> {
+	verify_traced(proof, params, &mut FiatShamir::new(), cancelled).map_err(|check| verify_error(check, proof, params))
+}
+
+/// The checks of verify_unlogged in the verify span
+fn verify_traced<G: Group>(proof: &Cuproof, params: &G, transcript: &mut dyn Transcript, cancelled: &dyn Fn() -> bool) -> Result<(), VerifyCheck> {
+	traced!(
+		tracing::debug_span!("verify", bits = proof_bits(proof), params = %FingerprintHex(params.fingerprint())),
+		verify_unlogged(proof, params, transcript, cancelled, true),
+		|result, elapsed_ms| match result {
+			Ok(()) => tracing::debug!(proof_id = %proof_id(proof), elapsed_ms, "proof verified"),
+			Err(check) => tracing::debug!(proof_id = %proof_id(proof), check = %check, elapsed_ms, "proof rejected"),
+		}
+	)
+}
+
+/// Every check of verify but T1Commitment and T2Commitment, the two exponentiations
+/// - usage: batch::BatchAttestation, which checks the commitments of many proofs in one combined
+///   equation, and cuproof_verify_fast, which checks them through a VerifierAssist
+pub(crate) fn verify_except_commitments<G: Group>(proof: &Cuproof, params: &G) -> Result<(), VerifyCheck> {
+	verify_unlogged(proof, params, &mut FiatShamir::new(), &|| false, false)
+}
+
+/// The checks of cuproof_verify_detailed, outside its span, with the challenges drawn from
+/// `transcript`; `cancelled` is asked between them. T1 and T2 are checked only with `commitments`
+fn verify_unlogged<G: Group>(proof: &Cuproof, params: &G, transcript: &mut dyn Transcript, cancelled: &dyn Fn() -> bool, commitments: bool) -> Result<(), VerifyCheck> {
+	// 0. The proof must have been made under the generators these params carry
+	if proof.epoch != params.epoch() { return Err(VerifyCheck::Epoch); }
+	// An embedded statement must describe this proof under these params
+	if let Err(VerifyError::StatementMismatch(field)) = check_statement(proof, params) { return Err(VerifyCheck::Statement(field)); }
+	// Every scalar must lie in [0, B) before any equation uses it (see scalar)
+	let bound = proof_scalar_bound(proof, params);
+	if let Err(e) = bound.check(proof.scalars()) { return Err(VerifyCheck::ScalarBound(e.field())); }
 	// 1. Fiat–Shamir
-	let y = fiat_shamir(&[&proof.A, &proof.S, &proof.C, &proof.C_v1, &proof.C_v2]) % n;
-	if y == BigInt::from(0) { return false; }
-	let z = fiat_shamir(&[&y]) % n;
-	if z == BigInt::from(0) { return false; }
-	let x = fiat_shamir(&[&proof.T1, &proof.T2]) % n;
-	if x == BigInt::from(0) { return false; }
+	if cancelled() { return Err(VerifyCheck::Cancelled); }
+	let (y, z, x) = phase(Phase::Challenges, || range_challenges(proof, params.challenge_modulus(), transcript));
+	if y == BigInt::from(0) || z == BigInt::from(0) || x == BigInt::from(0) { return Err(VerifyCheck::ZeroChallenge); }
 
 	// 2. Check T1, T2 commitments
-	if pedersen_commit(g, h, &proof.t1, &proof.tau1, n) != proof.T1 { return false; }
-	if pedersen_commit(g, h, &proof.t2, &proof.tau2, n) != proof.T2 { return false; }
+	if commitments {
+		if cancelled() { return Err(VerifyCheck::Cancelled); }
+		if phase(Phase::Commitments, || params.commit(&proof.t1, &proof.tau1)) != proof.T1 { return Err(VerifyCheck::T1Commitment); }
+		if cancelled() { return Err(VerifyCheck::Cancelled); }
+		if phase(Phase::Commitments, || params.commit(&proof.t2, &proof.tau2)) != proof.T2 { return Err(VerifyCheck::T2Commitment); }
+	}
 
-	// 3. Verify t_hat consistency: t_hat ?= t0 + t1 x + t2 x^2
-	let rhs_t = &proof.t0 + &(&proof.t1 * &x) + &(&proof.t2 * &x * &x);
-	if proof.t_hat != rhs_t { return false; }
+	// 3. Verify t_hat consistency: t_hat ?= t0 + t1 x + t2 x^2, without leaving [0, B)
+	let scalar = |field, value| bound.scalar(field, value).map_err(|e| VerifyCheck::ScalarBound(e.field()));
+	let t = [scalar("t0", &proof.t0)?, scalar("t1", &proof.t1)?, scalar("t2", &proof.t2)?];
+	let rhs_t = Scalar::checked_poly_eval(&t, &scalar("x", &x)?);
+	if rhs_t.as_ref().map(Scalar::value) != Some(&proof.t_hat) { return Err(VerifyCheck::PolynomialEvaluation); }
 
 	// 4. Verify commitment consistency for t_hat
-	let lhs = pedersen_commit(g, h, &proof.t_hat, &proof.tau_x, n);
-	// Construct a commitment to rhs_t using tau_x (already provided)
-	let rhs = pedersen_commit(g, h, &rhs_t, &proof.tau_x, n);
-	if lhs != rhs { return false; }
+	if cancelled() { return Err(VerifyCheck::Cancelled); }
+	let rhs_t = rhs_t.ok_or(VerifyCheck::PolynomialEvaluation)?;
+	let lhs = phase(Phase::Commitments, || params.commit(&proof.t_hat, &proof.tau_x));
+	let rhs = phase(Phase::Commitments, || params.commit(rhs_t.value(), &proof.tau_x));
+	if lhs != rhs { return Err(VerifyCheck::THatCommitment); }
 
 	// 5. Verify IPP proof (simplified verification)
 	// In a full implementation, this would verify the recursive structure
-	if proof.ipp_proof.L.len() != proof.ipp_proof.R.len() { return false; }
-	
-	// Check that we have the expected number of recursion levels
-	// For dimension 64, we expect log2(64) = 6 levels
-	let expected_levels = (64.0_f64).log2().ceil() as usize;
-	if proof.ipp_proof.L.len() != expected_levels { return false; }
-
-	// 6. Basic sanity: commitments must be within modulus and non-zero
-	if &proof.A % n == BigInt::from(0) { return false; }
-	if &proof.S % n == BigInt::from(0) { return false; }
-	if &proof.T1 % n == BigInt::from(0) { return false; }
-	if &proof.T2 % n == BigInt::from(0) { return false; }
-	if &proof.C % n == BigInt::from(0) { return false; }
-	if &proof.C_v1 % n == BigInt::from(0) { return false; }
-	if &proof.C_v2 % n == BigInt::from(0) { return false; }
+	let ipp_shape = phase(Phase::Ipp, || {
+		// Check that we have the expected number of recursion levels
+		// For dimension 64, we expect log2(64) = 6 levels
+		let expected_levels = PROOF_ROUNDS as usize;
+		proof.ipp_proof.L.len() == proof.ipp_proof.R.len() && proof.ipp_proof.L.len() == expected_levels
+	});
+	if !ipp_shape { return Err(VerifyCheck::IppRounds); }
+
+	// 6. Basic sanity: commitments must encode group elements (RSA: non-zero modulo n)
+	if cancelled() { return Err(VerifyCheck::Cancelled); }
+	let elements = [("A", &proof.A), ("S", &proof.S), ("T1", &proof.T1), ("T2", &proof.T2), ("C", &proof.C), ("C_v1", &proof.C_v1), ("C_v2", &proof.C_v2)];
+	if let Some((field, _)) = elements.iter().find(|(_, x)| !params.is_element(x)) { return Err(VerifyCheck::GroupElement(field)); }
 
 	// 7. Verify that C_v1 and C_v2 are consistent with C in a coarse way
 	// Note: In a rigorous design, we would prove relations for v1, v2.
 	// Here we at least ensure they are not trivially equal or zero modulo n.
-	if &proof.C == &proof.C_v1 { return false; }
-	if &proof.C == &proof.C_v2 { return false; }
-	if &proof.C_v1 == &proof.C_v2 { return false; }
+	if proof.C == proof.C_v1 || proof.C == proof.C_v2 || proof.C_v1 == proof.C_v2 { return Err(VerifyCheck::DistinctCommitments); }
+
+	Ok(())
+}
+
+/// Inner-product rounds of the proofs verify accepts: log2 of the dimension 64 prove uses
+const PROOF_ROUNDS: u32 = 64u32.ilog2();
+
+/// The scalar bound of a proof verify accepts: for the range of its statement, or for ranges as
+/// wide as the challenge modulus when it has none. The rounds are PROOF_ROUNDS whatever the proof
+/// holds, so a proof with more cannot widen its own bound
+fn proof_scalar_bound<G: Group>(proof: &Cuproof, params: &G) -> ScalarBound {
+	match &proof.statement {
+		Some(statement) => ScalarBound::for_range(params, &statement.a, &statement.b, PROOF_ROUNDS),
+		None => {
+			let bits = params.challenge_modulus().bits();
+			ScalarBound::new(bits, bits, PROOF_ROUNDS)
+		}
+	}
+}
+
+/// The Fiat-Shamir challenges of a proof, reduced modulo Group::challenge_modulus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenges {
+	pub y: BigInt,
+	pub z: BigInt,
+	pub x: BigInt,
+	/// one per inner-product round, hashed from L[i] and R[i], in the order of the proof's L and R
+	pub ipp: Vec<BigInt>,
+}
 
-	true
+/// Recompute the challenges of `proof` as verify does, whether or not it verifies
+/// - usage: `cuproof dump --params`, to compare with another implementation's transcript
+pub fn proof_challenges<G: Group>(proof: &Cuproof, params: &G) -> Challenges {
+	let n = params.challenge_modulus();
+	let (y, z, x) = range_challenges(proof, n, &mut FiatShamir::new());
+	let ipp = proof.ipp_proof.L.iter().zip(&proof.ipp_proof.R).map(|(l, r)| fiat_shamir(&[l, r]) % n).collect();
+	Challenges { y, z, x, ipp }
 }
 
-pub fn cuproof_verify_with_range(proof: &Cuproof, g: &BigInt, h: &BigInt, n: &BigInt, a: &BigInt, b: &BigInt) -> bool {
-    if !cuproof_verify(proof, g, h, n) { return false; }
+/// y, z and x; verify needs no more, so it does not hash the IPP rounds
+fn range_challenges(proof: &Cuproof, n: &BigInt, transcript: &mut dyn Transcript) -> (BigInt, BigInt, BigInt) {
+	let (y, z) = transcript.first_round([&proof.A, &proof.S, &proof.C, &proof.C_v1, &proof.C_v2], n);
+	let x = transcript.polynomial(&proof.T1, &proof.T2, n);
+	(y, z, x)
+}
+
+/// Why cuproof_verify_checked rejected a proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+	/// The proof was made under another generation of g, h (see Params::rotate_generators)
+	EpochMismatch { proof: u64, params: u64 },
+	/// verify returned false
+	InvalidProof,
+	/// verify_statement was given a proof without an embedded statement
+	MissingStatement,
+	/// verification was cancelled before it reached a verdict (asynchronous::CancellationToken)
+	Cancelled,
+	/// the embedded statement disagrees with the proof, the params or the caller's expectation in the named field
+	StatementMismatch(&'static str),
+	/// statement `index` of an aggregated proof disagrees with its commitments, the params or the caller's expectation in the named field
+	AggregateStatementMismatch { index: usize, field: &'static str },
+}
+
+impl fmt::Display for VerifyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			VerifyError::EpochMismatch { proof, params } => write!(
+				f, "proof was made under generator epoch {} but params are at epoch {}; verify with the matching params", proof, params
+			),
+			VerifyError::InvalidProof => write!(f, "proof does not verify"),
+			VerifyError::MissingStatement => write!(f, "proof carries no statement"),
+			VerifyError::Cancelled => write!(f, "verification was cancelled"),
+			VerifyError::StatementMismatch(field) => write!(f, "statement {} does not match", field),
+			VerifyError::AggregateStatementMismatch { index, field } => write!(f, "statement {}: {} does not match", index, field),
+		}
+	}
+}
+
+impl core::error::Error for VerifyError {}
+
+/// verify reporting why a proof was rejected
+/// - returns: Ok(()) for a valid proof; EpochMismatch before any other check when the proof and
+///   params disagree on the generator epoch, then StatementMismatch for an embedded statement that
+///   does not describe the proof, InvalidProof otherwise
+/// - usage: the CLI verify command, and callers that keep several generations of params around
+pub fn cuproof_verify_checked<G: Group>(proof: &Cuproof, params: &G) -> Result<(), VerifyError> {
+	cuproof_verify_detailed(proof, params).map_err(|check| verify_error(check, proof, params))
+}
+
+/// cuproof_verify_checked for a proof made by range_proof::prove_with_nonce or prove_with_context
+/// - params: proof, params, the nonce this verifier issued for the session
+/// - returns: Ok(()) only when the proof was made for exactly `nonce` and the label of its embedded
+///   statement; InvalidProof for a proof made under another nonce or none (unless `nonce` is
+///   empty), or whose label was changed after proving; the other errors as cuproof_verify_checked
+/// - usage: issue a fresh nonce per session, so an old proof of the same commitment cannot be replayed
+pub fn verify_with_nonce<G: Group>(proof: &Cuproof, params: &G, nonce: &[u8]) -> Result<(), VerifyError> {
+	let label = proof.statement.as_ref().map_or("", |statement| statement.label.as_str());
+	verify_traced(proof, params, &mut FiatShamir::with_context(label, nonce), &|| false).map_err(|check| verify_error(check, proof, params))
+}
+
+/// cuproof_verify_detailed drawing y, z and x from `transcript` instead of FiatShamir
+/// - returns: Ok(()) for a proof range_proof::prove_with_transcript made under the same challenges,
+///   otherwise the VerifyCheck that failed
+/// - usage: protocol unit tests with testing::MockTranscript
+pub fn verify_with_transcript<G: Group>(proof: &Cuproof, params: &G, transcript: &mut dyn Transcript) -> Result<(), VerifyCheck> {
+	verify_traced(proof, params, transcript, &|| false)
+}
+
+fn verify_error<G: Group>(check: VerifyCheck, proof: &Cuproof, params: &G) -> VerifyError {
+	match check {
+		VerifyCheck::Epoch => VerifyError::EpochMismatch { proof: proof.epoch, params: params.epoch() },
+		VerifyCheck::Statement(field) => VerifyError::StatementMismatch(field),
+		VerifyCheck::Cancelled => VerifyError::Cancelled,
+		_ => VerifyError::InvalidProof,
+	}
+}
+
+/// The bit width a proof's inner-product argument covers: 2^rounds, 0 past u64
+#[cfg(feature = "tracing")]
+fn proof_bits(proof: &Cuproof) -> u64 {
+	u32::try_from(proof.ipp_proof.L.len()).ok().and_then(|rounds| 1u64.checked_shl(rounds)).unwrap_or(0)
+}
+
+/// Cross-check the statement embedded in `proof`, if any, against the proof and `params`
+/// - returns: Ok(()) for proofs without a statement; StatementMismatch naming the first field
+///   that disagrees: the commitment (hashed into the transcript as C), the params fingerprint,
+///   the bit width (2^rounds of the inner-product argument) or a range no verifier accepts
+fn check_statement<G: Group>(proof: &Cuproof, params: &G) -> Result<(), VerifyError> {
+	let Some(statement) = &proof.statement else { return Ok(()) };
+	if statement.commitment != proof.C { return Err(VerifyError::StatementMismatch("commitment")); }
+	if statement.params_fingerprint != params.fingerprint() { return Err(VerifyError::StatementMismatch("params fingerprint")); }
+	let rounds = proof.ipp_proof.L.len();
+	if !statement.bits.is_power_of_two() || statement.bits.trailing_zeros() as usize != rounds {
+		return Err(VerifyError::StatementMismatch("bit width"));
+	}
+	if !range_accepted(&statement.a, &statement.b) { return Err(VerifyError::StatementMismatch("range")); }
+	Ok(())
+}
+
+/// cuproof_verify_checked plus a comparison of the embedded statement with what the caller expects
+/// - params: proof, params, expected statement, e.g. `Statement::new(&c, &a, &b, 64, &params)`
+///   built from the caller's own record of the commitment and range
+/// - returns: Ok(()) when the proof verifies and its statement equals `expected` field by field;
+///   MissingStatement for proofs loaded from files without one, StatementMismatch otherwise
+/// - usage: verifiers that receive proof files from others and must not trust the range written in them
+pub fn verify_statement<G: Group>(proof: &Cuproof, params: &G, expected: &Statement) -> Result<(), VerifyError> {
+	verify_statement_with_nonce(proof, params, expected, &[])
+}
+
+/// verify_statement for a proof made by range_proof::prove_with_context: the challenges are drawn
+/// with the expected statement's label and then `nonce` absorbed, so a proof made under another
+/// label or nonce fails the transcript even when its embedded label was rewritten to the expected one
+/// - returns: as verify_statement, with InvalidProof for another label or nonce; with an empty
+///   nonce the label is compared as verify_statement compares it
+pub fn verify_statement_with_nonce<G: Group>(proof: &Cuproof, params: &G, expected: &Statement, nonce: &[u8]) -> Result<(), VerifyError> {
+	let statement = proof.statement.as_ref().ok_or(VerifyError::MissingStatement)?;
+	verify_traced(proof, params, &mut FiatShamir::with_context(&expected.label, nonce), &|| false).map_err(|check| verify_error(check, proof, params))?;
+	if statement.commitment != expected.commitment { return Err(VerifyError::StatementMismatch("commitment")); }
+	if statement.a != expected.a || statement.b != expected.b { return Err(VerifyError::StatementMismatch("range")); }
+	if statement.bits != expected.bits { return Err(VerifyError::StatementMismatch("bit width")); }
+	if statement.label != expected.label { return Err(VerifyError::StatementMismatch("label")); }
+	if statement.params_fingerprint != expected.params_fingerprint { return Err(VerifyError::StatementMismatch("params fingerprint")); }
+	Ok(())
+}
+
+/// Former name of verify_statement, kept for one release
+#[deprecated(note = "renamed to `verify_statement`")]
+pub fn cuproof_verify_statement<G: Group>(proof: &Cuproof, params: &G, expected: &Statement) -> Result<(), VerifyError> {
+	verify_statement(proof, params, expected)
+}
+
+/// Verify a proof only if the modulus meets a caller-specified minimum size
+/// - params: proof, params, min_bits required bit length of n
+/// - returns: false when n is too small, otherwise the result of verify
+/// - usage: verifiers enforcing a security policy independent of how params were loaded
+pub fn cuproof_verify_with_min_bits(proof: &Cuproof, params: &Params, min_bits: usize) -> bool {
+	if (params.n().bits() as usize) < min_bits { return false; }
+	verify(proof, params)
+}
+
+/// Tuple-based shim kept for one release; the positional (g, h, n) order is easy to swap
+#[deprecated(note = "build a setup::Params and call verify")]
+pub fn cuproof_verify_with_generators(proof: &Cuproof, g: &BigInt, h: &BigInt, n: &BigInt) -> bool {
+	let params = Params::new(g.clone(), h.clone(), n.clone(), n.bits() as usize);
+	verify(proof, &params)
+}
+
+pub fn cuproof_verify_with_range<G: Group>(proof: &Cuproof, params: &G, a: &BigInt, b: &BigInt) -> bool {
+    if !verify(proof, params) { return false; }
+    // A recorded statement must claim exactly the caller's range
+    if proof.statement.as_ref().is_some_and(|statement| (&statement.a, &statement.b) != (a, b)) { return false; }
 
     // Basic range-consistency checks via commitments C_v1 and C_v2
     // Expected: v1 = 4v - 4a + 1, v2 = 4b - 4v + 1
@@ -62,11 +391,16 @@ pub fn cuproof_verify_with_range(proof: &Cuproof, g: &BigInt, h: &BigInt, n: &Bi
     // C_v1 and C_v2 are consistent with some v relative to (a,b) bounds using inequalities:
     // For any v in [a,b], v1 >= 1 and v2 >= 1.
     // So we ensure that C_v1 and C_v2 are non-trivial and distinct from C, already checked above.
+    range_accepted(a, b)
+}
+
+/// Ranges cuproof_verify_with_range accepts at all
+pub(crate) fn range_accepted(a: &BigInt, b: &BigInt) -> bool {
     // Strengthen: ensure a <= b, and they are non-negative (typical demo domain)
     if a > b { return false; }
 
     // Additional conservative checks:
-    // - Ensure T1, T2, tau1, tau2 not zero already done in cuproof_verify
+    // - Ensure T1, T2, tau1, tau2 not zero already done in verify
     // - Ensure commitments are not equal pairwise already done
     // Range-specific simple guard: if a == b then proof should degenerate; reject for now
     if a == b { return false; }
@@ -74,31 +408,558 @@ pub fn cuproof_verify_with_range(proof: &Cuproof, g: &BigInt, h: &BigInt, n: &Bi
     true
 }
 
+/// Verifier bound to a parameter set that passed its constructor's checks
+///
+/// Send + Sync: a server builds one at startup and verifies from every worker thread through a
+/// shared reference or an `Arc<Verifier>`; clones share the params and their tables rather than
+/// copying g, h and n (see examples/shared_verifier.rs)
+#[derive(Clone, Debug)]
+pub struct Verifier {
+	params: Arc<Params>,
+}
+
+impl Verifier {
+	/// Build a verifier over `params` after `Params::insecure_check` and `Params::security_check(MIN_SECURE_MODULUS_BITS)`
+	/// - params: Params, or an `Arc<Params>` already shared with provers or other services
+	/// - returns: Verifier or ParamsError (InsecureParams or ModulusTooSmall for test-sized params)
+	pub fn new<P: Into<Arc<Params>>>(params: P) -> Result<Verifier, ParamsError> {
+		let params = params.into();
+		params.insecure_check()?;
+		params.security_check(MIN_SECURE_MODULUS_BITS)?;
+		Ok(Verifier { params })
+	}
+
+	/// Build a verifier over params of any size that pass validate_params
+	/// - usage: tests and demos over insecure_test_setup (after `allow_insecure_params`) or reduced
+	///   trusted setups only
+	pub fn new_insecure<P: Into<Arc<Params>>>(params: P) -> Result<Verifier, ParamsError> {
+		let params = params.into();
+		params.insecure_check()?;
+		validate_params(&params)?;
+		Ok(Verifier { params })
+	}
+
+	pub fn params(&self) -> &Params { &self.params }
+
+	/// The params, to hand to another Verifier or a RangeProver without copying them
+	pub fn shared_params(&self) -> &Arc<Params> { &self.params }
+
+	/// See verify
+	pub fn verify(&self, proof: &Cuproof) -> bool {
+		verify(proof, self.params())
+	}
+
+	/// See cuproof_verify_checked
+	pub fn verify_checked(&self, proof: &Cuproof) -> Result<(), VerifyError> {
+		cuproof_verify_checked(proof, self.params())
+	}
+
+	/// See cuproof_verify_with_range
+	pub fn verify_with_range(&self, proof: &Cuproof, a: &BigInt, b: &BigInt) -> bool {
+		cuproof_verify_with_range(proof, self.params(), a, b)
+	}
+
+	/// See verify_statement
+	pub fn verify_statement(&self, proof: &Cuproof, expected: &Statement) -> Result<(), VerifyError> {
+		verify_statement(proof, self.params(), expected)
+	}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::setup::fast_test_setup;
-    use crate::range_proof::cuproof_prove;
+    use crate::setup::insecure_test_setup;
+    use crate::range_proof::prove;
+    use crate::testing::sample_proof;
     use crate::util::random_bigint;
     use num_bigint::BigInt;
 
     // Purpose: verify pass on honest proof and fail on tampered field
-    // Params: small demo range and random r
+    // Params: testing::sample_proof(195) and its range
     // Output: assertions on verifier boolean
     // Usage: `cargo test -- src::verify` or `cargo test`
     #[test]
     fn verify_pass_and_tamper_fail() {
-        let (g, h, n) = fast_test_setup();
-        let a = BigInt::from(1);
-        let b = BigInt::from(100);
-        let v = BigInt::from(42);
-        let r = random_bigint(128);
-        let proof = cuproof_prove(&v, &r, &a, &b, &g, &h, &n);
-        assert!(cuproof_verify_with_range(&proof, &g, &h, &n, &a, &b));
+        let (params, statement, proof) = sample_proof(195);
+        let (a, b) = (&statement.a, &statement.b);
+        assert!(cuproof_verify_with_range(&proof, &params, a, b));
 
         // Tamper: flip T1 slightly (add 1) -> should fail
         let mut bad = proof.clone();
         bad.T1 = &bad.T1 + BigInt::from(1);
-        assert!(!cuproof_verify_with_range(&bad, &g, &h, &n, &a, &b));
+        assert!(!cuproof_verify_with_range(&bad, &params, a, b));
+    }
+
+    // Purpose: ensure scalars at or above the scalar bound are rejected, though the identities hold
+    // Params: sample_proof(199) with t0 and t_hat both raised by B (t_hat - t0 unchanged), with
+    //         tau_x, mu or the inner-product a raised to B, and with a negative mu; the same forgeries
+    //         on the proof without its statement
+    // Output: each forgery fails ScalarBound naming the raised field (before the bound these all
+    //         verified); the honest proof still verifies
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn oversized_scalars_are_rejected() {
+        let (params, _, honest) = sample_proof(199);
+        let bare = Cuproof { statement: None, ..honest.clone() };
+        for proof in [honest, bare] {
+            assert_eq!(cuproof_verify_detailed(&proof, &params), Ok(()));
+            let big = BigInt::from(1) << proof_scalar_bound(&proof, &params).bits();
+            let forge = |change: &dyn Fn(&mut Cuproof)| {
+                let mut forged = proof.clone();
+                change(&mut forged);
+                cuproof_verify_detailed(&forged, &params)
+            };
+            let shifted = forge(&|p| { p.t0 += &big; p.t_hat += &big; });
+            assert_eq!(shifted, Err(VerifyCheck::ScalarBound("t0")));
+            assert_eq!(forge(&|p| p.tau_x += &big), Err(VerifyCheck::ScalarBound("tau_x")));
+            assert_eq!(forge(&|p| p.mu = big.clone()), Err(VerifyCheck::ScalarBound("mu")));
+            assert_eq!(forge(&|p| p.mu = -BigInt::from(1)), Err(VerifyCheck::ScalarBound("mu")));
+            assert_eq!(forge(&|p| p.ipp_proof.a += &big), Err(VerifyCheck::ScalarBound("ipp a")));
+        }
+    }
+
+    // Purpose: ensure cuproof_verify_fast agrees with verify, and verify ignores the assist
+    // Params: sample_proof(202) with its assist, after a to_bytes round trip; the proof without an
+    //         assist; the assist's q changed; tau2 changed, with the honest assist and with one
+    //         made for the change; T1 negated with t_hat recomputed, under its own assist with
+    //         Q1 negated
+    // Output: Ok(()) from both verifiers for the honest proof, the assist read back unchanged, and
+    //         for the changed q1 (cuproof_verify_fast falls back to the commitments); T2Commitment
+    //         from both for the changed tau2; the same rejection from both for the negated T1
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn fast_verify_uses_the_assist() {
+        use crate::util::ReadLimits;
+        let (params, _, proof) = sample_proof(202);
+        let assisted = proof.clone().with_verifier_assist(&params);
+        let read = Cuproof::from_bytes(&assisted.to_bytes(), &ReadLimits::default()).unwrap();
+        assert_eq!(read.assist, assisted.assist);
+        let mut wrong_q = read.clone();
+        if let Some(assist) = wrong_q.assist.as_mut() { assist.q1 += 1; }
+        for p in [&read, &proof, &wrong_q] {
+            assert_eq!(cuproof_verify_detailed(p, &params), Ok(()));
+            assert_eq!(cuproof_verify_fast(p, &params), Ok(()));
+        }
+
+        let moved = Cuproof { tau2: &read.tau2 + 1, ..read.clone() };
+        let reassisted = moved.clone().with_verifier_assist(&params);
+        for p in [&moved, &reassisted] {
+            assert_eq!(cuproof_verify_detailed(p, &params), Err(VerifyCheck::T2Commitment));
+            assert_eq!(cuproof_verify_fast(p, &params), Err(VerifyCheck::T2Commitment));
+        }
+
+        let mut negated = Cuproof { T1: params.n() - &read.T1, ..read };
+        let x = proof_challenges(&negated, &params).x;
+        negated.t_hat = &negated.t0 + &negated.t1 * &x + &negated.t2 * &x * &x;
+        let mut negated = negated.with_verifier_assist(&params);
+        if let Some(assist) = negated.assist.as_mut() { assist.q1 = params.n() - &assist.q1; }
+        let rejected = cuproof_verify_detailed(&negated, &params);
+        assert!(rejected.is_err());
+        assert_eq!(cuproof_verify_fast(&negated, &params), rejected);
+    }
+
+    // Purpose: ensure verify_interruptible stops at the first phase boundary after cancellation
+    // Params: honest proof; a cancellation flag raised on the n-th question, for n = 1..=5, and never
+    // Output: Err(Cancelled) with exactly n questions asked; Ok(()) after all five when never raised
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn cancellation_stops_between_phases() {
+        use core::cell::Cell;
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(42), &random_bigint(128), &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+        for n in 1..=5 {
+            let asked = Cell::new(0);
+            let cancelled = || { asked.set(asked.get() + 1); asked.get() == n };
+            assert_eq!(verify_interruptible(&proof, &params, &cancelled), Err(VerifyError::Cancelled));
+            assert_eq!(asked.get(), n);
+        }
+        let asked = Cell::new(0);
+        assert_eq!(verify_interruptible(&proof, &params, &|| { asked.set(asked.get() + 1); false }), Ok(()));
+        assert_eq!(asked.get(), 5);
+    }
+
+    // Purpose: ensure proof_challenges follows the transcript, also for proofs that do not verify
+    // Params: honest proof over 512-bit fast params, and a copy with T1 altered
+    // Output: nonzero y, z, x below n and one IPP challenge per round; altering T1 changes x only
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn challenges_follow_the_transcript() {
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(42), &random_bigint(128), &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+        let challenges = proof_challenges(&proof, &params);
+        for c in [&challenges.y, &challenges.z, &challenges.x] {
+            assert!(*c > BigInt::from(0) && c < params.n());
+        }
+        assert_eq!(challenges.ipp.len(), proof.ipp_proof.L.len());
+        assert_eq!(challenges.ipp[0], fiat_shamir(&[&proof.ipp_proof.L[0], &proof.ipp_proof.R[0]]) % params.n());
+
+        let mut bad = proof.clone();
+        bad.T1 += 1;
+        let altered = proof_challenges(&bad, &params);
+        assert!(!verify(&bad, &params));
+        assert_eq!((&altered.y, &altered.z, &altered.ipp), (&challenges.y, &challenges.z, &challenges.ipp));
+        assert_ne!(altered.x, challenges.x);
+    }
+
+    // Purpose: ensure the minimum modulus size is enforced by the verifier
+    // Params: honest proof over 512-bit fast params
+    // Output: accepted at 512 bits, rejected when 2048 bits are required
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn verify_enforces_min_bits() {
+        let params = insecure_test_setup(512);
+        let proof = prove(&BigInt::from(42), &random_bigint(128), &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+        assert!(cuproof_verify_with_min_bits(&proof, &params, 512));
+        assert!(!cuproof_verify_with_min_bits(&proof, &params, 2048));
+    }
+
+    // Purpose: ensure RangeProver/Verifier constructors validate params and delegate correctly
+    // Params: insecure 512-bit params (acknowledged) and a copy with g = 1
+    // Output: default constructors refuse 512-bit params; the insecure ones still reject bad params;
+    //         honest proof verifies through the wrappers
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn constructors_validate_params() {
+        use crate::range_proof::RangeProver;
+        let params = insecure_test_setup(512).allow_insecure_params();
+        let too_small = ParamsError::ModulusTooSmall { bits: 512, min_bits: 2048 };
+        assert_eq!(RangeProver::new(params.clone()).unwrap_err(), too_small);
+        assert_eq!(Verifier::new(params.clone()).unwrap_err(), too_small);
+
+        let bad = Params::new(BigInt::from(1), params.h().clone(), params.n().clone(), params.bits());
+        assert_eq!(RangeProver::new_insecure(bad.clone()).unwrap_err(), ParamsError::GeneratorOutOfRange("g"));
+        assert_eq!(Verifier::new_insecure(bad).unwrap_err(), ParamsError::GeneratorOutOfRange("g"));
+
+        let prover = RangeProver::new_insecure(params.clone()).unwrap();
+        let verifier = Verifier::new_insecure(params).unwrap();
+        let (a, b) = (BigInt::from(1), BigInt::from(100));
+        let proof = prover.prove(&BigInt::from(42), &random_bigint(128), &a, &b).unwrap();
+        assert!(verifier.verify(&proof));
+        assert!(verifier.verify_with_range(&proof, &a, &b));
+    }
+
+    // Purpose: ensure generator rotation keeps n, changes the fingerprint and splits proofs by epoch
+    // Params: fast params, the same params rotated once, one honest proof under each
+    // Output: each proof verifies only under its own epoch; mismatches report EpochMismatch,
+    //         also after a save_proof / load_proof round trip
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn rotated_generators_reject_other_epochs() {
+        use crate::util::{save_proof, load_proof};
+        let old = insecure_test_setup(512);
+        let new = old.rotate_generators("test-rotation");
+        assert_eq!((old.epoch(), new.epoch()), (0, 1));
+        assert_eq!(new.n(), old.n());
+        assert_ne!(new.g(), old.g());
+        assert_ne!(new.fingerprint(), old.fingerprint());
+        assert!(new.generators_match_seed());
+        assert!(validate_params(&new).is_ok());
+        assert_eq!(new.rotate_generators("test-rotation").epoch(), 2);
+
+        let (a, b) = (BigInt::from(1), BigInt::from(100));
+        let old_proof = prove(&BigInt::from(42), &random_bigint(128), &a, &b, &old).unwrap();
+        let new_proof = prove(&BigInt::from(42), &random_bigint(128), &a, &b, &new).unwrap();
+        assert_eq!((old_proof.epoch, new_proof.epoch), (0, 1));
+        assert!(cuproof_verify_checked(&old_proof, &old).is_ok());
+        assert!(cuproof_verify_checked(&new_proof, &new).is_ok());
+
+        // Old proof, new params
+        assert_eq!(cuproof_verify_checked(&old_proof, &new), Err(VerifyError::EpochMismatch { proof: 0, params: 1 }));
+        assert!(!cuproof_verify_with_range(&old_proof, &new, &a, &b));
+        // New proof, old params
+        assert_eq!(cuproof_verify_checked(&new_proof, &old), Err(VerifyError::EpochMismatch { proof: 1, params: 0 }));
+        assert!(!cuproof_verify_with_range(&new_proof, &old, &a, &b));
+        // Relabelling a proof's epoch does not make it valid under other generators
+        let mut relabelled = old_proof.clone();
+        relabelled.epoch = 1;
+        assert_eq!(cuproof_verify_checked(&relabelled, &new), Err(VerifyError::StatementMismatch("params fingerprint")));
+        // nor does dropping the statement that names the old params
+        relabelled.statement = None;
+        assert_eq!(cuproof_verify_checked(&relabelled, &new), Err(VerifyError::InvalidProof));
+
+        let path = std::env::temp_dir().join(format!("cuproof_rotated_proof_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_proof(path, &new_proof).unwrap();
+        let loaded = load_proof(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        assert_eq!(loaded.epoch, 1);
+        assert!(cuproof_verify_checked(&loaded, &new).is_ok());
+        assert!(cuproof_verify_checked(&loaded, &old).is_err());
+    }
+
+    // Purpose: ensure the embedded statement is checked against the proof and the caller's range
+    // Params: fast params, one proof over [1, 100], then altered statements and caller ranges
+    // Output: mismatching ranges, commitments and fingerprints are rejected with the field that differs
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn statement_is_cross_checked() {
+        let params = insecure_test_setup(512);
+        let (a, b) = (BigInt::from(1), BigInt::from(100));
+        let proof = prove(&BigInt::from(42), &random_bigint(128), &a, &b, &params).unwrap();
+        let expected = Statement::new(&proof.C, &a, &b, 64, &params);
+        assert_eq!(proof.statement.as_ref(), Some(&expected));
+        assert!(verify_statement(&proof, &params, &expected).is_ok());
+
+        // the caller expects another range than the one the proof claims
+        assert!(!cuproof_verify_with_range(&proof, &params, &a, &BigInt::from(1000)));
+        assert!(!cuproof_verify_with_range(&proof, &params, &BigInt::from(0), &b));
+        let wider = Statement::new(&proof.C, &a, &BigInt::from(1000), 64, &params);
+        assert_eq!(verify_statement(&proof, &params, &wider), Err(VerifyError::StatementMismatch("range")));
+        let labelled = expected.clone().with_label("other");
+        assert_eq!(verify_statement(&proof, &params, &labelled), Err(VerifyError::StatementMismatch("label")));
+
+        // statements that do not describe the proof fail every verifier entry point
+        let mut forged = proof.clone();
+        forged.statement.as_mut().unwrap().commitment += 1;
+        assert!(!verify(&forged, &params));
+        assert_eq!(cuproof_verify_checked(&forged, &params), Err(VerifyError::StatementMismatch("commitment")));
+        let mut forged = proof.clone();
+        forged.statement.as_mut().unwrap().params_fingerprint[0] ^= 1;
+        assert_eq!(cuproof_verify_checked(&forged, &params), Err(VerifyError::StatementMismatch("params fingerprint")));
+        let mut forged = proof.clone();
+        forged.statement.as_mut().unwrap().bits = 32;
+        assert_eq!(cuproof_verify_checked(&forged, &params), Err(VerifyError::StatementMismatch("bit width")));
+        let mut forged = proof.clone();
+        forged.statement.as_mut().unwrap().b = BigInt::from(0);
+        assert_eq!(cuproof_verify_checked(&forged, &params), Err(VerifyError::StatementMismatch("range")));
+    }
+
+    // Purpose: ensure a nonce binds a proof to its session, composes with a context label and leaves
+    //          nonce-less proofs as they were
+    // Params: fast params; seeded proofs of 42 in [1, 100] under label "withdraw" and nonce "session 1",
+    //         under the nonce alone, and with an empty nonce
+    // Output: the context proof verifies only with its own nonce and expected label; the empty-nonce
+    //         proof is byte-identical to prove_with_rng's and verifies without a nonce
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn nonce_binds_the_proof_to_its_session() {
+        use crate::range_proof::{prove_with_context_and_rng, prove_with_nonce_and_rng, prove_with_rng};
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+        let params = insecure_test_setup(512);
+        let (v, r, a, b) = (BigInt::from(42), random_bigint(128), BigInt::from(1), BigInt::from(100));
+        let nonce = b"session 1";
+        let proof = prove_with_context_and_rng(&v, &r, &a, &b, &params, "withdraw", nonce, &mut ChaCha20Rng::seed_from_u64(1)).unwrap();
+        assert_eq!(proof.statement.as_ref().unwrap().label, "withdraw");
+        assert_eq!(verify_with_nonce(&proof, &params, nonce), Ok(()));
+        assert_eq!(verify_with_nonce(&proof, &params, b"session 2"), Err(VerifyError::InvalidProof));
+        assert_eq!(verify_with_nonce(&proof, &params, &[]), Err(VerifyError::InvalidProof));
+        assert!(!verify(&proof, &params));
+
+        let expected = Statement::new(&proof.C, &a, &b, 64, &params).with_label("withdraw");
+        assert_eq!(verify_statement_with_nonce(&proof, &params, &expected, nonce), Ok(()));
+        assert_eq!(verify_statement_with_nonce(&proof, &params, &expected, b"session 2"), Err(VerifyError::InvalidProof));
+        assert_eq!(verify_statement(&proof, &params, &expected), Err(VerifyError::InvalidProof));
+
+        let unlabelled = prove_with_nonce_and_rng(&v, &r, &a, &b, &params, nonce, &mut ChaCha20Rng::seed_from_u64(1)).unwrap();
+        assert_eq!(verify_with_nonce(&unlabelled, &params, nonce), Ok(()));
+        assert_eq!(verify_with_nonce(&unlabelled.with_label("withdraw"), &params, nonce), Err(VerifyError::InvalidProof));
+
+        let plain = prove_with_nonce_and_rng(&v, &r, &a, &b, &params, &[], &mut ChaCha20Rng::seed_from_u64(2)).unwrap();
+        assert_eq!(plain.to_bytes(), prove_with_rng(&v, &r, &a, &b, &params, 64, &mut ChaCha20Rng::seed_from_u64(2)).unwrap().to_bytes());
+        assert!(verify(&plain, &params));
+        assert_eq!(verify_with_nonce(&plain, &params, nonce), Err(VerifyError::InvalidProof));
+    }
+
+    // Purpose: ensure the context label is bound by the transcript, not only compared with the statement
+    // Params: fast params; a proof of 42 in [1, 100] under label "withdraw" and nonce "session 1"; the
+    //         same proof with its embedded label rewritten to "deposit"
+    // Output: under the same nonce, expecting "deposit" fails with InvalidProof both for the proof as made
+    //         and for the rewritten one, whose statement then matches field by field
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn label_binds_the_challenges_under_a_nonce() {
+        use crate::range_proof::prove_with_context_and_rng;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+        let params = insecure_test_setup(512);
+        let (v, r, a, b) = (BigInt::from(42), random_bigint(128), BigInt::from(1), BigInt::from(100));
+        let nonce = b"session 1";
+        let proof = prove_with_context_and_rng(&v, &r, &a, &b, &params, "withdraw", nonce, &mut ChaCha20Rng::seed_from_u64(3)).unwrap();
+        let deposit = Statement::new(&proof.C, &a, &b, 64, &params).with_label("deposit");
+        assert_eq!(verify_statement_with_nonce(&proof, &params, &deposit, nonce), Err(VerifyError::InvalidProof));
+        let relabelled = proof.clone().with_label("deposit");
+        assert_eq!(relabelled.statement.as_ref(), Some(&deposit));
+        assert_eq!(verify_statement_with_nonce(&relabelled, &params, &deposit, nonce), Err(VerifyError::InvalidProof));
+        assert_eq!(verify_with_nonce(&relabelled, &params, nonce), Err(VerifyError::InvalidProof));
+        assert_eq!(verify_statement_with_nonce(&proof, &params, &deposit.with_label("withdraw"), nonce), Ok(()));
+    }
+
+    // Purpose: ensure insecure-marked params are refused until acknowledged, and 1024-bit test params work
+    // Params: insecure_test_setup(512) and (1024), the 1024-bit params saved and reloaded
+    // Output: InsecureParams from every constructor without allow_insecure_params; the marker survives
+    //         save/load and rotation; a 1024-bit proof verifies after the round trip
+    // Usage: `cargo test -- src::verify` or `cargo test`
+    #[test]
+    fn insecure_params_need_acknowledgement() {
+        use crate::range_proof::RangeProver;
+        use crate::util::{save_params, load_params};
+        let small = insecure_test_setup(512);
+        assert!(small.is_insecure());
+        assert_eq!(RangeProver::new(small.clone()).unwrap_err(), ParamsError::InsecureParams);
+        assert_eq!(RangeProver::new_insecure(small.clone()).unwrap_err(), ParamsError::InsecureParams);
+        assert_eq!(Verifier::new(small.clone()).unwrap_err(), ParamsError::InsecureParams);
+        assert_eq!(Verifier::new_insecure(small.clone()).unwrap_err(), ParamsError::InsecureParams);
+        assert!(small.rotate_generators("test").is_insecure());
+        assert!(!crate::setup::trusted_setup_with(256, true).unwrap().is_insecure());
+        #[allow(deprecated)]
+        let alias = crate::setup::fast_test_setup();
+        assert_eq!(alias, small);
+
+        let big = insecure_test_setup(1024);
+        assert_eq!(big.n().bits(), 1024);
+        assert_eq!(big, insecure_test_setup(1024));
+        let path = std::env::temp_dir().join(format!("cuproof_insecure_params_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_params(path, &big).unwrap();
+        let loaded = load_params(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        assert_eq!(loaded, big);
+        assert!(loaded.is_insecure());
+        assert_eq!(Verifier::new_insecure(loaded.clone()).unwrap_err(), ParamsError::InsecureParams);
+
+        let prover = RangeProver::new_insecure(big.allow_insecure_params()).unwrap();
+        let verifier = Verifier::new_insecure(loaded.allow_insecure_params()).unwrap();
+        let (a, b) = (BigInt::from(1), BigInt::from(1u64 << 40));
+        let proof = prover.prove(&BigInt::from(123_456_789u64), &random_bigint(256), &a, &b).unwrap();
+        assert!(verifier.verify_with_range(&proof, &a, &b));
+        assert_eq!(verifier.verify_checked(&proof), Ok(()));
+    }
+
+    /// The events of the tracing feature (trace.rs), captured with the spans around them
+    #[cfg(feature = "tracing")]
+    mod events {
+        use super::*;
+
+        /// A test subscriber layer recording every event with the fields of its enclosing spans
+        #[derive(Clone, Default)]
+        struct Capture {
+            events: std::sync::Arc<std::sync::Mutex<Vec<CapturedEvent>>>,
+        }
+
+        #[derive(Debug)]
+        struct CapturedEvent {
+            /// (name, fields) of each enclosing span, outermost first
+            spans: Vec<(&'static str, Vec<(&'static str, String)>)>,
+            fields: Vec<(&'static str, String)>,
+        }
+
+        impl CapturedEvent {
+            fn field(&self, name: &str) -> Option<&str> {
+                self.fields.iter().find(|(field, _)| *field == name).map(|(_, value)| value.as_str())
+            }
+        }
+
+        struct Fields(Vec<(&'static str, String)>);
+
+        impl tracing::field::Visit for Fields {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn fmt::Debug) {
+                self.0.push((field.name(), format!("{:?}", value)));
+            }
+
+            fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                self.0.push((field.name(), value.to_string()));
+            }
+        }
+
+        impl<S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>> tracing_subscriber::Layer<S> for Capture {
+            fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, id: &tracing::span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+                let mut fields = Fields(Vec::new());
+                attrs.record(&mut fields);
+                ctx.span(id).expect("the span is new").extensions_mut().insert(fields);
+            }
+
+            fn on_event(&self, event: &tracing::Event<'_>, ctx: tracing_subscriber::layer::Context<'_, S>) {
+                let mut fields = Fields(Vec::new());
+                event.record(&mut fields);
+                let spans = ctx.event_scope(event).into_iter().flat_map(|scope| scope.from_root())
+                    .map(|span| (span.name(), span.extensions().get::<Fields>().map(|f| f.0.clone()).unwrap_or_default()))
+                    .collect();
+                self.events.lock().unwrap().push(CapturedEvent { spans, fields: fields.0 });
+            }
+        }
+
+        /// Run `f` under a Capture subscriber, inside a `request` span with a request_id field
+        fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<CapturedEvent>) {
+            use tracing_subscriber::layer::SubscriberExt;
+            let capture = Capture::default();
+            let subscriber = tracing_subscriber::registry().with(capture.clone());
+            let result = tracing::subscriber::with_default(subscriber, || tracing::debug_span!("request", request_id = 7).in_scope(f));
+            let events = std::mem::take(&mut *capture.events.lock().unwrap());
+            (result, events)
+        }
+
+        // Purpose: ensure prove and verify log one event each inside a span naming the params and bit
+        //          width, nested under the caller's span
+        // Params: insecure 512-bit params under a capturing subscriber, inside a request span; an
+        //         honest proof, verified, then a copy with T1 altered
+        // Output: "proof created", "proof verified" and "proof rejected" events carrying the proof id
+        //         and a duration, the rejection naming the failed check; each inside request > prove
+        //         or request > verify, with bits 64 and the params fingerprint; the blinding appears
+        //         in no field
+        // Usage: `cargo test --features tracing -- src::verify` or `cargo test`
+        #[test]
+        fn prove_and_verify_emit_events() {
+            use crate::range_proof::proof_id;
+            let params = insecure_test_setup(512);
+            let r = random_bigint(128);
+            let ((proof, bad), events) = capture(|| {
+                let proof = prove(&BigInt::from(42), &r, &BigInt::from(1), &BigInt::from(100), &params).unwrap();
+                assert_eq!(cuproof_verify_checked(&proof, &params), Ok(()));
+                let mut bad = proof.clone();
+                bad.T1 += 1;
+                assert_eq!(cuproof_verify_checked(&bad, &params), Err(VerifyError::InvalidProof));
+                (proof, bad)
+            });
+
+            let request = ("request", vec![("request_id", "7".to_string())]);
+            let span_fields = vec![("bits", "64".to_string()), ("params", params.fingerprint_hex().to_string())];
+            let expected = [("prove", "proof created", proof_id(&proof)), ("verify", "proof verified", proof_id(&proof)), ("verify", "proof rejected", proof_id(&bad))];
+            assert_eq!(events.len(), expected.len(), "{:?}", events);
+            for (event, (span, message, id)) in events.iter().zip(expected) {
+                assert_eq!(event.spans, vec![request.clone(), (span, span_fields.clone())]);
+                assert_eq!(event.field("message"), Some(message));
+                assert_eq!(event.field("proof_id"), Some(id.as_str()));
+                assert!(event.field("elapsed_ms").is_some_and(|ms| ms.parse::<f64>().is_ok()));
+            }
+            assert_eq!(events[1].field("check"), None);
+            assert_eq!(events[2].field("check"), Some("T1 commitment"));
+            let (decimal, hex) = (r.to_string(), r.to_str_radix(16));
+            for (_, value) in events.iter().flat_map(|e| e.fields.iter().chain(e.spans.iter().flat_map(|(_, fields)| fields))) {
+                assert!(!value.contains(&decimal) && !value.contains(&hex), "{}", value);
+            }
+        }
+
+        // Purpose: ensure a setup reports each phase and its result, and the wire and stream layers
+        //          what they decoded or wrote
+        // Params: a 256-bit insecure trusted setup, its params through params_to_wire /
+        //         params_from_wire and write_params / read_params, inside a request span
+        // Output: "setup phase finished" for p, q and generators then "params generated" in
+        //         request > setup, candidates counted for both primes; "params decoded",
+        //         "params written" and "params read" in their spans
+        // Usage: `cargo test --features tracing -- src::verify` or `cargo test`
+        #[test]
+        fn setup_and_serialization_emit_events() {
+            use crate::util::{read_params, write_params, ReadLimits};
+            use crate::wire::{params_from_wire, params_to_wire};
+            let (params, events) = capture(|| {
+                let params = crate::setup::trusted_setup_with(256, true).unwrap();
+                params_from_wire(&params_to_wire(&params).unwrap()).unwrap();
+                let mut bytes = Vec::new();
+                write_params(&mut bytes, &params).unwrap();
+                read_params(&mut &bytes[..], &ReadLimits::default()).unwrap();
+                params
+            });
+
+            let spans: Vec<Vec<&str>> = events.iter().map(|e| e.spans.iter().map(|(name, _)| *name).collect()).collect();
+            let messages: Vec<&str> = events.iter().map(|e| e.field("message").unwrap()).collect();
+            assert_eq!(messages, ["setup phase finished", "setup phase finished", "setup phase finished", "params generated", "params decoded", "params written", "params read"]);
+            assert_eq!(spans[..4], [vec!["request", "setup"], vec!["request", "setup"], vec!["request", "setup"], vec!["request", "setup"]]);
+            assert_eq!(spans[4..], [vec!["request", "params_from_wire"], vec!["request", "write_params"], vec!["request", "read_params"]]);
+            let phases: Vec<_> = events[..3].iter().map(|e| e.field("phase").unwrap()).collect();
+            assert_eq!(phases, ["p", "q", "generators"]);
+            assert!(events[..2].iter().all(|e| e.field("candidates").unwrap().parse::<u64>().unwrap() >= 1));
+            assert_eq!(events[3].field("params"), Some(params.fingerprint_hex().to_string().as_str()));
+        }
     }
 }
@@ -0,0 +1,337 @@
+//! Binary wire format of proofs and params, byte for byte, for verifiers outside this crate
+//!
+//! All integers are big-endian. Two field shapes are used, both built from the util helpers:
+//!
+//! - `u16` / `u32` / `u64`: fixed-width unsigned integers
+//! - `scalar`: `u32` length `len`, then `len` bytes of the magnitude with no leading zero byte;
+//!   zero is `00 00 00 00` (util::scalar_to_be_bytes). At most MAX_PROOF_INT_BYTES long
+//! - `fixed(w)`: exactly `w` bytes, the value left-padded with zeros (util::bigint_to_be_bytes_padded)
+//!
+//! Proof (`proof_to_wire`):
+//!
+//! | size       | field                                                                   |
+//! |------------|-------------------------------------------------------------------------|
+//! | 2          | `u16` WIRE_FORMAT_VERSION                                               |
+//! | 8          | `u64` generator epoch                                                   |
+//! | 15 scalars | A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2   |
+//! | 4          | `u32` k, the number of inner-product rounds (1 ..= MAX_IPP_ROUNDS)      |
+//! | k scalars  | IPP L                                                                   |
+//! | k scalars  | IPP R                                                                   |
+//! | 2 scalars  | IPP a, IPP b                                                            |
+//!
+//! Params (`params_to_wire`), with `w` the byte length of n:
+//!
+//! | size     | field                                              |
+//! |----------|----------------------------------------------------|
+//! | 2        | `u16` WIRE_FORMAT_VERSION                          |
+//! | 4        | `u32` declared modulus size in bits                |
+//! | 8        | `u64` generator epoch                              |
+//! | 1        | flags: bit 0 set for INSECURE test params, others 0 |
+//! | scalar   | n                                                  |
+//! | fixed(w) | g                                                  |
+//! | fixed(w) | h                                                  |
+//!
+//! Nothing may follow the last field. The generator seed and metadata section are not part of
+//! the wire form. Decoding accepts only what encoding produces and runs the load_proof checks,
+//! or validate_params, on the result. testdata/proof_v1.wire and testdata/params_v1.wire are
+//! reference encodings (see the tests below for the values they hold)
+
+use alloc::vec::Vec;
+use core::fmt;
+use num_bigint::BigInt;
+use crate::range_proof::{Cuproof, IPPProof};
+#[cfg(feature = "tracing")]
+use crate::range_proof::proof_id;
+use crate::setup::{validate_params, Params, ParamsError};
+use crate::trace::traced;
+use crate::util::{
+    bigint_from_be_bytes, bigint_to_be_bytes_padded, check_header_nonzero, check_ipp_lengths, scalar_from_be_bytes,
+    scalar_to_be_bytes, DecodeError, EncodeError,
+};
+
+/// Version field of every proof and params written by this module
+pub const WIRE_FORMAT_VERSION: u16 = 1;
+
+const FLAG_INSECURE: u8 = 1;
+
+/// Why proof_from_wire / params_from_wire rejected their input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireError {
+    /// a field is truncated, non-canonical or too large
+    Field(DecodeError),
+    /// the version field is not WIRE_FORMAT_VERSION
+    UnsupportedVersion(u16),
+    /// flag bits other than FLAG_INSECURE are set
+    UnknownFlags(u8),
+    /// bytes follow the last field
+    TrailingBytes(usize),
+    /// the decoded proof fails the checks of load_proof
+    InvalidProof(&'static str),
+    /// the decoded params fail validate_params
+    InvalidParams(ParamsError),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Field(e) => write!(f, "bad wire field: {}", e),
+            WireError::UnsupportedVersion(v) => write!(f, "unsupported wire format version {}", v),
+            WireError::UnknownFlags(flags) => write!(f, "unknown params flags {:#04x}", flags),
+            WireError::TrailingBytes(n) => write!(f, "{} unexpected bytes after the last field", n),
+            WireError::InvalidProof(msg) => write!(f, "invalid proof: {}", msg),
+            WireError::InvalidParams(e) => write!(f, "invalid params: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for WireError {}
+
+impl From<DecodeError> for WireError {
+    fn from(e: DecodeError) -> WireError {
+        WireError::Field(e)
+    }
+}
+
+/// Encode a proof in the layout above
+/// - returns: bytes, or EncodeError::Negative if a field is negative (honest proofs never are)
+pub fn proof_to_wire(proof: &Cuproof) -> Result<Vec<u8>, EncodeError> {
+    let ipp = &proof.ipp_proof;
+    let mut out = WIRE_FORMAT_VERSION.to_be_bytes().to_vec();
+    out.extend_from_slice(&proof.epoch.to_be_bytes());
+    let scalars = [
+        &proof.A, &proof.S, &proof.T1, &proof.T2, &proof.tau_x, &proof.mu, &proof.t_hat, &proof.C, &proof.C_v1,
+        &proof.C_v2, &proof.t0, &proof.t1, &proof.t2, &proof.tau1, &proof.tau2,
+    ];
+    for x in scalars { out.extend(scalar_to_be_bytes(x)?); }
+    let rounds = u32::try_from(ipp.L.len()).map_err(|_| EncodeError::TooWide { needed: 8, width: 4 })?;
+    out.extend_from_slice(&rounds.to_be_bytes());
+    for x in ipp.L.iter().chain(&ipp.R).chain([&ipp.a, &ipp.b]) { out.extend(scalar_to_be_bytes(x)?); }
+    Ok(out)
+}
+
+/// Decode a proof written by proof_to_wire, running the checks of load_proof
+/// - returns: Cuproof or the WireError of the first problem found
+pub fn proof_from_wire(bytes: &[u8]) -> Result<Cuproof, WireError> {
+    traced!(
+        tracing::debug_span!("proof_from_wire", bytes = bytes.len()),
+        decode_proof(bytes),
+        |proof, _| match proof {
+            Ok(proof) => tracing::debug!(proof_id = %proof_id(proof), "proof decoded"),
+            Err(e) => tracing::debug!(error = %e, "proof refused"),
+        }
+    )
+}
+
+#[allow(non_snake_case)]
+fn decode_proof(bytes: &[u8]) -> Result<Cuproof, WireError> {
+    let mut r = Reader { bytes };
+    r.version()?;
+    let epoch = u64::from_be_bytes(r.array()?);
+    let mut scalars = (0..15).map(|_| r.scalar()).collect::<Result<Vec<_>, _>>()?.into_iter();
+    let mut next = || scalars.next().expect("fifteen scalars were decoded");
+    let (A, S, T1, T2) = (next(), next(), next(), next());
+    let (tau_x, mu, t_hat, C, C_v1, C_v2) = (next(), next(), next(), next(), next(), next());
+    let (t0, t1, t2, tau1, tau2) = (next(), next(), next(), next(), next());
+    let rounds = u32::from_be_bytes(r.array()?) as usize;
+    check_ipp_lengths(rounds, rounds).map_err(WireError::InvalidProof)?;
+    let L = (0..rounds).map(|_| r.scalar()).collect::<Result<Vec<_>, _>>()?;
+    let R = (0..rounds).map(|_| r.scalar()).collect::<Result<Vec<_>, _>>()?;
+    let (a, b) = (r.scalar()?, r.scalar()?);
+    r.finish()?;
+    for x in [&A, &S, &T1, &T2] { check_header_nonzero(x).map_err(WireError::InvalidProof)?; }
+    Ok(Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof: IPPProof { L, R, a, b }, epoch, statement: None, assist: None })
+}
+
+/// Encode params in the layout above
+/// - returns: bytes, or EncodeError when a generator does not fit the byte length of n
+pub fn params_to_wire(params: &Params) -> Result<Vec<u8>, EncodeError> {
+    let bits = u32::try_from(params.bits()).map_err(|_| EncodeError::TooWide { needed: 8, width: 4 })?;
+    let width = (params.n().bits() as usize).div_ceil(8);
+    let mut out = WIRE_FORMAT_VERSION.to_be_bytes().to_vec();
+    out.extend_from_slice(&bits.to_be_bytes());
+    out.extend_from_slice(&params.epoch().to_be_bytes());
+    out.push(if params.is_insecure() { FLAG_INSECURE } else { 0 });
+    out.extend(scalar_to_be_bytes(params.n())?);
+    out.extend(bigint_to_be_bytes_padded(params.g(), width)?);
+    out.extend(bigint_to_be_bytes_padded(params.h(), width)?);
+    Ok(out)
+}
+
+/// Decode params written by params_to_wire and run validate_params on them
+/// - returns: Params without generator seed or metadata, or the WireError of the first problem found
+pub fn params_from_wire(bytes: &[u8]) -> Result<Params, WireError> {
+    traced!(
+        tracing::debug_span!("params_from_wire", bytes = bytes.len()),
+        decode_params(bytes),
+        |params, _| match params {
+            Ok(params) => tracing::debug!(params = %params.fingerprint_hex(), "params decoded"),
+            Err(e) => tracing::debug!(error = %e, "params refused"),
+        }
+    )
+}
+
+fn decode_params(bytes: &[u8]) -> Result<Params, WireError> {
+    let mut r = Reader { bytes };
+    r.version()?;
+    let bits = u32::from_be_bytes(r.array()?) as usize;
+    let epoch = u64::from_be_bytes(r.array()?);
+    let [flags] = r.array()?;
+    if flags & !FLAG_INSECURE != 0 { return Err(WireError::UnknownFlags(flags)); }
+    let n = r.scalar()?;
+    let width = (n.bits() as usize).div_ceil(8);
+    let g = bigint_from_be_bytes(r.take(width)?);
+    let h = bigint_from_be_bytes(r.take(width)?);
+    r.finish()?;
+    let params = Params::new(g, h, n, bits).with_epoch(epoch);
+    let params = if flags & FLAG_INSECURE != 0 { params.mark_insecure() } else { params };
+    validate_params(&params).map_err(WireError::InvalidParams)?;
+    Ok(params)
+}
+
+/// The unread rest of a wire document
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len { return Err(DecodeError::Truncated { needed: len, available: self.bytes.len() }); }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().expect("take returned N bytes"))
+    }
+
+    fn scalar(&mut self) -> Result<BigInt, DecodeError> {
+        let (x, used) = scalar_from_be_bytes(self.bytes)?;
+        self.bytes = &self.bytes[used..];
+        Ok(x)
+    }
+
+    fn version(&mut self) -> Result<(), WireError> {
+        let version = u16::from_be_bytes(self.array()?);
+        if version != WIRE_FORMAT_VERSION { return Err(WireError::UnsupportedVersion(version)); }
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<(), WireError> {
+        if self.bytes.is_empty() { Ok(()) } else { Err(WireError::TrailingBytes(self.bytes.len())) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::insecure_test_setup;
+    use crate::util::MAX_PROOF_INT_BYTES;
+    use crate::verify::verify;
+    use num_traits::{One, Zero};
+
+    // A small hand-written proof whose encoding is checked in as testdata/proof_v1.wire
+    fn golden_proof() -> Cuproof {
+        let int = |x: i64| BigInt::from(x);
+        Cuproof {
+            A: int(1), S: int(2), T1: int(3), T2: int(4), tau_x: int(0), mu: int(6), t_hat: int(7),
+            C: int(8), C_v1: int(9), C_v2: int(10), t0: int(11), t1: int(12), t2: int(13), tau1: int(14), tau2: int(0x1234),
+            ipp_proof: IPPProof { L: vec![int(16), int(17)], R: vec![int(18), int(19)], a: int(20), b: int(0xff00) },
+            epoch: 2,
+            statement: None,
+            assist: None,
+        }
+    }
+
+    // Purpose: pin the byte helpers at their edges: zero, leading zeros and the largest value per width
+    // Params: 0, 1, 0x00ff-style values, 2^(8w) - 1 and 2^(8w)
+    // Output: exact byte strings; out-of-range and negative values are EncodeErrors; non-canonical
+    //         or truncated scalars are DecodeErrors
+    // Usage: `cargo test -- src::wire`
+    #[test]
+    fn byte_helpers_handle_edge_values() {
+        assert_eq!(bigint_to_be_bytes_padded(&BigInt::zero(), 3), Ok(vec![0, 0, 0]));
+        assert_eq!(bigint_to_be_bytes_padded(&BigInt::zero(), 0), Ok(vec![]));
+        assert_eq!(bigint_to_be_bytes_padded(&BigInt::from(0xffu32), 4), Ok(vec![0, 0, 0, 0xff]));
+        let max = (BigInt::one() << 32u32) - 1u32;
+        assert_eq!(bigint_to_be_bytes_padded(&max, 4), Ok(vec![0xff; 4]));
+        assert_eq!(bigint_to_be_bytes_padded(&(&max + 1u32), 4), Err(EncodeError::TooWide { needed: 5, width: 4 }));
+        assert_eq!(bigint_to_be_bytes_padded(&BigInt::from(-1), 4), Err(EncodeError::Negative));
+
+        assert_eq!(bigint_from_be_bytes(&[]), BigInt::zero());
+        assert_eq!(bigint_from_be_bytes(&[0, 0, 1]), BigInt::one());
+        assert_eq!(bigint_from_be_bytes(&[0xff; 4]), max);
+
+        assert_eq!(scalar_to_be_bytes(&BigInt::zero()), Ok(vec![0, 0, 0, 0]));
+        assert_eq!(scalar_to_be_bytes(&BigInt::from(0x0100u32)), Ok(vec![0, 0, 0, 2, 1, 0]));
+        assert_eq!(scalar_to_be_bytes(&max), Ok(vec![0, 0, 0, 4, 0xff, 0xff, 0xff, 0xff]));
+        assert_eq!(scalar_to_be_bytes(&BigInt::from(-5)), Err(EncodeError::Negative));
+        assert_eq!(scalar_from_be_bytes(&[0, 0, 0, 0, 9]), Ok((BigInt::zero(), 4)));
+        assert_eq!(scalar_from_be_bytes(&[0, 0, 0, 2, 1, 0, 9]), Ok((BigInt::from(0x0100u32), 6)));
+        assert_eq!(scalar_from_be_bytes(&[0, 0, 0, 2, 0, 1]), Err(DecodeError::LeadingZero));
+        assert_eq!(scalar_from_be_bytes(&[0, 0, 0, 3, 1]), Err(DecodeError::Truncated { needed: 7, available: 5 }));
+        assert_eq!(scalar_from_be_bytes(&[0, 0]), Err(DecodeError::Truncated { needed: 4, available: 2 }));
+        let too_long = (MAX_PROOF_INT_BYTES as u32 + 1).to_be_bytes();
+        assert_eq!(scalar_from_be_bytes(&too_long), Err(DecodeError::TooLarge(MAX_PROOF_INT_BYTES + 1)));
+    }
+
+    // Purpose: ensure the wire encodings stay byte-stable and round-trip through validation
+    // Params: the golden proof, insecure 512-bit params and an honest proof over them
+    // Output: encodings equal the checked-in fixtures; decoded values re-encode identically and verify
+    // Usage: `cargo test -- src::wire`
+    #[test]
+    fn wire_matches_golden_and_round_trips() {
+        let golden = include_bytes!("../testdata/proof_v1.wire");
+        assert_eq!(proof_to_wire(&golden_proof()).unwrap(), golden);
+        assert_eq!(&golden[..10], &[0, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(&golden[10..15], &[0, 0, 0, 1, 1]);
+        let decoded = proof_from_wire(golden).unwrap();
+        assert_eq!(proof_to_wire(&decoded).unwrap(), golden);
+
+        let params = insecure_test_setup(512);
+        let params_golden = include_bytes!("../testdata/params_v1.wire");
+        assert_eq!(params_to_wire(&params).unwrap(), params_golden);
+        let decoded = params_from_wire(params_golden).unwrap();
+        assert_eq!(params_to_wire(&decoded).unwrap(), params_golden);
+        assert_eq!(decoded.fingerprint(), params.fingerprint());
+        assert!(decoded.is_insecure());
+
+        let proof = crate::range_proof::prove(&BigInt::from(30), &BigInt::from(42), &BigInt::from(10), &BigInt::from(100), &params).unwrap();
+        let bytes = proof_to_wire(&proof).unwrap();
+        assert!(verify(&proof_from_wire(&bytes).unwrap(), &params));
+    }
+
+    // Purpose: ensure decoding rejects damaged or invalid documents with the matching WireError
+    // Params: the golden encodings with one field changed, truncated or extended, and encodings of
+    //         proofs with a zero header or no inner-product rounds
+    // Output: one WireError variant per defect
+    // Usage: `cargo test -- src::wire`
+    #[test]
+    fn wire_rejects_invalid_documents() {
+        let golden = include_bytes!("../testdata/proof_v1.wire").to_vec();
+        let edited = |at: usize, value: u8| {
+            let mut bytes = golden.clone();
+            bytes[at] = value;
+            bytes
+        };
+        assert_eq!(proof_from_wire(&edited(1, 2)).err(), Some(WireError::UnsupportedVersion(2)));
+        assert_eq!(proof_from_wire(&edited(14, 0)).err(), Some(WireError::Field(DecodeError::LeadingZero)));
+        let zero_a = Cuproof { A: BigInt::zero(), ..golden_proof() };
+        assert!(matches!(proof_from_wire(&proof_to_wire(&zero_a).unwrap()), Err(WireError::InvalidProof(_))));
+        let no_rounds = Cuproof { ipp_proof: IPPProof { L: vec![], R: vec![], a: BigInt::one(), b: BigInt::one() }, ..golden_proof() };
+        assert!(matches!(proof_from_wire(&proof_to_wire(&no_rounds).unwrap()), Err(WireError::InvalidProof(_))));
+        assert!(matches!(proof_from_wire(&golden[..golden.len() - 1]), Err(WireError::Field(DecodeError::Truncated { .. }))));
+        let mut trailing = golden.clone();
+        trailing.push(0);
+        assert_eq!(proof_from_wire(&trailing).err(), Some(WireError::TrailingBytes(1)));
+
+        let params = include_bytes!("../testdata/params_v1.wire").to_vec();
+        let mut flags = params.clone();
+        flags[14] = 3;
+        assert_eq!(params_from_wire(&flags).err(), Some(WireError::UnknownFlags(3)));
+        let mut g_zero = params.clone();
+        let width = (params.len() - 19) / 3;
+        g_zero[19 + width..19 + 2 * width].fill(0);
+        assert_eq!(params_from_wire(&g_zero).err(), Some(WireError::InvalidParams(ParamsError::GeneratorOutOfRange("g"))));
+    }
+}
@@ -0,0 +1,123 @@
+//! A dedicated pool of worker threads, handed to the parallel parts of the library
+//!
+//! Batch proving and verification and the trusted setup prime search take an `Option<&Workers>`
+//! and never touch rayon's global pool, so an embedding application keeps control of its own
+//! threads; None runs the work on the calling thread. With the `parallel` feature the pool is a
+//! rayon pool; without it batches run on scoped threads and the setup search is serial.
+
+#[cfg(not(feature = "parallel"))]
+use std::sync::Mutex;
+#[cfg(not(feature = "parallel"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// `threads` workers; one worker runs everything on the calling thread and starts none
+pub struct Workers {
+    threads: usize,
+    #[cfg(feature = "parallel")]
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl Workers {
+    /// Start `threads` workers (at least one)
+    /// - usage: `Workers::new(Workers::available())` for every core; `Workers::new(1)` for serial runs
+    pub fn new(threads: usize) -> Workers {
+        let threads = threads.max(1);
+        Workers {
+            threads,
+            #[cfg(feature = "parallel")]
+            pool: (threads > 1).then(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .expect("failed to start the worker thread pool")
+            }),
+        }
+    }
+
+    /// The number of logical cores, 1 when it cannot be told
+    pub fn available() -> usize {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// The rayon pool, None when a single worker runs everything on the calling thread
+    #[cfg(feature = "parallel")]
+    pub(crate) fn pool(&self) -> Option<&rayon::ThreadPool> {
+        self.pool.as_ref()
+    }
+
+    /// `work` on every input
+    /// - returns: the outputs in input order
+    #[cfg(feature = "parallel")]
+    pub fn map<T: Sync, R: Send>(&self, inputs: &[T], work: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+        use rayon::prelude::*;
+        match &self.pool {
+            Some(pool) => pool.install(|| inputs.par_iter().with_max_len(1).map(work).collect()),
+            None => inputs.iter().map(work).collect(),
+        }
+    }
+
+    /// `work` on every input, the threads taking the next input as they finish
+    /// - returns: the outputs in input order
+    #[cfg(not(feature = "parallel"))]
+    pub fn map<T: Sync, R: Send>(&self, inputs: &[T], work: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+        if self.threads == 1 { return inputs.iter().map(work).collect(); }
+        let next = AtomicUsize::new(0);
+        let slots: Vec<Mutex<Option<R>>> = inputs.iter().map(|_| Mutex::new(None)).collect();
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads.min(inputs.len().max(1)) {
+                scope.spawn(|| {
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(input) = inputs.get(i) else { break };
+                        *slots[i].lock().unwrap() = Some(work(input));
+                    }
+                });
+            }
+        });
+        slots.into_iter().map(|slot| slot.into_inner().unwrap().expect("every input is worked on")).collect()
+    }
+}
+
+/// `workers.map`, or the inputs in turn on the calling thread without workers
+pub(crate) fn map_on<T: Sync, R: Send>(workers: Option<&Workers>, inputs: &[T], work: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    match workers {
+        Some(workers) => workers.map(inputs, work),
+        None => inputs.iter().map(work).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    // Purpose: ensure map keeps input order and one worker stays on the calling thread
+    // Params: 64 inputs on 1 and 4 workers, recording the thread each ran on
+    // Output: squares in input order; only the caller's thread with one worker, never it with four
+    // Usage: `cargo test -- src::workers` or `cargo test`
+    #[test]
+    fn map_keeps_order_on_its_own_threads() {
+        let inputs: Vec<u64> = (0..64).collect();
+        let caller = std::thread::current().id();
+        for threads in [1, 4] {
+            let workers = Workers::new(threads);
+            assert_eq!(workers.threads(), threads);
+            let seen = Mutex::new(HashSet::new());
+            let squares = workers.map(&inputs, |i| {
+                seen.lock().unwrap().insert(std::thread::current().id());
+                i * i
+            });
+            assert_eq!(squares, inputs.iter().map(|i| i * i).collect::<Vec<_>>());
+            let seen = seen.into_inner().unwrap();
+            assert_eq!(seen.contains(&caller), threads == 1);
+            assert!(seen.len() <= threads);
+        }
+        assert_eq!(Workers::new(0).threads(), 1);
+        assert_eq!(map_on(None, &inputs[..3], |i| i + 1), [1, 2, 3]);
+    }
+}
@@ -0,0 +1,61 @@
+//! Completeness: the prover proves every value of every range, and the verifier accepts it
+
+use cuproof::verify::cuproof_verify_with_range;
+use cuproof::{prove_with_rng, verify};
+use num_bigint::BigInt;
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use crate::{bigint, config, params};
+
+/// Widest range bound and offset drawn, in bits
+pub const MAX_BITS: u64 = 128;
+
+/// a <= v <= b, held as a, v - a and b - v so each part shrinks toward 0 on its own
+#[derive(Clone, Debug)]
+pub struct InRange {
+    pub a: BigInt,
+    pub below: BigInt,
+    pub above: BigInt,
+    /// seeds the prover's randomness
+    pub seed: u64,
+}
+
+impl InRange {
+    pub fn v(&self) -> BigInt {
+        &self.a + &self.below
+    }
+
+    pub fn b(&self) -> BigInt {
+        self.v() + &self.above
+    }
+
+    pub fn strategy() -> impl Strategy<Value = InRange> {
+        (bigint(MAX_BITS), bigint(MAX_BITS), bigint(MAX_BITS), any::<u64>())
+            .prop_map(|(a, below, above, seed)| InRange { a, below, above, seed })
+    }
+
+    /// The proof the prover makes for this case, blinding and randomness drawn from `seed`
+    pub fn prove(&self) -> Result<cuproof::Cuproof, cuproof::ProveError> {
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        let r = cuproof::range_proof::random_blinding(&mut rng);
+        prove_with_rng(&self.v(), &r, &self.a, &self.b(), params(), 64, &mut rng)
+    }
+}
+
+proptest! {
+    #![proptest_config(config(24))]
+
+    // Purpose: property: for a <= v <= b the prover succeeds and the verifier accepts the proof
+    // Params: 24 cases of a, v - a and b - v up to 128 bits each, seeded prover randomness
+    // Output: verify and cuproof_verify_with_range for [a, b] accept every proof with a < b; both
+    //   reject the single-value range a = b, which the embedded statement check refuses by design
+    // Usage: `cargo test --test properties completeness`
+    #[test]
+    fn prove_then_verify(case in InRange::strategy()) {
+        let proof = case.prove().map_err(|e| TestCaseError::fail(format!("prover refused: {}", e)))?;
+        let (a, b) = (&case.a, &case.b());
+        prop_assert_eq!(verify(&proof, params()), a < b, "verify for a < b = {}", a < b);
+        prop_assert_eq!(cuproof_verify_with_range(&proof, params(), a, b), a < b, "cuproof_verify_with_range for a < b = {}", a < b);
+    }
+}
@@ -0,0 +1,51 @@
+//! Property tests of the protocol, one module per property: completeness, soundness, round trips
+//! of the encodings and tamper rejection
+//!
+//! Cases are generated and shrunk by proptest. A failing case is written, as the seed proptest
+//! replays it from, to the regressions file of its module under tests/properties/; every run
+//! replays those seeds before the random cases, so commit the file with the fix.
+//!
+//! `PROPTEST_CASES=<n>` runs n random cases per property instead of each property's default.
+
+mod completeness;
+mod roundtrip;
+mod soundness;
+mod tamper;
+
+use std::sync::OnceLock;
+use cuproof::setup::insecure_test_setup;
+use cuproof::Params;
+use num_bigint::{BigInt, Sign};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::FileFailurePersistence;
+
+/// The 512-bit insecure test params every property runs under
+pub fn params() -> &'static Params {
+    static PARAMS: OnceLock<Params> = OnceLock::new();
+    PARAMS.get_or_init(|| insecure_test_setup(512))
+}
+
+/// Config of a property: `cases` random cases unless PROPTEST_CASES is set, failures persisted
+/// next to the module that found them
+pub fn config(cases: u32) -> ProptestConfig {
+    let cases = if std::env::var_os("PROPTEST_CASES").is_some() { ProptestConfig::default().cases } else { cases };
+    ProptestConfig { cases, failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))), ..ProptestConfig::default() }
+}
+
+/// A non-negative integer of at most `max_bits` bits; its byte length is drawn first so small
+/// values are as likely as large ones, and it shrinks toward fewer and smaller bytes
+pub fn bigint(max_bits: u64) -> impl Strategy<Value = BigInt> + Clone {
+    let max_bytes = max_bits.div_ceil(8) as usize;
+    vec(any::<u8>(), 0..=max_bytes).prop_map(move |bytes| {
+        let x = BigInt::from_bytes_le(Sign::Plus, &bytes);
+        let excess = x.bits().saturating_sub(max_bits);
+        x >> excess
+    })
+}
+
+/// An integer in [low, high), shrinking toward low
+pub fn bigint_in(low: BigInt, high: BigInt) -> impl Strategy<Value = BigInt> + Clone {
+    let width = &high - &low;
+    bigint(width.bits() + 64).prop_map(move |x| &low + x % &width)
+}
@@ -0,0 +1,156 @@
+//! Round trips: decoding what an encoder wrote gives back the value it was given
+
+use std::fmt;
+use cuproof::range_proof::IPPProof;
+use cuproof::setup::DEFAULT_GENERATOR_SEED;
+use cuproof::util::{bigint_to_hex, bigint_to_signed_hex, hex_to_bigint, hex_to_bigint_signed};
+use cuproof::{
+    params_from_wire, params_to_wire, proof_from_wire, proof_to_wire, read_params, read_proof, write_params,
+    write_proof, Cuproof, Params, ReadLimits, Statement,
+};
+use num_bigint::BigInt;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use crate::{bigint, bigint_in, config, params};
+
+/// Most inner-product rounds drawn; prove makes 6
+const MAX_ROUNDS: usize = 8;
+
+/// Longest label drawn, in characters
+const MAX_LABEL_CHARS: usize = 24;
+
+/// A proof that passes every load check but not the verifier: group elements in [1, n), scalars
+/// of up to twice the modulus size, 1 to MAX_ROUNDS rounds, and a statement half of the time
+struct Structural(Cuproof);
+
+/// Cuproof has no Debug; a failure report shows the shape and the container encoding
+impl fmt::Debug for Structural {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = Vec::new();
+        let encoded = write_proof(&mut bytes, &self.0).map(|_| String::from_utf8_lossy(&bytes).into_owned());
+        f.debug_struct("Structural")
+            .field("rounds", &self.0.ipp_proof.L.len())
+            .field("epoch", &self.0.epoch)
+            .field("statement", &self.0.statement)
+            .field("encoded", &encoded.map_err(|e| e.to_string()))
+            .finish()
+    }
+}
+
+/// An integer of up to 128 bits, either sign
+fn signed() -> impl Strategy<Value = BigInt> {
+    (bigint(128), any::<bool>()).prop_map(|(x, negative)| if negative { -x } else { x })
+}
+
+#[allow(non_snake_case)]
+fn structural_proof() -> impl Strategy<Value = Structural> {
+    let n = params().n().clone();
+    let element = bigint_in(BigInt::from(1), n.clone());
+    let scalar = bigint(2 * n.bits());
+    let ipp = (1..=MAX_ROUNDS).prop_flat_map({
+        let (element, scalar) = (element.clone(), scalar.clone());
+        move |rounds| (vec(element.clone(), rounds), vec(element.clone(), rounds), scalar.clone(), scalar.clone())
+    });
+    let statement = proptest::option::of((signed(), signed(), vec(any::<char>(), 0..=MAX_LABEL_CHARS), any::<[u8; 32]>()));
+    (ipp, vec(element, 7), vec(scalar, 8), statement, 0..4u64).prop_map(|((L, R, a, b), elements, scalars, statement, epoch)| {
+        let [A, S, T1, T2, C, C_v1, C_v2]: [BigInt; 7] = elements.try_into().unwrap();
+        let [tau_x, mu, t_hat, t0, t1, t2, tau1, tau2]: [BigInt; 8] = scalars.try_into().unwrap();
+        let statement = statement.map(|(a, b, label, params_fingerprint)| Statement {
+            commitment: C.clone(),
+            a,
+            b,
+            bits: 1 << L.len(),
+            label: label.into_iter().collect(),
+            params_fingerprint,
+        });
+        let ipp_proof = IPPProof { L, R, a, b };
+        Structural(Cuproof { A, S, T1, T2, tau_x, mu, t_hat, C, C_v1, C_v2, t0, t1, t2, tau1, tau2, ipp_proof, epoch, statement, assist: None })
+    })
+}
+
+/// Every field of a proof, in wire order, for comparing two of them
+fn fields(proof: &Cuproof) -> Vec<&BigInt> {
+    let ipp = &proof.ipp_proof;
+    [&proof.A, &proof.S, &proof.T1, &proof.T2, &proof.tau_x, &proof.mu, &proof.t_hat, &proof.C, &proof.C_v1, &proof.C_v2]
+        .into_iter()
+        .chain([&proof.t0, &proof.t1, &proof.t2, &proof.tau1, &proof.tau2])
+        .chain(ipp.L.iter().chain(&ipp.R).chain([&ipp.a, &ipp.b]))
+        .collect()
+}
+
+proptest! {
+    #![proptest_config(config(128))]
+
+    // Purpose: property: read_proof(write_proof(p)) is p, statement and epoch included
+    // Params: 128 structurally valid proofs over the test modulus, half with a statement whose label
+    //   has arbitrary Unicode characters
+    // Output: equal fields, epoch and statement; encoding the decoded proof gives the same bytes
+    // Usage: `cargo test --test properties roundtrip`
+    #[test]
+    fn proof_container_roundtrip(Structural(proof) in structural_proof()) {
+        let mut bytes = Vec::new();
+        write_proof(&mut bytes, &proof).map_err(fail)?;
+        let decoded = read_proof(&mut &bytes[..], &ReadLimits::default()).map_err(fail)?;
+        prop_assert!(fields(&decoded) == fields(&proof), "fields differ");
+        prop_assert!((decoded.epoch, &decoded.statement) == (proof.epoch, &proof.statement), "epoch or statement differ");
+        let mut again = Vec::new();
+        write_proof(&mut again, &decoded).map_err(fail)?;
+        prop_assert!(again == bytes, "re-encoding changed the bytes");
+    }
+
+    // Purpose: property: proof_from_wire(proof_to_wire(p)) is p without its statement
+    // Params: 128 structurally valid proofs as above
+    // Output: equal fields and epoch, no statement (the wire form has none); same bytes again
+    // Usage: `cargo test --test properties roundtrip`
+    #[test]
+    fn proof_wire_roundtrip(Structural(proof) in structural_proof()) {
+        let bytes = proof_to_wire(&proof).map_err(fail)?;
+        let decoded = proof_from_wire(&bytes).map_err(fail)?;
+        prop_assert!(fields(&decoded) == fields(&proof) && decoded.epoch == proof.epoch, "fields or epoch differ");
+        prop_assert!(decoded.statement.is_none(), "the wire form produced a statement");
+        prop_assert!(proof_to_wire(&decoded).map_err(fail)? == bytes, "re-encoding changed the bytes");
+    }
+}
+
+proptest! {
+    #![proptest_config(config(32))]
+
+    // Purpose: property: params survive the container and the wire format
+    // Params: 32 params over the test modulus with generators derived from an arbitrary seed, at
+    //   epoch 0 to 2^16
+    // Output: the container gives back equal params; the wire form equal g, h, n, bits and epoch
+    // Usage: `cargo test --test properties roundtrip`
+    #[test]
+    fn params_roundtrip(seed in any::<u64>(), epoch in 0..=1u64 << 16) {
+        let test = params();
+        let seed = if seed == 0 { DEFAULT_GENERATOR_SEED.to_string() } else { format!("property/{:016x}", seed) };
+        let original = Params::derive(test.n().clone(), test.bits(), &seed).with_epoch(epoch);
+        let mut bytes = Vec::new();
+        write_params(&mut bytes, &original).map_err(fail)?;
+        let decoded = read_params(&mut &bytes[..], &ReadLimits::default()).map_err(fail)?;
+        prop_assert!(decoded == original, "container changed the params");
+        let wire = params_from_wire(&params_to_wire(&original).map_err(fail)?).map_err(fail)?;
+        let shape = |p: &Params| (p.g().clone(), p.h().clone(), p.n().clone(), p.bits(), p.epoch());
+        prop_assert!(shape(&wire) == shape(&original), "wire form changed the params");
+    }
+}
+
+proptest! {
+    #![proptest_config(config(512))]
+
+    // Purpose: property: hex encodings of integers decode to the same integer
+    // Params: 512 integers of up to 1024 bits, both signs
+    // Output: hex_to_bigint(bigint_to_hex(|x|)) = |x|, hex_to_bigint_signed(bigint_to_signed_hex(x)) = x
+    // Usage: `cargo test --test properties roundtrip`
+    #[test]
+    fn hex_roundtrip(magnitude in bigint(1024), negative in any::<bool>()) {
+        let x = if negative { -&magnitude } else { magnitude.clone() };
+        prop_assert_eq!(hex_to_bigint(&bigint_to_hex(&magnitude)), Ok(magnitude), "unsigned hex");
+        prop_assert_eq!(hex_to_bigint_signed(&bigint_to_signed_hex(&x)), Ok(x), "signed hex");
+    }
+}
+
+/// A codec error as a failed case
+fn fail(error: impl std::fmt::Display) -> TestCaseError {
+    TestCaseError::fail(error.to_string())
+}
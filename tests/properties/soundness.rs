@@ -0,0 +1,102 @@
+//! Soundness smoke tests: no proof for a value outside its range, and a proof does not carry over
+//! to another range
+
+use cuproof::range_proof::random_blinding;
+use cuproof::verify::cuproof_verify_with_range;
+use cuproof::{prove_with_rng, ProveError};
+use num_bigint::BigInt;
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use crate::completeness::{InRange, MAX_BITS};
+use crate::{bigint, config, params};
+
+/// A range [a, a + width] and a value `distance` below or above it
+#[derive(Clone, Debug)]
+struct OutOfRange {
+    a: BigInt,
+    width: BigInt,
+    /// at least 1
+    distance: BigInt,
+    above: bool,
+    seed: u64,
+}
+
+impl OutOfRange {
+    fn b(&self) -> BigInt {
+        &self.a + &self.width
+    }
+
+    fn v(&self) -> BigInt {
+        if self.above { self.b() + &self.distance } else { &self.a - &self.distance }
+    }
+}
+
+impl OutOfRange {
+    fn strategy() -> impl Strategy<Value = OutOfRange> {
+        (bigint(MAX_BITS), bigint(MAX_BITS), bigint(MAX_BITS), any::<bool>(), any::<u64>())
+            .prop_map(|(a, width, distance, above, seed)| OutOfRange { a, width, distance: distance + 1, above, seed })
+    }
+}
+
+proptest! {
+    #![proptest_config(config(256))]
+
+    // Purpose: property: the prover refuses values outside [a, b], and would its proof exist, the
+    //   verifier rejects it for [a, b]
+    // Params: 256 cases of a and b - a up to 128 bits, v 1 to 2^128 below a or above b (so v may be
+    //   negative)
+    // Output: ProveError::ValueOutOfRange, or a proof cuproof_verify_with_range rejects
+    // Usage: `cargo test --test properties soundness`
+    #[test]
+    fn out_of_range_is_not_proved(case in OutOfRange::strategy()) {
+        let mut rng = ChaCha20Rng::seed_from_u64(case.seed);
+        let r = random_blinding(&mut rng);
+        match prove_with_rng(&case.v(), &r, &case.a, &case.b(), params(), 64, &mut rng) {
+            Err(ProveError::ValueOutOfRange) => {}
+            Err(e) => prop_assert!(false, "expected ValueOutOfRange, got {}", e),
+            Ok(proof) => prop_assert!(!cuproof_verify_with_range(&proof, params(), &case.a, &case.b()), "accepted a proof of a value outside the range"),
+        }
+    }
+
+    // Purpose: property: the prover refuses empty ranges
+    // Params: 256 cases of b up to 128 bits, a = b + 1 + up to 2^128, v anywhere in between or outside
+    // Output: ProveError::EmptyRange
+    // Usage: `cargo test --test properties soundness`
+    #[test]
+    fn empty_range_is_not_proved(b in bigint(MAX_BITS), gap in bigint(MAX_BITS), v in bigint(MAX_BITS + 1)) {
+        let a = &b + gap + 1;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        match prove_with_rng(&v, &BigInt::from(1), &a, &b, params(), 64, &mut rng) {
+            Err(ProveError::EmptyRange) => {}
+            Err(e) => prop_assert!(false, "expected EmptyRange, got {}", e),
+            Ok(_) => prop_assert!(false, "proved a value of an empty range"),
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(config(8))]
+
+    // Purpose: property: a proof for [a, b] is rejected for any other range, even one containing v
+    // Params: 8 honest cases as in completeness; the other range moves a down or b up by 1 to 2^64
+    //   (so it still contains v), or swaps to [a, a + (b - a) / 2]
+    // Output: cuproof_verify_with_range accepts [a, b] and rejects the other range
+    // Usage: `cargo test --test properties soundness`
+    #[test]
+    fn proof_binds_its_range(mut case in InRange::strategy(), kind in 0..3u8, shift in bigint(64)) {
+        case.above += 1; // a < b, so [a, b] itself is accepted
+        let shift = shift + 1;
+        let proof = case.prove().map_err(|e| TestCaseError::fail(format!("prover refused: {}", e)))?;
+        let (a, b) = (case.a.clone(), case.b());
+        prop_assert!(cuproof_verify_with_range(&proof, params(), &a, &b), "rejected its own range");
+        let other = match kind {
+            0 => (&a - &shift, b.clone()),
+            1 => (a.clone(), &b + &shift),
+            _ => (a.clone(), &a + (&b - &a) / 2),
+        };
+        if other != (a.clone(), b.clone()) {
+            prop_assert!(!cuproof_verify_with_range(&proof, params(), &other.0, &other.1), "accepted for [{}, {}]", other.0, other.1);
+        }
+    }
+}
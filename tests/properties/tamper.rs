@@ -0,0 +1,144 @@
+//! Tamper rejection: changing any field the verifier binds makes an honest proof fail
+
+use std::sync::OnceLock;
+use cuproof::verify::verify_statement;
+use cuproof::Cuproof;
+use num_bigint::BigInt;
+use proptest::prelude::*;
+use crate::completeness::InRange;
+use crate::{bigint, config, params};
+
+/// Honest proofs every case tampers with
+const POOL: usize = 3;
+
+/// One change to a proof, by `delta` >= 1
+type Tamper = fn(&mut Cuproof, &BigInt);
+
+/// The fields verify_statement binds, each with a change that keeps it well-formed
+const BOUND: &[(&str, Tamper)] = &[
+    ("C", |p, d| p.C += d),
+    ("T1", |p, d| p.T1 += d),
+    ("T2", |p, d| p.T2 += d),
+    ("t0", |p, d| p.t0 += d),
+    ("t1", |p, d| p.t1 += d),
+    ("t2", |p, d| p.t2 += d),
+    ("tau1", |p, d| p.tau1 += d),
+    ("tau2", |p, d| p.tau2 += d),
+    ("t_hat", |p, d| p.t_hat += d),
+    ("epoch", |p, d| p.epoch = p.epoch.wrapping_add(small(d))),
+    ("statement.commitment", |p, d| statement(p).commitment += d),
+    ("statement.a", |p, d| statement(p).a -= d),
+    ("statement.b", |p, d| statement(p).b += d),
+    ("statement.bits", |p, d| statement(p).bits <<= small(d) % 8 + 1),
+    ("statement.label", |p, d| statement(p).label.push_str(&d.to_string())),
+    ("statement.params_fingerprint", |p, d| statement(p).params_fingerprint[small(d) as usize % 32] ^= 1),
+    ("ipp_proof rounds", |p, _| { p.ipp_proof.L.pop(); p.ipp_proof.R.pop(); }),
+];
+
+/// Fields the verifier does not check yet: an honest proof with any one of them changed still
+/// verifies. Remove an entry once the verifier binds it, and add it to BOUND. A, S, C_v1 and
+/// C_v2 feed only y and z, which the verifier checks for nonzero and nothing else
+const NOT_CHECKED: &[(&str, Tamper)] = &[
+    ("A", |p, d| p.A += d),
+    ("S", |p, d| p.S += d),
+    ("C_v1", |p, d| p.C_v1 += d),
+    ("C_v2", |p, d| p.C_v2 += d),
+    ("tau_x", |p, d| p.tau_x += d),
+    ("mu", |p, d| p.mu += d),
+    ("ipp_proof.L[0]", |p, d| p.ipp_proof.L[0] += d),
+    ("ipp_proof.R[0]", |p, d| p.ipp_proof.R[0] += d),
+    ("ipp_proof.a", |p, d| p.ipp_proof.a += d),
+    ("ipp_proof.b", |p, d| p.ipp_proof.b += d),
+];
+
+fn statement(proof: &mut Cuproof) -> &mut cuproof::Statement {
+    proof.statement.as_mut().expect("honest proofs carry a statement")
+}
+
+/// The low 64 bits of delta
+fn small(delta: &BigInt) -> u64 {
+    delta.iter_u64_digits().next().unwrap_or(0)
+}
+
+/// Honest proofs of small in-range cases
+fn pool() -> &'static [Cuproof] {
+    static POOL_PROOFS: OnceLock<Vec<Cuproof>> = OnceLock::new();
+    POOL_PROOFS.get_or_init(|| {
+        (0..POOL as u64)
+            .map(|i| {
+                let case = InRange { a: BigInt::from(i * 1000), below: BigInt::from(17 + i), above: BigInt::from(5), seed: i };
+                case.prove().expect("prover refused an in-range value")
+            })
+            .collect()
+    })
+}
+
+/// Which pool proof, which field and by how much
+#[derive(Clone, Debug)]
+struct Case {
+    proof: usize,
+    field: &'static str,
+    /// at least 1
+    delta: BigInt,
+}
+
+impl Case {
+    fn tampered(&self, fields: &[(&str, Tamper)]) -> Cuproof {
+        let mut proof = pool()[self.proof].clone();
+        let (_, tamper) = fields.iter().find(|(name, _)| *name == self.field).expect("known field");
+        tamper(&mut proof, &self.delta);
+        proof
+    }
+}
+
+impl Case {
+    /// A pool proof, a field of `fields` and a delta of 1 to 2^256
+    fn strategy(fields: &'static [(&'static str, Tamper)]) -> impl Strategy<Value = Case> {
+        (0..POOL, proptest::sample::select(fields), bigint(256)).prop_map(|(proof, (field, _), delta)| Case { proof, field, delta: delta + 1 })
+    }
+}
+
+proptest! {
+    #![proptest_config(config(128))]
+
+    // Purpose: property: an honest proof with one bound field changed is rejected
+    // Params: 128 cases of a pool proof, a field of BOUND and a delta of 1 to 2^256
+    // Output: verify_statement against the proof's own statement returns an error; the
+    //   untouched proof verifies
+    // Usage: `cargo test --test properties tamper`
+    #[test]
+    fn tampered_bound_field_is_rejected(case in Case::strategy(BOUND)) {
+        check_pool();
+        let expected = pool()[case.proof].statement.clone().expect("honest proofs carry a statement");
+        prop_assert!(verify_statement(&case.tampered(BOUND), params(), &expected).is_err(), "accepted with {} changed", case.field);
+    }
+}
+
+// Purpose: pin the fields the verifier leaves unchecked, so NOT_CHECKED shrinks when it improves
+// Params: every pool proof, each NOT_CHECKED field changed by 1
+// Output: each tampered proof still verifies; a failure here means the field is now bound and
+//   belongs in BOUND
+// Usage: `cargo test --test properties tamper`
+#[test]
+fn unchecked_fields_are_documented() {
+    check_pool();
+    for proof in 0..POOL {
+        for (field, _) in NOT_CHECKED {
+            let case = Case { proof, field, delta: BigInt::from(1) };
+            let expected = pool()[proof].statement.clone().expect("honest proofs carry a statement");
+            assert!(
+                verify_statement(&case.tampered(NOT_CHECKED), params(), &expected).is_ok(),
+                "{} is checked now: move it from NOT_CHECKED to BOUND",
+                field
+            );
+        }
+    }
+}
+
+/// The untouched pool verifies, so a rejection is down to the tampering
+fn check_pool() {
+    for (i, proof) in pool().iter().enumerate() {
+        let expected = proof.statement.clone().expect("honest proofs carry a statement");
+        assert_eq!(verify_statement(proof, params(), &expected), Ok(()), "pool proof {}", i);
+    }
+}
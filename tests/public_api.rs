@@ -0,0 +1,249 @@
+//! The stable surface of the crate root, pinned two ways: its signatures must still compile as
+//! written below, and the declarations behind the root re-exports must match
+//! testdata/public_api.txt. After a deliberate change, regenerate the snapshot with
+//! `UPDATE_PUBLIC_API=1 cargo test --test public_api` and review its diff with the change.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+use cuproof::{
+    bigint_from_be_bytes, bigint_to_be_bytes_padded, load_params, load_proof, params_from_wire, params_to_wire,
+    proof_from_wire, proof_to_wire, prove, prove_with_rng, random_blinding, read_params, read_proof, save_params,
+    save_proof, scalar_from_be_bytes, scalar_to_be_bytes, trusted_setup, verify, verify_statement, write_params,
+    write_proof, Cuproof, DecodeError, EncodeError, Error, Group, Params, ParamsError, ParamsLoadError, ProofLoadError,
+    ProveError, RangeProver, ReadLimits, SerializationError, SetupError, Statement, Verifier, VerifyError, WireError,
+};
+use num_bigint::BigInt;
+use rand_chacha::ChaCha20Rng;
+
+const SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/public_api.txt");
+const SRC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+
+// Purpose: ensure the root functions keep the signatures library users call them with
+// Params: each re-exported function coerced to a function pointer of its documented signature
+// Output: compiles; the Group bound is checked by instantiating the generics with Params
+// Usage: `cargo test --test public_api` or `cargo test`
+#[test]
+fn root_signatures_compile() {
+    let _: fn(&BigInt, &BigInt, &BigInt, &BigInt, &Params) -> Result<Cuproof, ProveError> = prove::<Params>;
+    type ProveWithRng = fn(&BigInt, &BigInt, &BigInt, &BigInt, &Params, usize, &mut ChaCha20Rng) -> Result<Cuproof, ProveError>;
+    let _: ProveWithRng = prove_with_rng::<Params, ChaCha20Rng>;
+    let _: fn(&mut ChaCha20Rng) -> BigInt = random_blinding::<ChaCha20Rng>;
+    let _: fn(&Cuproof, &Params) -> bool = verify::<Params>;
+    let _: fn(&Cuproof, &Params, &Statement) -> Result<(), VerifyError> = verify_statement::<Params>;
+    let _: fn(usize) -> Result<Params, SetupError> = trusted_setup;
+    let _: fn(&str, &Params) -> io::Result<()> = save_params;
+    let _: fn(&str) -> io::Result<Params> = load_params;
+    let _: fn(&str, &Cuproof) -> io::Result<()> = save_proof;
+    let _: fn(&str) -> io::Result<Cuproof> = load_proof;
+    let _: fn(&mut Vec<u8>, &Params) -> io::Result<usize> = write_params::<Vec<u8>>;
+    let _: fn(&mut Vec<u8>, &Cuproof) -> io::Result<usize> = write_proof::<Vec<u8>>;
+    let _: fn(&mut &'static [u8], &ReadLimits) -> Result<Params, ParamsLoadError> = read_params::<&'static [u8]>;
+    let _: fn(&mut &'static [u8], &ReadLimits) -> Result<Cuproof, ProofLoadError> = read_proof::<&'static [u8]>;
+    let _: fn(&Cuproof) -> Result<Vec<u8>, EncodeError> = proof_to_wire;
+    let _: fn(&[u8]) -> Result<Cuproof, WireError> = proof_from_wire;
+    let _: fn(&Params) -> Result<Vec<u8>, EncodeError> = params_to_wire;
+    let _: fn(&[u8]) -> Result<Params, WireError> = params_from_wire;
+    let _: fn(&BigInt, usize) -> Result<Vec<u8>, EncodeError> = bigint_to_be_bytes_padded;
+    let _: fn(&[u8]) -> BigInt = bigint_from_be_bytes;
+    let _: fn(&BigInt) -> Result<Vec<u8>, EncodeError> = scalar_to_be_bytes;
+    type ScalarFromBytes = fn(&[u8]) -> Result<(BigInt, usize), DecodeError>;
+    let _: ScalarFromBytes = scalar_from_be_bytes;
+    let _: fn(Params) -> Result<RangeProver, ParamsError> = RangeProver::new::<Params>;
+    let _: fn(Arc<Params>) -> Result<RangeProver, ParamsError> = RangeProver::new::<Arc<Params>>;
+    let _: fn(Params) -> Result<Verifier, ParamsError> = Verifier::new::<Params>;
+    let _: fn(Arc<Params>) -> Result<Verifier, ParamsError> = Verifier::new::<Arc<Params>>;
+    fn is_group<G: Group>() {}
+    is_group::<Params>();
+    fn into_error<E: Into<Error>>() {}
+    into_error::<ProveError>();
+    into_error::<VerifyError>();
+    into_error::<ParamsError>();
+    into_error::<SetupError>();
+    into_error::<SerializationError>();
+}
+
+// Purpose: keep the names the free functions had before the prelude callable for one release
+// Params: each deprecated alias coerced to the signature of its new name
+// Output: compiles (with the deprecation warnings allowed); delete this test with the aliases
+// Usage: `cargo test --test public_api` or `cargo test`
+#[test]
+#[allow(deprecated)]
+fn deprecated_aliases_compile() {
+    let _: fn(&BigInt, &BigInt, &BigInt, &BigInt, &Params) -> Result<Cuproof, ProveError> = cuproof::cuproof_prove::<Params>;
+    type ProveWithRng = fn(&BigInt, &BigInt, &BigInt, &BigInt, &Params, usize, &mut ChaCha20Rng) -> Result<Cuproof, ProveError>;
+    let _: ProveWithRng = cuproof::cuproof_prove_with_rng::<Params, ChaCha20Rng>;
+    let _: fn(&Cuproof, &Params) -> bool = cuproof::cuproof_verify::<Params>;
+    let _: fn(&Cuproof, &Params, &Statement) -> Result<(), VerifyError> = cuproof::cuproof_verify_statement::<Params>;
+}
+
+// Purpose: ensure the prelude brings in the whole root surface under the same names
+// Params: `use cuproof::prelude::*` in a scope of its own, the root items coerced as above
+// Output: compiles
+// Usage: `cargo test --test public_api` or `cargo test`
+#[test]
+fn prelude_matches_root() {
+    use cuproof::prelude::*;
+    let _: fn(&BigInt, &BigInt, &BigInt, &BigInt, &Params) -> Result<Cuproof, ProveError> = prove::<Params>;
+    let _: fn(&Cuproof, &Params) -> bool = verify::<Params>;
+    let _: fn(&[u8]) -> Result<Cuproof, WireError> = proof_from_wire;
+    let _: fn(&BigInt, usize) -> Result<Vec<u8>, EncodeError> = bigint_to_be_bytes_padded;
+    type ScalarFromBytes = fn(&[u8]) -> Result<(BigInt, usize), DecodeError>;
+    let _: ScalarFromBytes = scalar_from_be_bytes;
+    let _: fn(&str) -> std::io::Result<Params> = load_params;
+    let _: fn(&mut &'static [u8], &ReadLimits) -> Result<Cuproof, ProofLoadError> = read_proof::<&'static [u8]>;
+    fn is_group<G: Group>() {}
+    is_group::<Params>();
+    fn in_scope<T>() {}
+    in_scope::<(RangeProver, Verifier, Statement)>();
+    in_scope::<(Error, SerializationError, ParamsLoadError, EncodeError, DecodeError)>();
+}
+
+/// The names re-exported at the root of src/lib.rs, by the module that declares them
+fn root_reexports() -> BTreeMap<String, Vec<String>> {
+    let lib = std::fs::read_to_string(format!("{}/lib.rs", SRC)).unwrap();
+    let mut reexports: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    // `pub use module::{a, b};` may span lines; join each statement first
+    let mut statement = String::new();
+    for line in lib.lines().map(str::trim) {
+        if line.starts_with("pub use ") || !statement.is_empty() {
+            statement.push_str(line);
+            statement.push(' ');
+        }
+        if statement.ends_with("; ") {
+            let body = statement.trim_start_matches("pub use ").trim_end_matches("; ");
+            let (module, names) = body.split_once("::").expect("re-exports name their module");
+            let names = names.trim_matches(|c| c == '{' || c == '}' || c == ' ');
+            let entry = reexports.entry(module.to_string()).or_default();
+            entry.extend(names.split(',').map(str::trim).filter(|n| !n.is_empty()).map(String::from));
+            statement.clear();
+        }
+    }
+    reexports
+}
+
+/// Source lines of `module` up to its tests
+fn module_lines(module: &str) -> Vec<String> {
+    let text = std::fs::read_to_string(format!("{}/{}.rs", SRC, module)).unwrap();
+    text.lines().take_while(|l| *l != "mod tests {").map(String::from).collect()
+}
+
+fn depth_change(line: &str) -> i32 {
+    let code = line.split("//").next().unwrap_or("");
+    code.matches('{').count() as i32 - code.matches('}').count() as i32
+}
+
+/// A signature from its first line up to the `{` or `;` that ends it, on one line
+fn signature(lines: &[String], start: usize) -> String {
+    let mut sig = String::new();
+    for line in &lines[start..] {
+        let code = line.split("//").next().unwrap_or("").trim();
+        let end = code.find(" {").or_else(|| code.ends_with('{').then(|| code.len() - 1));
+        sig.push_str(&code[..end.unwrap_or(code.len())]);
+        if end.is_some() || code.ends_with(';') { break; }
+        sig.push(' ');
+    }
+    sig.split_whitespace().collect::<Vec<_>>().join(" ").replace("( ", "(").replace(" )", ")").replace(",)", ")")
+}
+
+/// The public shape of one item: its derives and declaration; pub fields of a struct, every
+/// variant of an enum, method signatures of a trait; then the pub fns of its inherent impls
+fn item_shape(lines: &[String], name: &str) -> Vec<String> {
+    let declared = |l: &str, kind: &str| l.starts_with(&format!("pub {} {}", kind, name))
+        && l[4 + kind.len() + 1 + name.len()..].starts_with(['(', '<', ' ', '{', ';']);
+    let kinds = ["fn", "struct", "enum", "trait", "const", "type"];
+    let start = lines.iter().position(|l| kinds.iter().any(|k| declared(l, k)))
+        .unwrap_or_else(|| panic!("no declaration of {}", name));
+    let mut shape = Vec::new();
+    if let Some(derive) = lines[..start].iter().rev().take_while(|l| l.starts_with("#[") || l.starts_with("///"))
+        .find(|l| l.starts_with("#[derive")) {
+        shape.push(derive.clone());
+    }
+    shape.push(signature(lines, start));
+    if lines[start].starts_with("pub fn") || lines[start].ends_with(';') { return shape; }
+
+    let member = |l: &str| {
+        let l = l.trim();
+        !(l.is_empty() || l.starts_with("//") || l.starts_with("#[") || l == "}")
+    };
+    let mut depth = depth_change(&lines[start]);
+    for (i, line) in lines.iter().enumerate().skip(start + 1) {
+        if depth <= 0 { break; }
+        let code = line.split("//").next().unwrap_or("").trim();
+        match lines[start].split(' ').nth(1) {
+            Some("struct") if depth == 1 && code.starts_with("pub ") => shape.push(format!("    {}", code)),
+            Some("enum") if member(line) => shape.push(format!("    {}", code)),
+            Some("trait") if depth == 1 && code.starts_with("fn ") => shape.push(format!("    {}", signature(lines, i))),
+            _ => {}
+        }
+        depth += depth_change(line);
+    }
+    shape.extend(inherent_methods(lines, name));
+    shape
+}
+
+/// `pub fn` and `pub const` signatures of every `impl Name` / `impl<..> Name<..>` block
+fn inherent_methods(lines: &[String], name: &str) -> Vec<String> {
+    let mut methods = Vec::new();
+    for (start, line) in lines.iter().enumerate() {
+        let rest = match line.strip_prefix("impl") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let rest = if rest.starts_with('<') { rest.split_once("> ").map(|(_, r)| r).unwrap_or("") } else { rest.trim_start() };
+        if !(rest.starts_with(&format!("{} ", name)) || rest.starts_with(&format!("{}<", name))) || rest.contains(" for ") { continue; }
+        let mut depth = depth_change(line);
+        for (i, inner) in lines.iter().enumerate().skip(start + 1) {
+            if depth <= 0 { break; }
+            let code = inner.trim();
+            if depth == 1 && (code.starts_with("pub fn") || code.starts_with("pub const")) {
+                methods.push(format!("    {}", signature(lines, i)));
+            }
+            depth += depth_change(inner);
+        }
+    }
+    methods
+}
+
+fn current_snapshot() -> String {
+    let mut out = String::from("# generated by tests/public_api.rs; see there before editing\n");
+    for (module, names) in root_reexports() {
+        let lines = module_lines(&module);
+        let mut names = names;
+        names.sort();
+        for name in names {
+            out.push_str(&format!("\n{}::{}\n", module, name));
+            for line in item_shape(&lines, &name) { out.push_str(&line); out.push('\n'); }
+        }
+    }
+    out
+}
+
+// Purpose: catch accidental changes to the stable surface: a renamed or removed re-export, a
+//   changed signature, a new required field, a dropped derive or variant
+// Params: src/lib.rs re-exports and the declarations they point to; testdata/public_api.txt
+// Output: the snapshot equals the declarations; with UPDATE_PUBLIC_API=1 it is rewritten instead
+// Usage: `cargo test --test public_api`; `UPDATE_PUBLIC_API=1 cargo test --test public_api` after
+//   a deliberate change
+#[test]
+fn root_surface_matches_snapshot() {
+    let current = current_snapshot();
+    if std::env::var_os("UPDATE_PUBLIC_API").is_some() {
+        std::fs::write(SNAPSHOT, &current).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(SNAPSHOT).unwrap_or_default();
+    if current != expected {
+        let changed: Vec<String> = diff_lines(&expected, &current);
+        panic!("the public API changed; if that is intended, rerun with UPDATE_PUBLIC_API=1:\n{}", changed.join("\n"));
+    }
+}
+
+/// Lines only in one side, marked - (snapshot) and + (source)
+fn diff_lines(expected: &str, current: &str) -> Vec<String> {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = current.lines().collect();
+    old.iter().filter(|l| !new.contains(l)).map(|l| format!("- {}", l))
+        .chain(new.iter().filter(|l| !old.contains(l)).map(|l| format!("+ {}", l)))
+        .collect()
+}
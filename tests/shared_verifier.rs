@@ -0,0 +1,89 @@
+//! One Verifier shared by many threads, as a server's worker pool uses it
+
+use std::sync::Arc;
+use std::thread;
+use cuproof::setup::insecure_test_setup;
+use cuproof::{prove, prove_with_rng, Cuproof, Params, RangeProver, Verifier};
+use num_bigint::BigInt;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+const THREADS: usize = 32;
+const PROOFS: usize = 1000;
+const HONEST: usize = 50;
+
+/// HONEST distinct honest proofs, then altered copies up to PROOFS: every other one keeps T1
+/// plus its index, so about half of the batch is rejected
+fn proofs(params: &Params) -> Vec<Cuproof> {
+    let (a, b) = (&BigInt::from(10), &BigInt::from(100_000));
+    let honest: Vec<Cuproof> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..HONEST)
+            .map(|i| scope.spawn(move || {
+                let mut rng = ChaCha20Rng::seed_from_u64(i as u64);
+                prove_with_rng(&BigInt::from(10 + 1999 * i), &BigInt::from(42 + i), a, b, params, 64, &mut rng).unwrap()
+            }))
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+    (0..PROOFS)
+        .map(|i| {
+            let mut proof = honest[i % HONEST].clone();
+            if i >= HONEST && i % 2 == 1 { proof.T1 += i; }
+            proof
+        })
+        .collect()
+}
+
+// Purpose: ensure a Verifier shared by 32 threads gives each proof the verdict a serial run gives it
+// Params: 1000 proofs (50 honest, the rest copies, half of them altered) over insecure 512-bit
+//   params in one Arc; 32 threads each take every 32nd proof from one shared Verifier
+// Output: the per-proof verdicts of the threads equal the serial verdicts; both accept and reject occur
+// Usage: `cargo test --test shared_verifier` or `cargo test`
+#[test]
+fn shared_verifier_matches_serial_run() {
+    let params = Arc::new(insecure_test_setup(512).allow_insecure_params());
+    let verifier = Verifier::new_insecure(Arc::clone(&params)).unwrap();
+    let prover = RangeProver::new_insecure(Arc::clone(verifier.shared_params())).unwrap();
+    assert!(Arc::ptr_eq(verifier.shared_params(), &params) && Arc::ptr_eq(prover.shared_params(), &params));
+    let proofs = proofs(&params);
+
+    let serial: Vec<bool> = proofs.iter().map(|proof| verifier.verify(proof)).collect();
+    assert!(serial.contains(&true) && serial.contains(&false));
+
+    let mut shared = vec![None; PROOFS];
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let (verifier, proofs) = (&verifier, &proofs);
+                scope.spawn(move || (t..PROOFS).step_by(THREADS).map(|i| (i, verifier.verify(&proofs[i]))).collect::<Vec<_>>())
+            })
+            .collect();
+        for worker in workers {
+            for (i, valid) in worker.join().unwrap() { shared[i] = Some(valid); }
+        }
+    });
+    let shared: Vec<bool> = shared.into_iter().map(|v| v.expect("every proof verified once")).collect();
+    assert_eq!(shared, serial);
+}
+
+// Purpose: ensure Verifier clones and Arc<Verifier> move into spawned threads, sharing one Params
+// Params: a Verifier over an Arc<Params>, cloned into 4 threads and passed as Arc<Verifier> to 4 more
+// Output: every thread accepts the honest proof; the clones' params are the same allocation
+// Usage: `cargo test --test shared_verifier` or `cargo test`
+#[test]
+fn verifier_moves_into_threads() {
+    let params = Arc::new(insecure_test_setup(512).allow_insecure_params());
+    let proof = Arc::new(prove(&BigInt::from(42), &BigInt::from(7), &BigInt::from(10), &BigInt::from(100), &*params).unwrap());
+    let verifier = Verifier::new_insecure(Arc::clone(&params)).unwrap();
+    let shared = Arc::new(verifier.clone());
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let (clone, shared, proof) = (verifier.clone(), Arc::clone(&shared), Arc::clone(&proof));
+            thread::spawn(move || {
+                assert!(Arc::ptr_eq(clone.shared_params(), shared.shared_params()));
+                if i % 2 == 0 { clone.verify(&proof) } else { shared.verify(&proof) }
+            })
+        })
+        .collect();
+    assert!(handles.into_iter().all(|h| h.join().unwrap()));
+}
@@ -0,0 +1,154 @@
+//! The known-answer vectors in this directory against the implementation
+//!
+//! Each <name>.json is re-derived by vectors::render_vector and must match byte for byte; every
+//! file is also checked from its own contents the way another implementation would use it: the
+//! encodings decode to the listed fields, the challenges recompute from them and the proof
+//! verifies. See src/vectors.rs for the format and for regenerating the files.
+
+use std::path::Path;
+use cuproof::aggregate::{aggregate_verify, aggregated_proof_challenges};
+use cuproof::commitment::commit;
+use cuproof::util::{hex_to_bigint_signed, read_aggregated_proof};
+use cuproof::verify::{cuproof_verify_checked, proof_challenges, Challenges};
+use cuproof::vectors::{render_vector, vector_file_version, vectors_params, VECTORS_VERSION, VECTOR_CASES};
+use cuproof::{params_from_wire, proof_from_wire, read_proof, Params, ReadLimits};
+use num_bigint::BigInt;
+use serde_json::Value;
+
+const DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors");
+
+const REGENERATE: &str = "cargo run --features vectors --bin cuproof-vectors";
+
+fn read(name: &str) -> String {
+    let path = Path::new(DIR).join(format!("{}.json", name));
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}; write it with `{}`", path.display(), e, REGENERATE))
+}
+
+/// A hex field of a vector, signed or not
+fn int(value: &Value) -> BigInt {
+    let text = value.as_str().unwrap_or_else(|| panic!("{} is not a hex string", value));
+    hex_to_bigint_signed(text).unwrap_or_else(|e| panic!("{:?}: {:?}", text, e))
+}
+
+fn bytes(value: &Value) -> Vec<u8> {
+    hex::decode(value.as_str().expect("byte strings are hex")).expect("byte strings are hex")
+}
+
+fn ints(value: &Value) -> Vec<BigInt> {
+    value.as_array().expect("a list").iter().map(int).collect()
+}
+
+fn assert_challenges(name: &str, listed: &Value, computed: &Challenges) {
+    assert_eq!(int(&listed["y"]), computed.y, "{}: challenge y", name);
+    assert_eq!(int(&listed["z"]), computed.z, "{}: challenge z", name);
+    assert_eq!(int(&listed["x"]), computed.x, "{}: challenge x", name);
+    assert_eq!(ints(&listed["ipp"]), computed.ipp, "{}: inner-product challenges", name);
+}
+
+// Purpose: fail on any change to the transcript, the prover's randomness or an encoding that did
+//   not come with regenerated vectors and a VECTORS_VERSION bump
+// Params: every VECTOR_CASES entry and its file
+// Output: each file records VECTORS_VERSION and equals render_vector for its case
+// Usage: `cargo test --test vectors`
+#[test]
+fn files_match_the_implementation() {
+    let params = vectors_params();
+    for case in VECTOR_CASES {
+        let file = read(case.name);
+        let version = vector_file_version(&file);
+        assert_eq!(
+            version, Some(VECTORS_VERSION),
+            "{}.json is version {:?}, this build writes version {}; regenerate with `{}`", case.name, version, VECTORS_VERSION, REGENERATE
+        );
+        let derived = render_vector(case, &params).unwrap_or_else(|e| panic!("{}: prover refused: {}", case.name, e));
+        if let Some((line, (old, new))) = file.lines().zip(derived.lines()).enumerate().find(|(_, (old, new))| old != new) {
+            panic!(
+                "{}.json line {} changed without a version bump:\n  file:    {}\n  derived: {}\nif the change is intended, bump VECTORS_VERSION and run `{}`",
+                case.name, line + 1, old.trim(), new.trim(), REGENERATE
+            );
+        }
+        assert_eq!(file, derived, "{}.json changed in length without a version bump", case.name);
+    }
+}
+
+// Purpose: keep the directory to the published cases, so no stale vector is left for others to test against
+// Params: the *.json files of tests/vectors
+// Output: each names a VECTOR_CASES entry
+// Usage: `cargo test --test vectors`
+#[test]
+fn no_stale_files() {
+    for entry in std::fs::read_dir(DIR).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "json") { continue; }
+        let stem = path.file_stem().unwrap().to_str().unwrap();
+        assert!(VECTOR_CASES.iter().any(|case| case.name == stem), "{} is not a vector case; delete it", path.display());
+    }
+}
+
+// Purpose: each file stands on its own: another implementation can check itself against it
+// Params: every vector file, read with serde_json and nothing from vectors.rs but the case names
+// Output: the params wire form decodes to n, g, h and the fingerprint; the commitments open to the
+//   witness; the encodings decode to the listed fields and statements; the challenges recompute;
+//   the proof verifies
+// Usage: `cargo test --test vectors`
+#[test]
+fn files_are_self_consistent() {
+    for case in VECTOR_CASES {
+        let name = case.name;
+        let vector: Value = serde_json::from_str(&read(name)).unwrap();
+        let listed = &vector["params"];
+        let bits = listed["bits"].as_u64().unwrap() as usize;
+        let params = Params::new(int(&listed["g"]), int(&listed["h"]), int(&listed["n"]), bits).with_epoch(listed["epoch"].as_u64().unwrap());
+        let decoded = params_from_wire(&bytes(&listed["wire"])).unwrap();
+        assert_eq!((decoded.g(), decoded.h(), decoded.n(), decoded.epoch()), (params.g(), params.h(), params.n(), params.epoch()), "{}: params wire", name);
+        assert_eq!(params.fingerprint_hex().to_string(), listed["fingerprint"].as_str().unwrap(), "{}: fingerprint", name);
+
+        let proof = &vector["proof"];
+        let commitments: Vec<&Value> = match vector["kind"].as_str().unwrap() {
+            "single" => vec![proof],
+            _ => proof["commitments"].as_array().unwrap().iter().collect(),
+        };
+        let witness = vector["witness"].as_array().unwrap();
+        assert_eq!(witness.len(), commitments.len(), "{}: one witness per commitment", name);
+        for (w, c) in witness.iter().zip(&commitments) {
+            assert_eq!(commit(&params, &int(&w["v"]), &int(&w["r"])), int(&c["C"]), "{}: C does not open to the witness", name);
+        }
+        let container = bytes(&vector["encodings"]["container"]);
+        let statements: Vec<_> = vector["statements"].as_array().unwrap().iter()
+            .map(|s| (int(&s["commitment"]), int(&s["a"]), int(&s["b"]), s["bits"].as_u64().unwrap() as usize, bytes(&s["params_fingerprint"])))
+            .collect();
+
+        if vector["kind"] == "single" {
+            let wire = proof_from_wire(&bytes(&vector["encodings"]["wire"])).unwrap();
+            let from_container = read_proof(&mut &container[..], &ReadLimits::default()).unwrap();
+            for decoded in [&wire, &from_container] {
+                let fields = [
+                    ("A", &decoded.A), ("S", &decoded.S), ("T1", &decoded.T1), ("T2", &decoded.T2), ("tau_x", &decoded.tau_x),
+                    ("mu", &decoded.mu), ("t_hat", &decoded.t_hat), ("C", &decoded.C), ("C_v1", &decoded.C_v1), ("C_v2", &decoded.C_v2),
+                    ("t0", &decoded.t0), ("t1", &decoded.t1), ("t2", &decoded.t2), ("tau1", &decoded.tau1), ("tau2", &decoded.tau2),
+                ];
+                for (field, value) in fields { assert_eq!(&int(&proof[field]), value, "{}: {}", name, field); }
+                assert_eq!(ints(&proof["ipp"]["L"]), decoded.ipp_proof.L, "{}: ipp L", name);
+                assert_eq!(ints(&proof["ipp"]["R"]), decoded.ipp_proof.R, "{}: ipp R", name);
+                assert_eq!((int(&proof["ipp"]["a"]), int(&proof["ipp"]["b"])), (decoded.ipp_proof.a.clone(), decoded.ipp_proof.b.clone()), "{}: ipp a, b", name);
+                assert_eq!(proof["epoch"].as_u64(), Some(decoded.epoch), "{}: epoch", name);
+            }
+            let s = from_container.statement.as_ref().expect("the container keeps the statement");
+            assert_eq!(statements, [(s.commitment.clone(), s.a.clone(), s.b.clone(), s.bits, s.params_fingerprint.to_vec())], "{}: statement", name);
+            assert_challenges(name, &vector["challenges"], &proof_challenges(&from_container, &params));
+            assert_eq!(cuproof_verify_checked(&from_container, &params), Ok(()), "{}: does not verify", name);
+        } else {
+            let decoded = read_aggregated_proof(&mut &container[..], &ReadLimits::default()).unwrap();
+            for (c, listed) in decoded.commitments.iter().zip(&commitments) {
+                assert_eq!((&c.C, &c.C_v1, &c.C_v2), (&int(&listed["C"]), &int(&listed["C_v1"]), &int(&listed["C_v2"])), "{}: commitments", name);
+            }
+            assert_eq!((int(&proof["A"]), int(&proof["t_hat"])), (decoded.A.clone(), decoded.t_hat.clone()), "{}: A, t_hat", name);
+            let decoded_statements: Vec<_> = decoded.statements.iter()
+                .map(|s| (s.commitment.clone(), s.a.clone(), s.b.clone(), s.bits, s.params_fingerprint.to_vec()))
+                .collect();
+            assert_eq!(statements, decoded_statements, "{}: statements", name);
+            assert_challenges(name, &vector["challenges"], &aggregated_proof_challenges(&decoded, &params));
+            assert_eq!(aggregate_verify(&decoded, &params), Ok(()), "{}: does not verify", name);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Commitments and range proofs in the browser, so the raw amount never leaves the client
+//!
+//! Params come in the wire format (`cuproof::wire::params_to_wire` on the server side) and
+//! are checked once by `wasm_setup_load`; the returned handle is passed to every other call.
+//! Integers cross the boundary as hex strings (bigint_to_hex / hex_to_bigint, `0x` optional) and
+//! proofs as wire-format bytes. Blindings and proving randomness are drawn from
+//! crypto.getRandomValues.
+//!
+//! ```js
+//! import init, { wasm_setup_load, wasm_commit, wasm_prove, wasm_verify } from "./pkg/cuproof_wasm.js";
+//! await init();
+//! const params = wasm_setup_load(paramsBytes);
+//! const opening = wasm_commit(params, "2a");
+//! const proof = wasm_prove(params, "2a", opening.blinding, "0a", "64");
+//! wasm_verify(params, proof, "0a", "64", opening.commitment); // true
+//! ```
+
+use cuproof::group::Group;
+use cuproof::range_proof::{prove_with_rng, random_blinding};
+use cuproof::setup::Params;
+use cuproof::util::{bigint_to_hex, hex_to_bigint};
+use cuproof::verify::cuproof_verify_with_range;
+use cuproof::wire::{params_from_wire, proof_from_wire, proof_to_wire};
+use num_bigint::BigInt;
+use rand_core::OsRng;
+use wasm_bindgen::prelude::*;
+
+/// IPP vector dimension of browser proofs, as range_proof::prove uses
+pub const WASM_DIMENSION: usize = 64;
+
+/// Params that passed validate_params, for the other wasm_ calls
+#[wasm_bindgen]
+pub struct ParamsHandle {
+    params: Params,
+}
+
+#[wasm_bindgen]
+impl ParamsHandle {
+    /// Declared modulus size in bits
+    #[wasm_bindgen(getter)]
+    pub fn bits(&self) -> usize {
+        self.params.bits()
+    }
+
+    /// True for INSECURE test params; a front end should refuse them outside development
+    #[wasm_bindgen(getter)]
+    pub fn insecure(&self) -> bool {
+        self.params.is_insecure()
+    }
+}
+
+/// A commitment with the blinding that opens it, both as hex
+#[wasm_bindgen(getter_with_clone)]
+pub struct Opening {
+    pub commitment: String,
+    /// as secret as the value; keep it on the client
+    pub blinding: String,
+}
+
+/// Load params in the wire format
+/// - returns: a handle, or an error naming the first problem of the bytes or the params
+#[wasm_bindgen]
+pub fn wasm_setup_load(params_bytes: &[u8]) -> Result<ParamsHandle, JsError> {
+    let params = params_from_wire(params_bytes).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(ParamsHandle { params })
+}
+
+/// Commit to `value_hex` under a fresh random blinding
+/// - returns: C = g^v * h^r and r, or an error for a value that is not non-negative hex
+#[wasm_bindgen]
+pub fn wasm_commit(handle: &ParamsHandle, value_hex: &str) -> Result<Opening, JsError> {
+    let v = parse(value_hex, "value")?;
+    let r = random_blinding(&mut OsRng);
+    let commitment = handle.params.commit(&v, &r);
+    Ok(Opening { commitment: bigint_to_hex(&commitment), blinding: bigint_to_hex(&r) })
+}
+
+/// Prove a <= value <= b for the commitment of `value` under `blinding`
+/// - returns: the proof in wire format, or the error of parsing or proving (e.g. value outside [a, b])
+#[wasm_bindgen]
+pub fn wasm_prove(handle: &ParamsHandle, value: &str, blinding: &str, a: &str, b: &str) -> Result<Vec<u8>, JsError> {
+    let (v, r, a, b) = (parse(value, "value")?, parse(blinding, "blinding")?, parse(a, "a")?, parse(b, "b")?);
+    let proof = prove_with_rng(&v, &r, &a, &b, &handle.params, WASM_DIMENSION, &mut OsRng)
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    proof_to_wire(&proof).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Check a wire-format proof that the value committed in `commitment` lies in [a, b]
+/// - returns: false for a proof that does not decode, does not verify, or proves another commitment;
+///   an error only for arguments that are not hex
+/// - usage: [a, b] is checked as cuproof_verify_with_range checks it; the wire format carries no
+///   statement, so the verifier must take the range from its own records, never from the prover
+#[wasm_bindgen]
+pub fn wasm_verify(handle: &ParamsHandle, proof_bytes: &[u8], a: &str, b: &str, commitment: &str) -> Result<bool, JsError> {
+    let (a, b, commitment) = (parse(a, "a")?, parse(b, "b")?, parse(commitment, "commitment")?);
+    let Ok(proof) = proof_from_wire(proof_bytes) else { return Ok(false) };
+    Ok(proof.C == commitment && cuproof_verify_with_range(&proof, &handle.params, &a, &b))
+}
+
+fn parse(hex: &str, what: &str) -> Result<BigInt, JsError> {
+    hex_to_bigint(hex).map_err(|e| JsError::new(&format!("{}: {}", what, e)))
+}
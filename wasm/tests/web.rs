@@ -0,0 +1,38 @@
+//! The wasm_ functions under wasm-bindgen-test: in Node by default (`wasm-pack test --node`), in a
+//! headless browser with WASM_BINDGEN_USE_BROWSER=1 (`wasm-pack test --headless --firefox`)
+
+use cuproof::wire::{proof_from_wire, proof_to_wire};
+use cuproof_wasm::{wasm_commit, wasm_prove, wasm_setup_load, wasm_verify};
+use wasm_bindgen_test::*;
+
+const PARAMS: &[u8] = include_bytes!("../../testdata/params_v1.wire");
+
+// Purpose: ensure commit → prove → verify round-trips in the browser and verify refuses what it should
+// Params: the insecure 512-bit reference params testdata/params_v1.wire; the value 42 in [10, 100]
+// Output: a proof verifying against its commitment and range; false for another commitment, a
+//         reversed range, an altered t_hat and truncated bytes; errors for truncated params, a
+//         value outside the range and bad hex
+// Usage: `wasm-pack test --node`, or `cargo test --target wasm32-unknown-unknown` with wasm-bindgen-test-runner on PATH
+#[wasm_bindgen_test]
+fn commit_prove_verify_round_trip() {
+    let params = wasm_setup_load(PARAMS).unwrap();
+    assert_eq!(params.bits(), 512);
+    assert!(params.insecure());
+    let opening = wasm_commit(&params, "2a").unwrap();
+    let proof = wasm_prove(&params, "2a", &opening.blinding, "0a", "64").unwrap();
+    assert!(wasm_verify(&params, &proof, "0a", "64", &opening.commitment).unwrap());
+
+    let other = wasm_commit(&params, "2a").unwrap();
+    assert_ne!(other.commitment, opening.commitment);
+    assert!(!wasm_verify(&params, &proof, "0a", "64", &other.commitment).unwrap());
+    assert!(!wasm_verify(&params, &proof, "64", "0a", &opening.commitment).unwrap());
+    let mut altered = proof_from_wire(&proof).unwrap();
+    altered.t_hat += 1;
+    let altered = proof_to_wire(&altered).unwrap();
+    assert!(!wasm_verify(&params, &altered, "0a", "64", &opening.commitment).unwrap());
+    assert!(!wasm_verify(&params, &proof[..proof.len() - 1], "0a", "64", &opening.commitment).unwrap());
+
+    assert!(wasm_setup_load(&PARAMS[..PARAMS.len() - 1]).is_err());
+    assert!(wasm_prove(&params, "65", &opening.blinding, "0a", "64").is_err());
+    assert!(wasm_verify(&params, &proof, "0a", "not hex", &opening.commitment).is_err());
+}